@@ -1,6 +1,7 @@
 // SPDX-License-Identifier: AGPL-3.0-only
 // SPDX-FileCopyrightText: 2025 Steve Clarke <stephenlclarke@mac.com> - https://xyzzy.tools
 
+use sha2::{Digest, Sha256};
 use std::process::Command;
 
 // Capture build metadata (rustc version, git commit) at build time so the binary
@@ -37,6 +38,20 @@ fn main() {
         .unwrap_or_else(|| "main".to_string());
     println!("cargo:rustc-env=FIXDECODER_BRANCH={branch}");
 
+    // Hash each embedded dictionary so the on-disk cache notices any edit to the XML,
+    // not just one that changes its length.
+    for name in [
+        "FIX40", "FIX41", "FIX42", "FIX43", "FIX44", "FIX50", "FIX50SP1", "FIX50SP2", "FIXT11",
+    ] {
+        let path = format!("resources/{name}.xml");
+        let xml = std::fs::read(&path).unwrap_or_else(|err| panic!("failed to read {path}: {err}"));
+        let hash: String = Sha256::digest(&xml)
+            .iter()
+            .map(|b| format!("{b:02x}"))
+            .collect();
+        println!("cargo:rustc-env=FIXDECODER_SHA256_{name}={hash}");
+    }
+
     // Surface the version being built so `cargo build` output includes our metadata.
     println!(
         "cargo:warning=Building fixdecoder {version} (branch:{branch}, commit:{commit}) [rust:{rustc}]"
@@ -0,0 +1,13 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Custom `--xml` dictionaries: parsing, then building the tag lookup from the result.
+#![no_main]
+
+use fixdecoder::decoder::FixDictionary;
+use fixdecoder::decoder::tag_lookup::FixTagLookup;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|xml: &str| {
+    if let Ok(dict) = FixDictionary::from_xml(xml) {
+        let _ = FixTagLookup::from_dictionary(&dict, "FUZZ");
+    }
+});
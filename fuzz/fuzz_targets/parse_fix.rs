@@ -0,0 +1,21 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Raw bytes through field splitting and the line reader that finds messages in logs.
+#![no_main]
+
+use fixdecoder::decoder::fixparser::parse_fix;
+use fixdecoder::decoder::{DecodeOptions, FixMessageIter};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = parse_fix(&String::from_utf8_lossy(data));
+    for join_lines in [false, true] {
+        let options = DecodeOptions {
+            unescape: true,
+            join_lines,
+            ..DecodeOptions::default()
+        };
+        for msg in FixMessageIter::new(data, options) {
+            let _ = msg;
+        }
+    }
+});
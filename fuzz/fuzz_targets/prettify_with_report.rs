@@ -0,0 +1,26 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Rendering, with and without validation annotations and hidden header fields.
+#![no_main]
+
+use fixdecoder::decoder::display::DisplayOptions;
+use fixdecoder::decoder::prettifier::{HiddenBlocks, prettify_with_report};
+use fixdecoder::decoder::tag_lookup::load_dictionary;
+use fixdecoder::decoder::validator::validate_fix_message;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|msg: &str| {
+    let dict = load_dictionary(msg);
+    let report = validate_fix_message(msg, &dict);
+    let hidden = HiddenBlocks {
+        header: true,
+        trailer: true,
+    }
+    .tags(&dict, Some(&report));
+    let display = DisplayOptions {
+        max_value_width: Some(16),
+        error_locations: true,
+        ..DisplayOptions::default()
+    };
+    let _ = prettify_with_report(msg, &dict, Some(&report), &hidden, &display);
+    let _ = prettify_with_report(msg, &dict, None, &Default::default(), &Default::default());
+});
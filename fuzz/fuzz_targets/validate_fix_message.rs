@@ -0,0 +1,12 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Validation of whatever text a log holds, against the dictionary it names.
+#![no_main]
+
+use fixdecoder::decoder::tag_lookup::load_dictionary;
+use fixdecoder::decoder::validator::validate_fix_message;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|msg: &str| {
+    let dict = load_dictionary(msg);
+    let _ = validate_fix_message(msg, &dict);
+});
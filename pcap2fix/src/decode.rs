@@ -0,0 +1,96 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// `--decode`: feed reassembled messages straight into the fixdecoder prettifier and
+// validator, one flow heading per change of flow, instead of printing raw FIX.
+
+use crate::{EmitOptions, FlowKey, MessageSink, Origin};
+use anyhow::Result;
+use fixdecoder::decoder::prettifier::{announce_source, finish_run, prettify_line};
+use fixdecoder::decoder::raw_bytes;
+use fixdecoder::decoder::summary::OrderSummary;
+use fixdecoder::decoder::tag_lookup::normalise_fix_key;
+use fixdecoder::decoder::{disable_output_colours, PrettifyContext};
+use fixdecoder::fix;
+use std::collections::HashMap;
+use std::io::{self, IsTerminal, Write};
+use std::process::ExitCode;
+
+/// Exit code when `--validate` found an invalid message, matching fixdecoder.
+const EXIT_INVALID: u8 = 2;
+
+/// The fixdecoder options that `--decode` passes through.
+pub struct DecodeSettings<'a> {
+    pub validate: bool,
+    pub summary: bool,
+    pub fix: Option<&'a str>,
+}
+
+/// Decodes each message as it is cut from its flow, announcing the flow's endpoints
+/// whenever the capture switches to a different flow.
+pub struct DecodeSink<'a> {
+    ctx: PrettifyContext<'a>,
+    current: Option<FlowKey>,
+    /// Messages seen per flow, reported by `--validate` as the line number.
+    counts: HashMap<FlowKey, usize>,
+}
+
+impl MessageSink for DecodeSink<'_> {
+    fn emit(
+        &mut self,
+        message: &[u8],
+        _opts: &EmitOptions,
+        origin: &Origin,
+        _scratch: &mut Vec<u8>,
+    ) -> Result<()> {
+        if self.current != Some(origin.key) {
+            self.current = Some(origin.key);
+            announce_source(&origin.key.label(), &mut self.ctx);
+        }
+        let count = self.counts.entry(origin.key).or_default();
+        *count += 1;
+        prettify_line(&raw_bytes::decode(message), *count, &mut self.ctx)?;
+        Ok(())
+    }
+
+    fn flush_output(&mut self) -> Result<()> {
+        self.ctx.out.flush()?;
+        Ok(())
+    }
+}
+
+/// Set up a decoding context on stdout, let `process` drive the capture through it,
+/// then print the end-of-run counts, summary and validation totals.
+pub fn run<F>(settings: DecodeSettings<'_>, process: F) -> Result<ExitCode>
+where
+    F: FnOnce(&mut DecodeSink<'_>) -> Result<()>,
+{
+    let is_terminal = io::stdout().is_terminal();
+    if !is_terminal {
+        disable_output_colours();
+    }
+    let fix_override = settings.fix.and_then(normalise_fix_key);
+    let obfuscator = fix::create_obfuscator(false);
+    let mut summary = settings.summary.then(|| OrderSummary::new('\u{0001}'));
+    let mut stdout = io::BufWriter::new(io::stdout().lock());
+    let mut stderr = io::stderr();
+
+    let mut ctx = PrettifyContext::new(&mut stdout, &mut stderr, &obfuscator, &mut summary);
+    ctx.fix_override = fix_override.as_deref();
+    ctx.validation_enabled = settings.validate;
+    ctx.live_status_enabled = is_terminal;
+
+    let mut sink = DecodeSink {
+        ctx,
+        current: None,
+        counts: HashMap::new(),
+    };
+    let result = process(&mut sink);
+    let outcome = finish_run(&mut sink.ctx, result.is_err());
+    drop(sink);
+    stdout.flush()?;
+    result?;
+    Ok(if outcome.validation.invalid > 0 {
+        ExitCode::from(EXIT_INVALID)
+    } else {
+        ExitCode::SUCCESS
+    })
+}
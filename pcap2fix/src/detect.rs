@@ -0,0 +1,173 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Content-based flow detection when no `--ports` are given: a flow is only buffered
+// once its first payload starts with `8=FIX` (which also covers `8=FIXT`).  Other
+// flows are remembered in a bounded least-recently-used set, so their later packets
+// are dropped without allocating anything.
+
+use crate::FlowKey;
+use std::collections::{BTreeMap, HashMap};
+
+/// Rejected flows remembered at once.  The least recently seen is forgotten first; if
+/// it shows up again its mid-stream payload is simply rejected again.
+const REJECTED_CAPACITY: usize = 4096;
+
+const FIX_PREFIX: &[u8] = b"8=FIX";
+
+/// Decides which new flows get a `FlowState`, and counts the decisions for the report
+/// printed at exit.
+#[derive(Debug)]
+pub struct FlowGate {
+    rejected: LruSet,
+    accepted: u64,
+    rejections: u64,
+}
+
+impl FlowGate {
+    pub fn new() -> Self {
+        Self::with_capacity(REJECTED_CAPACITY)
+    }
+
+    fn with_capacity(capacity: usize) -> Self {
+        FlowGate {
+            rejected: LruSet::new(capacity),
+            accepted: 0,
+            rejections: 0,
+        }
+    }
+
+    /// Whether a flow with no state yet should get one, judged by the first payload it
+    /// carries.  A first segment shorter than `8=FIX` is accepted when it matches as far
+    /// as it goes, since the rest of the prefix may be in the next segment.
+    pub fn admit(&mut self, key: FlowKey, payload: &[u8]) -> bool {
+        if self.rejected.touch(&key) {
+            return false;
+        }
+        let len = payload.len().min(FIX_PREFIX.len());
+        if len > 0 && payload[..len] == FIX_PREFIX[..len] {
+            self.accepted += 1;
+            true
+        } else {
+            self.rejected.insert(key);
+            self.rejections += 1;
+            false
+        }
+    }
+
+    /// The connection on `key` has ended or is starting again, so judge its next
+    /// payload afresh.
+    pub fn forget(&mut self, key: &FlowKey) {
+        self.rejected.remove(key);
+    }
+
+    /// e.g. `flow detection: 1 accepted as FIX, 3 rejected`.
+    pub fn summary(&self) -> String {
+        format!(
+            "flow detection: {} accepted as FIX, {} rejected",
+            self.accepted, self.rejections
+        )
+    }
+}
+
+/// Flow keys that forget the least recently used once `capacity` is reached.
+#[derive(Debug)]
+struct LruSet {
+    capacity: usize,
+    tick: u64,
+    last_used: HashMap<FlowKey, u64>,
+    by_age: BTreeMap<u64, FlowKey>,
+}
+
+impl LruSet {
+    fn new(capacity: usize) -> Self {
+        LruSet {
+            capacity,
+            tick: 0,
+            last_used: HashMap::new(),
+            by_age: BTreeMap::new(),
+        }
+    }
+
+    /// Mark `key` as just used, returning whether it is present.
+    fn touch(&mut self, key: &FlowKey) -> bool {
+        let Some(used) = self.last_used.get_mut(key) else {
+            return false;
+        };
+        self.by_age.remove(used);
+        self.tick += 1;
+        *used = self.tick;
+        self.by_age.insert(self.tick, *key);
+        true
+    }
+
+    fn insert(&mut self, key: FlowKey) {
+        if self.touch(&key) {
+            return;
+        }
+        if self.last_used.len() >= self.capacity {
+            if let Some((_, oldest)) = self.by_age.pop_first() {
+                self.last_used.remove(&oldest);
+            }
+        }
+        self.tick += 1;
+        self.last_used.insert(key, self.tick);
+        self.by_age.insert(self.tick, key);
+    }
+
+    fn remove(&mut self, key: &FlowKey) {
+        if let Some(used) = self.last_used.remove(key) {
+            self.by_age.remove(&used);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::Ipv4Addr;
+
+    fn key(sport: u16) -> FlowKey {
+        FlowKey {
+            src: Ipv4Addr::new(10, 0, 0, 1),
+            dst: Ipv4Addr::new(10, 0, 0, 2),
+            sport,
+            dport: 443,
+        }
+    }
+
+    #[test]
+    fn admits_flows_that_open_with_a_fix_begin_string() {
+        let mut gate = FlowGate::new();
+        assert!(gate.admit(key(1), b"8=FIX.4.4\x019=5\x01"));
+        assert!(gate.admit(key(2), b"8=FIXT.1.1\x01"));
+        assert!(
+            gate.admit(key(3), b"8="),
+            "a short first segment may still be FIX"
+        );
+        assert!(!gate.admit(key(4), b"GET / HTTP/1.1\r\n"));
+        assert!(!gate.admit(key(5), b"\x16\x03\x01\x02\x00"));
+        assert!(
+            !gate.admit(key(4), b"8=FIX.4.4\x01"),
+            "a rejected flow stays rejected mid-stream"
+        );
+        assert_eq!(
+            gate.summary(),
+            "flow detection: 3 accepted as FIX, 2 rejected"
+        );
+
+        gate.forget(&key(4));
+        assert!(gate.admit(key(4), b"8=FIX.4.4\x01"));
+    }
+
+    #[test]
+    fn rejected_set_forgets_the_least_recently_seen_flow() {
+        let mut gate = FlowGate::with_capacity(2);
+        assert!(!gate.admit(key(1), b"GET"));
+        assert!(!gate.admit(key(2), b"GET"));
+        assert!(!gate.admit(key(1), b"more"), "touches flow 1");
+        assert!(!gate.admit(key(3), b"GET"), "evicts flow 2");
+        assert_eq!(gate.rejected.last_used.len(), 2);
+        assert!(gate.rejected.touch(&key(1)));
+        assert!(!gate.rejected.touch(&key(2)));
+        assert!(gate.rejected.touch(&key(3)));
+    }
+}
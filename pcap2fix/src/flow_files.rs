@@ -0,0 +1,129 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// `--output-dir`: write each flow's messages to its own file instead of stdout.
+
+use crate::{format_message, EmitOptions, FlowKey, MessageSink, Origin};
+use anyhow::{Context, Result};
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+/// One `<src>_<sport>-<dst>_<dport>.fix` file per flow, opened when the flow's first
+/// message arrives and closed when the flow is torn down.
+pub struct FlowFiles {
+    dir: PathBuf,
+    open: HashMap<FlowKey, BufWriter<File>>,
+    /// Flows whose file has been created this run; a reused connection appends to it.
+    created: HashSet<FlowKey>,
+}
+
+impl FlowFiles {
+    pub fn new(dir: &Path) -> Result<Self> {
+        fs::create_dir_all(dir)
+            .with_context(|| format!("failed to create output directory {}", dir.display()))?;
+        Ok(FlowFiles {
+            dir: dir.to_path_buf(),
+            open: HashMap::new(),
+            created: HashSet::new(),
+        })
+    }
+
+    /// Flush any files still open.
+    pub fn finish(mut self) -> Result<()> {
+        for (_, mut file) in self.open.drain() {
+            file.flush()?;
+        }
+        Ok(())
+    }
+}
+
+fn file_name(key: &FlowKey) -> String {
+    format!("{}_{}-{}_{}.fix", key.src, key.sport, key.dst, key.dport)
+}
+
+impl MessageSink for FlowFiles {
+    fn emit(
+        &mut self,
+        message: &[u8],
+        opts: &EmitOptions,
+        origin: &Origin,
+        scratch: &mut Vec<u8>,
+    ) -> Result<()> {
+        let file = match self.open.entry(origin.key) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => {
+                let path = self.dir.join(file_name(&origin.key));
+                let file = if self.created.insert(origin.key) {
+                    File::create(&path)
+                } else {
+                    OpenOptions::new().append(true).open(&path)
+                }
+                .with_context(|| format!("failed to open {}", path.display()))?;
+                entry.insert(BufWriter::new(file))
+            }
+        };
+        scratch.clear();
+        format_message(message, opts, origin, scratch);
+        scratch.push(b'\n');
+        file.write_all(scratch)?;
+        Ok(())
+    }
+
+    fn close_flow(&mut self, key: &FlowKey) -> Result<()> {
+        if let Some(mut file) = self.open.remove(key) {
+            file.flush()?;
+        }
+        Ok(())
+    }
+
+    fn flush_output(&mut self) -> Result<()> {
+        for file in self.open.values_mut() {
+            file.flush()?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::OutputFormat;
+    use std::net::Ipv4Addr;
+    use std::time::Duration;
+
+    #[test]
+    fn files_are_named_by_flow_and_reopened_for_append() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path().join("flows");
+        let mut files = FlowFiles::new(&dir).unwrap();
+        let opts = EmitOptions {
+            delimiter: b'|',
+            max_flow_bytes: 1024,
+            ports: Vec::new(),
+            format: OutputFormat::Raw,
+        };
+        let origin = Origin {
+            ts: Duration::ZERO,
+            key: FlowKey {
+                src: Ipv4Addr::new(10, 0, 0, 1),
+                dst: Ipv4Addr::new(10, 0, 0, 2),
+                sport: 40000,
+                dport: 12083,
+            },
+        };
+        let mut scratch = Vec::new();
+
+        assert!(
+            fs::read_dir(&dir).unwrap().next().is_none(),
+            "created lazily"
+        );
+        files.emit(b"one", &opts, &origin, &mut scratch).unwrap();
+        files.close_flow(&origin.key).unwrap();
+        files.emit(b"two", &opts, &origin, &mut scratch).unwrap();
+        files.finish().unwrap();
+
+        let written = fs::read_to_string(dir.join("10.0.0.1_40000-10.0.0.2_12083.fix")).unwrap();
+        assert_eq!(written, "one\ntwo\n");
+    }
+}
@@ -0,0 +1,72 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// `--follow`: keep reading a capture that is still being written, such as a
+// `tcpdump -w` file or a named pipe, instead of stopping at its current end.
+
+use anyhow::Result;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// How long to wait before looking for more capture data.
+pub const FOLLOW_SLEEP: Duration = Duration::from_millis(250);
+
+/// The input being followed.  Only a regular file can be truncated or rotated; a pipe
+/// or stdin simply has no more data yet, so its size is never checked.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Follower {
+    file: Option<PathBuf>,
+}
+
+impl Follower {
+    /// Follow `input`, which is a path or "-" for stdin.
+    pub fn new(input: &str) -> Result<Self> {
+        let file = match input {
+            "-" => None,
+            path => Some(fs::metadata(path)?)
+                .filter(fs::Metadata::is_file)
+                .map(|_| PathBuf::from(path)),
+        };
+        Ok(Follower { file })
+    }
+
+    /// The file to reopen when it is now shorter than the `consumed` bytes already
+    /// read from it, as when `tcpdump -G` starts the same file name afresh.  A file
+    /// that has briefly vanished during rotation is not reported until it returns.
+    pub fn truncated(&self, consumed: usize) -> Option<&Path> {
+        let path = self.file.as_deref()?;
+        let len = fs::metadata(path).ok()?.len();
+        (len < consumed as u64).then_some(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn shrinking_files_are_reported_as_truncated() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(&[0; 100]).unwrap();
+        let path = file.path().to_str().unwrap();
+        let follower = Follower::new(path).unwrap();
+        assert_eq!(follower.truncated(100), None);
+        file.as_file().set_len(40).unwrap();
+        assert_eq!(follower.truncated(100), Some(file.path()));
+        assert_eq!(Follower::new("-").unwrap().truncated(100), None);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn named_pipes_are_never_truncated() {
+        let dir = tempfile::tempdir().unwrap();
+        let fifo = dir.path().join("capture.fifo");
+        let made = std::process::Command::new("mkfifo")
+            .arg(&fifo)
+            .status()
+            .unwrap();
+        assert!(made.success());
+        let follower = Follower::new(fifo.to_str().unwrap()).unwrap();
+        assert_eq!(follower.truncated(1_000_000), None);
+    }
+}
@@ -1,42 +1,149 @@
 // SPDX-License-Identifier: AGPL-3.0-only
 // Minimal PCAP-to-FIX filter: reads PCAP (file or stdin), reassembles TCP
-// streams, and emits FIX messages separated by the chosen delimiter.
+// streams, and emits FIX messages separated by the chosen delimiter, optionally
+// tagged with the capture timestamp and flow identity.
 
 use anyhow::{anyhow, Context, Result};
-use clap::Parser;
+use clap::{Parser, ValueEnum};
 use etherparse::{NetSlice, SlicedPacket, TransportSlice};
 use pcap_parser::data::{get_packetdata, PacketData, ETHERTYPE_IPV4, ETHERTYPE_IPV6};
-use pcap_parser::pcapng::Block;
+use pcap_parser::pcapng::{Block, EnhancedPacketBlock};
 use pcap_parser::traits::{PcapNGPacketBlock, PcapReaderIterator};
-use pcap_parser::{create_reader, Linktype, PcapBlockOwned};
+use pcap_parser::{create_reader, LegacyPcapBlock, Linktype, PcapBlockOwned};
 use std::collections::HashMap;
 use std::fs::File;
 use std::io::{self, Write};
 use std::net::Ipv4Addr;
-use std::time::{Duration, Instant};
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
 use thiserror::Error;
 
+use detect::FlowGate;
+use fixdecoder::decoder::delimiter::{self, DELIMITER_FORMS};
+use fixdecoder::decoder::prettifier::interrupt_flag;
+use fixdecoder::decoder::warnings::{self, WarningMode};
+use follow::{Follower, FOLLOW_SLEEP};
+
+mod decode;
+mod detect;
+mod flow_files;
+mod follow;
+
 #[derive(Parser, Debug)]
 #[command(author, version, about)]
 struct Args {
     /// PCAP file path or "-" for stdin
     #[arg(short, long, default_value = "-")]
     input: String,
-    /// TCP port filter (optional). If omitted, all ports are considered.
-    #[arg(short = 'p', long)]
-    port: Option<u16>,
-    /// Message delimiter. Accepts "SOH", literal char, or hex like \x01.
-    #[arg(short = 'd', long, default_value = "SOH")]
+    /// TCP ports to keep, comma-separated (e.g. 9878,9879). If omitted, a flow is kept
+    /// when its first payload starts with 8=FIX.
+    #[arg(
+        short = 'p',
+        long = "ports",
+        visible_alias = "port",
+        value_name = "PORT[,PORT...]",
+        value_delimiter = ','
+    )]
+    ports: Vec<u16>,
+    /// Message delimiter, as a single ASCII byte.
+    #[arg(
+        short = 'd',
+        long,
+        default_value = "SOH",
+        help = format!("Message delimiter, as a single ASCII byte: {DELIMITER_FORMS}")
+    )]
     delimiter: String,
     /// Max bytes to buffer per flow before eviction
     #[arg(long, default_value = "1048576")]
     max_flow_bytes: usize,
-    /// Idle timeout for flows (seconds)
+    /// Idle timeout for flows (seconds of capture time)
     #[arg(long, default_value = "60")]
     idle_timeout: u64,
+    /// Output format: raw messages, messages prefixed with timestamp and flow, or JSON lines
+    #[arg(long, value_enum, default_value_t = OutputFormat::Raw)]
+    format: OutputFormat,
+    /// Decode messages in-process with fixdecoder instead of printing them raw
+    #[arg(long, conflicts_with = "format")]
+    decode: bool,
+    /// With --decode: validate each message and report errors
+    #[arg(long, requires = "decode")]
+    validate: bool,
+    /// With --decode: track orders and print a summary instead of decoded messages
+    #[arg(long, requires = "decode")]
+    summary: bool,
+    /// With --decode: FIX version to decode with (e.g. 44), overriding BeginString
+    #[arg(long, value_name = "VER", requires = "decode")]
+    fix: Option<String>,
+    /// Write each flow's messages to DIR/<src>_<sport>-<dst>_<dport>.fix instead of stdout
+    #[arg(long, value_name = "DIR", conflicts_with = "decode")]
+    output_dir: Option<PathBuf>,
+    /// Keep reading as the capture file or pipe grows, until Ctrl+C
+    #[arg(short = 'f', long)]
+    follow: bool,
+    /// Print no warnings on stderr
+    #[arg(long, conflicts_with = "verbose_warnings")]
+    quiet: bool,
+    /// Print every occurrence of a warning instead of each distinct warning once
+    #[arg(long)]
+    verbose_warnings: bool,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum OutputFormat {
+    /// Message bytes only, one per line
+    #[default]
+    Raw,
+    /// `<epoch.micros> <src>:<sport> -> <dst>:<dport> ` before each message
+    Prefixed,
+    /// One JSON object per message with timestamp, flow and direction
+    Jsonl,
+}
+
+/// Settings shared by every packet while reassembling and emitting messages.
+#[derive(Debug, Clone)]
+struct EmitOptions {
+    delimiter: u8,
+    max_flow_bytes: usize,
+    /// `--ports`; empty means flows are chosen by content instead.
+    ports: Vec<u16>,
+    format: OutputFormat,
+}
+
+/// Where and when a message was captured.  `ts` is the capture time since the Unix epoch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Origin {
+    ts: Duration,
+    key: FlowKey,
+}
+
+impl Origin {
+    /// Direction relative to the `--ports` filter (traffic to a listed port is inbound),
+    /// or to the lower-numbered port, taken as the server side, when no filter is given.
+    fn direction(&self, ports: &[u16]) -> &'static str {
+        let inbound = if ports.is_empty() {
+            self.key.dport <= self.key.sport
+        } else {
+            ports.contains(&self.key.dport)
+        };
+        if inbound {
+            "inbound"
+        } else {
+            "outbound"
+        }
+    }
+}
+
+/// Link type and timestamp decoding parameters for a pcapng interface.
+#[derive(Debug, Clone, Copy)]
+struct Interface {
+    linktype: Linktype,
+    ts_offset: u64,
+    ts_resolution: u64,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
 struct FlowKey {
     src: Ipv4Addr,
     dst: Ipv4Addr,
@@ -45,11 +152,30 @@ struct FlowKey {
     // direction handled by seq tracking in FlowState
 }
 
+impl FlowKey {
+    /// `src:sport -> dst:dport`, as shown in prefixed output and decoded flow headings.
+    fn label(&self) -> String {
+        format!("{}:{} -> {}:{}", self.src, self.sport, self.dst, self.dport)
+    }
+
+    /// The same connection seen from the other end.
+    fn reversed(&self) -> FlowKey {
+        FlowKey {
+            src: self.dst,
+            dst: self.src,
+            sport: self.dport,
+            dport: self.sport,
+        }
+    }
+}
+
 #[derive(Debug)]
 struct FlowState {
     next_seq: Option<u32>,
     buffer: Vec<u8>,
-    last_seen: Instant,
+    /// Capture time of the most recent segment, used for idle eviction.
+    last_seen: Duration,
+    stats: FlowStats,
 }
 
 impl Default for FlowState {
@@ -57,7 +183,45 @@ impl Default for FlowState {
         FlowState {
             next_seq: None,
             buffer: Vec::new(),
-            last_seen: Instant::now(),
+            last_seen: Duration::ZERO,
+            stats: FlowStats::default(),
+        }
+    }
+}
+
+/// Per-flow counters reported on stderr when the flow is torn down.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+struct FlowStats {
+    packets: u64,
+    /// TCP payload bytes seen, including any later discarded.
+    bytes: u64,
+    messages: u64,
+    /// Payload skipped because it arrived beyond a gap in the sequence space.
+    gap_bytes: u64,
+    /// Retransmissions whose bytes differed from the copy already buffered.
+    conflicts: u64,
+    /// Buffered bytes thrown away to get back to the next `8=FIX` after corruption.
+    resync_bytes: u64,
+}
+
+/// Why a flow was torn down, as shown in its summary line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Teardown {
+    Fin,
+    Rst,
+    Idle,
+    EndOfCapture,
+    Interrupted,
+}
+
+impl Teardown {
+    fn label(self) -> &'static str {
+        match self {
+            Teardown::Fin => "FIN",
+            Teardown::Rst => "RST",
+            Teardown::Idle => "idle",
+            Teardown::EndOfCapture => "end of capture",
+            Teardown::Interrupted => "interrupted",
         }
     }
 }
@@ -68,119 +232,328 @@ enum ReassemblyError {
     Overflow,
 }
 
-fn main() -> Result<()> {
+fn main() -> Result<ExitCode> {
     let args = Args::parse();
-    let delimiter = parse_delimiter(&args.delimiter)?;
-    let mut reader = open_reader(&args.input)?;
+    warnings::set_mode(if args.quiet {
+        WarningMode::Quiet
+    } else if args.verbose_warnings {
+        WarningMode::Every
+    } else {
+        WarningMode::Once
+    });
+    let result = run(args);
+    let _ = warnings::report_suppressed(&mut io::stderr());
+    result
+}
 
-    let mut flows: HashMap<FlowKey, FlowState> = HashMap::new();
+/// Run the capture; `--decode --validate` exits 2 when a message is invalid, as
+/// `fixdecoder --validate` does.
+fn run(args: Args) -> Result<ExitCode> {
+    let opts = EmitOptions {
+        delimiter: parse_delimiter(&args.delimiter)?,
+        max_flow_bytes: args.max_flow_bytes,
+        ports: args.ports,
+        format: args.format,
+    };
+    ctrlc::set_handler(|| interrupt_flag().store(true, Ordering::Relaxed))
+        .context("failed to install Ctrl+C handler")?;
+    let reader = open_reader(&args.input)?;
     let idle = Duration::from_secs(args.idle_timeout);
+    let follow = args
+        .follow
+        .then(|| Follower::new(&args.input))
+        .transpose()
+        .with_context(|| format!("follow {}", args.input))?;
+    let follow = follow.as_ref();
+
+    if let Some(dir) = &args.output_dir {
+        let mut files = flow_files::FlowFiles::new(dir)?;
+        process_capture(reader, &opts, idle, follow, &mut files)?;
+        files.finish()?;
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    if args.decode {
+        let settings = decode::DecodeSettings {
+            validate: args.validate,
+            summary: args.summary,
+            fix: args.fix.as_deref(),
+        };
+        return decode::run(settings, |sink| {
+            process_capture(reader, &opts, idle, follow, sink)
+        });
+    }
+
     let mut stdout = io::BufWriter::new(io::stdout().lock());
-    let mut scratch = Vec::new();
+    process_capture(reader, &opts, idle, follow, &mut stdout)?;
+    stdout.flush()?;
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Receives each complete FIX message cut from a TCP flow.
+trait MessageSink {
+    /// Handle one message; `scratch` is a formatting buffer reused between calls.
+    fn emit(
+        &mut self,
+        message: &[u8],
+        opts: &EmitOptions,
+        origin: &Origin,
+        scratch: &mut Vec<u8>,
+    ) -> Result<()>;
+
+    /// The flow has been torn down; no more messages will arrive for it unless the
+    /// same endpoints start a new connection.
+    fn close_flow(&mut self, _key: &FlowKey) -> Result<()> {
+        Ok(())
+    }
+
+    /// Push out anything buffered; called while `--follow` waits for more data.
+    fn flush_output(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Plain output: each message in the selected `--format`, one per line.
+impl<W: Write> MessageSink for W {
+    fn emit(
+        &mut self,
+        message: &[u8],
+        opts: &EmitOptions,
+        origin: &Origin,
+        scratch: &mut Vec<u8>,
+    ) -> Result<()> {
+        scratch.clear();
+        format_message(message, opts, origin, scratch);
+        scratch.push(b'\n'); // newline so each FIX message prints on its own line
+        self.write_all(scratch)?;
+        Ok(())
+    }
+
+    fn flush_output(&mut self) -> Result<()> {
+        self.flush()?;
+        Ok(())
+    }
+}
+
+/// Read every block of the capture, reassembling flows and handing complete
+/// messages to `sink`.  With `follow`, the end of the input is waited out rather than
+/// ending the run; flows then go idle by wall-clock time spent waiting, and a file
+/// that shrinks is reopened from the start.  Ctrl+C closes every flow and returns.
+/// Without `--ports`, flows are admitted by content and the tally is reported at the end.
+fn process_capture<S: MessageSink>(
+    mut reader: Box<dyn PcapReaderIterator>,
+    opts: &EmitOptions,
+    idle: Duration,
+    follow: Option<&Follower>,
+    sink: &mut S,
+) -> Result<()> {
+    let mut flows: HashMap<FlowKey, FlowState> = HashMap::new();
+    let mut gate = opts.ports.is_empty().then(FlowGate::new);
     let mut legacy_linktype = None;
-    let mut idb_linktypes: HashMap<u32, Linktype> = HashMap::new();
+    let mut legacy_nanos = false;
+    let mut interfaces: HashMap<u32, Interface> = HashMap::new();
     let mut next_if_id: u32 = 0;
+    // Latest capture time seen; simple packet blocks carry no timestamp of their own.
+    let mut clock = Duration::ZERO;
+    // Time spent waiting for more data since the last block, under --follow.
+    let mut waited = Duration::ZERO;
+    let interrupted = interrupt_flag();
 
     loop {
+        if interrupted.load(Ordering::Relaxed) {
+            break;
+        }
+        // A partial block cannot complete once the last refill found nothing more.
+        let exhausted = reader.reader_exhausted();
         match reader.next() {
             Ok((offset, block)) => {
-                {
-                    match block {
-                        PcapBlockOwned::LegacyHeader(hdr) => {
-                            legacy_linktype = Some(hdr.network);
-                        }
-                        PcapBlockOwned::Legacy(b) => {
-                            let linktype = legacy_linktype.unwrap_or(Linktype::ETHERNET);
-                            if let Some(packet) =
-                                get_packetdata(b.data, linktype, b.caplen as usize)
-                            {
-                                if let Err(err) = handle_packet_data(
-                                    packet,
-                                    args.port,
-                                    delimiter,
-                                    args.max_flow_bytes,
-                                    &mut flows,
-                                    &mut stdout,
-                                ) {
-                                    eprintln!("warn: skipping packet: {err}");
-                                }
-                            }
-                        }
-                        PcapBlockOwned::NG(block) => match block {
-                            Block::SectionHeader(_) => {
-                                idb_linktypes.clear();
-                                next_if_id = 0;
-                            }
-                            Block::InterfaceDescription(idb) => {
-                                idb_linktypes.insert(next_if_id, idb.linktype);
-                                next_if_id += 1;
-                            }
-                            Block::EnhancedPacket(epb) => {
-                                if let Some(linktype) = idb_linktypes.get(&epb.if_id) {
-                                    if let Some(packet) = get_packetdata(
-                                        epb.packet_data(),
-                                        *linktype,
-                                        epb.caplen as usize,
-                                    ) {
-                                        if let Err(err) = handle_packet_data(
-                                            packet,
-                                            args.port,
-                                            delimiter,
-                                            args.max_flow_bytes,
-                                            &mut flows,
-                                            &mut stdout,
-                                        ) {
-                                            eprintln!("warn: skipping packet: {err}");
-                                        }
-                                    }
-                                }
-                            }
-                            Block::SimplePacket(spb) => {
-                                if let Some(linktype) = idb_linktypes.get(&0) {
-                                    if let Some(packet) = get_packetdata(
-                                        spb.packet_data(),
-                                        *linktype,
-                                        spb.origlen as usize,
-                                    ) {
-                                        if let Err(err) = handle_packet_data(
-                                            packet,
-                                            args.port,
-                                            delimiter,
-                                            args.max_flow_bytes,
-                                            &mut flows,
-                                            &mut stdout,
-                                        ) {
-                                            eprintln!("warn: skipping packet: {err}");
-                                        }
-                                    }
-                                }
-                            }
-                            _ => {}
-                        },
+                waited = Duration::ZERO;
+                let packet = match &block {
+                    PcapBlockOwned::LegacyHeader(hdr) => {
+                        legacy_linktype = Some(hdr.network);
+                        legacy_nanos = hdr.is_nanosecond_precision();
+                        None
+                    }
+                    PcapBlockOwned::Legacy(b) => {
+                        let linktype = legacy_linktype.unwrap_or(Linktype::ETHERNET);
+                        clock = legacy_timestamp(b, legacy_nanos);
+                        get_packetdata(b.data, linktype, b.caplen as usize)
+                    }
+                    PcapBlockOwned::NG(Block::SectionHeader(_)) => {
+                        interfaces.clear();
+                        next_if_id = 0;
+                        None
+                    }
+                    PcapBlockOwned::NG(Block::InterfaceDescription(idb)) => {
+                        interfaces.insert(
+                            next_if_id,
+                            Interface {
+                                linktype: idb.linktype,
+                                ts_offset: idb.ts_offset(),
+                                ts_resolution: idb.ts_resolution().unwrap_or(1_000_000),
+                            },
+                        );
+                        next_if_id += 1;
+                        None
+                    }
+                    PcapBlockOwned::NG(Block::EnhancedPacket(epb)) => {
+                        interfaces.get(&epb.if_id).and_then(|iface| {
+                            clock = enhanced_timestamp(epb, iface);
+                            get_packetdata(epb.packet_data(), iface.linktype, epb.caplen as usize)
+                        })
+                    }
+                    PcapBlockOwned::NG(Block::SimplePacket(spb)) => {
+                        interfaces.get(&0).and_then(|iface| {
+                            get_packetdata(spb.packet_data(), iface.linktype, spb.origlen as usize)
+                        })
+                    }
+                    PcapBlockOwned::NG(_) => None,
+                };
+                if let Some(packet) = packet {
+                    let gate = gate.as_mut();
+                    if let Err(err) =
+                        handle_packet_data(packet, clock, opts, &mut flows, gate, sink)
+                    {
+                        let err = err.to_string();
+                        warnings::warn("packet", &err, &format!("warn: skipping packet: {err}"));
                     }
                 }
                 reader.consume(offset);
-                evict_idle(&mut flows, idle);
+                for (key, flow) in evict_idle(&mut flows, idle, clock) {
+                    close_flow(key, flow, Teardown::Idle, opts, sink)?;
+                }
             }
-            Err(pcap_parser::PcapError::Eof) => break,
-            Err(pcap_parser::PcapError::Incomplete) => {
+            Err(pcap_parser::PcapError::Eof) if follow.is_none() => break,
+            Err(pcap_parser::PcapError::Incomplete) if !exhausted => {
                 // need more data
                 reader
                     .refill()
                     .map_err(|e| anyhow!("failed to refill reader: {e}"))?;
             }
+            Err(pcap_parser::PcapError::Eof | pcap_parser::PcapError::Incomplete) => {
+                let Some(follow) = follow else {
+                    warnings::warn(
+                        "truncated-block",
+                        "",
+                        "warn: capture ends with a truncated block",
+                    );
+                    break;
+                };
+                sink.flush_output()?;
+                std::thread::sleep(FOLLOW_SLEEP);
+                waited += FOLLOW_SLEEP;
+                for (key, flow) in evict_idle(&mut flows, idle, clock + waited) {
+                    close_flow(key, flow, Teardown::Idle, opts, sink)?;
+                }
+                if let Some(path) = follow.truncated(reader.consumed()) {
+                    // A rotated file may not have its header yet; retry on the next pass.
+                    if let Ok(reopened) = open_reader(&path.to_string_lossy()) {
+                        let path = path.display().to_string();
+                        warnings::warn(
+                            "truncated-file",
+                            &path,
+                            &format!("warn: {path} was truncated; reading it again from the start"),
+                        );
+                        reader = reopened;
+                    }
+                } else {
+                    reader
+                        .refill()
+                        .map_err(|e| anyhow!("failed to refill reader: {e}"))?;
+                }
+            }
             Err(e) => return Err(anyhow!("pcap parse error: {e}")),
         }
     }
 
     // flush any trailing message fragments (best effort)
-    for flow in flows.values_mut() {
-        flush_complete_messages(&mut flow.buffer, delimiter, &mut scratch, &mut stdout)?;
+    let reason = if interrupted.load(Ordering::Relaxed) {
+        Teardown::Interrupted
+    } else {
+        Teardown::EndOfCapture
+    };
+    let mut remaining: Vec<_> = flows.into_iter().collect();
+    remaining.sort_by_key(|(key, _)| *key);
+    for (key, flow) in remaining {
+        close_flow(key, flow, reason, opts, sink)?;
     }
-    stdout.flush()?;
+    if let Some(gate) = gate {
+        eprintln!("{}", gate.summary());
+    }
+    Ok(())
+}
+
+/// Emit whatever complete messages remain in a finished flow, tell the sink, and
+/// report the flow's counters on stderr.
+fn close_flow<S: MessageSink>(
+    key: FlowKey,
+    mut flow: FlowState,
+    reason: Teardown,
+    opts: &EmitOptions,
+    sink: &mut S,
+) -> Result<()> {
+    let origin = Origin {
+        ts: flow.last_seen,
+        key,
+    };
+    let mut scratch = Vec::new();
+    let (messages, resync_bytes) =
+        flush_complete_messages(&mut flow.buffer, opts, &origin, &mut scratch, sink)?;
+    flow.stats.messages += messages;
+    flow.stats.resync_bytes += resync_bytes;
+    sink.close_flow(&key)?;
+    eprintln!("{}", flow_summary(&key, &flow, reason));
     Ok(())
 }
 
+/// One-line teardown report, e.g.
+/// `flow 10.0.0.1:40000 -> 10.0.0.2:12083 closed (FIN): 3 packets, 210 bytes, 2 messages, 0 bytes discarded in gaps`.
+fn flow_summary(key: &FlowKey, flow: &FlowState, reason: Teardown) -> String {
+    let stats = &flow.stats;
+    let mut line = format!(
+        "flow {} closed ({}): {} packets, {} bytes, {} messages, {} bytes discarded in gaps",
+        key.label(),
+        reason.label(),
+        stats.packets,
+        stats.bytes,
+        stats.messages,
+        stats.gap_bytes
+    );
+    if stats.conflicts > 0 {
+        line.push_str(&format!(
+            ", {} conflicting retransmissions",
+            stats.conflicts
+        ));
+    }
+    if stats.resync_bytes > 0 {
+        line.push_str(&format!(
+            ", {} bytes discarded resynchronising",
+            stats.resync_bytes
+        ));
+    }
+    if !flow.buffer.is_empty() {
+        line.push_str(&format!(", {} bytes incomplete", flow.buffer.len()));
+    }
+    line
+}
+
+/// Capture time of a classic pcap record; `ts_usec` holds nanoseconds in nanosecond files.
+fn legacy_timestamp(block: &LegacyPcapBlock<'_>, nanosecond_precision: bool) -> Duration {
+    let nanos = if nanosecond_precision {
+        block.ts_usec
+    } else {
+        block.ts_usec.saturating_mul(1_000)
+    };
+    Duration::new(u64::from(block.ts_sec), 0) + Duration::from_nanos(u64::from(nanos))
+}
+
+/// Capture time of a pcapng enhanced packet, honouring the interface's if_tsresol/if_tsoffset.
+fn enhanced_timestamp(block: &EnhancedPacketBlock<'_>, iface: &Interface) -> Duration {
+    let (secs, fraction) = block.decode_ts(iface.ts_offset, iface.ts_resolution);
+    let nanos = u128::from(fraction) * 1_000_000_000 / u128::from(iface.ts_resolution);
+    Duration::new(u64::from(secs), 0) + Duration::from_nanos(nanos as u64)
+}
+
 fn open_reader(path: &str) -> Result<Box<dyn PcapReaderIterator>> {
     if path == "-" {
         let stdin = io::stdin();
@@ -191,66 +564,57 @@ fn open_reader(path: &str) -> Result<Box<dyn PcapReaderIterator>> {
     }
 }
 
+/// Parse `--delimiter` as fixdecoder does, then insist on a single ASCII byte, since
+/// messages are framed on raw bytes.
 fn parse_delimiter(raw: &str) -> Result<u8> {
-    if raw.eq_ignore_ascii_case("SOH") {
-        return Ok(0x01);
-    }
-    if let Some(hex) = raw.strip_prefix("\\x").or_else(|| raw.strip_prefix("0x")) {
-        let val =
-            u8::from_str_radix(hex, 16).map_err(|_| anyhow!("invalid hex delimiter: {raw}"))?;
-        return Ok(val);
-    }
-    if raw.len() == 1 {
-        return Ok(raw.as_bytes()[0]);
-    }
-    Err(anyhow!(
-        "delimiter must be SOH, hex (\\x01), or single byte"
-    ))
+    let c = delimiter::parse_delimiter(raw)?;
+    u8::try_from(c).ok().filter(u8::is_ascii).ok_or_else(|| {
+        anyhow!(
+            "delimiter '{c}' (U+{:04X}) is not ASCII; pcap2fix frames messages on a single byte",
+            c as u32
+        )
+    })
 }
 
-fn handle_packet_data<W: Write>(
+fn handle_packet_data<S: MessageSink>(
     packet: PacketData<'_>,
-    port_filter: Option<u16>,
-    delimiter: u8,
-    max_flow_bytes: usize,
+    ts: Duration,
+    opts: &EmitOptions,
     flows: &mut HashMap<FlowKey, FlowState>,
-    out: &mut W,
+    gate: Option<&mut FlowGate>,
+    out: &mut S,
 ) -> Result<()> {
     match packet {
         PacketData::L2(data) => {
             let sliced = SlicedPacket::from_ethernet(data).map_err(|e| anyhow!("parse: {e:?}"))?;
-            handle_sliced_packet(sliced, port_filter, delimiter, max_flow_bytes, flows, out)
+            handle_sliced_packet(sliced, ts, opts, flows, gate, out)
         }
         PacketData::L3(ethertype, data)
             if ethertype == ETHERTYPE_IPV4 || ethertype == ETHERTYPE_IPV6 =>
         {
             let sliced = SlicedPacket::from_ip(data).map_err(|e| anyhow!("parse: {e:?}"))?;
-            handle_sliced_packet(sliced, port_filter, delimiter, max_flow_bytes, flows, out)
+            handle_sliced_packet(sliced, ts, opts, flows, gate, out)
         }
         _ => Ok(()),
     }
 }
 
-fn handle_sliced_packet<W: Write>(
+fn handle_sliced_packet<S: MessageSink>(
     sliced: SlicedPacket<'_>,
-    port_filter: Option<u16>,
-    delimiter: u8,
-    max_flow_bytes: usize,
+    ts: Duration,
+    opts: &EmitOptions,
     flows: &mut HashMap<FlowKey, FlowState>,
-    out: &mut W,
+    mut gate: Option<&mut FlowGate>,
+    out: &mut S,
 ) -> Result<()> {
     let (ip, tcp) = match (sliced.net, sliced.transport) {
         (Some(NetSlice::Ipv4(ip)), Some(TransportSlice::Tcp(tcp))) => (ip, tcp),
         _ => return Ok(()),
     };
-    if let Some(p) = port_filter {
-        if tcp.source_port() != p && tcp.destination_port() != p {
-            return Ok(());
-        }
-    }
-
-    let payload = tcp.payload();
-    if payload.is_empty() {
+    if !opts.ports.is_empty()
+        && !opts.ports.contains(&tcp.source_port())
+        && !opts.ports.contains(&tcp.destination_port())
+    {
         return Ok(());
     }
 
@@ -262,109 +626,271 @@ fn handle_sliced_packet<W: Write>(
         dport: tcp.destination_port(),
     };
 
-    let seq = tcp.sequence_number();
-    let flow = flows.entry(key).or_default();
-    flow.last_seen = Instant::now();
+    // Segments without payload only matter to flows already carrying data, and a new
+    // flow only gets state once the gate (if any) has seen that it carries FIX.
+    let payload = tcp.payload();
+    if tcp.syn() {
+        if let Some(gate) = gate.as_deref_mut() {
+            gate.forget(&key);
+        }
+    }
+    let flow = if payload.is_empty() {
+        flows.get_mut(&key)
+    } else if flows.contains_key(&key)
+        || gate
+            .as_deref_mut()
+            .is_none_or(|gate| gate.admit(key, payload))
+    {
+        Some(flows.entry(key).or_default())
+    } else {
+        None
+    };
+    let mut result = Ok(());
+    if let Some(flow) = flow {
+        flow.last_seen = ts;
+        flow.stats.packets += 1;
+        flow.stats.bytes += payload.len() as u64;
+        if !payload.is_empty() {
+            let seq = tcp.sequence_number();
+            result = reassemble_and_emit(flow, seq, payload, opts, &Origin { ts, key }, out);
+        }
+    }
 
-    reassemble_and_emit(flow, seq, payload, delimiter, max_flow_bytes, out)
+    // FIN ends one direction; RST ends the whole connection.
+    let closing = if tcp.rst() {
+        vec![(key, Teardown::Rst), (key.reversed(), Teardown::Rst)]
+    } else if tcp.fin() {
+        vec![(key, Teardown::Fin)]
+    } else {
+        Vec::new()
+    };
+    for (key, reason) in closing {
+        if let Some(gate) = gate.as_deref_mut() {
+            gate.forget(&key);
+        }
+        if let Some(flow) = flows.remove(&key) {
+            close_flow(key, flow, reason, opts, out)?;
+        }
+    }
+    result
 }
 
-fn reassemble_and_emit<W: Write>(
+fn reassemble_and_emit<S: MessageSink>(
     flow: &mut FlowState,
     seq: u32,
     payload: &[u8],
-    delimiter: u8,
-    max_flow_bytes: usize,
-    out: &mut W,
+    opts: &EmitOptions,
+    origin: &Origin,
+    out: &mut S,
 ) -> Result<()> {
     let expected = flow.next_seq.unwrap_or(seq);
+    // Sequence numbers wrap, so compare them by their signed distance.
+    let ahead = seq.wrapping_sub(expected) as i32;
 
-    if seq == expected {
+    if ahead == 0 {
         flow.buffer.extend_from_slice(payload);
         flow.next_seq = Some(seq.wrapping_add(payload.len() as u32));
-    } else if seq > expected {
+    } else if ahead > 0 {
         // out-of-order future segment: skip for now
+        flow.stats.gap_bytes += payload.len() as u64;
         return Ok(());
     } else {
-        // retransmit or overlap
-        let end = seq.wrapping_add(payload.len() as u32);
-        if end <= expected {
-            // fully duplicate
+        let overlap = expected.wrapping_sub(seq) as usize;
+        if payload.len() <= 1 && overlap >= payload.len() {
+            // keep-alive: one byte (or none) from before the window
             return Ok(());
         }
-        let overlap = (expected - seq) as usize;
-        flow.buffer.extend_from_slice(&payload[overlap..]);
-        flow.next_seq = Some(expected.wrapping_add(payload.len() as u32 - overlap as u32));
+        // Retransmission: the bytes already buffered win over a copy that differs,
+        // and the message they belong to can no longer be trusted.
+        let conflict = retransmission_conflicts(&flow.buffer, overlap, payload);
+        if payload.len() > overlap {
+            flow.buffer.extend_from_slice(&payload[overlap..]);
+            flow.next_seq = Some(seq.wrapping_add(payload.len() as u32));
+        }
+        if conflict {
+            flow.stats.conflicts += 1;
+            flow.stats.resync_bytes += resynchronise(&mut flow.buffer) as u64;
+        }
     }
 
-    if flow.buffer.len() > max_flow_bytes {
+    if flow.buffer.len() > opts.max_flow_bytes {
         flow.buffer.clear();
         return Err(ReassemblyError::Overflow.into());
     }
 
     let mut scratch = Vec::new();
-    flush_complete_messages(&mut flow.buffer, delimiter, &mut scratch, out)?;
+    let (messages, resync_bytes) =
+        flush_complete_messages(&mut flow.buffer, opts, origin, &mut scratch, out)?;
+    flow.stats.messages += messages;
+    flow.stats.resync_bytes += resync_bytes;
     Ok(())
 }
 
-fn flush_complete_messages<W: Write>(
+/// Whether a retransmitted `payload`, starting `overlap` bytes before the next
+/// expected byte, disagrees with what is still buffered for the same bytes.  Bytes
+/// already emitted and drained from `buffer` cannot be compared.
+fn retransmission_conflicts(buffer: &[u8], overlap: usize, payload: &[u8]) -> bool {
+    // payload[i] is the byte `overlap - i` from the end of everything received.
+    let start = overlap.saturating_sub(buffer.len());
+    let end = overlap.min(payload.len());
+    (start..end).any(|i| payload[i] != buffer[buffer.len() - (overlap - i)])
+}
+
+/// Drop buffered bytes up to the next `8=FIX` after the first byte, so a corrupted
+/// message is abandoned and framing restarts at the one after it.  Without another
+/// `8=FIX`, everything goes except a tail that could be the start of one.  Returns
+/// the number of bytes dropped.
+fn resynchronise(buffer: &mut Vec<u8>) -> usize {
+    const BEGIN: &[u8] = b"8=FIX";
+    let keep_from = buffer
+        .windows(BEGIN.len())
+        .skip(1)
+        .position(|window| window == BEGIN)
+        .map(|pos| pos + 1)
+        .unwrap_or_else(|| {
+            let tail = (1..BEGIN.len())
+                .rev()
+                .find(|&len| buffer.len() > len && buffer.ends_with(&BEGIN[..len]))
+                .unwrap_or(0);
+            buffer.len() - tail
+        });
+    buffer.drain(..keep_from);
+    keep_from
+}
+
+/// Emit every complete message at the front of `buffer`, keeping any partial tail.
+/// A message whose framing is broken is skipped by resynchronising on the next
+/// `8=FIX`.  Returns the number of messages emitted and the bytes skipped.
+fn flush_complete_messages<S: MessageSink>(
     buffer: &mut Vec<u8>,
-    delimiter: u8,
+    opts: &EmitOptions,
+    origin: &Origin,
     scratch: &mut Vec<u8>,
-    out: &mut W,
-) -> Result<()> {
-    let mut cursor = 0;
-    while let Some(rel_end) = find_message_end(&buffer[cursor..], delimiter) {
-        let end = cursor + rel_end;
-        scratch.clear();
-        scratch.extend_from_slice(&buffer[cursor..=end]);
-        scratch.push(b'\n'); // newline so each FIX message prints on its own line
-        out.write_all(scratch)?;
-        cursor = end + 1;
+    out: &mut S,
+) -> Result<(u64, u64)> {
+    let mut emitted = 0;
+    let mut skipped = 0;
+    loop {
+        let mut cursor = 0;
+        let mut framing = frame_message(buffer, opts.delimiter);
+        while let Framing::Complete(rel_end) = framing {
+            let end = cursor + rel_end;
+            out.emit(&buffer[cursor..=end], opts, origin, scratch)?;
+            cursor = end + 1;
+            emitted += 1;
+            framing = frame_message(&buffer[cursor..], opts.delimiter);
+        }
+        if cursor > 0 {
+            buffer.drain(0..cursor);
+        }
+        if framing != Framing::Corrupt {
+            return Ok((emitted, skipped));
+        }
+        skipped += resynchronise(buffer) as u64;
     }
-    if cursor > 0 {
-        buffer.drain(0..cursor);
+}
+
+/// Where the message at the front of a buffer ends, as far as its bytes show.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Framing {
+    /// The message ends at this index, its final delimiter.
+    Complete(usize),
+    /// More bytes are needed to tell.
+    Incomplete,
+    /// The bytes cannot be the start of a well-formed message: they do not begin
+    /// with BeginString and BodyLength, or CheckSum is not where BodyLength says.
+    Corrupt,
+}
+
+/// Append `message` to `out` in the selected output format (without the trailing newline).
+fn format_message(message: &[u8], opts: &EmitOptions, origin: &Origin, out: &mut Vec<u8>) {
+    let key = &origin.key;
+    let ts = format_epoch_micros(origin.ts);
+    match opts.format {
+        OutputFormat::Raw => out.extend_from_slice(message),
+        OutputFormat::Prefixed => {
+            out.extend_from_slice(format!("{ts} {} ", key.label()).as_bytes());
+            out.extend_from_slice(message);
+        }
+        OutputFormat::Jsonl => {
+            let json = format!(
+                "{{\"ts\":{ts},\"src\":\"{}\",\"sport\":{},\"dst\":\"{}\",\"dport\":{},\"direction\":\"{}\",\"message\":\"{}\"}}",
+                key.src,
+                key.sport,
+                key.dst,
+                key.dport,
+                origin.direction(&opts.ports),
+                json_escape(&String::from_utf8_lossy(message))
+            );
+            out.extend_from_slice(json.as_bytes());
+        }
     }
-    Ok(())
 }
 
-fn find_message_end(buffer: &[u8], delimiter: u8) -> Option<usize> {
+fn format_epoch_micros(ts: Duration) -> String {
+    format!("{}.{:06}", ts.as_secs(), ts.subsec_micros())
+}
+
+fn json_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if u32::from(c) < 0x20 => escaped.push_str(&format!("\\u{:04x}", u32::from(c))),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn frame_message(buffer: &[u8], delimiter: u8) -> Framing {
     // Need at least "8=..|9=..|" plus checksum ("10=000|")
     if buffer.len() < 16 {
-        return None;
+        return Framing::Incomplete;
     }
-    let begin_end = buffer.iter().position(|b| *b == delimiter)?;
-    let body_len_field_start = begin_end + 1;
-    let body_len_end = body_len_field_start
-        + buffer[body_len_field_start..]
-            .iter()
-            .position(|b| *b == delimiter)?; // include delimiter
-    if body_len_end <= body_len_field_start + 1 {
-        return None;
+    if !buffer.starts_with(b"8=") {
+        return Framing::Corrupt;
     }
+    let Some(begin_end) = buffer.iter().position(|b| *b == delimiter) else {
+        return Framing::Incomplete;
+    };
+    let body_len_field_start = begin_end + 1;
+    let Some(body_len_len) = buffer[body_len_field_start..]
+        .iter()
+        .position(|b| *b == delimiter)
+    else {
+        return Framing::Incomplete;
+    };
+    let body_len_end = body_len_field_start + body_len_len; // include delimiter
     if !buffer[body_len_field_start..].starts_with(b"9=") {
-        return None;
+        return Framing::Corrupt;
     }
     let body_len_bytes = &buffer[body_len_field_start + 2..body_len_end];
-    let body_len: usize = parse_decimal(body_len_bytes)?;
+    let Some(body_len) = parse_decimal(body_len_bytes).filter(|_| !body_len_bytes.is_empty())
+    else {
+        return Framing::Corrupt;
+    };
     let body_start = body_len_end + 1;
-    let body_end = body_start.checked_add(body_len)?;
+    let Some(body_end) = body_start.checked_add(body_len) else {
+        return Framing::Corrupt;
+    };
     // checksum starts immediately after body
-    if body_end + 7 > buffer.len() {
-        return None;
-    }
-    if !buffer.get(body_end..)?.starts_with(b"10=") {
-        return None;
-    }
-    let checksum_val = buffer.get(body_end + 3..body_end + 6)?;
-    if checksum_val.iter().any(|b| !b.is_ascii_digit()) {
-        return None;
+    if body_end.saturating_add(7) > buffer.len() {
+        return Framing::Incomplete;
     }
-    let end_delim_idx = body_end + 6;
-    if *buffer.get(end_delim_idx)? != delimiter {
-        return None;
+    let checksum = &buffer[body_end..body_end + 7];
+    if checksum.starts_with(b"10=")
+        && checksum[3..6].iter().all(u8::is_ascii_digit)
+        && checksum[6] == delimiter
+    {
+        Framing::Complete(body_end + 6)
+    } else {
+        Framing::Corrupt
     }
-    Some(end_delim_idx)
 }
 
 fn parse_decimal(bytes: &[u8]) -> Option<usize> {
@@ -378,9 +904,24 @@ fn parse_decimal(bytes: &[u8]) -> Option<usize> {
     }
     Some(val)
 }
-fn evict_idle(flows: &mut HashMap<FlowKey, FlowState>, idle: Duration) {
-    let now = Instant::now();
-    flows.retain(|_, state| now.duration_since(state.last_seen) < idle);
+
+/// Remove and return flows idle for longer than `idle`, measured in capture time so
+/// offline files behave the same however fast they are read.
+fn evict_idle(
+    flows: &mut HashMap<FlowKey, FlowState>,
+    idle: Duration,
+    now: Duration,
+) -> Vec<(FlowKey, FlowState)> {
+    let mut expired: Vec<FlowKey> = flows
+        .iter()
+        .filter(|(_, state)| now.saturating_sub(state.last_seen) >= idle)
+        .map(|(key, _)| *key)
+        .collect();
+    expired.sort();
+    expired
+        .into_iter()
+        .filter_map(|key| flows.remove_entry(&key))
+        .collect()
 }
 
 #[cfg(test)]
@@ -398,12 +939,115 @@ mod tests {
         msg
     }
 
+    fn opts(delimiter: u8, format: OutputFormat) -> EmitOptions {
+        EmitOptions {
+            delimiter,
+            max_flow_bytes: 1024,
+            ports: Vec::new(),
+            format,
+        }
+    }
+
+    fn origin() -> Origin {
+        Origin {
+            ts: Duration::new(1_700_000_000, 123_456_789),
+            key: FlowKey {
+                src: Ipv4Addr::new(10, 0, 0, 1),
+                dst: Ipv4Addr::new(10, 0, 0, 2),
+                sport: 40000,
+                dport: 12083,
+            },
+        }
+    }
+
+    /// An Ethernet/IPv4/TCP frame from 10.0.0.1:`sport` to 10.0.0.2:`dport`.
+    fn tcp_frame(sport: u16, dport: u16, seq: u32, payload: &[u8]) -> Vec<u8> {
+        let builder =
+            etherparse::PacketBuilder::ethernet2([0, 1, 2, 3, 4, 5], [6, 7, 8, 9, 10, 11])
+                .ipv4([10, 0, 0, 1], [10, 0, 0, 2], 64)
+                .tcp(sport, dport, seq, 65535);
+        let mut frame = Vec::with_capacity(builder.size(payload.len()));
+        builder.write(&mut frame, payload).unwrap();
+        frame
+    }
+
+    #[test]
+    fn without_ports_only_flows_opening_with_fix_get_a_buffer() {
+        let opts = opts(b'|', OutputFormat::Raw);
+        let mut flows = HashMap::new();
+        let mut gate = FlowGate::new();
+        let mut out = Vec::new();
+        let message = build_fix_message("35=0|", b'|');
+        let (head, tail) = message.split_at(12);
+        let request = b"GET / HTTP/1.1\r\nHost: example.com\r\n\r\n";
+        let mut feed = |sport, dport, seq, payload: &[u8], flows: &mut HashMap<_, _>| {
+            let frame = tcp_frame(sport, dport, seq, payload);
+            let packet = PacketData::L2(&frame);
+            handle_packet_data(
+                packet,
+                Duration::ZERO,
+                &opts,
+                flows,
+                Some(&mut gate),
+                &mut out,
+            )
+            .unwrap();
+        };
+
+        feed(40000, 9878, 1, head, &mut flows);
+        feed(50000, 80, 1, request, &mut flows);
+        feed(50000, 80, 1 + request.len() as u32, b"8=FIX", &mut flows);
+        let fix_key = FlowKey {
+            src: Ipv4Addr::new(10, 0, 0, 1),
+            dst: Ipv4Addr::new(10, 0, 0, 2),
+            sport: 40000,
+            dport: 9878,
+        };
+        assert_eq!(flows.keys().collect::<Vec<_>>(), vec![&fix_key]);
+        assert_eq!(flows[&fix_key].buffer, head);
+
+        feed(40000, 9878, 1 + head.len() as u32, tail, &mut flows);
+        let mut expected = message.clone();
+        expected.push(b'\n');
+        assert_eq!(out, expected);
+        assert!(flows[&fix_key].buffer.is_empty());
+        assert_eq!(
+            gate.summary(),
+            "flow detection: 1 accepted as FIX, 1 rejected"
+        );
+    }
+
     #[test]
     fn parse_delimiter_variants() {
         assert_eq!(parse_delimiter("SOH").unwrap(), 0x01);
         assert_eq!(parse_delimiter("\\x02").unwrap(), 0x02);
         assert_eq!(parse_delimiter("0x03").unwrap(), 0x03);
         assert_eq!(parse_delimiter("|").unwrap(), b'|');
+        assert_eq!(parse_delimiter("TAB").unwrap(), b'\t');
+        assert_eq!(parse_delimiter("\\t").unwrap(), b'\t');
+        assert_eq!(parse_delimiter("us").unwrap(), 0x1f);
+        assert_eq!(parse_delimiter("RS").unwrap(), 0x1e);
+        assert_eq!(parse_delimiter("0x7f").unwrap(), 0x7f);
+    }
+
+    #[test]
+    fn parse_delimiter_rejects_what_fixdecoder_does_and_non_ascii() {
+        for (raw, reason) in [
+            ("", "cannot be empty"),
+            ("NUL", "NUL cannot be a delimiter"),
+            ("\\x00", "NUL cannot be a delimiter"),
+            ("\\n", "a line break cannot be a delimiter"),
+            ("||", "is 2 characters"),
+            ("e\u{0301}", "combining character"),
+            ("\u{0007}", "not printable"),
+            ("—", "'—' (U+2014) is not ASCII"),
+            ("😀", "(U+1F600) is not ASCII"),
+            ("\\xff", "(U+00FF) is not ASCII"),
+            ("0x80", "(U+0080) is not ASCII"),
+        ] {
+            let err = parse_delimiter(raw).unwrap_err().to_string();
+            assert!(err.contains(reason), "{raw:?}: {err}");
+        }
     }
 
     #[test]
@@ -414,13 +1058,21 @@ mod tests {
         let (part1, rest) = message.split_at(10);
         let (part2, part3) = rest.split_at(8);
 
-        reassemble_and_emit(&mut flow, 10, part1, 0x01, 1024, &mut out).unwrap();
+        reassemble_and_emit(
+            &mut flow,
+            10,
+            part1,
+            &opts(0x01, OutputFormat::Raw),
+            &origin(),
+            &mut out,
+        )
+        .unwrap();
         reassemble_and_emit(
             &mut flow,
             10 + part1.len() as u32,
             part2,
-            0x01,
-            1024,
+            &opts(0x01, OutputFormat::Raw),
+            &origin(),
             &mut out,
         )
         .unwrap();
@@ -429,8 +1081,8 @@ mod tests {
             &mut flow,
             10 + (part1.len() + part2.len()) as u32,
             part3,
-            0x01,
-            1024,
+            &opts(0x01, OutputFormat::Raw),
+            &origin(),
             &mut out,
         )
         .unwrap();
@@ -445,7 +1097,14 @@ mod tests {
         buf.extend_from_slice(b"extra");
         let mut out = Vec::new();
         let mut scratch = Vec::new();
-        flush_complete_messages(&mut buf, 0x01, &mut scratch, &mut out).unwrap();
+        flush_complete_messages(
+            &mut buf,
+            &opts(0x01, OutputFormat::Raw),
+            &origin(),
+            &mut scratch,
+            &mut out,
+        )
+        .unwrap();
         let mut expected = build_fix_message("35=0\u{0001}", 0x01);
         expected.push(b'\n');
         assert_eq!(out, expected);
@@ -456,8 +1115,24 @@ mod tests {
     fn retransmit_is_ignored() {
         let mut flow = FlowState::default();
         let mut out = Vec::new();
-        reassemble_and_emit(&mut flow, 1, b"ABC", b'|', 1024, &mut out).unwrap();
-        reassemble_and_emit(&mut flow, 1, b"ABC", b'|', 1024, &mut out).unwrap();
+        reassemble_and_emit(
+            &mut flow,
+            1,
+            b"ABC",
+            &opts(b'|', OutputFormat::Raw),
+            &origin(),
+            &mut out,
+        )
+        .unwrap();
+        reassemble_and_emit(
+            &mut flow,
+            1,
+            b"ABC",
+            &opts(b'|', OutputFormat::Raw),
+            &origin(),
+            &mut out,
+        )
+        .unwrap();
         assert!(flow.buffer.starts_with(b"ABC"));
     }
 
@@ -465,12 +1140,121 @@ mod tests {
     fn out_of_order_future_segment_is_skipped() {
         let mut flow = FlowState::default();
         let mut out = Vec::new();
-        reassemble_and_emit(&mut flow, 5, b"first", b'|', 1024, &mut out).unwrap();
+        reassemble_and_emit(
+            &mut flow,
+            5,
+            b"first",
+            &opts(b'|', OutputFormat::Raw),
+            &origin(),
+            &mut out,
+        )
+        .unwrap();
         // future seq skipped
-        reassemble_and_emit(&mut flow, 20, b"second", b'|', 1024, &mut out).unwrap();
+        reassemble_and_emit(
+            &mut flow,
+            20,
+            b"second",
+            &opts(b'|', OutputFormat::Raw),
+            &origin(),
+            &mut out,
+        )
+        .unwrap();
         assert_eq!(flow.buffer, b"first");
     }
 
+    /// Feed `segments` of (seq, payload) to one flow, returning it and its output.
+    fn reassemble(segments: &[(u32, &[u8])]) -> (FlowState, String) {
+        let mut flow = FlowState::default();
+        let mut out = Vec::new();
+        let opts = opts(b'|', OutputFormat::Raw);
+        for (seq, payload) in segments {
+            reassemble_and_emit(&mut flow, *seq, payload, &opts, &origin(), &mut out).unwrap();
+        }
+        (flow, String::from_utf8(out).unwrap())
+    }
+
+    #[test]
+    fn keep_alives_across_a_sequence_wrap_add_no_bytes() {
+        let first = build_fix_message("35=0|", b'|');
+        let second = build_fix_message("35=1|", b'|');
+        let start = u32::MAX - 9;
+        let (head, tail) = first.split_at(16);
+        let after_head = start.wrapping_add(16);
+        let after_first = start.wrapping_add(first.len() as u32);
+        let (flow, text) = reassemble(&[
+            (start, head),
+            (after_head.wrapping_sub(1), b"\0"),
+            (after_head.wrapping_sub(1), b""),
+            (after_head, tail),
+            (after_first.wrapping_sub(1), b"|"),
+            (after_first, &second),
+        ]);
+        let expected = format!(
+            "{}\n{}\n",
+            String::from_utf8_lossy(&first),
+            String::from_utf8_lossy(&second)
+        );
+        assert_eq!(text, expected);
+        assert_eq!(flow.stats.messages, 2);
+        assert_eq!(flow.stats.conflicts, 0);
+        assert!(flow.buffer.is_empty());
+    }
+
+    #[test]
+    fn matching_overlap_appends_only_the_new_bytes() {
+        let mut stream = build_fix_message("35=0|", b'|');
+        stream.extend_from_slice(&build_fix_message("35=1|", b'|'));
+        let (flow, text) = reassemble(&[
+            (100, &stream[..30]),
+            (110, &stream[10..45]),
+            (145, &stream[45..]),
+        ]);
+        assert_eq!(text.lines().count(), 2, "{text}");
+        assert_eq!(flow.stats.conflicts, 0);
+        assert_eq!(flow.stats.resync_bytes, 0);
+        assert!(flow.buffer.is_empty());
+    }
+
+    #[test]
+    fn conflicting_overlap_keeps_buffered_bytes_and_resyncs_on_the_next_message() {
+        let first = build_fix_message("35=D|11=ORDER1|", b'|');
+        let second = build_fix_message("35=0|", b'|');
+        let mut rewritten = first[10..30].to_vec();
+        rewritten[5] ^= 0x20;
+        let (flow, text) = reassemble(&[
+            (1, &first[..20]),
+            (11, &rewritten),
+            (31, &first[30..]),
+            (1 + first.len() as u32, &second),
+        ]);
+        let mut expected = second.clone();
+        expected.push(b'\n');
+        assert_eq!(text.as_bytes(), expected);
+        assert_eq!(flow.stats.messages, 1);
+        assert_eq!(flow.stats.conflicts, 1);
+        assert_eq!(flow.stats.resync_bytes, first.len() as u64);
+        assert!(flow.buffer.is_empty());
+    }
+
+    #[test]
+    fn broken_framing_resyncs_on_the_next_begin_string() {
+        let mut buffer = b"8=FIX.4.4|9=5|35=0|XX10=000|".to_vec();
+        let good = build_fix_message("35=0|", b'|');
+        buffer.extend_from_slice(&good);
+        let mut out = Vec::new();
+        let (messages, skipped) = flush_complete_messages(
+            &mut buffer,
+            &opts(b'|', OutputFormat::Raw),
+            &origin(),
+            &mut Vec::new(),
+            &mut out,
+        )
+        .unwrap();
+        assert_eq!((messages, skipped), (1, 28));
+        assert!(buffer.is_empty());
+        assert_eq!(resynchronise(&mut b"junk8=FI".to_vec()), 4);
+    }
+
     #[test]
     fn flush_complete_messages_emits_and_retains_tail() {
         let mut buf = Vec::new();
@@ -481,7 +1265,14 @@ mod tests {
         buf.extend_from_slice(b"partial");
         let mut scratch = Vec::new();
         let mut out = Vec::new();
-        flush_complete_messages(&mut buf, b'|', &mut scratch, &mut out).unwrap();
+        flush_complete_messages(
+            &mut buf,
+            &opts(b'|', OutputFormat::Raw),
+            &origin(),
+            &mut scratch,
+            &mut out,
+        )
+        .unwrap();
         let expected_out = {
             let mut v = msg1.clone();
             v.push(b'\n');
@@ -492,4 +1283,93 @@ mod tests {
         assert_eq!(out, expected_out);
         assert_eq!(buf, b"partial");
     }
+
+    #[test]
+    fn prefixed_format_carries_timestamp_and_flow() {
+        let msg = build_fix_message("35=0|", b'|');
+        let mut out = Vec::new();
+        format_message(
+            &msg,
+            &opts(b'|', OutputFormat::Prefixed),
+            &origin(),
+            &mut out,
+        );
+        let text = String::from_utf8(out).unwrap();
+        assert!(
+            text.starts_with("1700000000.123456 10.0.0.1:40000 -> 10.0.0.2:12083 8=FIX.4.4|"),
+            "{text}"
+        );
+    }
+
+    #[test]
+    fn jsonl_format_escapes_message_and_reports_direction() {
+        let msg = build_fix_message("35=0\u{0001}58=say \"hi\"\u{0001}", 0x01);
+        let mut out = Vec::new();
+        format_message(&msg, &opts(0x01, OutputFormat::Jsonl), &origin(), &mut out);
+        let text = String::from_utf8(out).unwrap();
+        assert!(text.starts_with(
+            "{\"ts\":1700000000.123456,\"src\":\"10.0.0.1\",\"sport\":40000,\"dst\":\"10.0.0.2\",\"dport\":12083,\"direction\":\"inbound\",\"message\":\"8=FIX.4.4\\u00019="
+        ), "{text}");
+        assert!(text.contains("58=say \\\"hi\\\"\\u0001"), "{text}");
+        assert!(text.ends_with("\"}"), "{text}");
+
+        let mut reply = origin();
+        std::mem::swap(&mut reply.key.src, &mut reply.key.dst);
+        std::mem::swap(&mut reply.key.sport, &mut reply.key.dport);
+        assert_eq!(reply.direction(&[]), "outbound");
+        assert_eq!(reply.direction(&[40000]), "inbound");
+        assert_eq!(reply.direction(&[9878, 40000]), "inbound");
+    }
+
+    #[test]
+    fn idle_eviction_uses_capture_time() {
+        let mut flows = HashMap::new();
+        let key = origin().key;
+        flows.insert(
+            key,
+            FlowState {
+                last_seen: Duration::from_secs(100),
+                ..FlowState::default()
+            },
+        );
+        let evicted = evict_idle(
+            &mut flows,
+            Duration::from_secs(60),
+            Duration::from_secs(159),
+        );
+        assert!(evicted.is_empty());
+        assert!(flows.contains_key(&key));
+        let evicted = evict_idle(
+            &mut flows,
+            Duration::from_secs(60),
+            Duration::from_secs(160),
+        );
+        assert_eq!(evicted.len(), 1);
+        assert_eq!(evicted[0].0, key);
+        assert!(flows.is_empty());
+    }
+
+    #[test]
+    fn flow_summary_reports_messages_and_gap_bytes() {
+        let mut flow = FlowState::default();
+        let mut out = Vec::new();
+        let message = build_fix_message("35=0|", b'|');
+        let opts = opts(b'|', OutputFormat::Raw);
+        reassemble_and_emit(&mut flow, 1, &message, &opts, &origin(), &mut out).unwrap();
+        let next = 1 + message.len() as u32;
+        reassemble_and_emit(&mut flow, next + 10, b"lost", &opts, &origin(), &mut out).unwrap();
+        reassemble_and_emit(&mut flow, next, b"8=FIX", &opts, &origin(), &mut out).unwrap();
+        flow.stats.packets = 3;
+        flow.stats.bytes = (message.len() + 9) as u64;
+
+        assert_eq!(flow.stats.messages, 1);
+        assert_eq!(flow.stats.gap_bytes, 4);
+        assert_eq!(
+            flow_summary(&origin().key, &flow, Teardown::Fin),
+            format!(
+                "flow 10.0.0.1:40000 -> 10.0.0.2:12083 closed (FIN): 3 packets, {} bytes, 1 messages, 4 bytes discarded in gaps, 5 bytes incomplete",
+                message.len() + 9
+            )
+        );
+    }
 }
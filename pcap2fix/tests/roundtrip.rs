@@ -1,4 +1,5 @@
 use assert_cmd::Command;
+use predicates::prelude::PredicateBooleanExt;
 
 /// Build a minimal FIX message with correct BodyLength/Checksum using the given delimiter.
 fn build_fix_message(delim: u8) -> Vec<u8> {
@@ -11,8 +12,15 @@ fn build_fix_message(delim: u8) -> Vec<u8> {
     msg
 }
 
-/// Construct a tiny PCAP (classic) containing one Ethernet/IPv4/TCP packet with the FIX payload.
-fn build_pcap(payload: &[u8]) -> Vec<u8> {
+/// Construct a tiny PCAP (classic) containing one Ethernet/IPv4/TCP packet with the FIX payload,
+/// captured at `ts_sec`.`ts_usec`.
+fn build_pcap(payload: &[u8], ts_sec: u32, ts_usec: u32) -> Vec<u8> {
+    let mut buf = pcap_header();
+    buf.extend_from_slice(&packet_record(payload, ts_sec, ts_usec, 1, 0x18));
+    buf
+}
+
+fn pcap_header() -> Vec<u8> {
     let mut buf = Vec::new();
 
     // PCAP global header (little-endian, Ethernet linktype)
@@ -23,6 +31,13 @@ fn build_pcap(payload: &[u8]) -> Vec<u8> {
     buf.extend_from_slice(&0u32.to_le_bytes()); // sigfigs
     buf.extend_from_slice(&65535u32.to_le_bytes()); // snaplen
     buf.extend_from_slice(&1u32.to_le_bytes()); // network = Ethernet
+    buf
+}
+
+/// One classic PCAP record: a 10.0.0.1:40000 -> 10.0.0.2:12083 segment with the given
+/// sequence number and TCP flags.
+fn packet_record(payload: &[u8], ts_sec: u32, ts_usec: u32, seq: u32, flags: u8) -> Vec<u8> {
+    let mut buf = Vec::new();
 
     // Build packet bytes
     let mut pkt = Vec::new();
@@ -48,9 +63,9 @@ fn build_pcap(payload: &[u8]) -> Vec<u8> {
     let dst_port: u16 = 12083;
     pkt.extend_from_slice(&src_port.to_be_bytes());
     pkt.extend_from_slice(&dst_port.to_be_bytes());
-    pkt.extend_from_slice(&1u32.to_be_bytes()); // seq
+    pkt.extend_from_slice(&seq.to_be_bytes()); // seq
     pkt.extend_from_slice(&0u32.to_be_bytes()); // ack
-    pkt.extend_from_slice(&[0x50, 0x18]); // data offset=5, flags=PSH+ACK
+    pkt.extend_from_slice(&[0x50, flags]); // data offset=5, flags
     pkt.extend_from_slice(&0xffffu16.to_be_bytes()); // window
     pkt.extend_from_slice(&[0x00, 0x00]); // checksum (omitted)
     pkt.extend_from_slice(&[0x00, 0x00]); // urgent ptr
@@ -59,8 +74,8 @@ fn build_pcap(payload: &[u8]) -> Vec<u8> {
 
     // PCAP packet header
     let pkt_len = pkt.len() as u32;
-    buf.extend_from_slice(&0u32.to_le_bytes()); // ts_sec
-    buf.extend_from_slice(&0u32.to_le_bytes()); // ts_usec
+    buf.extend_from_slice(&ts_sec.to_le_bytes()); // ts_sec
+    buf.extend_from_slice(&ts_usec.to_le_bytes()); // ts_usec
     buf.extend_from_slice(&pkt_len.to_le_bytes()); // incl_len
     buf.extend_from_slice(&pkt_len.to_le_bytes()); // orig_len
 
@@ -72,7 +87,7 @@ fn build_pcap(payload: &[u8]) -> Vec<u8> {
 fn pcap_roundtrip_matches_expected_output() {
     let delim = 0x01;
     let msg = build_fix_message(delim);
-    let pcap_bytes = build_pcap(&msg);
+    let pcap_bytes = build_pcap(&msg, 0, 0);
     let expected_output = {
         let mut v = msg.clone();
         v.push(b'\n');
@@ -87,3 +102,211 @@ fn pcap_roundtrip_matches_expected_output() {
         .success()
         .stdout(expected_output);
 }
+
+#[test]
+fn ports_accepts_a_list_and_skips_content_detection() {
+    let msg = build_fix_message(0x01);
+    let bin = assert_cmd::cargo::cargo_bin!("pcap2fix");
+    for (ports, expected) in [("9878,12083", &msg[..]), ("9878,9879", &b""[..])] {
+        let mut output = expected.to_vec();
+        if !output.is_empty() {
+            output.push(b'\n');
+        }
+        Command::new(bin)
+            .args(["--ports", ports])
+            .write_stdin(build_pcap(&msg, 0, 0))
+            .assert()
+            .success()
+            .stdout(output)
+            .stderr(predicates::str::contains("flow detection").not());
+    }
+}
+
+#[test]
+fn prefixed_and_jsonl_formats_carry_capture_time_and_flow() {
+    let msg = build_fix_message(b'|');
+    let pcap_bytes = build_pcap(&msg, 1_700_000_000, 42);
+    let text = String::from_utf8(msg).unwrap();
+    let bin = assert_cmd::cargo::cargo_bin!("pcap2fix");
+
+    Command::new(bin)
+        .args(["--delimiter", "|", "--format", "prefixed"])
+        .write_stdin(pcap_bytes.clone())
+        .assert()
+        .success()
+        .stdout(format!(
+            "1700000000.000042 10.0.0.1:40000 -> 10.0.0.2:12083 {text}\n"
+        ));
+
+    Command::new(bin)
+        .args(["--delimiter", "|", "--format", "jsonl", "--port", "12083"])
+        .write_stdin(pcap_bytes)
+        .assert()
+        .success()
+        .stdout(format!(
+            "{{\"ts\":1700000000.000042,\"src\":\"10.0.0.1\",\"sport\":40000,\"dst\":\"10.0.0.2\",\"dport\":12083,\"direction\":\"inbound\",\"message\":\"{text}\"}}\n"
+        ));
+}
+
+#[test]
+fn decode_prettifies_and_validates_in_process() {
+    let pcap_bytes = build_pcap(&build_fix_message(0x01), 0, 0);
+    let bin = assert_cmd::cargo::cargo_bin!("pcap2fix");
+
+    let output = Command::new(bin)
+        .args(["--decode", "--fix", "42"])
+        .write_stdin(pcap_bytes.clone())
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let text = String::from_utf8(output).unwrap();
+    assert!(text.contains("35 (MsgType):     0 (HEARTBEAT)"), "{text}");
+    assert!(!text.contains('\u{1b}'), "colour is off when piped: {text}");
+
+    let output = Command::new(bin)
+        .args(["--decode", "--validate"])
+        .write_stdin(pcap_bytes)
+        .assert()
+        .code(2)
+        .get_output()
+        .stdout
+        .clone();
+    let text = String::from_utf8(output).unwrap();
+    assert!(text.contains("Line 1:"), "{text}");
+    assert!(text.contains("Validated 1 message(s): 1 invalid"), "{text}");
+}
+
+#[test]
+fn output_dir_writes_per_flow_files_and_fin_closes_the_flow() {
+    let msg = build_fix_message(0x01);
+    let mut pcap_bytes = pcap_header();
+    pcap_bytes.extend_from_slice(&packet_record(&msg, 0, 0, 1, 0x18)); // PSH+ACK
+    pcap_bytes.extend_from_slice(&packet_record(&[], 0, 1, 1 + msg.len() as u32, 0x11)); // FIN+ACK
+    let dir = tempfile::tempdir().unwrap();
+    let bin = assert_cmd::cargo::cargo_bin!("pcap2fix");
+
+    let output = Command::new(bin)
+        .arg("--output-dir")
+        .arg(dir.path())
+        .write_stdin(pcap_bytes)
+        .assert()
+        .success()
+        .stdout("")
+        .get_output()
+        .stderr
+        .clone();
+    let stderr = String::from_utf8(output).unwrap();
+    assert_eq!(
+        stderr,
+        format!(
+            "flow 10.0.0.1:40000 -> 10.0.0.2:12083 closed (FIN): 2 packets, {} bytes, 1 messages, 0 bytes discarded in gaps\nflow detection: 1 accepted as FIX, 0 rejected\n",
+            msg.len()
+        )
+    );
+
+    let mut expected = msg.clone();
+    expected.push(b'\n');
+    let written = std::fs::read(dir.path().join("10.0.0.1_40000-10.0.0.2_12083.fix")).unwrap();
+    assert_eq!(written, expected);
+}
+
+/// Send Ctrl+C to a `--follow` run and collect what it printed.
+#[cfg(unix)]
+fn interrupt(child: std::process::Child) -> std::process::Output {
+    let status = std::process::Command::new("kill")
+        .args(["-INT", &child.id().to_string()])
+        .status()
+        .unwrap();
+    assert!(status.success());
+    child.wait_with_output().unwrap()
+}
+
+#[cfg(unix)]
+fn spawn_follow(input: &std::path::Path) -> std::process::Child {
+    std::process::Command::new(assert_cmd::cargo::cargo_bin!("pcap2fix"))
+        .arg("--follow")
+        .arg("--input")
+        .arg(input)
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        .spawn()
+        .unwrap()
+}
+
+#[test]
+#[cfg(unix)]
+fn follow_reads_appended_packets_and_reopens_truncated_files() {
+    use std::io::Write;
+    use std::time::Duration;
+
+    let msg = build_fix_message(0x01);
+    let next_seq = 1 + msg.len() as u32;
+    let capture = tempfile::NamedTempFile::new().unwrap();
+    let mut initial = pcap_header();
+    initial.extend_from_slice(&packet_record(&msg, 0, 0, 1, 0x18));
+    std::fs::write(capture.path(), &initial).unwrap();
+
+    let child = spawn_follow(capture.path());
+    std::thread::sleep(Duration::from_millis(600));
+    let mut file = std::fs::OpenOptions::new()
+        .append(true)
+        .open(capture.path())
+        .unwrap();
+    file.write_all(&packet_record(&msg, 0, 1, next_seq, 0x18))
+        .unwrap();
+    drop(file);
+    std::thread::sleep(Duration::from_millis(600));
+    let mut rotated = pcap_header();
+    rotated.extend_from_slice(&packet_record(&msg, 0, 2, 2 * next_seq - 1, 0x18));
+    std::fs::write(capture.path(), &rotated).unwrap();
+    std::thread::sleep(Duration::from_millis(600));
+
+    let output = interrupt(child);
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(stdout.lines().count(), 3, "{stdout}");
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.contains("was truncated; reading it again"),
+        "{stderr}"
+    );
+    assert!(
+        stderr.contains("closed (interrupted): 3 packets"),
+        "{stderr}"
+    );
+}
+
+#[test]
+#[cfg(unix)]
+fn follow_waits_on_named_pipes_without_treating_them_as_truncated() {
+    use std::io::Write;
+    use std::time::Duration;
+
+    let dir = tempfile::tempdir().unwrap();
+    let fifo = dir.path().join("capture.fifo");
+    let made = std::process::Command::new("mkfifo")
+        .arg(&fifo)
+        .status()
+        .unwrap();
+    assert!(made.success());
+
+    let child = spawn_follow(&fifo);
+    let mut writer = std::fs::OpenOptions::new().write(true).open(&fifo).unwrap();
+    writer
+        .write_all(&build_pcap(&build_fix_message(0x01), 0, 0))
+        .unwrap();
+    drop(writer);
+    std::thread::sleep(Duration::from_millis(600));
+
+    let output = interrupt(child);
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8(output.stdout).unwrap().lines().count(), 1);
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(!stderr.contains("truncated"), "{stderr}");
+    assert!(
+        stderr.contains("closed (interrupted): 1 packets"),
+        "{stderr}"
+    );
+}
@@ -0,0 +1,244 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// SPDX-FileCopyrightText: 2025 Steve Clarke <stephenlclarke@mac.com> - https://xyzzy.tools
+
+//! `--benchmark`: throughput of the decode pipeline, with the decoded output thrown
+//! away.  Time is split coarsely between the phases of each line: finding messages,
+//! parsing them, looking up their dictionary, validating and rendering.  Without the
+//! flag no [`Benchmark`] exists and a [`Lap`] never reads the clock, so the timing
+//! costs one `Option` check per phase.
+
+use std::io::{self, Write};
+use std::time::{Duration, Instant};
+
+/// A part of the decode pipeline whose time is reported separately.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    /// Finding the FIX messages in a line and applying `--only-msgtype`.
+    Extract,
+    /// Splitting a message into its fields.
+    Parse,
+    /// Choosing and loading the message's dictionary.
+    Lookup,
+    /// `--validate`.
+    Validate,
+    /// Writing the decoded tree, banner and errors.
+    Render,
+}
+
+impl Phase {
+    const ALL: [Phase; 5] = [
+        Phase::Extract,
+        Phase::Parse,
+        Phase::Lookup,
+        Phase::Validate,
+        Phase::Render,
+    ];
+
+    fn label(self) -> &'static str {
+        match self {
+            Phase::Extract => "Extraction",
+            Phase::Parse => "Parsing",
+            Phase::Lookup => "Dictionary lookup",
+            Phase::Validate => "Validation",
+            Phase::Render => "Rendering",
+        }
+    }
+
+    /// The key of the phase's share in the report line.
+    fn key(self) -> &'static str {
+        match self {
+            Phase::Extract => "extract",
+            Phase::Parse => "parse",
+            Phase::Lookup => "lookup",
+            Phase::Validate => "validate",
+            Phase::Render => "render",
+        }
+    }
+}
+
+/// Input read and time spent per [`Phase`] since the run started.
+#[derive(Debug)]
+pub struct Benchmark {
+    started: Instant,
+    bytes: u64,
+    spent: [Duration; Phase::ALL.len()],
+}
+
+impl Benchmark {
+    /// Start the wall clock.
+    pub fn start() -> Self {
+        Self {
+            started: Instant::now(),
+            bytes: 0,
+            spent: [Duration::ZERO; Phase::ALL.len()],
+        }
+    }
+
+    /// Count `bytes` of input read.
+    pub fn add_input(&mut self, bytes: usize) {
+        self.bytes += bytes as u64;
+    }
+
+    fn add(&mut self, phase: Phase, elapsed: Duration) {
+        self.spent[phase as usize] += elapsed;
+    }
+
+    /// Print the report for a run that decoded `messages` messages, ending with one
+    /// `benchmark:` line of `key=value` pairs for scripts to compare.
+    pub fn render(&self, messages: usize, out: &mut dyn Write) -> io::Result<()> {
+        self.render_for(self.started.elapsed(), messages, peak_rss_kb(), out)
+    }
+
+    fn render_for(
+        &self,
+        wall: Duration,
+        messages: usize,
+        peak_rss_kb: Option<u64>,
+        out: &mut dyn Write,
+    ) -> io::Result<()> {
+        let secs = wall.as_secs_f64();
+        let per_sec = |amount: f64| if secs > 0.0 { amount / secs } else { 0.0 };
+        let mb = self.bytes as f64 / 1_000_000.0;
+        let share = |spent: Duration| {
+            if secs > 0.0 {
+                100.0 * spent.as_secs_f64() / secs
+            } else {
+                0.0
+            }
+        };
+        let other = wall.saturating_sub(self.spent.iter().sum());
+
+        writeln!(out, "Benchmark:")?;
+        writeln!(out, "  Wall time:          {secs:.3}s")?;
+        writeln!(
+            out,
+            "  Messages:           {messages} ({:.0} msg/s)",
+            per_sec(messages as f64)
+        )?;
+        writeln!(
+            out,
+            "  Input:              {mb:.2} MB ({:.2} MB/s)",
+            per_sec(mb)
+        )?;
+        for phase in Phase::ALL {
+            let spent = self.spent[phase as usize];
+            writeln!(
+                out,
+                "  {:<19} {:>5.1}%  {:.3}s",
+                format!("{}:", phase.label()),
+                share(spent),
+                spent.as_secs_f64()
+            )?;
+        }
+        writeln!(
+            out,
+            "  {:<19} {:>5.1}%  {:.3}s",
+            "Other:",
+            share(other),
+            other.as_secs_f64()
+        )?;
+        match peak_rss_kb {
+            Some(kb) => writeln!(out, "  Peak RSS:           {:.1} MB", kb as f64 / 1024.0)?,
+            None => writeln!(out, "  Peak RSS:           n/a")?,
+        }
+
+        write!(
+            out,
+            "benchmark: wall_s={secs:.6} messages={messages} bytes={} msgs_per_s={:.1} mb_per_s={:.3}",
+            self.bytes,
+            per_sec(messages as f64),
+            per_sec(mb)
+        )?;
+        for phase in Phase::ALL {
+            write!(
+                out,
+                " {}_pct={:.1}",
+                phase.key(),
+                share(self.spent[phase as usize])
+            )?;
+        }
+        write!(out, " other_pct={:.1}", share(other))?;
+        match peak_rss_kb {
+            Some(kb) => writeln!(out, " peak_rss_kb={kb}"),
+            None => writeln!(out, " peak_rss_kb=n/a"),
+        }
+    }
+}
+
+/// The start of one phase's work, timed only when benchmarking.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Lap(Option<Instant>);
+
+impl Lap {
+    /// Read the clock if `bench` is running.
+    pub(crate) fn start(bench: &Option<Benchmark>) -> Self {
+        Self(bench.as_ref().map(|_| Instant::now()))
+    }
+
+    /// Charge the time since [`Lap::start`] to `phase`.
+    pub(crate) fn stop(self, bench: &mut Option<Benchmark>, phase: Phase) {
+        if let (Some(started), Some(bench)) = (self.0, bench.as_mut()) {
+            bench.add(phase, started.elapsed());
+        }
+    }
+}
+
+/// The process's peak resident set size, where `/proc` reports it.
+fn peak_rss_kb() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    status
+        .lines()
+        .find_map(|line| line.strip_prefix("VmHWM:"))
+        .and_then(|rest| rest.trim().trim_end_matches("kB").trim().parse().ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn report_ends_with_one_line_of_key_values() {
+        let mut bench = Benchmark::start();
+        bench.add_input(2_000_000);
+        bench.add(Phase::Extract, Duration::from_millis(250));
+        bench.add(Phase::Render, Duration::from_millis(500));
+        let mut out = Vec::new();
+        bench
+            .render_for(Duration::from_secs(1), 4000, Some(2048), &mut out)
+            .unwrap();
+        let text = String::from_utf8(out).unwrap();
+        assert!(
+            text.contains("Messages:           4000 (4000 msg/s)"),
+            "{text}"
+        );
+        assert!(text.contains("Peak RSS:           2.0 MB"), "{text}");
+
+        let line = text.lines().last().unwrap();
+        assert_eq!(
+            line,
+            "benchmark: wall_s=1.000000 messages=4000 bytes=2000000 msgs_per_s=4000.0 \
+             mb_per_s=2.000 extract_pct=25.0 parse_pct=0.0 lookup_pct=0.0 validate_pct=0.0 \
+             render_pct=50.0 other_pct=25.0 peak_rss_kb=2048"
+        );
+    }
+
+    #[test]
+    fn laps_cost_nothing_without_a_benchmark() {
+        let mut off: Option<Benchmark> = None;
+        let lap = Lap::start(&off);
+        assert!(lap.0.is_none());
+        lap.stop(&mut off, Phase::Parse);
+
+        let mut on = Some(Benchmark::start());
+        let lap = Lap::start(&on);
+        assert!(lap.0.is_some());
+        lap.stop(&mut on, Phase::Parse);
+    }
+
+    #[test]
+    fn peak_rss_is_read_where_proc_exists() {
+        if std::path::Path::new("/proc/self/status").exists() {
+            assert!(peak_rss_kb().is_some_and(|kb| kb > 0));
+        }
+    }
+}
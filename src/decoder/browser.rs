@@ -0,0 +1,643 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// SPDX-FileCopyrightText: 2025 Steve Clarke <stephenlclarke@mac.com> - https://xyzzy.tools
+
+//! `--browse`: an interactive three-pane view of the active dictionary.  The left pane
+//! lists messages (type to filter), the middle pane shows the selected message as a
+//! tree of fields, components and groups, and the right pane describes the selected
+//! node.  Everything is read from the [`SchemaTree`] already built for `--message`.
+
+use crate::decoder::schema::{ComponentNode, Field, FieldNode, GroupNode, MessageNode, SchemaTree};
+use anyhow::{Result, bail};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, List, ListItem, ListState, Paragraph, Wrap};
+use ratatui::{DefaultTerminal, Frame};
+use std::collections::HashSet;
+use std::io::{self, IsTerminal};
+
+const HELP: &str = " type to filter · ↑↓ move · ←→ collapse/expand · Tab switch pane · q/Esc quit ";
+
+/// Run the browser until the user quits, restoring the terminal on the way out.
+pub fn run(schema: &SchemaTree) -> Result<()> {
+    if !io::stdout().is_terminal() {
+        bail!(
+            "--browse needs an interactive terminal, but stdout is not a TTY; \
+             use --message NAME --verbose to print a message's structure instead"
+        );
+    }
+    let mut terminal = ratatui::try_init()?;
+    let result = Browser::new(schema).event_loop(&mut terminal);
+    ratatui::try_restore()?;
+    result
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Pane {
+    Messages,
+    Tree,
+}
+
+/// One node of a message's structure.
+#[derive(Debug, Clone, Copy)]
+enum Node<'a> {
+    Field(&'a FieldNode),
+    Component(&'a ComponentNode),
+    Group(&'a GroupNode),
+}
+
+/// A visible line of the structure tree.  `key` is the node's path from the message
+/// root, which is what the expanded set remembers.
+#[derive(Debug, Clone)]
+struct TreeRow<'a> {
+    depth: usize,
+    node: Node<'a>,
+    key: String,
+}
+
+impl TreeRow<'_> {
+    fn expandable(&self) -> bool {
+        !matches!(self.node, Node::Field(_))
+    }
+}
+
+struct Browser<'a> {
+    schema: &'a SchemaTree,
+    focus: Pane,
+    filter: String,
+    matches: Vec<&'a MessageNode>,
+    selected_message: usize,
+    expanded: HashSet<String>,
+    rows: Vec<TreeRow<'a>>,
+    selected_row: usize,
+}
+
+impl<'a> Browser<'a> {
+    fn new(schema: &'a SchemaTree) -> Self {
+        let mut browser = Self {
+            schema,
+            focus: Pane::Messages,
+            filter: String::new(),
+            matches: Vec::new(),
+            selected_message: 0,
+            expanded: HashSet::new(),
+            rows: Vec::new(),
+            selected_row: 0,
+        };
+        browser.apply_filter();
+        browser
+    }
+
+    fn event_loop(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+        loop {
+            terminal.draw(|frame| self.draw(frame))?;
+            if let Event::Key(key) = event::read()?
+                && key.kind == KeyEventKind::Press
+                && self.handle_key(key)
+            {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Apply a key press; returns `true` when the user asked to quit.
+    fn handle_key(&mut self, key: KeyEvent) -> bool {
+        if key.modifiers.contains(KeyModifiers::CONTROL) {
+            return matches!(key.code, KeyCode::Char('c') | KeyCode::Char('d'));
+        }
+        match (self.focus, key.code) {
+            (_, KeyCode::Tab | KeyCode::BackTab) => self.toggle_focus(),
+            (Pane::Messages, KeyCode::Esc) if !self.filter.is_empty() => {
+                self.filter.clear();
+                self.apply_filter();
+            }
+            (_, KeyCode::Esc) => return true,
+            (Pane::Messages, KeyCode::Char('q')) if self.filter.is_empty() => return true,
+            (Pane::Tree, KeyCode::Char('q')) => return true,
+            (Pane::Messages, KeyCode::Char(ch)) => {
+                self.filter.push(ch);
+                self.apply_filter();
+            }
+            (Pane::Messages, KeyCode::Backspace) => {
+                self.filter.pop();
+                self.apply_filter();
+            }
+            (Pane::Messages, KeyCode::Up) => {
+                self.select_message(self.selected_message.saturating_sub(1))
+            }
+            (Pane::Messages, KeyCode::Down) => self.select_message(self.selected_message + 1),
+            (Pane::Messages, KeyCode::Right | KeyCode::Enter) if !self.rows.is_empty() => {
+                self.focus = Pane::Tree;
+            }
+            (Pane::Tree, KeyCode::Up) => self.selected_row = self.selected_row.saturating_sub(1),
+            (Pane::Tree, KeyCode::Down) => {
+                self.selected_row = (self.selected_row + 1).min(self.rows.len().saturating_sub(1));
+            }
+            (Pane::Tree, KeyCode::Right) => self.expand_selected(),
+            (Pane::Tree, KeyCode::Left) => self.collapse_selected(),
+            (Pane::Tree, KeyCode::Enter | KeyCode::Char(' ')) => self.toggle_selected(),
+            _ => {}
+        }
+        false
+    }
+
+    fn toggle_focus(&mut self) {
+        self.focus = match self.focus {
+            Pane::Messages if !self.rows.is_empty() => Pane::Tree,
+            _ => Pane::Messages,
+        };
+    }
+
+    /// Keep the messages whose name or MsgType contains the filter, ignoring case.
+    fn apply_filter(&mut self) {
+        let needle = self.filter.to_ascii_lowercase();
+        self.matches = self
+            .schema
+            .messages
+            .values()
+            .filter(|msg| {
+                msg.name.to_ascii_lowercase().contains(&needle)
+                    || msg.msg_type.to_ascii_lowercase() == needle
+            })
+            .collect();
+        self.select_message(0);
+    }
+
+    fn select_message(&mut self, index: usize) {
+        self.selected_message = index.min(self.matches.len().saturating_sub(1));
+        self.expanded.clear();
+        self.selected_row = 0;
+        self.rebuild_tree();
+    }
+
+    fn current_message(&self) -> Option<&'a MessageNode> {
+        self.matches.get(self.selected_message).copied()
+    }
+
+    fn current_row(&self) -> Option<&TreeRow<'a>> {
+        self.rows.get(self.selected_row)
+    }
+
+    /// Key and depth of the selected row when it is a component or group.
+    fn selected_expandable(&self) -> Option<(String, usize)> {
+        self.current_row()
+            .filter(|row| row.expandable())
+            .map(|row| (row.key.clone(), row.depth))
+    }
+
+    /// Flatten the selected message into visible rows: the header, the body and the
+    /// trailer, descending only into expanded components and groups.
+    fn rebuild_tree(&mut self) {
+        let mut rows = Vec::new();
+        if let Some(msg) = self.current_message() {
+            if let Some(header) = self.schema.components.get("Header") {
+                self.push_node(&mut rows, "", 0, Node::Component(header));
+            }
+            self.push_children(&mut rows, "", 0, &msg.fields, &msg.components, &msg.groups);
+            if let Some(trailer) = self.schema.components.get("Trailer") {
+                self.push_node(&mut rows, "", 0, Node::Component(trailer));
+            }
+        }
+        self.rows = rows;
+        self.selected_row = self.selected_row.min(self.rows.len().saturating_sub(1));
+    }
+
+    fn push_children(
+        &self,
+        rows: &mut Vec<TreeRow<'a>>,
+        parent: &str,
+        depth: usize,
+        fields: &'a [FieldNode],
+        components: &'a [ComponentNode],
+        groups: &'a [GroupNode],
+    ) {
+        for field in fields {
+            self.push_node(rows, parent, depth, Node::Field(field));
+        }
+        for component in components {
+            self.push_node(rows, parent, depth, Node::Component(component));
+        }
+        for group in groups {
+            self.push_node(rows, parent, depth, Node::Group(group));
+        }
+    }
+
+    fn push_node(&self, rows: &mut Vec<TreeRow<'a>>, parent: &str, depth: usize, node: Node<'a>) {
+        let key = format!("{parent}/{}", node_name(node));
+        let expanded = self.expanded.contains(&key);
+        rows.push(TreeRow {
+            depth,
+            node,
+            key: key.clone(),
+        });
+        if !expanded {
+            return;
+        }
+        match node {
+            Node::Field(_) => {}
+            Node::Component(comp) => self.push_children(
+                rows,
+                &key,
+                depth + 1,
+                &comp.fields,
+                &comp.components,
+                &comp.groups,
+            ),
+            Node::Group(group) => self.push_children(
+                rows,
+                &key,
+                depth + 1,
+                &group.fields,
+                &group.components,
+                &group.groups,
+            ),
+        }
+    }
+
+    /// Expand a collapsed node, or step onto the first child of an expanded one.
+    fn expand_selected(&mut self) {
+        let Some((key, depth)) = self.selected_expandable() else {
+            return;
+        };
+        if self.expanded.insert(key) {
+            self.rebuild_tree();
+        } else if self
+            .rows
+            .get(self.selected_row + 1)
+            .is_some_and(|next| next.depth > depth)
+        {
+            self.selected_row += 1;
+        }
+    }
+
+    /// Collapse an expanded node, or step up to the parent of anything else.
+    fn collapse_selected(&mut self) {
+        let Some(row) = self.current_row() else {
+            return;
+        };
+        let (key, depth) = (row.key.clone(), row.depth);
+        if self.expanded.remove(&key) {
+            self.rebuild_tree();
+        } else if let Some(parent) = self.rows[..self.selected_row]
+            .iter()
+            .rposition(|candidate| candidate.depth < depth)
+        {
+            self.selected_row = parent;
+        }
+    }
+
+    fn toggle_selected(&mut self) {
+        let Some((key, _)) = self.selected_expandable() else {
+            return;
+        };
+        if !self.expanded.remove(&key) {
+            self.expanded.insert(key);
+        }
+        self.rebuild_tree();
+    }
+
+    fn draw(&self, frame: &mut Frame) {
+        let [body, footer] =
+            Layout::vertical([Constraint::Min(3), Constraint::Length(1)]).areas(frame.area());
+        let [messages, tree, details] = Layout::horizontal([
+            Constraint::Percentage(28),
+            Constraint::Percentage(40),
+            Constraint::Percentage(32),
+        ])
+        .areas(body);
+
+        self.draw_messages(frame, messages);
+        self.draw_tree(frame, tree);
+        let text: Vec<Line> = self.detail_lines().into_iter().map(Line::from).collect();
+        frame.render_widget(
+            Paragraph::new(text)
+                .block(Block::bordered().title(" Details "))
+                .wrap(Wrap { trim: false }),
+            details,
+        );
+        frame.render_widget(
+            Line::from(HELP).style(Style::new().add_modifier(Modifier::DIM)),
+            footer,
+        );
+    }
+
+    fn draw_messages(&self, frame: &mut Frame, area: Rect) {
+        let items: Vec<ListItem> = self
+            .matches
+            .iter()
+            .map(|msg| ListItem::new(format!("{:<4} {}", msg.msg_type, msg.name)))
+            .collect();
+        let title = if self.filter.is_empty() {
+            format!(" Messages ({}) ", self.matches.len())
+        } else {
+            format!(" Messages ({}) /{} ", self.matches.len(), self.filter)
+        };
+        let mut state = ListState::default()
+            .with_selected((!self.matches.is_empty()).then_some(self.selected_message));
+        frame.render_stateful_widget(
+            List::new(items)
+                .block(pane_block(title, self.focus == Pane::Messages))
+                .highlight_style(highlight()),
+            area,
+            &mut state,
+        );
+    }
+
+    fn draw_tree(&self, frame: &mut Frame, area: Rect) {
+        let items: Vec<ListItem> = self
+            .rows
+            .iter()
+            .map(|row| {
+                let marker = match (row.expandable(), self.expanded.contains(&row.key)) {
+                    (false, _) => "  ",
+                    (true, false) => "▸ ",
+                    (true, true) => "▾ ",
+                };
+                ListItem::new(format!(
+                    "{}{marker}{}",
+                    "  ".repeat(row.depth),
+                    row_label(row.node)
+                ))
+            })
+            .collect();
+        let title = self
+            .current_message()
+            .map(|msg| format!(" {} ({}) ", msg.name, msg.msg_type))
+            .unwrap_or_else(|| " Structure ".to_string());
+        let mut state = ListState::default()
+            .with_selected((!self.rows.is_empty()).then_some(self.selected_row));
+        frame.render_stateful_widget(
+            List::new(items)
+                .block(pane_block(title, self.focus == Pane::Tree))
+                .highlight_style(highlight()),
+            area,
+            &mut state,
+        );
+    }
+
+    /// Text for the details pane: the selected tree node, or the message itself while
+    /// the message list has focus.
+    fn detail_lines(&self) -> Vec<String> {
+        let Some(msg) = self.current_message() else {
+            return vec!["No message matches the filter".to_string()];
+        };
+        match (self.focus, self.current_row()) {
+            (Pane::Tree, Some(row)) => match row.node {
+                Node::Field(node) => self.field_details(&node.field, node.required),
+                Node::Component(comp) => self.component_details(comp),
+                Node::Group(group) => self.group_details(group),
+            },
+            _ => vec![
+                format!("Message: {}", msg.name),
+                format!("MsgType: {}", msg.msg_type),
+                format!("Category: {}", msg.msg_cat),
+                format!(
+                    "Body: {} field(s), {} component(s), {} group(s)",
+                    msg.fields.len(),
+                    msg.components.len(),
+                    msg.groups.len()
+                ),
+            ],
+        }
+    }
+
+    fn field_details(&self, field: &Field, required: bool) -> Vec<String> {
+        let mut lines = vec![
+            format!("Field: {} ({})", field.name, field.number),
+            format!("Type: {}", field.field_type),
+            format!("Required: {}", yes_no(required)),
+        ];
+        if let Some(usage) = self.schema.field_usage(field.number) {
+            lines.push(format!("Appears in: {} message(s)", usage.messages.len()));
+        }
+        let mut values: Vec<_> = field
+            .values
+            .iter()
+            .chain(field.values_wrapper.value.iter())
+            .collect();
+        if !values.is_empty() {
+            values.sort_by(|a, b| a.enumeration.cmp(&b.enumeration));
+            lines.push(String::new());
+            lines.push("Enums:".to_string());
+            lines.extend(
+                values
+                    .iter()
+                    .map(|value| format!("  {} = {}", value.enumeration, value.description)),
+            );
+        }
+        lines
+    }
+
+    fn component_details(&self, comp: &ComponentNode) -> Vec<String> {
+        let mut lines = vec![
+            format!("Component: {}", comp.name),
+            format!(
+                "Contains: {} field(s), {} component(s), {} group(s)",
+                comp.fields.len(),
+                comp.components.len(),
+                comp.groups.len()
+            ),
+        ];
+        if let Some(users) = self.schema.component_usage.get(&comp.name) {
+            lines.push(format!("Used by: {} message(s)/component(s)", users.len()));
+        }
+        lines
+    }
+
+    fn group_details(&self, group: &GroupNode) -> Vec<String> {
+        let count_tag = self
+            .schema
+            .fields
+            .get(&group.name)
+            .map(|field| format!(" ({})", field.number))
+            .unwrap_or_default();
+        let mut lines = vec![
+            format!("Group: {}{count_tag}", group.name),
+            format!("Required: {}", yes_no(group.required)),
+        ];
+        if let Some(first) = group.fields.first() {
+            lines.push(format!(
+                "Delimiter: {} ({})",
+                first.field.name, first.field.number
+            ));
+        }
+        lines.push(format!(
+            "Each entry: {} field(s), {} component(s), {} group(s)",
+            group.fields.len(),
+            group.components.len(),
+            group.groups.len()
+        ));
+        lines
+    }
+}
+
+fn node_name(node: Node<'_>) -> &str {
+    match node {
+        Node::Field(field) => &field.field.name,
+        Node::Component(comp) => &comp.name,
+        Node::Group(group) => &group.name,
+    }
+}
+
+fn row_label(node: Node<'_>) -> String {
+    match node {
+        Node::Field(field) => format!(
+            "{} ({}){}",
+            field.field.name,
+            field.field.number,
+            if field.required { " *" } else { "" }
+        ),
+        Node::Component(comp) => format!("[{}]", comp.name),
+        Node::Group(group) => format!(
+            "{{{}}}{}",
+            group.name,
+            if group.required { " *" } else { "" }
+        ),
+    }
+}
+
+fn yes_no(flag: bool) -> &'static str {
+    if flag { "yes" } else { "no" }
+}
+
+fn pane_block(title: String, focused: bool) -> Block<'static> {
+    let border = if focused {
+        Style::new().fg(Color::Cyan)
+    } else {
+        Style::new()
+    };
+    Block::bordered().title(title).border_style(border)
+}
+
+fn highlight() -> Style {
+    Style::new().add_modifier(Modifier::REVERSED)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decoder::schema::FixDictionary;
+    use crate::fix;
+
+    fn schema() -> SchemaTree {
+        SchemaTree::build(FixDictionary::from_xml(fix::choose_embedded_xml("44").unwrap()).unwrap())
+    }
+
+    fn press(browser: &mut Browser, code: KeyCode) -> bool {
+        browser.handle_key(KeyEvent::new(code, KeyModifiers::NONE))
+    }
+
+    fn type_text(browser: &mut Browser, text: &str) {
+        for ch in text.chars() {
+            assert!(!press(browser, KeyCode::Char(ch)));
+        }
+    }
+
+    fn labels(browser: &Browser) -> Vec<String> {
+        browser.rows.iter().map(|row| row_label(row.node)).collect()
+    }
+
+    #[test]
+    fn typing_filters_messages_by_name_or_msg_type() {
+        let schema = schema();
+        let mut browser = Browser::new(&schema);
+        assert_eq!(browser.matches.len(), schema.messages.len());
+
+        type_text(&mut browser, "executionrep");
+        let names: Vec<_> = browser.matches.iter().map(|m| m.name.as_str()).collect();
+        assert_eq!(names, ["ExecutionReport"]);
+
+        assert!(
+            !press(&mut browser, KeyCode::Esc),
+            "Esc clears the filter first"
+        );
+        assert!(browser.filter.is_empty());
+        type_text(&mut browser, "8");
+        assert!(browser.matches.iter().any(|m| m.msg_type == "8"));
+        assert!(!press(&mut browser, KeyCode::Backspace));
+        assert!(browser.filter.is_empty());
+        assert!(
+            press(&mut browser, KeyCode::Char('q')),
+            "q quits with no filter"
+        );
+    }
+
+    #[test]
+    fn tree_expands_and_collapses_groups_with_arrow_keys() {
+        let schema = schema();
+        let mut browser = Browser::new(&schema);
+        type_text(&mut browser, "ExecutionReport");
+        press(&mut browser, KeyCode::Right);
+        assert_eq!(browser.focus, Pane::Tree);
+        assert_eq!(
+            labels(&browser).first().map(String::as_str),
+            Some("[Header]")
+        );
+        assert_eq!(
+            labels(&browser).last().map(String::as_str),
+            Some("[Trailer]")
+        );
+
+        let parties = labels(&browser)
+            .iter()
+            .position(|label| label == "[Parties]")
+            .expect("Parties component");
+        browser.selected_row = parties;
+        let collapsed = browser.rows.len();
+        press(&mut browser, KeyCode::Right);
+        assert!(labels(&browser).contains(&"{NoPartyIDs}".to_string()));
+
+        press(&mut browser, KeyCode::Right);
+        assert_eq!(
+            browser.selected_row,
+            parties + 1,
+            "Right steps into the children"
+        );
+        press(&mut browser, KeyCode::Right);
+        assert!(labels(&browser).contains(&"PartyID (448)".to_string()));
+
+        press(&mut browser, KeyCode::Down);
+        assert_eq!(
+            browser.detail_lines()[..2],
+            [
+                "Field: PartyID (448)".to_string(),
+                "Type: STRING".to_string()
+            ]
+        );
+
+        press(&mut browser, KeyCode::Left);
+        assert_eq!(
+            browser.selected_row,
+            parties + 1,
+            "Left steps up to the group"
+        );
+        press(&mut browser, KeyCode::Left);
+        press(&mut browser, KeyCode::Left);
+        press(&mut browser, KeyCode::Left);
+        assert_eq!(browser.rows.len(), collapsed);
+        assert!(press(&mut browser, KeyCode::Char('q')));
+    }
+
+    #[test]
+    fn field_details_list_enums_and_ctrl_c_quits() {
+        let schema = schema();
+        let mut browser = Browser::new(&schema);
+        type_text(&mut browser, "NewOrderSingle");
+        press(&mut browser, KeyCode::Tab);
+        let side = browser
+            .rows
+            .iter()
+            .position(|row| matches!(row.node, Node::Field(f) if f.field.number == 54))
+            .expect("Side field");
+        browser.selected_row = side;
+        let details = browser.detail_lines();
+        assert!(
+            details.contains(&"Required: yes".to_string()),
+            "{details:?}"
+        );
+        assert!(details.contains(&"  1 = BUY".to_string()), "{details:?}");
+
+        assert!(browser.handle_key(KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL)));
+    }
+}
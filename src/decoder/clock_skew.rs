@@ -0,0 +1,204 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// SPDX-FileCopyrightText: 2025 Steve Clarke <stephenlclarke@mac.com> - https://xyzzy.tools
+
+//! `--check-clock-skew SECONDS`: measure how far TransactTime(60) drifts from
+//! SendingTime(52) so a venue whose clock has wandered shows up without reading every
+//! timestamp.  Both are compared as full datetimes, so a message sent just after
+//! midnight about a trade just before it is a few seconds apart, not a day.
+
+use crate::decoder::colours::palette;
+use crate::decoder::fixparser::ParsedMessage;
+use crate::decoder::summary::{format_elapsed, parse_fix_timestamp, render_table};
+use chrono::Duration;
+use std::collections::BTreeMap;
+use std::io::Write;
+
+/// The outcome of comparing a message's SendingTime(52) and TransactTime(60).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Skew {
+    /// SendingTime minus TransactTime: positive when TransactTime is behind.
+    Measured(Duration),
+    /// Both tags were present but at least one could not be parsed.
+    Unparseable,
+}
+
+/// Compare SendingTime(52) with TransactTime(60); `None` unless the message has both.
+pub fn measure(msg: &ParsedMessage<'_>) -> Option<Skew> {
+    let (sending, transact) = (msg.first(52)?, msg.first(60)?);
+    match (parse_fix_timestamp(sending), parse_fix_timestamp(transact)) {
+        (Some(sending), Some(transact)) => Some(Skew::Measured(sending - transact)),
+        _ => Some(Skew::Unparseable),
+    }
+}
+
+/// Describe a skew beyond `limit`, for the validation finding anchored on tag 60.
+pub fn describe(skew: Duration, limit: Duration) -> String {
+    let direction = if skew < Duration::zero() {
+        "ahead of"
+    } else {
+        "behind"
+    };
+    format!(
+        "TransactTime(60) is {} {direction} SendingTime(52), limit is {}",
+        format_elapsed(skew.abs()),
+        format_elapsed(limit)
+    )
+}
+
+/// Skews seen per session (SenderCompID(49) to TargetCompID(56)) for the end-of-run
+/// report, plus a count of messages whose timestamps could not be compared.
+#[derive(Debug)]
+pub struct ClockSkewStats {
+    limit: Duration,
+    sessions: BTreeMap<(String, String), Vec<Duration>>,
+    unparseable: usize,
+}
+
+impl ClockSkewStats {
+    pub fn new(limit: Duration) -> Self {
+        Self {
+            limit,
+            sessions: BTreeMap::new(),
+            unparseable: 0,
+        }
+    }
+
+    /// Record the message's skew against its session; messages without both
+    /// timestamps are ignored.
+    pub fn record(&mut self, msg: &ParsedMessage<'_>) {
+        match measure(msg) {
+            Some(Skew::Measured(skew)) => {
+                let session = (
+                    msg.first(49).unwrap_or("?").to_string(),
+                    msg.first(56).unwrap_or("?").to_string(),
+                );
+                self.sessions.entry(session).or_default().push(skew.abs());
+            }
+            Some(Skew::Unparseable) => self.unparseable += 1,
+            None => {}
+        }
+    }
+
+    /// Print the largest and 99th percentile skew per session, and how many messages
+    /// went over the limit.  Silent when no message carried both timestamps.
+    pub fn render(&self, out: &mut dyn Write) -> std::io::Result<()> {
+        if self.sessions.is_empty() && self.unparseable == 0 {
+            return Ok(());
+        }
+        let colours = palette();
+        writeln!(
+            out,
+            "{}Clock Skew{} (limit {})\n",
+            colours.title,
+            colours.reset,
+            format_elapsed(self.limit)
+        )?;
+        let rows: Vec<Vec<String>> = self
+            .sessions
+            .iter()
+            .map(|((sender, target), skews)| {
+                let mut sorted = skews.clone();
+                sorted.sort();
+                let over = sorted.iter().filter(|skew| **skew > self.limit).count();
+                vec![
+                    format!("{sender} -> {target}"),
+                    sorted.len().to_string(),
+                    format_elapsed(sorted[sorted.len() - 1]),
+                    format_elapsed(percentile(&sorted, 99)),
+                    over.to_string(),
+                ]
+            })
+            .collect();
+        render_table(
+            out,
+            &["Session", "Messages", "Max", "p99", "Over limit"],
+            &rows,
+        )?;
+        if self.unparseable > 0 {
+            writeln!(
+                out,
+                "Skipped {} message(s) with an unparseable SendingTime(52) or TransactTime(60)",
+                self.unparseable
+            )?;
+        }
+        writeln!(out)
+    }
+}
+
+/// Nearest-rank percentile of a sorted, non-empty slice.
+fn percentile(sorted: &[Duration], pct: usize) -> Duration {
+    let rank = (sorted.len() * pct).div_ceil(100).max(1);
+    sorted[rank - 1]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decoder::colours::disable_colours;
+
+    fn msg(sending: &str, transact: &str) -> String {
+        format!(
+            "8=FIX.4.4\u{0001}35=8\u{0001}49=VENUE\u{0001}56=US\u{0001}52={sending}\u{0001}60={transact}\u{0001}"
+        )
+    }
+
+    fn skew(sending: &str, transact: &str) -> Option<Skew> {
+        measure(&ParsedMessage::parse(&msg(sending, transact)))
+    }
+
+    #[test]
+    fn timestamps_of_differing_precision_and_across_midnight_compare_as_datetimes() {
+        assert_eq!(
+            skew("20250102-10:00:00.250", "20250102-10:00:00"),
+            Some(Skew::Measured(Duration::milliseconds(250)))
+        );
+        assert_eq!(
+            skew("20250102-10:00:00.000001", "20250102-10:00:00.000000000"),
+            Some(Skew::Measured(Duration::microseconds(1)))
+        );
+        assert_eq!(
+            skew("20250102-00:00:02", "20250101-23:59:58"),
+            Some(Skew::Measured(Duration::seconds(4)))
+        );
+        assert_eq!(
+            skew("20250102-10:00:00", "10:00:00"),
+            Some(Skew::Unparseable)
+        );
+        let no_transact = "8=FIX.4.4\u{0001}35=0\u{0001}52=20250102-10:00:00\u{0001}";
+        assert_eq!(measure(&ParsedMessage::parse(no_transact)), None);
+    }
+
+    #[test]
+    fn report_shows_max_and_p99_per_session() {
+        disable_colours();
+        let mut stats = ClockSkewStats::new(Duration::seconds(5));
+        for secs in 0..100 {
+            let transact = format!("20250102-09:58:{:02}", secs % 60);
+            let sending = if secs == 99 {
+                "20250102-10:08:39".to_string()
+            } else {
+                format!("20250102-09:58:{:02}", secs % 60)
+            };
+            stats.record(&ParsedMessage::parse(&msg(&sending, &transact)));
+        }
+        stats.record(&ParsedMessage::parse(&msg("bad", "20250102-10:00:00")));
+
+        let mut out = Vec::new();
+        stats.render(&mut out).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        assert!(text.contains("Clock Skew (limit 5.000s)"), "{text}");
+        let row: Vec<&str> = text
+            .lines()
+            .find(|line| line.contains("VENUE -> US"))
+            .unwrap()
+            .split("  ")
+            .map(str::trim)
+            .filter(|cell| !cell.is_empty())
+            .collect();
+        assert_eq!(row, ["VENUE -> US", "100", "10m00.000s", "0.000s", "1"]);
+        assert!(
+            text.contains("Skipped 1 message(s) with an unparseable"),
+            "{text}"
+        );
+    }
+}
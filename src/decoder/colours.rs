@@ -1,6 +1,8 @@
 // SPDX-License-Identifier: AGPL-3.0-only
 // SPDX-FileCopyrightText: 2025 Steve Clarke <stephenlclarke@mac.com> - https://xyzzy.tools
 
+use anyhow::{Result, anyhow, bail};
+use std::sync::OnceLock;
 use std::sync::atomic::{AtomicBool, Ordering};
 
 /// ANSI colour palette used across decoder output. The fields hold the SGR sequences for each role.
@@ -14,6 +16,7 @@ pub struct ColourPalette {
     pub enumeration: &'static str,
     pub file: &'static str,
     pub error: &'static str,
+    pub warning: &'static str,
     pub message: &'static str,
     pub title: &'static str,
 }
@@ -27,10 +30,40 @@ const COLOURED: ColourPalette = ColourPalette {
     enumeration: "\u{001b}[38;5;214m",
     file: "\u{001b}[95m",
     error: "\u{001b}[31m",
+    warning: "\u{001b}[33m",
     message: "\u{001b}[97m",
     title: "\u{001b}[31m",
 };
 
+const LIGHT: ColourPalette = ColourPalette {
+    reset: "\u{001b}[0m",
+    line: "\u{001b}[38;5;242m",
+    tag: "\u{001b}[38;5;25m",
+    name: "\u{001b}[38;5;28m",
+    value: "\u{001b}[38;5;130m",
+    enumeration: "\u{001b}[38;5;90m",
+    file: "\u{001b}[35m",
+    error: "\u{001b}[31m",
+    warning: "\u{001b}[38;5;136m",
+    message: "\u{001b}[30m",
+    title: "\u{001b}[31m",
+};
+
+/// Attributes only (bold, dim, underline) for monochrome terminals.
+const MONO: ColourPalette = ColourPalette {
+    reset: "\u{001b}[0m",
+    line: "\u{001b}[2m",
+    tag: "\u{001b}[1m",
+    name: "",
+    value: "",
+    enumeration: "\u{001b}[3m",
+    file: "\u{001b}[4m",
+    error: "\u{001b}[1;4m",
+    warning: "\u{001b}[1;3m",
+    message: "",
+    title: "\u{001b}[1m",
+};
+
 const PLAIN: ColourPalette = ColourPalette {
     reset: "",
     line: "",
@@ -40,22 +73,114 @@ const PLAIN: ColourPalette = ColourPalette {
     enumeration: "",
     file: "",
     error: "",
+    warning: "",
     message: "",
     title: "",
 };
 
+/// Selectable colour themes for `--theme`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Theme {
+    #[default]
+    Dark,
+    Light,
+    Mono,
+}
+
+impl Theme {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_ascii_lowercase().as_str() {
+            "dark" => Some(Theme::Dark),
+            "light" => Some(Theme::Light),
+            "mono" => Some(Theme::Mono),
+            _ => None,
+        }
+    }
+
+    pub fn palette(self) -> ColourPalette {
+        match self {
+            Theme::Dark => COLOURED,
+            Theme::Light => LIGHT,
+            Theme::Mono => MONO,
+        }
+    }
+}
+
 static ENABLED: AtomicBool = AtomicBool::new(true);
+static INSTALLED: OnceLock<ColourPalette> = OnceLock::new();
 
 /// Return the current colour palette, respecting the global enable/disable flag.
 pub fn palette() -> ColourPalette {
     if ENABLED.load(Ordering::Relaxed) {
-        COLOURED
+        *INSTALLED.get().unwrap_or(&COLOURED)
     } else {
         PLAIN
     }
 }
 
+/// Install the palette used for the rest of the run.  Only the first call takes
+/// effect, so this must happen before any output is produced.
+pub fn install_palette(palette: ColourPalette) {
+    let _ = INSTALLED.set(palette);
+}
+
+/// Apply per-element overrides such as `tag=36,value=1;32` (SGR parameters, as in
+/// `FIXDECODER_COLOURS`) on top of `base`.
+pub fn apply_overrides(base: ColourPalette, spec: &str) -> Result<ColourPalette> {
+    let mut palette = base;
+    for entry in spec.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+        let (element, sgr) = entry
+            .split_once('=')
+            .ok_or_else(|| anyhow!("colour override '{entry}' must look like element=SGR"))?;
+        if sgr.is_empty() || !sgr.bytes().all(|b| b.is_ascii_digit() || b == b';') {
+            bail!("invalid SGR code '{sgr}' for colour element {element}");
+        }
+        let code: &'static str = Box::leak(format!("\u{001b}[{sgr}m").into_boxed_str());
+        let slot = match element.trim().to_ascii_lowercase().as_str() {
+            "line" => &mut palette.line,
+            "tag" => &mut palette.tag,
+            "name" => &mut palette.name,
+            "value" => &mut palette.value,
+            "enum" | "enumeration" => &mut palette.enumeration,
+            "file" => &mut palette.file,
+            "error" => &mut palette.error,
+            "warning" => &mut palette.warning,
+            "message" => &mut palette.message,
+            "title" => &mut palette.title,
+            other => bail!("unknown colour element '{other}'"),
+        };
+        *slot = code;
+    }
+    Ok(palette)
+}
+
 /// Disable ANSI colour output globally (used when piping or when explicitly requested).
 pub fn disable_colours() {
     ENABLED.store(false, Ordering::Relaxed);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn light_theme_uses_different_escape_codes() {
+        let dark = Theme::Dark.palette();
+        let light = Theme::Light.palette();
+        assert_ne!(dark.value, light.value);
+        assert_ne!(dark.tag, light.tag);
+        assert!(light.value.starts_with('\u{001b}'));
+        assert_eq!(Theme::parse("LIGHT"), Some(Theme::Light));
+        assert_eq!(Theme::parse("neon"), None);
+    }
+
+    #[test]
+    fn overrides_replace_named_elements() {
+        let palette = apply_overrides(Theme::Dark.palette(), "tag=36, value=1;32").unwrap();
+        assert_eq!(palette.tag, "\u{001b}[36m");
+        assert_eq!(palette.value, "\u{001b}[1;32m");
+        assert_eq!(palette.name, COLOURED.name);
+        assert!(apply_overrides(COLOURED, "bogus=1").is_err());
+        assert!(apply_overrides(COLOURED, "tag=red").is_err());
+    }
+}
@@ -0,0 +1,217 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// SPDX-FileCopyrightText: 2025 Steve Clarke <stephenlclarke@mac.com> - https://xyzzy.tools
+
+//! `--dedupe`: collapse runs of identical messages, such as heartbeat floods and
+//! retransmitted ResendRequests, on a flaky session.  A message repeats the one before
+//! it in its direction of the session when every field outside the ignore set
+//! matches.  Sessions are told apart by their CompIDs; only a hash of those fields is
+//! kept per session, so the check costs one pass over the message.
+
+use std::collections::HashMap;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+const SOH: char = '\u{0001}';
+
+/// Fields that change on every resend and so are left out of the comparison:
+/// BodyLength, CheckSum, MsgSeqNum and SendingTime.
+pub const DEFAULT_IGNORE_TAGS: [u32; 4] = [9, 10, 34, 52];
+
+/// Whether a message repeats its predecessor, as decided by [`MessageDeduper::observe`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Seen<'a> {
+    /// Same as the previous message in its session; suppress it.
+    Repeat,
+    /// Differs from the previous message of its `session` (SenderCompID and
+    /// TargetCompID, empty when absent), ending a run that repeated `ended_run` times
+    /// (zero when there was nothing to suppress).
+    New {
+        ended_run: usize,
+        session: (&'a str, &'a str),
+    },
+}
+
+#[derive(Debug, Default)]
+struct Run {
+    /// SenderCompID and TargetCompID.
+    session: (String, String),
+    hash: u64,
+    repeats: usize,
+}
+
+/// The last message hash and current repeat count for each SenderCompID and
+/// TargetCompID pair, in the order the sessions were first seen.
+#[derive(Debug)]
+pub struct MessageDeduper {
+    ignore_tags: Vec<u32>,
+    index: HashMap<(String, String), usize>,
+    runs: Vec<Run>,
+}
+
+impl MessageDeduper {
+    pub fn new(ignore_tags: Vec<u32>) -> Self {
+        Self {
+            ignore_tags,
+            index: HashMap::new(),
+            runs: Vec::new(),
+        }
+    }
+
+    /// Compare `msg` (SOH-delimited) with the previous message from the same sender
+    /// to the same target.
+    pub fn observe<'a>(&mut self, msg: &'a str) -> Seen<'a> {
+        let mut content = DefaultHasher::new();
+        let (mut sender, mut target) = ("", "");
+        for field in msg.split(SOH).filter(|field| !field.is_empty()) {
+            let (tag, value) = field.split_once('=').unwrap_or((field, ""));
+            match tag {
+                "49" => sender = value,
+                "56" => target = value,
+                _ => {}
+            }
+            if tag
+                .parse::<u32>()
+                .is_ok_and(|tag| self.ignore_tags.contains(&tag))
+            {
+                continue;
+            }
+            field.hash(&mut content);
+        }
+        let hash = content.finish();
+
+        let session = (sender.to_string(), target.to_string());
+        let slot = *self
+            .index
+            .entry(session.clone())
+            .or_insert_with(|| self.runs.len());
+        if slot == self.runs.len() {
+            self.runs.push(Run {
+                session,
+                hash,
+                repeats: 0,
+            });
+            return Seen::New {
+                ended_run: 0,
+                session: (sender, target),
+            };
+        }
+        let run = &mut self.runs[slot];
+        if run.hash == hash {
+            run.repeats += 1;
+            return Seen::Repeat;
+        }
+        let ended_run = std::mem::take(&mut run.repeats);
+        run.hash = hash;
+        Seen::New {
+            ended_run,
+            session: (sender, target),
+        }
+    }
+
+    /// Close every open run at the end of the input, returning the session (sender,
+    /// target) and repeat count of those that suppressed anything.
+    pub fn finish(&mut self) -> Vec<((String, String), usize)> {
+        self.runs
+            .iter_mut()
+            .filter(|run| run.repeats > 0)
+            .map(|run| (run.session.clone(), std::mem::take(&mut run.repeats)))
+            .collect()
+    }
+}
+
+/// The note printed where a run of suppressed repeats ends, naming its session so it
+/// is not read as belonging to a message of another session printed beside it.
+pub fn repeat_note((sender, target): (&str, &str), repeats: usize) -> String {
+    let times = if repeats == 1 { "time" } else { "times" };
+    format!("(… {sender}->{target} repeated {repeats} {times})")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn msg(fields: &str) -> String {
+        fields.replace('|', "\u{0001}")
+    }
+
+    fn heartbeat(from: &str, to: &str, seq: u32) -> String {
+        msg(&format!(
+            "8=FIX.4.4|9=55|35=0|49={from}|56={to}|34={seq}|52=20240101-00:00:{seq:02}|10={:03}|",
+            seq * 7 % 256
+        ))
+    }
+
+    #[test]
+    fn heartbeat_runs_collapse_around_an_execution_report() {
+        let mut dedupe = MessageDeduper::new(DEFAULT_IGNORE_TAGS.to_vec());
+        let report = msg("8=FIX.4.4|9=70|35=8|49=AAA|56=BBB|34=4|37=O1|17=E1|150=0|10=123|");
+        let messages = [
+            heartbeat("AAA", "BBB", 1),
+            heartbeat("AAA", "BBB", 2),
+            heartbeat("CCC", "DDD", 1),
+            heartbeat("AAA", "BBB", 3),
+            report,
+            heartbeat("AAA", "BBB", 5),
+            heartbeat("AAA", "BBB", 6),
+        ];
+        let seen: Vec<Seen> = messages.iter().map(|m| dedupe.observe(m)).collect();
+        let new = |ended_run, session| Seen::New { ended_run, session };
+        assert_eq!(
+            seen,
+            [
+                new(0, ("AAA", "BBB")),
+                Seen::Repeat,
+                new(0, ("CCC", "DDD")),
+                Seen::Repeat,
+                new(2, ("AAA", "BBB")),
+                new(0, ("AAA", "BBB")),
+                Seen::Repeat,
+            ]
+        );
+        // The run the report ends is noted against its session, not CCC->DDD's.
+        assert_eq!(
+            repeat_note(("AAA", "BBB"), 2),
+            "(… AAA->BBB repeated 2 times)"
+        );
+        let finished = dedupe.finish();
+        assert_eq!(finished, [(("AAA".into(), "BBB".into()), 1)]);
+        let ((sender, target), repeats) = &finished[0];
+        assert_eq!(
+            repeat_note((sender, target), *repeats),
+            "(… AAA->BBB repeated 1 time)"
+        );
+        assert!(dedupe.finish().is_empty());
+    }
+
+    #[test]
+    fn each_direction_is_its_own_session() {
+        let mut dedupe = MessageDeduper::new(DEFAULT_IGNORE_TAGS.to_vec());
+        assert!(matches!(
+            dedupe.observe(&heartbeat("AAA", "BBB", 1)),
+            Seen::New { ended_run: 0, .. }
+        ));
+        assert!(matches!(
+            dedupe.observe(&heartbeat("BBB", "AAA", 1)),
+            Seen::New { ended_run: 0, .. }
+        ));
+        assert_eq!(dedupe.observe(&heartbeat("AAA", "BBB", 2)), Seen::Repeat);
+        assert_eq!(dedupe.observe(&heartbeat("BBB", "AAA", 2)), Seen::Repeat);
+        assert_eq!(
+            dedupe.finish(),
+            [
+                (("AAA".into(), "BBB".into()), 1),
+                (("BBB".into(), "AAA".into()), 1)
+            ]
+        );
+    }
+
+    #[test]
+    fn only_the_configured_tags_are_ignored() {
+        let mut dedupe = MessageDeduper::new(vec![9, 10, 34]);
+        dedupe.observe(&heartbeat("AAA", "BBB", 1));
+        assert!(matches!(
+            dedupe.observe(&heartbeat("AAA", "BBB", 2)),
+            Seen::New { ended_run: 0, .. }
+        ));
+    }
+}
@@ -0,0 +1,179 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// SPDX-FileCopyrightText: 2025 Steve Clarke <stephenlclarke@mac.com> - https://xyzzy.tools
+
+//! Parsing `--delimiter` values, shared by fixdecoder and pcap2fix so both accept the
+//! same spellings and explain a rejected one the same way.
+
+use anyhow::{Result, anyhow, bail};
+
+/// What a `--delimiter` value may be, for help text, usage and error messages alike.
+pub const DELIMITER_FORMS: &str = "a single printable character such as '|', a control character \
+     name (SOH, TAB, FS, GS, RS or US), the escape \\t, or a hex code such as \\x01 or 0x1f";
+
+/// Control characters that can be given by name, matched case-insensitively.
+const NAMED: &[(&str, char)] = &[
+    ("SOH", '\u{0001}'),
+    ("TAB", '\t'),
+    ("FS", '\u{001c}'),
+    ("GS", '\u{001d}'),
+    ("RS", '\u{001e}'),
+    ("US", '\u{001f}'),
+];
+
+/// Parse a `--delimiter` value.  NUL and line breaks are rejected whichever way they
+/// are spelt, and a literal control character must be given by name or hex code so
+/// that it cannot be passed by accident.
+pub fn parse_delimiter(value: &str) -> Result<char> {
+    if value.is_empty() {
+        bail!("delimiter cannot be empty; expected {DELIMITER_FORMS}");
+    }
+    if value.eq_ignore_ascii_case("NUL") {
+        return checked('\0');
+    }
+    if let Some((_, c)) = NAMED
+        .iter()
+        .find(|(name, _)| value.eq_ignore_ascii_case(name))
+    {
+        return Ok(*c);
+    }
+    if let Some(hex) = value
+        .strip_prefix("\\x")
+        .or_else(|| value.strip_prefix("0x"))
+    {
+        let code = u32::from_str_radix(hex, 16)
+            .map_err(|_| anyhow!("invalid delimiter hex value: {value}"))?;
+        let c = char::from_u32(code)
+            .ok_or_else(|| anyhow!("delimiter code {value} is not valid Unicode"))?;
+        return checked(c);
+    }
+    match value {
+        "\\t" => return Ok('\t'),
+        "\\n" => return checked('\n'),
+        "\\r" => return checked('\r'),
+        "\\0" => return checked('\0'),
+        _ => {}
+    }
+    let mut chars = value.chars();
+    let first = chars.next().unwrap_or_default();
+    if first == '\\' && value.len() == 2 {
+        bail!("unknown delimiter escape {value}; expected {DELIMITER_FORMS}");
+    }
+    if value.chars().any(is_combining) {
+        bail!(
+            "delimiter '{value}' includes a combining character, which joins onto the one \
+             before it; expected {DELIMITER_FORMS}"
+        );
+    }
+    if chars.next().is_some() {
+        bail!(
+            "delimiter '{value}' is {} characters; expected {DELIMITER_FORMS}",
+            value.chars().count()
+        );
+    }
+    let c = checked(first)?;
+    if c.is_control() && !NAMED.iter().any(|(_, named)| *named == c) {
+        bail!(
+            "delimiter U+{:04X} is not printable; give it as the hex code \\x{:02x} if it is intended",
+            c as u32,
+            c as u32
+        );
+    }
+    Ok(c)
+}
+
+/// Reject the characters no spelling may produce.
+fn checked(c: char) -> Result<char> {
+    match c {
+        '\0' => bail!(
+            "NUL cannot be a delimiter: it ends C strings and separates --stdin-format=nul \
+             records; use SOH or US instead"
+        ),
+        '\n' | '\r' => bail!(
+            "a line break cannot be a delimiter: input is read a line at a time, so every \
+             field would become a line of its own"
+        ),
+        _ => Ok(c),
+    }
+}
+
+/// Combining marks, variation selectors, skin-tone modifiers and the zero-width joiner:
+/// characters that render as part of the one before, so a value holding one is never
+/// the single character it looks like.
+fn is_combining(c: char) -> bool {
+    matches!(
+        c as u32,
+        0x0300..=0x036F
+            | 0x1AB0..=0x1AFF
+            | 0x1DC0..=0x1DFF
+            | 0x200D
+            | 0x20D0..=0x20FF
+            | 0xFE00..=0xFE0F
+            | 0xFE20..=0xFE2F
+            | 0x1F3FB..=0x1F3FF
+            | 0xE0100..=0xE01EF
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_literals_names_escapes_and_hex_codes() {
+        for (value, expected) in [
+            ("|", '|'),
+            (",", ','),
+            ("^", '^'),
+            (" ", ' '),
+            ("—", '—'),
+            ("\t", '\t'),
+            ("\u{0001}", '\u{0001}'),
+            ("SOH", '\u{0001}'),
+            ("soh", '\u{0001}'),
+            ("TAB", '\t'),
+            ("Tab", '\t'),
+            ("FS", '\u{001c}'),
+            ("GS", '\u{001d}'),
+            ("RS", '\u{001e}'),
+            ("us", '\u{001f}'),
+            ("\\t", '\t'),
+            ("\\x01", '\u{0001}'),
+            ("0x1f", '\u{001f}'),
+            ("\\x07", '\u{0007}'),
+            ("0x7c", '|'),
+        ] {
+            assert_eq!(parse_delimiter(value).unwrap(), expected, "{value:?}");
+        }
+    }
+
+    #[test]
+    fn rejects_with_the_reason_and_the_accepted_forms() {
+        for (value, reason) in [
+            ("", "cannot be empty; expected a single printable character"),
+            ("NUL", "NUL cannot be a delimiter"),
+            ("\\0", "NUL cannot be a delimiter"),
+            ("\\x00", "NUL cannot be a delimiter"),
+            ("\\n", "a line break cannot be a delimiter"),
+            ("\\r", "a line break cannot be a delimiter"),
+            ("0x0a", "a line break cannot be a delimiter"),
+            ("\n", "a line break cannot be a delimiter"),
+            ("\\q", "unknown delimiter escape \\q; expected"),
+            ("||", "'||' is 2 characters; expected"),
+            ("TABS", "'TABS' is 4 characters; expected"),
+            ("👍🏽", "includes a combining character"),
+            ("e\u{0301}", "includes a combining character"),
+            ("\u{0301}", "includes a combining character"),
+            (
+                "\u{0007}",
+                "U+0007 is not printable; give it as the hex code \\x07",
+            ),
+            ("\\xzz", "invalid delimiter hex value: \\xzz"),
+            ("0xd800", "delimiter code 0xd800 is not valid Unicode"),
+        ] {
+            let err = parse_delimiter(value).unwrap_err().to_string();
+            assert!(err.contains(reason), "{value:?}: {err}");
+        }
+        let err = parse_delimiter("ab").unwrap_err().to_string();
+        assert!(err.ends_with(DELIMITER_FORMS), "{err}");
+    }
+}
@@ -0,0 +1,133 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// SPDX-FileCopyrightText: 2025 Steve Clarke <stephenlclarke@mac.com> - https://xyzzy.tools
+
+//! Fields and enum values that later FIX versions deprecate, and what replaces them.
+//! Decoded output marks them, and `--validate --warn-deprecated` reports them as
+//! warnings that do not make a message invalid.  Each entry sits under the version
+//! that deprecated it and applies to every later version, so adding one is a single
+//! line in [`DEPRECATIONS`].
+
+/// A deprecated field, or one deprecated value of a field.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Deprecation {
+    pub tag: u32,
+    /// The field's name in the versions that still define it; later dictionaries may
+    /// not know the tag at all.
+    pub name: &'static str,
+    /// The deprecated enum value, or `None` when the whole field is deprecated.
+    pub value: Option<&'static str>,
+    /// What to send instead, e.g. `LastQty(32)`.
+    pub replacement: &'static str,
+}
+
+const fn field(tag: u32, name: &'static str, replacement: &'static str) -> Deprecation {
+    Deprecation {
+        tag,
+        name,
+        value: None,
+        replacement,
+    }
+}
+
+const fn value(
+    tag: u32,
+    name: &'static str,
+    value: &'static str,
+    replacement: &'static str,
+) -> Deprecation {
+    Deprecation {
+        tag,
+        name,
+        value: Some(value),
+        replacement,
+    }
+}
+
+/// Dictionary keys, oldest first.
+const VERSIONS: &[&str] = &[
+    "FIX40", "FIX41", "FIX42", "FIX43", "FIX44", "FIX50", "FIX50SP1", "FIX50SP2",
+];
+
+/// Deprecations keyed by the dictionary of the version that introduced them.
+pub const DEPRECATIONS: &[(&str, &[Deprecation])] = &[
+    (
+        "FIX43",
+        &[
+            field(20, "ExecTransType", "ExecType(150)"),
+            field(47, "Rule80A", "OrderCapacity(528)"),
+            field(76, "ExecBroker", "NoPartyIDs(453)"),
+            field(109, "ClientID", "NoPartyIDs(453)"),
+        ],
+    ),
+    (
+        "FIX44",
+        &[
+            value(150, "ExecType", "1", "ExecType(150)=F"),
+            value(150, "ExecType", "2", "ExecType(150)=F"),
+        ],
+    ),
+    ("FIX50", &[field(53, "Shares", "LastQty(32)")]),
+];
+
+impl Deprecation {
+    /// `deprecated, use LastQty(32)`, for the decoded field line.
+    pub fn hint(&self) -> String {
+        format!("deprecated, use {}", self.replacement)
+    }
+
+    /// The `--warn-deprecated` finding for a message decoded with dictionary `key`.
+    pub fn describe(&self, key: &str) -> String {
+        let field = match self.value {
+            Some(value) => format!("{}({})={value}", self.name, self.tag),
+            None => format!("{}({})", self.name, self.tag),
+        };
+        format!("{field} is deprecated in {key}, use {}", self.replacement)
+    }
+}
+
+/// The deprecation, if any, covering `tag` or its `value` in dictionary `key`.  A
+/// deprecated field wins over a deprecated value of it.
+pub fn lookup(key: &str, tag: u32, value: &str) -> Option<&'static Deprecation> {
+    let version = VERSIONS.iter().position(|v| *v == key)?;
+    let in_force = DEPRECATIONS
+        .iter()
+        .filter(|(since, _)| VERSIONS.iter().position(|v| v == since) <= Some(version))
+        .flat_map(|(_, entries)| entries.iter())
+        .filter(|dep| dep.tag == tag);
+    let mut matching = in_force.filter(|dep| dep.value.is_none_or(|v| v == value));
+    let first = matching.next()?;
+    Some(matching.find(|dep| dep.value.is_none()).unwrap_or(first))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_deprecated_field_applies_from_its_version_onwards() {
+        assert_eq!(lookup("FIX42", 20, "0"), None);
+        let dep = lookup("FIX50SP2", 20, "0").expect("ExecTransType is deprecated");
+        assert_eq!(dep.hint(), "deprecated, use ExecType(150)");
+        assert_eq!(
+            dep.describe("FIX50SP2"),
+            "ExecTransType(20) is deprecated in FIX50SP2, use ExecType(150)"
+        );
+        assert_eq!(
+            lookup("FIX50", 53, "100").map(Deprecation::hint).as_deref(),
+            Some("deprecated, use LastQty(32)")
+        );
+        assert_eq!(lookup("FIXT11", 20, "0"), None);
+    }
+
+    #[test]
+    fn a_deprecated_enum_value_leaves_the_other_values_alone() {
+        assert_eq!(lookup("FIX42", 150, "2"), None);
+        assert_eq!(lookup("FIX44", 150, "F"), None);
+        let dep = lookup("FIX44", 150, "2").expect("ExecType=2 is deprecated");
+        assert_eq!(
+            dep.describe("FIX44"),
+            "ExecType(150)=2 is deprecated in FIX44, use ExecType(150)=F"
+        );
+        assert!(lookup("FIX50SP1", 150, "1").is_some());
+    }
+}
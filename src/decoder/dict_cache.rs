@@ -0,0 +1,373 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// SPDX-FileCopyrightText: 2025 Steve Clarke <stephenlclarke@mac.com> - https://xyzzy.tools
+
+//! On-disk cache of parsed dictionaries.  Parsing a multi-megabyte XML dictionary
+//! dominates short runs such as `fixdecoder --tag 35`, so once a dictionary has been
+//! parsed it is written with bincode to `~/.cache/fixdecoder/<key>-<version>.bin` and
+//! read back by later runs.  Each file records the crate version and where the
+//! dictionary came from (a hash of the embedded XML, or a `--xml` file's size and
+//! modification time); a mismatch, or a file that will not deserialise, falls back to
+//! parsing the XML and rewrites the file.
+
+use crate::decoder::schema::FixDictionary;
+use crate::fix;
+use anyhow::Context;
+use bincode::Options;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::UNIX_EPOCH;
+
+/// The cache in use for this run; unset (the library default) means always parse.
+static CACHE: OnceLock<DiskCache> = OnceLock::new();
+
+/// `--allow-partial-dict`: custom dictionaries without `<components>` are still read.
+static ALLOW_PARTIAL: AtomicBool = AtomicBool::new(false);
+
+/// Read `--xml` files that lack only their `<components>` section with no components
+/// instead of refusing them (`--allow-partial-dict`).
+pub fn set_allow_partial(enabled: bool) {
+    ALLOW_PARTIAL.store(enabled, Ordering::Relaxed);
+}
+
+pub fn allow_partial() -> bool {
+    ALLOW_PARTIAL.load(Ordering::Relaxed)
+}
+
+/// Cache parsed dictionaries in `dir` for the rest of the run; only the first call
+/// has any effect.
+pub fn set_cache_dir(dir: PathBuf) {
+    let _ = CACHE.set(DiskCache::new(dir));
+}
+
+/// `$XDG_CACHE_HOME/fixdecoder`, else `~/.cache/fixdecoder`.
+pub fn default_cache_dir() -> Option<PathBuf> {
+    let non_empty = |name: &str| std::env::var_os(name).filter(|value| !value.is_empty());
+    let base = non_empty("XDG_CACHE_HOME")
+        .map(PathBuf::from)
+        .or_else(|| non_empty("HOME").map(|home| PathBuf::from(home).join(".cache")))?;
+    Some(base.join("fixdecoder"))
+}
+
+/// Parse the embedded dictionary with XML id `xml_id` (such as `44`), through the
+/// cache when one is set.
+pub fn parse_embedded(xml_id: &str, xml: &str) -> anyhow::Result<FixDictionary> {
+    match CACHE.get() {
+        Some(cache) => cache.parse_embedded(xml_id, xml),
+        None => FixDictionary::from_xml(xml),
+    }
+}
+
+/// Read and parse a custom dictionary file, through the cache when one is set.
+pub fn parse_file(path: &str) -> anyhow::Result<FixDictionary> {
+    match CACHE.get() {
+        Some(cache) => cache.parse_file(path),
+        None => parse_xml_file(path),
+    }
+}
+
+fn parse_xml_file(path: &str) -> anyhow::Result<FixDictionary> {
+    let xml = fs::read_to_string(path).with_context(|| format!("failed to read {path}"))?;
+    FixDictionary::from_xml_with(&xml, allow_partial())
+        .with_context(|| format!("failed to parse FIX XML from {path}"))
+}
+
+/// What a cache file was built from; it is only used when every part matches.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct CacheHeader {
+    version: String,
+    source: String,
+}
+
+/// Dictionary cache files in one directory, written and read by one crate version.
+#[derive(Debug)]
+pub struct DiskCache {
+    dir: PathBuf,
+    version: String,
+}
+
+impl DiskCache {
+    pub fn new(dir: PathBuf) -> Self {
+        Self {
+            dir,
+            version: env!("CARGO_PKG_VERSION").to_string(),
+        }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn parse_embedded(&self, xml_id: &str, xml: &str) -> anyhow::Result<FixDictionary> {
+        self.get_or_parse(&format!("FIX{xml_id}"), &embedded_source(xml), || {
+            FixDictionary::from_xml(xml)
+        })
+    }
+
+    /// Custom dictionaries are named after a hash of their full path, so editing a
+    /// file replaces its cache entry rather than adding another.
+    fn parse_file(&self, path: &str) -> anyhow::Result<FixDictionary> {
+        let meta = fs::metadata(path).with_context(|| format!("failed to read {path}"))?;
+        let full_path = fs::canonicalize(path).unwrap_or_else(|_| PathBuf::from(path));
+        let digest = Sha256::digest(full_path.to_string_lossy().as_bytes());
+        let hash: String = digest[..8].iter().map(|b| format!("{b:02x}")).collect();
+        let modified = meta
+            .modified()
+            .ok()
+            .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+            .map_or(0, |since| since.as_nanos());
+        // A file only readable with --allow-partial-dict is not served to runs without it.
+        let partial = if allow_partial() { ":partial" } else { "" };
+        let source = format!("{}:{}:{modified}{partial}", full_path.display(), meta.len());
+        self.get_or_parse(&format!("xml-{hash}"), &source, || parse_xml_file(path))
+    }
+
+    /// Load `name` from the cache when it was built from `source` by this version,
+    /// otherwise parse it and write it for next time.  Failing to write the cache
+    /// (a read-only home, say) is not an error; the next run simply parses again.
+    fn get_or_parse(
+        &self,
+        name: &str,
+        source: &str,
+        parse: impl FnOnce() -> anyhow::Result<FixDictionary>,
+    ) -> anyhow::Result<FixDictionary> {
+        let header = CacheHeader {
+            version: self.version.clone(),
+            source: source.to_string(),
+        };
+        let path = self.path_for(name);
+        if let Some(dict) = load(&path, &header) {
+            return Ok(dict);
+        }
+        let dict = parse()?;
+        if self.store(&path, &header, &dict).is_ok() {
+            self.remove_other_versions(name);
+        }
+        Ok(dict)
+    }
+
+    fn path_for(&self, name: &str) -> PathBuf {
+        self.dir.join(format!("{name}-{}.bin", self.version))
+    }
+
+    /// Write through a temporary file and rename it into place, so a run reading the
+    /// cache at the same time never sees half a file.
+    fn store(&self, path: &Path, header: &CacheHeader, dict: &FixDictionary) -> io::Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        let tmp = path.with_extension(format!("tmp{}", std::process::id()));
+        let written = (|| {
+            let mut out = BufWriter::new(fs::File::create(&tmp)?);
+            options()
+                .serialize_into(&mut out, header)
+                .and_then(|_| options().serialize_into(&mut out, dict))
+                .map_err(io::Error::other)?;
+            out.flush()
+        })();
+        match written.and_then(|_| fs::rename(&tmp, path)) {
+            Ok(()) => Ok(()),
+            Err(err) => {
+                let _ = fs::remove_file(&tmp);
+                Err(err)
+            }
+        }
+    }
+
+    /// Drop files for `name` left behind by other versions.
+    fn remove_other_versions(&self, name: &str) {
+        let current = format!("{name}-{}.bin", self.version);
+        let prefix = format!("{name}-");
+        for file_name in self.cache_files() {
+            if file_name != current && file_name.starts_with(&prefix) {
+                let _ = fs::remove_file(self.dir.join(file_name));
+            }
+        }
+    }
+
+    fn cache_files(&self) -> Vec<String> {
+        let Ok(entries) = fs::read_dir(&self.dir) else {
+            return Vec::new();
+        };
+        entries
+            .filter_map(|entry| entry.ok()?.file_name().into_string().ok())
+            .filter(|name| name.ends_with(".bin"))
+            .collect()
+    }
+
+    /// Remove every cached dictionary (`--clear-cache`), returning how many there were.
+    pub fn clear(&self) -> io::Result<usize> {
+        let files = self.cache_files();
+        for name in &files {
+            fs::remove_file(self.dir.join(name))?;
+        }
+        Ok(files.len())
+    }
+}
+
+/// Read a cache file, or `None` when it is missing, was built from something other
+/// than `expected`, or is corrupt.  The size limit stops a corrupt length prefix from
+/// asking for more memory than the file could hold.
+fn load(path: &Path, expected: &CacheHeader) -> Option<FixDictionary> {
+    let bytes = fs::read(path).ok()?;
+    let limit = bytes.len() as u64;
+    let mut reader = bytes.as_slice();
+    let header: CacheHeader = options()
+        .with_limit(limit)
+        .deserialize_from(&mut reader)
+        .ok()?;
+    if header != *expected {
+        return None;
+    }
+    options()
+        .with_limit(limit)
+        .deserialize_from(&mut reader)
+        .ok()
+}
+
+/// The cache source of an embedded dictionary: its SHA-256, taken at build time for the
+/// XML compiled in and computed here for anything else passed as embedded.
+fn embedded_source(xml: &str) -> String {
+    let hash = fix::embedded_xml_sha256(xml).map_or_else(
+        || {
+            Sha256::digest(xml.as_bytes())
+                .iter()
+                .map(|b| format!("{b:02x}"))
+                .collect()
+        },
+        str::to_string,
+    );
+    format!("embedded:{hash}")
+}
+
+fn options() -> impl Options + Copy {
+    bincode::DefaultOptions::new()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    fn cache_in(dir: &Path, version: &str) -> DiskCache {
+        DiskCache {
+            dir: dir.to_path_buf(),
+            version: version.to_string(),
+        }
+    }
+
+    /// Load FIX 4.4 through `cache`, returning it and whether the XML was parsed.
+    fn load_fix44(cache: &DiskCache) -> (FixDictionary, bool) {
+        let xml = fix::choose_embedded_xml("44").unwrap();
+        let parsed = Cell::new(false);
+        let dict = cache
+            .get_or_parse("FIX44", &embedded_source(xml), || {
+                parsed.set(true);
+                FixDictionary::from_xml(xml)
+            })
+            .unwrap();
+        (dict, parsed.get())
+    }
+
+    #[test]
+    fn second_load_is_served_from_the_cache_unchanged() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = cache_in(dir.path(), "1.0.0");
+        let (parsed, was_parsed) = load_fix44(&cache);
+        assert!(was_parsed);
+        assert!(dir.path().join("FIX44-1.0.0.bin").exists());
+
+        let (cached, was_parsed) = load_fix44(&cache);
+        assert!(!was_parsed, "second load should be a cache hit");
+        assert_eq!(cached, parsed);
+    }
+
+    #[test]
+    fn a_version_bump_ignores_and_replaces_the_old_cache() {
+        let dir = tempfile::tempdir().unwrap();
+        load_fix44(&cache_in(dir.path(), "1.0.0"));
+        // A file from an older version under the new name must not be trusted either.
+        fs::copy(
+            dir.path().join("FIX44-1.0.0.bin"),
+            dir.path().join("FIX44-1.1.0.bin"),
+        )
+        .unwrap();
+
+        let newer = cache_in(dir.path(), "1.1.0");
+        let (_, was_parsed) = load_fix44(&newer);
+        assert!(was_parsed, "stale cache must be re-parsed");
+        assert!(!dir.path().join("FIX44-1.0.0.bin").exists());
+        let (_, was_parsed) = load_fix44(&newer);
+        assert!(!was_parsed);
+    }
+
+    #[test]
+    fn a_corrupt_cache_file_falls_back_to_the_xml() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = cache_in(dir.path(), "1.0.0");
+        let (parsed, _) = load_fix44(&cache);
+        let path = dir.path().join("FIX44-1.0.0.bin");
+
+        let mut bytes = fs::read(&path).unwrap();
+        bytes.truncate(bytes.len() / 2);
+        fs::write(&path, &bytes).unwrap();
+        let (recovered, was_parsed) = load_fix44(&cache);
+        assert!(was_parsed);
+        assert_eq!(recovered, parsed);
+
+        fs::write(&path, [0xff; 64]).unwrap();
+        let (recovered, was_parsed) = load_fix44(&cache);
+        assert!(was_parsed);
+        assert_eq!(recovered, parsed);
+        assert!(!load_fix44(&cache).1, "the rewritten file is used again");
+    }
+
+    #[test]
+    fn an_embedded_edit_of_the_same_length_is_not_served_from_the_cache() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = cache_in(dir.path(), "1.0.0");
+        let xml = fix::choose_embedded_xml("44").unwrap();
+        let runtime: String = Sha256::digest(xml.as_bytes())
+            .iter()
+            .map(|b| format!("{b:02x}"))
+            .collect();
+        assert_eq!(fix::embedded_xml_sha256(xml), Some(runtime.as_str()));
+
+        let first = cache.parse_embedded("44", xml).unwrap();
+        let edited = xml.replacen("name='MsgType'", "name='MsgKind'", 1);
+        assert_eq!(edited.len(), xml.len());
+        let reparsed = cache.parse_embedded("44", &edited).unwrap();
+        assert_ne!(
+            reparsed, first,
+            "an edited dictionary must not come from the cache"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "dict-fix42")]
+    fn custom_files_are_keyed_by_size_and_modification_time() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = cache_in(&dir.path().join("cache"), "1.0.0");
+        let xml_path = dir.path().join("custom.xml");
+        let xml = fix::choose_embedded_xml("42").unwrap();
+        fs::write(&xml_path, xml).unwrap();
+        let path = xml_path.to_str().unwrap();
+
+        let first = cache.parse_file(path).unwrap();
+        assert_eq!(cache.cache_files().len(), 1);
+        assert_eq!(cache.parse_file(path).unwrap(), first);
+
+        let edited = xml.replacen("name='MsgType'", "name='MsgKind'", 1);
+        fs::write(&xml_path, edited).unwrap();
+        let reparsed = cache.parse_file(path).unwrap();
+        assert_ne!(
+            reparsed, first,
+            "an edited file must not come from the cache"
+        );
+        assert_eq!(cache.cache_files().len(), 1);
+        assert_eq!(cache.clear().unwrap(), 1);
+        assert!(cache.cache_files().is_empty());
+    }
+}
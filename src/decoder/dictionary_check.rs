@@ -0,0 +1,257 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// SPDX-FileCopyrightText: 2025 Steve Clarke <stephenlclarke@mac.com> - https://xyzzy.tools
+
+//! Consistency checks for FIX dictionaries.  Hand-merged XML can define a field
+//! number twice, give one name two numbers, reference fields that do not exist or
+//! count a group with a field that is not `NUMINGROUP`; lookups then silently keep
+//! whichever definition came last.  The checks report every such finding instead.
+
+use crate::decoder::schema::{FieldRef, FixDictionary, GroupDef};
+use std::collections::HashMap;
+use std::fmt;
+
+/// One inconsistency found in a dictionary.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DictionaryWarning {
+    /// Several `<field>` entries share a number; the names are in file order.
+    DuplicateFieldNumber { number: u32, names: Vec<String> },
+    /// One field name is defined with several numbers, in file order.
+    DuplicateFieldName { name: String, numbers: Vec<u32> },
+    /// A message, component or group refers to a field that is not defined.
+    UnknownField { location: String, name: String },
+    /// A group's count field exists but is not typed `NUMINGROUP`.
+    GroupCountType {
+        location: String,
+        group: String,
+        field_type: String,
+    },
+}
+
+impl fmt::Display for DictionaryWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::DuplicateFieldNumber { number, names } => write!(
+                f,
+                "field number {number} is defined {} times ({})",
+                names.len(),
+                names.join(", ")
+            ),
+            Self::DuplicateFieldName { name, numbers } => {
+                let numbers: Vec<String> = numbers.iter().map(u32::to_string).collect();
+                write!(
+                    f,
+                    "field name {name} is defined with numbers {}",
+                    numbers.join(", ")
+                )
+            }
+            Self::UnknownField { location, name } => {
+                write!(f, "{location} references undefined field {name}")
+            }
+            Self::GroupCountType {
+                location,
+                group,
+                field_type,
+            } => write!(
+                f,
+                "{location} declares group {group} but its count field has type {field_type}, expected NUMINGROUP"
+            ),
+        }
+    }
+}
+
+impl FixDictionary {
+    /// Parse a dictionary as [`FixDictionary::from_xml`] does and check it.
+    pub fn from_xml_with_warnings(xml: &str) -> anyhow::Result<(Self, Vec<DictionaryWarning>)> {
+        let dict = Self::from_xml(xml)?;
+        let warnings = dict.warnings();
+        Ok((dict, warnings))
+    }
+
+    /// Every inconsistency in the dictionary: duplicate field numbers and names first,
+    /// then field references from the header, trailer, messages and components.
+    pub fn warnings(&self) -> Vec<DictionaryWarning> {
+        let mut warnings = Vec::new();
+        // In file order, each indexed by number or name so large dictionaries stay linear.
+        let mut names_by_number: Vec<(u32, Vec<String>)> = Vec::new();
+        let mut numbers_by_name: Vec<(String, Vec<u32>)> = Vec::new();
+        let mut number_index: HashMap<u32, usize> = HashMap::new();
+        let mut name_index: HashMap<&str, usize> = HashMap::new();
+        for field in &self.fields.items {
+            match number_index.get(&field.number) {
+                Some(&idx) => names_by_number[idx].1.push(field.name.clone()),
+                None => {
+                    number_index.insert(field.number, names_by_number.len());
+                    names_by_number.push((field.number, vec![field.name.clone()]));
+                }
+            }
+            match name_index.get(field.name.as_str()) {
+                Some(&idx) => {
+                    let numbers = &mut numbers_by_name[idx].1;
+                    if !numbers.contains(&field.number) {
+                        numbers.push(field.number);
+                    }
+                }
+                None => {
+                    name_index.insert(&field.name, numbers_by_name.len());
+                    numbers_by_name.push((field.name.clone(), vec![field.number]));
+                }
+            }
+        }
+        warnings.extend(
+            names_by_number
+                .into_iter()
+                .filter(|(_, names)| names.len() > 1)
+                .map(|(number, names)| DictionaryWarning::DuplicateFieldNumber { number, names }),
+        );
+        warnings.extend(
+            numbers_by_name
+                .into_iter()
+                .filter(|(_, numbers)| numbers.len() > 1)
+                .map(|(name, numbers)| DictionaryWarning::DuplicateFieldName { name, numbers }),
+        );
+
+        let types: HashMap<&str, &str> = self
+            .fields
+            .items
+            .iter()
+            .map(|field| (field.name.as_str(), field.field_type.as_str()))
+            .collect();
+        let mut check = |location: String, fields: &[FieldRef], groups: &[GroupDef]| {
+            check_members(&types, &location, fields, groups, &mut warnings);
+        };
+        check(
+            "header".to_string(),
+            &self.header.fields,
+            &self.header.groups,
+        );
+        check(
+            "trailer".to_string(),
+            &self.trailer.fields,
+            &self.trailer.groups,
+        );
+        for msg in &self.messages.items {
+            check(
+                format!("message {} ({})", msg.name, msg.msg_type),
+                &msg.fields,
+                &msg.groups,
+            );
+        }
+        for comp in &self.components.items {
+            check(
+                format!("component {}", comp.name),
+                &comp.fields,
+                &comp.groups,
+            );
+        }
+        warnings
+    }
+}
+
+fn check_members(
+    types: &HashMap<&str, &str>,
+    location: &str,
+    fields: &[FieldRef],
+    groups: &[GroupDef],
+    warnings: &mut Vec<DictionaryWarning>,
+) {
+    for field in fields {
+        if !types.contains_key(field.name.as_str()) {
+            warnings.push(DictionaryWarning::UnknownField {
+                location: location.to_string(),
+                name: field.name.clone(),
+            });
+        }
+    }
+    for group in groups {
+        match types.get(group.name.as_str()) {
+            None => warnings.push(DictionaryWarning::UnknownField {
+                location: location.to_string(),
+                name: group.name.clone(),
+            }),
+            Some(field_type) if !field_type.eq_ignore_ascii_case("NUMINGROUP") => {
+                warnings.push(DictionaryWarning::GroupCountType {
+                    location: location.to_string(),
+                    group: group.name.clone(),
+                    field_type: field_type.to_string(),
+                })
+            }
+            Some(_) => {}
+        }
+        let inner = format!("{location} group {}", group.name);
+        check_members(types, &inner, &group.fields, &group.groups, warnings);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A minimal dictionary with `fields` and one message holding `body`.
+    fn warnings_for(fields: &str, body: &str) -> Vec<String> {
+        let xml = format!(
+            "<fix type='FIX' major='4' minor='4'>
+  <header><field name='BeginString' required='Y'/></header>
+  <trailer><field name='CheckSum' required='Y'/></trailer>
+  <messages><message name='Order' msgtype='D' msgcat='app'>{body}</message></messages>
+  <components/>
+  <fields>
+    <field number='8' name='BeginString' type='STRING'/>
+    <field number='10' name='CheckSum' type='STRING'/>
+    {fields}
+  </fields>
+</fix>"
+        );
+        let (_, warnings) = FixDictionary::from_xml_with_warnings(&xml).unwrap();
+        warnings.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn clean_dictionaries_have_no_warnings() {
+        let fields = "<field number='453' name='NoPartyIDs' type='NUMINGROUP'/>
+            <field number='448' name='PartyID' type='STRING'/>";
+        let body = "<group name='NoPartyIDs'><field name='PartyID'/></group>";
+        assert!(warnings_for(fields, body).is_empty());
+    }
+
+    #[test]
+    fn duplicate_field_numbers_and_names_are_reported() {
+        let fields = "<field number='5001' name='VenueFlag' type='CHAR'/>
+            <field number='5001' name='VenueFlagOld' type='CHAR'/>
+            <field number='5002' name='VenueFlag' type='CHAR'/>";
+        assert_eq!(
+            warnings_for(fields, ""),
+            [
+                "field number 5001 is defined 2 times (VenueFlag, VenueFlagOld)",
+                "field name VenueFlag is defined with numbers 5001, 5002",
+            ]
+        );
+    }
+
+    #[test]
+    fn undefined_field_references_are_reported_with_their_location() {
+        let fields = "<field number='453' name='NoPartyIDs' type='NUMINGROUP'/>";
+        let body =
+            "<field name='ClOrdId'/><group name='NoPartyIDs'><field name='PartyId'/></group>";
+        assert_eq!(
+            warnings_for(fields, body),
+            [
+                "message Order (D) references undefined field ClOrdId",
+                "message Order (D) group NoPartyIDs references undefined field PartyId",
+            ]
+        );
+    }
+
+    #[test]
+    fn group_count_fields_must_be_numingroup() {
+        let fields = "<field number='453' name='NoPartyIDs' type='INT'/>
+            <field number='448' name='PartyID' type='STRING'/>";
+        let body = "<group name='NoPartyIDs'><field name='PartyID'/></group><group name='NoLegs'/>";
+        assert_eq!(
+            warnings_for(fields, body),
+            [
+                "message Order (D) declares group NoPartyIDs but its count field has type INT, expected NUMINGROUP",
+                "message Order (D) references undefined field NoLegs",
+            ]
+        );
+    }
+}
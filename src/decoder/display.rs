@@ -7,10 +7,13 @@
 //! The module-level comment keeps the tone informal yet informative.
 
 use crate::decoder::colours::{ColourPalette, palette};
-use crate::decoder::layout::{NEST_INDENT, TAG_WIDTH};
+use crate::decoder::layout::{NEST_INDENT, TAG_WIDTH, VALUE_END, VALUE_START};
+use crate::decoder::raw_bytes;
 use crate::decoder::schema::{
-    ComponentNode, Field, FieldNode, GroupNode, MessageNode, SchemaTree, Value,
+    ComponentNode, Field, FieldNode, GroupNode, MessageNode, SchemaMatches, SchemaTree, Value,
 };
+use crate::decoder::tag_lookup::{FieldProvenance, is_multi_value_type};
+use std::borrow::Cow;
 use std::cmp;
 use std::collections::HashMap;
 use std::fmt;
@@ -33,6 +36,8 @@ pub struct DisplayStyle {
     colours: ColourPalette,
     columns: bool,
     layout: Option<ColumnLayout>,
+    /// Columns the layouts may fill, see [`output_width`].
+    width: usize,
 }
 
 impl DisplayStyle {
@@ -41,6 +46,15 @@ impl DisplayStyle {
             colours,
             columns,
             layout: None,
+            width: output_width(None),
+        }
+    }
+
+    /// Lay columns out to `width` (`--width`) instead of the terminal's width.
+    pub fn with_width(self, width: Option<usize>) -> Self {
+        Self {
+            width: output_width(width),
+            ..self
         }
     }
 
@@ -89,12 +103,12 @@ impl LayoutStats {
         self.max_indent = self.max_indent.max(indent);
     }
 
-    fn finalize(self) -> Option<ColumnLayout> {
+    fn finalize(self, width: usize) -> Option<ColumnLayout> {
         if self.max_entry_len == 0 {
             return None;
         }
         let column_width = self.max_entry_len + 2;
-        let usable_width = terminal_width().saturating_sub(self.max_indent);
+        let usable_width = width.saturating_sub(self.max_indent);
         let columns = cmp::max(1, usable_width / column_width);
         Some(ColumnLayout {
             column_width,
@@ -104,14 +118,266 @@ impl LayoutStats {
     }
 }
 
-pub(crate) fn terminal_width() -> usize {
-    if let Some((Width(w), _)) = terminal_size() {
-        w as usize
+/// Larger XML payloads are shown as a plain value.
+const XML_SIZE_LIMIT: usize = 64 * 1024;
+
+/// XML nested deeper than this is shown as a plain value.
+const XML_DEPTH_LIMIT: usize = 20;
+
+/// Per-run rendering options for decoded messages, carried on the decoding context so
+/// two runs in one process (or in parallel tests) never see each other's settings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DisplayOptions {
+    /// `--width`: the width used for separators and wrapping, which matters when
+    /// output is redirected and there is no terminal to ask.
+    pub width: Option<usize>,
+    /// `--max-value-width`: displayed field values are cut to this many visible
+    /// characters.
+    pub max_value_width: Option<usize>,
+    /// `--decode-xml`: pretty-print XML carried in DATA fields such as XmlData(213).
+    pub decode_xml: bool,
+    /// `--error-locations`: follow each validation error with where it sits in the
+    /// message.
+    pub error_locations: bool,
+    /// `--md-ladder`: follow MarketData W and X messages with their NoMDEntries(268)
+    /// as a price ladder.
+    pub md_ladder: bool,
+    /// `--show-dict`: follow each decoded message with the dictionary that decoded it.
+    pub show_dict: bool,
+    /// Put a backslash before each display delimiter found inside a value, so a
+    /// message shown with `--delimiter '|'` can be read back field by field
+    /// (`--no-escape` clears it).
+    pub escape_delimiter: bool,
+    /// `--prefix-file`: columns taken by the file tag in front of each output line.
+    pub line_prefix_width: usize,
+}
+
+impl Default for DisplayOptions {
+    fn default() -> Self {
+        Self {
+            width: None,
+            max_value_width: None,
+            decode_xml: false,
+            error_locations: false,
+            md_ladder: false,
+            show_dict: false,
+            escape_delimiter: true,
+            line_prefix_width: 0,
+        }
+    }
+}
+
+impl DisplayOptions {
+    /// The columns output may fill: `--width` or the terminal's, less any file tag.
+    pub(crate) fn terminal_width(&self) -> usize {
+        output_width(self.width).saturating_sub(self.line_prefix_width)
+    }
+
+    /// The columns values are wrapped to: `--width`, else the terminal's, less any
+    /// file tag.  `None` when stdout is not a terminal and no `--width` was given, so
+    /// piped output keeps each value on one line for `grep` and friends.
+    pub(crate) fn wrap_width(&self) -> Option<usize> {
+        let width = match self.width {
+            Some(width) => width,
+            None => {
+                let (Width(w), _) = terminal_size()?;
+                w as usize
+            }
+        };
+        Some(width.saturating_sub(self.line_prefix_width))
+    }
+
+    /// Render a field value for display: control characters and bytes that are not
+    /// UTF-8 become `\xNN` escapes and, under `--max-value-width`, anything past the
+    /// limit is replaced by a `… (+K bytes)` marker counting the bytes left out.  Only
+    /// ever used for output; checksums and validation keep working on the original value.
+    pub(crate) fn display_value<'v>(&self, value: &'v str) -> Cow<'v, str> {
+        render_value(value, self.max_value_width.unwrap_or(usize::MAX))
+    }
+
+    /// Whether `c`, met inside a message shown with `delimiter` in place of SOH, is a
+    /// value's own copy of the delimiter that needs a backslash in front of it.
+    pub(crate) fn escapes_delimiter(&self, c: char, delimiter: char) -> bool {
+        c == delimiter && c != '\u{0001}' && self.escape_delimiter
+    }
+
+    /// `value` with a backslash before each display delimiter it holds, e.g. `a\|b`.
+    pub(crate) fn escape_delimiter<'v>(&self, value: &'v str, delimiter: char) -> Cow<'v, str> {
+        if !value.chars().any(|c| self.escapes_delimiter(c, delimiter)) {
+            return Cow::Borrowed(value);
+        }
+        let mut out = String::with_capacity(value.len() + 2);
+        for c in value.chars() {
+            if self.escapes_delimiter(c, delimiter) {
+                out.push('\\');
+            }
+            out.push(c);
+        }
+        Cow::Owned(out)
+    }
+}
+
+/// The columns available for output: `width` (from `--width`) when given, else the
+/// terminal's, else 80.
+pub fn output_width(width: Option<usize>) -> usize {
+    match width {
+        Some(width) => width,
+        None => match terminal_size() {
+            Some((Width(w), _)) => w as usize,
+            None => 80,
+        },
+    }
+}
+
+fn render_value(value: &str, limit: usize) -> Cow<'_, str> {
+    if !value.chars().any(needs_escape) && value.chars().nth(limit).is_none() {
+        return Cow::Borrowed(value);
+    }
+    let mut out = String::with_capacity(value.len().min(limit.saturating_add(16)));
+    let mut shown = 0;
+    for (idx, c) in value.char_indices() {
+        let mut escaped = String::new();
+        if needs_escape(c) {
+            push_control_escape(&mut escaped, c);
+        } else {
+            escaped.push(c);
+        }
+        let width = escaped.chars().count();
+        if shown + width > limit {
+            out.push_str(&format!("… (+{} bytes)", value.len() - idx));
+            break;
+        }
+        out.push_str(&escaped);
+        shown += width;
+    }
+    Cow::Owned(out)
+}
+
+/// Whether `c` is shown escaped: a control character, a byte read from the log that
+/// was not valid UTF-8, or one of the markers the prettifier lays values out with.
+pub(crate) fn needs_escape(c: char) -> bool {
+    c.is_control() || raw_bytes::byte_of(c).is_some() || c == VALUE_START || c == VALUE_END
+}
+
+/// Append `c` as `\xNN` escapes, one per UTF-8 byte, so it cannot act on the terminal.
+/// Line breaks, which free text such as Text(58) sometimes carries, read as `\n` and
+/// `\r` instead, and a byte that was not UTF-8 as that byte.
+pub(crate) fn push_control_escape(out: &mut String, c: char) {
+    if let Some(b) = raw_bytes::byte_of(c) {
+        out.push_str(&format!("\\x{b:02X}"));
+        return;
+    }
+    match c {
+        '\n' => out.push_str("\\n"),
+        '\r' => out.push_str("\\r"),
+        _ => {
+            let mut buf = [0u8; 4];
+            for b in c.encode_utf8(&mut buf).bytes() {
+                out.push_str(&format!("\\x{b:02X}"));
+            }
+        }
+    }
+}
+
+/// Bytes of a DATA value shown by [`data_preview`].
+const DATA_PREVIEW_BYTES: usize = 32;
+
+/// Show a DATA value, which may hold binary such as credentials, as its length and a
+/// hex dump of its first [`DATA_PREVIEW_BYTES`] bytes, e.g. `<3 bytes: AB CD EF>`.
+pub(crate) fn data_preview(value: &str) -> String {
+    let bytes = raw_bytes::to_bytes(value);
+    if bytes.is_empty() {
+        return "<0 bytes>".to_string();
+    }
+    let hex: Vec<String> = bytes
+        .iter()
+        .take(DATA_PREVIEW_BYTES)
+        .map(|b| format!("{b:02X}"))
+        .collect();
+    let more = if bytes.len() > DATA_PREVIEW_BYTES {
+        " ..."
     } else {
-        80
+        ""
+    };
+    format!("<{} bytes: {}{more}>", bytes.len(), hex.join(" "))
+}
+
+/// Render an XML value as one element or text node per line, indented two spaces
+/// per level from `indent` and wrapped in `colour`.  Returns `None` when the value
+/// is not well-formed XML or exceeds [`XML_SIZE_LIMIT`] or [`XML_DEPTH_LIMIT`], so
+/// the caller can fall back to showing it as a plain value.
+pub(crate) fn render_xml_value(
+    value: &str,
+    indent: usize,
+    colour: &str,
+    reset: &str,
+) -> Option<String> {
+    let trimmed = value.trim();
+    if trimmed.len() > XML_SIZE_LIMIT || !trimmed.starts_with('<') {
+        return None;
+    }
+    let doc = roxmltree::Document::parse(trimmed).ok()?;
+    let mut lines = Vec::new();
+    collect_xml_lines(doc.root_element(), 0, &mut lines)?;
+    let mut out = String::new();
+    for (depth, line) in lines {
+        out.push_str(&format!(
+            "{}{colour}{line}{reset}\n",
+            " ".repeat(indent + depth * 2)
+        ));
+    }
+    Some(out)
+}
+
+/// The lines of `node` and its children, each with its depth.  Text and attribute
+/// values are escaped as field values are, since XML admits C1 controls such as
+/// `&#x9B;` (CSI) that would otherwise act on the terminal.
+fn collect_xml_lines(
+    node: roxmltree::Node,
+    depth: usize,
+    lines: &mut Vec<(usize, String)>,
+) -> Option<()> {
+    if depth >= XML_DEPTH_LIMIT {
+        return None;
     }
+    let name = node.tag_name().name();
+    let mut open = format!("<{name}");
+    for attr in node.attributes() {
+        open.push_str(&format!(
+            " {}=\"{}\"",
+            attr.name(),
+            render_value(attr.value(), usize::MAX)
+        ));
+    }
+    let children: Vec<_> = node
+        .children()
+        .filter(|child| child.is_element() || child.text().is_some_and(|t| !t.trim().is_empty()))
+        .collect();
+    match children.as_slice() {
+        [] => lines.push((depth, format!("{open}/>"))),
+        [text] if text.is_text() => {
+            let text = render_value(text.text().unwrap_or_default().trim(), usize::MAX);
+            lines.push((depth, format!("{open}>{text}</{name}>")));
+        }
+        _ => {
+            lines.push((depth, format!("{open}>")));
+            for child in children {
+                if child.is_element() {
+                    collect_xml_lines(child, depth + 1, lines)?;
+                } else if let Some(text) = child.text() {
+                    lines.push((
+                        depth + 1,
+                        render_value(text.trim(), usize::MAX).into_owned(),
+                    ));
+                }
+            }
+            lines.push((depth, format!("</{name}>")));
+        }
+    }
+    Some(())
 }
 
+/// Terminal columns `text` takes: one per character, skipping ANSI colour sequences.
 pub(crate) fn visible_width(text: &str) -> usize {
     let mut width = 0;
     let mut in_esc = false;
@@ -131,12 +397,112 @@ pub(crate) fn visible_width(text: &str) -> usize {
             i += 1;
             continue;
         }
-        width += 1;
+        // Count the lead byte of each UTF-8 character, not its continuation bytes.
+        if b & 0xC0 != 0x80 {
+            width += 1;
+        }
         i += 1;
     }
     width
 }
 
+/// `text` cut into pieces of at most `width` visible characters.  ANSI colour sequences
+/// stay with the character after them, or with the last piece when nothing follows.
+pub(crate) fn split_visible(text: &str, width: usize) -> Vec<&str> {
+    let width = width.max(1);
+    let mut pieces = Vec::new();
+    let mut start = 0;
+    let mut shown = 0;
+    let mut in_esc = false;
+    let mut escapes_from = None;
+    for (idx, c) in text.char_indices() {
+        if in_esc {
+            in_esc = c != 'm';
+            continue;
+        }
+        if c == '\u{1b}' {
+            in_esc = true;
+            escapes_from.get_or_insert(idx);
+            continue;
+        }
+        if shown == width {
+            let at = escapes_from.unwrap_or(idx);
+            pieces.push(&text[start..at]);
+            start = at;
+            shown = 0;
+        }
+        escapes_from = None;
+        shown += 1;
+    }
+    pieces.push(&text[start..]);
+    pieces
+}
+
+/// `text` cut into lines of at most `width` visible characters, broken at spaces, which
+/// are dropped.  A word wider than a line is cut where it reaches the end, except the
+/// word ending in a `… (+K bytes)` marker, which is kept whole with its marker even
+/// when that overflows the line.
+pub(crate) fn wrap_visible(text: &str, width: usize) -> Vec<&str> {
+    let width = width.max(1);
+    let glued = truncated_tail(text).unwrap_or(text.len());
+    let mut lines = Vec::new();
+    // The current line as a byte range of `text`, and how many columns it fills.
+    let mut line: Option<(usize, usize, usize)> = None;
+    let mut start = 0;
+    let breaks = text
+        .char_indices()
+        .filter(|&(idx, c)| c == ' ' && idx < glued)
+        .map(|(idx, _)| idx)
+        .chain(std::iter::once(text.len()));
+    for end in breaks {
+        let (word, word_start) = (&text[start..end], start);
+        start = (end + 1).min(text.len());
+        let word_width = visible_width(word);
+        if let Some((from, to, shown)) = line {
+            if shown + 1 + word_width <= width {
+                line = Some((from, end, shown + 1 + word_width));
+                continue;
+            }
+            lines.push(&text[from..to]);
+        }
+        if word_width > width && word_start < glued {
+            let mut pieces = split_visible(word, width);
+            let last = pieces.pop().unwrap_or_default();
+            lines.extend(pieces);
+            line = Some((end - last.len(), end, visible_width(last)));
+        } else {
+            line = Some((word_start, end, word_width));
+        }
+    }
+    if let Some((from, to, _)) = line {
+        lines.push(&text[from..to]);
+    }
+    lines
+}
+
+/// Where the word ending in the `… (+K bytes)` marker of a truncated value starts.
+fn truncated_tail(text: &str) -> Option<usize> {
+    let marker = text.rfind("… (+")?;
+    let count = &text[marker + "… (+".len()..];
+    let rest = count.trim_start_matches(|c: char| c.is_ascii_digit());
+    if rest.len() == count.len() || !rest.starts_with(" bytes)") {
+        return None;
+    }
+    Some(text[..marker].rfind(' ').map_or(0, |space| space + 1))
+}
+
+/// The ANSI colour sequences `text` starts with, e.g. the colour of a value.
+pub(crate) fn leading_escapes(text: &str) -> &str {
+    let mut end = 0;
+    while text[end..].starts_with('\u{1b}') {
+        match text[end..].find('m') {
+            Some(at) => end += at + 1,
+            None => break,
+        }
+    }
+    &text[..end]
+}
+
 pub(crate) fn pad_ansi(text: &str, width: usize) -> String {
     let visible = visible_width(text);
     if visible >= width {
@@ -188,12 +554,44 @@ fn format_required(required: bool, colours: ColourPalette) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::decoder::schema::ValuesWrapper;
+    use crate::decoder::schema::{ComponentUser, ValuesWrapper};
 
     #[test]
     fn visible_width_ignores_ansi_sequences() {
         let coloured = "\u{1b}[31mred\u{1b}[0m";
         assert_eq!(visible_width(coloured), 3);
+        assert_eq!(visible_width("\u{1b}[31mCafé\u{1b}[0m €"), 6);
+    }
+
+    #[test]
+    fn split_visible_counts_only_what_is_shown() {
+        assert_eq!(split_visible("abcdefg", 3), ["abc", "def", "g"]);
+        assert_eq!(split_visible("abc", 3), ["abc"]);
+        assert_eq!(split_visible("", 3), [""]);
+        let coloured = "\u{1b}[32mabcd\u{1b}[0m";
+        assert_eq!(split_visible(coloured, 2), ["\u{1b}[32mab", "cd\u{1b}[0m"]);
+        assert_eq!(leading_escapes(coloured), "\u{1b}[32m");
+        assert_eq!(leading_escapes("abcd"), "");
+        let recoloured = "ab\u{1b}[31mcd";
+        assert_eq!(split_visible(recoloured, 2), ["ab", "\u{1b}[31mcd"]);
+    }
+
+    #[test]
+    fn wrap_visible_breaks_at_spaces_and_keeps_the_truncation_marker_whole() {
+        assert_eq!(
+            wrap_visible("Order rejected by venue", 10),
+            ["Order", "rejected", "by venue"]
+        );
+        assert_eq!(wrap_visible("abcdefg hi", 3), ["abc", "def", "g", "hi"]);
+        assert_eq!(wrap_visible("", 3), [""]);
+        assert_eq!(
+            wrap_visible("some words xxxxxxxxxx… (+490 bytes)", 12),
+            ["some words", "xxxxxxxxxx… (+490 bytes)"]
+        );
+        assert_eq!(
+            wrap_visible("\u{1b}[32mgreen words\u{1b}[0m", 6),
+            ["\u{1b}[32mgreen", "words\u{1b}[0m"]
+        );
     }
 
     #[test]
@@ -227,14 +625,73 @@ mod tests {
         let mut stats = LayoutStats::default();
         stats.record(5, 2);
         stats.record(10, 4);
-        let layout = stats.finalize().expect("layout expected");
+        let layout = stats.finalize(80).expect("layout expected");
         assert!(layout.column_width >= 12);
         assert!(layout.columns >= 1);
     }
 
     #[test]
     fn terminal_width_is_positive() {
-        assert!(terminal_width() > 0);
+        assert!(output_width(None) > 0);
+        assert!(DisplayOptions::default().terminal_width() > 0);
+    }
+
+    #[test]
+    fn render_xml_value_indents_fixml_and_falls_back() {
+        let fixml = r#"<FIXML><ExecRpt ExecID="E1" Stat="2"><Instrmt Sym="VOD.L"/><Txt>filled</Txt></ExecRpt></FIXML>"#;
+        let text = render_xml_value(fixml, 4, "", "").expect("well-formed XML");
+        assert_eq!(
+            text,
+            "    <FIXML>\n      <ExecRpt ExecID=\"E1\" Stat=\"2\">\n        <Instrmt Sym=\"VOD.L\"/>\n        <Txt>filled</Txt>\n      </ExecRpt>\n    </FIXML>\n"
+        );
+
+        assert_eq!(render_xml_value("<a><b></a>", 0, "", ""), None);
+        assert_eq!(render_xml_value("plain text", 0, "", ""), None);
+        let deep = format!("{}{}", "<a>".repeat(25), "</a>".repeat(25));
+        assert_eq!(render_xml_value(&deep, 0, "", ""), None);
+        let huge = format!("<a>{}</a>", "x".repeat(XML_SIZE_LIMIT));
+        assert_eq!(render_xml_value(&huge, 0, "", ""), None);
+    }
+
+    #[test]
+    fn render_xml_value_escapes_control_characters() {
+        let xml = "<Txt Note=\"a&#9;b&#x85;\">&#x9B;2Jwiped<B>&#x9B;31m</B></Txt>";
+        let text = render_xml_value(xml, 0, "", "").expect("well-formed XML");
+        assert!(
+            !text.chars().any(|c| c.is_control() && c != '\n'),
+            "{text:?}"
+        );
+        assert_eq!(
+            text,
+            "<Txt Note=\"a\\x09b\\xC2\\x85\">\n  \\xC2\\x9B2Jwiped\n  <B>\\xC2\\x9B31m</B>\n</Txt>\n"
+        );
+    }
+
+    #[test]
+    fn render_value_escapes_and_truncates() {
+        assert!(matches!(render_value("VOD.L", 5), Cow::Borrowed("VOD.L")));
+        assert_eq!(render_value("abcdefgh", 3), "abc… (+5 bytes)");
+        assert_eq!(render_value("a\tb\u{7f}", usize::MAX), "a\\x09b\\x7F");
+        // An escape that would straddle the limit is dropped whole.
+        assert_eq!(render_value("ab\u{7}cd", 4), "ab… (+3 bytes)");
+        assert_eq!(render_value("ab\ncd", 5), "ab\\nc… (+1 bytes)");
+        assert_eq!(render_value("ab\r\ncd", usize::MAX), "ab\\r\\ncd");
+        let shift_jis = raw_bytes::decode(b"x\x93\x8c");
+        assert_eq!(render_value(&shift_jis, usize::MAX), "x\\x93\\x8C");
+    }
+
+    #[test]
+    fn data_preview_shows_length_and_leading_bytes() {
+        assert_eq!(data_preview("\u{1b}[A"), "<3 bytes: 1B 5B 41>");
+        assert_eq!(data_preview(""), "<0 bytes>");
+        assert_eq!(
+            data_preview(&raw_bytes::decode(b"\x93\x8c")),
+            "<2 bytes: 93 8C>"
+        );
+        let long = data_preview(&"z".repeat(40));
+        assert!(long.starts_with("<40 bytes: 7A 7A"), "{long}");
+        assert_eq!(long.matches("7A").count(), 32);
+        assert!(long.ends_with(" ...>"));
     }
 
     fn sample_value(enum_code: &str, desc: &str) -> Value {
@@ -356,20 +813,173 @@ mod tests {
         let mut messages = BTreeMap::new();
         messages.insert(message.name.clone(), message);
 
+        let mut component_usage = BTreeMap::new();
+        component_usage.insert(
+            "Block".to_string(),
+            [ComponentUser::Message("NewOrder".into())]
+                .into_iter()
+                .collect(),
+        );
+
         SchemaTree {
             fields,
             components,
             messages,
+            component_usage,
+            field_usage_index: Default::default(),
             version: "FIX 4.4".into(),
             service_pack: "-".into(),
         }
     }
 
+    #[test]
+    fn component_usage_lists_referencing_messages() {
+        let schema = schema_with_structures();
+        let mut out = Vec::new();
+        print_component_usage(&mut out, &schema, "Block", 0, palette()).unwrap();
+        let s = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = s.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0], "Used by:");
+        assert!(lines[1].starts_with("    "));
+        assert!(lines[1].contains("NewOrder"));
+        assert!(lines[1].contains(" -> "));
+        assert!(lines[1].contains("Block"));
+
+        let mut out = Vec::new();
+        print_component_usage(&mut out, &schema, "Header", 0, palette()).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "Used by: (none)\n");
+    }
+
+    #[test]
+    fn verbose_tag_details_list_where_the_field_appears() {
+        let schema = schema_with_structures();
+        let render = |field: &Field| {
+            let mut out = Vec::new();
+            print_tag_details_with_writer(&mut out, &schema, field, true, false, 80).unwrap();
+            String::from_utf8(out).unwrap()
+        };
+
+        let msg_type = schema.fields.get("MsgType").unwrap();
+        let s = render(msg_type);
+        assert!(s.contains("Appears in:\n"), "{s}");
+        assert!(s.contains("(header)") && s.contains("NewOrder"), "{s}");
+        assert!(!s.contains("(trailer)"), "{s}");
+
+        let orphan = Field {
+            name: "Orphan".into(),
+            number: 4242,
+            field_type: "STRING".into(),
+            values: Vec::new(),
+            values_wrapper: ValuesWrapper::default(),
+        };
+        assert!(render(&orphan).contains("Appears in: not referenced by any message"));
+    }
+
+    #[test]
+    fn enum_lookup_describes_each_code_of_a_multi_value_field() {
+        let field = Field {
+            name: "ExecInst".into(),
+            number: 18,
+            field_type: "MULTIPLEVALUESTRING".into(),
+            values: vec![
+                sample_value("1", "NOT_HELD"),
+                sample_value("6", "PARTICIPATE"),
+            ],
+            values_wrapper: ValuesWrapper::default(),
+        };
+        let ansi = regex::Regex::new("\x1b\\[[0-9;]*m").unwrap();
+        let render = |value: &str| {
+            let mut out = Vec::new();
+            print_enum_lookup_with_writer(&mut out, &field, value).unwrap();
+            ansi.replace_all(&String::from_utf8(out).unwrap(), "")
+                .into_owned()
+        };
+
+        let s = render("6");
+        assert!(s.contains("ExecInst"), "{s}");
+        assert!(s.ends_with("        6 : PARTICIPATE\n"), "{s}");
+
+        let s = render("1 Z 6");
+        let lines: Vec<&str> = s.lines().skip(1).collect();
+        assert_eq!(
+            lines,
+            [
+                "        1 : NOT_HELD",
+                "        Z : not a valid ExecInst value",
+                "        6 : PARTICIPATE",
+            ]
+        );
+    }
+
+    #[test]
+    fn find_groups_fix44_matches_by_kind() {
+        let xml = crate::fix::choose_embedded_xml("44").unwrap();
+        let schema = SchemaTree::build(crate::decoder::FixDictionary::from_xml(xml).unwrap());
+        let ansi = regex::Regex::new("\x1b\\[[0-9;]*m").unwrap();
+        let render = |limit: Option<usize>| {
+            let mut matches = schema.search("LEG");
+            let total = matches.len();
+            if let Some(limit) = limit {
+                matches.truncate(limit);
+            }
+            let mut out = Vec::new();
+            print_search_results_with_writer(&mut out, &matches, "LEG", false, 80, total).unwrap();
+            ansi.replace_all(&String::from_utf8(out).unwrap(), "")
+                .into_owned()
+        };
+
+        let s = render(None);
+        let titles: Vec<&str> = s.lines().filter(|l| !l.starts_with(' ')).collect();
+        assert_eq!(
+            titles,
+            [
+                "Fields (7):",
+                "Messages (1):",
+                "Components (1):",
+                "Enums (2):"
+            ]
+        );
+        assert!(s.contains("     600: LegSymbol (STRING)\n"), "{s}");
+        assert!(s.contains("    AB: NewOrderMultileg (app)\n"), "{s}");
+        assert!(s.contains("    InstrumentLeg\n"), "{s}");
+        assert!(
+            s.contains("     167 SecurityType: MLEG : MULTILEG_INSTRUMENT\n"),
+            "{s}"
+        );
+
+        let s = render(Some(8));
+        assert!(
+            s.contains("Messages (1):") && !s.contains("Components"),
+            "{s}"
+        );
+        assert!(
+            s.ends_with("... 3 more match(es); raise --limit to see them\n"),
+            "{s}"
+        );
+    }
+
+    #[test]
+    fn highlight_match_marks_the_matched_text_in_the_error_colour() {
+        let colours = crate::decoder::colours::Theme::Dark.palette();
+        assert_eq!(
+            highlight_match("NoLegs", "leg", colours.name, colours),
+            format!(
+                "{}No{}Leg{}{}s{}",
+                colours.name, colours.error, colours.reset, colours.name, colours.reset
+            )
+        );
+        assert_eq!(
+            highlight_match("Side", "leg", colours.name, colours),
+            format!("{}Side{}", colours.name, colours.reset)
+        );
+    }
+
     #[test]
     fn print_field_renders_required_indicator() {
         let node = sample_field_node(true);
         let mut out = Vec::new();
-        print_field(&mut out, &node, 2, palette()).unwrap();
+        print_field(&mut out, &node, 2, palette(), None).unwrap();
         let s = String::from_utf8(out).unwrap();
         assert!(s.contains("999"));
         assert!(s.contains("TestField"));
@@ -397,7 +1007,7 @@ mod tests {
             columns: 2,
             max_indent: 0,
         };
-        print_enum_columns(&mut out, &refs, 0, palette(), Some(layout)).unwrap();
+        print_enum_columns(&mut out, &refs, 0, palette(), Some(layout), 80).unwrap();
         let s = String::from_utf8(out).unwrap();
         // Two entries sorted and rendered in at most two lines.
         assert!(s.contains("A"));
@@ -409,7 +1019,7 @@ mod tests {
     fn compute_values_layout_uses_max_entry() {
         let values = [sample_value("LONG", "desc"), sample_value("S", "short")];
         let refs: Vec<&Value> = values.iter().collect();
-        let layout = compute_values_layout(&refs, 4).expect("layout expected");
+        let layout = compute_values_layout(&refs, 4, 80).expect("layout expected");
         assert!(layout.column_width >= "LONG: desc".len());
         assert!(layout.columns >= 1);
     }
@@ -432,6 +1042,57 @@ mod tests {
         assert!(s.contains("Trailer"));
     }
 
+    #[test]
+    fn depth_and_groups_only_trim_the_message_outline() {
+        let schema = schema_with_structures();
+        let msg = schema.messages.get("NewOrder").unwrap();
+        crate::decoder::colours::disable_colours();
+        let render = |outline: Outline| {
+            let mut out = Vec::new();
+            let style = DisplayStyle::new(palette(), true);
+            RenderContext::new(&mut out, &schema, style, true)
+                .with_outline(outline)
+                .render_message(msg, true, true, 0)
+                .unwrap();
+            String::from_utf8(out).unwrap()
+        };
+        let full = render(Outline::default());
+        let shallow = render(Outline {
+            depth: Some(0),
+            groups_only: false,
+        });
+        assert!(shallow.lines().count() < full.lines().count());
+        assert!(
+            shallow.contains(
+                "\n    Component: Block … (collapsed, 3 fields and 1 component hidden, use --depth)\n"
+            ),
+            "{shallow}"
+        );
+        assert!(shallow.contains(
+            "\n    Group: Allocs (201) … (collapsed, 4 fields and 2 components hidden, use --depth)\n"
+        ));
+
+        let one_level = render(Outline {
+            depth: Some(1),
+            groups_only: false,
+        });
+        assert!(one_level.contains(
+            "\n        Component: Block … (collapsed, 3 fields and 1 component hidden, use --depth)\n"
+        ));
+        assert!(one_level.contains("\n        Group: Nested (200) … (collapsed, 1 field and 0"));
+
+        let groups = render(Outline {
+            depth: None,
+            groups_only: true,
+        });
+        assert!(groups.lines().count() < full.lines().count());
+        assert!(!groups.contains("Header"), "no groups in the header");
+        assert!(!groups.contains("(STRING)"), "{groups}");
+        assert!(groups.contains(
+            "\n     201: Allocs (NUMINGROUP) - (Y)\n         999: TestField (delimiter)\n"
+        ));
+    }
+
     #[test]
     fn render_component_prints_matching_msg_type_enum_only() {
         let schema = schema_with_structures();
@@ -464,8 +1125,8 @@ mod tests {
     fn compute_message_layout_counts_header_and_trailer() {
         let schema = schema_with_structures();
         let msg = schema.messages.get("NewOrder").unwrap();
-        let layout =
-            compute_message_layout(&schema, msg, true, true, 0).expect("layout should be computed");
+        let layout = compute_message_layout(&schema, msg, true, true, 0, 80)
+            .expect("layout should be computed");
         assert!(layout.column_width > 0);
         assert!(layout.columns >= 1);
     }
@@ -522,15 +1183,28 @@ mod tests {
     }
 }
 
+/// Print one field line; `via` is the `--provenance` path of components and groups
+/// the field was flattened in through.
 fn print_field(
     out: &mut dyn Write,
     field: &FieldNode,
     indent_level: usize,
     colours: ColourPalette,
+    via: Option<&[String]>,
 ) -> io::Result<()> {
+    let via = via
+        .map(|path| {
+            format!(
+                " {}[via {}]{}",
+                colours.file,
+                path.join(" > "),
+                colours.reset
+            )
+        })
+        .unwrap_or_default();
     writeln!(
         out,
-        "{}{}{:width$}{}: {}{}{} ({}{}{}){}",
+        "{}{}{:width$}{}: {}{}{} ({}{}{}){}{}",
         indent(indent_level),
         colours.tag,
         field.field.number,
@@ -542,6 +1216,7 @@ fn print_field(
         field.field.field_type,
         colours.reset,
         format_required(field.required, colours),
+        via,
         width = TAG_WIDTH
     )
 }
@@ -571,6 +1246,7 @@ fn print_enum_columns(
     indent_level: usize,
     colours: ColourPalette,
     layout: Option<ColumnLayout>,
+    width: usize,
 ) -> io::Result<()> {
     if values.is_empty() {
         return Ok(());
@@ -579,7 +1255,7 @@ fn print_enum_columns(
     let mut sorted = values.to_vec();
     sorted.sort_by(|a, b| a.enumeration.cmp(&b.enumeration));
 
-    let layout_params = determine_enum_layout(indent_level, layout, &sorted);
+    let layout_params = determine_enum_layout(indent_level, layout, &sorted, width);
     let rows = sorted.len().div_ceil(layout_params.cols);
 
     for row in 0..rows {
@@ -614,6 +1290,7 @@ fn determine_enum_layout(
     indent_level: usize,
     layout: Option<ColumnLayout>,
     values: &[&Value],
+    width: usize,
 ) -> EnumLayout {
     if let Some(layout) = layout {
         return EnumLayout {
@@ -628,7 +1305,7 @@ fn determine_enum_layout(
         .map(|v| v.enumeration.len() + 2 + v.description.len())
         .max()
         .unwrap_or(0);
-    let usable_width = terminal_width().saturating_sub(indent_level);
+    let usable_width = width.saturating_sub(indent_level);
     let cols = cmp::max(1, usable_width / (max_len + 2));
 
     EnumLayout {
@@ -728,27 +1405,38 @@ fn tag_cell(
     ))
 }
 
-fn print_string_columns(items: &[DisplayCell]) -> io::Result<()> {
+fn print_string_columns(items: &[DisplayCell], width: usize) -> io::Result<()> {
+    write_string_columns(&mut io::stdout().lock(), items, 0, width)
+}
+
+/// Lay `items` out column-major to fit `width` columns, each row indented by
+/// `indent_level`.
+fn write_string_columns(
+    out: &mut dyn Write,
+    items: &[DisplayCell],
+    indent_level: usize,
+    width: usize,
+) -> io::Result<()> {
     if items.is_empty() {
         return Ok(());
     }
 
-    let width = terminal_width();
+    let width = width.saturating_sub(indent_level);
     let max_len = items.iter().map(|s| s.width).max().unwrap_or(0);
     let cols = cmp::max(1, width / (max_len + 2));
     let rows = items.len().div_ceil(cols);
 
-    let mut stdout = io::stdout().lock();
     for row in 0..rows {
+        write!(out, "{}", indent(indent_level))?;
         for col in 0..cols {
             let idx = col * rows + row;
             if idx < items.len() {
-                write_with_padding(&mut stdout, items[idx].width, max_len + 2, |out| {
+                write_with_padding(out, items[idx].width, max_len + 2, |out| {
                     write!(out, "{}", items[idx].text)
                 })?;
             }
         }
-        writeln!(stdout)?;
+        writeln!(out)?;
     }
     Ok(())
 }
@@ -786,6 +1474,16 @@ impl LayoutCacheKey {
     }
 }
 
+/// How much of a `--message` or `--component` structure to show.  `--depth` collapses
+/// components and groups nested deeper than `depth` (those directly in the message body,
+/// or in the component asked for, are at depth 1), and `--groups-only` keeps just the
+/// repeating groups and the components that lead to them.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Outline {
+    pub depth: Option<usize>,
+    pub groups_only: bool,
+}
+
 /// Coordinates rendering to a `Write` sink whilst caching layouts and
 /// enum buffers.  Designed to be short-lived per CLI action.
 struct RenderContext<'a, 'b, W: Write> {
@@ -795,6 +1493,10 @@ struct RenderContext<'a, 'b, W: Write> {
     style: DisplayStyle,
     enum_buf: EnumBuffer<'b>,
     layout_cache: HashMap<LayoutCacheKey, ColumnLayout>,
+    provenance: Option<&'b FieldProvenance>,
+    outline: Outline,
+    /// Depth of the component or group being rendered.
+    depth: usize,
 }
 
 impl<'a, 'b, W: Write> RenderContext<'a, 'b, W> {
@@ -807,9 +1509,56 @@ impl<'a, 'b, W: Write> RenderContext<'a, 'b, W> {
             style,
             enum_buf: Vec::new(),
             layout_cache: HashMap::new(),
+            provenance: None,
+            outline: Outline::default(),
+            depth: 0,
         }
     }
 
+    /// Limit what is shown to `outline`.
+    fn with_outline(mut self, outline: Outline) -> Self {
+        self.outline = outline;
+        self
+    }
+
+    /// Whether the component or group being rendered is beyond `--depth`.
+    fn collapsed(&self) -> bool {
+        self.outline.depth.is_some_and(|limit| self.depth > limit)
+    }
+
+    /// One line standing in for a block beyond `--depth`, e.g.
+    /// `Component: Instrument … (collapsed, 40 fields and 5 components hidden, use --depth)`.
+    fn write_collapsed(
+        &mut self,
+        kind: &str,
+        name: &str,
+        hidden: HiddenCount,
+        indent_level: usize,
+        colours: ColourPalette,
+    ) -> io::Result<()> {
+        writeln!(
+            self.out,
+            "{}{kind}: {}{name}{} … (collapsed, {hidden} hidden, use --depth)",
+            indent(indent_level),
+            colours.name,
+            colours.reset
+        )
+    }
+
+    /// Annotate field lines with the paths in `provenance`.
+    fn with_provenance(mut self, provenance: Option<&'b FieldProvenance>) -> Self {
+        self.provenance = provenance;
+        self
+    }
+
+    /// The provenance path of a field reached through a component or group.
+    fn via(&self, tag: u32) -> Option<&'b [String]> {
+        self.provenance?
+            .get(&tag)
+            .map(Vec::as_slice)
+            .filter(|path| !path.is_empty())
+    }
+
     /// Render a single message definition, optionally including header and
     /// trailer blocks.  Responsible for kicking off component/group rendering.
     fn render_message(
@@ -828,13 +1577,22 @@ impl<'a, 'b, W: Write> RenderContext<'a, 'b, W> {
 
         let shared_style = if self.verbose && self.style.columns_enabled() {
             let schema = self.schema;
+            let width = self.style.width;
             self.style.ensure_layout(|| {
-                compute_message_layout(schema, msg, include_header, include_trailer, indent_level)
+                compute_message_layout(
+                    schema,
+                    msg,
+                    include_header,
+                    include_trailer,
+                    indent_level,
+                    width,
+                )
             })
         } else {
             self.style
         };
 
+        self.depth = 1;
         if include_header && let Some(header) = self.schema.components.get("Header") {
             self.render_component_with_style(Some(msg), header, indent_level, shared_style)?;
         }
@@ -847,7 +1605,9 @@ impl<'a, 'b, W: Write> RenderContext<'a, 'b, W> {
             colours.reset
         )?;
 
-        self.print_field_collection(&msg.fields, indent_level + 2, shared_style)?;
+        if !self.outline.groups_only {
+            self.print_field_collection(&msg.fields, indent_level + 2, shared_style)?;
+        }
         for component in &msg.components {
             self.render_component_with_style(
                 Some(msg),
@@ -884,6 +1644,19 @@ impl<'a, 'b, W: Write> RenderContext<'a, 'b, W> {
         indent_level: usize,
         style: DisplayStyle,
     ) -> io::Result<()> {
+        if self.outline.groups_only && !component_has_groups(component) {
+            return Ok(());
+        }
+        if self.collapsed() {
+            let hidden = HiddenCount::of_component(component);
+            return self.write_collapsed(
+                "Component",
+                &component.name,
+                hidden,
+                indent_level,
+                style.colours(),
+            );
+        }
         let style = if self.verbose && style.columns_enabled() && style.layout().is_none() {
             style.with_layout(self.cached_component_layout(component, indent_level))
         } else {
@@ -899,13 +1672,17 @@ impl<'a, 'b, W: Write> RenderContext<'a, 'b, W> {
             colours.reset
         )?;
 
-        for field in &component.fields {
-            print_field(self.out, field, indent_level + NEST_INDENT, colours)?;
-            if self.verbose {
-                self.print_enums_for_field(field, msg, indent_level + NEST_INDENT + 2, style)?;
+        if !self.outline.groups_only {
+            for field in &component.fields {
+                let via = self.via(field.field.number);
+                print_field(self.out, field, indent_level + NEST_INDENT, colours, via)?;
+                if self.verbose {
+                    self.print_enums_for_field(field, msg, indent_level + NEST_INDENT + 2, style)?;
+                }
             }
         }
 
+        self.depth += 1;
         for sub in &component.components {
             self.render_component_with_style(msg, sub, indent_level + NEST_INDENT, style)?;
         }
@@ -913,6 +1690,7 @@ impl<'a, 'b, W: Write> RenderContext<'a, 'b, W> {
         for group in &component.groups {
             self.render_group_with_style(group, indent_level + NEST_INDENT, style)?;
         }
+        self.depth -= 1;
         Ok(())
     }
 
@@ -928,6 +1706,14 @@ impl<'a, 'b, W: Write> RenderContext<'a, 'b, W> {
         indent_level: usize,
         style: DisplayStyle,
     ) -> io::Result<()> {
+        if self.collapsed() {
+            let name = match self.schema.fields.get(&group.name) {
+                Some(count_field) => format!("{} ({})", group.name, count_field.number),
+                None => group.name.clone(),
+            };
+            let hidden = HiddenCount::of_group(group);
+            return self.write_collapsed("Group", &name, hidden, indent_level, style.colours());
+        }
         let style = if self.verbose && style.columns_enabled() && style.layout().is_none() {
             style.with_layout(self.cached_group_layout(group, indent_level))
         } else {
@@ -939,7 +1725,8 @@ impl<'a, 'b, W: Write> RenderContext<'a, 'b, W> {
                 required: group.required,
                 field: count_field.clone(),
             };
-            print_field(self.out, &count_node, indent_level, colours)?;
+            let via = self.via(count_node.field.number);
+            print_field(self.out, &count_node, indent_level, colours, via)?;
         } else {
             writeln!(
                 self.out,
@@ -952,8 +1739,28 @@ impl<'a, 'b, W: Write> RenderContext<'a, 'b, W> {
             )?;
         }
 
-        self.print_field_collection(&group.fields, indent_level + NEST_INDENT, style)?;
+        if self.outline.groups_only {
+            if let Some(delim) = group_delimiter(group) {
+                writeln!(
+                    self.out,
+                    "{}{}{:width$}{}: {}{}{} ({}delimiter{})",
+                    indent(indent_level + NEST_INDENT),
+                    colours.tag,
+                    delim.field.number,
+                    colours.reset,
+                    colours.name,
+                    delim.field.name,
+                    colours.reset,
+                    colours.value,
+                    colours.reset,
+                    width = TAG_WIDTH
+                )?;
+            }
+        } else {
+            self.print_field_collection(&group.fields, indent_level + NEST_INDENT, style)?;
+        }
 
+        self.depth += 1;
         for component in &group.components {
             self.render_component_with_style(None, component, indent_level + NEST_INDENT, style)?;
         }
@@ -961,6 +1768,7 @@ impl<'a, 'b, W: Write> RenderContext<'a, 'b, W> {
         for sub_group in &group.groups {
             self.render_group_with_style(sub_group, indent_level + NEST_INDENT, style)?;
         }
+        self.depth -= 1;
         Ok(())
     }
 
@@ -972,7 +1780,8 @@ impl<'a, 'b, W: Write> RenderContext<'a, 'b, W> {
     ) -> io::Result<()> {
         let colours = style.colours();
         for field in fields {
-            print_field(self.out, field, indent_level, colours)?;
+            let via = self.via(field.field.number);
+            print_field(self.out, field, indent_level, colours, via)?;
             if self.verbose {
                 if style.columns_enabled() {
                     let values =
@@ -983,6 +1792,7 @@ impl<'a, 'b, W: Write> RenderContext<'a, 'b, W> {
                         indent_level + 2,
                         colours,
                         style.layout(),
+                        style.width,
                     )?;
                 } else {
                     for value in field.field.values_iter() {
@@ -1025,7 +1835,14 @@ impl<'a, 'b, W: Write> RenderContext<'a, 'b, W> {
 
         if style.columns_enabled() {
             let values = collect_sorted_values(&mut self.enum_buf, field.field.values_iter());
-            print_enum_columns(self.out, values, indent_level, colours, style.layout())?;
+            print_enum_columns(
+                self.out,
+                values,
+                indent_level,
+                colours,
+                style.layout(),
+                style.width,
+            )?;
         } else {
             for value in field.field.values_iter() {
                 print_enum(self.out, value, indent_level, colours)?;
@@ -1043,7 +1860,7 @@ impl<'a, 'b, W: Write> RenderContext<'a, 'b, W> {
         if let Some(layout) = self.layout_cache.get(&key) {
             return Some(*layout);
         }
-        let layout = compute_component_layout(component, indent);
+        let layout = compute_component_layout(component, indent, self.style.width);
         if let Some(value) = layout {
             self.layout_cache.insert(key, value);
         }
@@ -1055,7 +1872,7 @@ impl<'a, 'b, W: Write> RenderContext<'a, 'b, W> {
         if let Some(layout) = self.layout_cache.get(&key) {
             return Some(*layout);
         }
-        let layout = compute_group_layout(group, indent);
+        let layout = compute_group_layout(group, indent, self.style.width);
         if let Some(value) = layout {
             self.layout_cache.insert(key, value);
         }
@@ -1063,7 +1880,76 @@ impl<'a, 'b, W: Write> RenderContext<'a, 'b, W> {
     }
 }
 
-pub fn print_message_columns(schema: &SchemaTree) -> io::Result<()> {
+/// What a collapsed component or group leaves out.  Nested groups count as components,
+/// so the tally reads the same whichever kind of block was collapsed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct HiddenCount {
+    fields: usize,
+    components: usize,
+}
+
+impl HiddenCount {
+    fn of_component(component: &ComponentNode) -> Self {
+        Self::of_block(&component.fields, &component.components, &component.groups)
+    }
+
+    fn of_group(group: &GroupNode) -> Self {
+        Self::of_block(&group.fields, &group.components, &group.groups)
+    }
+
+    fn of_block(fields: &[FieldNode], components: &[ComponentNode], groups: &[GroupNode]) -> Self {
+        let mut count = Self {
+            fields: fields.len() + groups.len(),
+            components: components.len() + groups.len(),
+        };
+        let nested = components
+            .iter()
+            .map(Self::of_component)
+            .chain(groups.iter().map(Self::of_group));
+        for inner in nested {
+            count.fields += inner.fields;
+            count.components += inner.components;
+        }
+        count
+    }
+}
+
+impl fmt::Display for HiddenCount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let plural = |n: usize| if n == 1 { "" } else { "s" };
+        write!(
+            f,
+            "{} field{} and {} component{}",
+            self.fields,
+            plural(self.fields),
+            self.components,
+            plural(self.components)
+        )
+    }
+}
+
+/// Whether `component` holds a repeating group at any depth.
+fn component_has_groups(component: &ComponentNode) -> bool {
+    !component.groups.is_empty() || component.components.iter().any(component_has_groups)
+}
+
+/// The field that starts each entry of `group`: its first field, or the first field of
+/// its first component when the entry opens with a component.
+fn group_delimiter(group: &GroupNode) -> Option<&FieldNode> {
+    fn first_field(component: &ComponentNode) -> Option<&FieldNode> {
+        component
+            .fields
+            .first()
+            .or_else(|| component.components.first().and_then(first_field))
+    }
+    group
+        .fields
+        .first()
+        .or_else(|| group.components.first().and_then(first_field))
+}
+
+/// Print messages in columns fitted to `width` (see [`output_width`]).
+pub fn print_message_columns(schema: &SchemaTree, width: usize) -> io::Result<()> {
     let colours = palette();
     let mut entries: Vec<_> = schema.messages.values().collect();
     entries.sort_by(|a, b| a.msg_type.cmp(&b.msg_type));
@@ -1071,12 +1957,130 @@ pub fn print_message_columns(schema: &SchemaTree) -> io::Result<()> {
         .iter()
         .map(|msg| message_cell(msg, colours))
         .collect();
-    print_string_columns(&cells)
+    print_string_columns(&cells, width)
+}
+
+/// Print `--find` results grouped by kind, with the text that matched highlighted.
+/// `total` is the number of matches before `--limit` cut them down to `matches`;
+/// with `columns` they are laid out to fit `width`.
+pub fn print_search_results(
+    matches: &SchemaMatches,
+    text: &str,
+    columns: bool,
+    width: usize,
+    total: usize,
+) -> io::Result<()> {
+    let stdout = io::stdout();
+    let mut handle = stdout.lock();
+    print_search_results_with_writer(&mut handle, matches, text, columns, width, total)
+}
+
+fn print_search_results_with_writer(
+    out: &mut dyn Write,
+    matches: &SchemaMatches,
+    text: &str,
+    columns: bool,
+    width: usize,
+    total: usize,
+) -> io::Result<()> {
+    let colours = palette();
+    let needle = text.to_ascii_lowercase();
+    let mark = |value: &str, base: &str| highlight_match(value, &needle, base, colours);
+    let fields = matches.fields.iter().map(|field| {
+        DisplayCell::new(format!(
+            "{}{:4}{}: {} ({}{}{})",
+            colours.tag,
+            field.number,
+            colours.reset,
+            mark(&field.name, colours.name),
+            colours.value,
+            field.field_type,
+            colours.reset
+        ))
+    });
+    let messages = matches.messages.iter().map(|msg| {
+        DisplayCell::new(format!(
+            "{}{:>2}{}: {} ({})",
+            colours.tag,
+            msg.msg_type,
+            colours.reset,
+            mark(&msg.name, colours.name),
+            msg.msg_cat
+        ))
+    });
+    let components = matches
+        .components
+        .iter()
+        .map(|component| DisplayCell::new(mark(&component.name, colours.name)));
+    let enums = matches.enums.iter().map(|(field, value)| {
+        DisplayCell::new(format!(
+            "{}{:4}{} {}{}{}: {} : {}",
+            colours.tag,
+            field.number,
+            colours.reset,
+            colours.name,
+            field.name,
+            colours.reset,
+            mark(&value.enumeration, colours.value),
+            mark(&value.description, colours.enumeration)
+        ))
+    });
+    let sections: [(&str, Vec<DisplayCell>); 4] = [
+        ("Fields", fields.collect()),
+        ("Messages", messages.collect()),
+        ("Components", components.collect()),
+        ("Enums", enums.collect()),
+    ];
+    for (title, cells) in sections.iter().filter(|(_, cells)| !cells.is_empty()) {
+        writeln!(
+            out,
+            "{}{title}{} ({}):",
+            colours.title,
+            colours.reset,
+            cells.len()
+        )?;
+        if columns {
+            write_string_columns(out, cells, 4, width)?;
+        } else {
+            for cell in cells {
+                writeln!(out, "{}{}", indent(4), cell.text)?;
+            }
+        }
+    }
+    let shown = matches.len();
+    if shown < total {
+        writeln!(
+            out,
+            "... {} more match(es); raise --limit to see them",
+            total - shown
+        )?;
+    }
+    Ok(())
+}
+
+/// `value` in the `base` colour with the first case-insensitive occurrence of `needle`
+/// (already lowercased) picked out in the error colour.
+fn highlight_match(value: &str, needle: &str, base: &str, colours: ColourPalette) -> String {
+    match value.to_ascii_lowercase().find(needle) {
+        Some(start) if !needle.is_empty() => {
+            let end = start + needle.len();
+            format!(
+                "{base}{}{}{}{}{base}{}{}",
+                &value[..start],
+                colours.error,
+                &value[start..end],
+                colours.reset,
+                &value[end..],
+                colours.reset
+            )
+        }
+        _ => format!("{base}{value}{}", colours.reset),
+    }
 }
 
 /// Print component names in columns for quick scanning.
 /// Print components in column form, primarily used by `--component` listings.
-pub fn print_component_columns(schema: &SchemaTree) -> io::Result<()> {
+pub fn print_component_columns(schema: &SchemaTree, width: usize) -> io::Result<()> {
     let colours = palette();
     let mut names: Vec<_> = schema.components.keys().cloned().collect();
     names.sort();
@@ -1084,7 +2088,7 @@ pub fn print_component_columns(schema: &SchemaTree) -> io::Result<()> {
         .iter()
         .map(|name| component_cell(name, colours))
         .collect();
-    print_string_columns(&cells)
+    print_string_columns(&cells, width)
 }
 
 /// List all messages with MsgType and name, one per line.
@@ -1130,31 +2134,44 @@ pub fn list_all_tags(schema: &SchemaTree) -> io::Result<()> {
 }
 
 /// Print all tags in column form for compact display.
-pub fn print_tags_in_columns(schema: &SchemaTree) -> io::Result<()> {
-    let colours = palette();
-    let mut fields: Vec<_> = schema.fields.values().collect();
+pub fn print_tags_in_columns(schema: &SchemaTree, width: usize) -> io::Result<()> {
+    let mut fields: Vec<&Field> = schema.fields.values().map(|f| f.as_ref()).collect();
     fields.sort_by_key(|f| f.number);
+    print_fields_in_columns(&fields, width)
+}
 
+/// Print the given fields in columns, in the order supplied.
+pub fn print_fields_in_columns(fields: &[&Field], width: usize) -> io::Result<()> {
+    let colours = palette();
     let cells: Vec<_> = fields
         .iter()
         .map(|field| tag_cell(field.number, &field.name, &field.field_type, false, colours))
         .collect();
 
-    print_string_columns(&cells)
+    print_string_columns(&cells, width)
 }
 
-/// Print details for a single tag, optionally including its enum values.
-pub fn print_tag_details(field: &Field, verbose: bool, columns: bool) -> io::Result<()> {
+/// Print details for a single tag; `verbose` adds its enum values and the messages
+/// it appears in, laid out to fit `width`.
+pub fn print_tag_details(
+    schema: &SchemaTree,
+    field: &Field,
+    verbose: bool,
+    columns: bool,
+    width: usize,
+) -> io::Result<()> {
     let stdout = io::stdout();
     let mut handle = stdout.lock();
-    print_tag_details_with_writer(&mut handle, field, verbose, columns)
+    print_tag_details_with_writer(&mut handle, schema, field, verbose, columns, width)
 }
 
 fn print_tag_details_with_writer(
     out: &mut dyn Write,
+    schema: &SchemaTree,
     field: &Field,
     verbose: bool,
     columns: bool,
+    width: usize,
 ) -> io::Result<()> {
     let colours = palette();
     let cell = tag_cell(field.number, &field.name, &field.field_type, false, colours);
@@ -1164,18 +2181,96 @@ fn print_tag_details_with_writer(
         if columns {
             let mut buf = Vec::new();
             let values = collect_sorted_values(&mut buf, field.values_iter());
-            let layout = compute_values_layout(values, 4);
-            print_enum_columns(out, values, 4, colours, layout)?;
+            let layout = compute_values_layout(values, 4, width);
+            print_enum_columns(out, values, 4, colours, layout, width)?;
         } else {
             for value in field.values_iter() {
                 print_enum(out, value, 4, colours)?;
             }
         }
+        print_field_usage(out, schema, field.number, 4, colours, width)?;
     }
     Ok(())
 }
 
+/// Print a tag and the description of `value` for `--tag N --enum VALUE`.  Fields
+/// holding space-separated codes, such as ExecInst(18), describe each code in turn.
+pub fn print_enum_lookup(field: &Field, value: &str) -> io::Result<()> {
+    let stdout = io::stdout();
+    let mut handle = stdout.lock();
+    print_enum_lookup_with_writer(&mut handle, field, value)
+}
+
+fn print_enum_lookup_with_writer(
+    out: &mut dyn Write,
+    field: &Field,
+    value: &str,
+) -> io::Result<()> {
+    let colours = palette();
+    let cell = tag_cell(field.number, &field.name, &field.field_type, false, colours);
+    writeln!(out, "{}", cell.text)?;
+
+    let whole = field.values_iter().any(|v| v.enumeration == value);
+    let codes: Vec<&str> = if !whole && is_multi_value_type(&field.field_type) {
+        value.split_whitespace().collect()
+    } else {
+        vec![value]
+    };
+    for code in codes {
+        match field.values_iter().find(|v| v.enumeration == code) {
+            Some(known) => print_enum(out, known, 4, colours)?,
+            None => writeln!(
+                out,
+                "{}{}{}{} : not a valid {} value",
+                indent(8),
+                colours.error,
+                code,
+                colours.reset,
+                field.name
+            )?,
+        }
+    }
+    Ok(())
+}
+
+/// Print the "Appears in:" section for a field: `(header)`/`(trailer)` first, then
+/// every message that can carry it, in columns.
+fn print_field_usage(
+    out: &mut dyn Write,
+    schema: &SchemaTree,
+    number: u32,
+    indent_level: usize,
+    colours: ColourPalette,
+    width: usize,
+) -> io::Result<()> {
+    let Some(usage) = schema.field_usage(number).filter(|u| !u.is_empty()) else {
+        return writeln!(
+            out,
+            "{}Appears in: not referenced by any message",
+            indent(indent_level)
+        );
+    };
+    writeln!(out, "{}Appears in:", indent(indent_level))?;
+    let mut cells = Vec::new();
+    for (present, label) in [(usage.header, "(header)"), (usage.trailer, "(trailer)")] {
+        if present {
+            cells.push(DisplayCell::new(label.to_string()));
+        }
+    }
+    cells.extend(
+        usage
+            .messages
+            .iter()
+            .map(|name| DisplayCell::new(format!("{}{}{}", colours.name, name, colours.reset))),
+    );
+    write_string_columns(out, &cells, indent_level + NEST_INDENT, width)
+}
+
 /// Display a message definition with optional header/trailer and enum verbosity.
+/// Print a message definition hierarchically.  With `provenance` (`--provenance`),
+/// each field reached through a component or group is annotated with that path.
+/// `outline` applies `--depth` and `--groups-only`.
+#[allow(clippy::too_many_arguments)]
 pub fn display_message(
     schema: &SchemaTree,
     msg: &MessageNode,
@@ -1184,15 +2279,15 @@ pub fn display_message(
     include_trailer: bool,
     indent_level: usize,
     style: DisplayStyle,
+    provenance: Option<&FieldProvenance>,
+    outline: Outline,
 ) -> io::Result<()> {
     let stdout = io::stdout();
     let mut handle = stdout.lock();
-    RenderContext::new(&mut handle, schema, style, verbose).render_message(
-        msg,
-        include_header,
-        include_trailer,
-        indent_level,
-    )
+    RenderContext::new(&mut handle, schema, style, verbose)
+        .with_provenance(provenance)
+        .with_outline(outline)
+        .render_message(msg, include_header, include_trailer, indent_level)
 }
 
 pub fn display_component(
@@ -1202,14 +2297,52 @@ pub fn display_component(
     verbose: bool,
     indent_level: usize,
     style: DisplayStyle,
+    outline: Outline,
 ) -> io::Result<()> {
     let stdout = io::stdout();
     let mut handle = stdout.lock();
-    RenderContext::new(&mut handle, schema, style, verbose).render_component(
-        msg,
-        component,
-        indent_level,
-    )
+    RenderContext::new(&mut handle, schema, style, verbose)
+        .with_outline(outline)
+        .render_component(msg, component, indent_level)?;
+    if verbose {
+        print_component_usage(
+            &mut handle,
+            schema,
+            &component.name,
+            indent_level,
+            style.colours(),
+        )?;
+    }
+    Ok(())
+}
+
+/// Print the "Used by:" section for a component, one reference chain per line from the
+/// outermost message down to the component itself.
+fn print_component_usage(
+    out: &mut dyn Write,
+    schema: &SchemaTree,
+    component: &str,
+    indent_level: usize,
+    colours: ColourPalette,
+) -> io::Result<()> {
+    let paths = schema.component_usage_paths(component);
+    if paths.is_empty() {
+        return writeln!(out, "{}Used by: (none)", indent(indent_level));
+    }
+    writeln!(out, "{}Used by:", indent(indent_level))?;
+    for path in paths {
+        let chain: Vec<String> = path
+            .iter()
+            .map(|name| format!("{}{}{}", colours.name, name, colours.reset))
+            .collect();
+        writeln!(
+            out,
+            "{}{}",
+            indent(indent_level + NEST_INDENT),
+            chain.join(" -> ")
+        )?;
+    }
+    Ok(())
 }
 
 #[allow(dead_code)]
@@ -1232,6 +2365,7 @@ fn compute_message_layout(
     include_header: bool,
     include_trailer: bool,
     indent_level: usize,
+    width: usize,
 ) -> Option<ColumnLayout> {
     let mut stats = LayoutStats::default();
     collect_fields_layout(&msg.fields, indent_level + 2, &mut stats);
@@ -1247,25 +2381,34 @@ fn compute_message_layout(
     if include_trailer && let Some(trailer) = schema.components.get("Trailer") {
         collect_component_layout(trailer, indent_level, &mut stats);
     }
-    stats.finalize()
+    stats.finalize(width)
 }
 
 fn compute_component_layout(
     component: &ComponentNode,
     indent_level: usize,
+    width: usize,
 ) -> Option<ColumnLayout> {
     let mut stats = LayoutStats::default();
     collect_component_layout(component, indent_level, &mut stats);
-    stats.finalize()
+    stats.finalize(width)
 }
 
-fn compute_group_layout(group: &GroupNode, indent_level: usize) -> Option<ColumnLayout> {
+fn compute_group_layout(
+    group: &GroupNode,
+    indent_level: usize,
+    width: usize,
+) -> Option<ColumnLayout> {
     let mut stats = LayoutStats::default();
     collect_group_layout(group, indent_level, &mut stats);
-    stats.finalize()
+    stats.finalize(width)
 }
 
-fn compute_values_layout(values: &[&Value], indent_level: usize) -> Option<ColumnLayout> {
+fn compute_values_layout(
+    values: &[&Value],
+    indent_level: usize,
+    width: usize,
+) -> Option<ColumnLayout> {
     if values.is_empty() {
         return None;
     }
@@ -1276,7 +2419,7 @@ fn compute_values_layout(values: &[&Value], indent_level: usize) -> Option<Colum
         .max()
         .unwrap_or(0);
     stats.record(max_entry, indent_level);
-    stats.finalize()
+    stats.finalize(width)
 }
 
 fn collect_fields_layout(fields: &[FieldNode], indent_level: usize, stats: &mut LayoutStats) {
@@ -0,0 +1,179 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// SPDX-FileCopyrightText: 2025 Steve Clarke <stephenlclarke@mac.com> - https://xyzzy.tools
+
+//! `--enum-overlay`: a venue's extra enum values, and names for tags the dictionary
+//! lacks, read from a small TOML or CSV file instead of a forked dictionary.  The
+//! overlay is applied to each dictionary as `tag_lookup` loads it, so it covers
+//! auto-detected versions and `--fix` overrides alike.
+
+use anyhow::{Context, Result, anyhow, bail};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+/// Extra enum values and tag names, keyed by tag number.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct EnumOverlay {
+    pub names: BTreeMap<u32, String>,
+    pub values: BTreeMap<u32, BTreeMap<String, String>>,
+}
+
+/// One tag's entry in a TOML overlay.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TomlTag {
+    name: Option<String>,
+    #[serde(default)]
+    values: BTreeMap<String, String>,
+}
+
+impl EnumOverlay {
+    /// Read an overlay, as CSV when the file name ends in `.csv` and TOML otherwise.
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = fs::read_to_string(path)
+            .with_context(|| format!("failed to read enum overlay {}", path.display()))?;
+        let is_csv = path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("csv"));
+        let parsed = if is_csv {
+            Self::from_csv(&text)
+        } else {
+            Self::from_toml(&text)
+        };
+        parsed.with_context(|| format!("invalid enum overlay {}", path.display()))
+    }
+
+    /// Parse tables keyed by tag number, each with an optional `name` and a `values`
+    /// table of code to description:
+    ///
+    /// ```toml
+    /// [150.values]
+    /// Z = "VENUE_PARKED"
+    ///
+    /// [9001]
+    /// name = "VenueFlag"
+    /// values = { Y = "YES", N = "NO" }
+    /// ```
+    pub fn from_toml(text: &str) -> Result<Self> {
+        let tags: BTreeMap<String, TomlTag> = toml::from_str(text)?;
+        let mut overlay = Self::default();
+        for (key, entry) in tags {
+            let tag = parse_tag(&key)?;
+            if let Some(name) = entry.name {
+                overlay.names.insert(tag, name);
+            }
+            if !entry.values.is_empty() {
+                overlay.values.entry(tag).or_default().extend(entry.values);
+            }
+        }
+        Ok(overlay)
+    }
+
+    /// Parse `tag,enum,description` rows.  A row with an empty enum names the tag
+    /// instead; blank lines, `#` comments and a `tag,...` header row are skipped.
+    pub fn from_csv(text: &str) -> Result<Self> {
+        let mut overlay = Self::default();
+        for (idx, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut cells = line.splitn(3, ',').map(str::trim);
+            let (tag, code, description) = (
+                cells.next().unwrap_or_default(),
+                cells.next().unwrap_or_default(),
+                unquote(cells.next().unwrap_or_default()),
+            );
+            if idx == 0 && tag.eq_ignore_ascii_case("tag") {
+                continue;
+            }
+            let row = idx + 1;
+            let tag = parse_tag(tag).with_context(|| format!("line {row}"))?;
+            if description.is_empty() {
+                bail!("line {row}: expected tag,enum,description");
+            }
+            if code.is_empty() {
+                overlay.names.insert(tag, description.to_string());
+            } else {
+                overlay
+                    .values
+                    .entry(tag)
+                    .or_default()
+                    .insert(code.to_string(), description.to_string());
+            }
+        }
+        Ok(overlay)
+    }
+
+    /// Stack `other` on top of this overlay; its entries win where both define one.
+    pub fn extend(&mut self, other: EnumOverlay) {
+        self.names.extend(other.names);
+        for (tag, values) in other.values {
+            self.values.entry(tag).or_default().extend(values);
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty() && self.values.is_empty()
+    }
+}
+
+pub(crate) fn parse_tag(text: &str) -> Result<u32> {
+    text.parse::<u32>()
+        .ok()
+        .filter(|tag| *tag > 0)
+        .ok_or_else(|| anyhow!("invalid tag number '{text}'"))
+}
+
+pub(crate) fn unquote(text: &str) -> &str {
+    text.strip_prefix('"')
+        .and_then(|rest| rest.strip_suffix('"'))
+        .unwrap_or(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entries(overlay: &EnumOverlay, tag: u32) -> Vec<(&str, &str)> {
+        overlay.values[&tag]
+            .iter()
+            .map(|(code, desc)| (code.as_str(), desc.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn toml_and_csv_describe_the_same_overlay() {
+        let toml = EnumOverlay::from_toml(
+            "[150.values]\nZ = \"VENUE_PARKED\"\n\n[9001]\nname = \"VenueFlag\"\nvalues = { Y = \"YES\" }\n",
+        )
+        .unwrap();
+        let csv = EnumOverlay::from_csv(
+            "tag,enum,description\n# venue extras\n150,Z,VENUE_PARKED\n9001,,VenueFlag\n9001,Y,\"YES\"\n",
+        )
+        .unwrap();
+        assert_eq!(toml, csv);
+        assert_eq!(entries(&toml, 150), vec![("Z", "VENUE_PARKED")]);
+        assert_eq!(toml.names[&9001], "VenueFlag");
+    }
+
+    #[test]
+    fn later_overlays_win() {
+        let mut overlay = EnumOverlay::from_csv("150,Z,PARKED\n150,Y,HELD\n").unwrap();
+        overlay.extend(EnumOverlay::from_csv("150,Z,VENUE_PARKED\n").unwrap());
+        assert_eq!(
+            entries(&overlay, 150),
+            vec![("Y", "HELD"), ("Z", "VENUE_PARKED")]
+        );
+    }
+
+    #[test]
+    fn malformed_rows_are_rejected_with_their_line() {
+        let err = EnumOverlay::from_csv("150,Z,OK\nExecType,Z,BAD\n").unwrap_err();
+        assert_eq!(format!("{err:#}"), "line 2: invalid tag number 'ExecType'");
+        let err = EnumOverlay::from_csv("150,Z\n").unwrap_err();
+        assert_eq!(err.to_string(), "line 1: expected tag,enum,description");
+        assert!(EnumOverlay::from_toml("[150]\nvalue = { Z = \"X\" }\n").is_err());
+    }
+}
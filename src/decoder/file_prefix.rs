@@ -0,0 +1,189 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// SPDX-FileCopyrightText: 2025 Steve Clarke <stephenlclarke@mac.com> - https://xyzzy.tools
+
+//! `--prefix-file`: every line written while a file is decoded starts with a short
+//! coloured `[basename]` tag, so output from many files stays attributable once the
+//! `Processing:` banner has scrolled away.  Tags are padded to the widest among the
+//! inputs, and that width is taken off the terminal width, so decoded values still
+//! line up and wrap inside the window.  Stdin gets no tag.  The run ends with a table
+//! of messages (and invalid messages, under `--validate`) per file.
+
+use crate::decoder::colours::palette;
+use crate::decoder::display::{pad_ansi, visible_width};
+use std::cell::RefCell;
+use std::io::{self, Write};
+use std::path::Path;
+use std::rc::Rc;
+
+/// The tag in front of each output line, shared between the [`PrefixedWriter`] that
+/// writes it and the decoder, which changes it as each file starts.
+#[derive(Debug, Clone, Default)]
+pub struct FilePrefix(Rc<RefCell<String>>);
+
+impl FilePrefix {
+    /// Tag the lines of `path` from now on, padded to `width` columns; `-` (stdin)
+    /// clears the tag.  Returns the columns the tag takes.
+    pub fn start_file(&self, path: &str, width: usize) -> usize {
+        let mut tag = self.0.borrow_mut();
+        tag.clear();
+        if path == "-" {
+            return 0;
+        }
+        let colours = palette();
+        let label = format!("{}[{}]{}", colours.file, short_name(path), colours.reset);
+        tag.push_str(&pad_ansi(&label, width));
+        tag.push(' ');
+        width + 1
+    }
+
+    /// Stop tagging lines, for the end-of-run output that covers every file.
+    pub fn clear(&self) {
+        self.0.borrow_mut().clear();
+    }
+}
+
+/// The width of the widest `[basename]` tag among `paths`, stdin aside.
+pub fn tag_width(paths: &[String]) -> usize {
+    paths
+        .iter()
+        .filter(|path| *path != "-")
+        .map(|path| visible_width(&format!("[{}]", short_name(path))))
+        .max()
+        .unwrap_or(0)
+}
+
+/// The last path component of a file, or of a URL's path.
+fn short_name(path: &str) -> &str {
+    let trimmed = path.trim_end_matches('/');
+    if trimmed.contains("://") {
+        return trimmed.rsplit('/').next().unwrap_or(trimmed);
+    }
+    Path::new(trimmed)
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or(trimmed)
+}
+
+/// Writes to `inner`, starting each line with the current [`FilePrefix`] tag.
+pub struct PrefixedWriter<W: Write> {
+    inner: W,
+    prefix: FilePrefix,
+    at_line_start: bool,
+}
+
+impl<W: Write> PrefixedWriter<W> {
+    pub fn new(inner: W) -> Self {
+        Self {
+            inner,
+            prefix: FilePrefix::default(),
+            at_line_start: true,
+        }
+    }
+
+    /// A handle for changing the tag this writer adds.
+    pub fn prefix(&self) -> FilePrefix {
+        self.prefix.clone()
+    }
+}
+
+impl<W: Write> Write for PrefixedWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let tag = self.prefix.0.borrow();
+        for line in buf.split_inclusive(|b| *b == b'\n') {
+            if self.at_line_start {
+                self.inner.write_all(tag.as_bytes())?;
+            }
+            self.inner.write_all(line)?;
+            self.at_line_start = line.ends_with(b"\n");
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// What one input contributed to the run.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FileStats {
+    pub source: String,
+    /// Messages counted in the MsgType totals.
+    pub messages: usize,
+    /// Messages `--validate` found invalid.
+    pub invalid: usize,
+}
+
+/// The end-of-run table of messages per file, with invalid messages when `validated`.
+pub fn render_file_table(
+    out: &mut dyn Write,
+    files: &[FileStats],
+    validated: bool,
+) -> io::Result<()> {
+    if files.is_empty() {
+        return Ok(());
+    }
+    let colours = palette();
+    let name_width = files
+        .iter()
+        .map(|file| visible_width(&file.source))
+        .max()
+        .unwrap_or(0)
+        .max("File".len());
+    write!(out, "{:<name_width$}  {:>8}", "File", "Messages")?;
+    if validated {
+        write!(out, "  {:>8}", "Invalid")?;
+    }
+    writeln!(out)?;
+    for file in files {
+        write!(
+            out,
+            "{}{}{}  {}{:>8}{}",
+            colours.file,
+            pad_ansi(&file.source, name_width),
+            colours.reset,
+            colours.value,
+            file.messages,
+            colours.reset
+        )?;
+        if validated {
+            let colour = if file.invalid > 0 {
+                colours.error
+            } else {
+                colours.value
+            };
+            write!(out, "  {colour}{:>8}{}", file.invalid, colours.reset)?;
+        }
+        writeln!(out)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_line_gets_the_current_tag_and_stdin_none() {
+        crate::decoder::colours::disable_colours();
+        let paths = ["logs/a.log".to_string(), "/var/log/venue.log".to_string()];
+        let width = tag_width(&paths);
+        assert_eq!(width, "[venue.log]".len());
+
+        let mut writer = PrefixedWriter::new(Vec::new());
+        let prefix = writer.prefix();
+        assert_eq!(prefix.start_file(&paths[0], width), width + 1);
+        write!(writer, "one\ntw").unwrap();
+        writer.write_all(b"o\n").unwrap();
+        prefix.start_file(&paths[1], width);
+        writeln!(writer, "three").unwrap();
+        assert_eq!(prefix.start_file("-", width), 0);
+        writeln!(writer, "four").unwrap();
+        prefix.clear();
+        assert_eq!(
+            String::from_utf8(writer.inner).unwrap(),
+            "[a.log]     one\n[a.log]     two\n[venue.log] three\nfour\n"
+        );
+        assert_eq!(short_name("https://host/logs/b.log"), "b.log");
+    }
+}
@@ -0,0 +1,294 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// SPDX-FileCopyrightText: 2025 Steve Clarke <stephenlclarke@mac.com> - https://xyzzy.tools
+
+//! `--report=FILE`: every `--validate` finding written for other tools as well as the
+//! terminal.  JSON Lines gives one object per finding as it is found; SARIF collects
+//! them into one document at the end of the run, with a rule per error category, which
+//! code-scanning uploads such as GitHub's turn into annotations on the file and line.
+
+use crate::decoder::fixparser::ParsedMessage;
+use crate::decoder::raw_bytes;
+use crate::decoder::validator::{ErrorCategory, ValidationError, ValidationReport};
+use serde::Serialize;
+use serde_json::json;
+use std::io::{self, Write};
+use std::ops::Range;
+
+/// How `--report` writes findings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportFormat {
+    Jsonl,
+    Sarif,
+}
+
+impl ReportFormat {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_ascii_lowercase().as_str() {
+            "jsonl" => Some(Self::Jsonl),
+            "sarif" => Some(Self::Sarif),
+            _ => None,
+        }
+    }
+
+    /// SARIF for a `.sarif` or `.sarif.json` file, JSON Lines for anything else.
+    pub fn for_path(path: &str) -> Self {
+        let lower = path.to_ascii_lowercase();
+        if lower.ends_with(".sarif") || lower.ends_with(".sarif.json") {
+            Self::Sarif
+        } else {
+            Self::Jsonl
+        }
+    }
+}
+
+/// One finding as written to the report.  Offsets count bytes from the start of the
+/// input line, as read, and cover the offending `tag=value` pair.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Finding {
+    pub file: String,
+    pub line: usize,
+    pub start: Option<usize>,
+    pub end: Option<usize>,
+    pub msg_type: Option<String>,
+    pub tag: Option<u32>,
+    pub category: ErrorCategory,
+    /// `error`, or `warning` for findings that do not make the message invalid.
+    pub severity: &'static str,
+    pub message: String,
+    /// One-based character columns of the same span, for SARIF regions.
+    #[serde(skip)]
+    columns: Option<Range<usize>>,
+}
+
+/// Writes the findings of a run to `--report`.
+pub struct FindingsReport<'a> {
+    out: &'a mut dyn Write,
+    format: ReportFormat,
+    /// e.g. `fixdecoder 0.3.0 (branch:main, commit:abc1234) [rust:1.90.0]`.
+    tool: String,
+    /// Held back for the single SARIF document.
+    pending: Vec<Finding>,
+}
+
+impl<'a> FindingsReport<'a> {
+    pub fn new(out: &'a mut dyn Write, format: ReportFormat, tool: String) -> Self {
+        Self {
+            out,
+            format,
+            tool,
+            pending: Vec::new(),
+        }
+    }
+
+    /// Report the findings for `msg`, which starts `msg_start` bytes into `line`, line
+    /// `line_number` of `file`.
+    pub fn record(
+        &mut self,
+        file: &str,
+        line_number: usize,
+        line: &str,
+        msg_start: usize,
+        msg: &ParsedMessage<'_>,
+        report: &ValidationReport,
+    ) -> io::Result<()> {
+        let findings = report
+            .errors
+            .iter()
+            .map(|err| (err, "error"))
+            .chain(report.warnings.iter().map(|warn| (warn, "warning")));
+        for (err, severity) in findings {
+            let finding = finding(file, line_number, line, msg_start, msg, err, severity);
+            match self.format {
+                ReportFormat::Jsonl => {
+                    serde_json::to_writer(&mut *self.out, &finding)?;
+                    self.out.write_all(b"\n")?;
+                }
+                ReportFormat::Sarif => self.pending.push(finding),
+            }
+        }
+        Ok(())
+    }
+
+    /// Write out anything held back and flush.
+    pub fn finish(&mut self) -> io::Result<()> {
+        if self.format == ReportFormat::Sarif {
+            let document = sarif(&self.tool, &self.pending);
+            serde_json::to_writer_pretty(&mut *self.out, &document)?;
+            self.out.write_all(b"\n")?;
+            self.pending.clear();
+        }
+        self.out.flush()
+    }
+}
+
+fn finding(
+    file: &str,
+    line_number: usize,
+    line: &str,
+    msg_start: usize,
+    msg: &ParsedMessage<'_>,
+    err: &ValidationError,
+    severity: &'static str,
+) -> Finding {
+    let span = err
+        .span
+        .clone()
+        .map(|span| msg_start + span.start..msg_start + span.end)
+        .filter(|span| line.get(span.clone()).is_some());
+    let offset = |at: usize| raw_bytes::byte_len(&line[..at]);
+    let column = |at: usize| line[..at].chars().count() + 1;
+    Finding {
+        file: file.to_string(),
+        line: line_number,
+        start: span.as_ref().map(|span| offset(span.start)),
+        end: span.as_ref().map(|span| offset(span.end)),
+        msg_type: msg.first(35).map(str::to_string),
+        tag: err.tag,
+        category: err.category,
+        severity,
+        message: err.message.clone(),
+        columns: span.map(|span| column(span.start)..column(span.end)),
+    }
+}
+
+/// The rule id for `category`, which is also how JSON Lines names it, e.g. `body_length`.
+fn rule_id(category: ErrorCategory) -> String {
+    serde_json::to_value(category)
+        .ok()
+        .and_then(|value| value.as_str().map(str::to_string))
+        .unwrap_or_default()
+}
+
+/// A SARIF 2.1.0 log with one run, one rule per error category and one result per
+/// finding.
+fn sarif(tool: &str, findings: &[Finding]) -> serde_json::Value {
+    let rules: Vec<_> = ErrorCategory::ALL
+        .iter()
+        .map(|category| {
+            json!({
+                "id": rule_id(*category),
+                "name": category.label(),
+                "shortDescription": { "text": format!("FIX validation: {}", category.label()) },
+            })
+        })
+        .collect();
+    let results: Vec<_> = findings
+        .iter()
+        .map(|finding| {
+            let mut region = json!({ "startLine": finding.line });
+            if let Some(columns) = &finding.columns {
+                region["startColumn"] = json!(columns.start);
+                region["endColumn"] = json!(columns.end);
+            }
+            json!({
+                "ruleId": rule_id(finding.category),
+                "ruleIndex": ErrorCategory::ALL.iter().position(|c| *c == finding.category),
+                "level": finding.severity,
+                "message": { "text": finding.message },
+                "locations": [{
+                    "physicalLocation": {
+                        "artifactLocation": { "uri": finding.file },
+                        "region": region,
+                    }
+                }],
+                "properties": { "msgType": finding.msg_type, "tag": finding.tag },
+            })
+        })
+        .collect();
+    json!({
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "fixdecoder",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "fullName": tool,
+                    "rules": rules,
+                }
+            },
+            "columnKind": "unicodeCodePoints",
+            "results": results,
+        }]
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decoder::tag_lookup::load_dictionary;
+    use crate::decoder::validator::validate_parsed_message;
+
+    const LINE: &str = "12:00:01 8=FIX.4.4\u{1}9=5\u{1}35=0\u{1}10=999\u{1}";
+
+    fn record(format: ReportFormat) -> String {
+        let start = LINE.find("8=FIX").unwrap();
+        let msg = ParsedMessage::parse(&LINE[start..]);
+        let report = validate_parsed_message(&msg, &load_dictionary(msg.raw));
+        let mut out = Vec::new();
+        let mut sink = FindingsReport::new(&mut out, format, "fixdecoder test".into());
+        sink.record("logs/a.log", 7, LINE, start, &msg, &report)
+            .unwrap();
+        sink.finish().unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn jsonl_has_a_located_record_per_finding() {
+        let text = record(ReportFormat::Jsonl);
+        let records: Vec<serde_json::Value> = text
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        let checksum = records
+            .iter()
+            .find(|r| r["category"] == "checksum")
+            .expect("checksum finding");
+        assert_eq!(checksum["file"], "logs/a.log");
+        assert_eq!(checksum["line"], 7);
+        assert_eq!(checksum["msg_type"], "0");
+        assert_eq!(checksum["tag"], 10);
+        assert_eq!(checksum["severity"], "error");
+        let (start, end) = (
+            checksum["start"].as_u64().unwrap() as usize,
+            checksum["end"].as_u64().unwrap() as usize,
+        );
+        assert_eq!(&LINE[start..end], "10=999");
+    }
+
+    #[test]
+    fn sarif_has_a_rule_per_category_and_a_result_per_finding() {
+        let document: serde_json::Value =
+            serde_json::from_str(&record(ReportFormat::Sarif)).unwrap();
+        assert_eq!(document["version"], "2.1.0");
+        let run = &document["runs"][0];
+        assert_eq!(run["tool"]["driver"]["fullName"], "fixdecoder test");
+        let rules = run["tool"]["driver"]["rules"].as_array().unwrap();
+        assert_eq!(rules.len(), ErrorCategory::ALL.len());
+        let results = run["results"].as_array().unwrap();
+        assert_eq!(results.len(), record(ReportFormat::Jsonl).lines().count());
+        let checksum = results
+            .iter()
+            .find(|r| r["ruleId"] == "checksum")
+            .expect("checksum result");
+        let index = checksum["ruleIndex"].as_u64().unwrap() as usize;
+        assert_eq!(rules[index]["id"], "checksum");
+        let region = &checksum["locations"][0]["physicalLocation"]["region"];
+        assert_eq!(region["startLine"], 7);
+        let column = LINE.find("10=999").unwrap() + 1;
+        assert_eq!(region["startColumn"], column);
+        assert_eq!(region["endColumn"], column + 6);
+    }
+
+    #[test]
+    fn format_follows_the_file_extension_unless_given() {
+        assert_eq!(ReportFormat::for_path("out.sarif"), ReportFormat::Sarif);
+        assert_eq!(
+            ReportFormat::for_path("OUT.SARIF.JSON"),
+            ReportFormat::Sarif
+        );
+        assert_eq!(ReportFormat::for_path("out.jsonl"), ReportFormat::Jsonl);
+        assert_eq!(ReportFormat::parse("SARIF"), Some(ReportFormat::Sarif));
+        assert_eq!(ReportFormat::parse("xml"), None);
+    }
+}
@@ -0,0 +1,158 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// SPDX-FileCopyrightText: 2025 Steve Clarke <stephenlclarke@mac.com> - https://xyzzy.tools
+
+//! `--fix-map SENDER:TARGET=KEY`: pick the dictionary per session for logs that mix
+//! counterparties on different FIX versions, where a single `--fix` override would be
+//! wrong for some of them.  Either CompID may be `*`.  The sessions seen and the
+//! dictionary each one was decoded with are remembered for an end-of-run note.
+
+use crate::decoder::tag_lookup::normalise_fix_key;
+use anyhow::{Result, anyhow};
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+/// One `--fix-map` entry; `None` stands for the `*` wildcard.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FixMapRule {
+    sender: Option<String>,
+    target: Option<String>,
+    key: String,
+}
+
+impl FixMapRule {
+    /// Parse `SENDER:TARGET=KEY`, normalising KEY as `--fix` does (`42` is `FIX42`).
+    pub fn parse(spec: &str) -> Result<Self> {
+        let invalid = || anyhow!("invalid --fix-map '{spec}': expected SENDER:TARGET=KEY");
+        let (session, key) = spec.rsplit_once('=').ok_or_else(invalid)?;
+        let (sender, target) = session.split_once(':').ok_or_else(invalid)?;
+        let comp_id = |id: &str| match id.trim() {
+            "" => Err(invalid()),
+            "*" => Ok(None),
+            id => Ok(Some(id.to_string())),
+        };
+        Ok(Self {
+            sender: comp_id(sender)?,
+            target: comp_id(target)?,
+            key: normalise_fix_key(key).ok_or_else(invalid)?,
+        })
+    }
+
+    /// The dictionary key the rule selects.
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    fn matches(&self, sender: &str, target: &str) -> bool {
+        self.sender.as_deref().is_none_or(|id| id == sender)
+            && self.target.as_deref().is_none_or(|id| id == target)
+    }
+}
+
+/// The `--fix-map` rules, tried in command-line order, and the dictionary each session
+/// ended up using.
+#[derive(Debug, Default)]
+pub struct FixMap {
+    rules: Vec<FixMapRule>,
+    used: Mutex<BTreeMap<(String, String), String>>,
+}
+
+impl FixMap {
+    pub fn new(rules: Vec<FixMapRule>) -> Self {
+        Self {
+            rules,
+            used: Mutex::default(),
+        }
+    }
+
+    /// The dictionary key of the first rule matching the message's SenderCompID (49)
+    /// and TargetCompID (56).
+    pub fn key_for(&self, msg: &str) -> Option<&str> {
+        let (sender, target) = comp_ids(msg);
+        self.rules
+            .iter()
+            .find(|rule| rule.matches(sender, target))
+            .map(|rule| rule.key.as_str())
+    }
+
+    /// Remember that the message's session was decoded with `key`.
+    pub fn record(&self, msg: &str, key: &str) {
+        let (sender, target) = comp_ids(msg);
+        if let Ok(mut used) = self.used.lock() {
+            used.insert((sender.to_string(), target.to_string()), key.to_string());
+        }
+    }
+
+    /// `(sender, target, key)` for every session seen, sorted by CompIDs.
+    pub fn usage(&self) -> Vec<(String, String, String)> {
+        self.used
+            .lock()
+            .map(|used| {
+                used.iter()
+                    .map(|((sender, target), key)| (sender.clone(), target.clone(), key.clone()))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+fn comp_ids(msg: &str) -> (&str, &str) {
+    let (mut sender, mut target) = ("", "");
+    for field in msg.split('\u{0001}') {
+        match field.split_once('=') {
+            Some(("49", value)) => sender = value,
+            Some(("56", value)) => target = value,
+            _ => {}
+        }
+    }
+    (sender, target)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn msg(sender: &str, target: &str) -> String {
+        format!("8=FIX.4.4\u{0001}35=0\u{0001}49={sender}\u{0001}56={target}\u{0001}")
+    }
+
+    #[test]
+    fn rules_parse_with_wildcards_and_normalised_keys() {
+        assert_eq!(
+            FixMapRule::parse("BANKX:US=42").unwrap(),
+            FixMapRule {
+                sender: Some("BANKX".to_string()),
+                target: Some("US".to_string()),
+                key: "FIX42".to_string(),
+            }
+        );
+        let wildcard = FixMapRule::parse("*:LSE=FIX.5.0SP2").unwrap();
+        assert_eq!((wildcard.sender, wildcard.key.as_str()), (None, "FIX50SP2"));
+        for bad in ["BANKX=FIX42", "BANKX:US", ":US=FIX42", "BANKX:US="] {
+            assert!(FixMapRule::parse(bad).is_err(), "{bad}");
+        }
+    }
+
+    #[test]
+    fn first_matching_rule_wins() {
+        let map = FixMap::new(vec![
+            FixMapRule::parse("BANKX:US=FIX42").unwrap(),
+            FixMapRule::parse("*:LSE=FIX50SP2").unwrap(),
+            FixMapRule::parse("BANKX:*=FIX43").unwrap(),
+        ]);
+        assert_eq!(map.key_for(&msg("BANKX", "US")), Some("FIX42"));
+        assert_eq!(map.key_for(&msg("US", "LSE")), Some("FIX50SP2"));
+        assert_eq!(map.key_for(&msg("BANKX", "LSE")), Some("FIX50SP2"));
+        assert_eq!(map.key_for(&msg("BANKX", "OTHER")), Some("FIX43"));
+        assert_eq!(map.key_for(&msg("US", "BANKX")), None);
+
+        map.record(&msg("US", "LSE"), "FIX50SP2");
+        map.record(&msg("BANKX", "US"), "FIX42");
+        assert_eq!(
+            map.usage(),
+            [
+                ("BANKX".into(), "US".into(), "FIX42".into()),
+                ("US".into(), "LSE".into(), "FIX50SP2".into()),
+            ]
+        );
+    }
+}
@@ -1,6 +1,9 @@
 // SPDX-License-Identifier: AGPL-3.0-only
 // SPDX-FileCopyrightText: 2025 Steve Clarke <stephenlclarke@mac.com> - https://xyzzy.tools
 
+use std::collections::HashMap;
+use std::ops::Range;
+
 const SOH: char = '\u{0001}';
 
 /// Parsed representation of a single FIX tag/value pair.
@@ -12,21 +15,87 @@ pub struct FieldValue {
 
 /// Split a FIX message string into ordered tag/value pairs, skipping fragments without `=`.
 pub fn parse_fix(msg: &str) -> Vec<FieldValue> {
+    parse_fix_with_spans(msg).0
+}
+
+/// [`parse_fix`] that also returns, for each field, the byte range its `tag=value`
+/// pair occupies in `msg` (excluding the trailing SOH).
+pub fn parse_fix_with_spans(msg: &str) -> (Vec<FieldValue>, Vec<Range<usize>>) {
+    let mut fields = Vec::new();
+    let mut spans = Vec::new();
     if !msg.contains(SOH) {
-        return Vec::new();
+        return (fields, spans);
+    }
+
+    let mut start = 0;
+    for fragment in msg.split(SOH) {
+        let span = start..start + fragment.len();
+        start = span.end + SOH.len_utf8();
+        let Some((tag, value)) = fragment.split_once('=') else {
+            continue;
+        };
+        let Ok(tag_num) = tag.parse::<u32>() else {
+            continue;
+        };
+        fields.push(FieldValue {
+            tag: tag_num,
+            value: value.to_string(),
+        });
+        spans.push(span);
+    }
+    (fields, spans)
+}
+
+/// A FIX message split into fields once and shared by the validator, prettifier and
+/// summary/count recorders, so validation mode does not re-parse every message.
+#[derive(Debug, Clone)]
+pub struct ParsedMessage<'a> {
+    pub raw: &'a str,
+    pub fields: Vec<FieldValue>,
+    /// Index into `fields` of the last occurrence of each tag.
+    last_index: HashMap<u32, usize>,
+    /// Byte range of each field within `raw`, parallel to `fields`.
+    spans: Vec<Range<usize>>,
+}
+
+impl<'a> ParsedMessage<'a> {
+    pub fn parse(raw: &'a str) -> Self {
+        let (fields, spans) = parse_fix_with_spans(raw);
+        let last_index = fields
+            .iter()
+            .enumerate()
+            .map(|(idx, field)| (field.tag, idx))
+            .collect();
+        Self {
+            raw,
+            fields,
+            last_index,
+            spans,
+        }
     }
 
-    msg.split(SOH)
-        .filter_map(|fragment| {
-            if fragment.is_empty() {
-                return None;
-            }
-            let (tag, value) = fragment.split_once('=')?;
-            let tag_num: u32 = tag.parse().ok()?;
-            Some(FieldValue {
-                tag: tag_num,
-                value: value.to_string(),
-            })
-        })
-        .collect()
+    /// Index into `fields` of the occurrence [`get`](Self::get) reads for `tag`.
+    pub fn position(&self, tag: u32) -> Option<usize> {
+        self.last_index.get(&tag).copied()
+    }
+
+    /// Byte range of the field at `index` within `raw`.
+    pub fn span(&self, index: usize) -> Option<Range<usize>> {
+        self.spans.get(index).cloned()
+    }
+
+    /// Value of `tag`; when a tag repeats the last occurrence wins.
+    pub fn get(&self, tag: u32) -> Option<&str> {
+        self.last_index
+            .get(&tag)
+            .map(|idx| self.fields[*idx].value.as_str())
+    }
+
+    /// Value of the first occurrence of `tag`.
+    pub fn first(&self, tag: u32) -> Option<&str> {
+        self.fields
+            .iter()
+            .find(|f| f.tag == tag)
+            .map(|f| f.value.as_str())
+    }
 }
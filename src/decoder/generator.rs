@@ -0,0 +1,293 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// SPDX-FileCopyrightText: 2025 Steve Clarke <stephenlclarke@mac.com> - https://xyzzy.tools
+
+//! Skeleton FIX message generation for `--message NAME --generate`.
+//! Fields are emitted in the same order the validator expects (fields, then
+//! components, then groups) with placeholder values chosen by field type, and
+//! BodyLength/CheckSum are computed last so the result validates cleanly.
+
+use crate::decoder::raw_bytes;
+use crate::decoder::schema::{ComponentNode, Field, FieldNode, GroupNode, MessageNode, SchemaTree};
+use chrono::NaiveDateTime;
+use std::collections::HashSet;
+
+const SOH: char = '\u{0001}';
+const BEGIN_STRING: u32 = 8;
+const BODY_LENGTH: u32 = 9;
+const CHECKSUM: u32 = 10;
+const MSG_TYPE: u32 = 35;
+const APPL_VER_ID: u32 = 1128;
+
+/// Build a single SOH-delimited message for `message`.  Only required fields (and
+/// groups containing them) are included unless `include_optional` is set.  `now`
+/// supplies the value for every time-based field.
+pub fn generate_message(
+    schema: &SchemaTree,
+    message: &MessageNode,
+    include_optional: bool,
+    now: NaiveDateTime,
+) -> String {
+    let (begin_string, appl_ver_id) = session_identity(schema);
+    let mut generator = Generator {
+        schema,
+        message,
+        include_optional,
+        now,
+        appl_ver_id,
+        seen: HashSet::new(),
+        body: Vec::new(),
+        after_length: false,
+    };
+
+    if let Some(header) = schema.components.get("Header") {
+        generator.component(header, false);
+    }
+    generator.fields(&message.fields, false);
+    for comp in &message.components {
+        generator.component(comp, false);
+    }
+    for group in &message.groups {
+        generator.group(group);
+    }
+    if let Some(trailer) = schema.components.get("Trailer") {
+        generator.component(trailer, false);
+    }
+
+    let body: String = generator
+        .body
+        .iter()
+        .map(|(tag, value)| format!("{tag}={value}{SOH}"))
+        .collect();
+    let head = format!(
+        "{BEGIN_STRING}={begin_string}{SOH}{BODY_LENGTH}={}{SOH}{body}",
+        raw_bytes::byte_len(&body)
+    );
+    let checksum = raw_bytes::checksum(&head);
+    format!("{head}{CHECKSUM}={checksum:03}{SOH}")
+}
+
+/// BeginString for the dictionary plus the ApplVerID FIX 5.0+ messages carry over FIXT.
+fn session_identity(schema: &SchemaTree) -> (String, Option<&'static str>) {
+    let (typ, version) = schema
+        .version
+        .split_once(' ')
+        .unwrap_or(("FIX", schema.version.as_str()));
+    if typ.eq_ignore_ascii_case("FIXT") {
+        return (format!("FIXT.{version}"), None);
+    }
+    if version.starts_with('5') {
+        let appl_ver_id = match schema.service_pack.as_str() {
+            "1" => "8",
+            "2" => "9",
+            _ => "7",
+        };
+        return ("FIXT.1.1".to_string(), Some(appl_ver_id));
+    }
+    (format!("FIX.{version}"), None)
+}
+
+struct Generator<'a> {
+    schema: &'a SchemaTree,
+    message: &'a MessageNode,
+    include_optional: bool,
+    now: NaiveDateTime,
+    appl_ver_id: Option<&'static str>,
+    seen: HashSet<u32>,
+    body: Vec<(u32, String)>,
+    /// Whether the last field written was a LENGTH, such as EncodedTextLen(354).
+    after_length: bool,
+}
+
+impl Generator<'_> {
+    /// Emit the wanted fields from `fields`.  Outside groups a tag is only written
+    /// once, even when several components reference it.
+    fn fields(&mut self, fields: &[FieldNode], in_group: bool) {
+        for node in fields {
+            let tag = node.field.number;
+            if matches!(tag, BEGIN_STRING | BODY_LENGTH | CHECKSUM) {
+                continue;
+            }
+            let forced = tag == MSG_TYPE || (tag == APPL_VER_ID && self.appl_ver_id.is_some());
+            if !(node.required || forced || self.include_optional) {
+                continue;
+            }
+            if !in_group && !self.seen.insert(tag) {
+                continue;
+            }
+            let value = self.value_for(&node.field);
+            self.push(&node.field, value);
+        }
+    }
+
+    fn component(&mut self, comp: &ComponentNode, in_group: bool) {
+        self.fields(&comp.fields, in_group);
+        for sub in &comp.components {
+            self.component(sub, in_group);
+        }
+        for group in &comp.groups {
+            self.group(group);
+        }
+    }
+
+    /// Emit a single-entry group when it is required, holds required fields, or
+    /// optional fields were requested.  The delimiter field always leads the entry.
+    fn group(&mut self, group: &GroupNode) {
+        if !(group.required || self.include_optional || group_has_required(group)) {
+            return;
+        }
+        let Some(count) = self.schema.fields.get(&group.name) else {
+            return;
+        };
+        if !self.seen.insert(count.number) {
+            return;
+        }
+        self.push(count, "1".to_string());
+
+        if let Some((delim, rest)) = group.fields.split_first() {
+            let value = self.value_for(&delim.field);
+            self.push(&delim.field, value);
+            self.fields(rest, true);
+        }
+        for comp in &group.components {
+            self.component(comp, true);
+        }
+        for sub in &group.groups {
+            self.group(sub);
+        }
+    }
+
+    /// Append a field.  A DATA field straight after a LENGTH field sets that length to
+    /// its own, as EncodedTextLen(354) does for EncodedText(355).
+    fn push(&mut self, field: &Field, value: String) {
+        let kind = field.field_type.to_ascii_uppercase();
+        if kind == "DATA"
+            && self.after_length
+            && let Some((_, length)) = self.body.last_mut()
+        {
+            *length = value.len().to_string();
+        }
+        self.after_length = kind == "LENGTH";
+        self.body.push((field.number, value));
+    }
+
+    /// Placeholder value for a field: the first enum when enumerated, otherwise a
+    /// value shaped by the FIX type.
+    fn value_for(&self, field: &Field) -> String {
+        match field.number {
+            MSG_TYPE => return self.message.msg_type.clone(),
+            APPL_VER_ID if self.appl_ver_id.is_some() => {
+                return self.appl_ver_id.unwrap_or_default().to_string();
+            }
+            _ => {}
+        }
+        if let Some(first) = field.values_iter().next() {
+            return first.enumeration.clone();
+        }
+        match field.field_type.to_ascii_uppercase().as_str() {
+            "INT" | "LENGTH" | "NUMINGROUP" | "SEQNUM" | "TAGNUM" | "DAYOFMONTH" => "1".into(),
+            "FLOAT" | "QTY" | "PRICE" | "PRICEOFFSET" | "AMT" | "PERCENTAGE" => "1".into(),
+            "BOOLEAN" => "N".into(),
+            "CHAR" => "X".into(),
+            "CURRENCY" => "USD".into(),
+            "EXCHANGE" => "XNYS".into(),
+            "COUNTRY" => "US".into(),
+            "LANGUAGE" => "en".into(),
+            "UTCTIMESTAMP" => self.now.format("%Y%m%d-%H:%M:%S%.3f").to_string(),
+            "TZTIMESTAMP" => self.now.format("%Y%m%d-%H:%M:%S%.3fZ").to_string(),
+            "UTCDATEONLY" | "UTCDATE" | "LOCALMKTDATE" | "DATE" => {
+                self.now.format("%Y%m%d").to_string()
+            }
+            "UTCTIMEONLY" | "TIME" => self.now.format("%H:%M:%S").to_string(),
+            "TZTIMEONLY" => self.now.format("%H:%M:%SZ").to_string(),
+            "MONTHYEAR" => self.now.format("%Y%m").to_string(),
+            _ => field.name.clone(),
+        }
+    }
+}
+
+fn group_has_required(group: &GroupNode) -> bool {
+    group.fields.iter().any(|f| f.required)
+        || group.components.iter().any(component_has_required)
+        || group
+            .groups
+            .iter()
+            .any(|g| g.required || group_has_required(g))
+}
+
+fn component_has_required(comp: &ComponentNode) -> bool {
+    comp.fields.iter().any(|f| f.required)
+        || comp.components.iter().any(component_has_required)
+        || comp
+            .groups
+            .iter()
+            .any(|g| g.required || group_has_required(g))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decoder::schema::FixDictionary;
+    use crate::decoder::tag_lookup::load_dictionary;
+    use crate::decoder::validator::validate_fix_message;
+    use crate::fix;
+    use chrono::NaiveDate;
+
+    fn schema_for(version: &str) -> SchemaTree {
+        let dict = FixDictionary::from_xml(fix::choose_embedded_xml(version).unwrap()).unwrap();
+        SchemaTree::build(dict)
+    }
+
+    fn fixed_now() -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2025, 3, 14)
+            .unwrap()
+            .and_hms_milli_opt(9, 26, 53, 589)
+            .unwrap()
+    }
+
+    #[test]
+    fn generated_messages_pass_validation() {
+        let schema = schema_for("44");
+        for (name, include_optional) in [
+            ("NewOrderSingle", false),
+            ("ExecutionReport", false),
+            ("Logon", false),
+            ("NewOrderSingle", true),
+        ] {
+            let message = &schema.messages[name];
+            let msg = generate_message(&schema, message, include_optional, fixed_now());
+            let report = validate_fix_message(&msg, &load_dictionary(&msg));
+            assert!(
+                report.is_clean(),
+                "{name} (optional={include_optional}) should validate: {:?}\n{}",
+                report.errors,
+                msg.replace(SOH, "|")
+            );
+        }
+    }
+
+    #[test]
+    fn required_only_message_has_expected_shape() {
+        let schema = schema_for("44");
+        let msg = generate_message(&schema, &schema.messages["Heartbeat"], false, fixed_now());
+        let shown = msg.replace(SOH, "|");
+        assert!(shown.starts_with("8=FIX.4.4|9="), "{shown}");
+        assert!(shown.contains("|35=0|"), "{shown}");
+        assert!(shown.contains("|52=20250314-09:26:53.589|"), "{shown}");
+        assert!(
+            !shown.contains("|112="),
+            "optional TestReqID omitted: {shown}"
+        );
+    }
+
+    #[test]
+    fn fix50_messages_travel_over_fixt_with_appl_ver_id() {
+        let schema = SchemaTree {
+            version: "FIX 5.0".into(),
+            service_pack: "2".into(),
+            ..schema_for("44")
+        };
+        let (begin, appl_ver_id) = session_identity(&schema);
+        assert_eq!(begin, "FIXT.1.1");
+        assert_eq!(appl_ver_id, Some("9"));
+    }
+}
@@ -0,0 +1,239 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// SPDX-FileCopyrightText: 2025 Steve Clarke <stephenlclarke@mac.com> - https://xyzzy.tools
+
+//! `--seek`, `--seek-line`, `--limit` and `--progress`: decode a slice of a large log
+//! instead of streaming all of it.  Seeking only applies to files; the prettifier
+//! decides where messages start and end and reports them here for `--limit`.
+
+use std::io::{self, BufRead, Seek, SeekFrom, Write};
+use std::time::{Duration, Instant};
+
+/// How often the `--progress` line is redrawn.
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Where `--seek` or `--seek-line` starts reading each input file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SeekTo {
+    /// A byte offset, moved forward to the start of the next line.
+    Byte(u64),
+    /// A 1-based line number; the lines before it are skipped but still counted.
+    Line(usize),
+}
+
+/// Which part of the input to decode and whether to report progress through it.
+#[derive(Debug, Default)]
+pub struct InputRange {
+    seek: Option<SeekTo>,
+    limit: Option<usize>,
+    progress: bool,
+    /// FIX messages decoded so far, counted against `limit` across every input.
+    decoded: usize,
+    meter: Option<ProgressMeter>,
+}
+
+impl InputRange {
+    pub fn new(seek: Option<SeekTo>, limit: Option<usize>, progress: bool) -> Self {
+        Self {
+            seek,
+            limit,
+            progress,
+            ..Self::default()
+        }
+    }
+
+    /// How many more messages `--limit` allows, or `None` when there is no limit.
+    pub fn remaining(&self) -> Option<usize> {
+        self.limit.map(|limit| limit.saturating_sub(self.decoded))
+    }
+
+    pub fn record_decoded(&mut self, messages: usize) {
+        self.decoded += messages;
+    }
+
+    pub fn limit_reached(&self) -> bool {
+        self.remaining() == Some(0)
+    }
+
+    /// Move `reader` to where decoding of a file of `len` bytes should start, and start
+    /// the progress meter from there.  Returns the number of lines skipped, so line
+    /// numbers stay absolute after `--seek-line`; after a byte `--seek` they count from
+    /// the first whole line read.
+    pub fn start_file<R: BufRead + Seek>(
+        &mut self,
+        reader: &mut R,
+        label: &str,
+        len: u64,
+    ) -> io::Result<usize> {
+        let skipped = match self.seek {
+            Some(SeekTo::Byte(offset)) => {
+                seek_to_line_start(reader, offset)?;
+                0
+            }
+            Some(SeekTo::Line(line)) => skip_lines(reader, line.saturating_sub(1))?,
+            None => 0,
+        };
+        if self.progress {
+            self.meter = Some(ProgressMeter {
+                label: label.to_string(),
+                done: reader.stream_position()?,
+                total: len,
+                last_draw: None,
+            });
+        }
+        Ok(skipped)
+    }
+
+    /// Count `bytes` read from the current file, redrawing the progress line now and then.
+    pub fn advance(&mut self, bytes: usize, out: &mut dyn Write) {
+        if let Some(meter) = self.meter.as_mut() {
+            meter.done += bytes as u64;
+            if meter
+                .last_draw
+                .is_none_or(|drawn| drawn.elapsed() >= PROGRESS_INTERVAL)
+            {
+                meter.draw(out);
+            }
+        }
+    }
+
+    /// Draw the final progress line for the current file and end it.
+    pub fn finish_file(&mut self, out: &mut dyn Write) {
+        if let Some(mut meter) = self.meter.take() {
+            meter.draw(out);
+            let _ = writeln!(out);
+        }
+    }
+}
+
+#[derive(Debug)]
+struct ProgressMeter {
+    label: String,
+    done: u64,
+    total: u64,
+    last_draw: Option<Instant>,
+}
+
+impl ProgressMeter {
+    fn draw(&mut self, out: &mut dyn Write) {
+        // A followed file can outgrow the size it had when opened.
+        let total = self.total.max(self.done);
+        let percent = if total == 0 {
+            100.0
+        } else {
+            self.done as f64 * 100.0 / total as f64
+        };
+        let _ = write!(
+            out,
+            "\r{}: {} / {} ({percent:.1}%)",
+            self.label,
+            format_size(self.done),
+            format_size(total)
+        );
+        let _ = out.flush();
+        self.last_draw = Some(Instant::now());
+    }
+}
+
+/// Position `reader` at `offset`, or at the start of the following line when `offset`
+/// falls part-way through one.
+fn seek_to_line_start<R: BufRead + Seek>(reader: &mut R, offset: u64) -> io::Result<()> {
+    if offset == 0 {
+        reader.seek(SeekFrom::Start(0))?;
+        return Ok(());
+    }
+    // Reading from the byte before `offset` up to a newline consumes just that newline
+    // when `offset` is already a line start, and the rest of the partial line otherwise.
+    reader.seek(SeekFrom::Start(offset - 1))?;
+    reader.read_until(b'\n', &mut Vec::new())?;
+    Ok(())
+}
+
+/// Skip up to `lines` lines, returning how many there were.
+fn skip_lines<R: BufRead>(reader: &mut R, lines: usize) -> io::Result<usize> {
+    let mut discard = Vec::new();
+    for skipped in 0..lines {
+        discard.clear();
+        if reader.read_until(b'\n', &mut discard)? == 0 {
+            return Ok(skipped);
+        }
+    }
+    Ok(lines)
+}
+
+/// Format a byte count with a binary unit, e.g. `1.5 GiB`.
+fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{bytes} B")
+    } else {
+        format!("{value:.1} {}", UNITS[unit])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    const LOG: &str = "first\nsecond\nthird\n";
+
+    fn rest(reader: Cursor<&str>) -> String {
+        let pos = reader.position() as usize;
+        reader
+            .into_inner()
+            .get(pos..)
+            .unwrap_or_default()
+            .to_string()
+    }
+
+    #[test]
+    fn byte_seek_snaps_forward_to_the_next_line() {
+        for (offset, expected) in [
+            (0, LOG),
+            (3, "second\nthird\n"),
+            (6, "second\nthird\n"),
+            (7, "third\n"),
+            (99, ""),
+        ] {
+            let mut reader = Cursor::new(LOG);
+            let mut range = InputRange::new(Some(SeekTo::Byte(offset)), None, false);
+            let skipped = range.start_file(&mut reader, "log", 19).unwrap();
+            assert_eq!((skipped, rest(reader).as_str()), (0, expected), "{offset}");
+        }
+    }
+
+    #[test]
+    fn line_seek_counts_the_lines_it_skips() {
+        let mut reader = Cursor::new(LOG);
+        let mut range = InputRange::new(Some(SeekTo::Line(3)), None, false);
+        assert_eq!(range.start_file(&mut reader, "log", 19).unwrap(), 2);
+        assert_eq!(rest(reader), "third\n");
+
+        let mut reader = Cursor::new(LOG);
+        let mut range = InputRange::new(Some(SeekTo::Line(10)), None, false);
+        assert_eq!(range.start_file(&mut reader, "log", 19).unwrap(), 3);
+    }
+
+    #[test]
+    fn progress_reports_bytes_and_percent() {
+        let mut reader = Cursor::new(LOG);
+        let mut range = InputRange::new(Some(SeekTo::Line(2)), None, true);
+        range.start_file(&mut reader, "big.log", 2048).unwrap();
+        let mut out = Vec::new();
+        range.advance(1018, &mut out);
+        range.finish_file(&mut out);
+        let text = String::from_utf8(out).unwrap();
+        assert!(
+            text.starts_with("\rbig.log: 1.0 KiB / 2.0 KiB (50.0%)"),
+            "{text:?}"
+        );
+        assert!(text.ends_with('\n'));
+        assert_eq!(format_size(512), "512 B");
+    }
+}
@@ -13,3 +13,11 @@ pub const NEST_INDENT: usize = 4;
 pub const NAME_TEXT_OFFSET: usize = TAG_WIDTH + 1;
 /// Indent applied to entries inside a repeating group (relative to the group's own indent).
 pub const ENTRY_FIELD_INDENT: usize = TAG_WIDTH + 1;
+/// Marks where the value starts on a decoded field line, until the message's value
+/// column is known.  A private-use character that values are never shown with unescaped.
+pub const VALUE_START: char = '\u{10FE00}';
+/// Marks where the value ends, before any enum description or error.
+pub const VALUE_END: char = '\u{10FE01}';
+/// Fewest columns left for values before a message is laid out line by line instead
+/// of in one aligned column.
+pub const MIN_VALUE_WIDTH: usize = 20;
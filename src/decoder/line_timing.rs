@@ -0,0 +1,350 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// SPDX-FileCopyrightText: 2025 Steve Clarke <stephenlclarke@mac.com> - https://xyzzy.tools
+
+//! `--line-timestamp` and `--max-gap`: read the time each log line was written from
+//! the prefix in front of its FIX messages, show it beside validation and `--banner`
+//! output, and report the gaps between consecutive messages of each session once the
+//! input ends.  Only lines holding a FIX message are parsed.
+
+use crate::decoder::colours::palette;
+use crate::decoder::summary::{format_elapsed, render_table};
+use anyhow::{Result, anyhow};
+use chrono::format::{Item, Parsed, StrftimeItems, parse_and_remainder};
+use chrono::{DateTime, NaiveDateTime, TimeDelta};
+use once_cell::sync::Lazy;
+use std::collections::BTreeMap;
+use std::io::Write;
+
+/// Date and time layouts tried, in order, for the `iso8601` preset; a `Z` or numeric
+/// offset after them is applied separately.
+static ISO8601_ITEMS: Lazy<Vec<Vec<Item<'static>>>> = Lazy::new(|| {
+    ["%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%d %H:%M:%S%.f"]
+        .iter()
+        .map(|fmt| {
+            StrftimeItems::new(fmt)
+                .parse_to_owned()
+                .expect("valid format")
+        })
+        .collect()
+});
+
+/// How the timestamp at the start of a log line is written.
+#[derive(Debug, Clone)]
+pub enum LineTimestampFormat {
+    /// `2024-01-01T12:00:00.123456Z`, with a space instead of `T`, an offset or no zone.
+    Iso8601,
+    /// Milliseconds since the Unix epoch.
+    EpochMillis,
+    /// A strftime-style format, compiled once.
+    Strftime(Vec<Item<'static>>),
+}
+
+impl LineTimestampFormat {
+    /// Accept the `iso8601` and `epoch_ms` presets or a strftime format such as
+    /// `%d/%m/%Y %H:%M:%S%.f`.
+    pub fn parse(text: &str) -> Result<Self> {
+        match text.to_ascii_lowercase().as_str() {
+            "iso8601" => return Ok(Self::Iso8601),
+            "epoch_ms" => return Ok(Self::EpochMillis),
+            _ => {}
+        }
+        let items = StrftimeItems::new(text)
+            .parse_to_owned()
+            .ok()
+            .filter(|_| text.contains('%'))
+            .ok_or_else(|| {
+                anyhow!(
+                    "invalid value for --line-timestamp: {text} (expected iso8601, epoch_ms or a strftime format)"
+                )
+            })?;
+        Ok(Self::Strftime(items))
+    }
+
+    /// The time at the start of `line`, in UTC when the prefix carries an offset.
+    pub fn extract(&self, line: &str) -> Option<NaiveDateTime> {
+        match self {
+            Self::Iso8601 => ISO8601_ITEMS
+                .iter()
+                .find_map(|items| parse_iso8601(preset_prefix(line), items)),
+            Self::EpochMillis => {
+                let text = preset_prefix(line);
+                let digits = text.bytes().take_while(u8::is_ascii_digit).count();
+                let millis = text[..digits].parse().ok()?;
+                DateTime::from_timestamp_millis(millis).map(|time| time.naive_utc())
+            }
+            Self::Strftime(items) => {
+                let mut parsed = Parsed::new();
+                parse_and_remainder(&mut parsed, line, items.iter()).ok()?;
+                parsed
+                    .to_datetime()
+                    .map(|time| time.naive_utc())
+                    .or_else(|_| parsed.to_naive_datetime_with_offset(0))
+                    .ok()
+            }
+        }
+    }
+}
+
+/// The presets allow leading spaces and an opening bracket, as in `[2024-01-01 ...]`.
+fn preset_prefix(line: &str) -> &str {
+    let line = line.trim_start();
+    line.strip_prefix('[').unwrap_or(line)
+}
+
+fn parse_iso8601(text: &str, items: &[Item<'static>]) -> Option<NaiveDateTime> {
+    let mut parsed = Parsed::new();
+    let rest = parse_and_remainder(&mut parsed, text, items.iter()).ok()?;
+    let local = parsed.to_naive_datetime_with_offset(0).ok()?;
+    Some(local - utc_offset(rest).unwrap_or(TimeDelta::zero()))
+}
+
+/// A `+hh:mm`, `-hh:mm` or `+hhmm` offset at the start of `text`.
+fn utc_offset(text: &str) -> Option<TimeDelta> {
+    let sign = match text.as_bytes().first()? {
+        b'+' => 1,
+        b'-' => -1,
+        _ => return None,
+    };
+    let digits: String = text[1..]
+        .chars()
+        .take(5)
+        .filter(|c| *c != ':')
+        .take_while(char::is_ascii_digit)
+        .collect();
+    let hours: i64 = digits.get(..2)?.parse().ok()?;
+    let minutes: i64 = digits.get(2..4)?.parse().ok()?;
+    Some(TimeDelta::minutes(sign * (hours * 60 + minutes)))
+}
+
+/// Show a line time the way the report and banners print it.
+pub fn format_line_time(time: NaiveDateTime) -> String {
+    time.format("%Y-%m-%d %H:%M:%S%.f").to_string()
+}
+
+/// Message times per session and the gaps that exceeded `--max-gap`.
+#[derive(Debug)]
+pub struct LineTiming {
+    format: LineTimestampFormat,
+    max_gap: Option<TimeDelta>,
+    sessions: BTreeMap<(String, String), SessionGaps>,
+    long_gaps: Vec<LongGap>,
+    /// Lines holding a FIX message whose prefix did not match the format.
+    unparsed_lines: usize,
+}
+
+#[derive(Debug, Default)]
+struct SessionGaps {
+    last: Option<NaiveDateTime>,
+    gaps: Vec<TimeDelta>,
+}
+
+#[derive(Debug)]
+struct LongGap {
+    line: usize,
+    session: String,
+    gap: TimeDelta,
+}
+
+impl LineTiming {
+    pub fn new(format: LineTimestampFormat, max_gap: Option<TimeDelta>) -> Self {
+        Self {
+            format,
+            max_gap,
+            sessions: BTreeMap::new(),
+            long_gaps: Vec::new(),
+            unparsed_lines: 0,
+        }
+    }
+
+    /// Time a line known to hold FIX messages, counting it when the prefix does not parse.
+    pub fn stamp_line(&mut self, line: &str) -> Option<NaiveDateTime> {
+        let time = self.format.extract(line);
+        if time.is_none() {
+            self.unparsed_lines += 1;
+        }
+        time
+    }
+
+    /// Note that `msg` was logged at `time` on `line`, measuring the gap since the
+    /// previous message between the same pair of CompIDs in either direction.
+    pub fn record_message(&mut self, msg: &str, time: NaiveDateTime, line: usize) {
+        let sender = field_value(msg, "49").unwrap_or("?");
+        let target = field_value(msg, "56").unwrap_or("?");
+        let key = if sender <= target {
+            (sender.to_string(), target.to_string())
+        } else {
+            (target.to_string(), sender.to_string())
+        };
+        let session = self.sessions.entry(key).or_default();
+        if let Some(last) = session.last.replace(time) {
+            let gap = time - last;
+            session.gaps.push(gap);
+            if self.max_gap.is_some_and(|max| gap > max) {
+                self.long_gaps.push(LongGap {
+                    line,
+                    session: format!("{sender} -> {target}"),
+                    gap,
+                });
+            }
+        }
+    }
+
+    /// Print min, median, p99 and max gap per session, the gaps over `--max-gap` and
+    /// the number of lines that had no usable timestamp.
+    pub fn render(&self, out: &mut dyn Write) -> std::io::Result<()> {
+        let colours = palette();
+        writeln!(
+            out,
+            "{}Message Gaps{} ({} session(s))\n",
+            colours.title,
+            colours.reset,
+            self.sessions.len()
+        )?;
+        let rows: Vec<Vec<String>> = self
+            .sessions
+            .iter()
+            .map(|((a, b), session)| {
+                let mut gaps = session.gaps.clone();
+                gaps.sort();
+                let cell = |q: f64| percentile(&gaps, q).map_or("-".to_string(), format_gap);
+                vec![
+                    format!("{a} <-> {b}"),
+                    gaps.len().to_string(),
+                    cell(0.0),
+                    cell(0.5),
+                    cell(0.99),
+                    cell(1.0),
+                ]
+            })
+            .collect();
+        render_table(
+            out,
+            &["Session", "Gaps", "Min", "Median", "p99", "Max"],
+            &rows,
+        )?;
+        if let Some(max) = self.max_gap {
+            writeln!(
+                out,
+                "\n  {}Gaps over {}:{} {}",
+                colours.name,
+                format_gap(max),
+                colours.reset,
+                self.long_gaps.len()
+            )?;
+            for long in &self.long_gaps {
+                writeln!(
+                    out,
+                    "    Line {}: {} {}{}{}",
+                    long.line,
+                    long.session,
+                    colours.error,
+                    format_gap(long.gap),
+                    colours.reset
+                )?;
+            }
+        }
+        if self.unparsed_lines > 0 {
+            writeln!(
+                out,
+                "\n  {} line(s) with FIX messages had no parseable timestamp",
+                self.unparsed_lines
+            )?;
+        }
+        writeln!(out)
+    }
+}
+
+/// Nearest-rank percentile of sorted gaps.
+fn percentile(sorted: &[TimeDelta], q: f64) -> Option<TimeDelta> {
+    let rank = ((q * sorted.len() as f64).ceil() as usize).max(1);
+    sorted.get(rank - 1).copied()
+}
+
+/// Sub-second gaps in milliseconds with microsecond precision, longer ones as elapsed time.
+fn format_gap(gap: TimeDelta) -> String {
+    match gap.num_microseconds() {
+        Some(micros) if micros.abs() < 1_000_000 => format!("{:.3}ms", micros as f64 / 1000.0),
+        _ => format_elapsed(gap),
+    }
+}
+
+fn field_value<'a>(msg: &'a str, tag: &str) -> Option<&'a str> {
+    msg.split('\u{0001}').find_map(|field| {
+        field
+            .split_once('=')
+            .filter(|(t, _)| *t == tag)
+            .map(|(_, value)| value)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decoder::colours::disable_colours;
+    use chrono::NaiveDate;
+
+    fn at(h: u32, m: u32, s: u32, ms: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2024, 1, 2)
+            .unwrap()
+            .and_hms_milli_opt(h, m, s, ms)
+            .unwrap()
+    }
+
+    #[test]
+    fn presets_and_strftime_formats_read_the_line_prefix() {
+        let iso = LineTimestampFormat::parse("iso8601").unwrap();
+        assert_eq!(
+            iso.extract("2024-01-02T10:00:00.250Z 8=FIX.4.4"),
+            Some(at(10, 0, 0, 250))
+        );
+        assert_eq!(
+            iso.extract("[2024-01-02 11:00:00.250+01:00] IN 8=FIX.4.4"),
+            Some(at(10, 0, 0, 250))
+        );
+        assert_eq!(iso.extract("IN 8=FIX.4.4"), None);
+
+        let epoch = LineTimestampFormat::parse("epoch_ms").unwrap();
+        let millis = at(10, 0, 0, 250).and_utc().timestamp_millis();
+        assert_eq!(
+            epoch.extract(&format!("{millis} 8=FIX.4.4")),
+            Some(at(10, 0, 0, 250))
+        );
+
+        let custom = LineTimestampFormat::parse("%d/%m/%Y %H:%M:%S%.f").unwrap();
+        assert_eq!(
+            custom.extract("02/01/2024 10:00:00.250 | 8=FIX.4.4"),
+            Some(at(10, 0, 0, 250))
+        );
+        assert!(LineTimestampFormat::parse("rfc-ish").is_err());
+    }
+
+    #[test]
+    fn gaps_are_measured_per_session_and_long_ones_flagged() {
+        disable_colours();
+        let mut timing = LineTiming::new(LineTimestampFormat::Iso8601, Some(TimeDelta::seconds(5)));
+        let ab = "8=FIX.4.4\u{0001}35=0\u{0001}49=A\u{0001}56=B\u{0001}";
+        let ba = "8=FIX.4.4\u{0001}35=0\u{0001}49=B\u{0001}56=A\u{0001}";
+        let cd = "8=FIX.4.4\u{0001}35=0\u{0001}49=C\u{0001}56=D\u{0001}";
+        timing.record_message(ab, at(10, 0, 0, 0), 1);
+        timing.record_message(cd, at(10, 0, 0, 500), 2);
+        timing.record_message(ba, at(10, 0, 0, 2), 3);
+        timing.record_message(ab, at(10, 0, 30, 2), 4);
+        assert!(timing.stamp_line("no time 8=FIX.4.4").is_none());
+
+        let mut out = Vec::new();
+        timing.render(&mut out).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        assert!(text.contains("Message Gaps (2 session(s))"), "{text}");
+        let row = text.lines().find(|l| l.contains("A <-> B")).unwrap();
+        let cells: Vec<&str> = row.split_whitespace().collect();
+        assert_eq!(
+            cells,
+            [
+                "A", "<->", "B", "2", "2.000ms", "2.000ms", "30.000s", "30.000s"
+            ]
+        );
+        assert!(text.contains("Gaps over 5.000s: 1"), "{text}");
+        assert!(text.contains("Line 4: A -> B 30.000s"), "{text}");
+        assert!(text.contains("1 line(s) with FIX messages had no parseable timestamp"));
+    }
+}
@@ -0,0 +1,230 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// SPDX-FileCopyrightText: 2025 Steve Clarke <stephenlclarke@mac.com> - https://xyzzy.tools
+
+//! `--md-ladder`: after the field dump of a MarketDataSnapshotFullRefresh (35=W) or
+//! MarketDataIncrementalRefresh (35=X), its NoMDEntries(268) entries again as a compact
+//! price ladder, one line each.  Bids come best first (highest price), then offers best
+//! first (lowest price), then any other entries, such as trades, in message order.
+
+use crate::decoder::colours::ColourPalette;
+use crate::decoder::display::{DisplayOptions, pad_ansi, visible_width};
+use crate::decoder::fixparser::FieldValue;
+use crate::decoder::layout::BASE_INDENT;
+use crate::decoder::tag_lookup::FixTagLookup;
+use std::cmp::Ordering;
+
+pub const NO_MD_ENTRIES: u32 = 268;
+const MD_ENTRY_TYPE: u32 = 269;
+const MD_ENTRY_PX: u32 = 270;
+const MD_ENTRY_SIZE: u32 = 271;
+const MD_UPDATE_ACTION: u32 = 279;
+const MD_ENTRY_POSITION_NO: u32 = 290;
+
+/// The fields of one NoMDEntries(268) entry that make up a ladder line.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LadderEntry {
+    pub action: Option<String>,
+    pub entry_type: Option<String>,
+    pub price: Option<String>,
+    pub size: Option<String>,
+    pub position: Option<String>,
+}
+
+impl LadderEntry {
+    /// The entry held in `fields`, the first occurrence of each tag winning.
+    pub fn from_fields(fields: &[FieldValue]) -> Self {
+        let first = |tag: u32| {
+            fields
+                .iter()
+                .find(|field| field.tag == tag)
+                .map(|field| field.value.clone())
+        };
+        Self {
+            action: first(MD_UPDATE_ACTION),
+            entry_type: first(MD_ENTRY_TYPE),
+            price: first(MD_ENTRY_PX),
+            size: first(MD_ENTRY_SIZE),
+            position: first(MD_ENTRY_POSITION_NO),
+        }
+    }
+
+    /// Bids, then offers, then everything else.
+    fn side_rank(&self) -> u8 {
+        match self.entry_type.as_deref() {
+            Some("0") => 0,
+            Some("1") => 1,
+            _ => 2,
+        }
+    }
+
+    fn price_value(&self) -> Option<f64> {
+        self.price.as_deref().and_then(|px| px.parse().ok())
+    }
+}
+
+/// Whether messages of `msg_type` get a ladder.
+pub fn is_market_data(msg_type: &str) -> bool {
+    matches!(msg_type, "W" | "X")
+}
+
+/// Best price first within each side; entries without a price go last on theirs.
+fn ladder_order(a: &LadderEntry, b: &LadderEntry) -> Ordering {
+    let best_first = |highest: bool| match (a.price_value(), b.price_value()) {
+        (Some(pa), Some(pb)) if highest => pb.total_cmp(&pa),
+        (Some(pa), Some(pb)) => pa.total_cmp(&pb),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    };
+    a.side_rank()
+        .cmp(&b.side_rank())
+        .then_with(|| match a.side_rank() {
+            0 => best_first(true),
+            1 => best_first(false),
+            _ => Ordering::Equal,
+        })
+}
+
+/// The ladder for `entries`, with each entry's MDUpdateAction(279) in front when
+/// `incremental`.  Values are shown under `display`.  Empty when there are no entries.
+pub fn render(
+    entries: &[LadderEntry],
+    incremental: bool,
+    dict: &FixTagLookup,
+    colours: &ColourPalette,
+    display: &DisplayOptions,
+) -> String {
+    if entries.is_empty() {
+        return String::new();
+    }
+    let mut sorted: Vec<&LadderEntry> = entries.iter().collect();
+    sorted.sort_by(|a, b| ladder_order(a, b));
+
+    let describe = |tag: u32, value: &Option<String>| match value {
+        Some(value) => format!(
+            "{}{}{}",
+            colours.enumeration,
+            display.display_value(dict.enum_description(tag, value).unwrap_or(value)),
+            colours.reset
+        ),
+        None => "-".to_string(),
+    };
+    let plain = |value: &Option<String>| match value {
+        Some(value) => format!(
+            "{}{}{}",
+            colours.value,
+            display.display_value(value),
+            colours.reset
+        ),
+        None => "-".to_string(),
+    };
+
+    let mut header = vec!["Type", "Price", "Size", "Position"];
+    if incremental {
+        header.insert(0, "Action");
+    }
+    let mut rows: Vec<Vec<String>> = vec![
+        header
+            .iter()
+            .map(|title| format!("{}{}{}", colours.name, title, colours.reset))
+            .collect(),
+    ];
+    for entry in sorted {
+        let mut row = vec![
+            describe(MD_ENTRY_TYPE, &entry.entry_type),
+            plain(&entry.price),
+            plain(&entry.size),
+            plain(&entry.position),
+        ];
+        if incremental {
+            row.insert(0, describe(MD_UPDATE_ACTION, &entry.action));
+        }
+        rows.push(row);
+    }
+    let widths: Vec<usize> = (0..header.len())
+        .map(|col| {
+            rows.iter()
+                .map(|row| visible_width(&row[col]))
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+
+    let indent = " ".repeat(BASE_INDENT);
+    let mut output = format!(
+        "{indent}{}Ladder ({} entries){}\n",
+        colours.title,
+        entries.len(),
+        colours.reset
+    );
+    for row in rows {
+        let last = row.len() - 1;
+        let cells: Vec<String> = row
+            .iter()
+            .enumerate()
+            .map(|(col, cell)| {
+                if col == last {
+                    cell.clone()
+                } else {
+                    pad_ansi(cell, widths[col])
+                }
+            })
+            .collect();
+        output.push_str(&format!("{indent}  {}\n", cells.join("  ")));
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decoder::tag_lookup::load_dictionary;
+
+    const PLAIN: ColourPalette = ColourPalette {
+        reset: "",
+        line: "",
+        tag: "",
+        name: "",
+        value: "",
+        enumeration: "",
+        file: "",
+        error: "",
+        warning: "",
+        message: "",
+        title: "",
+    };
+
+    fn entry(action: &str, entry_type: &str, price: Option<&str>, size: &str) -> LadderEntry {
+        LadderEntry {
+            action: Some(action.into()),
+            entry_type: Some(entry_type.into()),
+            price: price.map(str::to_string),
+            size: Some(size.into()),
+            position: None,
+        }
+    }
+
+    #[test]
+    fn incremental_refreshes_show_the_update_action_and_unpriced_entries_go_last() {
+        let dict = load_dictionary("8=FIX.4.4\u{1}");
+        let entries = [
+            entry("2", "1", None, "0"),
+            entry("0", "1", Some("10.5"), "100"),
+            entry("1", "0", Some("9.75"), "300"),
+            entry("0", "0", Some("10"), "200"),
+        ];
+        assert_eq!(
+            render(&entries, true, &dict, &PLAIN, &DisplayOptions::default()),
+            "  Ladder (4 entries)\n\
+             \x20   Action  Type   Price  Size  Position\n\
+             \x20   NEW     BID    10     200   -\n\
+             \x20   CHANGE  BID    9.75   300   -\n\
+             \x20   NEW     OFFER  10.5   100   -\n\
+             \x20   DELETE  OFFER  -      0     -\n"
+        );
+        assert_eq!(
+            render(&[], true, &dict, &PLAIN, &DisplayOptions::default()),
+            ""
+        );
+    }
+}
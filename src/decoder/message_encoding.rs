@@ -0,0 +1,67 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// SPDX-FileCopyrightText: 2025 Steve Clarke <stephenlclarke@mac.com> - https://xyzzy.tools
+
+//! MessageEncoding(347) names the character set of a message's Encoded* fields, such as
+//! EncodedText(355).  Decoded output shows those values as text in that encoding, so
+//! Japanese free text reads as written rather than as mojibake; validation and
+//! checksums keep working on the bytes as sent.
+
+use crate::decoder::raw_bytes;
+use crate::decoder::tag_lookup::FixTagLookup;
+use encoding_rs::{EUC_JP, Encoding, ISO_2022_JP, SHIFT_JIS, UTF_8};
+
+pub const MESSAGE_ENCODING_TAG: u32 = 347;
+
+/// The encodings FIX defines for MessageEncoding(347).
+const RECOGNISED: [&Encoding; 4] = [UTF_8, SHIFT_JIS, EUC_JP, ISO_2022_JP];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MessageEncoding(&'static Encoding);
+
+impl MessageEncoding {
+    /// The encoding a MessageEncoding(347) value names, e.g. `SHIFT_JIS`, when it is one
+    /// FIX defines.
+    pub fn from_value(value: &str) -> Option<Self> {
+        let encoding = Encoding::for_label(value.trim().as_bytes())?;
+        RECOGNISED.contains(&encoding).then_some(Self(encoding))
+    }
+
+    /// e.g. `Shift_JIS`.
+    pub fn name(&self) -> &'static str {
+        self.0.name()
+    }
+
+    /// `value` decoded from this encoding, or `None` when its bytes are not valid in it.
+    pub fn decode(&self, value: &str) -> Option<String> {
+        let bytes = raw_bytes::to_bytes(value);
+        self.0
+            .decode_without_bom_handling_and_without_replacement(&bytes)
+            .map(|text| text.into_owned())
+    }
+}
+
+/// Whether `tag` is one of the Encoded* DATA fields MessageEncoding(347) applies to.
+pub fn is_encoded_field(dict: &FixTagLookup, tag: u32) -> bool {
+    dict.field_type(tag) == Some("DATA") && dict.field_name(tag).starts_with("Encoded")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recognises_the_encodings_fix_defines() {
+        for value in ["SHIFT_JIS", "UTF-8", "EUC-JP", "ISO-2022-JP"] {
+            assert!(MessageEncoding::from_value(value).is_some(), "{value}");
+        }
+        assert_eq!(MessageEncoding::from_value("windows-1252"), None);
+        assert_eq!(MessageEncoding::from_value("EBCDIC"), None);
+
+        let sjis = MessageEncoding::from_value("SHIFT_JIS").unwrap();
+        assert_eq!(sjis.name(), "Shift_JIS");
+        let text = raw_bytes::decode(b"\x93\x8c\x8b\x9e");
+        assert_eq!(sjis.decode(&text).as_deref(), Some("東京"));
+        let truncated = raw_bytes::decode(b"\x93\x8c\x8b");
+        assert_eq!(sjis.decode(&truncated), None);
+    }
+}
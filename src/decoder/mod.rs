@@ -1,21 +1,55 @@
 // SPDX-License-Identifier: AGPL-3.0-only
 // SPDX-FileCopyrightText: 2025 Steve Clarke <stephenlclarke@mac.com> - https://xyzzy.tools
 
+pub mod benchmark;
+pub mod browser;
+pub mod clock_skew;
 pub mod colours;
+pub mod dedupe;
+pub mod delimiter;
+pub mod deprecations;
+pub mod dict_cache;
+pub mod dictionary_check;
 pub mod display;
+pub mod enum_overlay;
+pub mod file_prefix;
+pub mod findings_report;
+pub mod fix_map;
 pub mod fixparser;
+pub mod generator;
+pub mod input_range;
 pub mod layout;
+pub mod line_timing;
+pub mod md_ladder;
+pub mod message_encoding;
+pub mod msg_type_filter;
+pub mod order_chains;
 pub mod prettifier;
+pub mod raw_bytes;
+pub mod remote;
+pub mod repair;
+pub mod replay;
 pub mod schema;
+pub mod sequence_diagram;
+pub mod split_output;
+pub mod stream;
 pub mod summary;
+pub mod tag_histogram;
 pub mod tag_lookup;
+pub mod tag_usage;
+pub mod udf;
+pub mod validation_summary;
 pub mod validator;
+pub mod warnings;
+pub mod watcher;
 
 pub use display::{
-    DisplayStyle, display_component, display_message, list_all_components, list_all_messages,
-    list_all_tags, print_component_columns, print_message_columns, print_tag_details,
+    DisplayStyle, Outline, display_component, display_message, list_all_components,
+    list_all_messages, list_all_tags, print_component_columns, print_enum_lookup,
+    print_fields_in_columns, print_message_columns, print_search_results, print_tag_details,
     print_tags_in_columns,
 };
 pub use prettifier::{PrettifyContext, disable_output_colours, prettify_files};
 pub use schema::FixDictionary;
+pub use stream::{DecodeOptions, DecodedMessage, FixMessageIter};
 pub use tag_lookup::register_dictionary as register_fix_dictionary;
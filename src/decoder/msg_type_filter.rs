@@ -0,0 +1,85 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// SPDX-FileCopyrightText: 2025 Steve Clarke <stephenlclarke@mac.com> - https://xyzzy.tools
+
+//! `--only-msgtype` and `--exclude-msgtype`: keep or drop messages by MsgType(35) as
+//! soon as they are found, so the ones dropped are never decoded, validated, summarised
+//! or counted.  `--count-filtered` still counts them in the MsgType totals.
+
+use std::collections::BTreeSet;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MsgTypeFilter {
+    /// When not empty, only these MsgTypes are kept.
+    only: BTreeSet<String>,
+    exclude: BTreeSet<String>,
+    /// `--count-filtered`: dropped messages still count towards the MsgType totals.
+    pub count_filtered: bool,
+}
+
+impl MsgTypeFilter {
+    /// A filter from MsgType codes, or `Err` with the codes both kept and excluded.
+    pub fn new(
+        only: impl IntoIterator<Item = String>,
+        exclude: impl IntoIterator<Item = String>,
+        count_filtered: bool,
+    ) -> Result<Self, Vec<String>> {
+        let only: BTreeSet<String> = only.into_iter().collect();
+        let exclude: BTreeSet<String> = exclude.into_iter().collect();
+        let both: Vec<String> = only.intersection(&exclude).cloned().collect();
+        if !both.is_empty() {
+            return Err(both);
+        }
+        Ok(Self {
+            only,
+            exclude,
+            count_filtered,
+        })
+    }
+
+    /// True when every message is kept.
+    pub fn is_empty(&self) -> bool {
+        self.only.is_empty() && self.exclude.is_empty()
+    }
+
+    /// Whether a message of MsgType `msg_type` is kept.  One without a MsgType is kept
+    /// unless only some MsgTypes are.
+    pub fn keeps(&self, msg_type: Option<&str>) -> bool {
+        match msg_type {
+            Some(mt) => {
+                (self.only.is_empty() || self.only.contains(mt)) && !self.exclude.contains(mt)
+            }
+            None => self.only.is_empty(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn codes(list: &[&str]) -> Vec<String> {
+        list.iter().map(|code| code.to_string()).collect()
+    }
+
+    #[test]
+    fn keeps_only_or_drops_the_listed_msg_types() {
+        let only = MsgTypeFilter::new(codes(&["8", "9"]), codes(&[]), false).unwrap();
+        assert!(only.keeps(Some("8")));
+        assert!(!only.keeps(Some("D")));
+        assert!(!only.keeps(None));
+
+        let except = MsgTypeFilter::new(codes(&[]), codes(&["0", "1"]), false).unwrap();
+        assert!(!except.keeps(Some("0")));
+        assert!(except.keeps(Some("D")));
+        assert!(except.keeps(None));
+
+        assert!(MsgTypeFilter::default().is_empty());
+        assert!(MsgTypeFilter::default().keeps(Some("0")));
+    }
+
+    #[test]
+    fn a_msg_type_both_kept_and_excluded_is_refused() {
+        let err = MsgTypeFilter::new(codes(&["8", "0"]), codes(&["0", "1"]), false).unwrap_err();
+        assert_eq!(err, ["0"]);
+    }
+}
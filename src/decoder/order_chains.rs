@@ -0,0 +1,511 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// SPDX-FileCopyrightText: 2025 Steve Clarke <stephenlclarke@mac.com> - https://xyzzy.tools
+
+//! `--validate-session`: protocol errors that only show across the messages of an
+//! order.  Each session's orders are followed from NewOrderSingle through cancel and
+//! replace requests to their ExecutionReports, with every ClOrdID(11), OrigClOrdID(41)
+//! and OrderID(37) seen aliased to the order it belongs to, as `--summary` keys them.
+//! A finding is added to the offending message's validation report and counted for a
+//! table at the end of the run.
+
+use crate::decoder::colours::palette;
+use crate::decoder::fixparser::ParsedMessage;
+use crate::decoder::summary::{ord_status_state, render_table};
+use crate::decoder::validator::{ErrorCategory, ValidationReport};
+use std::collections::{BTreeMap, HashMap};
+use std::io::Write;
+
+/// The kinds of cross-message problem reported.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Check {
+    /// A cancel or replace request names an OrigClOrdID(41) never seen in the session.
+    UnknownOrigClOrdId,
+    /// CumQty(14) is more than the tolerance above OrderQty(38).
+    Overfill,
+    /// OrdStatus(39) moves backwards, or changes once the order is closed.
+    StatusRegression,
+    /// A Replaced or Canceled report's ClOrdID(11) and OrigClOrdID(41) do not match the
+    /// request it answers.
+    MismatchedAck,
+}
+
+impl Check {
+    fn label(self) -> &'static str {
+        match self {
+            Check::UnknownOrigClOrdId => "unknown OrigClOrdID",
+            Check::Overfill => "overfill",
+            Check::StatusRegression => "status regression",
+            Check::MismatchedAck => "mismatched ack",
+        }
+    }
+}
+
+/// A cancel or replace request waiting for its ExecutionReport.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Pending {
+    replace: bool,
+    orig: String,
+}
+
+#[derive(Debug, Default)]
+struct OrderState {
+    order_qty: Option<f64>,
+    /// The last OrdStatus(39) that places the order on the New, Partially Filled,
+    /// closed scale; pending and other states leave it alone.
+    status: Option<String>,
+}
+
+#[derive(Debug, Default)]
+struct SessionOrders {
+    /// Every ClOrdID, OrigClOrdID and OrderID seen, mapped to its order.
+    aliases: HashMap<String, usize>,
+    orders: Vec<OrderState>,
+    /// Open cancel (F) and replace (G) requests by their ClOrdID(11).
+    pending: HashMap<String, Pending>,
+}
+
+impl SessionOrders {
+    fn order_for(&mut self, ids: [Option<&str>; 3]) -> usize {
+        let found = ids
+            .iter()
+            .flatten()
+            .find_map(|id| self.aliases.get(*id).copied());
+        let order = found.unwrap_or_else(|| {
+            self.orders.push(OrderState::default());
+            self.orders.len() - 1
+        });
+        for id in ids.into_iter().flatten() {
+            self.aliases.entry(id.to_string()).or_insert(order);
+        }
+        order
+    }
+}
+
+/// Findings per session and [`Check`]: how many, and the line of the first.
+#[derive(Debug, Clone, Copy)]
+struct Tally {
+    count: usize,
+    first_line: usize,
+}
+
+/// Order state for every session, keyed by its CompID pair in either direction.
+#[derive(Debug)]
+pub struct OrderChains {
+    fill_tolerance: f64,
+    sessions: HashMap<(String, String), SessionOrders>,
+    tally: BTreeMap<((String, String), Check), Tally>,
+}
+
+impl OrderChains {
+    /// `fill_tolerance` is how far CumQty(14) may exceed OrderQty(38) before it counts
+    /// as an overfill.
+    pub fn new(fill_tolerance: f64) -> Self {
+        Self {
+            fill_tolerance,
+            sessions: HashMap::new(),
+            tally: BTreeMap::new(),
+        }
+    }
+
+    /// Follow `msg` through its order, adding a finding to `report` for each
+    /// problem.  `line` is where the message was read, for the end-of-run table.
+    pub fn check(&mut self, msg: &ParsedMessage<'_>, line: usize, report: &mut ValidationReport) {
+        let (sender, target) = (msg.first(49).unwrap_or("?"), msg.first(56).unwrap_or("?"));
+        let session = if sender <= target {
+            (sender.to_string(), target.to_string())
+        } else {
+            (target.to_string(), sender.to_string())
+        };
+        let orders = self.sessions.entry(session.clone()).or_default();
+        let findings = match msg.first(35) {
+            Some("D") => {
+                let order = orders.order_for([msg.first(11), None, None]);
+                orders.orders[order].order_qty = msg.first(38).and_then(parse_qty);
+                Vec::new()
+            }
+            Some(mt @ ("F" | "G")) => check_request(orders, msg, mt == "G"),
+            Some("8") => check_report(orders, msg, self.fill_tolerance),
+            Some("9") => {
+                if let Some(cl) = msg.first(11) {
+                    orders.pending.remove(cl);
+                }
+                Vec::new()
+            }
+            _ => Vec::new(),
+        };
+        for (check, tag, message) in findings {
+            report.add_tag_error(msg, tag, ErrorCategory::OrderState, message);
+            self.tally
+                .entry((session.clone(), check))
+                .and_modify(|tally| tally.count += 1)
+                .or_insert(Tally {
+                    count: 1,
+                    first_line: line,
+                });
+        }
+    }
+
+    /// Print the findings per session and check, with the line of the first of each.
+    /// Silent when every order chain was consistent.
+    pub fn render(&self, out: &mut dyn Write) -> std::io::Result<()> {
+        if self.tally.is_empty() {
+            return Ok(());
+        }
+        let colours = palette();
+        writeln!(
+            out,
+            "{}Order Chain Findings{}\n",
+            colours.title, colours.reset
+        )?;
+        let rows: Vec<Vec<String>> = self
+            .tally
+            .iter()
+            .map(|(((a, b), check), tally)| {
+                vec![
+                    format!("{a} <-> {b}"),
+                    check.label().to_string(),
+                    tally.count.to_string(),
+                    tally.first_line.to_string(),
+                ]
+            })
+            .collect();
+        render_table(out, &["Session", "Check", "Findings", "First line"], &rows)?;
+        writeln!(out)
+    }
+}
+
+type Finding = (Check, u32, String);
+
+/// An OrderCancelRequest (F) or OrderCancelReplaceRequest (G): its OrigClOrdID(41)
+/// must name an order already seen, and it waits for the report that answers it.
+fn check_request(
+    orders: &mut SessionOrders,
+    msg: &ParsedMessage<'_>,
+    replace: bool,
+) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    let (cl, orig) = (msg.first(11), msg.first(41));
+    if let Some(orig) = orig
+        && !orders.aliases.contains_key(orig)
+    {
+        findings.push((
+            Check::UnknownOrigClOrdId,
+            41,
+            format!("OrigClOrdID(41) {orig} matches no ClOrdID seen earlier in the session"),
+        ));
+    }
+    orders.order_for([orig, cl, None]);
+    if let (Some(cl), Some(orig)) = (cl, orig) {
+        orders.pending.insert(
+            cl.to_string(),
+            Pending {
+                replace,
+                orig: orig.to_string(),
+            },
+        );
+    }
+    findings
+}
+
+/// An ExecutionReport (8): acks must pair 11 and 41 as the request did, CumQty(14)
+/// must not pass OrderQty(38), and OrdStatus(39) must not move backwards.
+fn check_report(
+    orders: &mut SessionOrders,
+    msg: &ParsedMessage<'_>,
+    tolerance: f64,
+) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    let (cl, orig) = (msg.first(11), msg.first(41));
+    let exec_type = msg.first(150).unwrap_or_default();
+
+    if matches!(exec_type, "4" | "5") {
+        let replace = exec_type == "5";
+        let what = if replace { "Replaced" } else { "Canceled" };
+        let pending = cl.and_then(|cl| orders.pending.remove(cl));
+        match pending {
+            Some(pending) if pending.replace != replace => findings.push((
+                Check::MismatchedAck,
+                150,
+                format!(
+                    "{what} report answers an {} for ClOrdID(11) {}",
+                    request_name(pending.replace),
+                    cl.unwrap_or_default()
+                ),
+            )),
+            Some(pending) if orig != Some(pending.orig.as_str()) => findings.push((
+                Check::MismatchedAck,
+                41,
+                format!(
+                    "{what} report pairs ClOrdID(11) {} with OrigClOrdID(41) {}, but the {} named {}",
+                    cl.unwrap_or_default(),
+                    orig.unwrap_or("(none)"),
+                    request_name(pending.replace),
+                    pending.orig
+                ),
+            )),
+            Some(_) => {}
+            // A venue may cancel on its own, but a replace always answers a request.
+            None if replace => findings.push((
+                Check::MismatchedAck,
+                11,
+                format!(
+                    "Replaced report for ClOrdID(11) {} matches no pending OrderCancelReplaceRequest",
+                    cl.unwrap_or("(none)")
+                ),
+            )),
+            None => {}
+        }
+    }
+
+    let order = orders.order_for([cl, orig, msg.first(37)]);
+    let state = &mut orders.orders[order];
+    if let Some(qty) = msg.first(38).and_then(parse_qty) {
+        state.order_qty = Some(qty);
+    }
+    if let (Some(cum), Some(qty)) = (msg.first(14), state.order_qty)
+        && let Some(cum_qty) = parse_qty(cum)
+        && cum_qty > qty + tolerance
+    {
+        let allowance = if tolerance > 0.0 {
+            format!(" by more than the tolerance of {tolerance}")
+        } else {
+            String::new()
+        };
+        findings.push((
+            Check::Overfill,
+            14,
+            format!("CumQty(14) {cum} exceeds OrderQty(38) {qty}{allowance}"),
+        ));
+    }
+
+    // Trade corrections, trade cancels and status replies restate the order rather
+    // than move it on.
+    if let Some(status) = msg.first(39)
+        && !matches!(exec_type, "G" | "H" | "I")
+        && let Some(rank) = status_rank(status)
+    {
+        if let Some(previous) = state.status.as_deref()
+            && let Some(finding) = regression(previous, status, rank)
+        {
+            findings.push((Check::StatusRegression, 39, finding));
+        }
+        state.status = Some(status.to_string());
+    }
+    findings
+}
+
+/// Where an OrdStatus(39) sits on the way from New to closed; `None` for pending and
+/// other states that say nothing about progress.
+fn status_rank(status: &str) -> Option<u8> {
+    match status {
+        "A" | "0" => Some(0),
+        "1" => Some(1),
+        "2" | "4" | "8" | "C" => Some(2),
+        _ => None,
+    }
+}
+
+fn regression(previous: &str, status: &str, rank: u8) -> Option<String> {
+    let previous_rank = status_rank(previous)?;
+    let label = |code| ord_status_state(code).unwrap_or(code);
+    if previous_rank == 2 && status != previous {
+        Some(format!(
+            "OrdStatus(39) {} after the order was already {}",
+            label(status),
+            label(previous)
+        ))
+    } else if rank < previous_rank {
+        Some(format!(
+            "OrdStatus(39) went from {} back to {}",
+            label(previous),
+            label(status)
+        ))
+    } else {
+        None
+    }
+}
+
+fn request_name(replace: bool) -> &'static str {
+    if replace {
+        "OrderCancelReplaceRequest"
+    } else {
+        "OrderCancelRequest"
+    }
+}
+
+fn parse_qty(value: &str) -> Option<f64> {
+    value.parse::<f64>().ok().filter(|qty| qty.is_finite())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decoder::colours::disable_colours;
+
+    /// Run `messages` (`|`-delimited, between CLIENT and VENUE) through the checker,
+    /// returning each message's findings.
+    fn run(tolerance: f64, messages: &[&str]) -> (OrderChains, Vec<Vec<String>>) {
+        let mut chains = OrderChains::new(tolerance);
+        let findings = messages
+            .iter()
+            .enumerate()
+            .map(|(idx, body)| {
+                let (from, to) = if body.starts_with("35=8") || body.starts_with("35=9") {
+                    ("VENUE", "CLIENT")
+                } else {
+                    ("CLIENT", "VENUE")
+                };
+                let raw =
+                    format!("8=FIX.4.4|49={from}|56={to}|{body}|10=000|").replace('|', "\u{0001}");
+                let msg = ParsedMessage::parse(&raw);
+                let mut report = ValidationReport::default();
+                chains.check(&msg, idx + 1, &mut report);
+                assert_eq!(
+                    report.errors.len(),
+                    report.tag_errors.values().flatten().count()
+                );
+                report.errors.into_iter().map(|err| err.message).collect()
+            })
+            .collect();
+        (chains, findings)
+    }
+
+    fn flagged(findings: &[Vec<String>]) -> Vec<(usize, &str)> {
+        findings
+            .iter()
+            .enumerate()
+            .flat_map(|(idx, errs)| errs.iter().map(move |err| (idx + 1, err.as_str())))
+            .collect()
+    }
+
+    #[test]
+    fn a_clean_replace_chain_has_no_findings() {
+        let (_, findings) = run(
+            0.0,
+            &[
+                "35=D|11=A|38=100",
+                "35=8|11=A|37=X1|150=0|39=0|14=0|38=100",
+                "35=8|11=A|37=X1|150=F|39=1|14=40|38=100",
+                "35=G|11=B|41=A|38=200",
+                "35=8|11=B|41=A|37=X1|150=5|39=1|14=40|38=200",
+                "35=8|11=B|37=X1|150=F|39=2|14=200|38=200",
+            ],
+        );
+        assert!(flagged(&findings).is_empty(), "{findings:?}");
+    }
+
+    #[test]
+    fn requests_naming_an_unseen_orig_cl_ord_id_are_flagged() {
+        let (_, findings) = run(
+            0.0,
+            &["35=D|11=A|38=100", "35=G|11=B|41=Z|38=50", "35=F|11=C|41=A"],
+        );
+        assert_eq!(
+            flagged(&findings),
+            [(
+                2,
+                "OrigClOrdID(41) Z matches no ClOrdID seen earlier in the session"
+            )]
+        );
+    }
+
+    #[test]
+    fn fills_beyond_order_qty_are_flagged_past_the_tolerance() {
+        let messages = [
+            "35=D|11=A|38=100",
+            "35=8|11=A|150=F|39=1|14=100.5",
+            "35=8|11=A|150=F|39=2|14=102",
+        ];
+        let (_, strict) = run(0.0, &messages);
+        assert_eq!(
+            flagged(&strict),
+            [
+                (2, "CumQty(14) 100.5 exceeds OrderQty(38) 100"),
+                (3, "CumQty(14) 102 exceeds OrderQty(38) 100"),
+            ]
+        );
+        let (_, tolerant) = run(1.0, &messages);
+        assert_eq!(
+            flagged(&tolerant),
+            [(
+                3,
+                "CumQty(14) 102 exceeds OrderQty(38) 100 by more than the tolerance of 1"
+            )]
+        );
+    }
+
+    #[test]
+    fn status_regressions_and_reports_after_close_are_flagged() {
+        let (_, findings) = run(
+            0.0,
+            &[
+                "35=D|11=A|38=100",
+                "35=8|11=A|150=F|39=1|14=50",
+                "35=8|11=A|150=0|39=0|14=50",
+                "35=8|11=A|150=F|39=2|14=100",
+                "35=8|11=A|150=I|39=2|14=100",
+                "35=8|11=A|150=4|39=4|14=100",
+                "35=8|11=A|150=H|39=1|14=50",
+            ],
+        );
+        assert_eq!(
+            flagged(&findings),
+            [
+                (3, "OrdStatus(39) went from Partially Filled back to New"),
+                (
+                    6,
+                    "OrdStatus(39) Canceled after the order was already Filled"
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn acks_must_pair_cl_ord_id_and_orig_as_the_request_did() {
+        let (chains, findings) = run(
+            0.0,
+            &[
+                "35=D|11=A|38=100",
+                "35=G|11=B|41=A|38=200",
+                "35=8|11=B|41=Q|150=5|39=0",
+                "35=8|11=C|41=B|150=5|39=0",
+                "35=F|11=D|41=B",
+                "35=8|11=D|41=B|150=5|39=0",
+                "35=8|11=E|41=B|150=4|39=4",
+            ],
+        );
+        assert_eq!(
+            flagged(&findings),
+            [
+                (
+                    3,
+                    "Replaced report pairs ClOrdID(11) B with OrigClOrdID(41) Q, but the OrderCancelReplaceRequest named A"
+                ),
+                (
+                    4,
+                    "Replaced report for ClOrdID(11) C matches no pending OrderCancelReplaceRequest"
+                ),
+                (
+                    6,
+                    "Replaced report answers an OrderCancelRequest for ClOrdID(11) D"
+                ),
+            ]
+        );
+
+        disable_colours();
+        let mut out = Vec::new();
+        chains.render(&mut out).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        assert!(text.contains("Order Chain Findings"), "{text}");
+        let row = text
+            .lines()
+            .find(|line| line.contains("mismatched ack"))
+            .unwrap();
+        let cells: Vec<&str> = row
+            .split("  ")
+            .map(str::trim)
+            .filter(|c| !c.is_empty())
+            .collect();
+        assert_eq!(cells, ["CLIENT <-> VENUE", "mismatched ack", "3", "3"]);
+    }
+}
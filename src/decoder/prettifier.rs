@@ -1,25 +1,57 @@
 // SPDX-License-Identifier: AGPL-3.0-only
 // SPDX-FileCopyrightText: 2025 Steve Clarke <stephenlclarke@mac.com> - https://xyzzy.tools
 
+use crate::decoder::benchmark::{Benchmark, Lap, Phase};
+use crate::decoder::clock_skew::ClockSkewStats;
 use crate::decoder::colours::{disable_colours, palette};
-use crate::decoder::display::{indent, pad_ansi, terminal_width, visible_width};
-use crate::decoder::fixparser::{FieldValue, parse_fix};
-use crate::decoder::layout::{BASE_INDENT, ENTRY_FIELD_INDENT, NAME_TEXT_OFFSET};
-use crate::decoder::summary::OrderSummary;
+use crate::decoder::dedupe::{MessageDeduper, Seen, repeat_note};
+use crate::decoder::deprecations;
+use crate::decoder::display::{
+    DisplayOptions, data_preview, indent, leading_escapes, pad_ansi, push_control_escape,
+    render_xml_value, visible_width, wrap_visible,
+};
+use crate::decoder::file_prefix::{self, FilePrefix, FileStats};
+use crate::decoder::findings_report::FindingsReport;
+use crate::decoder::fix_map::FixMap;
+use crate::decoder::fixparser::{FieldValue, ParsedMessage};
+use crate::decoder::input_range::InputRange;
+use crate::decoder::layout::{
+    BASE_INDENT, ENTRY_FIELD_INDENT, MIN_VALUE_WIDTH, NAME_TEXT_OFFSET, VALUE_END, VALUE_START,
+};
+use crate::decoder::line_timing::{LineTiming, format_line_time};
+use crate::decoder::md_ladder::{self, LadderEntry};
+use crate::decoder::message_encoding::{self, MESSAGE_ENCODING_TAG, MessageEncoding};
+use crate::decoder::msg_type_filter::MsgTypeFilter;
+use crate::decoder::order_chains::OrderChains;
+use crate::decoder::raw_bytes;
+use crate::decoder::remote::{self, RemoteLog};
+use crate::decoder::replay::{self, Replayer};
+use crate::decoder::sequence_diagram::SequenceDiagram;
+use crate::decoder::split_output::SplitOutput;
+use crate::decoder::stream::{
+    LineDecoder, LogicalLine, RecordFormat, find_fix_message_indices, read_record,
+};
+use crate::decoder::summary::{OrderSummary, SessionSummary, parse_fix_timestamp};
+use crate::decoder::tag_histogram::TagHistogram;
 #[cfg(test)]
 use crate::decoder::tag_lookup::MessageDef;
 use crate::decoder::tag_lookup::{
-    FixTagLookup, GroupSpec as MessageDefGroupSpec, MessageDef as LookupMessageDef,
-    load_dictionary_with_override,
+    DictionaryChoice, FallbackKind, FixTagLookup, GroupSpec as MessageDefGroupSpec,
+    MessageDef as LookupMessageDef, Selection, declared_version, dictionary_choice, fix_map,
+    load_dictionary_with_override, load_label_dictionary,
 };
+use crate::decoder::tag_usage::TagUsage;
+use crate::decoder::validation_summary::ValidationSummary;
 use crate::decoder::validator;
+use crate::decoder::warnings;
+use crate::decoder::watcher::DictionaryWatcher;
 use crate::fix;
-use once_cell::sync::Lazy;
-use regex::Regex;
 use std::borrow::Cow;
-use std::collections::{HashMap, HashSet};
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs::File;
 use std::io::{self, BufRead, BufReader, Write};
+use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::Duration;
 
@@ -29,24 +61,279 @@ pub struct PrettifyContext<'a> {
     pub err_out: &'a mut dyn Write,
     pub obfuscator: &'a fix::Obfuscator,
     pub display_delimiter: char,
+    /// `--width`, `--max-value-width`, `--decode-xml` and the other switches that
+    /// change how a decoded message is shown.
+    pub display: DisplayOptions,
     pub summary: &'a mut Option<OrderSummary>,
+    pub session_summary: Option<SessionSummary>,
+    /// `--sequence-diagram`: an arrow per message, for main to write once the run ends.
+    pub sequence_diagram: Option<SequenceDiagram>,
+    pub line_timing: Option<LineTiming>,
+    /// `--dedupe`: decoded messages repeating the previous one in their session are
+    /// suppressed, with a note where each run ends.
+    pub dedupe: Option<MessageDeduper>,
     pub fix_override: Option<&'a str>,
     pub follow: bool,
     pub live_status_enabled: bool,
     pub validation_enabled: bool,
+    pub banner: bool,
+    pub dictionary_watcher: Option<DictionaryWatcher>,
+    pub count_only: bool,
+    /// `--only-msgtype` and `--exclude-msgtype`.
+    pub msg_types: MsgTypeFilter,
+    /// `--tag-usage`: tag counts per MsgType for a table at the end.
+    pub tag_usage: Option<TagUsage>,
+    /// `--histogram`: value counts of the `--tag` tags for a table at the end.
+    pub tag_histogram: Option<TagHistogram>,
+    /// `--split-output`: decoded messages go to files of their own instead of `out`.
+    pub split: Option<SplitOutput>,
+    /// `--benchmark`: time spent in each phase of the pipeline, for a report at the end.
+    pub benchmark: Option<Benchmark>,
+    pub dict_cache: DictionaryCache,
     pub message_counts: HashMap<String, MsgTypeCount>,
     pub counts_dirty: bool,
+    pub counts_layout: CountsLayout,
+    pub validation_stats: ValidationStats,
+    /// `--validate-summary`: errors tallied by category for a table at the end.
+    pub validation_summary: Option<ValidationSummary>,
+    /// `--check-clock-skew`: SendingTime(52) to TransactTime(60) skew per session for
+    /// a table at the end.
+    pub clock_skew: Option<ClockSkewStats>,
+    /// `--validate-session`: order state per session, checked against each message.
+    pub order_chains: Option<OrderChains>,
+    pub declared_versions: DeclaredVersions,
+    pub input_range: InputRange,
+    /// `--unescape` and `--join-lines`: how raw input lines become the logical lines
+    /// messages are decoded from.
+    pub lines: LineDecoder,
+    /// `--stdin-format`: how records are framed on stdin; files are always lines.
+    pub stdin_format: RecordFormat,
+    pub detected_delimiter: Option<char>,
+    /// `--passthrough`: echo each input line as the exact bytes read, ahead of the
+    /// blocks decoded from it, instead of the re-delimited, coloured line.
+    pub passthrough: bool,
+    /// `--raw-annotate`: show each invalid message as its raw text with carets under
+    /// the offending values and a numbered legend, instead of the decoded tree.
+    pub raw_annotate: bool,
+    /// `--hide-header` and `--hide-trailer`.
+    pub hidden: HiddenBlocks,
+    pub extract: ExtractSinks<'a>,
+    /// `--report`: validation findings written for other tools.
+    pub report: Option<FindingsReport<'a>>,
+    /// `--replay`: messages are re-sent raw at their logged pace instead of decoded.
+    pub replay: Option<Replayer<'a>>,
+    /// The file (or `(stdin)`) being decoded, as last given to [`announce_source`].
+    pub source: String,
+    /// Whether `source` is a file or URL, whose name then heads each `--validate` report.
+    pub source_is_file: bool,
+    /// `--prefix-file`: the tag in front of each output line, changed as each file starts.
+    pub file_prefix: Option<FilePrefix>,
     pub interrupted: &'static AtomicBool,
 }
 
+impl<'a> PrettifyContext<'a> {
+    /// A context with every optional mode switched off: SOH display delimiter, no
+    /// validation, banner, follow, joining or extraction.  Callers flip the public
+    /// fields they need.
+    pub fn new(
+        out: &'a mut dyn Write,
+        err_out: &'a mut dyn Write,
+        obfuscator: &'a fix::Obfuscator,
+        summary: &'a mut Option<OrderSummary>,
+    ) -> Self {
+        Self {
+            out,
+            err_out,
+            obfuscator,
+            display_delimiter: SOH,
+            display: DisplayOptions::default(),
+            summary,
+            session_summary: None,
+            sequence_diagram: None,
+            line_timing: None,
+            dedupe: None,
+            fix_override: None,
+            follow: false,
+            live_status_enabled: false,
+            validation_enabled: false,
+            banner: false,
+            dictionary_watcher: None,
+            count_only: false,
+            msg_types: MsgTypeFilter::default(),
+            tag_usage: None,
+            tag_histogram: None,
+            split: None,
+            benchmark: None,
+            dict_cache: DictionaryCache::default(),
+            message_counts: HashMap::new(),
+            counts_dirty: false,
+            counts_layout: CountsLayout::default(),
+            validation_stats: ValidationStats::default(),
+            validation_summary: None,
+            clock_skew: None,
+            order_chains: None,
+            declared_versions: DeclaredVersions::default(),
+            input_range: InputRange::default(),
+            lines: LineDecoder::default(),
+            stdin_format: RecordFormat::Lines,
+            detected_delimiter: None,
+            passthrough: false,
+            raw_annotate: false,
+            hidden: HiddenBlocks::default(),
+            extract: ExtractSinks::default(),
+            report: None,
+            replay: None,
+            source: String::new(),
+            source_is_file: false,
+            file_prefix: None,
+            interrupted: interrupt_flag(),
+        }
+    }
+}
+
+/// Destinations for `--extract` and `--extract-invalid`.  Messages are written one
+/// per line exactly as found in the input (after any `--secret` obfuscation).
+#[derive(Default)]
+pub struct ExtractSinks<'a> {
+    pub all: Option<&'a mut dyn Write>,
+    pub invalid: Option<&'a mut dyn Write>,
+}
+
+impl ExtractSinks<'_> {
+    pub fn flush(&mut self) -> io::Result<()> {
+        for sink in [self.all.as_mut(), self.invalid.as_mut()]
+            .into_iter()
+            .flatten()
+        {
+            sink.flush()?;
+        }
+        Ok(())
+    }
+}
+
+fn write_extracted(sink: &mut Option<&mut dyn Write>, msg: &str) -> io::Result<()> {
+    if let Some(out) = sink.as_mut() {
+        out.write_all(&raw_bytes::to_bytes(msg))?;
+        out.write_all(b"\n")?;
+    }
+    Ok(())
+}
+
+/// Running tally of messages checked by `--validate`, used for the end-of-run report
+/// and the exit code.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ValidationStats {
+    pub messages: usize,
+    pub invalid: usize,
+    /// Messages with `--warn-deprecated` warnings, valid or not.
+    pub warned: usize,
+}
+
+/// Messages checked by `--validate`, counted by the version each declares and the
+/// dictionary it was validated against, so the end-of-run report can show where the
+/// two disagree.
+#[derive(Debug, Default)]
+pub struct DeclaredVersions {
+    /// `(declared label, dictionary used)` to `(messages, whether the two disagree)`.
+    counts: BTreeMap<(String, String), (usize, bool)>,
+}
+
+impl DeclaredVersions {
+    /// Count one message, returning true the first time a mismatched pairing is seen.
+    fn record(&mut self, label: String, declared_key: &str, used_key: &str) -> bool {
+        let mismatch = !declared_key.eq_ignore_ascii_case(used_key);
+        let entry = self
+            .counts
+            .entry((label, used_key.to_string()))
+            .or_insert((0, mismatch));
+        entry.0 += 1;
+        mismatch && entry.0 == 1
+    }
+
+    fn has_mismatch(&self) -> bool {
+        self.counts.values().any(|(_, mismatch)| *mismatch)
+    }
+}
+
+/// Result of [`prettify_files`]: whether any input could not be read, plus the
+/// validation tallies for the run and what each input contributed to them.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct RunOutcome {
+    pub io_error: bool,
+    pub validation: ValidationStats,
+    pub files: Vec<FileStats>,
+}
+
 #[derive(Default, Clone)]
 pub struct MsgTypeCount {
     pub count: usize,
     pub label: Option<String>,
+    /// Total length of the raw messages counted.
+    pub bytes: usize,
+}
+
+/// How the MsgType counts are ordered (`--counts-sort`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CountsSort {
+    /// By MsgType code.
+    #[default]
+    Code,
+    /// Most frequent first.
+    Count,
+    /// Most bytes first.
+    Bytes,
+}
+
+impl CountsSort {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "code" => Some(Self::Code),
+            "count" => Some(Self::Count),
+            "bytes" => Some(Self::Bytes),
+            _ => None,
+        }
+    }
+}
+
+/// The layout of the MsgType counts printed at the end of a run.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CountsLayout {
+    /// `--counts-wide`: add each type's share of the messages, its bytes and average
+    /// size, and a totals row.
+    pub wide: bool,
+    pub sort: CountsSort,
+}
+
+/// Remembers the dictionary resolved for the previous message so runs of messages with
+/// the same BeginString skip the shared cache lookup.  FIXT.1.1 traffic is always
+/// re-resolved because the dictionary then depends on ApplVerID, as is everything under
+/// `--fix-map`, where it depends on the CompIDs.
+#[derive(Default)]
+pub struct DictionaryCache {
+    begin_string: String,
+    dict: Option<Arc<FixTagLookup>>,
+}
+
+impl DictionaryCache {
+    fn lookup(&mut self, msg: &str, fix_override: Option<&str>) -> &FixTagLookup {
+        let begin = msg
+            .strip_prefix("8=")
+            .and_then(|rest| rest.split('\u{0001}').next())
+            .unwrap_or_default();
+        let reusable = self.dict.is_some()
+            && begin != "FIXT.1.1"
+            && begin == self.begin_string
+            && fix_map().is_none();
+        if !reusable {
+            self.begin_string.clear();
+            self.begin_string.push_str(begin);
+            self.dict = Some(load_dictionary_with_override(msg, fix_override));
+        }
+        self.dict.as_deref().expect("dictionary resolved")
+    }
 }
 
-static FIX_REGEX: Lazy<Regex> =
-    Lazy::new(|| Regex::new(r"8=FIX.*?10=\d{3}\u{0001}").expect("valid regex"));
+const SOH: char = '\u{0001}';
 
 static INTERRUPTED: AtomicBool = AtomicBool::new(false);
 const FOLLOW_SLEEP: Duration = Duration::from_millis(250);
@@ -56,30 +343,86 @@ pub fn interrupt_flag() -> &'static AtomicBool {
     &INTERRUPTED
 }
 
+/// Standard header and trailer blocks left out of decoded messages (`--hide-header`,
+/// `--hide-trailer`) so the business payload stands out.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HiddenBlocks {
+    pub header: bool,
+    pub trailer: bool,
+}
+
+impl HiddenBlocks {
+    /// The tags to leave out of a message decoded with `dict`: those its Header and
+    /// Trailer components define, except MsgType(35) and any field `report` finds
+    /// fault with, which is always shown.
+    pub fn tags(
+        &self,
+        dict: &FixTagLookup,
+        report: Option<&validator::ValidationReport>,
+    ) -> HashSet<u32> {
+        let mut tags = HashSet::new();
+        if self.header {
+            tags.extend(dict.header_tags());
+        }
+        if self.trailer {
+            tags.extend(dict.trailer_tags());
+        }
+        tags.remove(&35);
+        for (tag, errs) in report.map(|r| &r.tag_errors).into_iter().flatten() {
+            if !errs.is_empty() {
+                tags.remove(tag);
+            }
+        }
+        tags
+    }
+}
+
 /// Render a single FIX message into a human-friendly string using the provided dictionary.
 /// When a validation report is supplied, tag-level errors are annotated inline and missing
-/// required fields are surfaced in the output.
+/// required fields are surfaced in the output.  Fields whose tags are in `hidden` are
+/// left out, see [`HiddenBlocks::tags`].  Values line up in one column, wrapped to the
+/// output width, see [`align_values`], and are shown as `display` asks.
+/// For callers holding the raw text; a message already split goes to [`prettify_parsed`].
 pub fn prettify_with_report(
     msg: &str,
     dict: &FixTagLookup,
     report: Option<&validator::ValidationReport>,
+    hidden: &HashSet<u32>,
+    display: &DisplayOptions,
+) -> String {
+    prettify_parsed(&ParsedMessage::parse(msg), dict, report, hidden, display)
+}
+
+/// [`prettify_with_report`] for a message that has already been split into fields.
+pub fn prettify_parsed(
+    msg: &ParsedMessage<'_>,
+    dict: &FixTagLookup,
+    report: Option<&validator::ValidationReport>,
+    hidden: &HashSet<u32>,
+    display: &DisplayOptions,
 ) -> String {
     let colours = palette();
     let mut output = String::new();
-    let fields = parse_fix(msg);
-    let annotations = report.map(|r| &r.tag_errors);
+    let fields = &msg.fields;
+    let located = report
+        .filter(|_| display.error_locations)
+        .map(|r| r.located_tag_errors());
+    let annotations = located.as_ref().or(report.map(|r| &r.tag_errors));
 
     let mut seen_tags = HashSet::new();
-    let msg_def = fields
-        .iter()
-        .find(|f| f.tag == 35)
-        .and_then(|f| dict.message_def(&f.value));
+    let msg_def = msg.first(35).and_then(|mt| dict.message_def(mt));
+    let encoding = msg.first(MESSAGE_ENCODING_TAG);
+    let ladder = (display.md_ladder && msg.first(35).is_some_and(md_ladder::is_market_data))
+        .then(|| RefCell::new(Vec::new()));
     let renderer = msg_def.map(|def| GroupRenderer {
         dict,
         annotations,
         colours: &colours,
+        display,
         msg_def: def,
-        fields: &fields,
+        fields,
+        encoding,
+        ladder: ladder.as_ref(),
     });
 
     let mut idx = 0;
@@ -89,23 +432,102 @@ pub fn prettify_with_report(
         if let Some(render) = renderer.as_ref()
             && let Some(spec) = render.msg_def.groups.get(&field.tag)
         {
-            let consumed = render.render_group(&mut output, idx, spec, BASE_INDENT);
-            idx += consumed.max(1);
+            // A hidden group, such as the header's NoHops(627), goes as a whole unless
+            // one of its fields is being shown.
+            let mut group = String::new();
+            let consumed = render
+                .render_group(&mut group, idx, spec, BASE_INDENT)
+                .max(1);
+            if fields[idx..idx + consumed]
+                .iter()
+                .any(|f| !hidden.contains(&f.tag))
+            {
+                output.push_str(&group);
+            }
+            idx += consumed;
         } else {
-            write_field_line(&mut output, dict, field, annotations, &colours, BASE_INDENT);
+            if !hidden.contains(&field.tag) {
+                write_field_line(
+                    &mut output,
+                    dict,
+                    field,
+                    annotations,
+                    &colours,
+                    display,
+                    BASE_INDENT,
+                    encoding,
+                );
+            }
             idx += 1;
         }
     }
 
     if let Some(ann) = annotations {
-        for (tag, errs) in ann {
-            if seen_tags.contains(tag) || errs.is_empty() {
-                continue;
-            }
+        // Sorted so repeated runs over the same log produce identical output.
+        let mut missing: Vec<_> = ann
+            .iter()
+            .filter(|(tag, errs)| !seen_tags.contains(*tag) && !errs.is_empty())
+            .collect();
+        missing.sort_by_key(|(tag, _)| **tag);
+        for (tag, errs) in missing {
             write_missing_line(&mut output, dict, *tag, errs, &colours);
         }
     }
 
+    let mut output = align_values(&output, display.wrap_width(), colours.reset);
+    if let Some(entries) = ladder {
+        let incremental = msg.first(35) == Some("X");
+        output.push_str(&md_ladder::render(
+            &entries.into_inner(),
+            incremental,
+            dict,
+            &colours,
+            display,
+        ));
+    }
+    output
+}
+
+/// Lay out the field lines of a rendered message, whose values are bracketed by
+/// [`VALUE_START`] and [`VALUE_END`].  Values start in one column, just past the widest
+/// `tag (Name): ` however deep its group, when that leaves [`MIN_VALUE_WIDTH`] columns
+/// of `width`; otherwise each line keeps its own.  A value too long for what is left
+/// is wrapped at spaces into continuation lines indented to its column, with anything
+/// after it, such as the enum description, after the last piece, each piece in the
+/// value's colour.  Without a `width` (piped output) values are aligned but not wrapped.
+fn align_values(text: &str, width: Option<usize>, reset: &str) -> String {
+    let column = text
+        .lines()
+        .filter_map(|line| line.find(VALUE_START).map(|at| visible_width(&line[..at])))
+        .max()
+        .unwrap_or_default();
+    let aligned = width.is_none_or(|width| column + MIN_VALUE_WIDTH <= width);
+    let mut output = String::with_capacity(text.len());
+    for line in text.split_inclusive('\n') {
+        let Some((prefix, rest)) = line.split_once(VALUE_START) else {
+            output.push_str(line);
+            continue;
+        };
+        let (value, suffix) = rest.split_once(VALUE_END).unwrap_or((rest, ""));
+        let start = if aligned {
+            column
+        } else {
+            visible_width(prefix)
+        };
+        output.push_str(&pad_ansi(prefix, start));
+        let colour = leading_escapes(value);
+        let pieces = match width {
+            Some(width) => wrap_visible(value, width.saturating_sub(start).max(MIN_VALUE_WIDTH)),
+            None => vec![value],
+        };
+        for (idx, piece) in pieces.iter().enumerate() {
+            if idx > 0 {
+                output.push_str(&format!("{reset}\n{}{colour}", indent(start)));
+            }
+            output.push_str(piece);
+        }
+        output.push_str(suffix);
+    }
     output
 }
 
@@ -113,8 +535,13 @@ struct GroupRenderer<'a> {
     dict: &'a FixTagLookup,
     annotations: Option<&'a std::collections::HashMap<u32, Vec<String>>>,
     colours: &'a crate::decoder::colours::ColourPalette,
+    display: &'a DisplayOptions,
     msg_def: &'a LookupMessageDef,
     fields: &'a [FieldValue],
+    /// MessageEncoding(347) of the message, for its Encoded* fields.
+    encoding: Option<&'a str>,
+    /// `--md-ladder`: the NoMDEntries(268) entries seen, collected as they are rendered.
+    ladder: Option<&'a RefCell<Vec<LadderEntry>>>,
 }
 
 impl<'a> GroupRenderer<'a> {
@@ -125,7 +552,9 @@ impl<'a> GroupRenderer<'a> {
             field,
             self.annotations,
             self.colours,
+            self.display,
             indent_spaces,
+            self.encoding,
         );
     }
 
@@ -136,13 +565,33 @@ impl<'a> GroupRenderer<'a> {
         spec: &MessageDefGroupSpec,
         indent_spaces: usize,
     ) -> usize {
+        let count = &self.fields[start_idx];
+        self.write_field(output, count, indent_spaces);
+        let expected = match count.value.parse::<usize>() {
+            // An empty group is just its count line.
+            Ok(0) => return 1,
+            Ok(expected) => expected,
+            Err(_) => {
+                // Under --validate the count line already carries the error.
+                if !self
+                    .annotations
+                    .is_some_and(|ann| ann.contains_key(&spec.count_tag))
+                {
+                    output.push_str(&format!(
+                        "{}{}Warning:{} NumInGroup {} ({}) is not a number: '{}'\n",
+                        indent(indent_spaces + 2),
+                        self.colours.error,
+                        self.colours.reset,
+                        spec.count_tag,
+                        spec.name,
+                        count.value
+                    ));
+                }
+                return 1;
+            }
+        };
         let mut consumed = 0usize;
         let mut entries = 0usize;
-        let expected = self.fields[start_idx]
-            .value
-            .parse::<usize>()
-            .unwrap_or_default();
-        self.write_field(output, &self.fields[start_idx], indent_spaces);
         let mut idx = start_idx + 1;
         while idx < self.fields.len() && entries < expected {
             if self.fields[idx].tag != spec.delim {
@@ -251,6 +700,13 @@ impl<'a> GroupRenderer<'a> {
                 break;
             }
         }
+        if spec.count_tag == md_ladder::NO_MD_ENTRIES
+            && let Some(ladder) = self.ladder
+        {
+            ladder
+                .borrow_mut()
+                .push(LadderEntry::from_fields(&self.fields[start_idx..idx]));
+        }
         idx - start_idx
     }
 }
@@ -338,8 +794,8 @@ fn message_field_order(fields: &[FieldValue], dict: &FixTagLookup) -> Option<Vec
     let msg_type = fields.iter().find(|f| f.tag == 35).map(|f| f.value.clone());
     msg_type
         .as_deref()
-        .and_then(|mt| dict.message_def(mt).cloned())
-        .map(|def| def.field_order)
+        .and_then(|mt| dict.message_def(mt))
+        .map(|def| def.field_order())
 }
 
 #[allow(dead_code)]
@@ -433,7 +889,7 @@ fn append_trailer_tags(
     }
 }
 
-pub fn prettify_files(paths: &[String], ctx: &mut PrettifyContext) -> i32 {
+pub fn prettify_files(paths: &[String], ctx: &mut PrettifyContext) -> RunOutcome {
     let mut had_error = false;
     let sources = if paths.is_empty() {
         vec!["-".to_string()]
@@ -441,31 +897,235 @@ pub fn prettify_files(paths: &[String], ctx: &mut PrettifyContext) -> i32 {
         paths.to_vec()
     };
 
+    let tag_width = file_prefix::tag_width(&sources);
+    let mut files = Vec::with_capacity(sources.len());
+
     for path in sources {
+        if ctx.input_range.limit_reached() {
+            break;
+        }
+        if let Some(prefix) = ctx.file_prefix.as_ref() {
+            ctx.display.line_prefix_width = prefix.start_file(&path, tag_width);
+        }
+        let messages_before = counted_messages(ctx);
+        let invalid_before = ctx.validation_stats.invalid;
         let res = if path == "-" {
             handle_stdin(ctx)
+        } else if remote::is_remote(&path) {
+            handle_remote(&path, ctx).map(|_| 0).unwrap_or(1)
         } else {
             handle_file(&path, ctx).map(|_| 0).unwrap_or(1)
         };
         if res != 0 {
             had_error = true;
         }
+        files.push(FileStats {
+            source: if path == "-" {
+                "(stdin)".to_string()
+            } else {
+                path
+            },
+            messages: counted_messages(ctx) - messages_before,
+            invalid: ctx.validation_stats.invalid - invalid_before,
+        });
+    }
+
+    if let Some(prefix) = ctx.file_prefix.as_ref() {
+        prefix.clear();
+        ctx.display.line_prefix_width = 0;
+    }
+    RunOutcome {
+        files,
+        ..finish_run(ctx, had_error)
     }
+}
+
+/// Messages in the MsgType totals so far.
+fn counted_messages(ctx: &PrettifyContext) -> usize {
+    ctx.message_counts.values().map(|count| count.count).sum()
+}
 
+/// Close any `--split-output` files, then print the end-of-run output (order summary,
+/// MsgType counts, `--tag-usage` table, `--fix-map` sessions, the `--check-clock-skew`
+/// report, `--validate` totals and the `--validate-summary` table) once all input has
+/// been fed through the context.
+pub fn finish_run(ctx: &mut PrettifyContext, mut io_error: bool) -> RunOutcome {
+    if let Some(split) = ctx.split.as_mut() {
+        let summary = ctx.summary.as_ref();
+        let finished = split.finish(|key, out| match summary {
+            Some(tracker) => tracker.render_order(key, out),
+            None => Ok(()),
+        });
+        let _ = match finished {
+            Ok(files) => writeln!(
+                ctx.err_out,
+                "Wrote {files} file(s) to {}",
+                split.dir().display()
+            ),
+            Err(err) => {
+                io_error = true;
+                writeln!(
+                    ctx.err_out,
+                    "error: failed to write --split-output files to {}: {err}",
+                    split.dir().display()
+                )
+            }
+        };
+    }
+    if let Some(dedupe) = ctx.dedupe.as_mut() {
+        let colours = palette();
+        for ((sender, target), repeats) in dedupe.finish() {
+            let _ = writeln!(
+                ctx.out,
+                "{}{}{}",
+                colours.line,
+                repeat_note((&sender, &target), repeats),
+                colours.reset
+            );
+        }
+    }
     if let Some(ref mut tracker) = ctx.summary.as_mut() {
         tracker.render(ctx.out).ok();
     }
+    if let Some(sessions) = ctx.session_summary.as_ref() {
+        sessions.render(ctx.out).ok();
+    }
+    if let Some(timing) = ctx.line_timing.as_ref() {
+        timing.render(ctx.out).ok();
+    }
     let _ = print_message_counts(ctx);
+    if let Some(usage) = ctx.tag_usage.as_ref() {
+        let _ = usage.render(ctx.out);
+    }
+    if let Some(histogram) = ctx.tag_histogram.as_ref() {
+        let _ = histogram.render(ctx.out, &ctx.display);
+    }
+    let _ = print_fix_map_usage(ctx);
+    if ctx.validation_enabled {
+        if let Some(skews) = ctx.clock_skew.as_ref() {
+            let _ = skews.render(ctx.out);
+        }
+        if let Some(chains) = ctx.order_chains.as_ref() {
+            let _ = chains.render(ctx.out);
+        }
+        if let Some(tally) = ctx.validation_summary.as_ref() {
+            let _ = tally.render(ctx.out);
+        }
+        let _ = print_validation_totals(ctx);
+        let _ = print_declared_versions(ctx);
+    }
+
+    RunOutcome {
+        io_error,
+        validation: ctx.validation_stats,
+        files: Vec::new(),
+    }
+}
 
-    if had_error { 1 } else { 0 }
+/// Decode one line of input that did not come from a file or stdin, such as a
+/// message reassembled from a packet capture.  `line_number` is reported by
+/// `--validate` and should count lines within the current source.
+pub fn prettify_line(line: &str, line_number: usize, ctx: &mut PrettifyContext) -> io::Result<()> {
+    let line = LogicalLine::new(line.to_string(), line_number);
+    process_logical_line(&line, &message_separator(&ctx.display), ctx)
+}
+
+/// Print the end-of-run `--validate` totals, even when every message was clean.
+fn print_validation_totals(ctx: &mut PrettifyContext) -> io::Result<()> {
+    let colours = palette();
+    let stats = ctx.validation_stats;
+    let invalid_colour = if stats.invalid > 0 {
+        colours.error
+    } else {
+        colours.value
+    };
+    write!(
+        ctx.out,
+        "Validated {}{}{} message(s): {}{}{} invalid",
+        colours.value, stats.messages, colours.reset, invalid_colour, stats.invalid, colours.reset
+    )?;
+    if validator::warn_deprecated_enabled() {
+        let warned_colour = if stats.warned > 0 {
+            colours.warning
+        } else {
+            colours.value
+        };
+        write!(
+            ctx.out,
+            ", {}{}{} with warnings",
+            warned_colour, stats.warned, colours.reset
+        )?;
+    }
+    writeln!(ctx.out)
+}
+
+/// List the versions messages declared when any was validated against a different
+/// dictionary; silent when every message matched.
+fn print_declared_versions(ctx: &mut PrettifyContext) -> io::Result<()> {
+    if !ctx.declared_versions.has_mismatch() {
+        return Ok(());
+    }
+    let colours = palette();
+    writeln!(ctx.out, "Declared versions:")?;
+    for ((label, used_key), (count, mismatch)) in &ctx.declared_versions.counts {
+        write!(
+            ctx.out,
+            "  {label:<20} {}{count:>8}{} message(s)",
+            colours.value, colours.reset
+        )?;
+        if *mismatch {
+            write!(
+                ctx.out,
+                ", {}validated against {used_key}{}",
+                colours.error, colours.reset
+            )?;
+        }
+        writeln!(ctx.out)?;
+    }
+    Ok(())
+}
+
+/// List the dictionary each session was decoded with under `--fix-map`.
+fn print_fix_map_usage(ctx: &mut PrettifyContext) -> io::Result<()> {
+    let usage = fix_map().map(FixMap::usage).unwrap_or_default();
+    if usage.is_empty() {
+        return Ok(());
+    }
+    let colours = palette();
+    writeln!(ctx.out, "Dictionaries by session:")?;
+    for (sender, target, key) in usage {
+        let session = format!("{sender} -> {target}");
+        writeln!(
+            ctx.out,
+            "  {session:<30} {}{key}{}",
+            colours.value, colours.reset
+        )?;
+    }
+    Ok(())
 }
 
 pub fn print_message_counts(ctx: &mut PrettifyContext) -> io::Result<()> {
     if ctx.message_counts.is_empty() || !ctx.counts_dirty {
         return Ok(());
     }
-    let mut entries: Vec<(&String, &MsgTypeCount)> = ctx.message_counts.iter().collect();
-    entries.sort_by(|a, b| a.0.cmp(b.0));
+    render_message_counts(ctx.out, &ctx.message_counts, ctx.counts_layout)?;
+    ctx.counts_dirty = false;
+    Ok(())
+}
+
+/// Write the MsgType counts table: code, label and count, plus the share of all
+/// messages, bytes and average size under `--counts-wide`.
+fn render_message_counts(
+    out: &mut dyn Write,
+    counts: &HashMap<String, MsgTypeCount>,
+    layout: CountsLayout,
+) -> io::Result<()> {
+    let mut entries: Vec<(&String, &MsgTypeCount)> = counts.iter().collect();
+    match layout.sort {
+        CountsSort::Code => entries.sort_by(|a, b| a.0.cmp(b.0)),
+        CountsSort::Count => entries.sort_by(|a, b| b.1.count.cmp(&a.1.count).then(a.0.cmp(b.0))),
+        CountsSort::Bytes => entries.sort_by(|a, b| b.1.bytes.cmp(&a.1.bytes).then(a.0.cmp(b.0))),
+    }
 
     let colours = palette();
     let mut prepared = Vec::new();
@@ -478,33 +1138,80 @@ pub fn print_message_counts(ctx: &mut PrettifyContext) -> io::Result<()> {
         );
         let width = visible_width(&label_display);
         max_label_width = max_label_width.max(width);
-        prepared.push((mt, info.count, label_display));
+        prepared.push((mt, *info, label_display));
     }
 
     let count_col_start = 2 + 3 + 3 + max_label_width + 3;
     let header_pad = count_col_start.saturating_sub("Message Type".len());
-    writeln!(ctx.out, "Message Type{:<pad$}Count:", "", pad = header_pad)?;
+    write!(out, "Message Type{:<pad$}Count:", "", pad = header_pad)?;
+    if layout.wide {
+        write!(out, "  {:>7}  {:>10}  {:>8}", "Share:", "Bytes:", "Avg:")?;
+    }
+    writeln!(out)?;
 
-    for (mt, count, label_display) in prepared {
+    let total_count: usize = entries.iter().map(|(_, info)| info.count).sum();
+    let total_bytes: usize = entries.iter().map(|(_, info)| info.bytes).sum();
+    let wide_columns = |count: usize, bytes: usize| {
+        let share = if total_count > 0 {
+            100.0 * count as f64 / total_count as f64
+        } else {
+            0.0
+        };
+        let average = if count > 0 {
+            bytes as f64 / count as f64
+        } else {
+            0.0
+        };
+        format!(
+            "  {}{share:>6.1}%  {bytes:>10}  {average:>8.1}{}",
+            colours.value, colours.reset
+        )
+    };
+    for (mt, info, label_display) in prepared {
         let padded_label = pad_ansi(&label_display, max_label_width);
-        writeln!(
-            ctx.out,
+        write!(
+            out,
             "  {}{:<3}{}   {}   {}{:>6}{}",
-            colours.value, mt, colours.reset, padded_label, colours.value, count, colours.reset
+            colours.value,
+            mt,
+            colours.reset,
+            padded_label,
+            colours.value,
+            info.count,
+            colours.reset
+        )?;
+        if layout.wide {
+            write!(out, "{}", wide_columns(info.count, info.bytes))?;
+        }
+        writeln!(out)?;
+    }
+    if layout.wide {
+        writeln!(
+            out,
+            "  {:<width$}{}{total_count:>6}{}{}",
+            "Total",
+            colours.value,
+            colours.reset,
+            wide_columns(total_count, total_bytes),
+            width = count_col_start - 2
         )?;
     }
-    ctx.counts_dirty = false;
     Ok(())
 }
 
 /// Write a single field line, including optional enum descriptions and validation errors.
+/// `encoding` is the message's MessageEncoding(347), which Encoded* fields are decoded
+/// from.
+#[allow(clippy::too_many_arguments)]
 fn write_field_line(
     output: &mut String,
     dict: &FixTagLookup,
     field: &crate::decoder::fixparser::FieldValue,
     annotations: Option<&std::collections::HashMap<u32, Vec<String>>>,
     colours: &crate::decoder::colours::ColourPalette,
+    display: &DisplayOptions,
     indent_spaces: usize,
+    encoding: Option<&str>,
 ) {
     let tag_errors: Option<&Vec<String>> = annotations.and_then(|ann| ann.get(&field.tag));
     let tag_colour = if tag_errors.is_some() {
@@ -520,16 +1227,53 @@ fn write_field_line(
         format!("{}{}{}", colours.name, name, colours.reset)
     };
     let name_section = format!("{}({}){}", colours.name, name_coloured, colours.reset);
-    let desc = dict.enum_description(field.tag, &field.value);
+    let desc = dict.describe_value(field.tag, &field.value);
+    let xml = display
+        .decode_xml
+        .then(|| dict.field_type(field.tag))
+        .flatten()
+        .filter(|kind| matches!(*kind, "DATA" | "XMLDATA"))
+        .and_then(|_| {
+            render_xml_value(
+                &field.value,
+                indent_spaces + NAME_TEXT_OFFSET + 2,
+                colours.enumeration,
+                colours.reset,
+            )
+        });
+    // DATA fields such as RawData(96) may carry binary; text payloads like XmlData(213)
+    // and EncodedText(355) share the type, so only values that are not text are dumped.
+    let is_binary_data = dict.field_type(field.tag) == Some("DATA")
+        && field.value.chars().any(|c| {
+            c.is_control() || c == char::REPLACEMENT_CHARACTER || raw_bytes::byte_of(c).is_some()
+        });
+    // Encoded* fields are text in MessageEncoding(347); one that will not decode in it,
+    // or names an encoding FIX does not define, is dumped like any other binary.
+    let encoded = encoding.filter(|_| message_encoding::is_encoded_field(dict, field.tag));
+    let decoded = encoded.and_then(|name| {
+        let encoding = MessageEncoding::from_value(name)?;
+        Some((encoding.decode(&field.value)?, encoding.name()))
+    });
+    let value = match (&xml, &decoded) {
+        (Some(_), _) => Cow::Owned(format!(
+            "(XML, {} bytes)",
+            raw_bytes::byte_len(&field.value)
+        )),
+        (None, Some((text, _))) => Cow::Owned(display.display_value(text).into_owned()),
+        (None, None) if is_binary_data || encoded.is_some() => {
+            Cow::Owned(data_preview(&field.value))
+        }
+        (None, None) => display.display_value(&field.value),
+    };
     output.push_str(&format!(
-        "{}{}{:4}{} {}: {}{}{}",
+        "{}{}{:4}{} {}: {VALUE_START}{}{}{}{VALUE_END}",
         indent(indent_spaces),
         tag_colour,
         field.tag,
         colours.reset,
         name_section,
         colours.value,
-        field.value,
+        value,
         colours.reset
     ));
 
@@ -540,12 +1284,31 @@ fn write_field_line(
         ));
     }
 
+    if let Some((_, encoding)) = decoded {
+        output.push_str(&format!(
+            " ({}{}{})",
+            colours.enumeration, encoding, colours.reset
+        ));
+    }
+
+    if let Some(dep) = deprecations::lookup(dict.schema_key(), field.tag, &field.value) {
+        output.push_str(&format!(
+            " ({}{}{})",
+            colours.warning,
+            dep.hint(),
+            colours.reset
+        ));
+    }
+
     if let Some(errs) = tag_errors {
         let msg = errs.join(", ");
         output.push_str(&format!("  {}{}{}", colours.error, msg, colours.reset));
     }
 
     output.push('\n');
+    if let Some(xml) = xml {
+        output.push_str(&xml);
+    }
 }
 
 /// Write a placeholder line for a missing field, showing validation errors when present.
@@ -563,7 +1326,7 @@ fn write_missing_line(
         errors.join(", ")
     };
     output.push_str(&format!(
-        "{}{}{:4}{} ({}{}{}): {}{}{}\n",
+        "{}{}{:4}{} ({}{}{}): {VALUE_START}{VALUE_END}{}{}{}\n",
         indent(BASE_INDENT),
         colours.error,
         tag,
@@ -579,10 +1342,11 @@ fn write_missing_line(
 
 /// Handle decoding from stdin (used when no file paths are provided).
 fn handle_stdin(ctx: &mut PrettifyContext) -> i32 {
-    ctx.obfuscator.reset();
+    ctx.obfuscator.reset("stdin");
     announce_source("(stdin)", ctx);
     let mut reader = BufReader::new(io::stdin().lock());
-    match stream_until_complete(&mut reader, ctx) {
+    let format = ctx.stdin_format;
+    match stream_until_complete(&mut reader, 0, format, ctx) {
         Ok(_) => 0,
         Err(_) => {
             let colours = palette();
@@ -598,8 +1362,9 @@ fn handle_stdin(ctx: &mut PrettifyContext) -> i32 {
 
 /// Handle decoding from a single file path, printing progress when validation is disabled.
 fn handle_file(path: &str, ctx: &mut PrettifyContext) -> io::Result<()> {
-    ctx.obfuscator.reset();
+    ctx.obfuscator.reset(path);
     announce_source(path, ctx);
+    ctx.source_is_file = true;
 
     let file = File::open(path).map_err(|err| {
         let colours = palette();
@@ -610,57 +1375,163 @@ fn handle_file(path: &str, ctx: &mut PrettifyContext) -> io::Result<()> {
         );
         err
     })?;
+    let len = file.metadata()?.len();
     let mut reader = BufReader::new(file);
-    stream_until_complete(&mut reader, ctx)
+    let skipped = ctx.input_range.start_file(&mut reader, path, len)?;
+    let result = stream_until_complete(&mut reader, skipped, RecordFormat::Lines, ctx);
+    ctx.input_range.finish_file(ctx.err_out);
+    result
+}
+
+/// Handle decoding from an `http(s)://` or `s3://` location, streaming the body as it
+/// arrives and, under `--follow`, polling for whatever is appended to it.
+fn handle_remote(url: &str, ctx: &mut PrettifyContext) -> io::Result<()> {
+    ctx.obfuscator.reset(url);
+    announce_source(url, ctx);
+    ctx.source_is_file = true;
+
+    let log = RemoteLog::open(url, ctx.follow).map_err(|err| {
+        let colours = palette();
+        let _ = writeln!(
+            ctx.err_out,
+            "{}Cannot open URL: {}{}",
+            colours.error, err, colours.reset
+        );
+        err
+    })?;
+    let mut reader = BufReader::new(log);
+    stream_until_complete(&mut reader, 0, RecordFormat::Lines, ctx)
 }
 
-/// Stream lines from a reader, emitting formatted FIX messages (and optionally validation output).
-fn stream_reader<R: BufRead>(reader: &mut R, ctx: &mut PrettifyContext) -> io::Result<bool> {
-    let mut line = String::new();
+/// The rule printed after each decoded message, as wide as the terminal.
+fn message_separator(display: &DisplayOptions) -> String {
     let colours = palette();
-    let separator = format!(
+    format!(
         "{}{}{}\n",
         colours.title,
-        "=".repeat(terminal_width()),
+        "=".repeat(display.terminal_width()),
         colours.reset
-    );
+    )
+}
 
-    let mut line_number = 0usize;
+/// Stream records (lines, or as `format` frames them) from a reader, emitting formatted
+/// FIX messages (and optionally validation output).  Line numbers count on from
+/// `skipped_lines`, the lines a `--seek-line` passed.
+fn stream_reader<R: BufRead>(
+    reader: &mut R,
+    skipped_lines: usize,
+    format: RecordFormat,
+    ctx: &mut PrettifyContext,
+) -> io::Result<bool> {
+    let mut raw = Vec::new();
+    let separator = message_separator(&ctx.display);
+
+    let mut line_number = skipped_lines;
     let mut read_any = false;
-    while !ctx.interrupted.load(Ordering::Relaxed) {
-        line.clear();
-        let bytes = read_line_with_follow(reader, &mut line, ctx.follow, ctx.interrupted)?;
+    while !ctx.interrupted.load(Ordering::Relaxed) && !ctx.input_range.limit_reached() {
+        raw.clear();
+        let bytes = read_record_with_follow(reader, &mut raw, format, ctx.follow, ctx.interrupted)?;
         if bytes == 0 {
             break;
         }
+        ctx.input_range.advance(bytes, ctx.err_out);
+        if let Some(bench) = ctx.benchmark.as_mut() {
+            bench.add_input(bytes);
+        }
         read_any = true;
         line_number += 1;
+        if ctx.passthrough && ctx.summary.is_none() {
+            ctx.out.write_all(&raw)?;
+            if !raw.ends_with(b"\n") {
+                ctx.out.write_all(b"\n")?;
+            }
+        }
 
-        trim_line_endings(&mut line);
+        // The raw line is kept for echoing; decoding works on the logical line.
+        let logical = ctx.lines.push(&raw, line_number);
+        poll_dictionary_watcher(ctx);
+        for line in logical {
+            process_logical_line(&line, &separator, ctx)?;
+        }
+    }
 
-        let processed = ctx.obfuscator.enabled_line(&line);
-        handle_log_line(&processed, line_number, &separator, ctx)?;
+    if !ctx.follow
+        && !ctx.input_range.limit_reached()
+        && let Some(line) = ctx.lines.finish()
+    {
+        process_logical_line(&line, &separator, ctx)?;
     }
 
     Ok(read_any)
 }
 
-fn stream_until_complete<R: BufRead>(reader: &mut R, ctx: &mut PrettifyContext) -> io::Result<()> {
+fn process_logical_line(
+    line: &LogicalLine,
+    separator: &str,
+    ctx: &mut PrettifyContext,
+) -> io::Result<()> {
+    let Some(delim) = line.delimiter else {
+        let processed = ctx.obfuscator.enabled_line(&line.text);
+        return handle_log_line(&processed, line.line_number, separator, ctx);
+    };
+    if ctx.detected_delimiter.is_none() {
+        warnings::warn(
+            "delimiter",
+            &delim.to_string(),
+            &format!("Detected {delim:?} as the FIX field delimiter; decoding it as SOH"),
+        );
+    }
+    ctx.detected_delimiter = Some(delim);
+
+    // Show the line with the delimiter it arrived with unless --delimiter chose one.
+    let display_delimiter = ctx.display_delimiter;
+    if display_delimiter == SOH {
+        ctx.display_delimiter = delim;
+    }
+    let processed = ctx.obfuscator.enabled_line(&line.text);
+    let result = handle_log_line(&processed, line.line_number, separator, ctx);
+    ctx.display_delimiter = display_delimiter;
+    result
+}
+
+fn stream_until_complete<R: BufRead>(
+    reader: &mut R,
+    skipped_lines: usize,
+    format: RecordFormat,
+    ctx: &mut PrettifyContext,
+) -> io::Result<()> {
     loop {
-        let read_any = stream_reader(reader, ctx)?;
-        if ctx.interrupted.load(Ordering::Relaxed) || !ctx.follow {
+        let read_any = stream_reader(reader, skipped_lines, format, ctx)?;
+        if ctx.interrupted.load(Ordering::Relaxed) || !ctx.follow || ctx.input_range.limit_reached()
+        {
             return Ok(());
         }
         if !read_any {
             std::thread::sleep(FOLLOW_SLEEP);
         }
+        poll_dictionary_watcher(ctx);
         if ctx.counts_dirty && ctx.live_status_enabled {
             let _ = print_message_counts(ctx);
         }
     }
 }
 
-fn announce_source(label: &str, ctx: &mut PrettifyContext) {
+/// Give the `--watch-xml` watcher a chance to re-register changed dictionaries so
+/// subsequent messages decode with the new tag names and enums.  A reload drops the
+/// dictionary remembered for the previous message.
+fn poll_dictionary_watcher(ctx: &mut PrettifyContext) {
+    if let Some(watcher) = ctx.dictionary_watcher.as_mut()
+        && watcher.poll(ctx.err_out) > 0
+    {
+        ctx.dict_cache = DictionaryCache::default();
+    }
+}
+
+/// Print the `Processing: <label>` heading shown before each input source on a
+/// terminal (skipped under `--validate`).
+pub fn announce_source(label: &str, ctx: &mut PrettifyContext) {
+    ctx.source = label.to_string();
+    ctx.source_is_file = false;
     if !ctx.validation_enabled && ctx.live_status_enabled {
         let colours = palette();
         let _ = writeln!(
@@ -671,23 +1542,15 @@ fn announce_source(label: &str, ctx: &mut PrettifyContext) {
     }
 }
 
-fn trim_line_endings(line: &mut String) {
-    if line.ends_with('\n') {
-        line.pop();
-        if line.ends_with('\r') {
-            line.pop();
-        }
-    }
-}
-
-fn read_line_with_follow<R: BufRead>(
+fn read_record_with_follow<R: BufRead>(
     reader: &mut R,
-    buf: &mut String,
+    buf: &mut Vec<u8>,
+    format: RecordFormat,
     follow: bool,
     interrupted: &AtomicBool,
 ) -> io::Result<usize> {
     loop {
-        match reader.read_line(buf) {
+        match read_record(reader, buf, format) {
             Ok(n) => return Ok(n),
             Err(e) if !follow => return Err(e),
             Err(_) => {
@@ -707,125 +1570,621 @@ fn handle_log_line(
     separator: &str,
     ctx: &mut PrettifyContext,
 ) -> io::Result<()> {
+    let line = apply_limit(line, ctx);
+    let stamp = stamp_line(line, line_number, ctx);
+    if ctx.replay.is_some() {
+        return replay_line(line, stamp, ctx);
+    }
+    let logged = stamp.map(format_line_time);
+    if ctx.count_only {
+        return process_count_only(line, ctx);
+    }
+    if ctx.split.is_some() {
+        return process_split(line, separator, logged.as_deref(), ctx);
+    }
     if !ctx.validation_enabled {
-        return process_without_validation(line, separator, ctx);
+        return process_without_validation(line, separator, logged.as_deref(), ctx);
     }
 
-    process_with_validation(line, line_number, ctx)
+    process_with_validation(line, line_number, logged.as_deref(), ctx)
 }
 
-fn process_without_validation(
+/// `--replay`: send each message on the line, raw, once the gap since the previous
+/// message has passed.  Messages are timed by SendingTime(52), or by the line's
+/// `--line-timestamp` time when that is set.
+fn replay_line(
     line: &str,
-    separator: &str,
+    stamp: Option<chrono::NaiveDateTime>,
     ctx: &mut PrettifyContext,
 ) -> io::Result<()> {
-    let matches = find_fix_message_indices(line);
-    let colours = palette();
-
-    if matches.is_empty() {
-        if ctx.summary.is_none() {
-            writeln!(ctx.out, "{}{}{}", colours.line, line, colours.reset)?;
+    for (start, end) in filter_msg_types(line, find_fix_message_indices(line), ctx) {
+        let msg = &line[start..end];
+        write_extracted(&mut ctx.extract.all, msg)?;
+        let time = match ctx.line_timing {
+            Some(_) => stamp,
+            None => replay::sending_time(msg),
+        };
+        if let Some(replayer) = ctx.replay.as_mut() {
+            replayer.send(msg, time, ctx.interrupted)?;
         }
-        return Ok(());
     }
+    Ok(())
+}
 
-    let (messages, coloured_line) =
-        extract_messages_and_format(line, &matches, ctx.display_delimiter);
-
-    if ctx.summary.is_none() {
-        write!(ctx.out, "{coloured_line}")?;
-        write!(ctx.out, "{separator}")?;
+/// `--line-timestamp`: read the time from the prefix of a line holding FIX messages
+/// and measure each message's gap within its session.  Other lines are not parsed.
+fn stamp_line(
+    line: &str,
+    line_number: usize,
+    ctx: &mut PrettifyContext,
+) -> Option<chrono::NaiveDateTime> {
+    let timing = ctx.line_timing.as_mut()?;
+    if !line.contains("8=FIX") {
+        return None;
     }
+    let spans = find_fix_message_indices(line);
+    if spans.is_empty() {
+        return None;
+    }
+    let time = timing.stamp_line(line)?;
+    for (start, end) in spans {
+        timing.record_message(&line[start..end], time, line_number);
+    }
+    Some(time)
+}
+
+fn process_without_validation(
+    line: &str,
+    separator: &str,
+    logged: Option<&str>,
+    ctx: &mut PrettifyContext,
+) -> io::Result<()> {
+    let lap = Lap::start(&ctx.benchmark);
+    let matches = find_fix_message_indices(line);
+    lap.stop(&mut ctx.benchmark, Phase::Extract);
+    let colours = palette();
 
+    if matches.is_empty() {
+        if ctx.summary.is_none() && !ctx.passthrough {
+            let shown = apply_surrounding_text(line, ctx.display_delimiter);
+            writeln!(ctx.out, "{}{}{}", colours.line, shown, colours.reset)?;
+        }
+        return Ok(());
+    }
+    let lap = Lap::start(&ctx.benchmark);
+    let matches = filter_msg_types(line, matches, ctx);
+    let extracted = (!matches.is_empty())
+        .then(|| extract_messages_and_format(line, &matches, ctx.display_delimiter, &ctx.display));
+    lap.stop(&mut ctx.benchmark, Phase::Extract);
+    let Some((messages, coloured_line)) = extracted else {
+        return Ok(());
+    };
+    for msg in &messages {
+        write_extracted(&mut ctx.extract.all, msg)?;
+    }
     record_messages(&messages, ctx);
-    emit_messages(&messages, ctx, separator)?;
+    let messages = drop_repeats(messages, ctx)?;
+
+    if ctx.summary.is_none() && !messages.is_empty() {
+        if !ctx.passthrough {
+            write!(ctx.out, "{coloured_line}")?;
+        }
+        write!(ctx.out, "{separator}")?;
+    }
+
+    emit_messages(&messages, ctx, separator, logged)?;
 
     render_summary_footer(ctx)
 }
 
+/// `--dedupe`: keep the messages that do not repeat their predecessor, noting the
+/// length of each run they end.  A line whose messages are all repeats is not echoed.
+fn drop_repeats(messages: Vec<String>, ctx: &mut PrettifyContext) -> io::Result<Vec<String>> {
+    let Some(dedupe) = ctx.dedupe.as_mut() else {
+        return Ok(messages);
+    };
+    let colours = palette();
+    let mut kept = Vec::with_capacity(messages.len());
+    for msg in messages {
+        match dedupe.observe(&msg) {
+            Seen::Repeat => continue,
+            Seen::New { ended_run, session } if ended_run > 0 => writeln!(
+                ctx.out,
+                "{}{}{}",
+                colours.line,
+                repeat_note(session, ended_run),
+                colours.reset
+            )?,
+            Seen::New { .. } => {}
+        }
+        kept.push(msg);
+    }
+    Ok(kept)
+}
+
+/// The spans of `line` holding messages `--only-msgtype` and `--exclude-msgtype` keep.
+/// Those dropped go no further, other than into the MsgType counts under
+/// `--count-filtered`.
+fn filter_msg_types(
+    line: &str,
+    matches: Vec<(usize, usize)>,
+    ctx: &mut PrettifyContext,
+) -> Vec<(usize, usize)> {
+    if ctx.msg_types.is_empty() {
+        return matches;
+    }
+    let mut kept = Vec::with_capacity(matches.len());
+    for (start, end) in matches {
+        let msg = &line[start..end];
+        let msg_type = extract_msg_type(msg);
+        if ctx.msg_types.keeps(msg_type) {
+            kept.push((start, end));
+        } else if ctx.msg_types.count_filtered
+            && let Some(mt) = msg_type
+        {
+            count_msg_type_with_label(msg, mt, ctx);
+        }
+    }
+    kept
+}
+
+/// Cut `line` after the last message `--limit` still allows, counting those kept.
+fn apply_limit<'a>(line: &'a str, ctx: &mut PrettifyContext) -> &'a str {
+    let Some(remaining) = ctx.input_range.remaining() else {
+        return line;
+    };
+    let mut kept = 0;
+    let mut end = 0;
+    for (_, message_end) in find_fix_message_indices(line) {
+        if kept == remaining {
+            ctx.input_range.record_decoded(kept);
+            return &line[..end];
+        }
+        kept += 1;
+        end = message_end;
+    }
+    ctx.input_range.record_decoded(kept);
+    line
+}
+
+/// `--count-only` fast path: feed messages straight into the MsgType counts and order
+/// summary without prettifying or rebuilding the coloured log line.
+fn process_count_only(line: &str, ctx: &mut PrettifyContext) -> io::Result<()> {
+    let mut found = false;
+    let lap = Lap::start(&ctx.benchmark);
+    let matches = filter_msg_types(line, find_fix_message_indices(line), ctx);
+    lap.stop(&mut ctx.benchmark, Phase::Extract);
+    for (start, end) in matches {
+        found = true;
+        let msg = &line[start..end];
+        write_extracted(&mut ctx.extract.all, msg)?;
+        if let Some(mt) = extract_msg_type(msg) {
+            count_msg_type_with_label(msg, mt, ctx);
+        }
+        let lap = Lap::start(&ctx.benchmark);
+        let dict = ctx.dict_cache.lookup(msg, ctx.fix_override);
+        lap.stop(&mut ctx.benchmark, Phase::Lookup);
+        if let Some(usage) = ctx.tag_usage.as_mut() {
+            usage.record(msg, dict);
+        }
+        if let Some(histogram) = ctx.tag_histogram.as_mut() {
+            histogram.record(msg, dict);
+        }
+        if let Some(tracker) = ctx.summary.as_mut() {
+            tracker.record_message_with_dict(msg, ctx.fix_override, dict);
+        }
+        if let Some(sessions) = ctx.session_summary.as_mut() {
+            sessions.record_message(msg);
+        }
+        if let Some(diagram) = ctx.sequence_diagram.as_mut() {
+            diagram.record(&ParsedMessage::parse(msg), ctx.fix_override);
+        }
+    }
+    if found {
+        render_summary_footer(ctx)?;
+    }
+    Ok(())
+}
+
+/// `--split-output`: decode every message on the line, errors included under
+/// `--validate`, into the file the split mode chooses for it.
+fn process_split(
+    line: &str,
+    separator: &str,
+    logged: Option<&str>,
+    ctx: &mut PrettifyContext,
+) -> io::Result<()> {
+    for (start, end) in filter_msg_types(line, find_fix_message_indices(line), ctx) {
+        let msg = ParsedMessage::parse(&line[start..end]);
+        write_extracted(&mut ctx.extract.all, msg.raw)?;
+        record_parsed_msg_type(&msg, ctx);
+        let order = ctx
+            .summary
+            .as_mut()
+            .and_then(|tracker| tracker.record_parsed(&msg, ctx.fix_override));
+        if let Some(sessions) = ctx.session_summary.as_mut() {
+            sessions.record_parsed(&msg);
+        }
+        if let Some(diagram) = ctx.sequence_diagram.as_mut() {
+            diagram.record(&msg, ctx.fix_override);
+        }
+
+        let layout = MessageLayout {
+            fix_override: ctx.fix_override,
+            validate: ctx.validation_enabled,
+            banner: ctx.banner,
+            hidden: &ctx.hidden,
+            display: &ctx.display,
+        };
+        let mut text = Vec::new();
+        let report = write_fix_message(
+            &mut text,
+            &msg,
+            &layout,
+            separator,
+            logged,
+            &mut ctx.benchmark,
+        )?;
+        if let Some(report) = report {
+            ctx.validation_stats.messages += 1;
+            if !report.warnings.is_empty() {
+                ctx.validation_stats.warned += 1;
+            }
+            if !report.is_clean() {
+                ctx.validation_stats.invalid += 1;
+                write_extracted(&mut ctx.extract.invalid, msg.raw)?;
+            }
+        }
+        if let Some(split) = ctx.split.as_mut() {
+            split.write_message(msg.first(35), order.as_deref(), &text)?;
+        }
+    }
+    Ok(())
+}
+
 fn process_with_validation(
     line: &str,
     line_number: usize,
+    logged: Option<&str>,
     ctx: &mut PrettifyContext,
 ) -> io::Result<()> {
-    let matches = find_fix_message_indices(line);
+    let lap = Lap::start(&ctx.benchmark);
+    let matches = filter_msg_types(line, find_fix_message_indices(line), ctx);
+    lap.stop(&mut ctx.benchmark, Phase::Extract);
     if matches.is_empty() {
         return Ok(());
     }
 
-    for (start, end) in &matches {
-        record_msg_type(&line[*start..*end], ctx);
+    // Split each message once; validation, rendering and the recorders share it.
+    let lap = Lap::start(&ctx.benchmark);
+    let parsed: Vec<ParsedMessage> = matches
+        .iter()
+        .map(|(start, end)| ParsedMessage::parse(&line[*start..*end]))
+        .collect();
+    lap.stop(&mut ctx.benchmark, Phase::Parse);
+    for msg in &parsed {
+        record_parsed_msg_type(msg, ctx);
         if let Some(ref mut tracker) = ctx.summary.as_mut() {
-            tracker.record_message(&line[*start..*end], ctx.fix_override);
+            tracker.record_parsed(msg, ctx.fix_override);
+        }
+        if let Some(sessions) = ctx.session_summary.as_mut() {
+            sessions.record_parsed(msg);
+        }
+        if let Some(diagram) = ctx.sequence_diagram.as_mut() {
+            diagram.record(msg, ctx.fix_override);
         }
     }
     render_summary_footer(ctx)?;
 
     let mut header_emitted = false;
     let colours = palette();
-    let display_line = apply_display_delimiter(line, ctx.display_delimiter);
-
-    for (start, end) in matches {
-        let msg = &line[start..end];
-        let dict = load_dictionary_with_override(msg, ctx.fix_override);
-        let report = validator::validate_fix_message(msg, &dict);
-        if report.is_clean() {
+    let display_line = display_log_line(line, &matches, ctx.display_delimiter, &ctx.display);
+
+    for (msg, (start, _)) in parsed.iter().zip(&matches) {
+        let lap = Lap::start(&ctx.benchmark);
+        let dict = load_dictionary_with_override(msg.raw, ctx.fix_override);
+        lap.stop(&mut ctx.benchmark, Phase::Lookup);
+        warn_on_version_mismatch(ctx, msg.raw, dict.schema_key())?;
+        let lap = Lap::start(&ctx.benchmark);
+        let mut report = validator::validate_parsed_message(msg, &dict);
+        if let Some(chains) = ctx.order_chains.as_mut() {
+            chains.check(msg, line_number, &mut report);
+        }
+        lap.stop(&mut ctx.benchmark, Phase::Validate);
+        if let Some(sink) = ctx.report.as_mut() {
+            sink.record(&ctx.source, line_number, line, *start, msg, &report)?;
+        }
+        ctx.validation_stats.messages += 1;
+        if let Some(tally) = ctx.validation_summary.as_mut() {
+            tally.record(&report, msg.first(35), line_number);
+        }
+        if let Some(skews) = ctx.clock_skew.as_mut() {
+            skews.record(msg);
+        }
+        write_extracted(&mut ctx.extract.all, msg.raw)?;
+        if !report.warnings.is_empty() {
+            ctx.validation_stats.warned += 1;
+        }
+        // Messages with only warnings are shown but do not count as invalid.
+        if report.is_clean() && report.warnings.is_empty() {
             continue;
         }
-        if !header_emitted {
-            writeln!(
-                ctx.out,
-                "Line {}: {}{}{}",
-                line_number, colours.line, display_line, colours.reset
-            )?;
-            header_emitted = true;
+        if !report.is_clean() {
+            ctx.validation_stats.invalid += 1;
+            write_extracted(&mut ctx.extract.invalid, msg.raw)?;
+        }
+        let lap = Lap::start(&ctx.benchmark);
+        let stamp = logged.map(|time| format!(" [{time}]")).unwrap_or_default();
+        if ctx.raw_annotate {
+            writeln!(ctx.out, "{}{stamp}:", line_heading(ctx, line_number))?;
+            let annotated =
+                render_raw_annotation(msg, &dict, &report, ctx.display_delimiter, &ctx.display);
+            writeln!(ctx.out, "{annotated}")?;
+        } else {
+            if !header_emitted {
+                writeln!(
+                    ctx.out,
+                    "{}{}: {}{}{}",
+                    line_heading(ctx, line_number),
+                    stamp,
+                    colours.line,
+                    display_line,
+                    colours.reset
+                )?;
+                header_emitted = true;
+            }
+            stream_invalid_message(ctx, msg, &dict, &report, logged)?;
         }
-        stream_invalid_message(ctx, msg, &dict, &report)?;
+        lap.stop(&mut ctx.benchmark, Phase::Render);
     }
 
     Ok(())
 }
 
-fn stream_invalid_message(
+/// The heading of a `--validate` report: `Line N`, or `<file> line N` for a file or URL.
+fn line_heading(ctx: &PrettifyContext, line_number: usize) -> String {
+    if ctx.source_is_file {
+        format!("{} line {line_number}", ctx.source)
+    } else {
+        format!("Line {line_number}")
+    }
+}
+
+/// Warn, once per pairing, when a message is validated against a dictionary other than
+/// the one its BeginString (or ApplVerID) names, since the errors that follow are then
+/// likely to be artefacts of the wrong dictionary.
+fn warn_on_version_mismatch(
     ctx: &mut PrettifyContext,
     msg: &str,
+    used_key: &str,
+) -> io::Result<()> {
+    let Some((label, declared_key)) = declared_version(msg) else {
+        return Ok(());
+    };
+    if !ctx
+        .declared_versions
+        .record(label.clone(), &declared_key, used_key)
+    {
+        return Ok(());
+    }
+    let reason = if ctx.fix_override.is_some() {
+        "due to --fix override"
+    } else {
+        "as no dictionary matches it"
+    };
+    let colours = palette();
+    writeln!(
+        ctx.err_out,
+        "{}warning:{} message declares {label} but validated against {used_key} {reason}",
+        colours.error, colours.reset
+    )
+}
+
+/// `--raw-annotate` rendering of an invalid message: the message with the display
+/// delimiter applied, a line of `^~~~` carets under the value of each field an error
+/// was found on, and a numbered legend of every error, naming the field for those
+/// that have one, followed by any warnings.  Columns are measured on the
+/// displayed text, so substituted delimiters and multi-byte characters stay aligned.
+fn render_raw_annotation(
+    msg: &ParsedMessage<'_>,
     dict: &FixTagLookup,
     report: &validator::ValidationReport,
+    delimiter: char,
+    display: &DisplayOptions,
+) -> String {
+    let colours = palette();
+    let width_of = |text: &str| visible_width(&apply_display_delimiter(text, delimiter, display));
+    let mut carets: Vec<char> = Vec::new();
+    for span in report.errors.iter().filter_map(|err| err.span.clone()) {
+        let pair = &msg.raw[span.clone()];
+        let value_start = span.start + pair.find('=').map_or(0, |eq| eq + 1);
+        let column = width_of(&msg.raw[..value_start]);
+        let width = width_of(&msg.raw[value_start..span.end]).max(1);
+        if carets.len() < column + width {
+            carets.resize(column + width, ' ');
+        }
+        for (offset, cell) in carets[column..column + width].iter_mut().enumerate() {
+            if offset == 0 {
+                *cell = '^';
+            } else if *cell == ' ' {
+                *cell = '~';
+            }
+        }
+    }
+
+    let mut out = format!(
+        "{}{}{}\n",
+        colours.line,
+        apply_display_delimiter(msg.raw, delimiter, display),
+        colours.reset
+    );
+    if !carets.is_empty() {
+        let carets: String = carets.into_iter().collect();
+        out.push_str(&format!("{}{}{}\n", colours.error, carets, colours.reset));
+    }
+    let legend = report
+        .errors
+        .iter()
+        .map(|err| (colours.error, err))
+        .chain(report.warnings.iter().map(|warn| (colours.warning, warn)));
+    for (number, (colour, err)) in legend.enumerate() {
+        let field = err
+            .tag
+            .filter(|_| err.span.is_some())
+            .map(|tag| format!("{tag} ({}): ", dict.field_name(tag)))
+            .unwrap_or_default();
+        out.push_str(&format!(
+            "  {}{}.{} {field}{err}\n",
+            colour,
+            number + 1,
+            colours.reset
+        ));
+    }
+    out
+}
+
+fn stream_invalid_message(
+    ctx: &mut PrettifyContext,
+    msg: &ParsedMessage<'_>,
+    dict: &FixTagLookup,
+    report: &validator::ValidationReport,
+    logged: Option<&str>,
 ) -> io::Result<()> {
-    let pretty = prettify_with_report(msg, dict, Some(report));
+    write_message_header(ctx.out, msg, dict, Some(report), ctx.banner, logged)?;
+    let hidden = ctx.hidden.tags(dict, Some(report));
+    let pretty = prettify_parsed(msg, dict, Some(report), &hidden, &ctx.display);
     write!(ctx.out, "{pretty}")?;
+    write_dictionary_line(ctx.out, msg, dict, ctx.fix_override, &ctx.display)?;
     writeln!(ctx.out)?;
     Ok(())
 }
 
+/// `--show-dict`: the line under a decoded message naming the dictionary that decoded
+/// it, how it was chosen, the `--xml` file it came from, and the fallback consulted for
+/// tags it does not define.
+fn write_dictionary_line(
+    out: &mut dyn Write,
+    msg: &ParsedMessage<'_>,
+    dict: &FixTagLookup,
+    fix_override: Option<&str>,
+    display: &DisplayOptions,
+) -> io::Result<()> {
+    if !display.show_dict {
+        return Ok(());
+    }
+    let choice = dictionary_choice(
+        msg.raw,
+        dict,
+        fix_override,
+        msg.fields.iter().map(|field| field.tag),
+    );
+    let colours = palette();
+    writeln!(
+        out,
+        "{}Dictionary:{} {}{}{}",
+        colours.name,
+        colours.reset,
+        colours.value,
+        format_dictionary_choice(&choice),
+        colours.reset
+    )
+}
+
+fn format_dictionary_choice(choice: &DictionaryChoice) -> String {
+    let detected = &choice.detected_key;
+    let mut text = match choice.selection {
+        Selection::AutoDetected => format!("{} auto-detected", choice.key),
+        Selection::Override => format!("{} by --fix override (detected {detected})", choice.key),
+        Selection::FixMap => format!("{} by --fix-map (detected {detected})", choice.key),
+        Selection::Default => format!("{} by default ({detected} not available)", choice.key),
+    };
+    if let Some(source) = &choice.source {
+        text.push_str(&format!(" from {source}"));
+    }
+    let Some(fallback) = &choice.fallback_key else {
+        text.push_str("; no fallback");
+        return text;
+    };
+    let role = match choice.fallback_role {
+        Some(FallbackKind::Session) => " (FIXT session)",
+        Some(FallbackKind::DetectedOverride) => " (detected version)",
+        None => "",
+    };
+    text.push_str(&format!("; fallback {fallback}{role}"));
+    if let Some(source) = &choice.fallback_source {
+        text.push_str(&format!(" from {source}"));
+    }
+    if choice.fallback_tags.is_empty() {
+        text.push_str(" not used");
+    } else {
+        let tags: Vec<String> = choice.fallback_tags.iter().map(u32::to_string).collect();
+        text.push_str(&format!(" used for {}", tags.join(", ")));
+    }
+    text
+}
+
 fn record_messages(messages: &[String], ctx: &mut PrettifyContext) {
     for msg in messages {
         record_msg_type(msg, ctx);
         if let Some(ref mut tracker) = ctx.summary.as_mut() {
             tracker.record_message(msg, ctx.fix_override);
         }
+        if let Some(sessions) = ctx.session_summary.as_mut() {
+            sessions.record_message(msg);
+        }
+        if let Some(diagram) = ctx.sequence_diagram.as_mut() {
+            diagram.record(&ParsedMessage::parse(msg), ctx.fix_override);
+        }
     }
 }
 
 fn record_msg_type(msg: &str, ctx: &mut PrettifyContext) {
     if let Some(mt) = extract_msg_type(msg) {
-        let entry = ctx.message_counts.entry(mt.clone()).or_default();
-        entry.count += 1;
-        if entry.label.is_none() {
-            let dict = load_dictionary_with_override(msg, ctx.fix_override);
-            entry.label = dict.enum_description(35, &mt).map(|s| s.to_string());
-        }
-        ctx.counts_dirty = true;
+        count_msg_type_with_label(msg, mt, ctx);
     }
 }
 
-fn extract_msg_type(msg: &str) -> Option<String> {
-    const SOH: char = '\u{0001}';
+fn record_parsed_msg_type(msg: &ParsedMessage<'_>, ctx: &mut PrettifyContext) {
+    if let Some(mt) = msg.first(35) {
+        count_msg_type_with_label(msg.raw, mt, ctx);
+    }
+}
+
+fn count_msg_type_with_label(msg: &str, mt: &str, ctx: &mut PrettifyContext) {
+    let fix_override = ctx.fix_override;
+    count_msg_type(&mut ctx.message_counts, mt, msg.len(), || {
+        let dict = load_label_dictionary(msg, fix_override);
+        dict.msg_type_label(mt).map(str::to_string)
+    });
+    ctx.counts_dirty = true;
+}
+
+/// Bump the count and bytes for a MsgType, resolving its label only the first time it
+/// is seen.
+fn count_msg_type(
+    counts: &mut HashMap<String, MsgTypeCount>,
+    mt: &str,
+    bytes: usize,
+    label: impl FnOnce() -> Option<String>,
+) {
+    let entry = match counts.get_mut(mt) {
+        Some(entry) => entry,
+        None => counts.entry(mt.to_string()).or_insert(MsgTypeCount {
+            count: 0,
+            label: label(),
+            bytes: 0,
+        }),
+    };
+    entry.count += 1;
+    entry.bytes += bytes;
+}
+
+fn extract_msg_type(msg: &str) -> Option<&str> {
     for field in msg.split(SOH) {
         if let Some((tag, val)) = field.split_once('=')
             && tag == "35"
         {
-            return Some(val.to_string());
+            return Some(val);
         }
     }
     None
@@ -835,44 +2194,31 @@ fn emit_messages(
     messages: &[String],
     ctx: &mut PrettifyContext,
     separator: &str,
+    logged: Option<&str>,
 ) -> io::Result<()> {
     if ctx.summary.is_some() {
         return Ok(());
     }
 
     for msg in messages {
-        process_fix_message(
-            msg,
-            ctx.out,
-            separator,
-            ctx.fix_override,
-            ctx.validation_enabled,
-        )?;
+        process_fix_message(msg, ctx, separator, logged)?;
     }
     Ok(())
 }
 
 fn render_summary_footer(ctx: &mut PrettifyContext) -> io::Result<()> {
+    let Some(tracker) = ctx.summary.as_mut() else {
+        return Ok(());
+    };
+    // Past --summary-max-orders the oldest completed orders go out now, terminal or not.
+    tracker.render_over_capacity(ctx.out)?;
     if !ctx.live_status_enabled {
         return Ok(());
     }
-    if let Some(ref mut tracker) = ctx.summary.as_mut() {
-        if ctx.follow {
-            let _printed = tracker.render_completed(ctx.out)?;
-            tracker.render_footer(ctx.out)?;
-        } else {
-            tracker.render_footer(ctx.out)?;
-        }
+    if ctx.follow {
+        let _printed = tracker.render_completed(ctx.out)?;
     }
-    Ok(())
-}
-
-/// Locate FIX message spans within a line using a permissive regex.
-fn find_fix_message_indices(line: &str) -> Vec<(usize, usize)> {
-    FIX_REGEX
-        .find_iter(line)
-        .map(|m| (m.start(), m.end()))
-        .collect()
+    tracker.render_footer(ctx.out)
 }
 
 /// Extract FIX messages from a line while also returning a coloured representation.
@@ -880,6 +2226,7 @@ fn extract_messages_and_format(
     line: &str,
     matches: &[(usize, usize)],
     display_delimiter: char,
+    display: &DisplayOptions,
 ) -> (Vec<String>, String) {
     let colours = palette();
     let mut output = String::new();
@@ -889,12 +2236,12 @@ fn extract_messages_and_format(
     for (start, end) in matches {
         output.push_str(colours.line);
         let before = &line[last..*start];
-        let before_display = apply_display_delimiter(before, display_delimiter);
+        let before_display = apply_surrounding_text(before, display_delimiter);
         output.push_str(&before_display);
 
         output.push_str(colours.message);
         let fix_segment = &line[*start..*end];
-        let fix_display = apply_display_delimiter(fix_segment, display_delimiter);
+        let fix_display = apply_display_delimiter(fix_segment, display_delimiter, display);
         output.push_str(&fix_display);
         fix_messages.push(line[*start..*end].to_string());
         last = *end;
@@ -902,7 +2249,7 @@ fn extract_messages_and_format(
 
     if last < line.len() {
         output.push_str(colours.line);
-        let tail_display = apply_display_delimiter(&line[last..], display_delimiter);
+        let tail_display = apply_surrounding_text(&line[last..], display_delimiter);
         output.push_str(&tail_display);
     } else {
         output.push_str(colours.line);
@@ -914,42 +2261,181 @@ fn extract_messages_and_format(
     (fix_messages, output)
 }
 
-/// Replace SOH display delimiters for human-readable rendering without mutating inputs.
-fn apply_display_delimiter<'a>(text: &'a str, delimiter: char) -> Cow<'a, str> {
-    const SOH: char = '\u{0001}';
-    if delimiter == SOH || !text.contains(SOH) {
-        Cow::Borrowed(text)
-    } else {
-        let mut output = String::with_capacity(text.len());
-        for ch in text.chars() {
-            if ch == SOH {
-                output.push(delimiter);
-            } else {
-                output.push(ch);
-            }
+/// `line` as displayed: the messages at `spans` as [`apply_display_delimiter`] shows
+/// them, and the text around them as [`apply_surrounding_text`] does.
+fn display_log_line(
+    line: &str,
+    spans: &[(usize, usize)],
+    delimiter: char,
+    display: &DisplayOptions,
+) -> String {
+    let mut output = String::with_capacity(line.len());
+    let mut last = 0;
+    for &(start, end) in spans {
+        output.push_str(&apply_surrounding_text(&line[last..start], delimiter));
+        output.push_str(&apply_display_delimiter(
+            &line[start..end],
+            delimiter,
+            display,
+        ));
+        last = end;
+    }
+    output.push_str(&apply_surrounding_text(&line[last..], delimiter));
+    output
+}
+
+/// Replace SOH display delimiters in FIX message text for human-readable rendering
+/// without mutating inputs.  A value's own copy of the delimiter is shown as `\|`
+/// unless `--no-escape` cleared it in `display`, so the line reads back unambiguously.
+/// Other control characters, tabs aside, are shown as `\xNN` so a garbled or hostile
+/// log cannot send escape sequences to the terminal.
+fn apply_display_delimiter<'a>(
+    text: &'a str,
+    delimiter: char,
+    display: &DisplayOptions,
+) -> Cow<'a, str> {
+    render_display_text(text, delimiter, Some(display))
+}
+
+/// The text of a log line around its messages, shown as [`apply_display_delimiter`]
+/// shows a message but with the delimiter left alone, since it is not part of a value.
+fn apply_surrounding_text(text: &str, delimiter: char) -> Cow<'_, str> {
+    render_display_text(text, delimiter, None)
+}
+
+/// `message` holds the options of the message `text` is part of; `None` for the text
+/// around messages.
+fn render_display_text<'a>(
+    text: &'a str,
+    delimiter: char,
+    message: Option<&DisplayOptions>,
+) -> Cow<'a, str> {
+    let escaped = |ch: char| {
+        (ch.is_control() && ch != delimiter && ch != '\t') || raw_bytes::byte_of(ch).is_some()
+    };
+    let backslashed =
+        |ch: char| message.is_some_and(|display| display.escapes_delimiter(ch, delimiter));
+    if !text.chars().any(|ch| escaped(ch) || backslashed(ch)) {
+        return Cow::Borrowed(text);
+    }
+    let mut output = String::with_capacity(text.len());
+    for ch in text.chars() {
+        if ch == SOH {
+            output.push(delimiter);
+        } else if backslashed(ch) {
+            output.push('\\');
+            output.push(ch);
+        } else if escaped(ch) {
+            push_control_escape(&mut output, ch);
+        } else {
+            output.push(ch);
         }
-        Cow::Owned(output)
     }
+    Cow::Owned(output)
 }
 
 /// Render a single FIX message (and validation errors when enabled) to the output stream.
 fn process_fix_message(
     msg: &str,
+    ctx: &mut PrettifyContext,
+    separator: &str,
+    logged: Option<&str>,
+) -> io::Result<()> {
+    let layout = MessageLayout {
+        fix_override: ctx.fix_override,
+        validate: ctx.validation_enabled,
+        banner: ctx.banner,
+        hidden: &ctx.hidden,
+        display: &ctx.display,
+    };
+    let lap = Lap::start(&ctx.benchmark);
+    let parsed = ParsedMessage::parse(msg);
+    lap.stop(&mut ctx.benchmark, Phase::Parse);
+    write_fix_message(
+        &mut *ctx.out,
+        &parsed,
+        &layout,
+        separator,
+        logged,
+        &mut ctx.benchmark,
+    )
+    .map(|_| ())
+}
+
+/// The settings a decoded message is rendered with, borrowed from the context so the
+/// message can be written somewhere other than its `out`.
+struct MessageLayout<'a> {
+    fix_override: Option<&'a str>,
+    validate: bool,
+    banner: bool,
+    hidden: &'a HiddenBlocks,
+    display: &'a DisplayOptions,
+}
+
+/// Write one decoded message, and its validation errors when `layout` validates,
+/// returning the validation report.  The time each step takes is charged to `bench`.
+fn write_fix_message(
     out: &mut dyn Write,
+    parsed: &ParsedMessage<'_>,
+    layout: &MessageLayout,
     separator: &str,
-    fix_override: Option<&str>,
-    validation_enabled: bool,
+    logged: Option<&str>,
+    bench: &mut Option<Benchmark>,
+) -> io::Result<Option<validator::ValidationReport>> {
+    let lap = Lap::start(bench);
+    let dict = load_dictionary_with_override(parsed.raw, layout.fix_override);
+    lap.stop(bench, Phase::Lookup);
+    let lap = Lap::start(bench);
+    let report = layout
+        .validate
+        .then(|| validator::validate_parsed_message(parsed, &dict));
+    lap.stop(bench, Phase::Validate);
+    let lap = Lap::start(bench);
+    let written = write_decoded(
+        out,
+        parsed,
+        &dict,
+        report.as_ref(),
+        layout,
+        separator,
+        logged,
+    );
+    lap.stop(bench, Phase::Render);
+    written.map(|_| report)
+}
+
+/// The rendering half of [`write_fix_message`]: banner, decoded tree and errors.
+fn write_decoded(
+    out: &mut dyn Write,
+    parsed: &ParsedMessage<'_>,
+    dict: &FixTagLookup,
+    report: Option<&validator::ValidationReport>,
+    layout: &MessageLayout,
+    separator: &str,
+    logged: Option<&str>,
 ) -> io::Result<()> {
-    let dict = load_dictionary_with_override(msg, fix_override);
-    let pretty = prettify_with_report(msg, &dict, None);
+    write_message_header(out, parsed, dict, report, layout.banner, logged)?;
+    let hidden = layout.hidden.tags(dict, report);
+    let pretty = prettify_parsed(parsed, dict, None, &hidden, layout.display);
     write!(out, "{pretty}")?;
+    write_dictionary_line(out, parsed, dict, layout.fix_override, layout.display)?;
 
-    if validation_enabled {
-        let report = validator::validate_fix_message(msg, &dict);
-        if !report.errors.is_empty() {
-            let colours = palette();
-            write!(out, "{separator}")?;
-            for err in report.errors {
+    if let Some(report) = report
+        && !report.errors.is_empty()
+    {
+        let colours = palette();
+        write!(out, "{separator}")?;
+        for err in &report.errors {
+            if layout.display.error_locations {
+                writeln!(
+                    out,
+                    "{}== {} {}{}",
+                    colours.error,
+                    err,
+                    err.location_label(),
+                    colours.reset
+                )?;
+            } else {
                 writeln!(out, "{}== {}{}", colours.error, err, colours.reset)?;
             }
         }
@@ -959,6 +2445,178 @@ fn process_fix_message(
     Ok(())
 }
 
+/// Write the `--banner` line above a decoded message, with the `--line-timestamp` time
+/// the line was logged and any session markers; without a banner the markers get a
+/// line of their own.
+fn write_message_header(
+    out: &mut dyn Write,
+    msg: &ParsedMessage<'_>,
+    dict: &FixTagLookup,
+    report: Option<&validator::ValidationReport>,
+    banner: bool,
+    logged: Option<&str>,
+) -> io::Result<()> {
+    write_message_line(out, msg, dict, report, banner, logged)?;
+    write_splice_warnings(out, report)
+}
+
+/// The `--banner` line, or the session markers on their own.
+fn write_message_line(
+    out: &mut dyn Write,
+    msg: &ParsedMessage<'_>,
+    dict: &FixTagLookup,
+    report: Option<&validator::ValidationReport>,
+    banner: bool,
+    logged: Option<&str>,
+) -> io::Result<()> {
+    let markers = format_session_markers(msg);
+    if banner {
+        let mut line = format_banner(msg, dict, report);
+        if let Some(time) = logged {
+            let colours = palette();
+            line.push_str(&format!(
+                "  {}Logged:{} {}{time}{}",
+                colours.name, colours.reset, colours.value, colours.reset
+            ));
+        }
+        if markers.is_empty() {
+            writeln!(out, "{line}")
+        } else {
+            writeln!(out, "{line}  {markers}")
+        }
+    } else if !markers.is_empty() {
+        writeln!(out, "{}{markers}", indent(BASE_INDENT))
+    } else {
+        Ok(())
+    }
+}
+
+/// A message whose BodyLength ends past its CheckSum or partway through a field is
+/// probably not the message that was sent, so say so before any of its fields.
+fn write_splice_warnings(
+    out: &mut dyn Write,
+    report: Option<&validator::ValidationReport>,
+) -> io::Result<()> {
+    let colours = palette();
+    let spliced = report
+        .into_iter()
+        .flat_map(|report| &report.errors)
+        .filter(|err| err.category == validator::ErrorCategory::Spliced);
+    for err in spliced {
+        writeln!(
+            out,
+            "{}!! TRUNCATED OR SPLICED: {err}{}",
+            colours.error, colours.reset
+        )?;
+    }
+    Ok(())
+}
+
+/// Flags that make a replayed or resent message stand out: PossDupFlag (43) and
+/// PossResend (97) set to `Y`, and an OrigSendingTime (122) later than SendingTime (52).
+fn session_markers(msg: &ParsedMessage<'_>) -> Vec<&'static str> {
+    let mut markers = Vec::new();
+    if msg.first(43) == Some("Y") {
+        markers.push("PossDup");
+    }
+    if msg.first(97) == Some("Y") {
+        markers.push("PossResend");
+    }
+    let orig_after_sending = msg
+        .first(122)
+        .and_then(parse_fix_timestamp)
+        .zip(msg.first(52).and_then(parse_fix_timestamp))
+        .is_some_and(|(orig, sending)| orig > sending);
+    if orig_after_sending {
+        markers.push("OrigSendingTime after SendingTime");
+    }
+    markers
+}
+
+fn format_session_markers(msg: &ParsedMessage<'_>) -> String {
+    let colours = palette();
+    session_markers(msg)
+        .iter()
+        .map(|marker| format!("[{}{marker}{}]", colours.error, colours.reset))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+const BANNER_ROUTE_WIDTH: usize = 24;
+const BANNER_MSG_TYPE_WIDTH: usize = 32;
+const BANNER_SEQ_WIDTH: usize = 12;
+
+/// Build the one-line `--banner` header shown above a decoded message: sender and target
+/// CompIDs, MsgType name, MsgSeqNum and SendingTime.  When a validation report is supplied
+/// the verdict and error count are appended.  Missing fields are shown as `?`.
+fn format_banner(
+    msg: &ParsedMessage<'_>,
+    dict: &FixTagLookup,
+    report: Option<&validator::ValidationReport>,
+) -> String {
+    let colours = palette();
+    let value_of = |tag: u32| msg.first(tag).unwrap_or("?");
+
+    let msg_type = value_of(35);
+    let route = format!(
+        "{}{}{} -> {}{}{}",
+        colours.value,
+        value_of(49),
+        colours.reset,
+        colours.value,
+        value_of(56),
+        colours.reset
+    );
+    let msg_type_cell = match dict.msg_type_label(msg_type) {
+        Some(name) => format!(
+            "{}{}{} ({}{}{})",
+            colours.enumeration, name, colours.reset, colours.value, msg_type, colours.reset
+        ),
+        None => format!("{}{}{}", colours.value, msg_type, colours.reset),
+    };
+    let seq_cell = format!(
+        "{}Seq:{} {}{}{}",
+        colours.name,
+        colours.reset,
+        colours.value,
+        value_of(34),
+        colours.reset
+    );
+    let time_cell = format!(
+        "{}Time:{} {}{}{}",
+        colours.name,
+        colours.reset,
+        colours.value,
+        value_of(52),
+        colours.reset
+    );
+
+    let mut banner = format!(
+        "{}>>{} {} {} {} {}",
+        colours.title,
+        colours.reset,
+        pad_ansi(&route, BANNER_ROUTE_WIDTH),
+        pad_ansi(&msg_type_cell, BANNER_MSG_TYPE_WIDTH),
+        pad_ansi(&seq_cell, BANNER_SEQ_WIDTH),
+        time_cell
+    );
+
+    if let Some(report) = report {
+        let count = report.errors.len();
+        if count == 0 {
+            banner.push_str(&format!("  [{}VALID{}]", colours.title, colours.reset));
+        } else {
+            let noun = if count == 1 { "error" } else { "errors" };
+            banner.push_str(&format!(
+                "  [{}INVALID: {count} {noun}{}]",
+                colours.error, colours.reset
+            ));
+        }
+    }
+
+    banner
+}
+
 pub fn disable_output_colours() {
     disable_colours();
 }
@@ -971,12 +2629,16 @@ fn test_lookup_with_order(field_order: Vec<u32>) -> FixTagLookup {
     messages.insert(
         "X".to_string(),
         MessageDef {
-            _name: "X".to_string(),
+            name: "X".to_string(),
             _msg_type: "X".to_string(),
-            field_order,
+            header_order: Vec::new(),
+            body_order: field_order,
+            trailer_order: Vec::new(),
             required: Vec::new(),
             groups: HashMap::new(),
             group_membership: HashMap::new(),
+            body_level: HashSet::new(),
+            provenance: HashMap::new(),
         },
     );
     FixTagLookup::new_for_tests(messages)
@@ -985,6 +2647,7 @@ fn test_lookup_with_order(field_order: Vec<u32>) -> FixTagLookup {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::decoder::repair::sealed_message;
     use crate::decoder::schema::FixDictionary;
     use crate::decoder::tag_lookup::load_dictionary;
     use crate::decoder::validator;
@@ -994,6 +2657,17 @@ mod tests {
     use std::sync::Mutex;
 
     const SOH: char = '\u{0001}';
+    /// The default display options, as a run without display flags has them.
+    const DISPLAY: DisplayOptions = DisplayOptions {
+        width: None,
+        max_value_width: None,
+        decode_xml: false,
+        error_locations: false,
+        md_ladder: false,
+        show_dict: false,
+        escape_delimiter: true,
+        line_prefix_width: 0,
+    };
     static TEST_GUARD: once_cell::sync::Lazy<Mutex<()>> =
         once_cell::sync::Lazy::new(|| Mutex::new(()));
 
@@ -1036,33 +2710,280 @@ mod tests {
     }
 
     #[test]
-    fn prettify_aligns_group_entries_without_header() {
+    fn values_share_a_column_unless_the_output_is_too_narrow() {
+        let text = format!(
+            "  35 (MsgType): {VALUE_START}D{VALUE_END} (NEW_ORDER_SINGLE)\n\
+             Group 1 ---\n\
+             \x20   448 (PartyID): {VALUE_START}{}{VALUE_END}\n",
+            "x".repeat(30)
+        );
+        assert_eq!(
+            align_values(&text, Some(39), ""),
+            format!(
+                "  35 (MsgType):    D (NEW_ORDER_SINGLE)\n\
+                 Group 1 ---\n\
+                 \x20   448 (PartyID): {}\n{}{}\n",
+                "x".repeat(20),
+                " ".repeat(19),
+                "x".repeat(10)
+            )
+        );
+        assert_eq!(
+            align_values(&text, Some(38), ""),
+            format!(
+                "  35 (MsgType): D (NEW_ORDER_SINGLE)\n\
+                 Group 1 ---\n\
+                 \x20   448 (PartyID): {}\n{}{}\n",
+                "x".repeat(20),
+                " ".repeat(19),
+                "x".repeat(10)
+            )
+        );
+    }
+
+    #[test]
+    fn prettify_aligns_group_entries_without_header() {
+        let _lock = TEST_GUARD.lock().unwrap();
+        disable_output_colours();
+        let dict = small_group_lookup();
+        let msg = format!(
+            "8=FIX.4.4{SOH}35=W{SOH}268=2{SOH}269=0{SOH}270=12.34{SOH}269=1{SOH}270=56.78{SOH}10=000{SOH}"
+        );
+        let rendered = prettify_with_report(&msg, &dict, None, &HashSet::new(), &DISPLAY);
+        assert!(
+            !rendered.contains("Group: NoMDEntries"),
+            "group header line should be omitted: {rendered}"
+        );
+        let count_line = rendered
+            .lines()
+            .find(|l| l.contains("NoMDEntries"))
+            .expect("count tag line present");
+        let group_line = rendered
+            .lines()
+            .find(|l| l.contains("Group 1"))
+            .expect("group entry label present");
+        let paren_col = count_line.find('(').expect("open paren present");
+        let dash_col = group_line.find('-').expect("dashes present");
+        assert_eq!(
+            dash_col,
+            paren_col + 1,
+            "group separator should start one space after '(' anchor"
+        );
+    }
+
+    #[test]
+    fn control_characters_in_logs_are_escaped_not_replayed() {
+        let _lock = TEST_GUARD.lock().unwrap();
+        disable_output_colours();
+        let red = "\u{1b}[31m";
+        let log = format!(
+            "IN {red}8=FIX.4.4{SOH}9=005{SOH}35=A{SOH}95=6{SOH}96=\u{0}{red}{SOH}58=hi{red}{SOH}10=000{SOH}\n"
+        );
+        let obfuscator = fix::create_obfuscator(false);
+        let mut out = Vec::new();
+        let mut err = Vec::new();
+        let mut summary = None;
+        let mut ctx = PrettifyContext::new(&mut out, &mut err, &obfuscator, &mut summary);
+        stream_reader(
+            &mut BufReader::new(Cursor::new(log)),
+            0,
+            RecordFormat::Lines,
+            &mut ctx,
+        )
+        .unwrap();
+
+        let output = String::from_utf8(out).unwrap();
+        assert!(!output.contains('\u{1b}'), "live escape in {output:?}");
+        assert!(output.contains("IN \\x1B[31m8=FIX.4.4"), "{output}");
+        assert!(
+            output.contains("(RawData):       <6 bytes: 00 1B 5B 33 31 6D>"),
+            "{output}"
+        );
+        assert!(output.contains("(Text):          hi\\x1B[31m"), "{output}");
+    }
+
+    #[test]
+    fn dictionary_reload_clears_the_cached_dictionary() {
+        fn reload(_: &str) -> anyhow::Result<String> {
+            Ok("FIX44".to_string())
+        }
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("custom.xml");
+        std::fs::write(&path, "<fix/>").unwrap();
+        let paths = vec![path.to_string_lossy().into_owned()];
+        let obfuscator = fix::create_obfuscator(false);
+        let mut out = Vec::new();
+        let mut err = Vec::new();
+        let mut summary = None;
+        let mut ctx = PrettifyContext::new(&mut out, &mut err, &obfuscator, &mut summary);
+        ctx.dictionary_watcher = Some(DictionaryWatcher::with_interval(
+            &paths,
+            reload,
+            Duration::ZERO,
+        ));
+        ctx.dict_cache.lookup("8=FIX.4.4\u{0001}35=0\u{0001}", None);
+
+        poll_dictionary_watcher(&mut ctx);
+        assert!(
+            ctx.dict_cache.dict.is_some(),
+            "unchanged file keeps the cache"
+        );
+
+        let later = std::time::SystemTime::now() + Duration::from_secs(10);
+        File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(later)
+            .unwrap();
+        poll_dictionary_watcher(&mut ctx);
+        assert!(ctx.dict_cache.dict.is_none(), "reload must clear the cache");
+    }
+
+    #[test]
+    fn lines_without_messages_are_escaped_too() {
+        let _lock = TEST_GUARD.lock().unwrap();
+        disable_output_colours();
+        let log = "session \u{1b}]0;pwned\u{7} idle\u{0}\n";
+        let obfuscator = fix::create_obfuscator(false);
+        let mut out = Vec::new();
+        let mut err = Vec::new();
+        let mut summary = None;
+        let mut ctx = PrettifyContext::new(&mut out, &mut err, &obfuscator, &mut summary);
+        stream_reader(
+            &mut BufReader::new(Cursor::new(log)),
+            0,
+            RecordFormat::Lines,
+            &mut ctx,
+        )
+        .unwrap();
+
+        let output = String::from_utf8(out).unwrap();
+        assert_eq!(output, "session \\x1B]0;pwned\\x07 idle\\x00\n");
+    }
+
+    #[test]
+    fn display_delimiter_inside_a_value_is_escaped_but_not_around_the_message() {
+        let msg = format!("8=FIX.4.4{SOH}35=D{SOH}58=a|b{SOH}10=000{SOH}");
+        assert_eq!(
+            apply_display_delimiter(&msg, '|', &DISPLAY),
+            "8=FIX.4.4|35=D|58=a\\|b|10=000|"
+        );
+        assert_eq!(apply_display_delimiter(&msg, SOH, &DISPLAY), msg);
+
+        let line = format!("IN|x {msg} |done");
+        let start = line.find("8=FIX").unwrap();
+        let end = start + msg.len();
+        assert_eq!(
+            display_log_line(&line, &[(start, end)], '|', &DISPLAY),
+            "IN|x 8=FIX.4.4|35=D|58=a\\|b|10=000| |done"
+        );
+    }
+
+    #[test]
+    fn framed_records_decode_exactly_like_the_same_lines() {
+        let _lock = TEST_GUARD.lock().unwrap();
+        disable_output_colours();
+        let records = [
+            format!("IN 8=FIX.4.4{SOH}9=005{SOH}35=A{SOH}10=000{SOH}"),
+            "session idle".to_string(),
+            format!("OUT 8=FIX.4.4{SOH}9=005{SOH}35=0{SOH}10=000{SOH}"),
+        ];
+        let decode = |input: Vec<u8>, format: RecordFormat| {
+            let obfuscator = fix::create_obfuscator(false);
+            let mut out = Vec::new();
+            let mut err = Vec::new();
+            let mut summary = None;
+            let mut ctx = PrettifyContext::new(&mut out, &mut err, &obfuscator, &mut summary);
+            ctx.validation_enabled = true;
+            stream_reader(&mut Cursor::new(input), 0, format, &mut ctx).unwrap();
+            finish_run(&mut ctx, false);
+            String::from_utf8(out).unwrap()
+        };
+
+        let lines: Vec<u8> = records
+            .iter()
+            .flat_map(|r| format!("{r}\n").into_bytes())
+            .collect();
+        let nul: Vec<u8> = records
+            .iter()
+            .flat_map(|r| format!("{r}\0").into_bytes())
+            .collect();
+        let len32: Vec<u8> = records
+            .iter()
+            .flat_map(|r| [&(r.len() as u32).to_be_bytes()[..], r.as_bytes()].concat())
+            .collect();
+
+        let expected = decode(lines, RecordFormat::Lines);
+        assert!(expected.contains("Validated 2 message(s)"), "{expected}");
+        assert_eq!(decode(nul, RecordFormat::Nul), expected);
+        assert_eq!(decode(len32, RecordFormat::Len32), expected);
+    }
+
+    #[test]
+    fn component_groups_render_as_entries_in_execution_report() {
         let _lock = TEST_GUARD.lock().unwrap();
         disable_output_colours();
-        let dict = small_group_lookup();
         let msg = format!(
-            "8=FIX.4.4{SOH}35=W{SOH}268=2{SOH}269=0{SOH}270=12.34{SOH}269=1{SOH}270=56.78{SOH}10=000{SOH}"
+            "8=FIX.4.4{SOH}9=000{SOH}35=8{SOH}37=O1{SOH}453=2{SOH}448=BRKR{SOH}447=D{SOH}452=1{SOH}\
+             448=CLNT{SOH}447=D{SOH}452=3{SOH}17=E1{SOH}150=0{SOH}39=0{SOH}55=IBM{SOH}54=1{SOH}10=000{SOH}"
         );
-        let rendered = prettify_with_report(&msg, &dict, None);
+        let dict = load_dictionary(&msg);
         assert!(
-            !rendered.contains("Group: NoMDEntries"),
-            "group header line should be omitted: {rendered}"
+            dict.message_def("8")
+                .is_some_and(|def| def.groups.contains_key(&453)),
+            "NoPartyIDs should be a top-level group of ExecutionReport"
         );
-        let count_line = rendered
-            .lines()
-            .find(|l| l.contains("NoMDEntries"))
-            .expect("count tag line present");
-        let group_line = rendered
-            .lines()
-            .find(|l| l.contains("Group 1"))
-            .expect("group entry label present");
-        let paren_col = count_line.find('(').expect("open paren present");
-        let dash_col = group_line.find('-').expect("dashes present");
-        assert_eq!(
-            dash_col,
-            paren_col + 1,
-            "group separator should start one space after '(' anchor"
+
+        let report = validator::validate_fix_message(&msg, &dict);
+        assert!(
+            !report
+                .errors
+                .iter()
+                .any(|e| e.message.starts_with("Duplicate tag")),
+            "party entries must not be reported as duplicates: {:?}",
+            report.errors
+        );
+
+        let rendered = prettify_with_report(&msg, &dict, None, &HashSet::new(), &DISPLAY);
+        assert!(rendered.contains("Group 1"), "{rendered}");
+        assert!(rendered.contains("Group 2"), "{rendered}");
+    }
+
+    #[test]
+    fn empty_absent_and_non_numeric_groups_render_without_spurious_warnings() {
+        let _lock = TEST_GUARD.lock().unwrap();
+        disable_output_colours();
+        let render = |groups: &str, validate: bool| {
+            let msg = format!(
+                "8=FIX.4.4{SOH}9=000{SOH}35=W{SOH}49=AAA{SOH}56=BBB{SOH}34=2{SOH}\
+                 52=20240101-00:00:00{SOH}55=X{SOH}{groups}10=000{SOH}"
+            );
+            let dict = load_dictionary(&msg);
+            let report = validate.then(|| validator::validate_fix_message(&msg, &dict));
+            prettify_with_report(&msg, &dict, report.as_ref(), &HashSet::new(), &DISPLAY)
+        };
+
+        let empty = render(&format!("268=0{SOH}"), true);
+        assert!(empty.contains("268 (NoMDEntries):  0\n"), "{empty}");
+        assert!(!empty.contains("269"), "{empty}");
+        assert!(!empty.contains("Warning"), "{empty}");
+
+        let absent = render("", true);
+        assert!(
+            absent.contains("Required group 268 (NoMDEntries) missing"),
+            "{absent}"
+        );
+        assert!(!absent.contains("269"), "{absent}");
+
+        let bad = render(&format!("268=x{SOH}"), false);
+        assert!(
+            bad.contains("Warning: NumInGroup 268 (NoMDEntries) is not a number: 'x'"),
+            "{bad}"
         );
+        let bad = render(&format!("268=x{SOH}"), true);
+        assert!(!bad.contains("Warning"), "{bad}");
+        assert!(bad.contains("Invalid NumInGroup value 'x'"), "{bad}");
     }
 
     #[test]
@@ -1083,17 +3004,50 @@ mod tests {
             err_out: &mut err,
             obfuscator: &obfuscator,
             display_delimiter: '|',
+            display: DISPLAY,
             summary: &mut summary,
+            session_summary: None,
+            sequence_diagram: None,
+            line_timing: None,
+            dedupe: None,
             fix_override: None,
             follow: false,
             live_status_enabled: true,
             validation_enabled: true,
+            banner: false,
+            dictionary_watcher: None,
+            count_only: false,
+            msg_types: MsgTypeFilter::default(),
+            tag_usage: None,
+            tag_histogram: None,
+            split: None,
+            benchmark: None,
+            dict_cache: DictionaryCache::default(),
             message_counts: HashMap::new(),
             counts_dirty: false,
+            counts_layout: CountsLayout::default(),
+            validation_stats: ValidationStats::default(),
+            validation_summary: None,
+            clock_skew: None,
+            order_chains: None,
+            declared_versions: DeclaredVersions::default(),
+            input_range: InputRange::default(),
+            lines: LineDecoder::default(),
+            stdin_format: RecordFormat::Lines,
+            detected_delimiter: None,
+            passthrough: false,
+            raw_annotate: false,
+            hidden: HiddenBlocks::default(),
+            extract: ExtractSinks::default(),
+            report: None,
+            replay: None,
+            source: String::new(),
+            source_is_file: false,
+            file_prefix: None,
             interrupted: interrupt_flag(),
         };
         let mut reader = BufReader::new(Cursor::new(line));
-        stream_reader(&mut reader, &mut ctx).unwrap();
+        stream_reader(&mut reader, 0, RecordFormat::Lines, &mut ctx).unwrap();
 
         let output = String::from_utf8(out).unwrap();
         assert!(
@@ -1110,6 +3064,123 @@ mod tests {
         );
     }
 
+    /// The stream path parses each message once; its output must match composing the
+    /// string-accepting validator and prettifier for every message in the fixture log.
+    #[test]
+    fn parsed_validation_stream_matches_string_apis_on_fixture() {
+        let _lock = TEST_GUARD.lock().unwrap();
+        let log = std::fs::read_to_string(concat!(env!("CARGO_MANIFEST_DIR"), "/invalidfix.txt"))
+            .expect("fixture log");
+        let obfuscator = fix::create_obfuscator(false);
+        let mut out = Vec::new();
+        let mut err = io::sink();
+        let mut summary = None;
+        let mut ctx = PrettifyContext {
+            out: &mut out,
+            err_out: &mut err,
+            obfuscator: &obfuscator,
+            display_delimiter: '|',
+            display: DISPLAY,
+            summary: &mut summary,
+            session_summary: None,
+            sequence_diagram: None,
+            line_timing: None,
+            dedupe: None,
+            fix_override: None,
+            follow: false,
+            live_status_enabled: false,
+            validation_enabled: true,
+            banner: true,
+            dictionary_watcher: None,
+            count_only: false,
+            msg_types: MsgTypeFilter::default(),
+            tag_usage: None,
+            tag_histogram: None,
+            split: None,
+            benchmark: None,
+            dict_cache: DictionaryCache::default(),
+            message_counts: HashMap::new(),
+            counts_dirty: false,
+            counts_layout: CountsLayout::default(),
+            validation_stats: ValidationStats::default(),
+            validation_summary: None,
+            clock_skew: None,
+            order_chains: None,
+            declared_versions: DeclaredVersions::default(),
+            input_range: InputRange::default(),
+            lines: LineDecoder::default(),
+            stdin_format: RecordFormat::Lines,
+            detected_delimiter: None,
+            passthrough: false,
+            raw_annotate: false,
+            hidden: HiddenBlocks::default(),
+            extract: ExtractSinks::default(),
+            report: None,
+            replay: None,
+            source: String::new(),
+            source_is_file: false,
+            file_prefix: None,
+            interrupted: interrupt_flag(),
+        };
+        let mut reader = BufReader::new(Cursor::new(log.clone()));
+        stream_reader(&mut reader, 0, RecordFormat::Lines, &mut ctx).unwrap();
+        let stats = ctx.validation_stats;
+
+        let colours = palette();
+        let mut expected = String::new();
+        let mut messages = 0;
+        for (idx, line) in log.lines().enumerate() {
+            let mut header_emitted = false;
+            for (start, end) in find_fix_message_indices(line) {
+                let msg = &line[start..end];
+                let dict = load_dictionary(msg);
+                let report = validator::validate_fix_message(msg, &dict);
+                messages += 1;
+                if report.is_clean() {
+                    continue;
+                }
+                if !header_emitted {
+                    let display = apply_display_delimiter(line, '|', &DISPLAY);
+                    expected.push_str(&format!(
+                        "Line {}: {}{}{}\n",
+                        idx + 1,
+                        colours.line,
+                        display,
+                        colours.reset
+                    ));
+                    header_emitted = true;
+                }
+                let banner = format_banner(&ParsedMessage::parse(msg), &dict, Some(&report));
+                expected.push_str(&format!("{banner}\n"));
+                for err in report
+                    .errors
+                    .iter()
+                    .filter(|err| err.category == validator::ErrorCategory::Spliced)
+                {
+                    expected.push_str(&format!(
+                        "{}!! TRUNCATED OR SPLICED: {err}{}\n",
+                        colours.error, colours.reset
+                    ));
+                }
+                expected.push_str(&prettify_with_report(
+                    msg,
+                    &dict,
+                    Some(&report),
+                    &HashSet::new(),
+                    &DISPLAY,
+                ));
+                expected.push('\n');
+            }
+        }
+
+        assert!(stats.invalid > 0, "fixture should contain invalid messages");
+        assert_eq!(stats.messages, messages);
+        assert!(
+            String::from_utf8(out).unwrap() == expected,
+            "stream output differs from the string-based APIs"
+        );
+    }
+
     #[test]
     fn validation_skips_valid_messages() {
         let _lock = TEST_GUARD.lock().unwrap();
@@ -1118,8 +3189,7 @@ mod tests {
         let order = lookup
             .message_def("0")
             .expect("heartbeat definition")
-            .field_order
-            .clone();
+            .field_order();
         let mut values = HashMap::new();
         values.insert(35u32, "0");
         values.insert(34u32, "1");
@@ -1147,17 +3217,50 @@ mod tests {
             err_out: &mut err,
             obfuscator: &obfuscator,
             display_delimiter: '|',
+            display: DISPLAY,
             summary: &mut summary,
+            session_summary: None,
+            sequence_diagram: None,
+            line_timing: None,
+            dedupe: None,
             fix_override: None,
             follow: false,
             live_status_enabled: true,
             validation_enabled: true,
+            banner: false,
+            dictionary_watcher: None,
+            count_only: false,
+            msg_types: MsgTypeFilter::default(),
+            tag_usage: None,
+            tag_histogram: None,
+            split: None,
+            benchmark: None,
+            dict_cache: DictionaryCache::default(),
             message_counts: HashMap::new(),
             counts_dirty: false,
+            counts_layout: CountsLayout::default(),
+            validation_stats: ValidationStats::default(),
+            validation_summary: None,
+            clock_skew: None,
+            order_chains: None,
+            declared_versions: DeclaredVersions::default(),
+            input_range: InputRange::default(),
+            lines: LineDecoder::default(),
+            stdin_format: RecordFormat::Lines,
+            detected_delimiter: None,
+            passthrough: false,
+            raw_annotate: false,
+            hidden: HiddenBlocks::default(),
+            extract: ExtractSinks::default(),
+            report: None,
+            replay: None,
+            source: String::new(),
+            source_is_file: false,
+            file_prefix: None,
             interrupted: interrupt_flag(),
         };
         let mut reader = BufReader::new(Cursor::new(line));
-        stream_reader(&mut reader, &mut ctx).unwrap();
+        stream_reader(&mut reader, 0, RecordFormat::Lines, &mut ctx).unwrap();
 
         let output = String::from_utf8(out).unwrap();
         assert!(
@@ -1181,21 +3284,54 @@ mod tests {
             err_out: &mut err,
             obfuscator: &obfuscator,
             display_delimiter: '|',
+            display: DISPLAY,
             summary: &mut summary,
+            session_summary: None,
+            sequence_diagram: None,
+            line_timing: None,
+            dedupe: None,
             fix_override: None,
             follow: false,
             live_status_enabled: true,
             validation_enabled: true,
+            banner: false,
+            dictionary_watcher: None,
+            count_only: false,
+            msg_types: MsgTypeFilter::default(),
+            tag_usage: None,
+            tag_histogram: None,
+            split: None,
+            benchmark: None,
+            dict_cache: DictionaryCache::default(),
             message_counts: HashMap::new(),
             counts_dirty: false,
+            counts_layout: CountsLayout::default(),
+            validation_stats: ValidationStats::default(),
+            validation_summary: None,
+            clock_skew: None,
+            order_chains: None,
+            declared_versions: DeclaredVersions::default(),
+            input_range: InputRange::default(),
+            lines: LineDecoder::default(),
+            stdin_format: RecordFormat::Lines,
+            detected_delimiter: None,
+            passthrough: false,
+            raw_annotate: false,
+            hidden: HiddenBlocks::default(),
+            extract: ExtractSinks::default(),
+            report: None,
+            replay: None,
+            source: String::new(),
+            source_is_file: false,
+            file_prefix: None,
             interrupted: interrupt_flag(),
         };
         let mut reader = BufReader::new(Cursor::new(line));
-        stream_reader(&mut reader, &mut ctx).unwrap();
+        stream_reader(&mut reader, 0, RecordFormat::Lines, &mut ctx).unwrap();
 
         let output = String::from_utf8(out).unwrap();
         assert!(
-            output.contains("35 (MsgType): Missing"),
+            output.contains("35 (MsgType):     Missing"),
             "missing tag should be shown in decoded output: {output}"
         );
     }
@@ -1212,7 +3348,7 @@ mod tests {
             .tag_errors
             .insert(34, vec!["missing sequence".to_string()]);
 
-        let pretty = prettify_with_report(&msg, &dict, Some(&report));
+        let pretty = prettify_with_report(&msg, &dict, Some(&report), &HashSet::new(), &DISPLAY);
         let lines: Vec<&str> = pretty.lines().collect();
         let missing_lines: Vec<&str> = lines
             .iter()
@@ -1227,6 +3363,70 @@ mod tests {
         );
     }
 
+    #[test]
+    fn hidden_header_and_trailer_still_show_faulty_fields() {
+        let _lock = TEST_GUARD.lock().unwrap();
+        disable_output_colours();
+        let message = |sending_time: &str| {
+            sealed_message(
+                "FIX.4.4",
+                &format!(
+                    "35=0{SOH}49=AAA{SOH}56=BBB{SOH}34=2{SOH}52={sending_time}{SOH}112=PING{SOH}"
+                ),
+            )
+        };
+        let hidden = HiddenBlocks {
+            header: true,
+            trailer: true,
+        };
+        let tags = |pretty: &str| -> Vec<u32> {
+            pretty
+                .lines()
+                .filter_map(|line| line.split_whitespace().next()?.parse().ok())
+                .collect()
+        };
+
+        let clean = message("20240101-00:00:00");
+        let dict = load_dictionary(&clean);
+        let parsed = ParsedMessage::parse(&clean);
+        let report = validator::validate_parsed_message(&parsed, &dict);
+        assert!(report.is_clean(), "{report:?}");
+        let pretty = prettify_parsed(
+            &parsed,
+            &dict,
+            None,
+            &hidden.tags(&dict, Some(&report)),
+            &DISPLAY,
+        );
+        assert_eq!(tags(&pretty), [35, 112], "{pretty}");
+
+        let bad = message("yesterday");
+        let parsed = ParsedMessage::parse(&bad);
+        let report = validator::validate_parsed_message(&parsed, &dict);
+        let pretty = prettify_parsed(
+            &parsed,
+            &dict,
+            Some(&report),
+            &hidden.tags(&dict, Some(&report)),
+            &DISPLAY,
+        );
+        assert_eq!(tags(&pretty), [35, 52, 112], "{pretty}");
+        assert!(!report.tag_errors[&52].is_empty());
+
+        let header_only = HiddenBlocks {
+            header: true,
+            trailer: false,
+        };
+        let pretty = prettify_with_report(
+            &clean,
+            &dict,
+            None,
+            &header_only.tags(&dict, None),
+            &DISPLAY,
+        );
+        assert_eq!(tags(&pretty), [35, 112, 10], "{pretty}");
+    }
+
     #[test]
     fn build_tag_order_respects_annotations_and_trailer() {
         let _lock = TEST_GUARD.lock().unwrap();
@@ -1234,12 +3434,16 @@ mod tests {
         messages.insert(
             "X".to_string(),
             MessageDef {
-                _name: "X".to_string(),
+                name: "X".to_string(),
                 _msg_type: "X".to_string(),
-                field_order: vec![8, 9, 35, 55],
+                header_order: vec![8, 9, 35],
+                body_order: vec![55],
+                trailer_order: Vec::new(),
                 required: Vec::new(),
                 groups: HashMap::new(),
                 group_membership: HashMap::new(),
+                body_level: HashSet::new(),
+                provenance: HashMap::new(),
             },
         );
         let dict = FixTagLookup::new_for_tests(messages);
@@ -1281,18 +3485,422 @@ mod tests {
     }
 
     #[test]
-    fn trim_line_endings_strips_crlf() {
-        let mut line = "abc\r\n".to_string();
-        trim_line_endings(&mut line);
-        assert_eq!(line, "abc");
+    fn read_record_with_follow_returns_zero_on_eof() {
+        let mut reader = Cursor::new("");
+        let mut buf = Vec::new();
+        let n = read_record_with_follow(
+            &mut reader,
+            &mut buf,
+            RecordFormat::Lines,
+            true,
+            interrupt_flag(),
+        )
+        .unwrap();
+        assert_eq!(n, 0);
+    }
+
+    #[test]
+    #[cfg(feature = "dict-fix42")]
+    fn dictionary_cache_reuses_lookup_for_same_begin_string() {
+        let mut cache = DictionaryCache::default();
+        let fix44 = format!("8=FIX.4.4{SOH}35=0{SOH}10=000{SOH}");
+        let fix42 = format!("8=FIX.4.2{SOH}35=0{SOH}10=000{SOH}");
+        let first = cache.lookup(&fix44, None) as *const FixTagLookup;
+        let second = cache.lookup(&fix44, None) as *const FixTagLookup;
+        assert_eq!(first, second, "same BeginString should reuse the lookup");
+        assert_eq!(cache.lookup(&fix42, None).schema_key(), "FIX42");
+        assert_eq!(cache.lookup(&fix44, None).schema_key(), "FIX44");
     }
 
     #[test]
-    fn read_line_with_follow_returns_zero_on_eof() {
-        let mut reader = Cursor::new("");
-        let mut buf = String::new();
-        let n = read_line_with_follow(&mut reader, &mut buf, true, interrupt_flag()).unwrap();
-        assert_eq!(n, 0);
+    fn banner_layout_is_stable_without_colours() {
+        let _lock = TEST_GUARD.lock().unwrap();
+        disable_output_colours();
+        let msg = format!(
+            "8=FIX.4.4{SOH}9=050{SOH}35=8{SOH}49=SNDR{SOH}56=TGT{SOH}34=12{SOH}52=20240101-12:00:00.000{SOH}10=000{SOH}"
+        );
+        let dict = load_dictionary(&msg);
+        assert_eq!(
+            format_banner(&ParsedMessage::parse(&msg), &dict, None),
+            ">> SNDR -> TGT              EXECUTION_REPORT (8)             Seq: 12      Time: 20240101-12:00:00.000"
+        );
+
+        let report = validator::ValidationReport::default();
+        assert!(
+            format_banner(&ParsedMessage::parse(&msg), &dict, Some(&report)).ends_with("  [VALID]"),
+            "clean report should be flagged VALID"
+        );
+
+        let mut report = validator::ValidationReport::default();
+        report.errors.push(validator::ValidationError::new(
+            validator::ErrorCategory::Checksum,
+            "one",
+        ));
+        report.errors.push(validator::ValidationError::new(
+            validator::ErrorCategory::BodyLength,
+            "two",
+        ));
+        assert!(
+            format_banner(&ParsedMessage::parse(&msg), &dict, Some(&report))
+                .ends_with("  [INVALID: 2 errors]"),
+            "error count should be reported"
+        );
+    }
+
+    #[test]
+    fn raw_annotation_aligns_carets_under_offending_values() {
+        let _lock = TEST_GUARD.lock().unwrap();
+        disable_output_colours();
+        let msg = format!(
+            "8=FIX.4.4{SOH}9=5{SOH}35=0{SOH}49=Café{SOH}56=B{SOH}34=1{SOH}52=20240101-00:00:00{SOH}10=000{SOH}"
+        );
+        let parsed = ParsedMessage::parse(&msg);
+        let dict = load_dictionary(&msg);
+        let report = validator::validate_parsed_message(&parsed, &dict);
+        let annotated = render_raw_annotation(&parsed, &dict, &report, '|', &DISPLAY);
+        let lines: Vec<&str> = annotated.lines().collect();
+        assert_eq!(
+            lines[0],
+            "8=FIX.4.4|9=5|35=0|49=Café|56=B|34=1|52=20240101-00:00:00|10=000|"
+        );
+        let carets: Vec<usize> = lines[1].match_indices('^').map(|(col, _)| col).collect();
+        let body_length = lines[0].find("9=5").unwrap() + 2;
+        let checksum = lines[0].chars().count() - "000|".len();
+        assert_eq!(carets, [body_length, checksum], "{annotated}");
+        assert_eq!(&lines[1][checksum..], "^~~");
+        assert_eq!(
+            lines[2],
+            "  1. 9 (BodyLength): BodyLength mismatch: got 5, expected 45"
+        );
+        assert!(lines[3].starts_with("  2. 10 (CheckSum): Checksum mismatch"));
+    }
+
+    #[test]
+    fn banner_marks_missing_fields() {
+        let _lock = TEST_GUARD.lock().unwrap();
+        disable_output_colours();
+        let msg = format!("8=FIX.4.4{SOH}9=005{SOH}35=ZZ{SOH}10=000{SOH}");
+        let dict = load_dictionary(&msg);
+        assert_eq!(
+            format_banner(&ParsedMessage::parse(&msg), &dict, None),
+            ">> ? -> ?                   ZZ                               Seq: ?       Time: ?"
+        );
+    }
+
+    #[test]
+    fn banner_precedes_invalid_messages_in_validation_mode() {
+        let _lock = TEST_GUARD.lock().unwrap();
+        disable_output_colours();
+        let obfuscator = fix::create_obfuscator(false);
+        let body = format!("35=0{SOH}34=1{SOH}49=AAA{SOH}52=20240101-00:00:00{SOH}56=BBB{SOH}");
+        let msg_without_checksum = format!("8=FIX.4.4{SOH}9={:03}{SOH}{}", body.len() + 1, body);
+        let checksum = validator::calculate_checksum(&format!("{msg_without_checksum}10=000{SOH}"));
+        let msg = format!("{msg_without_checksum}10={checksum:03}{SOH}");
+        let mut out = Vec::new();
+        let mut err = io::sink();
+        let mut summary = None;
+        let mut ctx = PrettifyContext {
+            out: &mut out,
+            err_out: &mut err,
+            obfuscator: &obfuscator,
+            display_delimiter: '|',
+            display: DISPLAY,
+            summary: &mut summary,
+            session_summary: None,
+            sequence_diagram: None,
+            line_timing: None,
+            dedupe: None,
+            fix_override: None,
+            follow: false,
+            live_status_enabled: false,
+            validation_enabled: true,
+            banner: true,
+            dictionary_watcher: None,
+            count_only: false,
+            msg_types: MsgTypeFilter::default(),
+            tag_usage: None,
+            tag_histogram: None,
+            split: None,
+            benchmark: None,
+            dict_cache: DictionaryCache::default(),
+            message_counts: HashMap::new(),
+            counts_dirty: false,
+            counts_layout: CountsLayout::default(),
+            validation_stats: ValidationStats::default(),
+            validation_summary: None,
+            clock_skew: None,
+            order_chains: None,
+            declared_versions: DeclaredVersions::default(),
+            input_range: InputRange::default(),
+            lines: LineDecoder::default(),
+            stdin_format: RecordFormat::Lines,
+            detected_delimiter: None,
+            passthrough: false,
+            raw_annotate: false,
+            hidden: HiddenBlocks::default(),
+            extract: ExtractSinks::default(),
+            report: None,
+            replay: None,
+            source: String::new(),
+            source_is_file: false,
+            file_prefix: None,
+            interrupted: interrupt_flag(),
+        };
+        let mut reader = BufReader::new(Cursor::new(format!("{msg}\n")));
+        stream_reader(&mut reader, 0, RecordFormat::Lines, &mut ctx).unwrap();
+
+        let output = String::from_utf8(out).unwrap();
+        let banner = output
+            .lines()
+            .find(|l| l.starts_with(">> "))
+            .expect("banner line present");
+        assert!(
+            banner.starts_with(">> AAA -> BBB               HEARTBEAT (0)"),
+            "banner should lead with route and message type: {banner}"
+        );
+        assert!(
+            banner.contains("[INVALID: "),
+            "validation verdict should be included: {banner}"
+        );
+    }
+
+    fn decode_file_with(obfuscator: &fix::Obfuscator, path: &str) -> String {
+        decode_file_with_summary(obfuscator, path, false, &mut None)
+    }
+
+    fn decode_file_with_summary(
+        obfuscator: &fix::Obfuscator,
+        path: &str,
+        validation_enabled: bool,
+        summary: &mut Option<OrderSummary>,
+    ) -> String {
+        let mut out = Vec::new();
+        let mut err = io::sink();
+        let mut ctx = PrettifyContext {
+            out: &mut out,
+            err_out: &mut err,
+            obfuscator,
+            display_delimiter: '|',
+            display: DISPLAY,
+            summary,
+            session_summary: None,
+            sequence_diagram: None,
+            line_timing: None,
+            dedupe: None,
+            fix_override: None,
+            follow: false,
+            live_status_enabled: false,
+            validation_enabled,
+            banner: false,
+            dictionary_watcher: None,
+            count_only: false,
+            msg_types: MsgTypeFilter::default(),
+            tag_usage: None,
+            tag_histogram: None,
+            split: None,
+            benchmark: None,
+            dict_cache: DictionaryCache::default(),
+            message_counts: HashMap::new(),
+            counts_dirty: false,
+            counts_layout: CountsLayout::default(),
+            validation_stats: ValidationStats::default(),
+            validation_summary: None,
+            clock_skew: None,
+            order_chains: None,
+            declared_versions: DeclaredVersions::default(),
+            input_range: InputRange::default(),
+            lines: LineDecoder::default(),
+            stdin_format: RecordFormat::Lines,
+            detected_delimiter: None,
+            passthrough: false,
+            raw_annotate: false,
+            hidden: HiddenBlocks::default(),
+            extract: ExtractSinks::default(),
+            report: None,
+            replay: None,
+            source: String::new(),
+            source_is_file: false,
+            file_prefix: None,
+            interrupted: interrupt_flag(),
+        };
+        handle_file(path, &mut ctx).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn stable_secret_aliases_match_across_files() {
+        let _lock = TEST_GUARD.lock().unwrap();
+        disable_output_colours();
+        let dir = tempfile::tempdir().unwrap();
+        let first = dir.path().join("a.log");
+        let second = dir.path().join("b.log");
+        let line = format!("8=FIX.4.4{SOH}9=010{SOH}35=D{SOH}1=ACC123{SOH}10=000{SOH}\n");
+        std::fs::write(
+            &first,
+            format!("8=FIX.4.4{SOH}9=010{SOH}35=D{SOH}1=OTHER{SOH}10=000{SOH}\n{line}"),
+        )
+        .unwrap();
+        std::fs::write(&second, &line).unwrap();
+        let first = first.to_string_lossy().into_owned();
+        let second = second.to_string_lossy().into_owned();
+
+        let obfuscator = fix::create_obfuscator(true).with_stable_key(Some("k1"));
+        let alias = obfuscator.obfuscate_line("1=ACC123");
+        let alias = alias.trim_start_matches("1=").to_string();
+        let out_a = decode_file_with(&obfuscator, &first);
+        let out_b = decode_file_with(&obfuscator, &second);
+        assert!(
+            out_a.contains(&alias),
+            "alias missing from first file: {out_a}"
+        );
+        assert!(
+            out_b.contains(&alias),
+            "alias missing from second file: {out_b}"
+        );
+        assert!(!out_a.contains("ACC123") && !out_b.contains("ACC123"));
+
+        let rekeyed = fix::create_obfuscator(true).with_stable_key(Some("k2"));
+        let out_c = decode_file_with(&rekeyed, &second);
+        assert!(
+            !out_c.contains(&alias),
+            "different key should give a different alias"
+        );
+    }
+
+    #[test]
+    fn summary_raw_messages_are_obfuscated_in_both_modes() {
+        let _lock = TEST_GUARD.lock().unwrap();
+        disable_output_colours();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("fills.log");
+        std::fs::write(
+            &path,
+            format!(
+                "8=FIX.4.4{SOH}9=010{SOH}35=8{SOH}1=SECRETACC{SOH}37=O1{SOH}39=2{SOH}10=000{SOH}\n"
+            ),
+        )
+        .unwrap();
+        let path = path.to_string_lossy().into_owned();
+        let obfuscator = fix::create_obfuscator(true);
+
+        for validation in [false, true] {
+            let mut summary = Some(OrderSummary::new('|').with_raw_messages(true));
+            let mut text = decode_file_with_summary(&obfuscator, &path, validation, &mut summary);
+            let mut rendered = Vec::new();
+            summary.unwrap().render(&mut rendered).unwrap();
+            text.push_str(&String::from_utf8(rendered).unwrap());
+            assert!(text.contains("Raw FIX messages"), "{text}");
+            assert!(text.contains("|37=O1|"), "{text}");
+            assert!(
+                !text.contains("SECRETACC"),
+                "validation={validation}: {text}"
+            );
+        }
+    }
+
+    #[test]
+    fn session_markers_flag_duplicates_and_late_original_times() {
+        let _lock = TEST_GUARD.lock().unwrap();
+        disable_output_colours();
+        let markers = |fields: &str| {
+            let msg = format!(
+                "8=FIX.4.4{SOH}35=8{SOH}{}10=000{SOH}",
+                fields.replace('|', &SOH.to_string())
+            );
+            session_markers(&ParsedMessage::parse(&msg))
+        };
+        assert!(markers("43=N|52=20250101-12:00:00|").is_empty());
+        assert_eq!(
+            markers("43=Y|97=Y|52=20250101-12:00:00|122=20250101-12:00:00.500|"),
+            vec!["PossDup", "PossResend", "OrigSendingTime after SendingTime"]
+        );
+        assert!(markers("52=20250101-12:00:01|122=20250101-12:00:00.500|").is_empty());
+
+        let msg = format!("8=FIX.4.4{SOH}35=0{SOH}43=Y{SOH}10=000{SOH}");
+        let dict = load_dictionary(&msg);
+        let parsed = ParsedMessage::parse(&msg);
+        let mut plain = Vec::new();
+        write_message_header(&mut plain, &parsed, &dict, None, false, None).unwrap();
+        assert_eq!(String::from_utf8(plain).unwrap().trim(), "[PossDup]");
+        let mut banner = Vec::new();
+        write_message_header(&mut banner, &parsed, &dict, None, true, None).unwrap();
+        let banner = String::from_utf8(banner).unwrap();
+        assert!(
+            banner.starts_with(">>") && banner.ends_with("  [PossDup]\n"),
+            "{banner}"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "dict-fix42")]
+    fn version_mismatch_warns_once_and_is_summarised() {
+        let _lock = TEST_GUARD.lock().unwrap();
+        disable_output_colours();
+        let heartbeat = |begin: &str| format!("8={begin}{SOH}9=005{SOH}35=0{SOH}10=000{SOH}\n");
+        let log = [
+            heartbeat("FIX.4.4"),
+            heartbeat("FIX.4.4"),
+            heartbeat("FIX.4.2"),
+        ]
+        .concat();
+        let obfuscator = fix::create_obfuscator(false);
+        let mut out = Vec::new();
+        let mut err = Vec::new();
+        let mut summary = None;
+        let mut ctx = PrettifyContext::new(&mut out, &mut err, &obfuscator, &mut summary);
+        ctx.validation_enabled = true;
+        ctx.fix_override = Some("FIX42");
+        stream_reader(
+            &mut BufReader::new(Cursor::new(log)),
+            0,
+            RecordFormat::Lines,
+            &mut ctx,
+        )
+        .unwrap();
+        finish_run(&mut ctx, false);
+
+        let warnings = String::from_utf8(err).unwrap();
+        assert_eq!(
+            warnings,
+            "warning: message declares FIX.4.4 but validated against FIX42 due to --fix override\n"
+        );
+        let output = String::from_utf8(out).unwrap();
+        let report = output.split("Declared versions:\n").nth(1).expect(&output);
+        let rows: Vec<Vec<&str>> = report
+            .lines()
+            .map(|line| line.split_whitespace().collect())
+            .collect();
+        assert_eq!(
+            rows,
+            vec![
+                vec!["FIX.4.2", "1", "message(s)"],
+                vec![
+                    "FIX.4.4",
+                    "2",
+                    "message(s),",
+                    "validated",
+                    "against",
+                    "FIX42"
+                ],
+            ]
+        );
+    }
+
+    #[test]
+    fn display_options_apply_only_to_the_render_they_are_given_to() {
+        let _lock = TEST_GUARD.lock().unwrap();
+        disable_output_colours();
+        let msg = format!("8=FIX.4.4{SOH}9=005{SOH}35=D{SOH}58=abcdefghij{SOH}10=999{SOH}");
+        let dict = load_dictionary(&msg);
+        let cut = DisplayOptions {
+            max_value_width: Some(4),
+            ..DISPLAY
+        };
+
+        let short = prettify_with_report(&msg, &dict, None, &HashSet::new(), &cut);
+        let full = prettify_with_report(&msg, &dict, None, &HashSet::new(), &DISPLAY);
+        assert!(short.contains("abcd… (+6 bytes)"), "{short}");
+        assert!(full.contains("abcdefghij"), "{full}");
     }
 
     #[test]
@@ -1302,7 +3910,7 @@ mod tests {
         let msg = format!("8=FIX.4.4{SOH}9=005{SOH}55=IBM{SOH}10=999{SOH}");
         let dict = load_dictionary(&msg);
 
-        let pretty = prettify_with_report(&msg, &dict, None);
+        let pretty = prettify_with_report(&msg, &dict, None, &HashSet::new(), &DISPLAY);
         let tags: Vec<u32> = pretty
             .lines()
             .filter_map(|line| line.split_whitespace().next())
@@ -1404,4 +4012,66 @@ mod tests {
         }
         out
     }
+
+    #[test]
+    fn wide_message_counts_add_share_bytes_average_and_totals_in_each_order() {
+        let _lock = TEST_GUARD.lock().unwrap();
+        disable_output_colours();
+        let counts: HashMap<String, MsgTypeCount> = [
+            ("0", "HEARTBEAT", 5, 300),
+            ("8", "EXECUTION_REPORT", 2, 500),
+            ("D", "ORDER_SINGLE", 3, 240),
+        ]
+        .into_iter()
+        .map(|(mt, label, count, bytes)| {
+            (
+                mt.to_string(),
+                MsgTypeCount {
+                    count,
+                    label: Some(label.to_string()),
+                    bytes,
+                },
+            )
+        })
+        .collect();
+        let render = |wide, sort| {
+            let mut out = Vec::new();
+            render_message_counts(&mut out, &counts, CountsLayout { wide, sort }).unwrap();
+            String::from_utf8(out).unwrap()
+        };
+        let codes = |text: &str| -> Vec<String> {
+            text.lines()
+                .skip(1)
+                .filter_map(|line| line.split_whitespace().next().map(str::to_string))
+                .collect()
+        };
+
+        let compact = render(false, CountsSort::Code);
+        assert_eq!(
+            compact,
+            "Message Type                 Count:\n\
+             \x20 0     (HEARTBEAT)               5\n\
+             \x20 8     (EXECUTION_REPORT)        2\n\
+             \x20 D     (ORDER_SINGLE)            3\n"
+        );
+        let wide = render(true, CountsSort::Code);
+        assert_eq!(
+            wide,
+            "Message Type                 Count:   Share:      Bytes:      Avg:\n\
+             \x20 0     (HEARTBEAT)               5    50.0%         300      60.0\n\
+             \x20 8     (EXECUTION_REPORT)        2    20.0%         500     250.0\n\
+             \x20 D     (ORDER_SINGLE)            3    30.0%         240      80.0\n\
+             \x20 Total                          10   100.0%        1040     104.0\n"
+        );
+        assert_eq!(codes(&wide), ["0", "8", "D", "Total"]);
+        assert_eq!(
+            codes(&render(true, CountsSort::Count)),
+            ["0", "D", "8", "Total"]
+        );
+        assert_eq!(
+            codes(&render(true, CountsSort::Bytes)),
+            ["8", "0", "D", "Total"]
+        );
+        assert_eq!(codes(&render(false, CountsSort::Count)), ["0", "D", "8"]);
+    }
 }
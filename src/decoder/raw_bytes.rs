@@ -0,0 +1,122 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// SPDX-FileCopyrightText: 2025 Steve Clarke <stephenlclarke@mac.com> - https://xyzzy.tools
+
+//! Log lines are decoded as text, but FIX values are bytes: EncodedText(355) may hold
+//! Shift_JIS and RawData(96) anything at all.  Bytes that are not valid UTF-8 are
+//! carried through the text as one private-use character each, rather than replaced,
+//! so CheckSum, BodyLength, data lengths and encoded-text decoding still see the bytes
+//! as they were sent, and output shows them as `\xNN`.
+
+use std::borrow::Cow;
+
+/// `U+10FF80` to `U+10FFFF` stand for the bytes `0x80` to `0xFF`; a byte below `0x80`
+/// is always valid UTF-8 on its own.  Text that genuinely uses the top of plane 16's
+/// private-use area is read back as those bytes.
+const BYTE_BASE: u32 = 0x10_FF00;
+
+/// `raw` as text, with each byte that is not part of valid UTF-8 kept as a stand-in
+/// character.
+pub fn decode(raw: &[u8]) -> Cow<'_, str> {
+    if let Ok(text) = std::str::from_utf8(raw) {
+        return Cow::Borrowed(text);
+    }
+    let mut text = String::with_capacity(raw.len() + 8);
+    for chunk in raw.utf8_chunks() {
+        text.push_str(chunk.valid());
+        text.extend(chunk.invalid().iter().map(|b| stand_in(*b)));
+    }
+    Cow::Owned(text)
+}
+
+/// The character standing for `byte`, which must be `0x80` or above.
+pub fn stand_in(byte: u8) -> char {
+    char::from_u32(BYTE_BASE + u32::from(byte)).expect("stand-ins are scalar values")
+}
+
+/// The raw byte a stand-in character represents.
+pub fn byte_of(c: char) -> Option<u8> {
+    let code = u32::from(c);
+    (BYTE_BASE + 0x80..=BYTE_BASE + 0xFF)
+        .contains(&code)
+        .then(|| (code - BYTE_BASE) as u8)
+}
+
+/// `text` as the bytes it was read from.
+pub fn to_bytes(text: &str) -> Cow<'_, [u8]> {
+    if !text.chars().any(|c| byte_of(c).is_some()) {
+        return Cow::Borrowed(text.as_bytes());
+    }
+    let mut bytes = Vec::with_capacity(text.len());
+    let mut buf = [0u8; 4];
+    for c in text.chars() {
+        match byte_of(c) {
+            Some(byte) => bytes.push(byte),
+            None => bytes.extend_from_slice(c.encode_utf8(&mut buf).as_bytes()),
+        }
+    }
+    Cow::Owned(bytes)
+}
+
+/// Length in bytes of what `text` was read from.
+pub fn byte_len(text: &str) -> usize {
+    text.len() - 3 * text.chars().filter(|c| byte_of(*c).is_some()).count()
+}
+
+/// FIX CheckSum(10) of what `text` was read from: its byte sum modulo 256, counting
+/// each stand-in as the one byte it replaced.
+pub fn checksum(text: &str) -> u32 {
+    checksum_bytes(&to_bytes(text))
+}
+
+/// FIX CheckSum(10) of `bytes`.
+pub fn checksum_bytes(bytes: &[u8]) -> u32 {
+    bytes.iter().map(|b| u32::from(*b)).sum::<u32>() % 256
+}
+
+/// How much of `text` holds its first `len` bytes as read, or `None` when `text` is
+/// shorter or the count ends partway through a character.
+pub fn text_len(text: &str, len: usize) -> Option<usize> {
+    let mut read = 0;
+    for (idx, c) in text.char_indices() {
+        if read == len {
+            return Some(idx);
+        }
+        read += byte_of(c).map_or(c.len_utf8(), |_| 1);
+        if read > len {
+            return None;
+        }
+    }
+    (read == len).then_some(text.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn invalid_utf8_survives_the_round_trip() {
+        assert!(matches!(decode(b"35=0\x01"), Cow::Borrowed("35=0\x01")));
+
+        // "Tokyo" in Shift_JIS, between valid UTF-8 on either side.
+        let raw = b"58=\x93\x8c\x8b\x9e \xc3\xa9\x01";
+        let text = decode(raw);
+        assert_eq!(text.chars().filter(|c| byte_of(*c).is_some()).count(), 4);
+        assert!(text.ends_with(" é\u{1}"));
+        assert_eq!(to_bytes(&text).as_ref(), raw);
+        assert_eq!(byte_len(&text), raw.len());
+        assert_eq!(text_len(&text, 3), Some(3));
+        assert_eq!(text_len(&text, 7), Some(3 + 4 * 4));
+        assert_eq!(text_len(&text, 9), None, "inside the two bytes of é");
+        assert_eq!(text_len(&text, raw.len()), Some(text.len()));
+        assert_eq!(text_len(&text, raw.len() + 1), None);
+        assert_eq!(byte_of('A'), None);
+        assert_eq!(byte_of(stand_in(0xFF)), Some(0xFF));
+        let sum = raw.iter().map(|b| u32::from(*b)).sum::<u32>() % 256;
+        assert_eq!(checksum(&text), sum);
+        assert_ne!(
+            checksum(&text),
+            checksum_bytes(text.as_bytes()),
+            "stand-ins count as one byte"
+        );
+    }
+}
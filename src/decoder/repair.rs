@@ -0,0 +1,215 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// SPDX-FileCopyrightText: 2025 Steve Clarke <stephenlclarke@mac.com> - https://xyzzy.tools
+
+//! `--repair`: recompute BodyLength(9) and CheckSum(10) for messages that were edited
+//! by hand and write them back out raw.  Only those two fields are rewritten, or
+//! inserted where they belong when missing; every other byte passes through as read.
+
+use crate::decoder::raw_bytes;
+use crate::decoder::stream::begin_string_delimiter;
+use std::io::{self, BufRead, Write};
+
+const SOH: u8 = 0x01;
+
+/// How many messages `--repair` had to fix and how many were already correct.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct RepairStats {
+    pub repaired: usize,
+    pub correct: usize,
+}
+
+/// Rebuild `msg` (SOH-delimited, starting at `8=`) with a BodyLength matching the
+/// bytes between it and the CheckSum, and a CheckSum matching everything before it.
+/// BodyLength goes straight after BeginString and CheckSum at the end when missing;
+/// anything after the last CheckSum field is dropped.  Returns the message and
+/// whether it differed from the input.
+pub fn repair_message(msg: &[u8]) -> (Vec<u8>, bool) {
+    let mut fields: Vec<&[u8]> = msg.split(|b| *b == SOH).collect();
+    if fields.last().is_some_and(|field| field.is_empty()) {
+        fields.pop();
+    }
+    let Some((begin, rest)) = fields.split_first() else {
+        return (msg.to_vec(), false);
+    };
+    let rest = match rest.first() {
+        Some(field) if field.starts_with(b"9=") => &rest[1..],
+        _ => rest,
+    };
+    let checksum = rest.iter().rposition(|field| field.starts_with(b"10="));
+    let body = &rest[..checksum.unwrap_or(rest.len())];
+
+    let mut repaired = Vec::with_capacity(msg.len() + 16);
+    repaired.extend_from_slice(begin);
+    repaired.push(SOH);
+    let body_len: usize = body.iter().map(|field| field.len() + 1).sum();
+    write!(repaired, "9={body_len}").expect("writing to a Vec cannot fail");
+    repaired.push(SOH);
+    for field in body {
+        repaired.extend_from_slice(field);
+        repaired.push(SOH);
+    }
+    let sum = raw_bytes::checksum_bytes(&repaired);
+    write!(repaired, "10={sum:03}").expect("writing to a Vec cannot fail");
+    repaired.push(SOH);
+
+    // Compare with the input as far as its CheckSum, with the terminating SOH that
+    // hand-edited messages often lose.
+    let kept = checksum.map_or(fields.len(), |idx| fields.len() - rest.len() + idx + 1);
+    let original: Vec<u8> = fields[..kept]
+        .iter()
+        .flat_map(|field| field.iter().copied().chain([SOH]))
+        .collect();
+    let changed = original != repaired;
+    (repaired, changed)
+}
+
+/// Repair every message in `reader`, writing each on its own line with SOH shown as
+/// `delimiter`.  Lines without a message are skipped.
+pub fn repair_stream<R: BufRead>(
+    reader: &mut R,
+    delimiter: char,
+    out: &mut dyn Write,
+    stats: &mut RepairStats,
+) -> io::Result<()> {
+    let mut line = Vec::new();
+    let mut encoded = [0u8; 4];
+    let delimiter = delimiter.encode_utf8(&mut encoded).as_bytes();
+    loop {
+        line.clear();
+        if reader.read_until(b'\n', &mut line)? == 0 {
+            return Ok(());
+        }
+        for message in split_messages(&line) {
+            let (repaired, changed) = repair_message(&message);
+            if changed {
+                stats.repaired += 1;
+            } else {
+                stats.correct += 1;
+            }
+            for chunk in repaired.split_inclusive(|b| *b == SOH) {
+                match chunk.split_last() {
+                    Some((&SOH, field)) => {
+                        out.write_all(field)?;
+                        out.write_all(delimiter)?;
+                    }
+                    _ => out.write_all(chunk)?,
+                }
+            }
+            out.write_all(b"\n")?;
+        }
+    }
+}
+
+/// Cut a line into messages at each `8=FIX` that is not the tail of a longer tag
+/// number.  BodyLength cannot be trusted to find the ends, so each message runs to
+/// the next one or the end of the line.  Messages whose SOH bytes were replaced
+/// with a printable delimiter are converted back to SOH.
+fn split_messages(line: &[u8]) -> Vec<Vec<u8>> {
+    let starts: Vec<usize> = (0..line.len())
+        .filter(|&i| line[i..].starts_with(b"8=FIX") && !(i > 0 && line[i - 1].is_ascii_digit()))
+        .collect();
+    starts
+        .iter()
+        .enumerate()
+        .map(|(n, &start)| {
+            let end = starts.get(n + 1).copied().unwrap_or(line.len());
+            let text = line[start..end].trim_ascii_end();
+            match wire_delimiter(text) {
+                Some(delim) => text
+                    .iter()
+                    .map(|&b| if b == delim { SOH } else { b })
+                    .collect(),
+                None => text.to_vec(),
+            }
+        })
+        .collect()
+}
+
+/// The substitute delimiter of a message without SOH bytes, as the decoder finds it
+/// after the BeginString value.  BodyLength may be the field missing, so it is not
+/// required to follow.
+fn wire_delimiter(text: &[u8]) -> Option<u8> {
+    if text.contains(&SOH) {
+        return None;
+    }
+    let delim = begin_string_delimiter(&raw_bytes::decode(text))?;
+    u8::try_from(delim).ok()
+}
+
+/// `body` (SOH-terminated fields from MsgType on) behind BeginString `begin`, with the
+/// BodyLength and CheckSum of the bytes it stands for, for tests that need a valid
+/// message.
+#[cfg(test)]
+pub(crate) fn sealed_message(begin: &str, body: &str) -> String {
+    let head = format!(
+        "8={begin}\u{0001}9={}\u{0001}{body}",
+        raw_bytes::byte_len(body)
+    );
+    format!("{head}10={:03}\u{0001}", raw_bytes::checksum(&head))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decoder::tag_lookup::load_dictionary;
+    use crate::decoder::validator::validate_fix_message;
+    use std::io::Cursor;
+
+    fn valid_heartbeat() -> String {
+        sealed_message(
+            "FIX.4.4",
+            "35=0\u{0001}49=AAA\u{0001}56=BBB\u{0001}34=7\u{0001}52=20240101-00:00:00\u{0001}",
+        )
+    }
+
+    #[test]
+    fn mangled_value_lengths_are_repaired_into_valid_messages() {
+        let valid = valid_heartbeat();
+        let (same, changed) = repair_message(valid.as_bytes());
+        assert!(!changed);
+        assert_eq!(same, valid.as_bytes());
+
+        let mangled = valid.replace("49=AAA", "49=AAAAAA");
+        let dict = load_dictionary(&mangled);
+        assert!(!validate_fix_message(&mangled, &dict).is_clean());
+        let (repaired, changed) = repair_message(mangled.as_bytes());
+        let repaired = String::from_utf8(repaired).unwrap();
+        assert!(changed);
+        assert!(repaired.contains("\u{0001}49=AAAAAA\u{0001}"));
+        assert!(validate_fix_message(&repaired, &dict).is_clean());
+    }
+
+    #[test]
+    fn missing_length_and_checksum_are_inserted_in_place() {
+        let valid = valid_heartbeat();
+        let body_start = valid.find("35=").unwrap();
+        let checksum_start = valid.find("10=").unwrap();
+        let bare =
+            format!("8=FIX.4.4|{}", &valid[body_start..checksum_start]).replace('\u{1}', "|");
+        let (repaired, changed) = repair_message(&split_messages(bare.as_bytes())[0]);
+        assert!(changed);
+        assert_eq!(repaired, valid.as_bytes());
+    }
+
+    #[test]
+    fn stream_repair_uses_the_output_delimiter_and_counts_messages() {
+        let valid = valid_heartbeat();
+        let broken = valid.replace("34=7", "34=700");
+        let log = format!("IN {valid}\nnot fix\nOUT {broken} trailing\n");
+        let mut out = Vec::new();
+        let mut stats = RepairStats::default();
+        repair_stream(&mut Cursor::new(log), '|', &mut out, &mut stats).unwrap();
+        assert_eq!(
+            stats,
+            RepairStats {
+                repaired: 1,
+                correct: 1
+            }
+        );
+        let text = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0], valid.replace('\u{1}', "|"));
+        assert!(lines[1].starts_with("8=FIX.4.4|9=47|35=0|") && lines[1].contains("|34=700|"));
+    }
+}
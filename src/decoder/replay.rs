@@ -0,0 +1,148 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// SPDX-FileCopyrightText: 2025 Steve Clarke <stephenlclarke@mac.com> - https://xyzzy.tools
+
+//! `--replay[=SPEED]`: write the FIX messages of a log back out, raw and one per line,
+//! spaced as they were when logged so downstream consumers can be load-tested at a
+//! realistic (or scaled) pace.  The gap between two messages is the difference
+//! between their SendingTime(52) values, or their lines' `--line-timestamp` times,
+//! divided by SPEED.
+
+use crate::decoder::raw_bytes;
+use crate::decoder::summary::parse_fix_timestamp;
+use chrono::NaiveDateTime;
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+const SOH: char = '\u{0001}';
+
+/// How long a pause sleeps between looks at the interrupt flag, so Ctrl-C is not
+/// held up by a long gap in the log.
+const INTERRUPT_POLL: Duration = Duration::from_millis(20);
+
+pub struct Replayer<'a> {
+    out: &'a mut dyn Write,
+    /// 1.0 replays in real time, 2.0 twice as fast; 0 sends without pausing.
+    speed: f64,
+    /// The time of the last message sent that had one.
+    previous: Option<NaiveDateTime>,
+}
+
+impl<'a> Replayer<'a> {
+    pub fn new(out: &'a mut dyn Write, speed: f64) -> Self {
+        Self {
+            out,
+            speed,
+            previous: None,
+        }
+    }
+
+    /// Send `msg` once the gap between `time` and the previous message's time, scaled
+    /// by the speed, has passed.  A message without a time goes straight after the one
+    /// before it, and so does one logged earlier than it.  Nothing is sent once
+    /// `interrupted` is set, including when it is set during the pause.
+    pub fn send(
+        &mut self,
+        msg: &str,
+        time: Option<NaiveDateTime>,
+        interrupted: &AtomicBool,
+    ) -> io::Result<()> {
+        if let Some(gap) = self.gap_before(time) {
+            pause(gap, interrupted);
+        }
+        if interrupted.load(Ordering::Relaxed) {
+            return Ok(());
+        }
+        if time.is_some() {
+            self.previous = time;
+        }
+        self.out.write_all(&raw_bytes::to_bytes(msg))?;
+        self.out.write_all(b"\n")?;
+        self.out.flush()
+    }
+
+    fn gap_before(&self, time: Option<NaiveDateTime>) -> Option<Duration> {
+        if self.speed == 0.0 {
+            return None;
+        }
+        let elapsed = (time? - self.previous?).to_std().ok()?;
+        // A speed close to zero stretches the gap past what a Duration holds.
+        Some(
+            Duration::try_from_secs_f64(elapsed.as_secs_f64() / self.speed)
+                .unwrap_or(Duration::MAX),
+        )
+    }
+}
+
+/// SendingTime(52) of an SOH-delimited message.
+pub fn sending_time(msg: &str) -> Option<NaiveDateTime> {
+    msg.split(SOH)
+        .find_map(|field| field.strip_prefix("52="))
+        .and_then(parse_fix_timestamp)
+}
+
+fn pause(gap: Duration, interrupted: &AtomicBool) {
+    // A gap too long to add to the clock lasts until interrupted.
+    let until = Instant::now().checked_add(gap);
+    while !interrupted.load(Ordering::Relaxed) {
+        let left = until.map_or(gap, |until| until.saturating_duration_since(Instant::now()));
+        if left.is_zero() {
+            return;
+        }
+        std::thread::sleep(left.min(INTERRUPT_POLL));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(time: &str) -> Option<NaiveDateTime> {
+        parse_fix_timestamp(time)
+    }
+
+    #[test]
+    fn gaps_follow_the_logged_times_scaled_by_speed() {
+        let mut out = Vec::new();
+        let mut replayer = Replayer::new(&mut out, 2.0);
+        replayer.previous = at("20240101-09:00:00.000");
+        assert_eq!(
+            replayer.gap_before(at("20240101-09:00:01.000")),
+            Some(Duration::from_millis(500))
+        );
+        assert_eq!(replayer.gap_before(None), None);
+        assert_eq!(replayer.gap_before(at("20240101-08:59:59.000")), None);
+        replayer.speed = 1e-30;
+        assert_eq!(
+            replayer.gap_before(at("20240101-09:00:01.000")),
+            Some(Duration::MAX)
+        );
+        replayer.speed = 0.0;
+        assert_eq!(replayer.gap_before(at("20240101-09:00:01.000")), None);
+    }
+
+    #[test]
+    fn untimed_messages_keep_the_last_time_and_interrupts_stop_sending() {
+        let mut out = Vec::new();
+        let flag = AtomicBool::new(false);
+        let mut replayer = Replayer::new(&mut out, 1.0);
+        let heartbeat = "8=FIX.4.4\u{1}35=0\u{1}52=20240101-09:00:00\u{1}10=000\u{1}";
+        replayer
+            .send(heartbeat, sending_time(heartbeat), &flag)
+            .unwrap();
+        replayer
+            .send("8=FIX.4.4\u{1}35=0\u{1}", None, &flag)
+            .unwrap();
+        assert_eq!(replayer.previous, at("20240101-09:00:00"));
+
+        // An hour's gap, cut short by the interrupt.
+        flag.store(true, Ordering::Relaxed);
+        replayer
+            .send(heartbeat, at("20240101-10:00:00"), &flag)
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            format!("{heartbeat}\n8=FIX.4.4\u{1}35=0\u{1}\n")
+        );
+    }
+}
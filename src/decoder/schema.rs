@@ -5,16 +5,19 @@
 //! The code leans on serde for XML parsing, then uses a custom builder to
 //! produce the immutable tree consumed by the CLI and renderers.
 
+use crate::decoder::warnings;
 use anyhow::{Context, anyhow};
 use rayon::prelude::*;
 use roxmltree::{Document, Node};
-use serde::Deserialize;
+use serde::ser::{SerializeStruct, Serializer};
+use serde::{Deserialize, Serialize};
 use std::{
-    collections::{BTreeMap, HashMap},
-    sync::Arc,
+    borrow::Cow,
+    collections::{BTreeMap, BTreeSet, HashMap},
+    sync::{Arc, OnceLock},
 };
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 #[serde(rename = "fix")]
 pub struct FixDictionary {
     #[serde(rename = "@type", default)]
@@ -39,19 +42,37 @@ pub struct FixDictionary {
 
 impl FixDictionary {
     pub fn from_xml(xml: &str) -> anyhow::Result<Self> {
-        let doc = Document::parse(xml)?;
+        Self::from_xml_with(xml, false)
+    }
+
+    /// [`FixDictionary::from_xml`], except that with `allow_partial` a dictionary whose
+    /// only missing section is `<components>` is read with no components rather than
+    /// refused (`--allow-partial-dict`).  Malformed XML is reported with the line,
+    /// column and byte offset of the error, the section it was found in and how many
+    /// entries each section had by then, which is usually enough to tell a file cut
+    /// short by a failed download.
+    pub fn from_xml_with(xml: &str, allow_partial: bool) -> anyhow::Result<Self> {
+        let doc = Document::parse(xml).map_err(|err| malformed_xml(xml, &err))?;
         let root = doc.root_element();
 
-        let fields_node =
-            find_child(root, "fields").ok_or_else(|| anyhow!("missing <fields> section"))?;
-        let messages_node =
-            find_child(root, "messages").ok_or_else(|| anyhow!("missing <messages> section"))?;
-        let components_node = find_child(root, "components")
-            .ok_or_else(|| anyhow!("missing <components> section"))?;
-        let header_node =
-            find_child(root, "header").ok_or_else(|| anyhow!("missing <header> section"))?;
-        let trailer_node =
-            find_child(root, "trailer").ok_or_else(|| anyhow!("missing <trailer> section"))?;
+        let missing: Vec<&str> = SECTIONS
+            .iter()
+            .copied()
+            .filter(|name| find_child(root, name).is_none())
+            .collect();
+        let partial = allow_partial && missing == ["components"];
+        if !missing.is_empty() && !partial {
+            return Err(missing_sections(xml, &missing));
+        }
+        let section = |name: &str| find_child(root, name).expect("checked present above");
+        let fields_node = section("fields");
+        let messages_node = section("messages");
+        let header_node = section("header");
+        let trailer_node = section("trailer");
+        let components = match find_child(root, "components") {
+            Some(node) => parse_components(node)?,
+            None => Vec::new(),
+        };
 
         Ok(FixDictionary {
             typ: root.attribute("type").unwrap_or("FIX").to_string(),
@@ -67,13 +88,321 @@ impl FixDictionary {
             messages: MessageContainer {
                 items: parse_messages(messages_node)?,
             },
-            components: ComponentContainer {
-                items: parse_components(components_node)?,
-            },
+            components: ComponentContainer { items: components },
             header: parse_component_def(header_node, false)?,
             trailer: parse_component_def(trailer_node, false)?,
         })
     }
+
+    /// Serialise the dictionary back to QuickFIX-style XML (`--export-xml`).  Fields
+    /// are sorted by number and messages by MsgType; components keep their order and
+    /// enums keep whichever layout (inline `<value>` or a `<values>` wrapper) they
+    /// were read with, so [`FixDictionary::from_xml`] reads back the same dictionary.
+    pub fn to_xml(&self) -> String {
+        let mut out = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        out.push_str(&format!(
+            "<fix type=\"{}\" major=\"{}\" minor=\"{}\"",
+            escape_xml(&self.typ),
+            escape_xml(&self.major),
+            escape_xml(&self.minor)
+        ));
+        if let Some(sp) = &self.service_pack {
+            out.push_str(&format!(" servicepack=\"{}\"", escape_xml(sp)));
+        }
+        out.push_str(">\n");
+
+        write_component_xml(&mut out, "header", &self.header, 1);
+        write_component_xml(&mut out, "trailer", &self.trailer, 1);
+
+        let mut messages: Vec<&Message> = self.messages.items.iter().collect();
+        messages.sort_by(|a, b| a.msg_type.cmp(&b.msg_type));
+        out.push_str("  <messages>\n");
+        for msg in messages {
+            out.push_str(&format!(
+                "    <message name=\"{}\" msgtype=\"{}\" msgcat=\"{}\">\n",
+                escape_xml(&msg.name),
+                escape_xml(&msg.msg_type),
+                escape_xml(&msg.msg_cat)
+            ));
+            write_members_xml(&mut out, &msg.fields, &msg.groups, &msg.components, 3);
+            out.push_str("    </message>\n");
+        }
+        out.push_str("  </messages>\n");
+
+        out.push_str("  <components>\n");
+        for comp in &self.components.items {
+            write_component_xml(&mut out, "component", comp, 2);
+        }
+        out.push_str("  </components>\n");
+
+        let mut fields: Vec<&Field> = self.fields.items.iter().collect();
+        fields.sort_by_key(|field| field.number);
+        out.push_str("  <fields>\n");
+        for field in fields {
+            write_field_xml(&mut out, field);
+        }
+        out.push_str("  </fields>\n");
+        out.push_str("</fix>\n");
+        out
+    }
+}
+
+fn escape_xml(text: &str) -> Cow<'_, str> {
+    if !text.contains(['&', '<', '>', '"', '\'']) {
+        return Cow::Borrowed(text);
+    }
+    let mut out = String::with_capacity(text.len() + 8);
+    for ch in text.chars() {
+        match ch {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            _ => out.push(ch),
+        }
+    }
+    Cow::Owned(out)
+}
+
+fn required_attr(required: Option<&String>) -> String {
+    required
+        .map(|value| format!(" required=\"{}\"", escape_xml(value)))
+        .unwrap_or_default()
+}
+
+fn write_field_xml(out: &mut String, field: &Field) {
+    out.push_str(&format!(
+        "    <field number=\"{}\" name=\"{}\" type=\"{}\"",
+        field.number,
+        escape_xml(&field.name),
+        escape_xml(&field.field_type)
+    ));
+    if field.values.is_empty() && field.values_wrapper.value.is_empty() {
+        out.push_str("/>\n");
+        return;
+    }
+    out.push_str(">\n");
+    let write_value = |out: &mut String, value: &Value, depth: usize| {
+        out.push_str(&format!(
+            "{}<value enum=\"{}\" description=\"{}\"/>\n",
+            "  ".repeat(depth),
+            escape_xml(&value.enumeration),
+            escape_xml(&value.description)
+        ));
+    };
+    for value in &field.values {
+        write_value(out, value, 3);
+    }
+    if !field.values_wrapper.value.is_empty() {
+        out.push_str("      <values>\n");
+        for value in &field.values_wrapper.value {
+            write_value(out, value, 4);
+        }
+        out.push_str("      </values>\n");
+    }
+    out.push_str("    </field>\n");
+}
+
+fn write_component_xml(out: &mut String, tag: &str, comp: &ComponentDef, depth: usize) {
+    let pad = "  ".repeat(depth);
+    if comp.name.is_empty() {
+        out.push_str(&format!("{pad}<{tag}>\n"));
+    } else {
+        out.push_str(&format!(
+            "{pad}<{tag} name=\"{}\">\n",
+            escape_xml(&comp.name)
+        ));
+    }
+    write_members_xml(out, &comp.fields, &comp.groups, &comp.components, depth + 1);
+    out.push_str(&format!("{pad}</{tag}>\n"));
+}
+
+fn write_members_xml(
+    out: &mut String,
+    fields: &[FieldRef],
+    groups: &[GroupDef],
+    components: &[ComponentRef],
+    depth: usize,
+) {
+    let pad = "  ".repeat(depth);
+    for field in fields {
+        out.push_str(&format!(
+            "{pad}<field name=\"{}\"{}/>\n",
+            escape_xml(&field.name),
+            required_attr(field.required.as_ref())
+        ));
+    }
+    for group in groups {
+        out.push_str(&format!(
+            "{pad}<group name=\"{}\"{}>\n",
+            escape_xml(&group.name),
+            required_attr(group.required.as_ref())
+        ));
+        write_members_xml(
+            out,
+            &group.fields,
+            &group.groups,
+            &group.components,
+            depth + 1,
+        );
+        out.push_str(&format!("{pad}</group>\n"));
+    }
+    for comp in components {
+        out.push_str(&format!(
+            "{pad}<component name=\"{}\"{}/>\n",
+            escape_xml(&comp.name),
+            required_attr(comp._required.as_ref())
+        ));
+    }
+}
+
+/// The top-level sections of a QuickFIX dictionary.
+const SECTIONS: [&str; 5] = ["fields", "messages", "components", "header", "trailer"];
+
+/// A top-level section as far as a scan of the XML text got.
+#[derive(Debug, PartialEq, Eq)]
+struct SectionScan {
+    name: String,
+    /// Complete child elements: fields, messages, components and so on.
+    entries: usize,
+    closed: bool,
+}
+
+/// The top-level sections in `xml`, in file order, found by following tags rather than
+/// parsing, so it works on text cut off partway through an element.
+fn scan_sections(xml: &str) -> Vec<SectionScan> {
+    let mut sections: Vec<SectionScan> = Vec::new();
+    let mut depth = 0usize;
+    let mut rest = xml;
+    while let Some(at) = rest.find('<') {
+        rest = &rest[at..];
+        let end_marker = if rest.starts_with("<!--") {
+            "-->"
+        } else if rest.starts_with("<![CDATA[") {
+            "]]>"
+        } else {
+            ">"
+        };
+        let Some(end) = rest.find(end_marker) else {
+            break;
+        };
+        let tag = &rest[1..end];
+        rest = &rest[end + end_marker.len()..];
+        if tag.starts_with(['?', '!']) {
+            continue;
+        }
+        if tag.starts_with('/') {
+            depth = depth.saturating_sub(1);
+            match (depth, sections.last_mut()) {
+                (1, Some(section)) => section.closed = true,
+                (2, Some(section)) => section.entries += 1,
+                _ => {}
+            }
+            continue;
+        }
+        let self_closing = tag.ends_with('/');
+        if depth == 1 {
+            let name = tag
+                .split(|c: char| c.is_whitespace() || c == '/')
+                .next()
+                .unwrap_or_default();
+            sections.push(SectionScan {
+                name: name.to_string(),
+                entries: 0,
+                closed: self_closing,
+            });
+        } else if depth == 2
+            && self_closing
+            && let Some(section) = sections.last_mut()
+        {
+            section.entries += 1;
+        }
+        if !self_closing {
+            depth += 1;
+        }
+    }
+    sections
+}
+
+/// e.g. `<header> 8 entries, <trailer> 3 entries`.
+fn describe_sections<'a>(sections: impl Iterator<Item = &'a SectionScan>) -> String {
+    let described: Vec<String> = sections
+        .map(|section| format!("<{}> {} entries", section.name, section.entries))
+        .collect();
+    described.join(", ")
+}
+
+/// Where `err` sits in `xml`, and the section the document had reached there.
+fn malformed_xml(xml: &str, err: &roxmltree::Error) -> anyhow::Error {
+    // roxmltree places errors it finds at the end of the input at 1:1.
+    let at_end = matches!(
+        err,
+        roxmltree::Error::UnexpectedEndOfStream | roxmltree::Error::UnclosedRootNode
+    );
+    let offset = if at_end {
+        xml.len()
+    } else {
+        let pos = err.pos();
+        let line_start: usize = xml
+            .split_inclusive('\n')
+            .take(pos.row.saturating_sub(1) as usize)
+            .map(str::len)
+            .sum();
+        xml[line_start..]
+            .char_indices()
+            .nth(pos.col.saturating_sub(1) as usize)
+            .map_or(xml.len(), |(idx, _)| line_start + idx)
+    };
+    let before = &xml[..offset];
+    let line = before.matches('\n').count() + 1;
+    let column = before
+        .rsplit('\n')
+        .next()
+        .unwrap_or_default()
+        .chars()
+        .count()
+        + 1;
+    let mut message =
+        format!("malformed XML at line {line}, column {column} (byte {offset}): {err}");
+    let sections = scan_sections(&xml[..offset]);
+    let (open, read): (Vec<&SectionScan>, Vec<&SectionScan>) =
+        sections.iter().partition(|section| !section.closed);
+    match open.first() {
+        Some(section) => message.push_str(&format!(
+            "; it stopped inside <{}> after {} complete entries",
+            section.name, section.entries
+        )),
+        None => message.push_str("; it stopped outside any section"),
+    }
+    if !read.is_empty() {
+        message.push_str(&format!(
+            " (sections read: {})",
+            describe_sections(read.into_iter())
+        ));
+    }
+    if xml[offset..].trim().is_empty() {
+        message.push_str("; the file looks truncated");
+    }
+    anyhow!(message)
+}
+
+/// The error for a well-formed dictionary without some of its sections.
+fn missing_sections(xml: &str, missing: &[&str]) -> anyhow::Error {
+    let names: Vec<String> = missing.iter().map(|name| format!("<{name}>")).collect();
+    let mut message = format!(
+        "missing {} section{}",
+        names.join(", "),
+        if missing.len() == 1 { "" } else { "s" }
+    );
+    let sections = scan_sections(xml);
+    if !sections.is_empty() {
+        message.push_str(&format!(" (found {})", describe_sections(sections.iter())));
+    }
+    if missing == ["components"] {
+        message.push_str("; use --allow-partial-dict to load it without components");
+    }
+    anyhow!(message)
 }
 
 fn find_child<'a, 'input>(node: Node<'a, 'input>, tag: &str) -> Option<Node<'a, 'input>> {
@@ -217,25 +546,40 @@ fn attr<'a, 'input>(node: Node<'a, 'input>, name: &str) -> anyhow::Result<String
         .ok_or_else(|| anyhow!("missing attribute @{name} on <{tag_name}>"))
 }
 
-#[derive(Debug, Clone, Deserialize, Default)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize, Default)]
 pub struct FieldContainer {
-    #[serde(rename = "field", default)]
+    #[serde(rename = "field", default, serialize_with = "serialize_field_items")]
     pub items: Vec<Field>,
 }
 
-#[derive(Debug, Clone, Deserialize, Default)]
+/// [`Field`] serialises to its `--json` shape, so the dictionary cache writes each one
+/// as its members in declaration order instead, which is what the derived
+/// `Deserialize` reads back from a non-self-describing format such as bincode.
+fn serialize_field_items<S: Serializer>(items: &[Field], serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_seq(items.iter().map(|field| {
+        (
+            &field.name,
+            field.number,
+            &field.field_type,
+            &field.values,
+            &field.values_wrapper,
+        )
+    }))
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize, Default)]
 pub struct MessageContainer {
     #[serde(rename = "message", default)]
     pub items: Vec<Message>,
 }
 
-#[derive(Debug, Clone, Deserialize, Default)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize, Default)]
 pub struct ComponentContainer {
     #[serde(rename = "component", default)]
     pub items: Vec<ComponentDef>,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize)]
 pub struct Field {
     #[serde(rename = "@name")]
     pub name: String,
@@ -255,13 +599,13 @@ impl Field {
     }
 }
 
-#[derive(Debug, Clone, Deserialize, Default)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize, Default)]
 pub struct ValuesWrapper {
     #[serde(rename = "value", default)]
     pub value: Vec<Value>,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct Value {
     #[serde(rename = "@enum")]
     pub enumeration: String,
@@ -269,7 +613,7 @@ pub struct Value {
     pub description: String,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct FieldRef {
     #[serde(rename = "@name")]
     pub name: String,
@@ -277,7 +621,7 @@ pub struct FieldRef {
     pub required: Option<String>,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct GroupDef {
     #[serde(rename = "@name")]
     pub name: String,
@@ -291,7 +635,7 @@ pub struct GroupDef {
     pub components: Vec<ComponentRef>,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct ComponentRef {
     #[serde(rename = "@name")]
     pub name: String,
@@ -299,7 +643,7 @@ pub struct ComponentRef {
     pub _required: Option<String>,
 }
 
-#[derive(Debug, Clone, Deserialize, Default)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize, Default)]
 pub struct ComponentDef {
     #[serde(rename = "@name", default)]
     pub name: String,
@@ -311,7 +655,7 @@ pub struct ComponentDef {
     pub components: Vec<ComponentRef>,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct Message {
     #[serde(rename = "@name")]
     pub name: String,
@@ -333,7 +677,53 @@ pub struct FieldNode {
     pub field: Arc<Field>,
 }
 
-#[derive(Debug, Clone)]
+/// One enum value as written by `--json`.
+#[derive(Serialize)]
+struct JsonEnumValue<'a> {
+    #[serde(rename = "enum")]
+    value: &'a str,
+    description: &'a str,
+}
+
+/// Write a field as `{name, number, type, [required,] values}` for `--json`, folding
+/// both XML layouts of its enum values into one list.
+fn serialize_field<S: Serializer>(
+    field: &Field,
+    required: Option<bool>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    let mut state = serializer.serialize_struct("Field", 5)?;
+    state.serialize_field("name", &field.name)?;
+    state.serialize_field("number", &field.number)?;
+    state.serialize_field("type", &field.field_type)?;
+    if let Some(required) = required {
+        state.serialize_field("required", &required)?;
+    }
+    let values: Vec<JsonEnumValue> = field
+        .values_iter()
+        .map(|v| JsonEnumValue {
+            value: &v.enumeration,
+            description: &v.description,
+        })
+        .collect();
+    state.serialize_field("values", &values)?;
+    state.end()
+}
+
+impl Serialize for Field {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_field(self, None, serializer)
+    }
+}
+
+/// The shared field is written inline, alongside the reference's required flag.
+impl Serialize for FieldNode {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_field(&self.field, Some(self.required), serializer)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
 pub struct ComponentNode {
     pub name: String,
     pub fields: Vec<FieldNode>,
@@ -341,7 +731,7 @@ pub struct ComponentNode {
     pub components: Vec<ComponentNode>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct GroupNode {
     pub name: String,
     pub required: bool,
@@ -350,7 +740,7 @@ pub struct GroupNode {
     pub groups: Vec<GroupNode>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct MessageNode {
     pub name: String,
     pub msg_type: String,
@@ -360,11 +750,37 @@ pub struct MessageNode {
     pub groups: Vec<GroupNode>,
 }
 
+/// Something that references a component, either directly or from inside one of its groups.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ComponentUser {
+    Message(String),
+    Component(String),
+}
+
+/// Where a field may legally appear, as reported by [`SchemaTree::field_usage`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FieldUsage {
+    pub header: bool,
+    pub trailer: bool,
+    pub messages: BTreeSet<String>,
+}
+
+impl FieldUsage {
+    pub fn is_empty(&self) -> bool {
+        !self.header && !self.trailer && self.messages.is_empty()
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct SchemaTree {
     pub fields: BTreeMap<String, Arc<Field>>,
     pub components: BTreeMap<String, ComponentNode>,
     pub messages: BTreeMap<String, MessageNode>,
+    /// Reverse index from component name to the messages and components that reference it.
+    pub component_usage: BTreeMap<String, BTreeSet<ComponentUser>>,
+    /// Reverse index from field number to where it appears; built on first use by
+    /// [`SchemaTree::field_usage`] so decoding never pays for it.
+    pub field_usage_index: OnceLock<HashMap<u32, FieldUsage>>,
     #[allow(dead_code)]
     pub version: String,
     pub service_pack: String,
@@ -392,6 +808,7 @@ impl SchemaTree {
         trailer.name = "Trailer".to_string();
         component_defs.insert(trailer.name.clone(), trailer);
 
+        let component_usage = build_component_usage(&dict.messages.items, &component_defs);
         let mut builder = ComponentBuilder::new(&field_map, &component_defs);
 
         let mut component_names: Vec<_> = component_defs.keys().cloned().collect();
@@ -420,6 +837,8 @@ impl SchemaTree {
             fields: field_map,
             components,
             messages,
+            component_usage,
+            field_usage_index: OnceLock::new(),
             version: format!("{} {}.{}", dict.typ, dict.major, dict.minor),
             service_pack,
         }
@@ -431,6 +850,213 @@ impl SchemaTree {
             .find(|f| f.number == number)
             .map(|arc| arc.as_ref())
     }
+
+    /// Where tag `number` can appear: the header, the trailer and every message that
+    /// carries it directly or through its components and groups.
+    pub fn field_usage(&self, number: u32) -> Option<&FieldUsage> {
+        self.field_usage_index
+            .get_or_init(|| self.build_field_usage())
+            .get(&number)
+    }
+
+    fn build_field_usage(&self) -> HashMap<u32, FieldUsage> {
+        let mut index: HashMap<u32, FieldUsage> = HashMap::new();
+        if let Some(header) = self.components.get("Header") {
+            self.visit_component_tags(header, &mut |tag| {
+                index.entry(tag).or_default().header = true;
+            });
+        }
+        if let Some(trailer) = self.components.get("Trailer") {
+            self.visit_component_tags(trailer, &mut |tag| {
+                index.entry(tag).or_default().trailer = true;
+            });
+        }
+        for msg in self.messages.values() {
+            self.visit_tags(&msg.fields, &msg.components, &msg.groups, &mut |tag| {
+                index
+                    .entry(tag)
+                    .or_default()
+                    .messages
+                    .insert(msg.name.clone());
+            });
+        }
+        index
+    }
+
+    fn visit_component_tags(&self, comp: &ComponentNode, visit: &mut dyn FnMut(u32)) {
+        self.visit_tags(&comp.fields, &comp.components, &comp.groups, visit);
+    }
+
+    fn visit_tags(
+        &self,
+        fields: &[FieldNode],
+        components: &[ComponentNode],
+        groups: &[GroupNode],
+        visit: &mut dyn FnMut(u32),
+    ) {
+        for node in fields {
+            visit(node.field.number);
+        }
+        for comp in components {
+            self.visit_component_tags(comp, visit);
+        }
+        for group in groups {
+            if let Some(count) = self.fields.get(&group.name) {
+                visit(count.number);
+            }
+            self.visit_tags(&group.fields, &group.components, &group.groups, visit);
+        }
+    }
+
+    /// Fields whose name matches `pattern` case-insensitively, ordered by tag number.
+    /// `*` matches any run of characters, so `*Px` or `Settl*` list a family of fields.
+    pub fn find_fields_by_name(&self, pattern: &str) -> Vec<&Field> {
+        let pattern = pattern.to_ascii_lowercase();
+        let mut matches: Vec<&Field> = self
+            .fields
+            .values()
+            .filter(|f| glob_matches(&pattern, &f.name.to_ascii_lowercase()))
+            .map(|arc| arc.as_ref())
+            .collect();
+        matches.sort_by_key(|f| f.number);
+        matches
+    }
+
+    /// Everything `--find` turns up for `text`, ignoring case: fields, messages and
+    /// components whose names contain it, and enum values whose code or description
+    /// does.  Fields and enums are ordered by tag number, the rest by name.
+    pub fn search(&self, text: &str) -> SchemaMatches<'_> {
+        let needle = text.to_ascii_lowercase();
+        let hit = |candidate: &str| candidate.to_ascii_lowercase().contains(&needle);
+        let mut by_number: Vec<&Field> = self.fields.values().map(|f| f.as_ref()).collect();
+        by_number.sort_by_key(|f| f.number);
+        let mut messages: Vec<&MessageNode> =
+            self.messages.values().filter(|m| hit(&m.name)).collect();
+        messages.sort_by(|a, b| a.name.cmp(&b.name));
+        SchemaMatches {
+            fields: by_number.iter().copied().filter(|f| hit(&f.name)).collect(),
+            messages,
+            components: self.components.values().filter(|c| hit(&c.name)).collect(),
+            enums: by_number
+                .iter()
+                .flat_map(|field| field.values_iter().map(move |value| (*field, value)))
+                .filter(|(_, value)| hit(&value.enumeration) || hit(&value.description))
+                .collect(),
+        }
+    }
+
+    /// Field names containing `query` (ignoring case and any `*`), used to suggest
+    /// alternatives when a name lookup finds nothing.
+    pub fn similar_field_names(&self, query: &str) -> Vec<&str> {
+        let needle = query.replace('*', "").to_ascii_lowercase();
+        if needle.is_empty() {
+            return Vec::new();
+        }
+        let mut names: Vec<&str> = self
+            .fields
+            .values()
+            .filter(|f| {
+                let name = f.name.to_ascii_lowercase();
+                name.contains(&needle) || needle.contains(&name)
+            })
+            .map(|f| f.name.as_str())
+            .collect();
+        names.sort_unstable();
+        names
+    }
+
+    /// Every chain of references that pulls `component` in, outermost first, e.g.
+    /// `["ExecutionReport", "Instrument", "SecAltIDGrp"]`.  Chains normally start at a
+    /// message; a chain starting at a component means nothing references that component.
+    /// Cyclic component references are cut rather than followed.
+    pub fn component_usage_paths(&self, component: &str) -> Vec<Vec<String>> {
+        let mut paths = Vec::new();
+        let mut trail = vec![component.to_string()];
+        self.collect_usage_paths(component, &mut trail, &mut paths);
+        paths.sort();
+        paths.dedup();
+        paths
+    }
+
+    fn collect_usage_paths(
+        &self,
+        name: &str,
+        trail: &mut Vec<String>,
+        paths: &mut Vec<Vec<String>>,
+    ) {
+        let Some(users) = self.component_usage.get(name) else {
+            return;
+        };
+        for user in users {
+            match user {
+                ComponentUser::Message(msg) => {
+                    paths.push(
+                        std::iter::once(msg)
+                            .chain(trail.iter().rev())
+                            .cloned()
+                            .collect(),
+                    );
+                }
+                ComponentUser::Component(parent) => {
+                    if trail.contains(parent) {
+                        continue;
+                    }
+                    trail.push(parent.clone());
+                    if self.component_usage.contains_key(parent) {
+                        self.collect_usage_paths(parent, trail, paths);
+                    } else {
+                        paths.push(trail.iter().rev().cloned().collect());
+                    }
+                    trail.pop();
+                }
+            }
+        }
+    }
+}
+
+/// Build the component reverse index from the raw definitions, counting references made
+/// from within repeating groups as references by the enclosing message or component.
+fn build_component_usage(
+    messages: &[Message],
+    component_defs: &HashMap<String, ComponentDef>,
+) -> BTreeMap<String, BTreeSet<ComponentUser>> {
+    let mut usage: BTreeMap<String, BTreeSet<ComponentUser>> = BTreeMap::new();
+    let mut refs = Vec::new();
+
+    for msg in messages {
+        refs.clear();
+        collect_component_refs(&msg.components, &msg.groups, &mut refs);
+        for name in &refs {
+            usage
+                .entry(name.to_string())
+                .or_default()
+                .insert(ComponentUser::Message(msg.name.clone()));
+        }
+    }
+
+    for comp in component_defs.values() {
+        refs.clear();
+        collect_component_refs(&comp.components, &comp.groups, &mut refs);
+        for name in &refs {
+            usage
+                .entry(name.to_string())
+                .or_default()
+                .insert(ComponentUser::Component(comp.name.clone()));
+        }
+    }
+
+    usage
+}
+
+fn collect_component_refs<'a>(
+    components: &'a [ComponentRef],
+    groups: &'a [GroupDef],
+    out: &mut Vec<&'a str>,
+) {
+    out.extend(components.iter().map(|cref| cref.name.as_str()));
+    for group in groups {
+        collect_component_refs(&group.components, &group.groups, out);
+    }
 }
 
 fn build_field_nodes(refs: &[FieldRef], fields: &BTreeMap<String, Arc<Field>>) -> Vec<FieldNode> {
@@ -474,7 +1100,11 @@ impl<'a> ComponentBuilder<'a> {
             return Some(node.clone());
         }
         if self.stack.contains(&name.to_string()) {
-            eprintln!("warning: recursive component detected at {name}, skipping");
+            warnings::warn(
+                "component-recursion",
+                name,
+                &format!("warning: recursive component detected at {name}, skipping"),
+            );
             return None;
         }
         let def = self.defs.get(name)?;
@@ -556,6 +1186,60 @@ fn build_message_node(
     node
 }
 
+/// The results of [`SchemaTree::search`].
+#[derive(Debug, Default)]
+pub struct SchemaMatches<'a> {
+    pub fields: Vec<&'a Field>,
+    pub messages: Vec<&'a MessageNode>,
+    pub components: Vec<&'a ComponentNode>,
+    pub enums: Vec<(&'a Field, &'a Value)>,
+}
+
+impl SchemaMatches<'_> {
+    pub fn len(&self) -> usize {
+        self.fields.len() + self.messages.len() + self.components.len() + self.enums.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Keep the first `limit` results, taking fields, then messages, components and
+    /// enums, in that order.
+    pub fn truncate(&mut self, limit: usize) {
+        let mut left = limit;
+        let mut keep = |len: usize| {
+            let kept = len.min(left);
+            left -= kept;
+            kept
+        };
+        self.fields.truncate(keep(self.fields.len()));
+        self.messages.truncate(keep(self.messages.len()));
+        self.components.truncate(keep(self.components.len()));
+        self.enums.truncate(keep(self.enums.len()));
+    }
+}
+
+/// Minimal glob matcher where `*` matches any (possibly empty) run of characters.
+fn glob_matches(pattern: &str, text: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = text.strip_prefix(first) else {
+        return false;
+    };
+    let parts: Vec<&str> = parts.collect();
+    let Some((last, middle)) = parts.split_last() else {
+        return rest.is_empty();
+    };
+    for part in middle {
+        match rest.find(part) {
+            Some(pos) => rest = &rest[pos + part.len()..],
+            None => return false,
+        }
+    }
+    rest.len() >= last.len() && rest.ends_with(last)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -600,4 +1284,236 @@ mod tests {
         assert_eq!(root.items[0].name, "one");
         assert_eq!(root.items[1].name, "two");
     }
+
+    const USAGE_XML: &str = r#"
+<fix type='FIX' major='4' minor='4'>
+  <header/>
+  <trailer/>
+  <messages>
+    <message name='ExecutionReport' msgtype='8' msgcat='app'>
+      <component name='Instrument' required='Y'/>
+    </message>
+    <message name='Loop' msgtype='L' msgcat='app'>
+      <component name='CycA'/>
+    </message>
+  </messages>
+  <components>
+    <component name='Instrument'>
+      <field name='Symbol'/>
+      <component name='SecAltIDGrp'/>
+    </component>
+    <component name='SecAltIDGrp'>
+      <group name='NoSecurityAltID'>
+        <field name='SecurityAltID'/>
+      </group>
+    </component>
+    <component name='CycA'>
+      <component name='CycB'/>
+    </component>
+    <component name='CycB'>
+      <component name='CycA'/>
+    </component>
+  </components>
+  <fields>
+    <field number='55' name='Symbol' type='STRING'/>
+    <field number='454' name='NoSecurityAltID' type='NUMINGROUP'/>
+    <field number='455' name='SecurityAltID' type='STRING'/>
+  </fields>
+</fix>
+"#;
+
+    #[test]
+    fn component_usage_records_direct_users() {
+        let schema = SchemaTree::build(FixDictionary::from_xml(USAGE_XML).unwrap());
+        let users = schema.component_usage.get("Instrument").unwrap();
+        assert!(users.contains(&ComponentUser::Message("ExecutionReport".into())));
+        let users = schema.component_usage.get("SecAltIDGrp").unwrap();
+        assert!(users.contains(&ComponentUser::Component("Instrument".into())));
+    }
+
+    #[test]
+    fn component_usage_paths_are_transitive_and_cycle_safe() {
+        let schema = SchemaTree::build(FixDictionary::from_xml(USAGE_XML).unwrap());
+        assert_eq!(
+            schema.component_usage_paths("SecAltIDGrp"),
+            vec![vec![
+                "ExecutionReport".to_string(),
+                "Instrument".to_string(),
+                "SecAltIDGrp".to_string()
+            ]]
+        );
+        assert_eq!(
+            schema.component_usage_paths("CycB"),
+            vec![vec![
+                "Loop".to_string(),
+                "CycA".to_string(),
+                "CycB".to_string()
+            ]]
+        );
+        assert!(schema.component_usage_paths("Unused").is_empty());
+    }
+
+    #[test]
+    fn message_serialises_to_json_with_fields_inline() {
+        let schema = SchemaTree::build(FixDictionary::from_xml(USAGE_XML).unwrap());
+        let json = serde_json::to_value(&schema.messages["ExecutionReport"]).unwrap();
+        assert_eq!(json["msg_type"], "8");
+        let instrument = &json["components"][0];
+        assert_eq!(instrument["name"], "Instrument");
+        assert_eq!(
+            instrument["fields"][0],
+            serde_json::json!({
+                "name": "Symbol",
+                "number": 55,
+                "type": "STRING",
+                "required": false,
+                "values": [],
+            })
+        );
+        let group = &instrument["components"][0]["groups"][0];
+        assert_eq!(group["name"], "NoSecurityAltID");
+        assert_eq!(group["fields"][0]["number"], 455);
+    }
+
+    #[test]
+    fn field_names_resolve_by_exact_name_and_glob() {
+        let schema = SchemaTree::build(FixDictionary::from_xml(USAGE_XML).unwrap());
+        let numbers = |pattern: &str| {
+            schema
+                .find_fields_by_name(pattern)
+                .iter()
+                .map(|f| f.number)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(numbers("symbol"), vec![55]);
+        assert_eq!(numbers("*SecurityAltID"), vec![454, 455]);
+        assert_eq!(numbers("Sec*"), vec![455]);
+        assert_eq!(numbers("*"), vec![55, 454, 455]);
+        assert!(numbers("Sym").is_empty());
+        assert_eq!(
+            schema.similar_field_names("altid"),
+            vec!["NoSecurityAltID", "SecurityAltID"]
+        );
+    }
+
+    #[test]
+    fn glob_handles_anchors_and_inner_wildcards() {
+        assert!(glob_matches("*px", "lastpx"));
+        assert!(glob_matches("settl*", "settldate"));
+        assert!(glob_matches("a*b*c", "axxbyyc"));
+        assert!(!glob_matches("a*b*c", "axxbyy"));
+        assert!(!glob_matches("ab*ba", "aba"));
+    }
+
+    #[test]
+    fn field_usage_walks_components_groups_and_header() {
+        let schema = SchemaTree::build(FixDictionary::from_xml(USAGE_XML).unwrap());
+        let usage = schema.field_usage(455).expect("SecurityAltID is used");
+        assert_eq!(
+            usage.messages.iter().collect::<Vec<_>>(),
+            vec!["ExecutionReport"]
+        );
+        assert!(!usage.header && !usage.trailer);
+        assert!(
+            schema.field_usage(454).is_some(),
+            "group count tags count too"
+        );
+
+        let xml = USAGE_XML.replace(
+            "<header/>",
+            "<header><field name='Symbol' required='N'/></header>",
+        );
+        let schema = SchemaTree::build(FixDictionary::from_xml(&xml).unwrap());
+        assert!(schema.field_usage(55).unwrap().header);
+        assert_eq!(schema.field_usage(9999), None);
+    }
+
+    #[test]
+    #[cfg(feature = "dict-fix42")]
+    fn exported_xml_round_trips_embedded_fix42() {
+        let dict = FixDictionary::from_xml(crate::fix::choose_embedded_xml("42").unwrap()).unwrap();
+        let xml = dict.to_xml();
+        let reparsed = FixDictionary::from_xml(&xml).expect("exported XML parses");
+
+        let mut expected = dict.clone();
+        expected.fields.items.sort_by_key(|field| field.number);
+        expected
+            .messages
+            .items
+            .sort_by(|a, b| a.msg_type.cmp(&b.msg_type));
+        assert_eq!(reparsed, expected);
+        assert_eq!(reparsed.to_xml(), xml);
+        assert!(xml.contains("<value enum=\"D\" description=\"ORDER_SINGLE\"/>"));
+    }
+
+    #[test]
+    fn exported_xml_escapes_attribute_values() {
+        let mut dict = FixDictionary::from_xml(USAGE_XML).unwrap();
+        dict.fields.items[0].values.push(Value {
+            enumeration: "<&>".into(),
+            description: "SAY_\"HI\"".into(),
+        });
+        let reparsed = FixDictionary::from_xml(&dict.to_xml()).unwrap();
+        let name = &dict.fields.items[0].name;
+        let field = reparsed.fields.items.iter().find(|f| &f.name == name);
+        let value = field.and_then(|f| f.values_iter().last());
+        assert_eq!(value.map(|v| v.enumeration.as_str()), Some("<&>"));
+        assert_eq!(value.map(|v| v.description.as_str()), Some("SAY_\"HI\""));
+    }
+
+    #[test]
+    fn truncated_xml_names_the_position_and_the_section_reached() {
+        let cut = USAGE_XML
+            .find("<component name='CycA'/>\n    </message>")
+            .unwrap();
+        let err = FixDictionary::from_xml(&USAGE_XML[..cut]).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "malformed XML at line 10, column 7 (byte {cut}): the root node was opened but never closed; \
+                 it stopped inside <messages> after 1 complete entries \
+                 (sections read: <header> 0 entries, <trailer> 0 entries); the file looks truncated"
+            )
+        );
+
+        let garbled = USAGE_XML.replacen("</component>", "</componnet>", 1);
+        let err = FixDictionary::from_xml(&garbled).unwrap_err().to_string();
+        assert!(
+            err.starts_with("malformed XML at line 17, column 5"),
+            "{err}"
+        );
+        assert!(
+            err.contains("inside <components> after 0 complete entries"),
+            "{err}"
+        );
+        assert!(!err.contains("truncated"), "{err}");
+    }
+
+    #[test]
+    fn a_missing_components_section_can_be_allowed() {
+        let start = USAGE_XML.find("  <components>").unwrap();
+        let end = USAGE_XML.find("  <fields>").unwrap();
+        let xml = format!("{}{}", &USAGE_XML[..start], &USAGE_XML[end..]);
+        let err = FixDictionary::from_xml(&xml).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "missing <components> section (found <header> 0 entries, <trailer> 0 entries, \
+             <messages> 2 entries, <fields> 3 entries); use --allow-partial-dict to load it \
+             without components"
+        );
+        let dict = FixDictionary::from_xml_with(&xml, true).expect("allowed without components");
+        assert!(dict.components.items.is_empty());
+        assert_eq!(dict.messages.items.len(), 2);
+
+        let no_fields = xml
+            .replace("<fields>", "<fieldz>")
+            .replace("</fields>", "</fieldz>");
+        let err = FixDictionary::from_xml_with(&no_fields, true).unwrap_err();
+        assert!(
+            err.to_string()
+                .starts_with("missing <fields>, <components> sections"),
+            "{err}"
+        );
+        assert!(!err.to_string().contains("--allow-partial-dict"));
+    }
 }
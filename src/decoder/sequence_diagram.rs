@@ -0,0 +1,280 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// SPDX-FileCopyrightText: 2025 Steve Clarke <stephenlclarke@mac.com> - https://xyzzy.tools
+
+//! `--sequence-diagram`: the messages of a run as a Mermaid `sequenceDiagram`, or a
+//! PlantUML one, ready to paste into an incident write-up.  Each SenderCompID(49) and
+//! TargetCompID(56) becomes a participant in order of appearance, and each message an
+//! arrow from its sender to its target labelled with its message name and whichever of
+//! ClOrdID(11), OrderID(37) and ExecID(17) it carries.  Messages without both CompIDs
+//! are left out.  Only the first `--diagram-limit` messages are drawn, so the diagram
+//! of a large log stays readable; a note at the end says how many more there were.
+
+use crate::decoder::fixparser::ParsedMessage;
+use crate::decoder::tag_lookup::load_label_dictionary;
+use std::collections::HashMap;
+use std::io::{self, Write};
+
+/// Messages drawn when `--diagram-limit` is not given.
+pub const DEFAULT_LIMIT: usize = 200;
+
+/// ClOrdID(11), OrderID(37) and ExecID(17), shown in each arrow's label when present.
+const KEY_TAGS: [u32; 3] = [11, 37, 17];
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DiagramFormat {
+    #[default]
+    Mermaid,
+    PlantUml,
+}
+
+impl DiagramFormat {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_ascii_lowercase().as_str() {
+            "mermaid" => Some(Self::Mermaid),
+            "plantuml" => Some(Self::PlantUml),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Arrow {
+    from: usize,
+    to: usize,
+    label: String,
+}
+
+/// The participants and arrows of a run, in the order they were seen.
+#[derive(Debug)]
+pub struct SequenceDiagram {
+    format: DiagramFormat,
+    limit: usize,
+    participants: Vec<String>,
+    arrows: Vec<Arrow>,
+    /// Message names by MsgType, resolved the first time each is seen.
+    names: HashMap<String, String>,
+    /// Messages past the limit.
+    omitted: usize,
+}
+
+impl SequenceDiagram {
+    pub fn new(format: DiagramFormat, limit: usize) -> Self {
+        Self {
+            format,
+            limit,
+            participants: Vec::new(),
+            arrows: Vec::new(),
+            names: HashMap::new(),
+            omitted: 0,
+        }
+    }
+
+    /// Add one message as an arrow from its SenderCompID(49) to its TargetCompID(56).
+    pub fn record(&mut self, msg: &ParsedMessage<'_>, fix_override: Option<&str>) {
+        let (Some(sender), Some(target)) = (msg.first(49), msg.first(56)) else {
+            return;
+        };
+        if self.arrows.len() >= self.limit {
+            self.omitted += 1;
+            return;
+        }
+        let msg_type = msg.first(35).unwrap_or("?");
+        let name = self
+            .names
+            .entry(msg_type.to_string())
+            .or_insert_with(|| {
+                let dict = load_label_dictionary(msg.raw, fix_override);
+                dict.message_def(msg_type)
+                    .map(|def| def.name.as_str())
+                    .or_else(|| dict.msg_type_label(msg_type))
+                    .unwrap_or(msg_type)
+                    .to_string()
+            })
+            .clone();
+        let ids: Vec<String> = KEY_TAGS
+            .iter()
+            .filter_map(|tag| msg.first(*tag).map(|value| format!("{tag}={value}")))
+            .collect();
+        let label = if ids.is_empty() {
+            name
+        } else {
+            format!("{name}({})", ids.join(", "))
+        };
+        let from = self.participant(sender);
+        let to = self.participant(target);
+        self.arrows.push(Arrow { from, to, label });
+    }
+
+    fn participant(&mut self, comp_id: &str) -> usize {
+        match self.participants.iter().position(|p| p == comp_id) {
+            Some(idx) => idx,
+            None => {
+                self.participants.push(comp_id.to_string());
+                self.participants.len() - 1
+            }
+        }
+    }
+
+    /// How participant `idx` is named in arrows: its CompID when that is a plain
+    /// identifier, or else a `P<n>` alias declared with the CompID as its label.
+    fn id(&self, idx: usize) -> String {
+        let comp_id = &self.participants[idx];
+        if is_identifier(comp_id) {
+            comp_id.clone()
+        } else {
+            format!("P{}", idx + 1)
+        }
+    }
+
+    pub fn render(&self, out: &mut dyn Write) -> io::Result<()> {
+        match self.format {
+            DiagramFormat::Mermaid => self.render_mermaid(out),
+            DiagramFormat::PlantUml => self.render_plantuml(out),
+        }
+    }
+
+    fn render_mermaid(&self, out: &mut dyn Write) -> io::Result<()> {
+        writeln!(out, "sequenceDiagram")?;
+        for (idx, comp_id) in self.participants.iter().enumerate() {
+            let id = self.id(idx);
+            if id == *comp_id {
+                writeln!(out, "    participant {id}")?;
+            } else {
+                writeln!(out, "    participant {id} as {}", mermaid_text(comp_id))?;
+            }
+        }
+        for arrow in &self.arrows {
+            writeln!(
+                out,
+                "    {}->>{}: {}",
+                self.id(arrow.from),
+                self.id(arrow.to),
+                mermaid_text(&arrow.label)
+            )?;
+        }
+        if let Some(over) = self.note_span() {
+            writeln!(out, "    Note over {over}: {}", self.omitted_note())?;
+        }
+        Ok(())
+    }
+
+    fn render_plantuml(&self, out: &mut dyn Write) -> io::Result<()> {
+        writeln!(out, "@startuml")?;
+        for (idx, comp_id) in self.participants.iter().enumerate() {
+            let id = self.id(idx);
+            if id == *comp_id {
+                writeln!(out, "participant {id}")?;
+            } else {
+                writeln!(out, "participant \"{comp_id}\" as {id}")?;
+            }
+        }
+        for arrow in &self.arrows {
+            writeln!(
+                out,
+                "{} -> {} : {}",
+                self.id(arrow.from),
+                self.id(arrow.to),
+                arrow.label
+            )?;
+        }
+        if let Some(over) = self.note_span() {
+            writeln!(
+                out,
+                "note over {} : {}",
+                over.replace(',', ", "),
+                self.omitted_note()
+            )?;
+        }
+        writeln!(out, "@enduml")
+    }
+
+    /// The participants a truncation note spans, when messages were left out.
+    fn note_span(&self) -> Option<String> {
+        if self.omitted == 0 || self.participants.is_empty() {
+            return None;
+        }
+        let last = self.participants.len() - 1;
+        Some(if last == 0 {
+            self.id(0)
+        } else {
+            format!("{},{}", self.id(0), self.id(last))
+        })
+    }
+
+    fn omitted_note(&self) -> String {
+        format!(
+            "{} more message(s) not shown (--diagram-limit {})",
+            self.omitted, self.limit
+        )
+    }
+}
+
+fn is_identifier(comp_id: &str) -> bool {
+    !comp_id.is_empty()
+        && comp_id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// `text` with the characters Mermaid reads as syntax in a message written as entity
+/// codes.
+fn mermaid_text(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '#' => escaped.push_str("#35;"),
+            ';' => escaped.push_str("#59;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(body: &str) -> String {
+        format!("8=FIX.4.4\u{1}9=0\u{1}{body}10=000\u{1}")
+    }
+
+    #[test]
+    fn comp_ids_that_are_not_identifiers_are_aliased_and_the_cap_is_noted() {
+        let mut diagram = SequenceDiagram::new(DiagramFormat::PlantUml, 2);
+        for body in [
+            "35=D\u{1}49=BUY-SIDE\u{1}56=VENUE\u{1}11=A;1\u{1}",
+            "35=8\u{1}49=VENUE\u{1}56=BUY-SIDE\u{1}37=E1\u{1}11=A;1\u{1}17=X1\u{1}",
+            "35=0\u{1}49=VENUE\u{1}56=BUY-SIDE\u{1}",
+            "35=0\u{1}49=VENUE\u{1}",
+        ] {
+            let raw = message(body);
+            diagram.record(&ParsedMessage::parse(&raw), None);
+        }
+        let mut out = Vec::new();
+        diagram.render(&mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "@startuml\n\
+             participant \"BUY-SIDE\" as P1\n\
+             participant VENUE\n\
+             P1 -> VENUE : NewOrderSingle(11=A;1)\n\
+             VENUE -> P1 : ExecutionReport(11=A;1, 37=E1, 17=X1)\n\
+             note over P1, VENUE : 1 more message(s) not shown (--diagram-limit 2)\n\
+             @enduml\n"
+        );
+
+        diagram.format = DiagramFormat::Mermaid;
+        let mut out = Vec::new();
+        diagram.render(&mut out).unwrap();
+        let mermaid = String::from_utf8(out).unwrap();
+        assert!(
+            mermaid.contains("    participant P1 as BUY-SIDE\n"),
+            "{mermaid}"
+        );
+        assert!(
+            mermaid.contains("    P1->>VENUE: NewOrderSingle(11=A#59;1)\n"),
+            "{mermaid}"
+        );
+        assert_eq!(mermaid_text("#;"), "#35;#59;");
+    }
+}
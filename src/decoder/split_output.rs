@@ -0,0 +1,233 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// SPDX-FileCopyrightText: 2025 Steve Clarke <stephenlclarke@mac.com> - https://xyzzy.tools
+
+//! `--split-output DIR`: decoded messages written to files instead of stdout, one file
+//! per message or, with `--split-mode=per-order`, one file per order holding the order's
+//! summary block followed by each of its messages.  Orders are keyed and aliased by the
+//! `--summary` tracker, so a replace that changes ClOrdID(11) stays in the same file.
+
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+/// Order files held open at once.  The one written least recently is closed first and
+/// reopened for appending when its order turns up again.
+const MAX_OPEN_FILES: usize = 64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SplitMode {
+    /// `DIR/NNNNNN_<MsgType>.txt` for each message, numbered in input order.
+    PerMessage,
+    /// `DIR/<OrderKey>.txt` for each order `--summary` tracks.
+    PerOrder,
+}
+
+impl SplitMode {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "per-message" => Some(Self::PerMessage),
+            "per-order" => Some(Self::PerOrder),
+            _ => None,
+        }
+    }
+}
+
+pub struct SplitOutput {
+    dir: PathBuf,
+    mode: SplitMode,
+    /// Messages written under `per-message`, numbering the next file.
+    messages: usize,
+    /// The file claimed for each order key.
+    order_files: HashMap<String, PathBuf>,
+    /// Order keys in the order their files were created.
+    order_keys: Vec<String>,
+    /// Open order files, least recently written first.
+    open: Vec<(String, BufWriter<File>)>,
+    max_open: usize,
+    /// Files created so far.
+    created: usize,
+}
+
+impl SplitOutput {
+    /// Write into `dir`, creating it if needed.
+    pub fn new(dir: &Path, mode: SplitMode) -> io::Result<Self> {
+        fs::create_dir_all(dir)?;
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            mode,
+            messages: 0,
+            order_files: HashMap::new(),
+            order_keys: Vec::new(),
+            open: Vec::new(),
+            max_open: MAX_OPEN_FILES,
+            created: 0,
+        })
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Write one decoded message: to a file of its own, or appended to `order`'s file.
+    /// Under `per-order`, messages that belong to no order are not written.
+    pub fn write_message(
+        &mut self,
+        msg_type: Option<&str>,
+        order: Option<&str>,
+        text: &[u8],
+    ) -> io::Result<()> {
+        match self.mode {
+            SplitMode::PerMessage => {
+                self.messages += 1;
+                let stem = format!(
+                    "{:06}_{}",
+                    self.messages,
+                    sanitise(msg_type.unwrap_or("UNKNOWN"))
+                );
+                let (_, mut file) = self.create(&stem)?;
+                file.write_all(text)
+            }
+            SplitMode::PerOrder => match order {
+                Some(key) => self.order_file(key)?.write_all(text),
+                None => Ok(()),
+            },
+        }
+    }
+
+    /// Close every file.  Under `per-order`, each order's file is rewritten with the
+    /// block `render` writes for its key ahead of the messages.  Returns the number of
+    /// files created.
+    pub fn finish(
+        &mut self,
+        mut render: impl FnMut(&str, &mut dyn Write) -> io::Result<()>,
+    ) -> io::Result<usize> {
+        for (_, mut file) in self.open.drain(..) {
+            file.flush()?;
+        }
+        for key in &self.order_keys {
+            let path = &self.order_files[key];
+            let messages = fs::read(path)?;
+            let mut file = BufWriter::new(File::create(path)?);
+            render(key, &mut file)?;
+            file.write_all(&messages)?;
+            file.flush()?;
+        }
+        Ok(self.created)
+    }
+
+    /// Create `DIR/<stem>.txt`, or `<stem>-2.txt` and so on when that name is taken by
+    /// an earlier file or run.
+    fn create(&mut self, stem: &str) -> io::Result<(PathBuf, File)> {
+        let mut attempt = 1;
+        loop {
+            let name = match attempt {
+                1 => format!("{stem}.txt"),
+                n => format!("{stem}-{n}.txt"),
+            };
+            let path = self.dir.join(name);
+            match OpenOptions::new().write(true).create_new(true).open(&path) {
+                Ok(file) => {
+                    self.created += 1;
+                    return Ok((path, file));
+                }
+                Err(err) if err.kind() == io::ErrorKind::AlreadyExists => attempt += 1,
+                Err(err) => return Err(err),
+            }
+        }
+    }
+
+    /// The open file for `key`, creating or reopening it and closing the least recently
+    /// written file when [`MAX_OPEN_FILES`] are already open.
+    fn order_file(&mut self, key: &str) -> io::Result<&mut BufWriter<File>> {
+        if let Some(pos) = self.open.iter().position(|(open, _)| open == key) {
+            let entry = self.open.remove(pos);
+            self.open.push(entry);
+        } else {
+            let file = match self.order_files.get(key) {
+                Some(path) => OpenOptions::new().append(true).open(path)?,
+                None => {
+                    let (path, file) = self.create(&sanitise(key))?;
+                    self.order_files.insert(key.to_string(), path);
+                    self.order_keys.push(key.to_string());
+                    file
+                }
+            };
+            if self.open.len() >= self.max_open {
+                let (_, mut oldest) = self.open.remove(0);
+                oldest.flush()?;
+            }
+            self.open.push((key.to_string(), BufWriter::new(file)));
+        }
+        let (_, file) = self.open.last_mut().expect("order file was just opened");
+        Ok(file)
+    }
+}
+
+/// `name` made safe as a file name: anything but letters, digits, `-`, `_` and a `.`
+/// that does not lead becomes `_`.
+fn sanitise(name: &str) -> String {
+    let safe: String = name
+        .chars()
+        .enumerate()
+        .map(|(idx, ch)| match ch {
+            'A'..='Z' | 'a'..='z' | '0'..='9' | '-' | '_' => ch,
+            '.' if idx > 0 => ch,
+            _ => '_',
+        })
+        .collect();
+    if safe.is_empty() {
+        "_".to_string()
+    } else {
+        safe
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read(dir: &Path, name: &str) -> String {
+        fs::read_to_string(dir.join(name)).unwrap()
+    }
+
+    #[test]
+    fn per_message_files_are_numbered_and_never_overwrite() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("000002_8.txt"), "earlier run").unwrap();
+        let mut split = SplitOutput::new(dir.path(), SplitMode::PerMessage).unwrap();
+        split.write_message(Some("D"), None, b"first").unwrap();
+        split.write_message(Some("8"), None, b"second").unwrap();
+        split.write_message(None, None, b"third").unwrap();
+        assert_eq!(split.finish(|_, _| Ok(())).unwrap(), 3);
+
+        assert_eq!(read(dir.path(), "000001_D.txt"), "first");
+        assert_eq!(read(dir.path(), "000002_8.txt"), "earlier run");
+        assert_eq!(read(dir.path(), "000002_8-2.txt"), "second");
+        assert_eq!(read(dir.path(), "000003_UNKNOWN.txt"), "third");
+    }
+
+    #[test]
+    fn per_order_files_survive_closing_and_get_their_block_first() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut split = SplitOutput::new(dir.path(), SplitMode::PerOrder).unwrap();
+        split.max_open = 1;
+        split.write_message(Some("D"), Some("A/1"), b"a1 ").unwrap();
+        split.write_message(Some("D"), Some("A_1"), b"b1 ").unwrap();
+        split.write_message(Some("8"), Some("A/1"), b"a2").unwrap();
+        split.write_message(Some("0"), None, b"heartbeat").unwrap();
+        let files = split.finish(|key, out| write!(out, "[{key}] ")).unwrap();
+        assert_eq!(files, 2);
+
+        assert_eq!(read(dir.path(), "A_1.txt"), "[A/1] a1 a2");
+        assert_eq!(read(dir.path(), "A_1-2.txt"), "[A_1] b1 ");
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 2);
+    }
+
+    #[test]
+    fn names_are_sanitised() {
+        assert_eq!(sanitise("ORD-1.a"), "ORD-1.a");
+        assert_eq!(sanitise("../x y"), "_._x_y");
+        assert_eq!(sanitise(""), "_");
+    }
+}
@@ -0,0 +1,930 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// SPDX-FileCopyrightText: 2025 Steve Clarke <stephenlclarke@mac.com> - https://xyzzy.tools
+
+//! Finding FIX messages in log text, shared by the prettifier and library users.
+//!
+//! [`LineDecoder`] turns the raw lines read from a log into logical lines (invalid
+//! UTF-8 replaced, `--unescape`, `--join-lines` and substitute delimiters rewritten to
+//! SOH) and [`LogicalLine::messages`] finds the messages within them.  The prettifier
+//! reads its input through these, and [`FixMessageIter`] wraps them as an iterator
+//! of decoded messages over any reader, so both agree on what counts as a message.
+
+use crate::decoder::fixparser::{FieldValue, ParsedMessage};
+use crate::decoder::raw_bytes;
+use crate::decoder::tag_lookup::{load_dictionary_with_override, normalise_fix_key};
+use crate::decoder::validator::{self, ValidationReport};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::borrow::Cow;
+use std::collections::VecDeque;
+use std::io::{self, BufRead, Read};
+use std::ops::Range;
+
+/// Options for [`FixMessageIter`].  The defaults decode SOH (or substitute) delimited
+/// lines with the dictionary each message's BeginString or ApplVerID names, without
+/// validating.
+#[derive(Debug, Clone, Default)]
+pub struct DecodeOptions {
+    /// Decode every message with this dictionary, given as `--fix` takes it (`44`,
+    /// `FIX.5.0SP2`, ...).
+    pub fix_override: Option<String>,
+    /// Validate each message, as `--validate` does, into [`DecodedMessage::validation`].
+    pub validate: bool,
+    /// Rewrite textual SOH escapes such as `\u0001` and `^A`, as `--unescape` does.
+    pub unescape: bool,
+    /// Rejoin messages wrapped across lines, as `--join-lines` does.
+    pub join_lines: bool,
+    /// How records are framed in the input, as `--stdin-format` chooses.
+    pub format: RecordFormat,
+}
+
+/// One FIX message found by [`FixMessageIter`].
+#[derive(Debug, Clone)]
+pub struct DecodedMessage {
+    /// The message as found, with its delimiter rewritten to SOH.
+    pub raw: String,
+    pub fields: Vec<FieldValue>,
+    /// The line the message was read from; a joined message keeps its first line.
+    pub line_number: usize,
+    /// The dictionary the message was decoded with, e.g. `FIX44`.
+    pub dictionary_key: String,
+    /// The `--validate` findings, when [`DecodeOptions::validate`] is set.
+    pub validation: Option<ValidationReport>,
+}
+
+impl DecodedMessage {
+    /// Value of the first occurrence of `tag`.
+    pub fn first(&self, tag: u32) -> Option<&str> {
+        self.fields
+            .iter()
+            .find(|field| field.tag == tag)
+            .map(|field| field.value.as_str())
+    }
+
+    /// The MsgType(35), if present.
+    pub fn msg_type(&self) -> Option<&str> {
+        self.first(35)
+    }
+
+    /// [`raw`](Self::raw) as the bytes it was read from, which differ from its UTF-8
+    /// text where a value held bytes that were not UTF-8, such as Latin-1 names.
+    pub fn wire_bytes(&self) -> Cow<'_, [u8]> {
+        raw_bytes::to_bytes(&self.raw)
+    }
+}
+
+/// Iterate over the FIX messages in a log, reading lines lazily from `reader` and
+/// skipping the text around the messages.  Reading stops at the first I/O error,
+/// which is yielded as the last item.
+///
+/// ```
+/// use fixdecoder::decoder::stream::{DecodeOptions, FixMessageIter};
+/// use std::collections::BTreeMap;
+/// use std::io::Cursor;
+///
+/// let log = "09:00:00 IN 8=FIX.4.4|9=5|35=A|10=000|\n\
+///            09:00:01 heartbeat timer armed\n\
+///            09:00:30 OUT 8=FIX.4.4|9=5|35=0|10=000| 8=FIX.4.4|9=5|35=0|10=000|\n";
+/// let mut counts = BTreeMap::new();
+/// for msg in FixMessageIter::new(Cursor::new(log), DecodeOptions::default()) {
+///     let msg = msg?;
+///     assert_eq!(msg.dictionary_key, "FIX44");
+///     *counts.entry(msg.msg_type().unwrap_or("?").to_string()).or_insert(0) += 1;
+/// }
+/// assert_eq!(counts, BTreeMap::from([("0".to_string(), 2), ("A".to_string(), 1)]));
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub struct FixMessageIter<R> {
+    reader: R,
+    fix_override: Option<String>,
+    validate: bool,
+    lines: LineDecoder,
+    format: RecordFormat,
+    line_number: usize,
+    buf: Vec<u8>,
+    pending: VecDeque<DecodedMessage>,
+    finished: bool,
+}
+
+impl<R: BufRead> FixMessageIter<R> {
+    pub fn new(reader: R, options: DecodeOptions) -> Self {
+        Self {
+            reader,
+            fix_override: options
+                .fix_override
+                .as_deref()
+                .map(|key| normalise_fix_key(key).unwrap_or_else(|| key.to_string())),
+            validate: options.validate,
+            lines: LineDecoder::new(options.unescape, options.join_lines),
+            format: options.format,
+            line_number: 0,
+            buf: Vec::new(),
+            pending: VecDeque::new(),
+            finished: false,
+        }
+    }
+
+    fn decode(&mut self, line: LogicalLine) {
+        for raw in line.messages() {
+            let parsed = ParsedMessage::parse(raw);
+            let dict = load_dictionary_with_override(raw, self.fix_override.as_deref());
+            let validation = self
+                .validate
+                .then(|| validator::validate_parsed_message(&parsed, &dict));
+            self.pending.push_back(DecodedMessage {
+                raw: raw.to_string(),
+                fields: parsed.fields,
+                line_number: line.line_number,
+                dictionary_key: dict.schema_key().to_string(),
+                validation,
+            });
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for FixMessageIter<R> {
+    type Item = io::Result<DecodedMessage>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(msg) = self.pending.pop_front() {
+                return Some(Ok(msg));
+            }
+            if self.finished {
+                return None;
+            }
+            self.buf.clear();
+            match read_record(&mut self.reader, &mut self.buf, self.format) {
+                Ok(0) => {
+                    self.finished = true;
+                    if let Some(line) = self.lines.finish() {
+                        self.decode(line);
+                    }
+                }
+                Ok(_) => {
+                    self.line_number += 1;
+                    for line in self.lines.push(&self.buf, self.line_number) {
+                        self.decode(line);
+                    }
+                }
+                Err(err) => {
+                    self.finished = true;
+                    return Some(Err(err));
+                }
+            }
+        }
+    }
+}
+
+/// Turns raw input lines into the [`LogicalLine`]s messages are found in: invalid
+/// UTF-8 is replaced, the line ending trimmed, textual SOH escapes rewritten when
+/// unescaping and wrapped messages rejoined when joining lines.  Without
+/// `--join-lines` a message split by a newline inside one of its values is still
+/// rejoined, see [`NewlineJoiner`].
+#[derive(Debug, Default)]
+pub struct LineDecoder {
+    unescape: bool,
+    joiner: Option<LineJoiner>,
+    newlines: NewlineJoiner,
+}
+
+impl LineDecoder {
+    pub fn new(unescape: bool, join_lines: bool) -> Self {
+        Self {
+            unescape,
+            joiner: join_lines.then(LineJoiner::default),
+            newlines: NewlineJoiner::default(),
+        }
+    }
+
+    /// Feed one line as read, line ending included, returning the logical lines ready
+    /// to decode once nothing is left waiting for a continuation.  There are two when
+    /// a line held back in case it continued turns out not to.
+    pub fn push(
+        &mut self,
+        raw: &[u8],
+        line_number: usize,
+    ) -> impl Iterator<Item = LogicalLine> + use<> {
+        // Neither framing nor values need be UTF-8: EncodedText(355) may be Shift_JIS.
+        // Invalid bytes are kept as stand-ins so checksums still see them.
+        let mut line = raw_bytes::decode(raw).into_owned();
+        trim_line_endings(&mut line);
+        if self.unescape
+            && let Some(text) = unescape_soh(&line)
+        {
+            line = text;
+        }
+        let ready = match self.joiner.as_mut() {
+            Some(joiner) => [joiner.push(&line, line_number), None],
+            None => self.newlines.push(line, line_number),
+        };
+        ready
+            .into_iter()
+            .flatten()
+            .map(|(text, start)| LogicalLine::new(text, start))
+    }
+
+    /// Release a partly joined message still buffered at the end of the input.
+    pub fn finish(&mut self) -> Option<LogicalLine> {
+        let (text, start) = match self.joiner.as_mut() {
+            Some(joiner) => joiner.finish()?,
+            None => self.newlines.finish()?,
+        };
+        Some(LogicalLine::new(text, start))
+    }
+}
+
+/// How records are framed on an input stream (`--stdin-format`).  Each record is
+/// decoded as if it were one line of a log.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RecordFormat {
+    /// Newline-terminated lines.
+    #[default]
+    Lines,
+    /// Records terminated by a 0x00 byte, as `find -print0` style tools write them.
+    Nul,
+    /// A 4-byte big-endian length followed by that many bytes, as replay tools write.
+    Len32,
+}
+
+impl RecordFormat {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_ascii_lowercase().as_str() {
+            "lines" => Some(Self::Lines),
+            "nul" => Some(Self::Nul),
+            "len32" => Some(Self::Len32),
+            _ => None,
+        }
+    }
+}
+
+/// Read the next record into `buf`, returning the number of bytes consumed (zero at
+/// the end of the input).  Lines keep their newline, as [`LineDecoder::push`] expects;
+/// NUL terminators and length prefixes are dropped.  A length-prefixed record cut
+/// short by the end of the input is an `UnexpectedEof` error.
+pub fn read_record<R: BufRead>(
+    reader: &mut R,
+    buf: &mut Vec<u8>,
+    format: RecordFormat,
+) -> io::Result<usize> {
+    match format {
+        RecordFormat::Lines => reader.read_until(b'\n', buf),
+        RecordFormat::Nul => {
+            let consumed = reader.read_until(0, buf)?;
+            if buf.last() == Some(&0) {
+                buf.pop();
+            }
+            Ok(consumed)
+        }
+        RecordFormat::Len32 => {
+            let mut header = [0u8; 4];
+            let mut filled = 0;
+            while filled < header.len() {
+                match reader.read(&mut header[filled..]) {
+                    Ok(0) => break,
+                    Ok(n) => filled += n,
+                    Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                    Err(err) => return Err(err),
+                }
+            }
+            if filled == 0 {
+                return Ok(0);
+            }
+            if filled < header.len() {
+                return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    format!("len32 record header cut short after {filled} byte(s)"),
+                ));
+            }
+            let len = u32::from_be_bytes(header) as u64;
+            // Read through `take` so a corrupt length cannot force a huge allocation.
+            let read = reader.take(len).read_to_end(buf)? as u64;
+            if read < len {
+                return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    format!("len32 record of {len} byte(s) cut short after {read}"),
+                ));
+            }
+            Ok(header.len() + read as usize)
+        }
+    }
+}
+
+/// A line of input ready for message extraction, with any substitute field delimiter
+/// already rewritten to SOH.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogicalLine {
+    pub text: String,
+    pub line_number: usize,
+    /// The delimiter the line's messages arrived with, when it was not SOH.
+    pub delimiter: Option<char>,
+}
+
+impl LogicalLine {
+    pub fn new(text: String, line_number: usize) -> Self {
+        match normalise_wire_delimiter(&text) {
+            Some((text, delimiter)) => Self {
+                text,
+                line_number,
+                delimiter: Some(delimiter),
+            },
+            None => Self {
+                text,
+                line_number,
+                delimiter: None,
+            },
+        }
+    }
+
+    /// The complete FIX messages in the line, in order.
+    pub fn messages(&self) -> impl Iterator<Item = &str> {
+        find_fix_message_indices(&self.text)
+            .into_iter()
+            .map(|(start, end)| &self.text[start..end])
+    }
+}
+
+/// A CheckSum field closing a message, matched permissively for hand-written logs.
+static CHECKSUM_FIELD: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"10=\d{3}\u{0001}").expect("valid regex"));
+
+const SOH: char = '\u{0001}';
+
+static TRAILING_CHECKSUM: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\u{0001}10=\d{3}\s*$").expect("valid regex"));
+
+/// Guess the wire delimiter of a message whose SOH bytes were replaced upstream
+/// (with `|`, `^`, `;`, a tab, ...).  `text` starts at `8=FIX`; the
+/// [`begin_string_delimiter`] counts when it is followed by the `9=` BodyLength tag.
+fn detect_wire_delimiter(text: &str) -> Option<char> {
+    let delim = begin_string_delimiter(text)?;
+    let (_, rest) = text.split_once(delim)?;
+    rest.starts_with("9=").then_some(delim)
+}
+
+/// The character after the BeginString value of `text` (which starts at `8=`) when
+/// it could stand in for SOH: printable punctuation other than `=`, or a tab.
+pub(crate) fn begin_string_delimiter(text: &str) -> Option<char> {
+    let rest = text.strip_prefix("8=")?;
+    let token_len = rest.find(|c: char| !(c.is_ascii_alphanumeric() || c == '.'))?;
+    let delim = rest[token_len..].chars().next()?;
+    (delim == '\t' || (delim.is_ascii_punctuation() && delim != '=')).then_some(delim)
+}
+
+/// Rewrite a line whose FIX message uses a substitute delimiter back to SOH so the
+/// regular decoding path recognises it.  Returns `None` when the line already holds
+/// a well-formed message or no delimiter could be detected.  A CheckSum at the very
+/// end of the line gets the SOH terminator many tools drop along with the delimiter.
+pub(crate) fn normalise_wire_delimiter(line: &str) -> Option<(String, char)> {
+    let start = line.find("8=FIX")?;
+    if !find_fix_message_indices(line).is_empty() {
+        return None;
+    }
+    let delim = detect_wire_delimiter(&line[start..])?;
+    let mut text = String::with_capacity(line.len() + 1);
+    text.push_str(&line[..start]);
+    text.extend(
+        line[start..]
+            .chars()
+            .map(|c| if c == delim { SOH } else { c }),
+    );
+    if TRAILING_CHECKSUM.is_match(&text) {
+        text.truncate(text.trim_end().len());
+        text.push(SOH);
+    }
+    Some((text, delim))
+}
+
+static ESCAPED_SOH: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\\u0001|\\x01|\\001|\^A").expect("valid regex"));
+
+/// The escaped CheckSum field closing a message written with textual SOH escapes.
+static ESCAPED_CHECKSUM: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?:\\u0001|\\x01|\\001|\^A)10=\d{3}(?:\\u0001|\\x01|\\001|\^A)?")
+        .expect("valid regex")
+});
+
+/// Rewrite the textual SOH escapes that JSON and syslog-style loggers leave behind
+/// (`\u0001`, `\x01`, `\001` and caret notation `^A`) back to SOH for `--unescape`.
+/// Only the messages are touched, from `8=FIX` to their escaped CheckSum field (or the
+/// end of the line for one still open), and only those exact sequences, so other
+/// backslashes and the surrounding text survive.  Returns `None` when nothing changed.
+fn unescape_soh(line: &str) -> Option<String> {
+    let mut text = String::with_capacity(line.len());
+    let mut changed = false;
+    let mut pos = 0;
+    while let Some(offset) = line[pos..].find("8=FIX") {
+        let start = pos + offset;
+        let end = ESCAPED_CHECKSUM
+            .find(&line[start..])
+            .map_or(line.len(), |m| start + m.end());
+        let message = ESCAPED_SOH.replace_all(&line[start..end], "\u{0001}");
+        changed |= matches!(message, Cow::Owned(_));
+        text.push_str(&line[pos..start]);
+        text.push_str(&message);
+        pos = end;
+    }
+    text.push_str(&line[pos..]);
+    changed.then_some(text)
+}
+
+/// Upper bound on the text `--join-lines` buffers while waiting for a CheckSum field.
+const JOIN_LIMIT: usize = 64 * 1024;
+
+/// Reassembles FIX messages that a logger wrapped across several physical lines
+/// (`--join-lines`).  A line holding `8=FIX` without a terminating `10=NNN<SOH>` is
+/// buffered and the following lines appended until the checksum appears or
+/// [`JOIN_LIMIT`] is reached; the joined text keeps the number of its first line.
+#[derive(Debug, Default)]
+pub struct LineJoiner {
+    pending: String,
+    start_line: usize,
+}
+
+impl LineJoiner {
+    /// Feed one physical line, returning the logical line ready to process (with
+    /// its starting line number) once nothing is left waiting for a continuation.
+    fn push(&mut self, line: &str, line_number: usize) -> Option<(String, usize)> {
+        if self.pending.is_empty() {
+            if !has_unterminated_message(line) {
+                return Some((line.to_string(), line_number));
+            }
+            self.start_line = line_number;
+        }
+        self.pending.push_str(line);
+        if has_unterminated_message(&self.pending) && self.pending.len() < JOIN_LIMIT {
+            return None;
+        }
+        self.finish()
+    }
+
+    /// Release whatever is still buffered, e.g. at end of input.
+    fn finish(&mut self) -> Option<(String, usize)> {
+        if self.pending.is_empty() {
+            return None;
+        }
+        Some((std::mem::take(&mut self.pending), self.start_line))
+    }
+}
+
+/// Rejoins a message split in two by a newline inside one of its values, as when a
+/// venue's free text is forwarded verbatim into Text(58).  It is always on, so it
+/// only joins when the result is certain: a line ending in an unterminated message is
+/// held back, and the next line is appended (the newline restored) only if the
+/// message's BodyLength(9) then reaches a CheckSum beyond the break.  Otherwise both
+/// lines are released unchanged.
+#[derive(Debug, Default)]
+struct NewlineJoiner {
+    held: Option<(String, usize)>,
+}
+
+impl NewlineJoiner {
+    fn push(&mut self, line: String, line_number: usize) -> [Option<(String, usize)>; 2] {
+        match self.held.take() {
+            Some((held, start)) => match join_across_newline(&held, &line) {
+                Some(joined) => [self.hold(joined, start), None],
+                None => [Some((held, start)), self.hold(line, line_number)],
+            },
+            None => [self.hold(line, line_number), None],
+        }
+    }
+
+    /// Keep `line` back when it may continue on the next line, else hand it on.
+    fn hold(&mut self, line: String, line_number: usize) -> Option<(String, usize)> {
+        if line.len() < JOIN_LIMIT && has_unterminated_message(&line) {
+            self.held = Some((line, line_number));
+            return None;
+        }
+        Some((line, line_number))
+    }
+
+    fn finish(&mut self) -> Option<(String, usize)> {
+        self.held.take()
+    }
+}
+
+/// `held` and `next` joined by a newline, when that completes a message started in
+/// `held` whose BodyLength(9) counts the newline.
+fn join_across_newline(held: &str, next: &str) -> Option<String> {
+    let joined = format!("{held}\n{next}");
+    let normalised = normalise_wire_delimiter(&joined);
+    let text = normalised
+        .as_ref()
+        .map_or(joined.as_str(), |(text, _)| text);
+    let completed = find_fix_message_indices(text)
+        .into_iter()
+        .any(|(start, end)| {
+            start < held.len() && end > held.len() && end_from_body_length(text, start) == Some(end)
+        });
+    completed.then_some(joined)
+}
+
+/// True when `8=FIX` appears after the last complete message in `text`.
+fn has_unterminated_message(text: &str) -> bool {
+    if let Some((normalised, _)) = normalise_wire_delimiter(text) {
+        return tail_is_unterminated(&normalised);
+    }
+    tail_is_unterminated(text)
+}
+
+fn tail_is_unterminated(text: &str) -> bool {
+    let tail_start = find_fix_message_indices(text)
+        .last()
+        .map_or(0, |(_, end)| *end);
+    text[tail_start..].contains("8=FIX")
+}
+
+/// Locate FIX message spans within a line.  Binary framing or other noise between
+/// messages is skipped, and a truncated message is dropped rather than merged with the
+/// one after it.
+pub(crate) fn find_fix_message_indices(line: &str) -> Vec<(usize, usize)> {
+    let mut spans = Vec::new();
+    let mut checksums = ChecksumCursor::default();
+    let mut pos = 0;
+    while let Some(offset) = line[pos..].find("8=FIX") {
+        let start = pos + offset;
+        match find_message_end(line, start, &mut checksums) {
+            Some(end) => {
+                spans.push((start, end));
+                pos = end;
+            }
+            None => pos = start + 1,
+        }
+    }
+    spans
+}
+
+/// End of the message starting at `start`: where its BodyLength(9) puts the CheckSum
+/// field when that checks out, otherwise just past the first CheckSum field, provided
+/// no other message starts before it.
+fn find_message_end(line: &str, start: usize, checksums: &mut ChecksumCursor) -> Option<usize> {
+    if let Some(end) = end_from_body_length(line, start) {
+        return Some(end);
+    }
+    let checksum = checksums.next_at(line, start)?;
+    match next_message_start(line, start + 1) {
+        Some(next) if next < checksum.start => None,
+        _ => Some(checksum.end),
+    }
+}
+
+/// The CheckSum field a scan last found.  Message starts are tried left to right, so
+/// one search serves every start before it, and a line with thousands of unterminated
+/// `8=FIX` prefixes is searched once rather than once per prefix.
+#[derive(Default)]
+struct ChecksumCursor {
+    /// `Some(None)` once a search found nothing, so nothing later can match either.
+    found: Option<Option<Range<usize>>>,
+}
+
+impl ChecksumCursor {
+    /// The first CheckSum field at or after `from`, which never goes backwards.
+    fn next_at(&mut self, line: &str, from: usize) -> Option<Range<usize>> {
+        match &self.found {
+            Some(Some(found)) if found.start >= from => return Some(found.clone()),
+            Some(None) => return None,
+            _ => {}
+        }
+        let found = CHECKSUM_FIELD.find_at(line, from).map(|m| m.range());
+        self.found = Some(found.clone());
+        found
+    }
+}
+
+/// Longest BeginString value looked for, `FIXT.1.1` with room to spare, so a prefix
+/// with no SOH after it is not followed to the end of the line.
+const MAX_BEGIN_STRING: usize = 16;
+
+/// More BodyLength digits than any real message needs; longer runs are not a length.
+const MAX_BODY_LENGTH_DIGITS: usize = 9;
+
+/// Follow `8=...<SOH>9=N<SOH>` to the end of the body and expect `10=NNN<SOH>` there.
+/// N counts bytes as sent, which is also the length in `line` unless the body holds
+/// bytes that were not UTF-8.
+fn end_from_body_length(line: &str, start: usize) -> Option<usize> {
+    const SOH_BYTE: u8 = 0x01;
+    // The first byte of the private-use stand-ins for such bytes.
+    const STAND_IN_LEAD: u8 = 0xF4;
+    let bytes = line.as_bytes();
+    let begin_end = start
+        + bytes[start..]
+            .iter()
+            .take(MAX_BEGIN_STRING)
+            .position(|b| *b == SOH_BYTE)?;
+    let length_field = bytes[begin_end + 1..].strip_prefix(b"9=")?;
+    let digits = length_field
+        .iter()
+        .take(MAX_BODY_LENGTH_DIGITS + 1)
+        .take_while(|b| b.is_ascii_digit())
+        .count();
+    if digits == 0 || digits > MAX_BODY_LENGTH_DIGITS || length_field.get(digits) != Some(&SOH_BYTE)
+    {
+        return None;
+    }
+    let body_len: usize = std::str::from_utf8(&length_field[..digits])
+        .ok()?
+        .parse()
+        .ok()?;
+    let body_start = begin_end + 1 + 2 + digits + 1;
+    let body = bytes.get(body_start..)?;
+    let body_end = if body.iter().take(body_len).any(|b| *b == STAND_IN_LEAD) {
+        body_start + raw_bytes::text_len(line.get(body_start..)?, body_len)?
+    } else {
+        body_start.checked_add(body_len)?
+    };
+    if body_len > 0 && bytes.get(body_end - 1) != Some(&SOH_BYTE) {
+        return None;
+    }
+    let trailer = bytes.get(body_end..body_end + 7)?;
+    let well_formed = trailer.starts_with(b"10=")
+        && trailer[3..6].iter().all(u8::is_ascii_digit)
+        && trailer[6] == SOH_BYTE;
+    well_formed.then_some(body_end + 7)
+}
+
+/// The next `8=FIX` at or after `from` that is not the tail of a longer tag number.
+fn next_message_start(line: &str, from: usize) -> Option<usize> {
+    let mut pos = from;
+    while let Some(offset) = line[pos..].find("8=FIX") {
+        let candidate = pos + offset;
+        if !line.as_bytes()[..candidate]
+            .last()
+            .is_some_and(u8::is_ascii_digit)
+        {
+            return Some(candidate);
+        }
+        pos = candidate + 1;
+    }
+    None
+}
+
+fn trim_line_endings(line: &mut String) {
+    if line.ends_with('\n') {
+        line.pop();
+        if line.ends_with('\r') {
+            line.pop();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decoder::repair::sealed_message;
+    use crate::decoder::validator::ErrorCategory;
+    use std::io::Cursor;
+
+    #[test]
+    fn line_joiner_rebuilds_wrapped_messages() {
+        let mut joiner = LineJoiner::default();
+        assert_eq!(joiner.push("noise", 1), Some(("noise".to_string(), 1)));
+        assert_eq!(joiner.push("INFO 8=FIX.4.4\u{0001}9=5\u{0001}", 2), None);
+        assert_eq!(joiner.push("35=0\u{0001}", 3), None);
+        let (joined, start) = joiner.push("10=123\u{0001} trailing", 4).unwrap();
+        assert_eq!(start, 2);
+        assert_eq!(
+            find_fix_message_indices(&joined).len(),
+            1,
+            "joined line holds the whole message: {joined:?}"
+        );
+        assert_eq!(joiner.finish(), None);
+
+        assert_eq!(joiner.push("8=FIX.4.4\u{0001}", 5), None);
+        let (flushed, start) = joiner.push(&"x".repeat(JOIN_LIMIT), 6).unwrap();
+        assert_eq!(start, 5);
+        assert!(flushed.len() > JOIN_LIMIT);
+    }
+
+    #[test]
+    fn newline_inside_a_value_is_rejoined_only_when_body_length_agrees() {
+        let message = |body: &str| sealed_message("FIX.4.4", body);
+        let split = message(&format!("35=3{SOH}45=1{SOH}58=line one\nline two{SOH}"));
+        let (first, second) = split.split_once('\n').unwrap();
+        let mut lines = LineDecoder::default();
+        assert_eq!(lines.push(format!("IN {first}\n").as_bytes(), 1).count(), 0);
+        let joined: Vec<LogicalLine> = lines.push(format!("{second}\n").as_bytes(), 2).collect();
+        assert_eq!(joined.len(), 1);
+        assert_eq!(joined[0].line_number, 1);
+        assert_eq!(joined[0].messages().collect::<Vec<_>>(), [split.as_str()]);
+
+        // A wrapped message, whose BodyLength does not count the break, is left to
+        // `--join-lines`; both halves come out as they were read.
+        let wrapped = message(&format!("35=0{SOH}"));
+        let (first, second) = wrapped.split_at(14);
+        assert_eq!(lines.push(first.as_bytes(), 3).count(), 0);
+        let released: Vec<usize> = lines
+            .push(second.as_bytes(), 4)
+            .map(|line| line.line_number)
+            .collect();
+        assert_eq!(released, [3, 4]);
+
+        assert_eq!(lines.push(b"8=FIX.4.4\x01", 5).count(), 0);
+        assert_eq!(lines.finish().map(|line| line.line_number), Some(5));
+    }
+
+    #[test]
+    fn thousands_of_unterminated_prefixes_are_scanned_once() {
+        let whole = format!("8=FIX.4.4{SOH}9=5{SOH}35=0{SOH}10=000{SOH}");
+        let line = format!(
+            "{}{whole}",
+            "8=FIX.4.4 9=99999999999999999999 ".repeat(20_000)
+        );
+        assert_eq!(
+            find_fix_message_indices(&line),
+            [(line.len() - whole.len(), line.len())]
+        );
+
+        let unterminated = format!("8=FIX{}10=000{SOH}", "8=FIX".repeat(50_000));
+        assert_eq!(find_fix_message_indices(&unterminated).len(), 1);
+    }
+
+    #[test]
+    fn message_boundaries_follow_body_length_through_binary_framing() {
+        let framed = |body: &str| sealed_message("FIX.4.4", body);
+        // MinQty(110) ends in "10=100<SOH>", which a plain pattern takes for a CheckSum.
+        let first = framed(&format!("35=D{SOH}110=100{SOH}"));
+        let second = framed(&format!("35=0{SOH}"));
+        let third = framed(&format!("35=8{SOH}"));
+        let truncated = format!("8=FIX.4.4{SOH}9=40{SOH}35=D{SOH}");
+        let prefix: String = (third.len() as u32)
+            .to_le_bytes()
+            .into_iter()
+            .map(char::from)
+            .collect();
+        let line = format!("{first}{second}{truncated}\u{fffd}{prefix}{third}");
+
+        let third_start = line.len() - third.len();
+        assert_eq!(
+            find_fix_message_indices(&line),
+            vec![
+                (0, first.len()),
+                (first.len(), first.len() + second.len()),
+                (third_start, line.len()),
+            ]
+        );
+    }
+
+    #[test]
+    fn body_length_counts_bytes_that_are_not_utf8_once() {
+        // "Société" in Latin-1, then MinQty(110) ending in something like a CheckSum.
+        let body = b"35=D\x01448=Soci\xe9t\xe9\x01110=100\x01";
+        let one = sealed_message("FIX.4.4", &raw_bytes::decode(body));
+        let raw = raw_bytes::to_bytes(&one).into_owned();
+        let line = format!("{one}{one}");
+        assert_eq!(
+            find_fix_message_indices(&line),
+            vec![(0, one.len()), (one.len(), line.len())]
+        );
+
+        let mut messages = FixMessageIter::new(
+            Cursor::new([raw.clone(), b"\n".to_vec()].concat()),
+            DecodeOptions {
+                validate: true,
+                ..DecodeOptions::default()
+            },
+        );
+        let msg = messages.next().expect("one message").expect("decoded");
+        assert_eq!(msg.wire_bytes().as_ref(), raw.as_slice());
+        assert!(msg.validation.expect("validated").errors.iter().all(|e| {
+            !matches!(
+                e.category,
+                ErrorCategory::Checksum | ErrorCategory::BodyLength | ErrorCategory::Spliced
+            )
+        }));
+    }
+
+    #[test]
+    fn substitute_delimiters_are_detected_and_normalised() {
+        for delim in ['|', '\t', ';', '^'] {
+            let line = format!("IN 8=FIX.4.4{delim}9=5{delim}35=0{delim}10=123{delim}");
+            let (text, found) = normalise_wire_delimiter(&line).expect("delimiter detected");
+            assert_eq!(found, delim);
+            assert_eq!(text, "IN 8=FIX.4.4\u{1}9=5\u{1}35=0\u{1}10=123\u{1}");
+            assert_eq!(find_fix_message_indices(&text), vec![(3, text.len())]);
+        }
+
+        let (text, _) = normalise_wire_delimiter("8=FIXT.1.1|9=5|35=0|10=123 ").unwrap();
+        assert_eq!(text, "8=FIXT.1.1\u{1}9=5\u{1}35=0\u{1}10=123\u{1}");
+
+        assert_eq!(
+            normalise_wire_delimiter("8=FIX.4.4\u{1}9=5\u{1}10=123\u{1}"),
+            None
+        );
+        assert_eq!(normalise_wire_delimiter("8=FIX.4.4 9=5 10=123"), None);
+        assert_eq!(normalise_wire_delimiter("8=FIX.4.4|35=0|10=123|"), None);
+        assert!(!has_unterminated_message("8=FIX.4.4|9=5|35=0|10=123|"));
+    }
+
+    #[test]
+    fn textual_soh_escapes_are_unescaped() {
+        for escape in [r"\u0001", r"\x01", r"\001", "^A"] {
+            let line = format!(
+                r#"{{"path":"C:\\logs","msg":"8=FIX.4.4{escape}9=5{escape}35=0{escape}10=123{escape}"}}"#
+            );
+            let text = unescape_soh(&line).expect("escapes rewritten");
+            assert_eq!(
+                text,
+                r#"{"path":"C:\\logs","msg":"8=FIX.4.4"#.to_string()
+                    + "\u{1}9=5\u{1}35=0\u{1}10=123\u{1}\"}"
+            );
+        }
+
+        let line = r#"{"msg":"8=FIX.4.4\u00019=5\u000135=0\u000110=123\u0001","sep":"\u0001"}"#;
+        assert_eq!(
+            unescape_soh(line).expect("escapes rewritten"),
+            "{\"msg\":\"8=FIX.4.4\u{1}9=5\u{1}35=0\u{1}10=123\u{1}".to_string()
+                + r#"","sep":"\u0001"}"#,
+            "text after the message is left as logged"
+        );
+
+        assert_eq!(unescape_soh(r"8=FIX.4.4|58=a\b|10=123|"), None);
+        assert_eq!(unescape_soh(r"prefix \u0001 without a message"), None);
+    }
+
+    #[test]
+    fn trim_line_endings_strips_crlf() {
+        let mut line = "abc\r\n".to_string();
+        trim_line_endings(&mut line);
+        assert_eq!(line, "abc");
+    }
+
+    #[test]
+    fn records_are_read_in_each_framing() {
+        let read_all = |input: &[u8], format| {
+            let mut reader = Cursor::new(input.to_vec());
+            let mut records = Vec::new();
+            loop {
+                let mut buf = Vec::new();
+                match read_record(&mut reader, &mut buf, format) {
+                    Ok(0) => return Ok(records),
+                    Ok(_) => records.push(String::from_utf8(buf).unwrap()),
+                    Err(err) => return Err(err),
+                }
+            }
+        };
+        assert_eq!(
+            read_all(b"a\nb", RecordFormat::Lines).unwrap(),
+            ["a\n", "b"]
+        );
+        assert_eq!(
+            read_all(b"a\nb\0\0c", RecordFormat::Nul).unwrap(),
+            ["a\nb", "", "c"]
+        );
+        assert_eq!(
+            read_all(b"\0\0\0\x02ab\0\0\0\0\0\0\0\x01\n", RecordFormat::Len32).unwrap(),
+            ["ab", "", "\n"]
+        );
+
+        for cut_short in [&b"\0\0\0\x05abc"[..], b"\0\0\0\x01a\0\0"] {
+            let err = read_all(cut_short, RecordFormat::Len32).unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+        }
+        assert_eq!(RecordFormat::parse("LEN32"), Some(RecordFormat::Len32));
+        assert_eq!(RecordFormat::parse("csv"), None);
+    }
+
+    #[test]
+    #[cfg(all(feature = "dict-fix42", feature = "dict-fix50sp2"))]
+    fn iterator_yields_each_message_with_its_line_and_dictionary() {
+        let log = "noise\n\
+                   IN 8=FIX.4.4^A9=5^A35=0^A10=000^A\n\
+                   OUT 8=FIX.4.4|9=5|\n\
+                   35=D|10=000|\n\
+                   8=FIX.4.2\u{1}9=5\u{1}35=8\u{1}10=000\u{1}";
+        let options = DecodeOptions {
+            validate: true,
+            unescape: true,
+            join_lines: true,
+            ..DecodeOptions::default()
+        };
+        let decoded: Vec<DecodedMessage> = FixMessageIter::new(Cursor::new(log), options)
+            .collect::<io::Result<_>>()
+            .unwrap();
+        let summary: Vec<(Option<&str>, usize, &str)> = decoded
+            .iter()
+            .map(|msg| (msg.msg_type(), msg.line_number, msg.dictionary_key.as_str()))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                (Some("0"), 2, "FIX44"),
+                (Some("D"), 3, "FIX44"),
+                (Some("8"), 5, "FIX42"),
+            ]
+        );
+        assert!(decoded[1].raw.starts_with("8=FIX.4.4\u{1}9=5\u{1}35=D"));
+        assert!(decoded.iter().all(|msg| msg.validation.is_some()));
+
+        // Without unescaping or joining only the last line holds a whole message.
+        let options = DecodeOptions {
+            fix_override: Some("50SP2".to_string()),
+            ..DecodeOptions::default()
+        };
+        let decoded: Vec<DecodedMessage> = FixMessageIter::new(Cursor::new(log), options)
+            .collect::<io::Result<_>>()
+            .unwrap();
+        assert_eq!(decoded.len(), 1, "{decoded:?}");
+        assert_eq!(decoded[0].dictionary_key, "FIX50SP2");
+        assert!(decoded[0].validation.is_none());
+    }
+}
@@ -2,14 +2,15 @@
 // SPDX-FileCopyrightText: 2025 Steve Clarke <stephenlclarke@mac.com> - https://xyzzy.tools
 
 use crate::decoder::colours::palette;
-use crate::decoder::display::{pad_ansi, visible_width};
-use crate::decoder::fixparser::parse_fix;
+use crate::decoder::display::{DisplayOptions, pad_ansi, visible_width};
+use crate::decoder::fixparser::{FieldValue, ParsedMessage};
 use crate::decoder::tag_lookup::{
-    FixTagLookup, clear_override_cache_for, load_dictionary_with_override,
+    FixTagLookup, GroupSpec, MessageDef, clear_override_cache_for, load_dictionary_with_override,
 };
-use chrono::{Datelike, Duration, NaiveDate};
-use std::collections::{HashMap, hash_map::Entry};
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime};
+use std::collections::{BTreeMap, HashMap, HashSet, hash_map::Entry};
 use std::io::Write;
+use std::rc::Rc;
 
 /// Captures FIX order lifecycles while streaming messages so a concise summary
 /// can be rendered after processing input.
@@ -24,16 +25,158 @@ pub struct OrderSummary {
     footer_width: usize,
     fix_override_key: Option<String>,
     display_delimiter: char,
+    /// How raw messages are shown: `--max-value-width` and `--no-escape`.
+    display: DisplayOptions,
+    terminal_states: Vec<String>,
+    keep_open: bool,
+    raw_messages: bool,
+    group: Option<SummaryGroup>,
+    /// MsgTypes fed into the tracker; anything else is skipped before it is parsed.
+    msg_types: Vec<String>,
+    /// `--summary-group` totals of orders already printed and dropped by `--follow`.
+    retired_totals: BTreeMap<(String, String), GroupTotals>,
+    /// Tags compared when an order is replaced (`--summary-diff`); empty disables it.
+    diff_tags: Vec<u32>,
+    /// `--summary-max-orders`: orders held, open and completed, before the oldest
+    /// completed ones are printed and dropped.
+    max_orders: Option<usize>,
+    /// Symbols and sides, shared by every order that carries them.
+    strings: Interner,
 }
 
+/// One shared copy of each value that recurs across orders, such as a symbol.
+#[derive(Default)]
+struct Interner(HashSet<Rc<str>>);
+
+impl Interner {
+    fn intern(&mut self, value: &str) -> Rc<str> {
+        if let Some(shared) = self.0.get(value) {
+            return shared.clone();
+        }
+        let shared: Rc<str> = value.into();
+        self.0.insert(shared.clone());
+        shared
+    }
+}
+
+/// How `--summary-group` rolls orders up after the individual summaries.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SummaryGroup {
+    Symbol,
+    SymbolSide,
+}
+
+impl SummaryGroup {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "symbol" => Some(Self::Symbol),
+            "symbol-side" => Some(Self::SymbolSide),
+            _ => None,
+        }
+    }
+
+    /// The (symbol, side) row an order belongs to; the side is blank when not grouped by it.
+    fn key(self, record: &OrderRecord) -> (String, String) {
+        let symbol = record.symbol.as_deref().unwrap_or("-").to_string();
+        let side = match self {
+            Self::Symbol => String::new(),
+            Self::SymbolSide => record.side.as_deref().map_or("-", side_label).to_string(),
+        };
+        (symbol, side)
+    }
+}
+
+/// Orders rolled up into one `--summary-group` row.
+#[derive(Debug, Default, Clone, PartialEq)]
+struct GroupTotals {
+    orders: usize,
+    ordered_qty: f64,
+    executed_qty: f64,
+    canceled: usize,
+    rejected: usize,
+    /// Quantity values present but not numeric, left out of the totals.
+    skipped_qty: usize,
+}
+
+impl GroupTotals {
+    fn add(&mut self, record: &OrderRecord) {
+        self.orders += 1;
+        self.ordered_qty += self.quantity(record.qty.as_deref());
+        let stats = record.fill_stats();
+        self.skipped_qty += stats.unparsed_qty;
+        self.executed_qty += if stats.fills > 0 {
+            stats.exec_qty
+        } else {
+            self.quantity(record.cum_qty.as_deref())
+        };
+        let states = record.state_path();
+        if states.last().is_some_and(|state| state == "Canceled") {
+            self.canceled += 1;
+        }
+        if states.iter().any(|state| state == "Rejected") {
+            self.rejected += 1;
+        }
+    }
+
+    /// Parse a quantity, counting it as skipped when present but not a number.
+    fn quantity(&mut self, value: Option<&str>) -> f64 {
+        match (value, parse_number(value)) {
+            (_, Some(qty)) => qty,
+            (Some(_), None) => {
+                self.skipped_qty += 1;
+                0.0
+            }
+            (None, None) => 0.0,
+        }
+    }
+
+    fn fill_rate(&self) -> String {
+        if self.ordered_qty > 0.0 {
+            format_percent(self.executed_qty / self.ordered_qty)
+        } else {
+            "-".to_string()
+        }
+    }
+
+    fn cancel_rate(&self) -> String {
+        format_percent(self.canceled as f64 / self.orders.max(1) as f64)
+    }
+}
+
+/// Order states that close an order unless `--summary-terminal` says otherwise.
+pub const DEFAULT_TERMINAL_STATES: &[&str] = &[
+    "Filled",
+    "Canceled",
+    "Rejected",
+    "Done for Day",
+    "Expired",
+    "Stopped",
+    "Suspended",
+    "Calculated",
+];
+
+/// MsgTypes that affect orders and are always tracked: NewOrderSingle (D),
+/// OrderCancelRequest (F), OrderCancelReplaceRequest (G), ExecutionReport (8),
+/// OrderCancelReject (9), BN, NewOrderMultileg (AB), MultilegOrderCancelReplace (AC),
+/// NewOrderList (E) and NewOrderCross (s).  `--summary-msgtypes` can add more.
+pub const DEFAULT_SUMMARY_MSG_TYPES: &[&str] =
+    &["D", "F", "G", "8", "9", "BN", "AB", "AC", "E", "s"];
+
+/// Tags `--summary-diff` compares when an order is replaced: OrderQty (38), Price (44),
+/// OrdType (40), TimeInForce (59) and SettlDate (64).
+pub const DEFAULT_DIFF_TAGS: &[u32] = &[38, 44, 40, 59, 64];
+
+/// VWAP and AvgPx (6) may drift by this fraction of AvgPx before a warning is shown.
+const AVG_PX_TOLERANCE: f64 = 1e-6;
+
 #[derive(Debug, Clone)]
 struct OrderRecord {
     key: String,
     order_id: Option<String>,
     cl_ord_id: Option<String>,
     orig_cl_ord_id: Option<String>,
-    symbol: Option<String>,
-    side: Option<String>,
+    symbol: Option<Rc<str>>,
+    side: Option<Rc<str>>,
     qty: Option<String>,
     cum_qty: Option<String>,
     leaves_qty: Option<String>,
@@ -43,6 +186,9 @@ struct OrderRecord {
     trade_date: Option<String>,
     settl_date: Option<String>,
     settl_date2: Option<String>,
+    /// ExpireTime(126) and ExpireDate(432), shown for GTD orders.
+    expire_time: Option<String>,
+    expire_date: Option<String>,
     currency: Option<String>,
     ord_type_desc: Option<String>,
     tif_desc: Option<String>,
@@ -63,7 +209,17 @@ struct OrderRecord {
     last_qty: Option<String>,
     bn_seen: bool,
     bn_exec_amt: Option<String>,
+    /// ExecIDs (17) already seen, so a replayed execution is not counted twice.
+    exec_ids: HashSet<String>,
+    /// IDs that resolve to this order, dropped with it once it has been printed.
+    aliases: Vec<String>,
+    /// Last accepted value of each `--summary-diff` tag, from the order and its executions.
+    diff_values: HashMap<u32, String>,
+    /// Legs of a multi-leg order or swap, from the latest message carrying NoLegs (555).
+    legs: Vec<LegInfo>,
     events: Vec<OrderEvent>,
+    /// Raw messages, kept only for `--summary-raw`, or for `--summary-keep-open` while
+    /// the order is open.
     messages: Vec<String>,
 }
 
@@ -84,29 +240,201 @@ struct OrderEvent {
     text: Option<String>,
     cl_ord_id: Option<String>,
     orig_cl_ord_id: Option<String>,
+    /// An ExecutionReport repeating an ExecID already seen for the order.
+    duplicate: bool,
+    /// Fields a cancel/replace request (G) or Replaced execution changed.
+    diff: Vec<FieldChange>,
+}
+
+/// One entry of the NoLegs (555) group.
+#[derive(Debug, Clone, Default, PartialEq)]
+struct LegInfo {
+    symbol: Option<String>,
+    side: Option<String>,
+    qty: Option<String>,
+    settl_date: Option<String>,
+}
+
+/// One field changed by a cancel/replace; `old` is `None` when its value was never seen.
+#[derive(Debug, Clone, PartialEq)]
+struct FieldChange {
+    tag: u32,
+    old: Option<String>,
+    new: String,
+}
+
+/// Executions aggregated across an order's ExecutionReports.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+struct FillStats {
+    fills: usize,
+    exec_qty: f64,
+    priced_qty: f64,
+    notional: f64,
+    /// LastQty (32) values that were present but not a number.
+    unparsed_qty: usize,
 }
 
+impl FillStats {
+    fn vwap(&self) -> Option<f64> {
+        (self.priced_qty > 0.0).then(|| self.notional / self.priced_qty)
+    }
+
+    /// Describe a mismatch between the VWAP of the fills and the reported AvgPx (6).
+    fn avg_px_warning(&self, avg_px: Option<&str>) -> Option<String> {
+        let vwap = self.vwap()?;
+        let avg = parse_number(avg_px)?;
+        let tolerance = avg.abs() * AVG_PX_TOLERANCE;
+        ((vwap - avg).abs() > tolerance).then(|| {
+            format!(
+                "Warning: VWAP {} of fills differs from AvgPx {}",
+                format_number(vwap),
+                avg_px.unwrap_or("-")
+            )
+        })
+    }
+}
+
+/// Resolve an OrdStatus (39) code such as `4`, or a state name such as `Canceled`,
+/// to the state name the tracker uses.
+pub fn ord_status_state(value: &str) -> Option<&'static str> {
+    label_ord_status_raw(Some(value)).or_else(|| {
+        ORD_STATUS_CODES
+            .iter()
+            .filter_map(|code| label_ord_status_raw(Some(code)))
+            .find(|label| label.eq_ignore_ascii_case(value))
+    })
+}
+
+const ORD_STATUS_CODES: &[&str] = &[
+    "A", "0", "1", "2", "3", "4", "5", "6", "7", "8", "9", "B", "C", "D", "E",
+];
+
 impl OrderSummary {
     pub fn new(display_delimiter: char) -> Self {
         Self {
             display_delimiter,
+            terminal_states: DEFAULT_TERMINAL_STATES
+                .iter()
+                .map(|s| s.to_string())
+                .collect(),
+            msg_types: DEFAULT_SUMMARY_MSG_TYPES
+                .iter()
+                .map(|s| s.to_string())
+                .collect(),
             ..Self::default()
         }
     }
 
+    /// Show raw messages under `display` (`--max-value-width`, `--no-escape`).
+    pub fn with_display(mut self, display: DisplayOptions) -> Self {
+        self.display = display;
+        self
+    }
+
+    /// Replace the set of order states that close an order.
+    pub fn with_terminal_states(mut self, states: Vec<String>) -> Self {
+        self.terminal_states = states;
+        self
+    }
+
+    /// Print open orders in full, raw messages included, when the summary is rendered
+    /// at the end of input.
+    pub fn with_keep_open(mut self, keep_open: bool) -> Self {
+        self.keep_open = keep_open;
+        self
+    }
+
+    /// Echo each completed order's raw messages under its summary (`--summary-raw`).
+    pub fn with_raw_messages(mut self, raw_messages: bool) -> Self {
+        self.raw_messages = raw_messages;
+        self
+    }
+
+    /// Finish the summary with per-symbol (or per-symbol and side) totals.
+    pub fn with_group(mut self, group: Option<SummaryGroup>) -> Self {
+        self.group = group;
+        self
+    }
+
+    /// Show what each cancel/replace changed among `tags` in the timeline (`--summary-diff`).
+    pub fn with_diff_tags(mut self, tags: Vec<u32>) -> Self {
+        self.diff_tags = tags;
+        self
+    }
+
+    /// Hold at most `max` orders, printing and dropping the oldest completed ones as
+    /// more complete, so memory stays bounded however long the log
+    /// (`--summary-max-orders`).  Open orders are never dropped.
+    pub fn with_max_orders(mut self, max: Option<usize>) -> Self {
+        self.max_orders = max;
+        self
+    }
+
+    /// Track these MsgTypes as well as [`DEFAULT_SUMMARY_MSG_TYPES`].
+    pub fn with_extra_msg_types(mut self, msg_types: Vec<String>) -> Self {
+        for msg_type in msg_types {
+            if !self.msg_types.contains(&msg_type) {
+                self.msg_types.push(msg_type);
+            }
+        }
+        self
+    }
+
+    /// Whether a raw message has a MsgType the tracker cares about.  Scanning for `35=`
+    /// is far cheaper than parsing, and market data and heartbeats are most of a busy log.
+    fn tracks(&self, msg: &str) -> bool {
+        raw_msg_type(msg).is_some_and(|mt| self.msg_types.iter().any(|t| t == mt))
+    }
+
     pub fn record_message(&mut self, msg: &str, fix_override: Option<&str>) {
-        let fields = parse_fix(msg);
+        if self.tracks(msg) {
+            self.record_parsed(&ParsedMessage::parse(msg), fix_override);
+        }
+    }
+
+    /// Record a message that has already been split into fields, returning the key of
+    /// the order it was filed under, or `None` for a message the tracker skips.
+    pub fn record_parsed(
+        &mut self,
+        msg: &ParsedMessage<'_>,
+        fix_override: Option<&str>,
+    ) -> Option<String> {
+        if !self.tracks(msg.raw) {
+            return None;
+        }
+        let dict = load_dictionary_with_override(msg.raw, fix_override);
+        self.record_parsed_with_dict(msg, fix_override, &dict)
+    }
+
+    /// Record a message using an already-resolved dictionary, letting hot loops reuse
+    /// the lookup across messages that share a BeginString.
+    pub fn record_message_with_dict(
+        &mut self,
+        msg: &str,
+        fix_override: Option<&str>,
+        dict: &FixTagLookup,
+    ) {
+        if self.tracks(msg) {
+            self.record_parsed_with_dict(&ParsedMessage::parse(msg), fix_override, dict);
+        }
+    }
+
+    fn record_parsed_with_dict(
+        &mut self,
+        msg: &ParsedMessage<'_>,
+        fix_override: Option<&str>,
+        dict: &FixTagLookup,
+    ) -> Option<String> {
+        let fields = &msg.fields;
         if fields.is_empty() {
-            return;
+            return None;
         }
         if let Some(key) = fix_override {
             self.fix_override_key.get_or_insert_with(|| key.to_string());
         }
 
-        let mut map = HashMap::new();
-        for field in &fields {
-            map.insert(field.tag, field.value.clone());
-        }
+        let def = msg.first(35).and_then(|mt| dict.message_def(mt));
+        let map = body_fields(fields, def);
 
         let order_id = map.get(&37).cloned();
         let cl_ord_id = map.get(&11).cloned();
@@ -117,14 +445,13 @@ impl OrderSummary {
             cl_ord_id.as_deref(),
             orig_cl_ord_id.as_deref(),
         );
-        let dict = load_dictionary_with_override(msg, fix_override);
-        self.note_aliases(&key, order_id, cl_ord_id, orig_cl_ord_id);
+        let aliases = self.note_aliases(&key, order_id, cl_ord_id, orig_cl_ord_id);
         let record = match self.orders.entry(key.clone()) {
             Entry::Occupied(o) => o.into_mut(),
             Entry::Vacant(v) => {
                 if let Some(pos) = self.completed.iter().position(|r| r.key == key) {
                     let rec = self.completed.remove(pos);
-                    if rec.is_terminal() && self.terminal_orders > 0 {
+                    if rec.is_terminal(&self.terminal_states) && self.terminal_orders > 0 {
                         self.terminal_orders -= 1;
                     }
                     v.insert(rec)
@@ -135,24 +462,52 @@ impl OrderSummary {
             }
         };
 
+        record.aliases.extend(aliases);
         record.merge_ids(
             map.get(&37).cloned(),
             map.get(&11).cloned(),
             map.get(&41).cloned(),
         );
-        record.absorb_fields(&map, &dict, map.get(&35).map(|s| s.as_str()));
+        // A replayed execution must not roll the order's quantities back or count twice.
+        let duplicate = map.get(&35).is_some_and(|mt| mt == "8")
+            && map
+                .get(&17)
+                .is_some_and(|exec_id| !record.exec_ids.insert(exec_id.clone()));
+        if !duplicate {
+            record.absorb_fields(
+                &map,
+                fields,
+                dict,
+                map.get(&35).map(|s| s.as_str()),
+                &mut self.strings,
+            );
+        }
 
-        let event = OrderEvent::from_fields(&map, &dict);
+        let mut event = OrderEvent::from_fields(&map, dict);
+        event.duplicate = duplicate;
+        if !self.diff_tags.is_empty() && !duplicate {
+            event.diff = record.note_diff_values(&map, &self.diff_tags);
+        }
         record.events.push(event);
-        record
-            .messages
-            .push(display_with_delimiter(msg, self.display_delimiter));
+        if self.raw_messages || self.keep_open {
+            record.messages.push(msg.raw.to_string());
+        }
 
-        if record.is_terminal() {
-            self.completed.push(record.clone());
-            self.orders.remove(&key);
+        if record.is_terminal(&self.terminal_states)
+            && let Some(mut record) = self.orders.remove(&key)
+        {
+            if !self.raw_messages {
+                record.messages = Vec::new();
+            }
+            self.completed.push(record);
             self.terminal_orders += 1;
         }
+        Some(key)
+    }
+
+    /// Orders held in memory, open and completed.
+    fn held(&self) -> usize {
+        self.orders.len() + self.completed.len()
     }
 
     /// Render and clear any completed orders to allow streaming output in summary-only mode.
@@ -169,12 +524,21 @@ impl OrderSummary {
 
         for record in &self.completed {
             self.render_record(out, record)?;
-            self.render_messages(out, record)?;
+            if self.raw_messages {
+                self.render_messages(out, record)?;
+            }
         }
 
         for key in keys {
             let record = &self.orders[key];
             self.render_record(out, record)?;
+            if self.keep_open {
+                self.render_messages(out, record)?;
+            }
+        }
+
+        if let Some(group) = self.group {
+            self.render_group_totals(out, group)?;
         }
 
         let res = writeln!(
@@ -190,29 +554,62 @@ impl OrderSummary {
 
     /// Render only newly completed orders and clear them. Returns true if anything was printed.
     pub fn render_completed(&mut self, out: &mut dyn Write) -> std::io::Result<bool> {
-        if self.completed.is_empty() {
+        self.retire_completed(out, self.completed.len())
+    }
+
+    /// Under `--summary-max-orders`, render and drop the oldest completed orders until
+    /// no more than the cap are held.  Returns true if anything was printed.
+    pub fn render_over_capacity(&mut self, out: &mut dyn Write) -> std::io::Result<bool> {
+        let Some(max) = self.max_orders else {
+            return Ok(false);
+        };
+        let excess = self.held().saturating_sub(max).min(self.completed.len());
+        self.retire_completed(out, excess)
+    }
+
+    /// Render the oldest `count` completed orders, keeping only their `--summary-group`
+    /// totals.
+    fn retire_completed(&mut self, out: &mut dyn Write, count: usize) -> std::io::Result<bool> {
+        if count == 0 {
             return Ok(false);
         }
         if self.footer_width > 0 {
             write!(out, "\r{}\r", " ".repeat(self.footer_width))?;
         }
-        for record in &self.completed {
+        for record in &self.completed[..count] {
             self.render_record(out, record)?;
-            self.render_messages(out, record)?;
+            if self.raw_messages {
+                self.render_messages(out, record)?;
+            }
+        }
+        if let Some(group) = self.group {
+            for record in &self.completed[..count] {
+                self.retired_totals
+                    .entry(group.key(record))
+                    .or_default()
+                    .add(record);
+            }
         }
         self.clear_override_cache();
-        self.completed.clear();
+        for record in self.completed.drain(..count) {
+            for id in &record.aliases {
+                self.aliases.remove(id);
+            }
+        }
         out.flush()?;
         Ok(true)
     }
 
     pub fn render_footer(&mut self, out: &mut dyn Write) -> std::io::Result<()> {
-        let line = format!(
+        let mut line = format!(
             "Status: open={} filled={} total={}",
             self.orders.len(),
             self.terminal_orders,
             self.total_orders
         );
+        if let Some(max) = self.max_orders {
+            line.push_str(&format!(" held={}/{max}", self.held()));
+        }
         let width = visible_width(&line).max(self.footer_width);
         let pad = " ".repeat(width.saturating_sub(visible_width(&line)));
         write!(out, "\r{}{pad}", line)?;
@@ -221,19 +618,86 @@ impl OrderSummary {
         Ok(())
     }
 
+    /// Roll every order seen so far up by `group` and print one row per group.
+    fn render_group_totals(&self, out: &mut dyn Write, group: SummaryGroup) -> std::io::Result<()> {
+        let mut totals = self.retired_totals.clone();
+        for record in self.completed.iter().chain(self.orders.values()) {
+            totals.entry(group.key(record)).or_default().add(record);
+        }
+        if totals.is_empty() {
+            return Ok(());
+        }
+
+        let colours = palette();
+        let title = match group {
+            SummaryGroup::Symbol => "Totals by Symbol",
+            SummaryGroup::SymbolSide => "Totals by Symbol and Side",
+        };
+        writeln!(out, "{}{title}{}", colours.title, colours.reset)?;
+        let mut headers = vec!["Symbol"];
+        if group == SummaryGroup::SymbolSide {
+            headers.push("Side");
+        }
+        headers.extend([
+            "Orders", "Ordered", "Executed", "Fill %", "Cancel %", "Rejects",
+        ]);
+        let mut skipped = 0;
+        let rows: Vec<Vec<String>> = totals
+            .iter()
+            .map(|((symbol, side), row)| {
+                skipped += row.skipped_qty;
+                let mut cells = vec![symbol.clone()];
+                if group == SummaryGroup::SymbolSide {
+                    cells.push(side.clone());
+                }
+                cells.extend([
+                    row.orders.to_string(),
+                    format_number(row.ordered_qty),
+                    format_number(row.executed_qty),
+                    row.fill_rate(),
+                    row.cancel_rate(),
+                    row.rejected.to_string(),
+                ]);
+                cells
+            })
+            .collect();
+        render_table(out, &headers, &rows)?;
+        if skipped > 0 {
+            writeln!(
+                out,
+                "    {}Skipped {skipped} unparseable quantity value(s){}",
+                colours.error, colours.reset
+            )?;
+        }
+        writeln!(out)
+    }
+
     fn render_messages(&self, out: &mut dyn Write, record: &OrderRecord) -> std::io::Result<()> {
-        if record.messages.is_empty() || !record.is_terminal() {
+        if record.messages.is_empty() {
             return Ok(());
         }
         let colours = palette();
         writeln!(out, "    {}Raw FIX messages:{}", colours.tag, colours.reset)?;
         for msg in &record.messages {
+            let msg = display_with_delimiter(msg, self.display_delimiter, &self.display);
             writeln!(out, "      {}{}{}", colours.line, msg, colours.reset)?;
         }
         writeln!(out)?;
         Ok(())
     }
 
+    /// Render the summary block of the order filed under `key`, if it is still held.
+    pub fn render_order(&self, key: &str, out: &mut dyn Write) -> std::io::Result<()> {
+        let record = self
+            .orders
+            .get(key)
+            .or_else(|| self.completed.iter().find(|record| record.key == key));
+        match record {
+            Some(record) => self.render_record(out, record),
+            None => Ok(()),
+        }
+    }
+
     fn clear_override_cache(&self) {
         if let Some(key) = &self.fix_override_key {
             clear_override_cache_for(key);
@@ -243,8 +707,15 @@ impl OrderSummary {
     fn render_record(&self, out: &mut dyn Write, record: &OrderRecord) -> std::io::Result<()> {
         let colours = palette();
         render_record_header(out, record, colours)?;
-        let (headers, values) = build_summary_row(record, colours);
+        let terminal = record.is_terminal(&self.terminal_states);
+        let (headers, values) = build_summary_row(record, terminal, colours);
         render_table_row(out, &headers, &values)?;
+        if let Some(warning) = record.fill_stats().avg_px_warning(record.avg_px.as_deref()) {
+            writeln!(out, "    {}{}{}", colours.error, warning, colours.reset)?;
+        }
+        if !record.legs.is_empty() {
+            render_legs(out, &record.legs)?;
+        }
 
         writeln!(out)?;
         render_timeline(out, record, colours)?;
@@ -279,10 +750,187 @@ impl OrderSummary {
         order_id: Option<String>,
         cl_ord_id: Option<String>,
         orig: Option<String>,
-    ) {
+    ) -> Vec<String> {
+        let mut added = Vec::new();
         for id in [order_id, cl_ord_id, orig].into_iter().flatten() {
-            self.aliases.entry(id).or_insert_with(|| key.to_string());
+            if let Entry::Vacant(slot) = self.aliases.entry(id) {
+                added.push(slot.key().clone());
+                slot.insert(key.to_string());
+            }
         }
+        added
+    }
+}
+
+/// Session-level traffic gathered for `--session-summary`, one table per CompID
+/// pair: logons, logouts, heartbeats, TestRequests with round-trip times,
+/// ResendRequest ranges and SequenceResets split into GapFill and hard reset.
+#[derive(Default)]
+pub struct SessionSummary {
+    sessions: BTreeMap<(String, String), SessionStats>,
+}
+
+#[derive(Default)]
+struct SessionStats {
+    logons: usize,
+    logouts: usize,
+    heartbeats: usize,
+    test_requests: usize,
+    /// Outstanding TestRequests keyed by sender and TestReqID(112), with their
+    /// SendingTime when it parsed.
+    pending_tests: HashMap<(String, String), Option<NaiveDateTime>>,
+    /// One entry per answered TestRequest; `None` when either timestamp was unusable.
+    round_trips: Vec<Option<Duration>>,
+    resend_requests: Vec<String>,
+    gap_fills: usize,
+    resets: usize,
+    sequence_resets: Vec<String>,
+}
+
+impl SessionSummary {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record_message(&mut self, msg: &str) {
+        self.record_parsed(&ParsedMessage::parse(msg));
+    }
+
+    /// Note a session-level message; application messages are ignored.
+    pub fn record_parsed(&mut self, msg: &ParsedMessage<'_>) {
+        let Some(msg_type @ ("A" | "5" | "0" | "1" | "2" | "4")) = msg.first(35) else {
+            return;
+        };
+        let sender = msg.first(49).unwrap_or("?");
+        let target = msg.first(56).unwrap_or("?");
+        let key = if sender <= target {
+            (sender.to_string(), target.to_string())
+        } else {
+            (target.to_string(), sender.to_string())
+        };
+        let sent = msg.first(52).and_then(parse_fix_timestamp);
+        let stats = self.sessions.entry(key).or_default();
+        match msg_type {
+            "A" => stats.logons += 1,
+            "5" => stats.logouts += 1,
+            "1" => {
+                stats.test_requests += 1;
+                if let Some(id) = msg.first(112) {
+                    stats
+                        .pending_tests
+                        .insert((sender.to_string(), id.to_string()), sent);
+                }
+            }
+            "0" => {
+                stats.heartbeats += 1;
+                if let Some(id) = msg.first(112)
+                    && let Some(requested) = stats
+                        .pending_tests
+                        .remove(&(target.to_string(), id.to_string()))
+                {
+                    stats
+                        .round_trips
+                        .push(requested.zip(sent).map(|(a, b)| b - a));
+                }
+            }
+            "2" => {
+                let begin = msg.first(7).unwrap_or("?");
+                let end = match msg.first(16) {
+                    Some("0") => "infinity",
+                    other => other.unwrap_or("?"),
+                };
+                stats
+                    .resend_requests
+                    .push(format!("{sender} -> {target} {begin}-{end}"));
+            }
+            _ => {
+                let from = msg.first(34).unwrap_or("?");
+                let to = msg.first(36).unwrap_or("?");
+                if msg.first(123) == Some("Y") {
+                    stats.gap_fills += 1;
+                    stats
+                        .sequence_resets
+                        .push(format!("GapFill {sender} -> {target} {from} -> {to}"));
+                } else {
+                    stats.resets += 1;
+                    stats
+                        .sequence_resets
+                        .push(format!("Reset {sender} -> {target} to {to}"));
+                }
+            }
+        }
+    }
+
+    /// Print one table per CompID pair, followed by the individual resend and
+    /// sequence reset events.
+    pub fn render(&self, out: &mut dyn Write) -> std::io::Result<()> {
+        let colours = palette();
+        writeln!(
+            out,
+            "{}Session Summary{} ({} session(s))\n",
+            colours.title,
+            colours.reset,
+            self.sessions.len()
+        )?;
+        for ((a, b), stats) in &self.sessions {
+            writeln!(out, "  {}{a} <-> {b}{}", colours.file, colours.reset)?;
+            let headers = [
+                "Logon",
+                "Logout",
+                "Heartbeat",
+                "TestRequest",
+                "RTT",
+                "ResendRequest",
+                "SeqReset",
+            ];
+            let values = [
+                stats.logons.to_string(),
+                stats.logouts.to_string(),
+                stats.heartbeats.to_string(),
+                stats.test_requests.to_string(),
+                stats.rtt_label(),
+                stats.resend_requests.len().to_string(),
+                format!("{} GapFill, {} Reset", stats.gap_fills, stats.resets),
+            ];
+            render_table_row(out, &headers, &values)?;
+            for (label, events) in [
+                ("ResendRequest", &stats.resend_requests),
+                ("SequenceReset", &stats.sequence_resets),
+            ] {
+                for event in events {
+                    writeln!(
+                        out,
+                        "    {}{label}{}: {}",
+                        colours.name,
+                        colours.reset,
+                        colour_value(colours, event)
+                    )?;
+                }
+            }
+            writeln!(out)?;
+        }
+        Ok(())
+    }
+}
+
+impl SessionStats {
+    /// Average and maximum TestRequest round trip, with the number of answered
+    /// requests; `-` when no TestRequest was answered within the log.
+    fn rtt_label(&self) -> String {
+        let timed: Vec<Duration> = self.round_trips.iter().flatten().copied().collect();
+        let answered = self.round_trips.len();
+        if answered == 0 {
+            return "-".to_string();
+        }
+        let Some(max) = timed.iter().max() else {
+            return format!("n/a ({answered})");
+        };
+        let avg = timed.iter().copied().sum::<Duration>() / timed.len() as i32;
+        format!(
+            "avg {} max {} ({answered})",
+            format_elapsed(avg),
+            format_elapsed(*max)
+        )
     }
 }
 
@@ -306,12 +954,13 @@ fn render_record_header(
 
 fn build_summary_row(
     record: &OrderRecord,
+    terminal: bool,
     colours: crate::decoder::colours::ColourPalette,
 ) -> (Vec<&str>, Vec<String>) {
     let qty_label = record.order_qty_name.as_deref().unwrap_or("qty");
     let value_date =
         preferred_settl_date(record.settl_date.as_deref(), record.settl_date2.as_deref());
-    let date_diff = date_diff_days(record.trade_date.as_deref(), value_date);
+    let fx = record.looks_like_fx();
 
     let mut headers = vec![
         "Side",
@@ -319,9 +968,6 @@ fn build_summary_row(
         qty_label,
         "Price",
         record.trade_date_name.as_deref().unwrap_or("TradeDate"),
-        "Tenor",
-        record.tif_name.as_deref().unwrap_or("TimeInForce"),
-        record.ord_type_name.as_deref().unwrap_or("OrdType"),
     ];
     let mut values = vec![
         colour_enum_text(
@@ -336,13 +982,28 @@ fn build_summary_row(
         colour_value(colours, record.qty.as_deref().unwrap_or("-")),
         format_price(colours, record.price.as_deref(), record.currency.as_deref()),
         colour_value(colours, record.trade_date.as_deref().unwrap_or("-")),
-        format_tenor(colours, date_diff),
-        colour_enum_text(colours, record.tif_desc.as_deref().map(|s| s.to_string())),
-        colour_enum_text(
-            colours,
-            record.ord_type_desc.as_deref().map(|s| s.to_string()),
-        ),
     ];
+    if fx {
+        headers.push("Tenor");
+        values.push(format_tenor(
+            colours,
+            date_diff_days(record.trade_date.as_deref(), value_date),
+        ));
+    }
+    headers.push(record.tif_name.as_deref().unwrap_or("TimeInForce"));
+    values.push(colour_enum_text(
+        colours,
+        record.tif_desc.as_deref().map(|s| s.to_string()),
+    ));
+    if let Some(expiry) = record.expiry() {
+        headers.push("Expiry");
+        values.push(colour_value(colours, expiry));
+    }
+    headers.push(record.ord_type_name.as_deref().unwrap_or("OrdType"));
+    values.push(colour_enum_text(
+        colours,
+        record.ord_type_desc.as_deref().map(|s| s.to_string()),
+    ));
 
     if record.bn_seen {
         headers.push(record.spot_rate_name.as_deref().unwrap_or("SpotPrice"));
@@ -355,12 +1016,112 @@ fn build_summary_row(
         values.push(colour_value(colours, exec_amt.unwrap_or("-")));
     }
 
-    headers.push(settlement_header(record));
-    values.push(colour_value(colours, value_date.unwrap_or("-")));
+    if fx {
+        headers.push(settlement_header(record));
+        values.push(colour_value(colours, value_date.unwrap_or("-")));
+    }
+
+    let stats = record.fill_stats();
+    if stats.fills > 0 {
+        headers.extend(["Fills", "ExecQty", "VWAP"]);
+        values.push(colour_value(colours, &stats.fills.to_string()));
+        values.push(colour_value(colours, &format_number(stats.exec_qty)));
+        values.push(colour_value(
+            colours,
+            &stats
+                .vwap()
+                .map(format_number)
+                .unwrap_or_else(|| "-".into()),
+        ));
+    }
+    if let Some(elapsed) = terminal.then(|| record.new_to_last()).flatten() {
+        headers.push("Elapsed");
+        values.push(colour_value(colours, &format_elapsed(elapsed)));
+    }
 
     (headers, values)
 }
 
+/// The first value of each tag that is not inside one of the message's repeating
+/// groups, so a SettlDate(64) or OrderQty(38) carried by a party or leg entry cannot
+/// stand in for the order's own.  A group runs from its NumInGroup field for as long
+/// as the tags that follow belong to it, nested groups included.  Without a message
+/// definition every field counts.
+fn body_fields(fields: &[FieldValue], def: Option<&MessageDef>) -> HashMap<u32, String> {
+    let mut map = HashMap::new();
+    let mut open: Option<&GroupSpec> = None;
+    for field in fields {
+        if let Some(spec) = open
+            && def.is_some_and(|def| def.group_membership.contains_key(&field.tag))
+            && group_holds(spec, field.tag)
+        {
+            continue;
+        }
+        open = def.and_then(|def| def.groups.get(&field.tag));
+        map.entry(field.tag).or_insert_with(|| field.value.clone());
+    }
+    map
+}
+
+/// Whether `tag` belongs to an entry of `spec` or of a group nested in it.
+fn group_holds(spec: &GroupSpec, tag: u32) -> bool {
+    spec.entry_tag_set.contains(&tag) || spec.nested.values().any(|n| group_holds(n, tag))
+}
+
+/// Read the NoLegs (555) group from a message's fields in order.  Each leg starts at
+/// LegSymbol (600) and takes the LegSide (624), LegQty (687) and LegSettlDate (588)
+/// that follow it; a LegSymbol beyond the declared count is not part of the group.
+/// `None` when the message has no NoLegs.
+fn scan_legs(ordered: &[FieldValue]) -> Option<Vec<LegInfo>> {
+    let start = ordered.iter().position(|f| f.tag == 555)?;
+    let count: usize = ordered[start].value.parse().unwrap_or(0);
+    let mut legs: Vec<LegInfo> = Vec::with_capacity(count.min(16));
+    for field in &ordered[start + 1..] {
+        let slot = match field.tag {
+            600 if legs.len() == count => break,
+            600 => {
+                legs.push(LegInfo::default());
+                legs.last_mut().map(|leg| &mut leg.symbol)
+            }
+            624 => legs.last_mut().map(|leg| &mut leg.side),
+            687 => legs.last_mut().map(|leg| &mut leg.qty),
+            588 => legs.last_mut().map(|leg| &mut leg.settl_date),
+            555 | 10 => break,
+            _ => None,
+        };
+        if let Some(slot) = slot {
+            slot.get_or_insert_with(|| field.value.clone());
+        }
+    }
+    Some(legs)
+}
+
+/// The legs of a multi-leg order as a table indented under its summary row.
+fn render_legs(out: &mut dyn Write, legs: &[LegInfo]) -> std::io::Result<()> {
+    let dash = |value: &Option<String>| value.clone().unwrap_or_else(|| "-".into());
+    let rows: Vec<Vec<String>> = legs
+        .iter()
+        .enumerate()
+        .map(|(idx, leg)| {
+            vec![
+                (idx + 1).to_string(),
+                dash(&leg.symbol),
+                leg.side
+                    .as_deref()
+                    .map_or("-".into(), |side| side_label(side).to_ascii_uppercase()),
+                dash(&leg.qty),
+                dash(&leg.settl_date),
+            ]
+        })
+        .collect();
+    render_table_indented(
+        out,
+        "      ",
+        &["Leg", "LegSymbol", "LegSide", "LegQty", "LegSettlDate"],
+        &rows,
+    )
+}
+
 fn settlement_header(record: &OrderRecord) -> &str {
     if record.settl_date2.is_some() {
         record.settl_date2_name.as_deref().unwrap_or("SettlDate2")
@@ -482,14 +1243,41 @@ fn build_timeline_cells(
         &colour_value(colours, event.avg_px.as_deref().unwrap_or("-")),
         10,
     ));
-    cells.push(pad_ansi(
-        &colour_text(colours, event.text.as_deref().unwrap_or("")),
-        0,
-    ));
+    let text = match (format_diff(&event.diff), event.text.as_deref()) {
+        (Some(diff), Some(text)) => {
+            format!(
+                "{} {}",
+                colour_value(colours, &diff),
+                colour_text(colours, text)
+            )
+        }
+        (Some(diff), None) => colour_value(colours, &diff),
+        (None, text) => colour_text(colours, text.unwrap_or("")),
+    };
+    cells.push(pad_ansi(&text, 0));
 
     cells
 }
 
+/// `38:100→150 44:1.10→1.12`, with `?` for a value not seen before the replace.
+fn format_diff(diff: &[FieldChange]) -> Option<String> {
+    if diff.is_empty() {
+        return None;
+    }
+    let parts: Vec<String> = diff
+        .iter()
+        .map(|change| {
+            format!(
+                "{}:{}→{}",
+                change.tag,
+                change.old.as_deref().unwrap_or("?"),
+                change.new
+            )
+        })
+        .collect();
+    Some(parts.join(" "))
+}
+
 fn flow_label(states: &[String]) -> String {
     if states.is_empty() {
         return "Unknown".to_string();
@@ -524,6 +1312,8 @@ impl OrderRecord {
             trade_date: None,
             settl_date: None,
             settl_date2: None,
+            expire_time: None,
+            expire_date: None,
             currency: None,
             ord_type_desc: None,
             tif_desc: None,
@@ -533,6 +1323,10 @@ impl OrderRecord {
             last_qty: None,
             bn_seen: false,
             bn_exec_amt: None,
+            exec_ids: HashSet::new(),
+            aliases: Vec::new(),
+            diff_values: HashMap::new(),
+            legs: Vec::new(),
             order_qty_name: None,
             cum_qty_name: None,
             leaves_qty_name: None,
@@ -549,19 +1343,45 @@ impl OrderRecord {
         }
     }
 
-    fn is_terminal(&self) -> bool {
+    /// Compare a cancel/replace request (G) or Replaced execution (150=5) against the
+    /// values last accepted for `tags`, then remember the values an order or execution
+    /// carries.  A request is only pending, so it leaves the accepted values alone.
+    fn note_diff_values(
+        &mut self,
+        fields: &HashMap<u32, String>,
+        tags: &[u32],
+    ) -> Vec<FieldChange> {
+        let msg_type = fields.get(&35).map(String::as_str);
+        let replaced = msg_type == Some("G")
+            || (msg_type == Some("8") && fields.get(&150).is_some_and(|et| et == "5"));
+        let changes = if replaced {
+            tags.iter()
+                .filter_map(|tag| {
+                    let new = fields.get(tag)?;
+                    let old = self.diff_values.get(tag);
+                    (old != Some(new)).then(|| FieldChange {
+                        tag: *tag,
+                        old: old.cloned(),
+                        new: new.clone(),
+                    })
+                })
+                .collect()
+        } else {
+            Vec::new()
+        };
+        if matches!(msg_type, Some("D" | "8")) {
+            for tag in tags {
+                if let Some(value) = fields.get(tag) {
+                    self.diff_values.insert(*tag, value.clone());
+                }
+            }
+        }
+        changes
+    }
+
+    fn is_terminal(&self, terminal_states: &[String]) -> bool {
         if let Some(state) = self.state_path().last()
-            && matches!(
-                state.as_str(),
-                "Filled"
-                    | "Canceled"
-                    | "Rejected"
-                    | "Done for Day"
-                    | "Expired"
-                    | "Stopped"
-                    | "Suspended"
-                    | "Calculated"
-            )
+            && terminal_states.iter().any(|t| t == state)
         {
             return true;
         }
@@ -596,23 +1416,39 @@ impl OrderRecord {
         }
     }
 
+    /// Take the latest values from a message.  `fields` holds the first value of each
+    /// tag outside repeating groups, so legs are read from the message's `ordered` fields.
     fn absorb_fields(
         &mut self,
         fields: &HashMap<u32, String>,
+        ordered: &[FieldValue],
         dict: &FixTagLookup,
         msg_type: Option<&str>,
+        strings: &mut Interner,
     ) {
-        self.copy_core_fields(fields, dict);
+        self.copy_core_fields(fields, dict, strings);
         self.copy_enum_fields(fields, dict);
         self.copy_trade_and_settlement(fields, dict);
         if msg_type == Some("BN") {
             self.absorb_block_notice(fields, dict);
         }
+        if let Some(legs) = scan_legs(ordered) {
+            self.legs = legs;
+        }
     }
 
-    fn copy_core_fields(&mut self, fields: &HashMap<u32, String>, dict: &FixTagLookup) {
-        Self::set_value(&mut self.symbol, fields.get(&55));
-        Self::set_value(&mut self.side, fields.get(&54));
+    fn copy_core_fields(
+        &mut self,
+        fields: &HashMap<u32, String>,
+        dict: &FixTagLookup,
+        strings: &mut Interner,
+    ) {
+        if let Some(symbol) = fields.get(&55) {
+            self.symbol = Some(strings.intern(symbol));
+        }
+        if let Some(side) = fields.get(&54) {
+            self.side = Some(strings.intern(side));
+        }
         Self::set_named_field(&mut self.qty, &mut self.order_qty_name, fields, dict, 38);
         Self::set_value(&mut self.currency, fields.get(&15));
         Self::set_value(&mut self.last_qty, fields.get(&32));
@@ -680,6 +1516,37 @@ impl OrderRecord {
             self.settl_date2_name
                 .get_or_insert_with(|| dict.field_name(193));
         }
+        Self::set_value(&mut self.expire_time, fields.get(&126));
+        Self::set_value(&mut self.expire_date, fields.get(&432));
+    }
+
+    /// The Expiry column: ExpireTime(126), or else ExpireDate(432), for a GTD order and
+    /// `GTC` for a GTC one.  Other orders have no expiry to show.
+    fn expiry(&self) -> Option<&str> {
+        match self.tif_code.as_deref()? {
+            "6" => Some(
+                self.expire_time
+                    .as_deref()
+                    .or(self.expire_date.as_deref())
+                    .unwrap_or("-"),
+            ),
+            "1" => Some("GTC"),
+            _ => None,
+        }
+    }
+
+    /// Whether FX value-date conventions apply: the order carries a settlement date,
+    /// or its symbol is a currency pair such as `EUR/USD`.
+    fn looks_like_fx(&self) -> bool {
+        self.settl_date.is_some()
+            || self.settl_date2.is_some()
+            || self.symbol.as_deref().is_some_and(|symbol| {
+                symbol.split_once('/').is_some_and(|(base, quote)| {
+                    [base, quote]
+                        .iter()
+                        .all(|ccy| ccy.len() == 3 && ccy.chars().all(|c| c.is_ascii_alphabetic()))
+                })
+            })
     }
 
     fn absorb_block_notice(&mut self, fields: &HashMap<u32, String>, dict: &FixTagLookup) {
@@ -734,7 +1601,7 @@ impl OrderRecord {
 
     fn state_path(&self) -> Vec<String> {
         let mut states = Vec::new();
-        for ev in &self.events {
+        for ev in self.events.iter().filter(|ev| !ev.duplicate) {
             if let Some(last) = states.last()
                 && last == &ev.state
             {
@@ -745,6 +1612,43 @@ impl OrderRecord {
         states
     }
 
+    /// Aggregate the executions reported for this order. Trade corrections and
+    /// cancels (ExecType G/H) are skipped so busts do not count as fills.
+    fn fill_stats(&self) -> FillStats {
+        let mut stats = FillStats::default();
+        for ev in &self.events {
+            if ev.msg_type.as_deref() != Some("8")
+                || ev.duplicate
+                || matches!(ev.exec_type.as_deref(), Some("G" | "H"))
+            {
+                continue;
+            }
+            let Some(qty) = parse_number(ev.last_qty.as_deref()).filter(|q| *q > 0.0) else {
+                if ev.last_qty.is_some() && parse_number(ev.last_qty.as_deref()).is_none() {
+                    stats.unparsed_qty += 1;
+                }
+                continue;
+            };
+            stats.fills += 1;
+            stats.exec_qty += qty;
+            if let Some(px) = parse_number(ev.last_px.as_deref()) {
+                stats.priced_qty += qty;
+                stats.notional += qty * px;
+            }
+        }
+        stats
+    }
+
+    /// Time from the first New to the last message, when both carry a timestamp.
+    /// Only meaningful once the order is terminal.
+    fn new_to_last(&self) -> Option<Duration> {
+        let terminal = self.events.last()?;
+        let new = self.events.iter().find(|ev| ev.state == "New")?;
+        let start = parse_fix_timestamp(new.time.as_deref()?)?;
+        let end = parse_fix_timestamp(terminal.time.as_deref()?)?;
+        Some(end - start)
+    }
+
     fn display_id(&self) -> String {
         if let Some(order_id) = &self.order_id {
             return order_id.clone();
@@ -783,7 +1687,7 @@ impl OrderEvent {
             msg_type: fields.get(&35).cloned(),
             msg_type_desc: fields
                 .get(&35)
-                .and_then(|mt| dict.enum_description(35, mt).map(|d| d.to_string())),
+                .and_then(|mt| dict.msg_type_label(mt).map(str::to_string)),
             exec_type,
             ord_status,
             exec_ack_status,
@@ -796,6 +1700,8 @@ impl OrderEvent {
             text: fields.get(&58).cloned(),
             cl_ord_id: fields.get(&11).cloned(),
             orig_cl_ord_id: fields.get(&41).cloned(),
+            duplicate: false,
+            diff: Vec::new(),
         }
     }
 
@@ -937,6 +1843,53 @@ fn colour_value(colours: crate::decoder::colours::ColourPalette, value: &str) ->
     format!("{}{}{}", colours.value, value, colours.reset)
 }
 
+fn parse_number(value: Option<&str>) -> Option<f64> {
+    value?.trim().parse::<f64>().ok().filter(|v| v.is_finite())
+}
+
+/// Render a quantity or price without float noise or trailing zeros.
+fn format_number(value: f64) -> String {
+    let text = format!("{value:.8}");
+    let text = text.trim_end_matches('0').trim_end_matches('.');
+    if text == "-0" { "0" } else { text }.to_string()
+}
+
+/// The MsgType (35) of a raw SOH-delimited message, found without splitting it.
+fn raw_msg_type(msg: &str) -> Option<&str> {
+    let start = if msg.starts_with("35=") {
+        3
+    } else {
+        msg.find("\u{0001}35=")? + 4
+    };
+    msg[start..].split('\u{0001}').next()
+}
+
+/// Render a ratio as a percentage with one decimal place.
+fn format_percent(ratio: f64) -> String {
+    format!("{:.1}%", ratio * 100.0)
+}
+
+/// Parse a UTCTimestamp such as `20250101-12:00:00.123`.
+pub(crate) fn parse_fix_timestamp(value: &str) -> Option<NaiveDateTime> {
+    NaiveDateTime::parse_from_str(value, "%Y%m%d-%H:%M:%S%.f").ok()
+}
+
+pub(crate) fn format_elapsed(elapsed: Duration) -> String {
+    let millis = elapsed.num_milliseconds();
+    let sign = if millis < 0 { "-" } else { "" };
+    let millis = millis.unsigned_abs();
+    let (hours, rest) = (millis / 3_600_000, millis % 3_600_000);
+    let (mins, rest) = (rest / 60_000, rest % 60_000);
+    let (secs, ms) = (rest / 1000, rest % 1000);
+    if hours > 0 {
+        format!("{sign}{hours}h{mins:02}m{secs:02}.{ms:03}s")
+    } else if mins > 0 {
+        format!("{sign}{mins}m{secs:02}.{ms:03}s")
+    } else {
+        format!("{sign}{secs}.{ms:03}s")
+    }
+}
+
 fn colour_text(colours: crate::decoder::colours::ColourPalette, value: &str) -> String {
     if value.is_empty() {
         return format!("{}-{}", colours.name, colours.reset);
@@ -990,6 +1943,12 @@ fn format_msg_cell(colours: crate::decoder::colours::ColourPalette, ev: &OrderEv
         format!("{}-{}", colours.error, colours.reset)
     };
 
+    let base = if ev.duplicate {
+        format!("{base} {}dup{}", colours.error, colours.reset)
+    } else {
+        base
+    };
+
     let mut ids = Vec::new();
     if let Some(cl) = ev.cl_ord_id.as_deref() {
         ids.push(format!("{}{}{}", colours.value, cl, colours.reset));
@@ -1021,20 +1980,31 @@ fn format_tenor(colours: crate::decoder::colours::ColourPalette, diff: Option<i6
     )
 }
 
-fn display_with_delimiter(msg: &str, delimiter: char) -> String {
-    const SOH: char = '\u{0001}';
-    if delimiter == SOH {
-        return msg.to_string();
+/// Show a stored raw message with `delimiter` in place of SOH, applying the display
+/// rules for values (`--max-value-width`, `\xNN` escapes) to each field and showing a
+/// value's own copy of the delimiter as `\|` unless `--no-escape`.
+fn display_with_delimiter(msg: &str, delimiter: char, display: &DisplayOptions) -> String {
+    let mut out = String::with_capacity(msg.len());
+    for (idx, field) in msg.split('\u{0001}').enumerate() {
+        if idx > 0 {
+            out.push(delimiter);
+        }
+        match field.split_once('=') {
+            Some((tag, value)) => {
+                out.push_str(tag);
+                out.push('=');
+                out.push_str(&display.escape_delimiter(&display.display_value(value), delimiter));
+            }
+            None => out.push_str(field),
+        }
     }
-    msg.chars()
-        .map(|c| if c == SOH { delimiter } else { c })
-        .collect()
+    out
 }
 
 /// Compute business-day diff skipping only weekends (no holiday calendar).
 fn date_diff_days(trade: Option<&str>, settl: Option<&str>) -> Option<i64> {
-    let trade = NaiveDate::parse_from_str(trade?, "%Y%m%d").ok()?;
-    let settl = NaiveDate::parse_from_str(settl?, "%Y%m%d").ok()?;
+    let trade = parse_date(trade?)?;
+    let settl = parse_date(settl?)?;
     if settl < trade {
         return None;
     }
@@ -1057,6 +2027,15 @@ fn is_business_day(date: NaiveDate) -> bool {
     !matches!(date.weekday(), chrono::Weekday::Sat | chrono::Weekday::Sun)
 }
 
+/// The date of a `YYYYMMDD` value, a timestamp starting with one, or a
+/// `YYYY-MM-DD` date as some venues send.
+fn parse_date(value: &str) -> Option<NaiveDate> {
+    if let Some(date) = extract_date_part(value) {
+        return NaiveDate::parse_from_str(&date, "%Y%m%d").ok();
+    }
+    NaiveDate::parse_from_str(value.get(..10)?, "%Y-%m-%d").ok()
+}
+
 fn extract_date_part(ts: &str) -> Option<String> {
     if ts.len() >= 8 && ts.chars().take(8).all(|c| c.is_ascii_digit()) {
         return Some(ts.chars().take(8).collect());
@@ -1106,6 +2085,52 @@ fn render_table_row(
     writeln!(out)
 }
 
+/// Print a header row and aligned rows beneath it, each column as wide as its widest cell.
+pub(crate) fn render_table(
+    out: &mut dyn Write,
+    headers: &[&str],
+    rows: &[Vec<String>],
+) -> std::io::Result<()> {
+    render_table_indented(out, "    ", headers, rows)
+}
+
+fn render_table_indented(
+    out: &mut dyn Write,
+    indent: &str,
+    headers: &[&str],
+    rows: &[Vec<String>],
+) -> std::io::Result<()> {
+    let colours = palette();
+    let widths: Vec<usize> = headers
+        .iter()
+        .enumerate()
+        .map(|(i, head)| {
+            rows.iter()
+                .filter_map(|row| row.get(i))
+                .map(|cell| visible_width(cell))
+                .chain([visible_width(head)])
+                .max()
+                .unwrap_or(0)
+                + 2
+        })
+        .collect();
+
+    write!(out, "{indent}")?;
+    for (head, width) in headers.iter().zip(&widths) {
+        let coloured = format!("{}{}{}", colours.name, head, colours.reset);
+        write!(out, "{} ", pad_ansi(&coloured, *width))?;
+    }
+    writeln!(out)?;
+    for row in rows {
+        write!(out, "{indent}")?;
+        for (cell, width) in row.iter().zip(&widths) {
+            write!(out, "{} ", pad_ansi(&colour_value(colours, cell), *width))?;
+        }
+        writeln!(out)?;
+    }
+    Ok(())
+}
+
 fn colour_instrument(text: String) -> String {
     let colours = palette();
     // Apply value/yellow tone to side+symbol for parity with decoded FIX fields.
@@ -1294,6 +2319,70 @@ mod tests {
         assert!(text.contains("XYZ"), "order id should be present: {text}");
     }
 
+    #[test]
+    fn multileg_fx_swap_lists_each_leg_under_the_summary_row() {
+        crate::decoder::colours::disable_colours();
+        let mut summary = OrderSummary::new('\u{0001}');
+        summary.record_message(
+            &msg(&[
+                ("8", "FIX.4.4"),
+                ("35", "AB"),
+                ("11", "SWAP1"),
+                ("55", "EUR/USD"),
+                ("555", "2"),
+                ("600", "EUR/USD"),
+                ("624", "1"),
+                ("687", "1000000"),
+                ("588", "20250103"),
+                ("600", "EUR/USD"),
+                ("624", "2"),
+                ("687", "1000000"),
+                ("588", "20250203"),
+                ("600", "NOT-A-LEG"),
+                ("10", "000"),
+            ]),
+            None,
+        );
+
+        let record = &summary.orders["SWAP1"];
+        let leg = |side: &str, date: &str| LegInfo {
+            symbol: Some("EUR/USD".into()),
+            side: Some(side.into()),
+            qty: Some("1000000".into()),
+            settl_date: Some(date.into()),
+        };
+        assert_eq!(
+            record.legs,
+            vec![leg("1", "20250103"), leg("2", "20250203")],
+            "a LegSymbol beyond NoLegs=2 is not a leg"
+        );
+
+        let mut buf = Vec::new();
+        summary.render(&mut buf).unwrap();
+        let text = String::from_utf8(buf).unwrap();
+        let rows: Vec<Vec<&str>> = text
+            .lines()
+            .skip_while(|line| !line.contains("LegSymbol"))
+            .take(3)
+            .map(|line| line.split_whitespace().collect())
+            .collect();
+        assert_eq!(
+            rows,
+            [
+                vec!["Leg", "LegSymbol", "LegSide", "LegQty", "LegSettlDate"],
+                vec!["1", "EUR/USD", "BUY", "1000000", "20250103"],
+                vec!["2", "EUR/USD", "SELL", "1000000", "20250203"],
+            ],
+            "{text}"
+        );
+        assert!(
+            text.lines()
+                .find(|line| line.contains("LegSymbol"))
+                .is_some_and(|line| line.starts_with("      Leg")),
+            "legs are indented under the summary row: {text}"
+        );
+    }
+
     #[test]
     fn bn_message_sets_state_and_spot_price() {
         let mut summary = OrderSummary::new('\u{0001}');
@@ -1378,7 +2467,7 @@ mod tests {
         fields.insert(151u32, "90".to_string());
         fields.insert(6u32, "12.3".to_string());
         fields.insert(44u32, "15.0".to_string());
-        record.absorb_fields(&fields, &dict, Some("D"));
+        record.absorb_fields(&fields, &[], &dict, Some("D"), &mut Interner::default());
         assert_eq!(record.symbol.as_deref(), Some("AAPL"));
         assert_eq!(record.qty.as_deref(), Some("100"));
         assert_eq!(record.cum_qty_name.as_deref(), Some("CumQty"));
@@ -1395,12 +2484,85 @@ mod tests {
         let mut fields = HashMap::new();
         fields.insert(31u32, "1.2345".to_string());
         fields.insert(38u32, "500".to_string());
-        record.absorb_fields(&fields, &dict, Some("BN"));
+        record.absorb_fields(&fields, &[], &dict, Some("BN"), &mut Interner::default());
         assert!(record.bn_seen);
         assert_eq!(record.spot_rate.as_deref(), Some("1.2345"));
         assert_eq!(record.bn_exec_amt.as_deref(), Some("500"));
     }
 
+    #[test]
+    fn fields_inside_repeating_groups_do_not_override_the_order_s_own() {
+        let xml = r#"
+<fix type='FIX' major='4' minor='4'>
+  <header><field name='BeginString' required='Y'/></header>
+  <trailer><field name='CheckSum' required='Y'/></trailer>
+  <messages>
+    <message name='ExecutionReport' msgtype='8' msgcat='app'>
+      <field name='OrderID'/>
+      <field name='ClOrdID'/>
+      <field name='OrderQty'/>
+      <field name='SettlDate'/>
+      <group name='NoPartyIDs'>
+        <field name='PartyID'/>
+        <field name='OrderQty'/>
+      </group>
+      <group name='NoLegs'>
+        <field name='LegSymbol'/>
+        <field name='SettlDate'/>
+      </group>
+      <field name='Symbol'/>
+    </message>
+  </messages>
+  <components/>
+  <fields>
+    <field number='8' name='BeginString' type='STRING'/>
+    <field number='10' name='CheckSum' type='STRING'/>
+    <field number='11' name='ClOrdID' type='STRING'/>
+    <field number='35' name='MsgType' type='STRING'/>
+    <field number='37' name='OrderID' type='STRING'/>
+    <field number='38' name='OrderQty' type='QTY'/>
+    <field number='55' name='Symbol' type='STRING'/>
+    <field number='64' name='SettlDate' type='LOCALMKTDATE'/>
+    <field number='448' name='PartyID' type='STRING'/>
+    <field number='453' name='NoPartyIDs' type='NUMINGROUP'/>
+    <field number='555' name='NoLegs' type='NUMINGROUP'/>
+    <field number='600' name='LegSymbol' type='STRING'/>
+  </fields>
+</fix>
+"#;
+        let dict = crate::decoder::FixDictionary::from_xml(xml).expect("dictionary parses");
+        let dict = FixTagLookup::from_dictionary(&dict, "TEST");
+        let raw = msg(&[
+            ("8", "FIX.4.4"),
+            ("35", "8"),
+            ("37", "E1"),
+            ("11", "O1"),
+            ("38", "100"),
+            ("64", "20250103"),
+            ("453", "1"),
+            ("448", "BROKER"),
+            ("38", "999"),
+            ("555", "1"),
+            ("600", "EUR/USD"),
+            ("64", "20991231"),
+            ("55", "EUR/USD"),
+            ("10", "000"),
+        ]);
+        let parsed = ParsedMessage::parse(&raw);
+        let fields = body_fields(&parsed.fields, dict.message_def("8"));
+        assert_eq!(fields.get(&38).map(String::as_str), Some("100"));
+        assert_eq!(fields.get(&64).map(String::as_str), Some("20250103"));
+        assert_eq!(fields.get(&55).map(String::as_str), Some("EUR/USD"));
+        assert_eq!(fields.get(&453).map(String::as_str), Some("1"));
+        assert!(!fields.contains_key(&448) && !fields.contains_key(&600));
+
+        let mut summary = OrderSummary::new('\u{0001}');
+        summary.record_message_with_dict(&raw, None, &dict);
+        let record = &summary.orders["E1"];
+        assert_eq!(record.qty.as_deref(), Some("100"));
+        assert_eq!(record.settl_date.as_deref(), Some("20250103"));
+    }
+
     #[test]
     fn flow_label_skips_leading_unknown() {
         let states = [
@@ -1419,11 +2581,559 @@ mod tests {
         record.bn_seen = true;
         record.spot_rate = Some("1.25".into());
         record.bn_exec_amt = Some("1000".into());
-        let (headers, values) = build_summary_row(&record, colours);
+        let (headers, values) = build_summary_row(&record, false, colours);
         assert!(headers.contains(&"ExecAmt"));
         assert!(values.iter().any(|v| v.contains("1.25")));
     }
 
+    /// The summary table headers and values of a single NewOrderSingle with `extra` fields.
+    fn new_order_row(extra: &[(&str, &str)]) -> (Vec<String>, Vec<String>) {
+        let mut fields = vec![("35", "D"), ("11", "ROW1"), ("54", "1"), ("38", "100")];
+        fields.extend_from_slice(extra);
+        let mut summary = OrderSummary::new('|');
+        summary.record_message(&msg(&fields), None);
+        let record = &summary.orders["ROW1"];
+        let (headers, values) = build_summary_row(record, false, palette());
+        (headers.into_iter().map(String::from).collect(), values)
+    }
+
+    #[test]
+    fn gtd_equity_order_shows_its_expiry_and_no_fx_columns() {
+        let (headers, values) = new_order_row(&[
+            ("55", "VOD.L"),
+            ("59", "6"),
+            ("126", "20250110-16:30:00"),
+            ("75", "20250102"),
+        ]);
+        assert_eq!(
+            headers,
+            [
+                "Side",
+                "Symbol",
+                "OrderQty",
+                "Price",
+                "TradeDate",
+                "TimeInForce",
+                "Expiry",
+                "OrdType"
+            ]
+        );
+        assert!(values[6].contains("20250110-16:30:00"), "{values:?}");
+
+        let (_, values) = new_order_row(&[("55", "VOD.L"), ("59", "6"), ("432", "20250110")]);
+        assert!(values[6].contains("20250110"), "{values:?}");
+    }
+
+    #[test]
+    fn gtc_order_shows_gtc_as_its_expiry() {
+        let (headers, values) = new_order_row(&[("55", "AAPL"), ("59", "1")]);
+        let expiry = headers.iter().position(|h| h == "Expiry").unwrap();
+        assert!(values[expiry].contains("GTC"), "{values:?}");
+        assert!(!headers.iter().any(|h| h == "Tenor"), "{headers:?}");
+
+        let (headers, _) = new_order_row(&[("55", "AAPL"), ("59", "0")]);
+        assert!(!headers.iter().any(|h| h == "Expiry"), "{headers:?}");
+    }
+
+    #[test]
+    fn fx_spot_order_shows_tenor_and_value_date() {
+        let (headers, values) = new_order_row(&[
+            ("55", "EUR/USD"),
+            ("59", "0"),
+            ("60", "20250102-10:00:00"),
+            ("64", "2025-01-06"),
+        ]);
+        assert_eq!(
+            headers,
+            [
+                "Side",
+                "Symbol",
+                "OrderQty",
+                "Price",
+                "TradeDate",
+                "Tenor",
+                "TimeInForce",
+                "OrdType",
+                "SettlDate"
+            ]
+        );
+        assert!(
+            values[5].contains("T+2") && values[5].contains("SPOT"),
+            "{values:?}"
+        );
+
+        // A currency pair without a settlement date still gets the FX columns.
+        let (headers, _) = new_order_row(&[("55", "GBP/JPY")]);
+        assert!(headers.iter().any(|h| h == "Tenor"), "{headers:?}");
+    }
+
+    #[test]
+    fn date_diff_days_accepts_same_day_and_dashed_dates() {
+        assert_eq!(date_diff_days(Some("20250102"), Some("20250102")), Some(0));
+        assert_eq!(
+            date_diff_days(Some("20250102-09:00:00"), Some("2025-01-03")),
+            Some(1)
+        );
+        assert_eq!(date_diff_days(Some("2025-01-03"), Some("2025-01-02")), None);
+    }
+
+    fn record_fills(summary: &mut OrderSummary, avg_px: &str) {
+        summary.record_message(
+            &msg(&[
+                ("35", "8"),
+                ("37", "F1"),
+                ("150", "0"),
+                ("39", "0"),
+                ("60", "20250101-12:00:00.000"),
+            ]),
+            None,
+        );
+        summary.record_message(
+            &msg(&[
+                ("35", "8"),
+                ("37", "F1"),
+                ("150", "F"),
+                ("39", "1"),
+                ("32", "40"),
+                ("31", "10.00"),
+                ("60", "20250101-12:00:01.250"),
+            ]),
+            None,
+        );
+        summary.record_message(
+            &msg(&[
+                ("35", "8"),
+                ("37", "F1"),
+                ("150", "F"),
+                ("39", "2"),
+                ("32", "60"),
+                ("31", "10.10"),
+                ("6", avg_px),
+                ("60", "20250101-12:01:02.500"),
+            ]),
+            None,
+        );
+    }
+
+    #[test]
+    fn replayed_executions_are_marked_dup_and_not_counted_twice() {
+        let fill = |exec_id: &str, last: &str, cum: &str, status: &str, extra: &[(&str, &str)]| {
+            let mut fields = vec![
+                ("35", "8"),
+                ("37", "R1"),
+                ("17", exec_id),
+                ("150", "F"),
+                ("39", status),
+                ("32", last),
+                ("31", "10"),
+                ("14", cum),
+            ];
+            fields.extend_from_slice(extra);
+            msg(&fields)
+        };
+        let mut summary = OrderSummary::new('|');
+        for message in [
+            fill("E1", "40", "40", "1", &[]),
+            fill("E1", "40", "40", "1", &[("43", "Y")]),
+            fill("E2", "60", "100", "2", &[]),
+            fill("E1", "40", "40", "1", &[("43", "Y")]),
+        ] {
+            summary.record_message(&message, None);
+        }
+
+        let record = summary.completed.first().expect("order completed");
+        let stats = record.fill_stats();
+        assert_eq!((stats.fills, stats.exec_qty), (2, 100.0));
+        assert_eq!(record.cum_qty.as_deref(), Some("100"));
+        let dups: Vec<bool> = record.events.iter().map(|ev| ev.duplicate).collect();
+        assert_eq!(dups, vec![false, true, false, true]);
+
+        let mut out = Vec::new();
+        render_timeline(&mut out, record, palette()).unwrap();
+        let timeline = String::from_utf8(out).unwrap();
+        assert_eq!(timeline.matches("dup").count(), 2, "{timeline}");
+    }
+
+    #[test]
+    fn aggregates_fills_vwap_and_elapsed_time() {
+        let mut summary = OrderSummary::new('|');
+        record_fills(&mut summary, "10.06");
+        let record = summary.completed.first().expect("order completed");
+        let stats = record.fill_stats();
+        assert_eq!(stats.fills, 2);
+        assert_eq!(format_number(stats.exec_qty), "100");
+        assert_eq!(stats.vwap().map(format_number).as_deref(), Some("10.06"));
+        assert!(stats.avg_px_warning(record.avg_px.as_deref()).is_none());
+        assert_eq!(
+            record.new_to_last().map(format_elapsed).as_deref(),
+            Some("1m02.500s")
+        );
+
+        let (headers, values) = build_summary_row(record, true, palette());
+        for head in ["Fills", "ExecQty", "VWAP", "Elapsed"] {
+            assert!(headers.contains(&head), "missing {head}: {headers:?}");
+        }
+        assert!(values.iter().any(|v| v.contains("1m02.500s")));
+    }
+
+    #[test]
+    fn warns_when_vwap_disagrees_with_avg_px() {
+        let mut summary = OrderSummary::new('|');
+        record_fills(&mut summary, "10.07");
+        let mut buf = Vec::new();
+        summary.render(&mut buf).unwrap();
+        let text = String::from_utf8(buf).unwrap();
+        assert!(
+            text.contains("VWAP 10.06 of fills differs from AvgPx 10.07"),
+            "expected tolerance warning: {text}"
+        );
+    }
+
+    #[test]
+    fn cancel_replace_shows_the_fields_it_changed() {
+        let replace_sequence = |summary: &mut OrderSummary| {
+            summary.record_message(
+                &msg(&[
+                    ("35", "D"),
+                    ("11", "A"),
+                    ("55", "EURUSD"),
+                    ("38", "100"),
+                    ("44", "1.10"),
+                    ("40", "2"),
+                    ("59", "0"),
+                ]),
+                None,
+            );
+            summary.record_message(
+                &msg(&[
+                    ("35", "G"),
+                    ("11", "B"),
+                    ("41", "A"),
+                    ("38", "150"),
+                    ("44", "1.12"),
+                    ("40", "2"),
+                ]),
+                None,
+            );
+            summary.record_message(
+                &msg(&[
+                    ("35", "8"),
+                    ("37", "O1"),
+                    ("11", "B"),
+                    ("41", "A"),
+                    ("150", "5"),
+                    ("39", "0"),
+                    ("38", "150"),
+                    ("44", "1.12"),
+                    ("64", "20250105"),
+                ]),
+                None,
+            );
+        };
+
+        let mut summary = OrderSummary::new('|').with_diff_tags(DEFAULT_DIFF_TAGS.to_vec());
+        replace_sequence(&mut summary);
+        let record = summary.orders.values().next().expect("order captured");
+        let diffs: Vec<Option<String>> = record
+            .events
+            .iter()
+            .map(|event| format_diff(&event.diff))
+            .collect();
+        assert_eq!(
+            diffs,
+            [
+                None,
+                Some("38:100→150 44:1.10→1.12".to_string()),
+                Some("38:100→150 44:1.10→1.12 64:?→20250105".to_string()),
+            ]
+        );
+        let mut buf = Vec::new();
+        summary.render(&mut buf).unwrap();
+        let text = String::from_utf8(buf).unwrap();
+        assert!(text.contains("38:100→150 44:1.10→1.12"), "{text}");
+
+        let mut plain = OrderSummary::new('|');
+        replace_sequence(&mut plain);
+        let record = plain.orders.values().next().expect("order captured");
+        assert!(record.events.iter().all(|event| event.diff.is_empty()));
+    }
+
+    #[test]
+    fn terminal_states_are_configurable() {
+        let mut summary =
+            OrderSummary::new('|').with_terminal_states(vec!["Partially Filled".into()]);
+        summary.record_message(
+            &msg(&[("35", "8"), ("37", "T1"), ("39", "1"), ("32", "5")]),
+            None,
+        );
+        assert!(summary.orders.is_empty());
+        assert_eq!(summary.completed.len(), 1);
+
+        let mut summary = OrderSummary::new('|').with_terminal_states(vec!["Filled".into()]);
+        summary.record_message(&msg(&[("35", "8"), ("37", "T2"), ("39", "4")]), None);
+        assert!(
+            summary.orders.contains_key("T2"),
+            "Canceled is no longer terminal"
+        );
+        assert_eq!(ord_status_state("canceled"), Some("Canceled"));
+        assert_eq!(ord_status_state("C"), Some("Expired"));
+        assert_eq!(ord_status_state("nope"), None);
+    }
+
+    #[test]
+    fn untracked_msg_types_do_not_create_records() {
+        let heartbeat = msg(&[("8", "FIX.4.4"), ("35", "0"), ("112", "T1")]);
+        let quote = msg(&[("8", "FIX.4.4"), ("35", "S"), ("117", "Q1")]);
+        let mut summary = OrderSummary::new('|');
+        summary.record_message(&heartbeat, None);
+        summary.record_parsed(&ParsedMessage::parse(&quote), None);
+        assert!(summary.orders.is_empty() && summary.completed.is_empty());
+        assert_eq!((summary.total_orders, summary.unknown_counter), (0, 0));
+
+        let mut summary = OrderSummary::new('|').with_extra_msg_types(vec!["S".into()]);
+        summary.record_message(&heartbeat, None);
+        summary.record_message(&quote, None);
+        assert_eq!(summary.orders.keys().collect::<Vec<_>>(), ["UNKNOWN-1"]);
+        let summary = OrderSummary::new('|');
+        for msg_type in ["AB", "AC", "E", "s"] {
+            assert!(
+                summary.tracks(&format!("35={msg_type}\u{0001}")),
+                "{msg_type}"
+            );
+        }
+        assert_eq!(raw_msg_type("35=BN\u{0001}"), Some("BN"));
+        assert_eq!(raw_msg_type("135=8\u{0001}"), None);
+    }
+
+    #[test]
+    fn group_totals_roll_orders_up_by_symbol_and_side() {
+        let order = |id: &str, symbol: &str, side: &str, qty: &str| {
+            msg(&[
+                ("35", "D"),
+                ("11", id),
+                ("55", symbol),
+                ("54", side),
+                ("38", qty),
+            ])
+        };
+        let report = |id: &str, status: &str, last: &str| {
+            msg(&[
+                ("35", "8"),
+                ("11", id),
+                ("150", status),
+                ("39", status),
+                ("32", last),
+                ("31", "1.1"),
+            ])
+        };
+        let messages = [
+            order("A", "EUR/USD", "1", "1000000.50"),
+            report("A", "2", "1000000.50"),
+            order("B", "EUR/USD", "2", "1,000"),
+            report("B", "4", "0"),
+            order("C", "GBP/USD", "1", "500"),
+            report("C", "8", "0"),
+            order("D", "EUR/USD", "1", "200"),
+        ];
+        let render = |group| {
+            let mut summary = OrderSummary::new('|').with_group(Some(group));
+            for message in &messages {
+                summary.record_message(message, None);
+            }
+            let mut out = Vec::new();
+            summary.render(&mut out).unwrap();
+            let ansi = regex::Regex::new("\x1b\\[[0-9;]*m").unwrap();
+            ansi.replace_all(&String::from_utf8(out).unwrap(), "")
+                .into_owned()
+        };
+        let rows = |text: &str, title: &str| -> Vec<Vec<String>> {
+            let table = &text[text.find(title).expect("totals table")..];
+            table
+                .lines()
+                .skip(2)
+                .take_while(|line| line.starts_with("    ") && !line.contains("Skipped"))
+                .map(|line| line.split_whitespace().map(str::to_string).collect())
+                .collect()
+        };
+
+        let text = render(SummaryGroup::Symbol);
+        assert_eq!(
+            rows(&text, "Totals by Symbol"),
+            vec![
+                vec![
+                    "EUR/USD",
+                    "3",
+                    "1000200.5",
+                    "1000000.5",
+                    "100.0%",
+                    "33.3%",
+                    "0"
+                ],
+                vec!["GBP/USD", "1", "500", "0", "0.0%", "0.0%", "1"],
+            ]
+        );
+        assert!(
+            text.contains("Skipped 1 unparseable quantity value(s)"),
+            "{text}"
+        );
+
+        let text = render(SummaryGroup::SymbolSide);
+        let by_side = rows(&text, "Totals by Symbol and Side");
+        assert_eq!(by_side.len(), 3, "{text}");
+        assert_eq!(by_side[1][..3], ["EUR/USD", "Sell", "1"]);
+        assert_eq!(
+            SummaryGroup::parse("Symbol-Side"),
+            Some(SummaryGroup::SymbolSide)
+        );
+        assert_eq!(SummaryGroup::parse("side"), None);
+    }
+
+    #[test]
+    fn session_summary_tracks_admin_traffic_per_comp_id_pair() {
+        let mut sessions = SessionSummary::new();
+        let admin = |mt: &str, from: &str, to: &str, extra: &[(&str, &str)]| {
+            let mut fields = vec![("35", mt), ("49", from), ("56", to)];
+            fields.extend_from_slice(extra);
+            msg(&fields)
+        };
+        for text in [
+            admin("A", "AAA", "BBB", &[]),
+            admin("A", "BBB", "AAA", &[]),
+            admin(
+                "1",
+                "AAA",
+                "BBB",
+                &[("52", "20250101-12:00:00.000"), ("112", "T1")],
+            ),
+            admin(
+                "0",
+                "BBB",
+                "AAA",
+                &[("52", "20250101-12:00:00.250"), ("112", "T1")],
+            ),
+            admin("1", "BBB", "AAA", &[("112", "T2")]),
+            admin(
+                "0",
+                "AAA",
+                "BBB",
+                &[("52", "20250101-12:00:01"), ("112", "T2")],
+            ),
+            admin("0", "AAA", "BBB", &[("112", "T1")]),
+            admin("2", "AAA", "BBB", &[("7", "5"), ("16", "0")]),
+            admin("4", "BBB", "AAA", &[("34", "5"), ("123", "Y"), ("36", "9")]),
+            admin("4", "BBB", "AAA", &[("36", "1")]),
+            admin("5", "CCC", "AAA", &[]),
+            msg(&[("35", "D"), ("49", "AAA"), ("56", "BBB"), ("11", "X")]),
+        ] {
+            sessions.record_message(&text);
+        }
+
+        let pair = &sessions.sessions[&("AAA".to_string(), "BBB".to_string())];
+        assert_eq!((pair.logons, pair.logouts, pair.heartbeats), (2, 0, 3));
+        assert_eq!(pair.test_requests, 2);
+        assert_eq!(
+            pair.round_trips,
+            vec![Some(Duration::milliseconds(250)), None]
+        );
+        assert_eq!(pair.rtt_label(), "avg 0.250s max 0.250s (2)");
+        assert_eq!(pair.resend_requests, vec!["AAA -> BBB 5-infinity"]);
+        assert_eq!((pair.gap_fills, pair.resets), (1, 1));
+        assert_eq!(pair.sequence_resets[0], "GapFill BBB -> AAA 5 -> 9");
+        assert_eq!(sessions.sessions.len(), 2);
+
+        let mut out = Vec::new();
+        sessions.render(&mut out).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        assert!(text.contains("Session Summary") && text.contains("(2 session(s))"));
+        assert!(text.contains("AAA <-> CCC") && text.contains("Reset BBB -> AAA to 1"));
+    }
+
+    #[test]
+    fn keep_open_renders_raw_messages_for_open_orders() {
+        let open = msg(&[("35", "D"), ("11", "OPEN1"), ("55", "VOD.L")]);
+        let mut summary = OrderSummary::new('|');
+        summary.record_message(&open, None);
+        let mut buf = Vec::new();
+        summary.render(&mut buf).unwrap();
+        let text = String::from_utf8(buf).unwrap();
+        assert!(text.contains("OPEN1") && !text.contains("Raw FIX messages"));
+
+        let mut summary = OrderSummary::new('|').with_keep_open(true);
+        summary.record_message(&open, None);
+        let mut buf = Vec::new();
+        summary.render(&mut buf).unwrap();
+        let text = String::from_utf8(buf).unwrap();
+        assert!(text.contains("Raw FIX messages"), "{text}");
+        assert!(text.contains("35=D|11=OPEN1|55=VOD.L|"), "{text}");
+    }
+
+    #[test]
+    fn completed_orders_echo_raw_messages_only_when_asked() {
+        let filled = msg(&[("35", "8"), ("37", "DONE1"), ("39", "2"), ("55", "VOD.L")]);
+        let render = |summary: OrderSummary| {
+            let mut summary = summary;
+            summary.record_message(&filled, None);
+            let mut buf = Vec::new();
+            summary.render(&mut buf).unwrap();
+            String::from_utf8(buf).unwrap()
+        };
+
+        let text = render(OrderSummary::new('|'));
+        assert!(
+            text.contains("DONE1") && !text.contains("Raw FIX messages"),
+            "{text}"
+        );
+
+        let text = render(OrderSummary::new('|').with_raw_messages(true));
+        assert!(text.contains("Raw FIX messages"), "{text}");
+        assert!(text.contains("35=8|37=DONE1|39=2|55=VOD.L|"), "{text}");
+    }
+
+    #[test]
+    fn raw_messages_escape_the_display_delimiter_inside_values() {
+        let filled = msg(&[("35", "8"), ("37", "DONE1"), ("39", "2"), ("58", "a|b")]);
+        let mut summary = OrderSummary::new('|').with_raw_messages(true);
+        summary.record_message(&filled, None);
+        let mut buf = Vec::new();
+        summary.render(&mut buf).unwrap();
+        let text = String::from_utf8(buf).unwrap();
+        assert!(text.contains("35=8|37=DONE1|39=2|58=a\\|b|"), "{text}");
+    }
+
+    #[test]
+    fn max_orders_bounds_memory_across_a_hundred_thousand_orders() {
+        const ORDERS: usize = 100_000;
+        const CAP: usize = 100;
+        let dict = crate::decoder::tag_lookup::load_dictionary(&msg(&[("35", "D")]));
+        let mut summary = OrderSummary::new('|')
+            .with_keep_open(true)
+            .with_max_orders(Some(CAP));
+        let mut out = std::io::sink();
+        for n in 0..ORDERS {
+            let id = format!("ORD{n}");
+            let side = if n % 2 == 0 { "1" } else { "2" };
+            let symbol = ["VOD.L", "BARC.L", "HSBA.L"][n % 3];
+            let new_order = msg(&[("35", "D"), ("11", &id), ("54", side), ("55", symbol)]);
+            let fill = msg(&[("35", "8"), ("11", &id), ("37", &id), ("39", "2")]);
+            summary.record_message_with_dict(&new_order, None, &dict);
+            summary.record_message_with_dict(&fill, None, &dict);
+            summary.render_over_capacity(&mut out).unwrap();
+            assert!(summary.held() <= CAP);
+        }
+        assert_eq!(summary.total_orders, ORDERS);
+        assert_eq!(summary.completed.len(), CAP);
+        assert!(summary.completed.iter().all(|r| r.messages.is_empty()));
+        assert_eq!(summary.strings.0.len(), 5);
+        assert!(summary.aliases.len() <= CAP);
+
+        // Open orders are never flushed, and keep their messages for --summary-keep-open.
+        let open = msg(&[("35", "D"), ("11", "LIVE"), ("55", "VOD.L")]);
+        summary.record_message_with_dict(&open, None, &dict);
+        summary.render_over_capacity(&mut out).unwrap();
+        assert_eq!(summary.orders["LIVE"].messages.len(), 1);
+        assert_eq!(summary.held(), CAP);
+    }
+
     #[test]
     fn render_record_header_includes_id_and_instrument() {
         let colours = palette();
@@ -1511,6 +3221,8 @@ mod tests {
             text: None,
             cl_ord_id: None,
             orig_cl_ord_id: None,
+            duplicate: false,
+            diff: Vec::new(),
         });
         record.events.push(OrderEvent {
             state: "New".into(),
@@ -0,0 +1,241 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// SPDX-FileCopyrightText: 2025 Steve Clarke <stephenlclarke@mac.com> - https://xyzzy.tools
+
+//! `--tag TAG --histogram`: how often each distinct value of a tag occurs across a log,
+//! such as which ExecTypes(150) a session actually received.  Only the distinct values
+//! and their counts are kept, so memory grows with the number of values, not with the
+//! size of the log.
+
+use crate::decoder::colours::palette;
+use crate::decoder::display::DisplayOptions;
+use crate::decoder::summary::render_table;
+use crate::decoder::tag_lookup::FixTagLookup;
+use std::collections::HashMap;
+use std::io::Write;
+
+/// Characters of a value shown in the table before it is cut short, unless `--verbose`.
+const MAX_VALUE_WIDTH: usize = 40;
+
+#[derive(Debug, Default)]
+struct ValueTally {
+    /// The enum description from the first dictionary that knew the value.
+    description: Option<String>,
+    count: usize,
+}
+
+#[derive(Debug)]
+struct TagValues {
+    tag: u32,
+    /// The field name from the first dictionary that knew the tag.
+    name: Option<String>,
+    values: HashMap<String, ValueTally>,
+    /// Messages that did not carry the tag at all.
+    missing: usize,
+}
+
+/// Value counts for each `--tag` given with `--histogram`.
+#[derive(Debug)]
+pub struct TagHistogram {
+    tags: Vec<TagValues>,
+    messages: usize,
+    /// `--verbose`: show long values in full.
+    full_values: bool,
+}
+
+impl TagHistogram {
+    pub fn new(tags: &[u32], full_values: bool) -> Self {
+        Self {
+            tags: tags
+                .iter()
+                .map(|&tag| TagValues {
+                    tag,
+                    name: None,
+                    values: HashMap::new(),
+                    missing: 0,
+                })
+                .collect(),
+            messages: 0,
+            full_values,
+        }
+    }
+
+    /// Count the values of the requested tags in one message, described with `dict`.
+    pub fn record(&mut self, msg: &str, dict: &FixTagLookup) {
+        self.messages += 1;
+        let mut carried = vec![false; self.tags.len()];
+        for field in msg.split('\u{0001}') {
+            let Some((tag, value)) = field.split_once('=') else {
+                continue;
+            };
+            let Ok(tag) = tag.parse::<u32>() else {
+                continue;
+            };
+            let Some(idx) = self.tags.iter().position(|t| t.tag == tag) else {
+                continue;
+            };
+            carried[idx] = true;
+            let tally = &mut self.tags[idx];
+            if dict.has_tag(tag) {
+                tally.name.get_or_insert_with(|| dict.field_name(tag));
+            }
+            match tally.values.get_mut(value) {
+                Some(seen) => seen.count += 1,
+                None => {
+                    let description = dict.describe_value(tag, value).map(|d| d.into_owned());
+                    tally.values.insert(
+                        value.to_string(),
+                        ValueTally {
+                            description,
+                            count: 1,
+                        },
+                    );
+                }
+            }
+        }
+        for (tally, carried) in self.tags.iter_mut().zip(carried) {
+            if !carried {
+                tally.missing += 1;
+            }
+        }
+    }
+
+    /// Print one table per tag: each value with its description, count and share of the
+    /// tag's occurrences, busiest first, then how many messages lacked the tag.  Values
+    /// and descriptions are shown under `display`, so control characters are escaped.
+    pub fn render(&self, out: &mut dyn Write, display: &DisplayOptions) -> std::io::Result<()> {
+        let colours = palette();
+        for tally in &self.tags {
+            let name = tally.name.as_deref().unwrap_or("-");
+            writeln!(
+                out,
+                "{}Tag Histogram{} {} {} ({} value(s) in {} message(s))\n",
+                colours.title,
+                colours.reset,
+                tally.tag,
+                name,
+                tally.values.len(),
+                self.messages
+            )?;
+            let occurrences: usize = tally.values.values().map(|v| v.count).sum();
+            let mut by_count: Vec<(&String, &ValueTally)> = tally.values.iter().collect();
+            by_count.sort_by(|a, b| b.1.count.cmp(&a.1.count).then_with(|| a.0.cmp(b.0)));
+            let rows: Vec<Vec<String>> = by_count
+                .into_iter()
+                .map(|(value, seen)| {
+                    vec![
+                        self.shown(&display.display_value(value)),
+                        seen.description.as_deref().map_or_else(
+                            || "-".to_string(),
+                            |d| display.display_value(d).into_owned(),
+                        ),
+                        seen.count.to_string(),
+                        format!("{:.1}%", seen.count as f64 * 100.0 / occurrences as f64),
+                    ]
+                })
+                .collect();
+            if !rows.is_empty() {
+                render_table(out, &["Value", "Description", "Count", "Share"], &rows)?;
+            }
+            writeln!(
+                out,
+                "    {} message(s) without tag {}\n",
+                tally.missing, tally.tag
+            )?;
+        }
+        Ok(())
+    }
+
+    /// `value` as shown in the table: cut to [`MAX_VALUE_WIDTH`] characters with a
+    /// trailing `…` unless `--verbose`.
+    fn shown(&self, value: &str) -> String {
+        if self.full_values || value.chars().count() <= MAX_VALUE_WIDTH {
+            return value.to_string();
+        }
+        let mut cut: String = value.chars().take(MAX_VALUE_WIDTH - 1).collect();
+        cut.push('…');
+        cut
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decoder::colours::disable_colours;
+    use crate::decoder::tag_lookup::load_dictionary_with_override;
+
+    #[test]
+    fn values_are_counted_busiest_first_with_descriptions_and_missing_messages() {
+        disable_colours();
+        let dict = load_dictionary_with_override("8=FIX.4.4\u{0001}", None);
+        let long = "X".repeat(50);
+        let mut histogram = TagHistogram::new(&[150, 58], false);
+        let messages = [
+            "35=8|150=0|".to_string(),
+            "35=8|150=F|".to_string(),
+            "35=8|150=F|58=a|".to_string(),
+            "35=D|".to_string(),
+            format!("35=8|150=F|58={long}|"),
+        ];
+        for msg in &messages {
+            histogram.record(&msg.replace('|', "\u{0001}"), &dict);
+        }
+
+        let mut out = Vec::new();
+        histogram
+            .render(&mut out, &DisplayOptions::default())
+            .unwrap();
+        let text = String::from_utf8(out).unwrap();
+        assert!(
+            text.contains("Tag Histogram 150 ExecType (2 value(s) in 5 message(s))"),
+            "{text}"
+        );
+        let rows: Vec<Vec<&str>> = text
+            .lines()
+            .filter(|line| line.starts_with("    ") && !line.contains("Value"))
+            .map(|line| {
+                line.split("  ")
+                    .map(str::trim)
+                    .filter(|cell| !cell.is_empty())
+                    .collect()
+            })
+            .collect();
+        assert_eq!(rows[0], ["F", "TRADE", "3", "75.0%"], "{text}");
+        assert_eq!(rows[1], ["0", "NEW", "1", "25.0%"], "{text}");
+        assert_eq!(rows[2], ["1 message(s) without tag 150"], "{text}");
+        let cut = format!("{}…", "X".repeat(39));
+        assert!(text.contains(&cut) && !text.contains(&long), "{text}");
+        assert!(text.contains("3 message(s) without tag 58"), "{text}");
+
+        histogram.full_values = true;
+        let mut out = Vec::new();
+        histogram
+            .render(&mut out, &DisplayOptions::default())
+            .unwrap();
+        assert!(String::from_utf8(out).unwrap().contains(&long));
+    }
+
+    #[test]
+    fn control_characters_in_values_are_escaped_and_keep_the_columns_aligned() {
+        disable_colours();
+        let dict = load_dictionary_with_override("8=FIX.4.4\u{0001}", None);
+        let mut histogram = TagHistogram::new(&[58], false);
+        for text in ["\u{001b}[2Jwiped", "plain"] {
+            histogram.record(&format!("35=8\u{0001}58={text}\u{0001}"), &dict);
+        }
+
+        let mut out = Vec::new();
+        histogram
+            .render(&mut out, &DisplayOptions::default())
+            .unwrap();
+        let text = String::from_utf8(out).unwrap();
+        assert!(!text.contains('\u{001b}'), "{text:?}");
+        assert!(text.contains("\\x1B[2Jwiped"), "{text}");
+        let counts: Vec<usize> = text
+            .lines()
+            .filter(|line| line.contains("wiped") || line.contains("plain"))
+            .map(|line| line.find(" 1 ").expect("count column"))
+            .collect();
+        assert_eq!(counts.len(), 2, "{text}");
+        assert_eq!(counts[0], counts[1], "{text}");
+    }
+}
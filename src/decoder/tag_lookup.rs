@@ -1,23 +1,53 @@
 // SPDX-License-Identifier: AGPL-3.0-only
 // SPDX-FileCopyrightText: 2025 Steve Clarke <stephenlclarke@mac.com> - https://xyzzy.tools
 
+use crate::decoder::dict_cache;
+use crate::decoder::enum_overlay::EnumOverlay;
+use crate::decoder::fix_map::FixMap;
 use crate::decoder::schema::{ComponentDef, FixDictionary, GroupDef, Message, MessageContainer};
+use crate::decoder::udf::UdfCatalogue;
+use crate::decoder::warnings;
 use crate::fix;
 use once_cell::sync::Lazy;
+use std::borrow::Cow;
 use std::collections::{HashMap, HashSet};
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::{Arc, RwLock};
+use std::sync::{Arc, OnceLock, RwLock};
 
 #[derive(Clone, Debug)]
 pub struct MessageDef {
-    pub _name: String,
+    pub name: String,
     pub _msg_type: String,
-    pub field_order: Vec<u32>,
+    /// Standard header fields, in dictionary order.
+    pub header_order: Vec<u32>,
+    /// The message's own fields, components and groups, flattened in dictionary order.
+    pub body_order: Vec<u32>,
+    /// Standard trailer fields, in dictionary order.
+    pub trailer_order: Vec<u32>,
     pub required: Vec<u32>,
     pub groups: HashMap<u32, GroupSpec>,
     pub group_membership: HashMap<u32, u32>,
+    /// Tags the message declares outside any repeating group, header and trailer
+    /// included.  A group member may be one too.
+    pub body_level: HashSet<u32>,
+    pub provenance: FieldProvenance,
 }
 
+impl MessageDef {
+    /// Header, body and trailer fields as one list, the order they are displayed in.
+    pub fn field_order(&self) -> Vec<u32> {
+        let mut order = self.header_order.clone();
+        order.extend(&self.body_order);
+        order.extend(&self.trailer_order);
+        order
+    }
+}
+
+/// Where each flattened field of a message came from: the components and groups
+/// between the message and the field, outermost first.  Fields declared directly
+/// in the message body have an empty path; the first occurrence of a tag wins.
+pub type FieldProvenance = HashMap<u32, Vec<String>>;
+
 #[derive(Debug, Clone)]
 pub struct GroupSpec {
     pub name: String,
@@ -37,7 +67,7 @@ pub struct FixTagLookup {
     field_types: Arc<HashMap<u32, String>>,
     messages: Arc<HashMap<String, MessageDef>>,
     repeatable_tags: Arc<HashSet<u32>>,
-    #[allow(dead_code)]
+    header_order: Arc<Vec<u32>>,
     trailer_order: Arc<Vec<u32>>,
     fallback: Option<Arc<FixTagLookup>>,
     fallback_role: Option<FallbackKind>,
@@ -92,17 +122,22 @@ impl FixTagLookup {
 
         let messages = build_message_defs(&dict.messages, &component_map, &name_to_tag);
         let repeatable_tags = collect_repeatable_from_specs(&messages);
-        let mut trailer_order = Vec::new();
-        let mut stack = Vec::new();
-        append_component_fields(
-            "Trailer",
-            &component_map,
-            &name_to_tag,
-            &mut stack,
-            &mut trailer_order,
-            &mut Vec::new(),
-        );
-        dedupe(&mut trailer_order);
+        let component_tags = |name: &str| {
+            let mut order = Vec::new();
+            append_component_fields(
+                name,
+                &component_map,
+                &name_to_tag,
+                &mut Vec::new(),
+                &mut order,
+                &mut Vec::new(),
+                &mut Trail::default(),
+            );
+            dedupe(&mut order);
+            order
+        };
+        let header_order = component_tags("Header");
+        let trailer_order = component_tags("Trailer");
 
         FixTagLookup {
             schema_key: key.to_string(),
@@ -111,6 +146,7 @@ impl FixTagLookup {
             field_types: Arc::new(field_types),
             messages: Arc::new(messages),
             repeatable_tags: Arc::new(repeatable_tags),
+            header_order: Arc::new(header_order),
             trailer_order: Arc::new(trailer_order),
             fallback: None,
             fallback_role: None,
@@ -136,6 +172,36 @@ impl FixTagLookup {
             .and_then(|fallback| fallback.enum_description(tag, value))
     }
 
+    /// Describe `value` for display.  Fields whose type holds several space-separated
+    /// codes (such as ExecInst(18)) get one description per code, joined with commas,
+    /// and `?` for any code the dictionary does not know.
+    pub fn describe_value(&self, tag: u32, value: &str) -> Option<Cow<'_, str>> {
+        if let Some(description) = self.enum_description(tag, value) {
+            return Some(Cow::Borrowed(description));
+        }
+        if !self.is_multi_value(tag) {
+            return None;
+        }
+        let enums = self.enums_for(tag)?;
+        let descriptions: Vec<Option<&String>> = value
+            .split_whitespace()
+            .map(|code| enums.get(code))
+            .collect();
+        if descriptions.iter().all(Option::is_none) {
+            return None;
+        }
+        let joined: Vec<&str> = descriptions
+            .into_iter()
+            .map(|d| d.map_or("?", String::as_str))
+            .collect();
+        Some(Cow::Owned(joined.join(", ")))
+    }
+
+    /// Whether the field's values are space-separated lists of codes.
+    pub fn is_multi_value(&self, tag: u32) -> bool {
+        self.field_type(tag).is_some_and(is_multi_value_type)
+    }
+
     pub fn enums_for(&self, tag: u32) -> Option<&HashMap<String, String>> {
         self.enum_map
             .get(&tag)
@@ -149,12 +215,36 @@ impl FixTagLookup {
             .or_else(|| self.fallback.as_ref().and_then(|f| f.field_type(tag)))
     }
 
+    /// Canonical dictionary key (e.g. `FIX44`, `FIX50SP2`) this lookup was built from.
+    pub fn schema_key(&self) -> &str {
+        &self.schema_key
+    }
+
+    /// The key of the dictionary consulted for tags this one does not define, if any.
+    pub fn fallback_key(&self) -> Option<&str> {
+        self.fallback.as_ref().map(|f| f.schema_key())
+    }
+
+    /// Why the fallback is there: FIXT.1.1 session fields for a FIX 5.0+ dictionary, or
+    /// the detected version behind a `--fix` override.
+    pub fn fallback_role(&self) -> Option<FallbackKind> {
+        self.fallback_role
+    }
+
     pub fn message_def(&self, msg_type: &str) -> Option<&MessageDef> {
         self.messages
             .get(msg_type)
             .or_else(|| self.fallback.as_ref().and_then(|f| f.message_def(msg_type)))
     }
 
+    /// Label for a MsgType in counts and summaries: its MsgType(35) enum description,
+    /// or else the name of the message that defines it, since custom dictionaries often
+    /// add messages such as `U1` without listing them as MsgType values.
+    pub fn msg_type_label(&self, msg_type: &str) -> Option<&str> {
+        self.enum_description(35, msg_type)
+            .or_else(|| self.message_def(msg_type).map(|def| def.name.as_str()))
+    }
+
     pub fn is_repeatable(&self, tag: u32) -> bool {
         self.repeatable_tags.contains(&tag)
             || self
@@ -164,6 +254,18 @@ impl FixTagLookup {
                 .unwrap_or(false)
     }
 
+    /// Tags of the standard header, nested groups included; FIX 5.0 dictionaries take
+    /// theirs from the FIXT transport fallback.
+    pub fn header_tags(&self) -> &[u32] {
+        if !self.header_order.is_empty() {
+            self.header_order.as_slice()
+        } else if let Some(fallback) = &self.fallback {
+            fallback.header_tags()
+        } else {
+            self.header_order.as_slice()
+        }
+    }
+
     pub fn trailer_tags(&self) -> &[u32] {
         if !self.trailer_order.is_empty() {
             self.trailer_order.as_slice()
@@ -191,7 +293,62 @@ impl FixTagLookup {
         }
     }
 
-    fn has_tag(&self, tag: u32) -> bool {
+    /// Add the overlay's enum values to this dictionary, on top of any the field already
+    /// has (including those inherited from the fallback), and name the tags it does not
+    /// know.
+    pub fn apply_overlay(&mut self, overlay: &EnumOverlay) {
+        for (tag, name) in &overlay.names {
+            if !self.has_tag(*tag) {
+                Arc::make_mut(&mut self.tag_to_name).insert(*tag, name.clone());
+            }
+        }
+        for (tag, values) in &overlay.values {
+            let mut enums = self.enums_for(*tag).cloned().unwrap_or_default();
+            enums.extend(
+                values
+                    .iter()
+                    .map(|(code, desc)| (code.clone(), desc.clone())),
+            );
+            Arc::make_mut(&mut self.enum_map).insert(*tag, enums);
+        }
+    }
+
+    /// Add the `--udf` fields to this dictionary with their names, types and enums.  A
+    /// tag the dictionary (or its fallback) already defines keeps that definition, with
+    /// a warning, unless `replace` is set.  Returns the tags that clashed.
+    pub fn apply_udf(&mut self, udf: &UdfCatalogue, replace: bool) -> Vec<u32> {
+        let mut clashes = Vec::new();
+        for (tag, field) in &udf.fields {
+            if self.has_tag(*tag) {
+                clashes.push(*tag);
+                if !replace {
+                    warnings::warn(
+                        "udf-conflict",
+                        &format!("{}:{tag}", self.schema_key),
+                        &format!(
+                            "warning: --udf tag {tag} ({}) is already {} in {}; keeping the dictionary definition (--udf-override replaces it)",
+                            field.name,
+                            self.field_name(*tag),
+                            self.schema_key
+                        ),
+                    );
+                    continue;
+                }
+            }
+            Arc::make_mut(&mut self.tag_to_name).insert(*tag, field.name.clone());
+            Arc::make_mut(&mut self.field_types).insert(*tag, field.field_type.clone());
+            let enum_map = Arc::make_mut(&mut self.enum_map);
+            if field.values.is_empty() {
+                enum_map.remove(tag);
+            } else {
+                enum_map.insert(*tag, field.values.clone().into_iter().collect());
+            }
+        }
+        clashes
+    }
+
+    /// Whether the dictionary, or its transport fallback, defines `tag`.
+    pub fn has_tag(&self, tag: u32) -> bool {
         self.tag_to_name.contains_key(&tag)
             || self
                 .fallback
@@ -211,6 +368,7 @@ impl FixTagLookup {
             field_types: Arc::new(HashMap::new()),
             messages: Arc::new(messages),
             repeatable_tags: Arc::new(HashSet::new()),
+            header_order: Arc::new(vec![8, 9, 35]),
             trailer_order: Arc::new(vec![10]),
             fallback: None,
             fallback_role: None,
@@ -221,6 +379,11 @@ impl FixTagLookup {
 static LOOKUPS: Lazy<RwLock<HashMap<String, Arc<FixTagLookup>>>> =
     Lazy::new(|| RwLock::new(HashMap::new()));
 
+/// Keys registered from `--xml` custom dictionaries rather than built from the
+/// embedded XML, with the file each came from.
+static CUSTOM_SOURCES: Lazy<RwLock<HashMap<String, String>>> =
+    Lazy::new(|| RwLock::new(HashMap::new()));
+
 static OVERRIDE_MISS: AtomicBool = AtomicBool::new(false);
 
 const SESSION_KEY: &str = "FIXT11";
@@ -232,8 +395,9 @@ pub fn clear_override_cache_for(key: &str) {
     }
 }
 
+/// The embedded XML identifier for a schema key, if that dictionary was compiled in.
 fn schema_to_xml_id(key: &str) -> Option<&'static str> {
-    match key {
+    let xml_id = match key {
         "FIX27" => Some("40"),
         "FIX30" => Some("40"),
         "FIX40" => Some("40"),
@@ -246,7 +410,8 @@ fn schema_to_xml_id(key: &str) -> Option<&'static str> {
         "FIX50SP2" => Some("50SP2"),
         "FIXT11" => Some("T11"),
         _ => None,
-    }
+    };
+    xml_id.filter(|id| fix::choose_embedded_xml(id).is_some())
 }
 
 fn needs_session_merge(key: &str) -> bool {
@@ -259,11 +424,15 @@ fn get_dictionary(key: &str) -> Option<Arc<FixTagLookup>> {
     }
 
     let xml_id = schema_to_xml_id(key)?;
-    let xml = fix::choose_embedded_xml(xml_id);
-    let dict = match FixDictionary::from_xml(xml) {
+    let xml = fix::choose_embedded_xml(xml_id)?;
+    let dict = match dict_cache::parse_embedded(xml_id, xml) {
         Ok(dict) => dict,
         Err(err) => {
-            eprintln!("failed to parse embedded FIX XML for {key}: {err}");
+            warnings::warn(
+                "embedded-xml",
+                key,
+                &format!("failed to parse embedded FIX XML for {key}: {err}"),
+            );
             return None;
         }
     };
@@ -302,6 +471,20 @@ fn detect_schema_key(msg: &str) -> String {
     "FIX44".to_string()
 }
 
+/// The version a message declares for itself, as a display label and the dictionary key
+/// it maps to: the BeginString, or for FIXT.1.1 the ApplVerID (1128, else 1137).
+/// FIXT.1.1 messages without a recognised ApplVerID, such as session traffic, declare no
+/// application version and give `None`.
+pub fn declared_version(msg: &str) -> Option<(String, String)> {
+    let begin = get_tag_value(msg, "8")?;
+    if begin == "FIXT.1.1" {
+        let appl_ver_id = get_tag_value(msg, "1128").or_else(|| get_tag_value(msg, "1137"))?;
+        let key = appl_ver_to_schema(appl_ver_id)?;
+        return Some((format!("FIXT.1.1/{key}"), key.to_string()));
+    }
+    Some((begin.to_string(), begin.replace('.', "")))
+}
+
 fn appl_ver_to_schema(value: &str) -> Option<&'static str> {
     match value {
         "0" => Some("FIX27"),
@@ -321,12 +504,30 @@ fn appl_ver_to_schema(value: &str) -> Option<&'static str> {
 pub fn load_dictionary(msg: &str) -> Arc<FixTagLookup> {
     let key = detect_schema_key(msg);
     get_dictionary(&key)
-        .or_else(|| get_dictionary("FIX44"))
-        .expect("FIX44 dictionary available")
+        .or_else(default_dictionary)
+        .expect("default dictionary available")
+}
+
+/// The embedded dictionary used when the one a message asks for is not available.
+fn default_dictionary() -> Option<Arc<FixTagLookup>> {
+    match fix::default_xml_id() {
+        "T11" => get_dictionary(SESSION_KEY),
+        id => get_dictionary(&format!("FIX{id}")),
+    }
 }
 
 /// Load a dictionary, allowing an override schema key to force the selection used for decoding.
+/// A `--fix-map` rule matching the message's CompIDs takes precedence over the override.
 pub fn load_dictionary_with_override(msg: &str, override_key: Option<&str>) -> Arc<FixTagLookup> {
+    let Some(map) = FIX_MAP.get() else {
+        return resolve_dictionary(msg, override_key);
+    };
+    let dict = resolve_dictionary(msg, map.key_for(msg).or(override_key));
+    map.record(msg, dict.schema_key());
+    dict
+}
+
+fn resolve_dictionary(msg: &str, override_key: Option<&str>) -> Arc<FixTagLookup> {
     if let Some(key) = override_key {
         let detected_key = detect_schema_key(msg);
         let combo_key = format!("{key}+{detected_key}");
@@ -345,15 +546,32 @@ pub fn load_dictionary_with_override(msg: &str, override_key: Option<&str>) -> A
             }
             return merged;
         }
-        eprintln!(
-            "warning: FIX override '{}' not found; falling back to auto-detected dictionary",
-            key
+        warnings::warn(
+            "fix-override",
+            key,
+            &format!(
+                "warning: FIX override '{key}' not found; falling back to auto-detected dictionary"
+            ),
         );
         warn_override_miss();
     }
     load_dictionary(msg)
 }
 
+/// The dictionary used to label a message's MsgType: a custom dictionary registered
+/// for the version the message declares wins, then the `--fix` override, then the
+/// embedded dictionary.
+pub fn load_label_dictionary(msg: &str, override_key: Option<&str>) -> Arc<FixTagLookup> {
+    let detected_key = detect_schema_key(msg);
+    let custom = CUSTOM_SOURCES
+        .read()
+        .is_ok_and(|sources| sources.contains_key(&detected_key));
+    if custom && let Some(dict) = get_dictionary(&detected_key) {
+        return dict;
+    }
+    load_dictionary_with_override(msg, override_key)
+}
+
 fn warn_override_miss() {
     OVERRIDE_MISS.store(true, Ordering::Relaxed);
 }
@@ -378,12 +596,121 @@ pub fn override_warn_triggered() -> bool {
     OVERRIDE_MISS.load(Ordering::Relaxed)
 }
 
-pub fn register_dictionary(key: &str, dict: &FixDictionary) {
+/// FIX field types whose values are space-separated lists of codes.
+pub fn is_multi_value_type(field_type: &str) -> bool {
+    matches!(
+        field_type.to_ascii_uppercase().as_str(),
+        "MULTIPLEVALUESTRING" | "MULTIPLESTRINGVALUE" | "MULTIPLECHARVALUE"
+    )
+}
+
+/// Normalise user-supplied FIX version identifiers (e.g. `4.4`, `fix44`)
+/// into the canonical keys used throughout the project.
+pub fn normalise_fix_key(raw: &str) -> Option<String> {
+    let trimmed = raw.trim();
+    if trimmed.is_empty() {
+        return None;
+    }
+
+    let mut cleaned = trimmed.replace('.', "");
+    cleaned = cleaned.to_ascii_uppercase();
+
+    if cleaned.starts_with("FIX") {
+        Some(cleaned)
+    } else {
+        Some(format!("FIX{}", cleaned))
+    }
+}
+
+/// Register a custom dictionary read from `source` under `key`, in place of any
+/// embedded one.
+pub fn register_dictionary(key: &str, dict: &FixDictionary, source: &str) {
     let lookup = build_lookup_from_dict(key, dict);
     let mut guard = LOOKUPS.write().expect("dictionary cache poisoned");
     guard.insert(key.to_string(), Arc::new(lookup));
 
     drop_combo_entries_for(key, &mut guard);
+    drop(guard);
+    if let Ok(mut sources) = CUSTOM_SOURCES.write() {
+        sources.insert(key.to_string(), source.to_string());
+    }
+}
+
+/// The file a custom dictionary registered under `key` was read from.
+fn custom_source(key: &str) -> Option<String> {
+    CUSTOM_SOURCES.read().ok()?.get(key).cloned()
+}
+
+/// How the dictionary that decoded a message was chosen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Selection {
+    /// From the message's own BeginString, or ApplVerID for FIXT.1.1.
+    AutoDetected,
+    /// By the `--fix` override.
+    Override,
+    /// By a `--fix-map` rule matching the message's CompIDs.
+    FixMap,
+    /// The default dictionary, because the detected one is not available.
+    Default,
+}
+
+/// Which dictionary decoded a message and where its definitions came from, for
+/// `--show-dict`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DictionaryChoice {
+    pub key: String,
+    pub selection: Selection,
+    /// The key the message itself asks for.
+    pub detected_key: String,
+    /// The `--xml` file behind the dictionary, when it is a custom one.
+    pub source: Option<String>,
+    pub fallback_key: Option<String>,
+    pub fallback_role: Option<FallbackKind>,
+    pub fallback_source: Option<String>,
+    /// Tags of the message that only the fallback defines, in the order first met.
+    pub fallback_tags: Vec<u32>,
+}
+
+/// Describe how `dict` came to decode `msg`, whose fields carry `tags`, when it was
+/// loaded with `override_key` as the `--fix` override.
+pub fn dictionary_choice(
+    msg: &str,
+    dict: &FixTagLookup,
+    override_key: Option<&str>,
+    tags: impl IntoIterator<Item = u32>,
+) -> DictionaryChoice {
+    let key = dict.schema_key();
+    let detected_key = detect_schema_key(msg);
+    let selection = if FIX_MAP
+        .get()
+        .and_then(|map| map.key_for(msg))
+        .is_some_and(|mapped| mapped == key)
+    {
+        Selection::FixMap
+    } else if override_key == Some(key) {
+        Selection::Override
+    } else if key == detected_key {
+        Selection::AutoDetected
+    } else {
+        Selection::Default
+    };
+    let mut fallback_tags = Vec::new();
+    for tag in tags {
+        let presence = dict.tag_presence(tag);
+        if !presence.in_primary && presence.in_fallback && !fallback_tags.contains(&tag) {
+            fallback_tags.push(tag);
+        }
+    }
+    DictionaryChoice {
+        key: key.to_string(),
+        selection,
+        source: custom_source(key),
+        fallback_key: dict.fallback_key().map(str::to_string),
+        fallback_role: dict.fallback_role(),
+        fallback_source: dict.fallback_key().and_then(custom_source),
+        fallback_tags,
+        detected_key,
+    }
 }
 
 fn build_lookup_from_dict(key: &str, dict: &FixDictionary) -> FixTagLookup {
@@ -395,10 +722,48 @@ fn build_lookup_from_dict(key: &str, dict: &FixDictionary) -> FixTagLookup {
         lookup.fallback = Some(session);
         lookup.fallback_role = Some(FallbackKind::Session);
     }
+    if let Some((udf, replace)) = UDF.get() {
+        lookup.apply_udf(udf, *replace);
+    }
+    if let Some(overlay) = ENUM_OVERLAY.get() {
+        lookup.apply_overlay(overlay);
+    }
 
     lookup
 }
 
+/// `--udf` fields, and whether `--udf-override` lets them replace the dictionary's
+/// own definitions, applied to every dictionary as it is built.
+static UDF: OnceLock<(UdfCatalogue, bool)> = OnceLock::new();
+
+/// `--enum-overlay` entries, applied to every dictionary as it is built.
+static ENUM_OVERLAY: OnceLock<EnumOverlay> = OnceLock::new();
+
+/// `--fix-map` rules, consulted for every message before the `--fix` override.
+static FIX_MAP: OnceLock<FixMap> = OnceLock::new();
+
+/// Install the `--fix-map` rules; only the first call has any effect.
+pub fn set_fix_map(map: FixMap) {
+    let _ = FIX_MAP.set(map);
+}
+
+/// The installed `--fix-map`, if any.
+pub fn fix_map() -> Option<&'static FixMap> {
+    FIX_MAP.get()
+}
+
+/// Install the `--enum-overlay` entries.  Call before any dictionary is loaded or
+/// registered; only the first call has any effect.
+pub fn set_enum_overlay(overlay: EnumOverlay) {
+    let _ = ENUM_OVERLAY.set(overlay);
+}
+
+/// Install the `--udf` fields.  Call before any dictionary is loaded or registered;
+/// only the first call has any effect.
+pub fn set_udf(udf: UdfCatalogue, replace: bool) {
+    let _ = UDF.set((udf, replace));
+}
+
 fn drop_combo_entries_for(key: &str, guard: &mut HashMap<String, Arc<FixTagLookup>>) {
     let stale: Vec<String> = guard
         .keys()
@@ -421,52 +786,65 @@ fn build_message_defs(
 ) -> HashMap<String, MessageDef> {
     let mut map = HashMap::new();
     for msg in &messages.items {
-        let (field_order, required) = expand_message_fields(msg, components, name_to_tag, true);
-        let (groups, membership) = collect_group_specs(&msg.groups, components, name_to_tag);
+        let (sections, required, trail) = expand_message_fields(msg, components, name_to_tag);
+        let (groups, membership) = collect_group_specs(msg, components, name_to_tag);
         map.insert(
             msg.msg_type.clone(),
             MessageDef {
-                _name: msg.name.clone(),
+                name: msg.name.clone(),
                 _msg_type: msg.msg_type.clone(),
-                field_order,
+                header_order: sections.header,
+                body_order: sections.body,
+                trailer_order: sections.trailer,
                 required,
                 groups,
                 group_membership: membership,
+                body_level: trail.body_level,
+                provenance: trail.provenance,
             },
         );
     }
     map
 }
 
+/// A message's fields split into the sections `--validate` checks separately.
+#[derive(Default)]
+struct Sections {
+    header: Vec<u32>,
+    body: Vec<u32>,
+    trailer: Vec<u32>,
+}
+
 fn expand_message_fields(
     msg: &Message,
     components: &HashMap<String, ComponentDef>,
     name_to_tag: &HashMap<String, u32>,
-    include_header_trailer: bool,
-) -> (Vec<u32>, Vec<u32>) {
-    let mut order = Vec::new();
+) -> (Sections, Vec<u32>, Trail) {
+    let mut sections = Sections::default();
     let mut required = Vec::new();
     let mut stack = Vec::new();
-
-    if include_header_trailer {
-        append_component_fields(
-            "Header",
-            components,
-            name_to_tag,
-            &mut stack,
-            &mut order,
-            &mut required,
-        );
-    }
-    append_field_refs(&msg.fields, name_to_tag, &mut order, &mut required);
+    let mut trail = Trail::default();
+
+    append_component_fields(
+        "Header",
+        components,
+        name_to_tag,
+        &mut stack,
+        &mut sections.header,
+        &mut required,
+        &mut trail,
+    );
+    let order = &mut sections.body;
+    append_field_refs(&msg.fields, name_to_tag, order, &mut required, &mut trail);
     for comp in &msg.components {
         append_component_fields(
             &comp.name,
             components,
             name_to_tag,
             &mut stack,
-            &mut order,
+            order,
             &mut required,
+            &mut trail,
         );
     }
     for group in &msg.groups {
@@ -475,24 +853,45 @@ fn expand_message_fields(
             components,
             name_to_tag,
             &mut stack,
-            &mut order,
-            &mut required,
-        );
-    }
-
-    if include_header_trailer {
-        append_component_fields(
-            "Trailer",
-            components,
-            name_to_tag,
-            &mut stack,
-            &mut order,
+            order,
             &mut required,
+            &mut trail,
         );
     }
+    append_component_fields(
+        "Trailer",
+        components,
+        name_to_tag,
+        &mut stack,
+        &mut sections.trailer,
+        &mut required,
+        &mut trail,
+    );
 
     dedupe(&mut required);
-    (order, required)
+    (sections, required, trail)
+}
+
+/// The component and group names leading to the fields being expanded, and the
+/// path recorded for each tag the first time it was reached.  Tags reached outside
+/// every group are collected as well.
+#[derive(Default)]
+struct Trail {
+    path: Vec<String>,
+    group_depth: usize,
+    provenance: FieldProvenance,
+    body_level: HashSet<u32>,
+}
+
+impl Trail {
+    fn note(&mut self, tag: u32) {
+        if self.group_depth == 0 {
+            self.body_level.insert(tag);
+        }
+        if !self.provenance.contains_key(&tag) {
+            self.provenance.insert(tag, self.path.clone());
+        }
+    }
 }
 
 fn append_field_refs(
@@ -500,10 +899,12 @@ fn append_field_refs(
     name_to_tag: &HashMap<String, u32>,
     order: &mut Vec<u32>,
     required: &mut Vec<u32>,
+    trail: &mut Trail,
 ) {
     for field in refs {
         if let Some(tag) = name_to_tag.get(&field.name) {
             order.push(*tag);
+            trail.note(*tag);
             if field.required.as_deref() == Some("Y") {
                 required.push(*tag);
             }
@@ -518,24 +919,47 @@ fn append_component_fields(
     stack: &mut Vec<String>,
     order: &mut Vec<u32>,
     required: &mut Vec<u32>,
+    trail: &mut Trail,
 ) {
     if stack.contains(&name.to_string()) {
-        eprintln!("warning: component recursion detected at {name}, skipping nested expansion");
+        warnings::warn(
+            "component-recursion",
+            name,
+            &format!("warning: recursive component detected at {name}, skipping"),
+        );
         return;
     }
     let Some(comp) = components.get(name) else {
         return;
     };
     stack.push(name.to_string());
+    trail.path.push(name.to_string());
 
-    append_field_refs(&comp.fields, name_to_tag, order, required);
+    append_field_refs(&comp.fields, name_to_tag, order, required, trail);
     for sub in &comp.components {
-        append_component_fields(&sub.name, components, name_to_tag, stack, order, required);
+        append_component_fields(
+            &sub.name,
+            components,
+            name_to_tag,
+            stack,
+            order,
+            required,
+            trail,
+        );
     }
     for group in &comp.groups {
-        append_group_fields(group, components, name_to_tag, stack, order, required);
+        append_group_fields(
+            group,
+            components,
+            name_to_tag,
+            stack,
+            order,
+            required,
+            trail,
+        );
     }
 
+    trail.path.pop();
     stack.pop();
 }
 
@@ -546,14 +970,50 @@ fn append_group_fields(
     stack: &mut Vec<String>,
     order: &mut Vec<u32>,
     required: &mut Vec<u32>,
+    trail: &mut Trail,
 ) {
-    append_field_refs(&group.fields, name_to_tag, order, required);
+    if let Some(count_tag) = name_to_tag.get(&group.name) {
+        trail.note(*count_tag);
+        if group.required.as_deref() == Some("Y") {
+            required.push(*count_tag);
+        }
+    }
+    // Fields an entry requires are only required when there is an entry, which the
+    // message-level list cannot express, so they are left out of it.
+    let mut entry_required = Vec::new();
+    trail.path.push(group.name.clone());
+    trail.group_depth += 1;
+    append_field_refs(
+        &group.fields,
+        name_to_tag,
+        order,
+        &mut entry_required,
+        trail,
+    );
     for comp in &group.components {
-        append_component_fields(&comp.name, components, name_to_tag, stack, order, required);
+        append_component_fields(
+            &comp.name,
+            components,
+            name_to_tag,
+            stack,
+            order,
+            &mut entry_required,
+            trail,
+        );
     }
     for sub in &group.groups {
-        append_group_fields(sub, components, name_to_tag, stack, order, required);
+        append_group_fields(
+            sub,
+            components,
+            name_to_tag,
+            stack,
+            order,
+            &mut entry_required,
+            trail,
+        );
     }
+    trail.group_depth -= 1;
+    trail.path.pop();
 }
 
 fn dedupe(values: &mut Vec<u32>) {
@@ -561,32 +1021,53 @@ fn dedupe(values: &mut Vec<u32>) {
     values.retain(|v| seen.insert(*v));
 }
 
+/// Build the top-level group specs for `msg`: its own groups plus those declared in
+/// the header, trailer and any component the message references (transitively
+/// through sub-components).  Groups reached via another group are nested specs.
 fn collect_group_specs(
-    groups: &[GroupDef],
+    msg: &Message,
     components: &HashMap<String, ComponentDef>,
     name_to_tag: &HashMap<String, u32>,
 ) -> (HashMap<u32, GroupSpec>, HashMap<u32, u32>) {
+    let mut top_level: Vec<&GroupDef> = msg.groups.iter().collect();
+    let mut visited = HashSet::new();
+    let comp_names = std::iter::once("Header")
+        .chain(msg.components.iter().map(|c| c.name.as_str()))
+        .chain(std::iter::once("Trailer"));
+    for name in comp_names {
+        collect_component_groups(name, components, &mut visited, &mut top_level);
+    }
+
     let mut specs = HashMap::new();
     let mut membership = HashMap::new();
     let mut stack = HashSet::new();
-    for group in groups {
+    for group in top_level {
         if let Some(spec) = build_group_spec(group, components, name_to_tag, &mut stack) {
             membership.extend(collect_memberships(&spec, spec.count_tag));
-            specs.insert(spec.count_tag, spec);
-        }
-    }
-    // also scan groups reachable via components referenced in the message
-    for comp in components.values() {
-        for group in &comp.groups {
-            if let Some(spec) = build_group_spec(group, components, name_to_tag, &mut stack) {
-                membership.extend(collect_memberships(&spec, spec.count_tag));
-                specs.entry(spec.count_tag).or_insert(spec);
-            }
+            specs.entry(spec.count_tag).or_insert(spec);
         }
     }
     (specs, membership)
 }
 
+fn collect_component_groups<'a>(
+    name: &str,
+    components: &'a HashMap<String, ComponentDef>,
+    visited: &mut HashSet<String>,
+    out: &mut Vec<&'a GroupDef>,
+) {
+    if !visited.insert(name.to_string()) {
+        return;
+    }
+    let Some(comp) = components.get(name) else {
+        return;
+    };
+    out.extend(comp.groups.iter());
+    for sub in &comp.components {
+        collect_component_groups(&sub.name, components, visited, out);
+    }
+}
+
 fn build_group_spec(
     group: &GroupDef,
     components: &HashMap<String, ComponentDef>,
@@ -602,7 +1083,13 @@ fn build_group_spec(
         .unwrap_or(count_tag);
     let mut order = Vec::new();
     let mut required = Vec::new();
-    append_field_refs(&group.fields, name_to_tag, &mut order, &mut required);
+    append_field_refs(
+        &group.fields,
+        name_to_tag,
+        &mut order,
+        &mut required,
+        &mut Trail::default(),
+    );
 
     let mut nested = HashMap::new();
     for comp in &group.components {
@@ -654,7 +1141,13 @@ fn append_component_fields_for_spec(
         return;
     };
 
-    append_field_refs(&comp.fields, name_to_tag, order, required);
+    append_field_refs(
+        &comp.fields,
+        name_to_tag,
+        order,
+        required,
+        &mut Trail::default(),
+    );
     for sub_comp in &comp.components {
         append_component_fields_for_spec(
             &sub_comp.name,
@@ -754,6 +1247,12 @@ mod tests {
             for (key, _) in &self.originals {
                 clear_override_cache_for(key);
             }
+            // Unit tests only register custom dictionaries under a guard.
+            if let Ok(mut sources) = CUSTOM_SOURCES.write() {
+                for (key, _) in &self.originals {
+                    sources.remove(key);
+                }
+            }
         }
     }
 
@@ -814,6 +1313,94 @@ mod tests {
         FixDictionary::from_xml(xml).expect("detected test dictionary parses")
     }
 
+    #[test]
+    fn multi_value_fields_describe_each_code() {
+        let _lock = LOOKUP_TEST_GUARD.lock().unwrap();
+        let dict = load_dictionary("8=FIX.4.4\u{0001}35=D\u{0001}10=000\u{0001}");
+        assert!(dict.is_multi_value(18));
+        assert!(!dict.is_multi_value(54));
+        assert_eq!(dict.describe_value(18, "1").as_deref(), Some("NOT_HELD"));
+        assert_eq!(
+            dict.describe_value(18, "1 6").as_deref(),
+            Some("NOT_HELD, PARTICIPATE_DONT_INITIATE")
+        );
+        assert_eq!(
+            dict.describe_value(18, "1 Z").as_deref(),
+            Some("NOT_HELD, ?")
+        );
+        assert_eq!(dict.describe_value(18, "Y Z"), None);
+        // Single-valued fields are never split.
+        assert_eq!(dict.describe_value(54, "1 2"), None);
+    }
+
+    #[test]
+    fn overlay_adds_enums_and_names_for_unknown_tags() {
+        let _lock = LOOKUP_TEST_GUARD.lock().unwrap();
+        let msg = "8=FIX.4.4\u{0001}35=8\u{0001}10=000\u{0001}";
+        let mut dict = (*load_dictionary(msg)).clone();
+        let overlay =
+            EnumOverlay::from_csv("150,Z,VENUE_PARKED\n9001,,VenueFlag\n9001,Y,YES\n").unwrap();
+        dict.apply_overlay(&overlay);
+
+        assert_eq!(dict.enum_description(150, "Z"), Some("VENUE_PARKED"));
+        assert_eq!(dict.enum_description(150, "F"), Some("TRADE"));
+        assert_eq!(dict.field_name(9001), "VenueFlag");
+        assert_eq!(dict.enum_description(9001, "Y"), Some("YES"));
+        // Names never replace the dictionary's own.
+        let renamed = EnumOverlay::from_csv("55,,Ticker\n").unwrap();
+        dict.apply_overlay(&renamed);
+        assert_eq!(dict.field_name(55), "Symbol");
+    }
+
+    #[test]
+    fn udf_fields_are_named_typed_and_enumerated_and_clashes_keep_the_dictionary() {
+        let _lock = LOOKUP_TEST_GUARD.lock().unwrap();
+        let msg = "8=FIX.4.4\u{0001}35=D\u{0001}10=000\u{0001}";
+        let base = (*load_dictionary(msg)).clone();
+        let udf = UdfCatalogue::from_csv(
+            "20001,InternalOrderTag,STRING\n\
+             20002,DeskCode,CHAR,E,EQUITIES\n\
+             20002,DeskCode,CHAR,F,FX\n\
+             54,Direction,STRING\n",
+        )
+        .unwrap();
+
+        let mut dict = base.clone();
+        assert_eq!(dict.apply_udf(&udf, false), vec![54]);
+        assert_eq!(dict.field_name(20001), "InternalOrderTag");
+        assert_eq!(dict.field_type(20001), Some("STRING"));
+        assert!(dict.enums_for(20001).is_none());
+        assert_eq!(dict.field_type(20002), Some("CHAR"));
+        assert_eq!(dict.enum_description(20002, "F"), Some("FX"));
+        assert_eq!(dict.enum_description(20002, "X"), None);
+        assert!(dict.tag_presence(20002).in_primary);
+        // The clash keeps Side(54) as the dictionary defines it, and says so once.
+        assert_eq!(dict.field_name(54), "Side");
+        assert_eq!(dict.enum_description(54, "1"), Some("BUY"));
+        assert_eq!(warnings::repeats("udf-conflict", "FIX44:54"), Some(0));
+
+        let mut dict = base;
+        assert_eq!(dict.apply_udf(&udf, true), vec![54]);
+        assert_eq!(dict.field_name(54), "Direction");
+        assert_eq!(dict.field_type(54), Some("STRING"));
+        assert!(dict.enums_for(54).is_none());
+    }
+
+    #[test]
+    fn declared_version_reads_begin_string_and_appl_ver_id() {
+        let declared = |msg: &str| declared_version(&msg.replace('|', "\u{0001}"));
+        assert_eq!(
+            declared("8=FIX.4.4|35=D|"),
+            Some(("FIX.4.4".to_string(), "FIX44".to_string()))
+        );
+        assert_eq!(
+            declared("8=FIXT.1.1|35=D|1128=9|"),
+            Some(("FIXT.1.1/FIX50SP2".to_string(), "FIX50SP2".to_string()))
+        );
+        assert_eq!(declared("8=FIXT.1.1|35=0|"), None);
+        assert_eq!(declared("35=0|"), None);
+    }
+
     #[test]
     fn detects_schema_from_default_appl_ver_id() {
         let _lock = LOOKUP_TEST_GUARD.lock().unwrap();
@@ -822,6 +1409,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg(feature = "dict-fix50")]
     fn load_dictionary_respects_override_key() {
         let _lock = LOOKUP_TEST_GUARD.lock().unwrap();
         reset_override_warn();
@@ -853,8 +1441,8 @@ mod tests {
         let _lock = LOOKUP_TEST_GUARD.lock().unwrap();
         let _cache_guard = LookupCacheGuard::new(&["FIX44", "FIX50SP2"]);
         reset_override_warn();
-        register_dictionary("FIX44", &small_override_dictionary());
-        register_dictionary("FIX50SP2", &small_detected_dictionary());
+        register_dictionary("FIX44", &small_override_dictionary(), "custom44.xml");
+        register_dictionary("FIX50SP2", &small_detected_dictionary(), "custom50sp2.xml");
         clear_override_cache_for("FIX44");
         clear_override_cache_for("FIX50SP2");
         let msg = "8=FIXT.1.1\u{0001}35=0\u{0001}1128=9\u{0001}10=000\u{0001}";
@@ -868,6 +1456,25 @@ mod tests {
             !override_warn_triggered(),
             "successful fallback should not trigger override warning flag"
         );
+
+        let choice = dictionary_choice(msg, &dict, Some("FIX44"), [8, 35, 1128, 10, 1128]);
+        assert_eq!(
+            choice,
+            DictionaryChoice {
+                key: "FIX44".to_string(),
+                selection: Selection::Override,
+                detected_key: "FIX50SP2".to_string(),
+                source: Some("custom44.xml".to_string()),
+                fallback_key: Some("FIX50SP2".to_string()),
+                fallback_role: Some(FallbackKind::DetectedOverride),
+                fallback_source: Some("custom50sp2.xml".to_string()),
+                fallback_tags: vec![1128],
+            }
+        );
+        let plain = "8=FIX.4.4\u{0001}35=0\u{0001}10=000\u{0001}";
+        let auto = dictionary_choice(plain, &load_dictionary(plain), None, [35]);
+        assert_eq!(auto.selection, Selection::AutoDetected);
+        assert_eq!(auto.fallback_key, None);
     }
 
     #[test]
@@ -908,4 +1515,130 @@ mod tests {
         assert!(lookup.is_repeatable(910), "nested group count tag tracked");
         assert!(lookup.is_repeatable(911), "nested field repeatable");
     }
+
+    #[test]
+    fn recursive_components_warn_once_each_however_many_messages_use_them() {
+        let xml = r#"
+<fix type='FIX' major='4' minor='4'>
+  <header><field name='BeginString' required='Y'/></header>
+  <trailer><field name='CheckSum' required='Y'/></trailer>
+  <messages>
+    <message name='First' msgtype='U1' msgcat='app'>
+      <component name='RecursionTestA'/>
+      <component name='RecursionTestB'/>
+    </message>
+    <message name='Second' msgtype='U2' msgcat='app'>
+      <component name='RecursionTestA'/>
+      <component name='RecursionTestB'/>
+    </message>
+    <message name='Third' msgtype='U3' msgcat='app'>
+      <component name='RecursionTestB'/>
+    </message>
+  </messages>
+  <components>
+    <component name='RecursionTestA'>
+      <field name='PartyID'/>
+      <component name='RecursionTestA'/>
+    </component>
+    <component name='RecursionTestB'>
+      <field name='Text'/>
+      <component name='RecursionTestB'/>
+    </component>
+  </components>
+  <fields>
+    <field number='8' name='BeginString' type='STRING'/>
+    <field number='10' name='CheckSum' type='STRING'/>
+    <field number='35' name='MsgType' type='STRING'/>
+    <field number='58' name='Text' type='STRING'/>
+    <field number='448' name='PartyID' type='STRING'/>
+  </fields>
+</fix>
+"#;
+        let dict = FixDictionary::from_xml(xml).expect("dictionary parses");
+        let (lookup, printed) = warnings::capture(|| {
+            let lookup = FixTagLookup::from_dictionary(&dict, "RECURSION");
+            crate::decoder::schema::SchemaTree::build(dict);
+            lookup
+        });
+        assert!(lookup.message_def("U3").is_some());
+        // One warning each, printed once; every later detection is only counted.
+        assert_eq!(
+            printed,
+            "warning: recursive component detected at RecursionTestA, skipping\n\
+             warning: recursive component detected at RecursionTestB, skipping\n"
+        );
+    }
+
+    #[test]
+    fn group_specs_follow_components_referenced_by_each_message() {
+        let _lock = LOOKUP_TEST_GUARD.lock().unwrap();
+        let xml = r#"
+<fix type='FIX' major='4' minor='4'>
+  <header><field name='BeginString' required='Y'/></header>
+  <trailer><field name='CheckSum' required='Y'/></trailer>
+  <messages>
+    <message name='WithParties' msgtype='P' msgcat='app'>
+      <component name='Outer'/>
+    </message>
+    <message name='Plain' msgtype='Q' msgcat='app'>
+      <field name='PartyID'/>
+    </message>
+  </messages>
+  <components>
+    <component name='Outer'>
+      <component name='Parties'/>
+    </component>
+    <component name='Parties'>
+      <group name='NoPartyIDs'>
+        <field name='PartyID'/>
+      </group>
+    </component>
+  </components>
+  <fields>
+    <field number='8' name='BeginString' type='STRING'/>
+    <field number='10' name='CheckSum' type='STRING'/>
+    <field number='35' name='MsgType' type='STRING'/>
+    <field number='448' name='PartyID' type='STRING'/>
+    <field number='453' name='NoPartyIDs' type='NUMINGROUP'/>
+  </fields>
+</fix>
+"#;
+        let dict = FixDictionary::from_xml(xml).expect("dictionary parses");
+        let lookup = FixTagLookup::from_dictionary(&dict, "TEST");
+        let with_parties = lookup.message_def("P").expect("P defined");
+        assert!(
+            with_parties.groups.contains_key(&453),
+            "group reached through nested components is top-level"
+        );
+        assert_eq!(with_parties.group_membership.get(&448), Some(&453));
+        let plain = lookup.message_def("Q").expect("Q defined");
+        assert!(
+            plain.groups.is_empty(),
+            "messages not referencing Parties must not pick up its group"
+        );
+    }
+
+    #[test]
+    fn execution_report_fields_record_their_provenance() {
+        let dict = load_dictionary("8=FIX.4.4\u{0001}35=8\u{0001}10=000\u{0001}");
+        let report = dict.message_def("8").expect("ExecutionReport defined");
+        let path = |tag: u32| report.provenance.get(&tag).map(|p| p.join(" > "));
+        assert_eq!(path(37).as_deref(), Some(""), "body field has no path");
+        assert_eq!(path(49).as_deref(), Some("Header"));
+        assert_eq!(path(453).as_deref(), Some("Parties"));
+        for tag in [448, 447, 452] {
+            assert_eq!(path(tag).as_deref(), Some("Parties > NoPartyIDs"), "{tag}");
+        }
+        assert_eq!(
+            path(523).as_deref(),
+            Some("Parties > NoPartyIDs > PtysSubGrp > NoPartySubIDs")
+        );
+    }
+
+    #[test]
+    fn normalise_fix_key_handles_variants() {
+        assert_eq!(normalise_fix_key("4.4"), Some("FIX44".into()));
+        assert_eq!(normalise_fix_key("fixt1.1"), Some("FIXT11".into()));
+        assert!(normalise_fix_key("   ").is_none());
+    }
 }
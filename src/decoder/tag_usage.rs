@@ -0,0 +1,194 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// SPDX-FileCopyrightText: 2025 Steve Clarke <stephenlclarke@mac.com> - https://xyzzy.tools
+
+//! `--tag-usage`: count which tags a log carries, how often and in which MsgTypes, so a
+//! counterparty sending fields it should not (or leaving out ones it should) stands
+//! out.  Only counts are kept, never messages, so memory is bounded by the number of
+//! distinct MsgType and tag pairs however large the log.
+
+use crate::decoder::colours::palette;
+use crate::decoder::summary::render_table;
+use crate::decoder::tag_lookup::FixTagLookup;
+use std::collections::{BTreeMap, HashSet};
+use std::io::Write;
+
+/// MsgTypes listed individually in a tag's breakdown before the rest become "others".
+const TOP_MSG_TYPES: usize = 3;
+
+#[derive(Debug, Default)]
+struct TagTally {
+    /// The field name from the first dictionary that knew the tag.
+    name: Option<String>,
+    /// Set when any message's dictionary did not define the tag.
+    unknown: bool,
+    occurrences: usize,
+    /// Messages of each MsgType carrying the tag at least once.
+    carried_by: BTreeMap<String, usize>,
+}
+
+/// Tag occurrences per MsgType across a run.
+#[derive(Debug, Default)]
+pub struct TagUsage {
+    msg_types: BTreeMap<String, usize>,
+    tags: BTreeMap<u32, TagTally>,
+}
+
+impl TagUsage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Count the tags of one message, decoded with `dict`.
+    pub fn record(&mut self, msg: &str, dict: &FixTagLookup) {
+        let fields: Vec<u32> = msg
+            .split('\u{0001}')
+            .filter_map(|field| field.split_once('=')?.0.parse().ok())
+            .collect();
+        let msg_type = msg
+            .split('\u{0001}')
+            .find_map(|field| field.strip_prefix("35="))
+            .unwrap_or("?");
+        *self.msg_types.entry(msg_type.to_string()).or_default() += 1;
+
+        let mut carried = HashSet::new();
+        for tag in fields {
+            let tally = self.tags.entry(tag).or_default();
+            tally.occurrences += 1;
+            if !carried.insert(tag) {
+                continue;
+            }
+            if dict.has_tag(tag) {
+                tally.name.get_or_insert_with(|| dict.field_name(tag));
+            } else {
+                tally.unknown = true;
+            }
+            match tally.carried_by.get_mut(msg_type) {
+                Some(count) => *count += 1,
+                None => {
+                    tally.carried_by.insert(msg_type.to_string(), 1);
+                }
+            }
+        }
+    }
+
+    /// Print one row per tag in tag order: its name, total occurrences, the MsgTypes
+    /// carrying it most often with the share of those messages that do, and a flag for
+    /// tags the dictionary does not define.
+    pub fn render(&self, out: &mut dyn Write) -> std::io::Result<()> {
+        if self.tags.is_empty() {
+            return Ok(());
+        }
+        let colours = palette();
+        let messages: usize = self.msg_types.values().sum();
+        writeln!(
+            out,
+            "{}Tag Usage{} ({} tag(s) in {} message(s))\n",
+            colours.title,
+            colours.reset,
+            self.tags.len(),
+            messages
+        )?;
+        let rows: Vec<Vec<String>> = self
+            .tags
+            .iter()
+            .map(|(tag, tally)| {
+                vec![
+                    tag.to_string(),
+                    tally.name.clone().unwrap_or_else(|| "-".to_string()),
+                    tally.occurrences.to_string(),
+                    self.breakdown(tally),
+                    if tally.unknown { "unknown" } else { "" }.to_string(),
+                ]
+            })
+            .collect();
+        render_table(out, &["Tag", "Name", "Count", "MsgTypes", "Flag"], &rows)?;
+        writeln!(out)
+    }
+
+    /// `D 120 (100%), 8 40 (50%), 0 3 (1%), others 5`: messages of each MsgType
+    /// carrying the tag, busiest first, and the share of that MsgType they make up.
+    fn breakdown(&self, tally: &TagTally) -> String {
+        let mut by_count: Vec<(&String, &usize)> = tally.carried_by.iter().collect();
+        by_count.sort_by(|a, b| b.1.cmp(a.1).then_with(|| a.0.cmp(b.0)));
+        let mut parts: Vec<String> = by_count
+            .iter()
+            .take(TOP_MSG_TYPES)
+            .map(|(msg_type, count)| {
+                let total = self.msg_types.get(*msg_type).copied().unwrap_or(1);
+                let share = (**count as f64 * 100.0 / total as f64).round();
+                format!("{msg_type} {count} ({share}%)")
+            })
+            .collect();
+        let others: usize = by_count
+            .iter()
+            .skip(TOP_MSG_TYPES)
+            .map(|(_, count)| **count)
+            .sum();
+        if others > 0 {
+            parts.push(format!("others {others}"));
+        }
+        parts.join(", ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decoder::colours::disable_colours;
+    use crate::decoder::tag_lookup::load_dictionary_with_override;
+
+    #[test]
+    fn tags_are_counted_per_msg_type_with_shares_and_unknown_flags() {
+        disable_colours();
+        let dict = load_dictionary_with_override("8=FIX.4.4\u{0001}", None);
+        let mut usage = TagUsage::new();
+        let messages = [
+            "35=D|55=X|55=Y|9999=1|",
+            "35=D|38=5|55=X|",
+            "35=8|55=X|",
+            "35=8|38=5|",
+            "35=0|55=X|",
+            "35=1|55=X|",
+        ];
+        for msg in messages {
+            usage.record(&msg.replace('|', "\u{0001}"), &dict);
+        }
+
+        let symbol = &usage.tags[&55];
+        assert_eq!(symbol.occurrences, 6);
+        assert_eq!(symbol.carried_by["D"], 2, "repeats count once per message");
+        assert_eq!(
+            usage.breakdown(symbol),
+            "D 2 (100%), 0 1 (100%), 1 1 (100%), others 1"
+        );
+        assert!(usage.tags[&9999].unknown);
+        assert!(!symbol.unknown);
+
+        let mut out = Vec::new();
+        usage.render(&mut out).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        assert!(
+            text.contains("Tag Usage (4 tag(s) in 6 message(s))"),
+            "{text}"
+        );
+        let rows: Vec<Vec<&str>> = text
+            .lines()
+            .skip(3)
+            .take_while(|line| !line.trim().is_empty())
+            .map(|line| {
+                line.split("  ")
+                    .map(str::trim)
+                    .filter(|cell| !cell.is_empty())
+                    .collect()
+            })
+            .collect();
+        let tags: Vec<&str> = rows.iter().map(|row| row[0]).collect();
+        assert_eq!(tags, ["35", "38", "55", "9999"], "{text}");
+        assert_eq!(rows[0][1..3], ["MsgType", "6"], "{text}");
+        assert_eq!(
+            rows[3],
+            ["9999", "-", "1", "D 1 (50%)", "unknown"],
+            "{text}"
+        );
+    }
+}
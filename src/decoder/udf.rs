@@ -0,0 +1,169 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// SPDX-FileCopyrightText: 2025 Steve Clarke <stephenlclarke@mac.com> - https://xyzzy.tools
+
+//! `--udf`: user-defined fields from a CSV catalogue, for firms whose custom tags live
+//! in a spreadsheet rather than a dictionary XML.  Each tag gets a name and a type,
+//! and optionally enum values, which are merged into every dictionary as `tag_lookup`
+//! builds it, so decoding and `--validate` treat them like dictionary fields.
+
+use crate::decoder::enum_overlay::{parse_tag, unquote};
+use anyhow::{Context, Result, bail};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+/// One user-defined field.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UdfField {
+    pub name: String,
+    /// The FIX type, such as `STRING` or `PRICE`, upper-cased.
+    pub field_type: String,
+    /// Enum code to description; empty when the field takes any value of its type.
+    pub values: BTreeMap<String, String>,
+    /// What the field is for, from a row without an enum code.
+    pub description: Option<String>,
+}
+
+/// User-defined fields keyed by tag number.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct UdfCatalogue {
+    pub fields: BTreeMap<u32, UdfField>,
+}
+
+impl UdfCatalogue {
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = fs::read_to_string(path)
+            .with_context(|| format!("failed to read --udf file {}", path.display()))?;
+        Self::from_csv(&text).with_context(|| format!("invalid --udf file {}", path.display()))
+    }
+
+    /// Parse `tag,name,type[,enum,description]` rows.  A tag with enum values takes
+    /// one row per value, each repeating its name and type; a row without an enum
+    /// code may carry a description of the field itself.  Blank lines, `#` comments
+    /// and a `tag,...` header row are skipped.
+    ///
+    /// ```text
+    /// tag,name,type,enum,description
+    /// 20001,InternalOrderTag,STRING
+    /// 20002,DeskCode,CHAR,E,EQUITIES
+    /// 20002,DeskCode,CHAR,F,FX
+    /// ```
+    pub fn from_csv(text: &str) -> Result<Self> {
+        let mut catalogue = Self::default();
+        for (idx, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut cells = line.splitn(5, ',').map(str::trim);
+            let mut next = || cells.next().unwrap_or_default();
+            let (tag, name, field_type, code, description) =
+                (next(), next(), next(), next(), unquote(next()));
+            if idx == 0 && tag.eq_ignore_ascii_case("tag") {
+                continue;
+            }
+            let row = idx + 1;
+            let tag = parse_tag(tag).with_context(|| format!("line {row}"))?;
+            if name.is_empty() || field_type.is_empty() {
+                bail!("line {row}: expected tag,name,type[,enum,description]");
+            }
+            let field_type = field_type.to_ascii_uppercase();
+            let field = catalogue.fields.entry(tag).or_insert_with(|| UdfField {
+                name: name.to_string(),
+                field_type: field_type.clone(),
+                values: BTreeMap::new(),
+                description: None,
+            });
+            if field.name != name || field.field_type != field_type {
+                bail!(
+                    "line {row}: tag {tag} is already {} ({}); rows for one tag must agree",
+                    field.name,
+                    field.field_type
+                );
+            }
+            match (code.is_empty(), description.is_empty()) {
+                (false, true) => bail!("line {row}: enum value '{code}' needs a description"),
+                (false, false) => {
+                    field
+                        .values
+                        .insert(code.to_string(), description.to_string());
+                }
+                (true, false) => field.description = Some(description.to_string()),
+                (true, true) => {}
+            }
+        }
+        Ok(catalogue)
+    }
+
+    /// Stack `other` on top of this catalogue; its fields replace any with the same tag.
+    pub fn extend(&mut self, other: UdfCatalogue) {
+        self.fields.extend(other.fields);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rows_name_type_and_enumerate_fields() {
+        let udf = UdfCatalogue::from_csv(
+            "tag,name,type,enum,description\n\
+             # desk tags\n\
+             20001,InternalOrderTag,string,,\"Our id, not the client's\"\n\
+             20002,DeskCode,CHAR,E,EQUITIES\n\
+             20002,DeskCode,CHAR,F,\"FX\"\n",
+        )
+        .unwrap();
+        let tag = &udf.fields[&20001];
+        assert_eq!(
+            (tag.name.as_str(), tag.field_type.as_str()),
+            ("InternalOrderTag", "STRING")
+        );
+        assert!(tag.values.is_empty());
+        assert_eq!(tag.description.as_deref(), Some("Our id, not the client's"));
+        let desk = &udf.fields[&20002];
+        assert_eq!(desk.field_type, "CHAR");
+        assert_eq!(
+            desk.values.iter().collect::<Vec<_>>(),
+            vec![
+                (&"E".to_string(), &"EQUITIES".to_string()),
+                (&"F".to_string(), &"FX".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn malformed_rows_are_rejected_with_their_line() {
+        let err = UdfCatalogue::from_csv("20001,Tag\n").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "line 1: expected tag,name,type[,enum,description]"
+        );
+        let err = UdfCatalogue::from_csv("Desk,DeskCode,CHAR\n").unwrap_err();
+        assert_eq!(format!("{err:#}"), "line 1: invalid tag number 'Desk'");
+        let err =
+            UdfCatalogue::from_csv("20002,DeskCode,CHAR,E,EQ\n20002,Desk,CHAR,F,FX\n").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "line 2: tag 20002 is already DeskCode (CHAR); rows for one tag must agree"
+        );
+        let err = UdfCatalogue::from_csv("20002,DeskCode,CHAR,E\n").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "line 1: enum value 'E' needs a description"
+        );
+    }
+
+    #[test]
+    fn later_files_replace_whole_fields() {
+        let mut udf = UdfCatalogue::from_csv("20002,DeskCode,CHAR,E,EQUITIES\n").unwrap();
+        udf.extend(UdfCatalogue::from_csv("20002,Desk,STRING\n").unwrap());
+        assert_eq!(udf.fields[&20002].name, "Desk");
+        assert!(udf.fields[&20002].values.is_empty());
+    }
+}
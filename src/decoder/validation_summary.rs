@@ -0,0 +1,140 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// SPDX-FileCopyrightText: 2025 Steve Clarke <stephenlclarke@mac.com> - https://xyzzy.tools
+
+//! `--validate-summary`: tally the errors `--validate` finds by category across the
+//! whole run, with the MsgTypes each category affected and the first line it was seen
+//! on, so a noisy log can be triaged without scrolling through every finding.
+
+use crate::decoder::colours::palette;
+use crate::decoder::summary::render_table;
+use crate::decoder::validator::{ErrorCategory, ValidationReport};
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::Write;
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+struct CategoryTally {
+    count: usize,
+    msg_types: BTreeSet<String>,
+    first_line: usize,
+}
+
+/// Validation errors counted per [`ErrorCategory`].
+#[derive(Debug, Default)]
+pub struct ValidationSummary {
+    categories: BTreeMap<ErrorCategory, CategoryTally>,
+}
+
+impl ValidationSummary {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Count every error in `report`, found in a message of `msg_type` on `line_number`.
+    pub fn record(
+        &mut self,
+        report: &ValidationReport,
+        msg_type: Option<&str>,
+        line_number: usize,
+    ) {
+        for error in &report.errors {
+            let tally = self
+                .categories
+                .entry(error.category)
+                .or_insert_with(|| CategoryTally {
+                    first_line: line_number,
+                    ..CategoryTally::default()
+                });
+            tally.count += 1;
+            tally.msg_types.insert(msg_type.unwrap_or("?").to_string());
+        }
+    }
+
+    /// The error count for `category`.
+    pub fn count(&self, category: ErrorCategory) -> usize {
+        self.categories
+            .get(&category)
+            .map_or(0, |tally| tally.count)
+    }
+
+    /// Print one row per category that had errors, in a fixed category order, with the
+    /// distinct MsgTypes affected and the first line each category was seen on.
+    pub fn render(&self, out: &mut dyn Write) -> std::io::Result<()> {
+        let colours = palette();
+        let total: usize = self.categories.values().map(|tally| tally.count).sum();
+        writeln!(
+            out,
+            "{}Validation Summary{} ({} error(s))\n",
+            colours.title, colours.reset, total
+        )?;
+        let rows: Vec<Vec<String>> = self
+            .categories
+            .iter()
+            .map(|(category, tally)| {
+                let msg_types: Vec<&str> = tally.msg_types.iter().map(String::as_str).collect();
+                vec![
+                    category.label().to_string(),
+                    tally.count.to_string(),
+                    format!("{} ({})", msg_types.len(), msg_types.join(",")),
+                    tally.first_line.to_string(),
+                ]
+            })
+            .collect();
+        render_table(
+            out,
+            &["Category", "Errors", "MsgTypes", "Example line"],
+            &rows,
+        )?;
+        writeln!(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decoder::validator::ValidationError;
+
+    fn report(categories: &[ErrorCategory]) -> ValidationReport {
+        let mut report = ValidationReport::default();
+        for category in categories {
+            report
+                .errors
+                .push(ValidationError::new(*category, category.label()));
+        }
+        report
+    }
+
+    #[test]
+    fn errors_are_counted_per_category_with_their_msg_types() {
+        let mut summary = ValidationSummary::new();
+        summary.record(
+            &report(&[ErrorCategory::Checksum, ErrorCategory::BadEnum]),
+            Some("D"),
+            3,
+        );
+        summary.record(&report(&[ErrorCategory::BadEnum]), Some("8"), 7);
+        summary.record(&report(&[ErrorCategory::BadEnum]), Some("8"), 9);
+        assert_eq!(summary.count(ErrorCategory::Checksum), 1);
+        assert_eq!(summary.count(ErrorCategory::BadEnum), 3);
+        assert_eq!(summary.count(ErrorCategory::Ordering), 0);
+
+        let enums = &summary.categories[&ErrorCategory::BadEnum];
+        assert_eq!(enums.first_line, 3);
+        assert_eq!(
+            enums
+                .msg_types
+                .iter()
+                .map(String::as_str)
+                .collect::<Vec<_>>(),
+            ["8", "D"]
+        );
+
+        let mut out = Vec::new();
+        summary.render(&mut out).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        assert!(text.contains("Validation Summary (4 error(s))"));
+        let checksum = text.find("checksum").unwrap();
+        let bad_enum = text.find("bad enum").unwrap();
+        assert!(checksum < bad_enum, "{text}");
+        assert!(text.contains("2 (8,D)"), "{text}");
+    }
+}
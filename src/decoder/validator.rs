@@ -1,134 +1,636 @@
 // SPDX-License-Identifier: AGPL-3.0-only
 // SPDX-FileCopyrightText: 2025 Steve Clarke <stephenlclarke@mac.com> - https://xyzzy.tools
 
-use crate::decoder::fixparser::{FieldValue, parse_fix};
+use crate::decoder::clock_skew::{self, Skew};
+use crate::decoder::deprecations;
+use crate::decoder::fixparser::{FieldValue, ParsedMessage};
+use crate::decoder::raw_bytes;
 use crate::decoder::tag_lookup::{FixTagLookup, GroupSpec as MessageDefGroupSpec, MessageDef};
-use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+use chrono::{Duration, NaiveDate, NaiveTime};
 use once_cell::sync::Lazy;
 use regex::Regex;
+use serde::Serialize;
 use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::ops::Range;
+use std::sync::OnceLock;
 
-#[derive(Debug, Default)]
+/// The kind of problem a [`ValidationError`] reports, for `--validate-summary`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCategory {
+    Checksum,
+    BodyLength,
+    /// BodyLength(9) ends somewhere other than the start of CheckSum(10): past it, or
+    /// partway through a field.  The sign of a message truncated or spliced upstream
+    /// and then given a fresh CheckSum.
+    Spliced,
+    DataLength,
+    MissingRequired,
+    UnknownMsgType,
+    UnknownTag,
+    BadEnum,
+    BadType,
+    Ordering,
+    Duplicate,
+    Group,
+    BusinessRule,
+    ClockSkew,
+    /// `--validate-session`: a message that contradicts the state of its order.
+    OrderState,
+    Deprecated,
+}
+
+impl ErrorCategory {
+    /// Every category, in the order summaries list them.
+    pub const ALL: [ErrorCategory; 16] = [
+        Self::Checksum,
+        Self::BodyLength,
+        Self::Spliced,
+        Self::DataLength,
+        Self::MissingRequired,
+        Self::UnknownMsgType,
+        Self::UnknownTag,
+        Self::BadEnum,
+        Self::BadType,
+        Self::Ordering,
+        Self::Duplicate,
+        Self::Group,
+        Self::BusinessRule,
+        Self::ClockSkew,
+        Self::OrderState,
+        Self::Deprecated,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Checksum => "checksum",
+            Self::BodyLength => "body length",
+            Self::Spliced => "truncated or spliced",
+            Self::DataLength => "data length",
+            Self::MissingRequired => "missing required",
+            Self::UnknownMsgType => "unknown MsgType",
+            Self::UnknownTag => "unknown tag",
+            Self::BadEnum => "bad enum",
+            Self::BadType => "bad type",
+            Self::Ordering => "ordering",
+            Self::Duplicate => "duplicate",
+            Self::Group => "group",
+            Self::BusinessRule => "business rule",
+            Self::ClockSkew => "clock skew",
+            Self::OrderState => "order state",
+            Self::Deprecated => "deprecated",
+        }
+    }
+}
+
+/// One validation error, anchored where possible to the field it concerns.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ValidationError {
+    pub category: ErrorCategory,
+    pub message: String,
+    /// Tag the error is annotated against; `None` for message-level problems.
+    pub tag: Option<u32>,
+    /// Zero-based index of the offending field, or `None` when the tag is absent.
+    pub field_index: Option<usize>,
+    /// Byte range of the offending `tag=value` pair in the original message.
+    pub span: Option<Range<usize>>,
+}
+
+impl ValidationError {
+    /// An error that is not tied to any field.
+    pub fn new(category: ErrorCategory, message: impl Into<String>) -> Self {
+        Self {
+            category,
+            message: message.into(),
+            tag: None,
+            field_index: None,
+            span: None,
+        }
+    }
+
+    /// Where the error sits in the message, e.g. `(field #7, bytes 45..58)`.  Field
+    /// numbers are one-based to match how a reader counts along the message.
+    pub fn location_label(&self) -> String {
+        match (self.field_index, &self.span, self.tag) {
+            (Some(idx), Some(span), _) => {
+                format!("(field #{}, bytes {}..{})", idx + 1, span.start, span.end)
+            }
+            (_, _, Some(_)) => "(not present in message)".to_string(),
+            _ => "(no field location)".to_string(),
+        }
+    }
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+#[derive(Debug, Default, Clone)]
 pub struct ValidationReport {
-    pub errors: Vec<String>,
+    pub errors: Vec<ValidationError>,
     pub tag_errors: HashMap<u32, Vec<String>>,
+    /// Findings that do not make the message invalid (`--warn-deprecated`).
+    pub warnings: Vec<ValidationError>,
 }
 
 impl ValidationReport {
     pub fn is_clean(&self) -> bool {
         self.errors.is_empty()
     }
+
+    /// Add an error about `tag` found outside [`validate_parsed_message`], located and
+    /// annotated as the validator's own are.
+    pub fn add_tag_error(
+        &mut self,
+        msg: &ParsedMessage<'_>,
+        tag: u32,
+        category: ErrorCategory,
+        message: String,
+    ) {
+        let field_index = msg.position(tag);
+        self.tag_errors
+            .entry(tag)
+            .or_default()
+            .push(message.clone());
+        self.errors.push(ValidationError {
+            category,
+            message,
+            tag: Some(tag),
+            field_index,
+            span: field_index.and_then(|idx| msg.span(idx)),
+        });
+    }
+
+    /// [`tag_errors`](Self::tag_errors) with each annotation followed by its
+    /// [`location_label`](ValidationError::location_label), for `--error-locations`.
+    pub fn located_tag_errors(&self) -> HashMap<u32, Vec<String>> {
+        let mut located: HashMap<u32, Vec<String>> = HashMap::new();
+        for err in &self.errors {
+            if let Some(tag) = err.tag {
+                located.entry(tag).or_default().push(format!(
+                    "{} {}",
+                    err.message,
+                    err.location_label()
+                ));
+            }
+        }
+        located
+    }
+}
+
+/// Collects the errors for one message, keeping the structured list and the per-tag
+/// annotations used by the prettifier in step.
+struct Findings<'m, 'a> {
+    msg: &'m ParsedMessage<'a>,
+    errors: Vec<ValidationError>,
+    tag_errors: HashMap<u32, Vec<String>>,
+    warnings: Vec<ValidationError>,
+}
+
+impl<'m, 'a> Findings<'m, 'a> {
+    fn new(msg: &'m ParsedMessage<'a>) -> Self {
+        Self {
+            msg,
+            errors: Vec::new(),
+            tag_errors: HashMap::new(),
+            warnings: Vec::new(),
+        }
+    }
+
+    /// An error about the field at `index`.
+    fn at(&mut self, index: usize, category: ErrorCategory, message: String) {
+        let tag = self.msg.fields[index].tag;
+        self.push(tag, Some(index), category, message);
+    }
+
+    /// An error about `tag`, located at the occurrence the message's value is read
+    /// from, or unlocated when the tag is absent.
+    fn on_tag(&mut self, tag: u32, category: ErrorCategory, message: String) {
+        let index = self.msg.position(tag);
+        self.push(tag, index, category, message);
+    }
+
+    /// A message-level error that is not annotated against any field.
+    fn general(&mut self, category: ErrorCategory, message: String) {
+        self.errors.push(ValidationError::new(category, message));
+    }
+
+    /// A warning about the field at `index`.  Warnings are not annotated on the field,
+    /// which carries its own hint when decoded.
+    fn warn_at(&mut self, index: usize, category: ErrorCategory, message: String) {
+        self.warnings.push(ValidationError {
+            category,
+            message,
+            tag: Some(self.msg.fields[index].tag),
+            field_index: Some(index),
+            span: self.msg.span(index),
+        });
+    }
+
+    fn push(
+        &mut self,
+        tag: u32,
+        field_index: Option<usize>,
+        category: ErrorCategory,
+        message: String,
+    ) {
+        self.tag_errors
+            .entry(tag)
+            .or_default()
+            .push(message.clone());
+        self.errors.push(ValidationError {
+            category,
+            message,
+            tag: Some(tag),
+            field_index,
+            span: field_index.and_then(|idx| self.msg.span(idx)),
+        });
+    }
+
+    fn into_report(self) -> ValidationReport {
+        ValidationReport {
+            errors: self.errors,
+            tag_errors: self.tag_errors,
+            warnings: self.warnings,
+        }
+    }
 }
 
 /// Validate a single FIX message string against the provided dictionary,
 /// returning a list of human-readable errors (or empty when valid).
+/// For callers holding the raw text; a message already split goes to
+/// [`validate_parsed_message`].
 pub fn validate_fix_message(msg: &str, dict: &FixTagLookup) -> ValidationReport {
-    let fields = parse_fix(msg);
-    let (field_map, seen_tags, duplicates) = build_field_map(&fields, dict);
-    let mut errors = Vec::new();
-    let mut tag_errors: HashMap<u32, Vec<String>> = HashMap::new();
-
-    for dup in duplicates {
-        let err = format!("Duplicate tag {} encountered", dup);
-        errors.push(err.clone());
-        tag_errors.entry(dup).or_default().push(err);
-    }
-
-    let (msg_type_errs, msg_def_opt) = validate_msg_type(&field_map, dict, &mut tag_errors);
-    errors.extend(msg_type_errs);
-    errors.extend(validate_body_length(msg, &field_map, &mut tag_errors));
-    errors.extend(validate_field_enums_and_types(
-        &fields,
-        dict,
-        &mut tag_errors,
-    ));
+    validate_parsed_message(&ParsedMessage::parse(msg), dict)
+}
+
+/// Validate a message that has already been split into fields.
+pub fn validate_parsed_message(msg: &ParsedMessage<'_>, dict: &FixTagLookup) -> ValidationReport {
+    let fields = &msg.fields;
+    let mut findings = Findings::new(msg);
+    let seen_tags: HashSet<u32> = fields.iter().map(|f| f.tag).collect();
+    let duplicates = match msg.get(35).and_then(|mt| dict.message_def(mt)) {
+        Some(msg_def) => collect_duplicates(fields, msg_def),
+        None => collect_unscoped_duplicates(fields, dict),
+    };
+
+    for idx in duplicates {
+        findings.at(
+            idx,
+            ErrorCategory::Duplicate,
+            format!("Duplicate tag {} encountered", fields[idx].tag),
+        );
+    }
+
+    let msg_def_opt = validate_msg_type(msg, dict, &mut findings);
+    validate_body_length(msg, &mut findings);
+    validate_field_enums_and_types(fields, dict, &mut findings);
+    validate_data_lengths(fields, dict, &mut findings);
 
     if let Some(msg_def) = msg_def_opt {
-        errors.extend(validate_required_fields(
-            &msg_def.required,
-            &seen_tags,
-            dict,
-            &mut tag_errors,
-        ));
-        errors.extend(validate_field_ordering(
-            &fields,
-            &msg_def.field_order,
-            &mut tag_errors,
-        ));
-        errors.extend(validate_repeating_groups(
-            &fields,
-            msg_def,
-            dict,
-            &mut tag_errors,
-        ));
-    }
-    errors.extend(validate_checksum_field(msg, &field_map, &mut tag_errors));
-
-    ValidationReport { errors, tag_errors }
-}
-
-fn build_field_map(
+        validate_required_fields(msg_def, &seen_tags, dict, &mut findings);
+        validate_field_ordering(fields, msg_def, dict, &mut findings);
+        validate_repeating_groups(fields, msg_def, dict, &mut findings);
+    }
+    validate_checksum_field(msg, &mut findings);
+    validate_business_rules(
+        msg,
+        DISABLED_RULES.get().unwrap_or(&HashSet::new()),
+        &mut findings,
+    );
+    validate_clock_skew(msg, &mut findings);
+    if warn_deprecated_enabled() {
+        validate_deprecations(msg, dict.schema_key(), &mut findings);
+    }
+
+    findings.into_report()
+}
+
+/// A cross-field business rule checked on top of the dictionary.  `check` returns
+/// each violation anchored to the tag that should be highlighted.  Adding a rule
+/// means writing one `check` function and listing it in [`BUSINESS_RULES`].
+pub struct BusinessRule {
+    pub name: &'static str,
+    pub description: &'static str,
+    check: fn(&ParsedMessage<'_>) -> Vec<(u32, String)>,
+}
+
+pub const BUSINESS_RULES: &[BusinessRule] = &[
+    BusinessRule {
+        name: "logon-reset-seqnum",
+        description: "Logon with ResetSeqNumFlag(141)=Y must have MsgSeqNum(34)=1",
+        check: check_logon_reset_seqnum,
+    },
+    BusinessRule {
+        name: "limit-price",
+        description: "Limit and stop-limit orders (OrdType(40)=2/4) require Price(44)",
+        check: check_limit_price,
+    },
+    BusinessRule {
+        name: "stop-price",
+        description: "Stop and stop-limit orders (OrdType(40)=3/4) require StopPx(99)",
+        check: check_stop_price,
+    },
+    BusinessRule {
+        name: "fill-last",
+        description: "ExecutionReport fills (ExecType(150)=F) require LastQty(32) and LastPx(31)",
+        check: check_fill_last,
+    },
+    BusinessRule {
+        name: "settl-after-trade",
+        description: "SettlDate(64) must not be before TradeDate(75)",
+        check: check_settl_after_trade,
+    },
+];
+
+static DISABLED_RULES: OnceLock<HashSet<&'static str>> = OnceLock::new();
+
+/// Look up a business rule by name.
+pub fn find_rule(name: &str) -> Option<&'static BusinessRule> {
+    BUSINESS_RULES.iter().find(|rule| rule.name == name)
+}
+
+/// Switch off business rules for the rest of the run (`--rules -NAME`).  Only the
+/// first call takes effect.
+pub fn disable_rules(names: HashSet<&'static str>) {
+    let _ = DISABLED_RULES.set(names);
+}
+
+static MAX_DECIMALS: OnceLock<usize> = OnceLock::new();
+
+/// Flag price, quantity and amount values with more than `limit` decimal places for
+/// the rest of the run (`--max-decimals`).  Only the first call takes effect.
+pub fn set_max_decimals(limit: usize) {
+    let _ = MAX_DECIMALS.set(limit);
+}
+
+static CLOCK_SKEW_LIMIT: OnceLock<Duration> = OnceLock::new();
+
+/// Flag messages whose SendingTime(52) and TransactTime(60) are more than `limit` apart
+/// for the rest of the run (`--check-clock-skew`).  Only the first call takes effect.
+pub fn set_clock_skew_limit(limit: Duration) {
+    let _ = CLOCK_SKEW_LIMIT.set(limit);
+}
+
+static WARN_DEPRECATED: OnceLock<bool> = OnceLock::new();
+
+/// Report deprecated fields and enum values as warnings for the rest of the run
+/// (`--warn-deprecated`).  Only the first call takes effect.
+pub fn set_warn_deprecated(enabled: bool) {
+    let _ = WARN_DEPRECATED.set(enabled);
+}
+
+pub fn warn_deprecated_enabled() -> bool {
+    WARN_DEPRECATED.get().copied().unwrap_or(false)
+}
+
+/// Warn about each field the table in [`deprecations`] marks as deprecated in the
+/// dictionary `key` the message was validated against.
+fn validate_deprecations(msg: &ParsedMessage<'_>, key: &str, findings: &mut Findings) {
+    for (idx, field) in msg.fields.iter().enumerate() {
+        if let Some(dep) = deprecations::lookup(key, field.tag, &field.value) {
+            findings.warn_at(idx, ErrorCategory::Deprecated, dep.describe(key));
+        }
+    }
+}
+
+fn validate_clock_skew(msg: &ParsedMessage<'_>, findings: &mut Findings) {
+    if let Some(limit) = CLOCK_SKEW_LIMIT.get()
+        && let Some(Skew::Measured(skew)) = clock_skew::measure(msg)
+        && skew.abs() > *limit
+    {
+        findings.on_tag(
+            60,
+            ErrorCategory::ClockSkew,
+            clock_skew::describe(skew, *limit),
+        );
+    }
+}
+
+fn validate_business_rules(
+    msg: &ParsedMessage<'_>,
+    disabled: &HashSet<&'static str>,
+    findings: &mut Findings,
+) {
+    for rule in BUSINESS_RULES.iter().filter(|r| !disabled.contains(r.name)) {
+        for (tag, message) in (rule.check)(msg) {
+            findings.on_tag(
+                tag,
+                ErrorCategory::BusinessRule,
+                format!("{message} [{}]", rule.name),
+            );
+        }
+    }
+}
+
+/// Order entry messages that carry the order's own OrdType and prices.
+const ORDER_ENTRY_TYPES: &[&str] = &["D", "G"];
+
+fn is_order_entry(msg: &ParsedMessage<'_>) -> bool {
+    msg.get(35)
+        .is_some_and(|mt| ORDER_ENTRY_TYPES.contains(&mt))
+}
+
+fn check_logon_reset_seqnum(msg: &ParsedMessage<'_>) -> Vec<(u32, String)> {
+    if msg.get(35) != Some("A") || msg.get(141) != Some("Y") {
+        return Vec::new();
+    }
+    match msg.get(34) {
+        Some("1") => Vec::new(),
+        got => vec![(
+            34,
+            format!(
+                "Logon with ResetSeqNumFlag(141)=Y must have MsgSeqNum(34)=1, got {}",
+                got.unwrap_or("none")
+            ),
+        )],
+    }
+}
+
+fn check_limit_price(msg: &ParsedMessage<'_>) -> Vec<(u32, String)> {
+    require_for_ord_type(msg, &["2", "4"], 44, "Price")
+}
+
+fn check_stop_price(msg: &ParsedMessage<'_>) -> Vec<(u32, String)> {
+    require_for_ord_type(msg, &["3", "4"], 99, "StopPx")
+}
+
+fn require_for_ord_type(
+    msg: &ParsedMessage<'_>,
+    ord_types: &[&str],
+    tag: u32,
+    name: &str,
+) -> Vec<(u32, String)> {
+    match msg.get(40) {
+        Some(ord_type)
+            if is_order_entry(msg) && ord_types.contains(&ord_type) && msg.get(tag).is_none() =>
+        {
+            vec![(
+                tag,
+                format!("OrdType(40)={ord_type} requires {name}({tag})"),
+            )]
+        }
+        _ => Vec::new(),
+    }
+}
+
+fn check_fill_last(msg: &ParsedMessage<'_>) -> Vec<(u32, String)> {
+    if msg.get(35) != Some("8") || msg.get(150) != Some("F") {
+        return Vec::new();
+    }
+    [(32, "LastQty"), (31, "LastPx")]
+        .into_iter()
+        .filter(|(tag, _)| msg.get(*tag).is_none())
+        .map(|(tag, name)| (tag, format!("ExecType(150)=F requires {name}({tag})")))
+        .collect()
+}
+
+fn check_settl_after_trade(msg: &ParsedMessage<'_>) -> Vec<(u32, String)> {
+    let parse = |tag| NaiveDate::parse_from_str(msg.get(tag)?, "%Y%m%d").ok();
+    match (parse(64), parse(75)) {
+        (Some(settl), Some(trade)) if settl < trade => vec![(
+            64,
+            format!(
+                "SettlDate(64) {} is before TradeDate(75) {}",
+                settl.format("%Y%m%d"),
+                trade.format("%Y%m%d")
+            ),
+        )],
+        _ => Vec::new(),
+    }
+}
+
+/// Index of every repeated tag that the message's own groups do not account for.
+/// Body-level tags may appear once outside their groups; a group member may appear
+/// once per entry.  Group members stranded outside their group are left to the group
+/// checks.
+fn collect_duplicates(fields: &[FieldValue], msg_def: &MessageDef) -> Vec<usize> {
+    let mut duplicates = Vec::new();
+    let mut seen = HashSet::new();
+    let mut idx = 0;
+    while idx < fields.len() {
+        let tag = fields[idx].tag;
+        let body_level =
+            msg_def.body_level.contains(&tag) || !msg_def.group_membership.contains_key(&tag);
+        if body_level && !seen.insert(tag) {
+            duplicates.push(idx);
+        }
+        idx += match msg_def.groups.get(&tag) {
+            Some(spec) => collect_group_duplicates(fields, idx, spec, &mut duplicates),
+            None => 1,
+        };
+    }
+    duplicates
+}
+
+/// Walk the entries of the group whose count tag is at `start_idx`, recording tags that
+/// repeat within a single entry.  Returns the number of fields consumed.
+fn collect_group_duplicates(
     fields: &[FieldValue],
-    dict: &FixTagLookup,
-) -> (HashMap<u32, String>, HashSet<u32>, Vec<u32>) {
-    let mut field_map = HashMap::new();
+    start_idx: usize,
+    spec: &MessageDefGroupSpec,
+    duplicates: &mut Vec<usize>,
+) -> usize {
+    let mut idx = start_idx + 1;
+    while idx < fields.len() && fields[idx].tag == spec.delim {
+        let entry_start = idx;
+        let mut seen = HashSet::new();
+        while idx < fields.len() {
+            let tag = fields[idx].tag;
+            if (tag == spec.delim && idx != entry_start) || !spec.entry_tag_set.contains(&tag) {
+                break;
+            }
+            if !seen.insert(tag) {
+                duplicates.push(idx);
+            }
+            idx += match spec.nested.get(&tag) {
+                Some(nested) => collect_group_duplicates(fields, idx, nested, duplicates),
+                None => 1,
+            };
+        }
+    }
+    idx - start_idx
+}
+
+/// Fallback for messages without a definition: any repeat of a tag that no group in
+/// the dictionary could carry.
+fn collect_unscoped_duplicates(fields: &[FieldValue], dict: &FixTagLookup) -> Vec<usize> {
     let mut seen = HashSet::new();
     let mut duplicates = Vec::new();
-    for field in fields {
+    for (idx, field) in fields.iter().enumerate() {
         if !seen.insert(field.tag) && !dict.is_repeatable(field.tag) {
-            duplicates.push(field.tag);
+            duplicates.push(idx);
         }
-        field_map.insert(field.tag, field.value.clone());
     }
-    (field_map, seen, duplicates)
+    duplicates
 }
 
 fn validate_msg_type<'a>(
-    field_map: &HashMap<u32, String>,
+    msg: &ParsedMessage<'_>,
     dict: &'a FixTagLookup,
-    tag_errors: &mut HashMap<u32, Vec<String>>,
-) -> (Vec<String>, Option<&'a MessageDef>) {
-    match field_map.get(&35) {
+    findings: &mut Findings,
+) -> Option<&'a MessageDef> {
+    match msg.get(35) {
         None => {
-            let err = "Missing required tag 35 (MsgType)".to_string();
-            tag_errors.entry(35).or_default().push(err.clone());
-            (vec![err], None)
+            findings.on_tag(
+                35,
+                ErrorCategory::MissingRequired,
+                "Missing required tag 35 (MsgType)".to_string(),
+            );
+            None
         }
         Some(msg_type) => match dict.message_def(msg_type) {
-            Some(def) => (Vec::new(), Some(def)),
+            Some(def) => Some(def),
             None => {
-                let err = format!("Unknown MsgType: {}", msg_type);
-                tag_errors.entry(35).or_default().push(err.clone());
-                (vec![err], None)
+                findings.on_tag(
+                    35,
+                    ErrorCategory::UnknownMsgType,
+                    format!("Unknown MsgType: {}", msg_type),
+                );
+                None
             }
         },
     }
 }
 
+/// Report each required tag the message lacks.  A required repeating group is missing
+/// when its NumInGroup tag is; `NoX=0` with no entries is a present, empty group.
 fn validate_required_fields(
-    required: &[u32],
+    msg_def: &MessageDef,
     seen_tags: &HashSet<u32>,
     dict: &FixTagLookup,
-    tag_errors: &mut HashMap<u32, Vec<String>>,
-) -> Vec<String> {
-    let mut errors = Vec::new();
-    for tag in required {
-        if !seen_tags.contains(tag) {
-            let err = format!("Missing required tag {} ({})", tag, dict.field_name(*tag));
-            errors.push(err.clone());
-            tag_errors.entry(*tag).or_default().push(err);
+    findings: &mut Findings,
+) {
+    for tag in &msg_def.required {
+        if seen_tags.contains(tag) {
+            continue;
+        }
+        if msg_def.groups.contains_key(tag) {
+            findings.on_tag(
+                *tag,
+                ErrorCategory::Group,
+                format!("Required group {} ({}) missing", tag, dict.field_name(*tag)),
+            );
+        } else {
+            findings.on_tag(
+                *tag,
+                ErrorCategory::MissingRequired,
+                format!("Missing required tag {} ({})", tag, dict.field_name(*tag)),
+            );
         }
     }
-    errors
 }
 
 fn validate_field_enums_and_types(
     fields: &[FieldValue],
     dict: &FixTagLookup,
-    tag_errors: &mut HashMap<u32, Vec<String>>,
-) -> Vec<String> {
-    let mut errors = Vec::new();
-    for field in fields {
+    findings: &mut Findings,
+) {
+    let time_rules = TimeRules::for_schema(dict.schema_key());
+    for (idx, field) in fields.iter().enumerate() {
         let presence = dict.tag_presence(field.tag);
         if !presence.in_primary && !presence.in_fallback {
             let err = if let Some(fbk) = presence.fallback_key {
@@ -139,8 +641,7 @@ fn validate_field_enums_and_types(
             } else {
                 format!("Unknown tag {} in FIX {}", field.tag, presence.primary_key)
             };
-            errors.push(err.clone());
-            tag_errors.entry(field.tag).or_default().push(err);
+            findings.at(idx, ErrorCategory::UnknownTag, err);
             continue;
         }
 
@@ -156,88 +657,254 @@ fn validate_field_enums_and_types(
                 "Tag {} is defined in override FIX {} but unknown in detected FIX {}",
                 field.tag, presence.primary_key, fbk
             );
-            errors.push(err.clone());
-            tag_errors.entry(field.tag).or_default().push(err);
+            findings.at(idx, ErrorCategory::UnknownTag, err);
         }
 
-        if let Some(enums) = dict.enums_for(field.tag)
-            && !enums.contains_key(&field.value)
-        {
-            let err = format!("Invalid enum value '{}'", field.value);
-            errors.push(err.clone());
-            tag_errors.entry(field.tag).or_default().push(err);
+        if let Some(enums) = dict.enums_for(field.tag) {
+            for code in invalid_enum_codes(dict, field, enums) {
+                findings.at(
+                    idx,
+                    ErrorCategory::BadEnum,
+                    format!("Invalid enum value '{code}'"),
+                );
+            }
         }
 
-        if let Some(field_type) = dict.field_type(field.tag)
-            && !is_valid_type(&field.value, field_type)
+        let Some(field_type) = dict.field_type(field.tag) else {
+            continue;
+        };
+        if let Some(err) =
+            float_encoding_error(&field.value, field_type, MAX_DECIMALS.get().copied())
         {
+            findings.at(idx, ErrorCategory::BadType, err);
+        } else if !is_valid_type(&field.value, field_type, time_rules) {
             let err = format!(
                 "Invalid type: expected {}, got '{}'",
                 field_type, field.value
             );
-            errors.push(err.clone());
-            tag_errors.entry(field.tag).or_default().push(err);
+            findings.at(idx, ErrorCategory::BadType, err);
+        }
+    }
+}
+
+/// The codes in `field` that are not in `enums`.  Multi-value fields such as
+/// ExecInst(18) are checked code by code rather than as one string.
+fn invalid_enum_codes<'a>(
+    dict: &FixTagLookup,
+    field: &'a FieldValue,
+    enums: &HashMap<String, String>,
+) -> Vec<&'a str> {
+    let value = field.value.as_str();
+    if enums.contains_key(value) {
+        return Vec::new();
+    }
+    if !dict.is_multi_value(field.tag) || value.split_whitespace().next().is_none() {
+        return vec![value];
+    }
+    value
+        .split_whitespace()
+        .filter(|code| !enums.contains_key(*code))
+        .collect()
+}
+
+/// A LENGTH field such as EncodedTextLen(354) declares the size in bytes of the DATA
+/// field after it.  BodyLength(9), which measures the whole body, is checked separately.
+fn validate_data_lengths(fields: &[FieldValue], dict: &FixTagLookup, findings: &mut Findings) {
+    for (idx, pair) in fields.windows(2).enumerate() {
+        let (length, data) = (&pair[0], &pair[1]);
+        if length.tag == 9
+            || dict.field_type(length.tag) != Some("LENGTH")
+            || dict.field_type(data.tag) != Some("DATA")
+        {
+            continue;
+        }
+        let Ok(declared) = length.value.parse::<usize>() else {
+            continue;
+        };
+        let actual = raw_bytes::byte_len(&data.value);
+        if declared != actual {
+            findings.at(
+                idx,
+                ErrorCategory::DataLength,
+                format!(
+                    "{}({}) declares {} bytes but {}({}) has {}",
+                    dict.field_name(length.tag),
+                    length.tag,
+                    declared,
+                    dict.field_name(data.tag),
+                    data.tag,
+                    actual
+                ),
+            );
+        }
+    }
+}
+
+/// The fields every message must start with, in this order.
+const LEADING_TAGS: [u32; 3] = [8, 9, 35];
+
+/// The part of a message a tag is defined in, for the ordering rules.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Section {
+    Header,
+    Body,
+    Trailer,
+}
+
+impl Section {
+    fn label(self) -> &'static str {
+        match self {
+            Self::Header => "header",
+            Self::Body => "body",
+            Self::Trailer => "trailer",
         }
     }
-    errors
 }
 
+/// Check where each field sits: BeginString(8), BodyLength(9) and MsgType(35) first,
+/// the rest of the header before the body, the trailer after it, and the body and the
+/// trailer each in dictionary order.  FIX leaves the order of the remaining header
+/// fields open, so it is not checked.  Tags the message does not define are ignored.
 fn validate_field_ordering(
     fields: &[FieldValue],
-    expected_order: &[u32],
-    tag_errors: &mut HashMap<u32, Vec<String>>,
-) -> Vec<String> {
-    let mut order_index = HashMap::new();
-    for (idx, tag) in expected_order.iter().enumerate() {
-        order_index.insert(*tag, idx);
-    }
-
-    let mut errors = Vec::new();
-    let mut last_index = -1isize;
-    for field in fields {
-        if let Some(&idx) = order_index.get(&field.tag) {
-            let idx = idx as isize;
-            if idx < last_index {
-                let err = format!("Tag {} out of order", field.tag);
-                errors.push(err.clone());
-                tag_errors.entry(field.tag).or_default().push(err);
+    msg_def: &MessageDef,
+    dict: &FixTagLookup,
+    findings: &mut Findings,
+) {
+    let named = |tag: u32| format!("{} ({})", tag, dict.field_name(tag));
+    for (slot, &tag) in LEADING_TAGS.iter().enumerate() {
+        if fields.get(slot).is_some_and(|field| field.tag == tag) {
+            continue;
+        }
+        if let Some(pos) = fields.iter().position(|field| field.tag == tag) {
+            findings.at(
+                pos,
+                ErrorCategory::Ordering,
+                format!(
+                    "Tag {} must be field {} of the message: the header starts 8, 9, 35",
+                    tag,
+                    slot + 1
+                ),
+            );
+        }
+    }
+
+    let positions = |order: &[u32]| -> HashMap<u32, usize> {
+        order
+            .iter()
+            .enumerate()
+            .map(|(idx, &tag)| (tag, idx))
+            .collect()
+    };
+    let header: HashSet<u32> = msg_def.header_order.iter().copied().collect();
+    let body = positions(&msg_def.body_order);
+    let trailer = positions(&msg_def.trailer_order);
+    let sections: Vec<Option<Section>> = fields
+        .iter()
+        .map(|field| {
+            if header.contains(&field.tag) {
+                Some(Section::Header)
+            } else if trailer.contains_key(&field.tag) {
+                Some(Section::Trailer)
+            } else if body.contains_key(&field.tag) {
+                Some(Section::Body)
+            } else {
+                None
             }
-            last_index = idx;
+        })
+        .collect();
+
+    if let Some(first) = sections
+        .iter()
+        .position(|section| matches!(section, Some(Section::Body | Section::Trailer)))
+    {
+        let section = sections[first].map_or("", Section::label);
+        for (pos, field) in fields.iter().enumerate().skip(first + 1) {
+            let tag = field.tag;
+            if sections[pos] == Some(Section::Header) && !LEADING_TAGS.contains(&tag) {
+                findings.at(
+                    pos,
+                    ErrorCategory::Ordering,
+                    format!(
+                        "Header tag {} appears after {} tag {}; the header must precede the body",
+                        tag,
+                        section,
+                        named(fields[first].tag)
+                    ),
+                );
+            }
+        }
+    }
+    for (pos, field) in fields.iter().enumerate() {
+        if sections[pos] != Some(Section::Trailer) {
+            continue;
+        }
+        let later_body = (pos + 1..fields.len()).find(|&p| sections[p] == Some(Section::Body));
+        if let Some(body_pos) = later_body {
+            findings.at(
+                pos,
+                ErrorCategory::Ordering,
+                format!(
+                    "Trailer tag {} appears before body tag {}; the trailer must follow the body",
+                    field.tag,
+                    named(fields[body_pos].tag)
+                ),
+            );
+        }
+    }
+
+    for (section, order) in [(Section::Body, &body), (Section::Trailer, &trailer)] {
+        let mut last_index = None;
+        for (pos, field) in fields.iter().enumerate() {
+            if sections[pos] != Some(section) {
+                continue;
+            }
+            let idx = order[&field.tag];
+            if last_index.is_some_and(|last| idx < last) {
+                findings.at(
+                    pos,
+                    ErrorCategory::Ordering,
+                    format!(
+                        "Tag {} out of order within the {}",
+                        field.tag,
+                        section.label()
+                    ),
+                );
+            }
+            last_index = Some(idx);
         }
     }
-    errors
 }
 
 fn validate_repeating_groups(
     fields: &[FieldValue],
     msg_def: &MessageDef,
     dict: &FixTagLookup,
-    tag_errors: &mut HashMap<u32, Vec<String>>,
-) -> Vec<String> {
-    let mut errors = Vec::new();
+    findings: &mut Findings,
+) {
     let mut idx = 0;
     while idx < fields.len() {
         let tag = fields[idx].tag;
         if let Some(spec) = msg_def.groups.get(&tag) {
-            let (consumed, mut errs) =
-                validate_group_instance(fields, idx, spec, msg_def, dict, tag_errors);
-            errors.append(&mut errs);
-            idx += consumed;
+            idx += validate_group_instance(fields, idx, spec, msg_def, dict, findings);
         } else {
-            if let Some(owner) = msg_def.group_membership.get(&tag) {
-                let err = format!(
-                    "Tag {} ({}) appears outside of repeating group {}",
-                    tag,
-                    dict.field_name(tag),
-                    owner
+            if let Some(owner) = msg_def.group_membership.get(&tag)
+                && !msg_def.body_level.contains(&tag)
+            {
+                findings.at(
+                    idx,
+                    ErrorCategory::Group,
+                    format!(
+                        "Tag {} ({}) appears outside of repeating group {}",
+                        tag,
+                        dict.field_name(tag),
+                        owner
+                    ),
                 );
-                errors.push(err.clone());
-                tag_errors.entry(tag).or_default().push(err);
             }
             idx += 1;
         }
     }
-    errors
 }
 
 fn validate_group_instance(
@@ -246,58 +913,56 @@ fn validate_group_instance(
     spec: &MessageDefGroupSpec,
     msg_def: &MessageDef,
     dict: &FixTagLookup,
-    tag_errors: &mut HashMap<u32, Vec<String>>,
-) -> (usize, Vec<String>) {
-    let mut errors = Vec::new();
-    let count = fields[start_idx]
-        .value
-        .parse::<usize>()
-        .unwrap_or_else(|_| {
-            let err = format!(
-                "Invalid NumInGroup value '{}' for tag {}",
-                fields[start_idx].value, spec.count_tag
+    findings: &mut Findings,
+) -> usize {
+    let count = match fields[start_idx].value.parse::<usize>() {
+        Ok(count) => count,
+        Err(_) => {
+            findings.at(
+                start_idx,
+                ErrorCategory::Group,
+                format!(
+                    "Invalid NumInGroup value '{}' for tag {}",
+                    fields[start_idx].value, spec.count_tag
+                ),
             );
-            errors.push(err.clone());
-            tag_errors
-                .entry(spec.count_tag)
-                .or_default()
-                .push(err.clone());
             0
-        });
+        }
+    };
     let mut entries = 0usize;
     let mut idx = start_idx + 1;
-
     while idx < fields.len() && entries < count {
         if fields[idx].tag != spec.delim {
             if msg_def.group_membership.get(&fields[idx].tag) == Some(&spec.count_tag) {
-                let err = format!(
-                    "Expected group delimiter tag {} before tag {}",
-                    spec.delim, fields[idx].tag
+                findings.at(
+                    idx,
+                    ErrorCategory::Group,
+                    format!(
+                        "Expected group delimiter tag {} before tag {}",
+                        spec.delim, fields[idx].tag
+                    ),
                 );
-                errors.push(err.clone());
-                tag_errors.entry(fields[idx].tag).or_default().push(err);
                 idx += 1;
                 continue;
             } else {
                 break;
             }
         }
-        let (consumed, mut errs) =
-            validate_group_entry(fields, idx, spec, msg_def, dict, tag_errors);
-        errors.append(&mut errs);
-        idx += consumed;
+        idx += validate_group_entry(fields, idx, spec, msg_def, dict, findings);
         entries += 1;
     }
 
     if entries != count {
-        let err = format!(
-            "NumInGroup {} declared {}, but {} instance(s) found",
-            spec.count_tag, count, entries
+        findings.at(
+            start_idx,
+            ErrorCategory::Group,
+            format!(
+                "NumInGroup {} declared {}, but {} instance(s) found",
+                spec.count_tag, count, entries
+            ),
         );
-        errors.push(err.clone());
-        tag_errors.entry(spec.count_tag).or_default().push(err);
     }
-    (idx - start_idx, errors)
+    idx - start_idx
 }
 
 fn validate_group_entry(
@@ -306,9 +971,8 @@ fn validate_group_entry(
     spec: &MessageDefGroupSpec,
     msg_def: &MessageDef,
     dict: &FixTagLookup,
-    tag_errors: &mut HashMap<u32, Vec<String>>,
-) -> (usize, Vec<String>) {
-    let mut errors = Vec::new();
+    findings: &mut Findings,
+) -> usize {
     let mut idx = start_idx;
     let mut last_pos = -1isize;
     while idx < fields.len() {
@@ -317,22 +981,21 @@ fn validate_group_entry(
             break;
         }
         if let Some(nested) = spec.nested.get(&tag) {
-            let (consumed, mut errs) =
-                validate_group_instance(fields, idx, nested, msg_def, dict, tag_errors);
-            errors.append(&mut errs);
-            idx += consumed;
+            idx += validate_group_instance(fields, idx, nested, msg_def, dict, findings);
             continue;
         }
         if let Some(pos) = spec.entry_order.iter().position(|t| *t == tag) {
             if (pos as isize) < last_pos {
-                let err = format!(
-                    "Tag {} ({}) out of order within repeating group {}",
-                    tag,
-                    dict.field_name(tag),
-                    spec.count_tag
+                findings.at(
+                    idx,
+                    ErrorCategory::Ordering,
+                    format!(
+                        "Tag {} ({}) out of order within repeating group {}",
+                        tag,
+                        dict.field_name(tag),
+                        spec.count_tag
+                    ),
                 );
-                errors.push(err.clone());
-                tag_errors.entry(tag).or_default().push(err);
             }
             last_pos = pos as isize;
             idx += 1;
@@ -341,69 +1004,189 @@ fn validate_group_entry(
             break;
         }
     }
-    (idx - start_idx, errors)
+    idx - start_idx
 }
 
-fn validate_checksum_field(
-    msg: &str,
-    field_map: &HashMap<u32, String>,
-    tag_errors: &mut HashMap<u32, Vec<String>>,
-) -> Vec<String> {
-    let mut errors = Vec::new();
-    match field_map.get(&10) {
-        None => errors.push("Missing required checksum tag 10".to_string()),
+fn validate_checksum_field(msg: &ParsedMessage<'_>, findings: &mut Findings) {
+    match msg.get(10) {
+        None => findings.general(
+            ErrorCategory::Checksum,
+            "Missing required checksum tag 10".to_string(),
+        ),
         Some(value) => {
-            let expected = format!("{:03}", calculate_checksum(msg));
-            if &expected != value {
-                let err = format!("Checksum mismatch: got {}, expected {}", value, expected);
-                errors.push(err.clone());
-                tag_errors.entry(10).or_default().push(err);
+            let expected = format!("{:03}", calculate_checksum(msg.raw));
+            if expected != value {
+                findings.on_tag(
+                    10,
+                    ErrorCategory::Checksum,
+                    format!("Checksum mismatch: got {}, expected {}", value, expected),
+                );
             }
         }
     }
-    errors
 }
 
-fn validate_body_length(
-    msg: &str,
-    field_map: &HashMap<u32, String>,
-    tag_errors: &mut HashMap<u32, Vec<String>>,
-) -> Vec<String> {
-    let mut errors = Vec::new();
-    match field_map.get(&9) {
-        None => errors.push("Missing required BodyLength tag 9".to_string()),
+fn validate_body_length(msg: &ParsedMessage<'_>, findings: &mut Findings) {
+    match msg.get(9) {
+        None => findings.general(
+            ErrorCategory::BodyLength,
+            "Missing required BodyLength tag 9".to_string(),
+        ),
         Some(value) => match value.parse::<usize>() {
-            Err(_) => errors.push(format!("Invalid BodyLength value '{}'", value)),
-            Ok(declared) => match compute_actual_body_length(msg) {
-                None => errors.push("Unable to compute BodyLength from message".to_string()),
+            Err(_) => findings.general(
+                ErrorCategory::BodyLength,
+                format!("Invalid BodyLength value '{}'", value),
+            ),
+            Ok(declared) => match compute_actual_body_length(msg.raw) {
+                None => findings.general(
+                    ErrorCategory::BodyLength,
+                    "Unable to compute BodyLength from message".to_string(),
+                ),
                 Some(actual) if declared != actual => {
-                    let err = format!("BodyLength mismatch: got {}, expected {}", declared, actual);
-                    tag_errors.entry(9).or_default().push(err.clone());
-                    errors.push(err);
+                    let (category, message) = body_length_mismatch(msg.raw, declared, actual);
+                    findings.on_tag(9, category, message);
                 }
                 _ => {}
             },
         },
     }
-    errors
+}
+
+/// Describe a BodyLength of `declared` bytes where the body holds `actual`.  One that
+/// ends on a field boundary inside the body is a plain mismatch; one that ends past
+/// CheckSum, or partway through a field, means the message was cut short or had bytes
+/// spliced into it before being resealed.
+fn body_length_mismatch(msg: &str, declared: usize, actual: usize) -> (ErrorCategory, String) {
+    let mismatch = format!("BodyLength mismatch: got {declared}, expected {actual}");
+    if declared > actual {
+        return (
+            ErrorCategory::Spliced,
+            format!(
+                "{mismatch}; it points {} bytes past the CheckSum field, so the message was likely truncated and resealed",
+                declared - actual
+            ),
+        );
+    }
+    if ends_mid_field(msg, declared) {
+        return (
+            ErrorCategory::Spliced,
+            format!(
+                "{mismatch}; it ends mid-field, {} bytes before the CheckSum field, so the message was likely spliced",
+                actual - declared
+            ),
+        );
+    }
+    (ErrorCategory::BodyLength, mismatch)
+}
+
+/// Whether the first `declared` bytes of the body stop short of a field's closing SOH.
+fn ends_mid_field(msg: &str, declared: usize) -> bool {
+    message_frame(msg).is_some_and(|frame| {
+        let body = raw_bytes::to_bytes(&msg[frame.body_start..frame.checksum_start]);
+        declared > 0 && body.get(declared - 1).is_some_and(|b| *b != 0x01)
+    })
 }
 
 pub fn calculate_checksum(msg: &str) -> i32 {
-    const SOH: &str = "\u{0001}";
-    if let Some(idx) = msg.rfind(&(SOH.to_string() + "10=")) {
-        let fragment = &msg[..idx + 1];
-        let sum: i32 = fragment.bytes().map(|b| b as i32).sum();
-        sum % 256
-    } else {
-        -1
+    match message_frame(msg) {
+        Some(frame) => raw_bytes::checksum(&msg[..frame.checksum_start]) as i32,
+        None => -1,
     }
 }
 
-fn is_valid_type(value: &str, field_type: &str) -> bool {
+/// Byte offsets framing a message: where the body starts (just after the
+/// BodyLength field's SOH) and where the CheckSum field starts.
+struct MessageFrame {
+    body_start: usize,
+    checksum_start: usize,
+}
+
+/// Locate the frame by walking field boundaries rather than searching for
+/// substrings: BeginString must open the message, BodyLength must be the second
+/// field, and the body runs up to the last field whose tag is exactly 10.  Values
+/// that merely contain `9=`, `10=` or `8=FIX` are never mistaken for those fields.
+fn message_frame(msg: &str) -> Option<MessageFrame> {
+    const SOH: u8 = 0x01;
+    let bytes = msg.as_bytes();
+    let field_end = |start: usize| {
+        bytes[start..]
+            .iter()
+            .position(|b| *b == SOH)
+            .map(|i| start + i)
+    };
+
+    if !bytes.starts_with(b"8=") {
+        return None;
+    }
+    let length_start = field_end(0)? + 1;
+    if !bytes[length_start..].starts_with(b"9=") {
+        return None;
+    }
+    let body_start = field_end(length_start)? + 1;
+
+    let mut checksum_start = None;
+    let mut start = body_start;
+    while start < bytes.len() {
+        if bytes[start..].starts_with(b"10=") {
+            checksum_start = Some(start);
+        }
+        match field_end(start) {
+            Some(end) => start = end + 1,
+            None => break,
+        }
+    }
+    Some(MessageFrame {
+        body_start,
+        checksum_start: checksum_start?,
+    })
+}
+
+/// Time formats accepted for a dictionary.  FIX 5.0 onwards permits micro- and
+/// nanosecond precision; older versions stop at milliseconds but tolerate the
+/// minute-only `YYYYMMDD-HH:MM` timestamps some counterparties still send.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TimeRules {
+    Legacy,
+    Extended,
+}
+
+impl TimeRules {
+    fn for_schema(key: &str) -> Self {
+        if key.starts_with("FIX5") || key.starts_with("FIXT") {
+            TimeRules::Extended
+        } else {
+            TimeRules::Legacy
+        }
+    }
+
+    /// Formats for the time portion of a UTCTIMESTAMP/TZTIMESTAMP.
+    fn timestamp_time_formats(self) -> &'static [&'static str] {
+        match self {
+            TimeRules::Legacy => &["%H:%M", "%H:%M:%S", "%H:%M:%S%.3f"],
+            TimeRules::Extended => &["%H:%M:%S", "%H:%M:%S%.3f", "%H:%M:%S%.6f", "%H:%M:%S%.9f"],
+        }
+    }
+
+    /// Formats for UTCTIMEONLY/TZTIMEONLY, which have always allowed `HH:MM`.
+    fn time_only_formats(self) -> &'static [&'static str] {
+        match self {
+            TimeRules::Legacy => &["%H:%M", "%H:%M:%S", "%H:%M:%S%.3f"],
+            TimeRules::Extended => &[
+                "%H:%M",
+                "%H:%M:%S",
+                "%H:%M:%S%.3f",
+                "%H:%M:%S%.6f",
+                "%H:%M:%S%.9f",
+            ],
+        }
+    }
+}
+
+fn is_valid_type(value: &str, field_type: &str, rules: TimeRules) -> bool {
     match field_type.to_ascii_uppercase().as_str() {
         "INT" | "LENGTH" | "NUMINGROUP" | "SEQNUM" | "DAYOFMONTH" => value.parse::<i64>().is_ok(),
         "FLOAT" | "QTY" | "PRICE" | "PRICEOFFSET" | "AMT" | "PERCENTAGE" => {
-            value.parse::<f64>().is_ok()
+            FIX_FLOAT_REGEX.is_match(value)
         }
         "BOOLEAN" => value == "Y" || value == "N",
         "CHAR" => value.chars().count() == 1,
@@ -414,69 +1197,125 @@ fn is_valid_type(value: &str, field_type: &str) -> bool {
         | "COUNTRY"
         | "MULTIPLEVALUESTRING"
         | "MULTIPLESTRINGVALUE" => true,
-        "UTCTIMESTAMP" => is_valid_timestamp(value),
-        "UTCDATEONLY" => NaiveDate::parse_from_str(value, "%Y%m%d").is_ok(),
-        "UTCTIMEONLY" => ["%H:%M", "%H:%M:%S", "%H:%M:%S%.3f"]
-            .iter()
-            .any(|fmt| NaiveTime::parse_from_str(value, fmt).is_ok()),
+        "UTCTIMESTAMP" => is_valid_timestamp(value, rules),
+        "TZTIMESTAMP" => split_tz_offset(value, 9)
+            .is_some_and(|(stamp, offset)| is_valid_timestamp(stamp, rules) && is_valid_tz(offset)),
+        "UTCDATEONLY" | "LOCALMKTDATE" => is_valid_date(value),
+        "UTCTIMEONLY" => is_valid_time(value, rules.time_only_formats()),
+        "TZTIMEONLY" => split_tz_offset(value, 0).is_some_and(|(time, offset)| {
+            is_valid_time(time, rules.time_only_formats()) && is_valid_tz(offset)
+        }),
         "MONTHYEAR" => MONTH_YEAR_REGEX.is_match(value),
         _ => true,
     }
 }
 
-fn is_valid_timestamp(value: &str) -> bool {
-    ["%Y%m%d-%H:%M:%S", "%Y%m%d-%H:%M:%S%.3f"]
-        .iter()
-        .any(|fmt| NaiveDateTime::parse_from_str(value, fmt).is_ok())
+/// Explain why a float-typed value breaks FIX float encoding in a way `f64::parse`
+/// would accept or that deserves a clearer message than a type error: exponent
+/// notation, a leading `+`, or thousands separators.  With `max_decimals`, price,
+/// quantity and amount values with more decimal places than that are reported
+/// too.  Anything else that is not a number is left to the type check.
+fn float_encoding_error(
+    value: &str,
+    field_type: &str,
+    max_decimals: Option<usize>,
+) -> Option<String> {
+    let kind = field_type.to_ascii_uppercase();
+    if !matches!(
+        kind.as_str(),
+        "FLOAT" | "QTY" | "PRICE" | "PRICEOFFSET" | "AMT" | "PERCENTAGE"
+    ) {
+        return None;
+    }
+    if FIX_FLOAT_REGEX.is_match(value) {
+        let limit = max_decimals
+            .filter(|_| matches!(kind.as_str(), "PRICE" | "PRICEOFFSET" | "QTY" | "AMT"))?;
+        let decimals = value.split_once('.').map_or(0, |(_, frac)| frac.len());
+        return (decimals > limit).then(|| {
+            format!("Too many decimal places: '{value}' has {decimals}, limit is {limit}")
+        });
+    }
+    let reason = if EXPONENT_FLOAT_REGEX.is_match(value) {
+        "exponent notation is not allowed"
+    } else if value
+        .strip_prefix('+')
+        .is_some_and(|rest| FIX_FLOAT_REGEX.is_match(rest))
+    {
+        "a leading '+' is not allowed"
+    } else if GROUPED_FLOAT_REGEX.is_match(value) {
+        "thousands separators are not allowed"
+    } else {
+        return None;
+    };
+    Some(format!("Invalid {field_type} encoding '{value}': {reason}"))
 }
 
-static MONTH_YEAR_REGEX: Lazy<Regex> =
-    Lazy::new(|| Regex::new(r"^\d{6}(\d{2}|(-\d{1,2})|(-?w[1-5]))?$").expect("valid regex"));
+fn is_valid_timestamp(value: &str, rules: TimeRules) -> bool {
+    value.split_once('-').is_some_and(|(date, time)| {
+        is_valid_date(date) && is_valid_time(time, rules.timestamp_time_formats())
+    })
+}
 
-fn compute_actual_body_length(msg: &str) -> Option<usize> {
-    const SOH: u8 = 0x01;
-    let bytes = msg.as_bytes();
+/// Strict `YYYYMMDD` check; chrono alone would accept short or over-long years.
+fn is_valid_date(value: &str) -> bool {
+    value.len() == 8
+        && value.bytes().all(|b| b.is_ascii_digit())
+        && NaiveDate::parse_from_str(value, "%Y%m%d").is_ok()
+}
 
-    // find start of 9= field
-    let mut len_pos = None;
-    for i in 0..bytes.len().saturating_sub(1) {
-        if bytes[i] == b'9' && bytes[i + 1] == b'=' {
-            len_pos = Some(i);
-            break;
-        }
-    }
-    let len_pos = len_pos?;
+fn is_valid_time(value: &str, formats: &[&str]) -> bool {
+    TIME_SHAPE_REGEX.is_match(value)
+        && formats
+            .iter()
+            .any(|fmt| NaiveTime::parse_from_str(value, fmt).is_ok())
+}
 
-    // find delimiter after 9= value
-    let mut body_start = None;
-    for (i, byte) in bytes.iter().enumerate().skip(len_pos) {
-        if *byte == SOH {
-            body_start = Some(i + 1);
-            break;
-        }
+/// Split a TZ-qualified value into its local part and offset (`Z`, `+hh`, `-hh:mm`, or
+/// empty when absent).  `search_from` skips the date so its `-` is not mistaken for an offset.
+fn split_tz_offset(value: &str, search_from: usize) -> Option<(&str, &str)> {
+    if let Some(local) = value.strip_suffix('Z') {
+        return Some((local, "Z"));
     }
-    let body_start = body_start?;
-
-    // find last occurrence of SOH10=
-    let mut checksum_start = None;
-    for i in (0..bytes.len().saturating_sub(3)).rev() {
-        if bytes[i] == SOH && bytes[i + 1] == b'1' && bytes[i + 2] == b'0' && bytes[i + 3] == b'=' {
-            checksum_start = Some(i);
-            break;
-        }
+    let tail = value.get(search_from..)?;
+    match tail.rfind(['+', '-']) {
+        Some(pos) => Some(value.split_at(search_from + pos)),
+        None => Some((value, "")),
     }
-    let checksum_start = checksum_start?;
+}
 
-    if checksum_start >= body_start {
-        Some(checksum_start - body_start + 1)
-    } else {
-        None
-    }
+fn is_valid_tz(offset: &str) -> bool {
+    offset.is_empty() || offset == "Z" || TZ_OFFSET_REGEX.is_match(offset)
+}
+
+static TIME_SHAPE_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^\d{2}:\d{2}(:\d{2}(\.\d{3}|\.\d{6}|\.\d{9})?)?$").expect("valid regex")
+});
+
+static TZ_OFFSET_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^[+-](0\d|1[0-4])(:?[0-5]\d)?$").expect("valid regex"));
+
+/// FIX float encoding: optional minus, digits and at most one decimal point.
+static FIX_FLOAT_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^-?(\d+\.?\d*|\.\d+)$").expect("valid regex"));
+
+static EXPONENT_FLOAT_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^[+-]?(\d+\.?\d*|\.\d+)[eE][+-]?\d+$").expect("valid regex"));
+
+static GROUPED_FLOAT_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^[+-]?\d{1,3}([,' _]\d{3})+(\.\d*)?$").expect("valid regex"));
+
+static MONTH_YEAR_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^\d{6}(\d{2}|(-\d{1,2})|(-?w[1-5]))?$").expect("valid regex"));
+
+fn compute_actual_body_length(msg: &str) -> Option<usize> {
+    message_frame(msg)
+        .map(|frame| raw_bytes::byte_len(&msg[frame.body_start..frame.checksum_start]))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::decoder::repair::sealed_message;
     use crate::decoder::schema::{
         ComponentContainer, ComponentDef, Field, FieldContainer, FieldRef, FixDictionary, GroupDef,
         Message, MessageContainer, ValuesWrapper,
@@ -505,9 +1344,13 @@ mod tests {
                     field("BeginString", 8, "STRING"),
                     field("BodyLength", 9, "LENGTH"),
                     field("MsgType", 35, "STRING"),
+                    field("SenderCompID", 49, "STRING"),
+                    field("OnBehalfOfCompID", 115, "STRING"),
                     field("CheckSum", 10, "STRING"),
                     field("NoItems", 100, "NUMINGROUP"),
                     field("ItemValue", 101, "STRING"),
+                    field("EncodedTextLen", 354, "LENGTH"),
+                    field("EncodedText", 355, "DATA"),
                 ],
             },
             messages: MessageContainer {
@@ -548,6 +1391,14 @@ mod tests {
                         name: "MsgType".to_string(),
                         required: Some("Y".to_string()),
                     },
+                    FieldRef {
+                        name: "SenderCompID".to_string(),
+                        required: Some("N".to_string()),
+                    },
+                    FieldRef {
+                        name: "OnBehalfOfCompID".to_string(),
+                        required: Some("N".to_string()),
+                    },
                 ],
                 groups: Vec::new(),
                 components: Vec::new(),
@@ -602,12 +1453,55 @@ mod tests {
             errors
                 .errors
                 .iter()
-                .any(|e| e.contains("BodyLength mismatch")),
+                .any(|e| e.message.contains("BodyLength mismatch")),
             "expected body length error, got {:?}",
             errors.errors
         );
     }
 
+    #[test]
+    fn body_length_ending_past_checksum_or_mid_field_is_a_splice() {
+        let dict = test_lookup();
+        let body_length_error = |fields: &[(u32, &str)]| {
+            let msg = build_message(fields, Some(20));
+            let report = validate_fix_message(&msg, &dict);
+            report
+                .errors
+                .into_iter()
+                .find(|e| e.tag == Some(9))
+                .expect("BodyLength error")
+        };
+
+        // Cut short after "101=ON" and resealed with a fresh CheckSum.
+        let truncated = body_length_error(&[(35, "Z"), (100, "1"), (101, "ON")]);
+        assert_eq!(truncated.category, ErrorCategory::Spliced);
+        assert!(
+            truncated
+                .message
+                .starts_with("BodyLength mismatch: got 20, expected 18; it points 2 bytes past"),
+            "{}",
+            truncated.message
+        );
+
+        let elongated = body_length_error(&[(35, "Z"), (100, "1"), (101, "ONLY-AND-MORE")]);
+        assert_eq!(elongated.category, ErrorCategory::Spliced);
+        assert!(
+            elongated
+                .message
+                .contains("it ends mid-field, 9 bytes before the CheckSum field"),
+            "{}",
+            elongated.message
+        );
+
+        // Ending on a field boundary is an ordinary mismatch.
+        let extra_field = body_length_error(&[(35, "Z"), (100, "1"), (101, "ONLY"), (58, "X")]);
+        assert_eq!(extra_field.category, ErrorCategory::BodyLength);
+        assert_eq!(
+            extra_field.message,
+            "BodyLength mismatch: got 20, expected 25"
+        );
+    }
+
     #[test]
     fn detects_checksum_mismatch() {
         let dict = test_lookup();
@@ -622,12 +1516,134 @@ mod tests {
             errors
                 .errors
                 .iter()
-                .any(|e| e.contains("Checksum mismatch")),
+                .any(|e| e.message.contains("Checksum mismatch")),
             "expected checksum mismatch, got {:?}",
             errors.errors
         );
     }
 
+    #[test]
+    fn lengths_and_checksums_count_the_bytes_as_sent() {
+        let dict = test_lookup();
+        // "Tokyo" in Shift_JIS: four bytes, none of them valid UTF-8 here.
+        let tokyo = raw_bytes::decode(b"\x93\x8c\x8b\x9e").into_owned();
+        let msg = sealed_message(
+            "FIX.4.4",
+            &format!("35=Z{SOH}100=0{SOH}354=4{SOH}355={tokyo}{SOH}"),
+        );
+        let report = validate_fix_message(&msg, &dict);
+        assert!(report.is_clean(), "{:?}", report.errors);
+
+        let short = msg.replace("354=4", "354=3");
+        let report = validate_fix_message(&short, &dict);
+        let err = report
+            .errors
+            .iter()
+            .find(|e| e.category == ErrorCategory::DataLength)
+            .expect("data length error");
+        assert_eq!(
+            err.message,
+            "EncodedTextLen(354) declares 3 bytes but EncodedText(355) has 4"
+        );
+        assert_eq!(err.tag, Some(354));
+    }
+
+    #[test]
+    fn errors_carry_field_index_and_byte_span() {
+        let dict = test_lookup();
+        let mut msg = build_message(&[(35, "Z")], None);
+        let pos = msg.rfind("10=").unwrap();
+        msg.truncate(pos + 3);
+        msg.push_str("999\u{0001}");
+
+        let report = validate_fix_message(&msg, &dict);
+        let checksum = report
+            .errors
+            .iter()
+            .find(|e| e.message.starts_with("Checksum mismatch"))
+            .expect("checksum error");
+        assert_eq!(checksum.tag, Some(10));
+        assert_eq!(checksum.field_index, Some(3));
+        assert_eq!(checksum.span, Some(21..27));
+        assert_eq!(&msg[21..27], "10=999");
+        assert_eq!(checksum.location_label(), "(field #4, bytes 21..27)");
+
+        let missing = report
+            .errors
+            .iter()
+            .find(|e| e.message.starts_with("Required group 100"))
+            .expect("missing NoItems error");
+        assert_eq!(missing.tag, Some(100));
+        assert_eq!((missing.field_index, missing.span.clone()), (None, None));
+        assert_eq!(missing.location_label(), "(not present in message)");
+
+        let located = report.located_tag_errors();
+        assert_eq!(
+            located[&100],
+            vec!["Required group 100 (NoItems) missing (not present in message)"]
+        );
+    }
+
+    fn ordering_errors(msg: &str) -> Vec<String> {
+        validate_fix_message(msg, &test_lookup())
+            .errors
+            .into_iter()
+            .filter(|e| e.category == ErrorCategory::Ordering)
+            .map(|e| e.message)
+            .collect()
+    }
+
+    #[test]
+    fn header_fields_after_the_first_three_may_come_in_any_order() {
+        for header in [[(49, "AAA"), (115, "BBB")], [(115, "BBB"), (49, "AAA")]] {
+            let mut fields = vec![(35, "Z")];
+            fields.extend(header);
+            fields.extend([(100, "1"), (101, "ONLY")]);
+            let msg = build_message(&fields, None);
+            assert_eq!(ordering_errors(&msg), Vec::<String>::new());
+        }
+    }
+
+    #[test]
+    fn a_header_tag_after_the_body_breaks_the_header_rule() {
+        let msg = build_message(
+            &[
+                (35, "Z"),
+                (49, "AAA"),
+                (100, "1"),
+                (101, "ONLY"),
+                (115, "BBB"),
+            ],
+            None,
+        );
+        assert_eq!(
+            ordering_errors(&msg),
+            [
+                "Header tag 115 appears after body tag 100 (NoItems); the header must precede the body"
+            ]
+        );
+    }
+
+    #[test]
+    fn a_checksum_before_the_body_breaks_the_trailer_rule() {
+        let msg = format!("8=FIX.4.4{SOH}9=27{SOH}35=Z{SOH}10=000{SOH}100=1{SOH}101=ONLY{SOH}");
+        assert_eq!(
+            ordering_errors(&msg),
+            [
+                "Trailer tag 10 appears before body tag 100 (NoItems); the trailer must follow the body"
+            ]
+        );
+    }
+
+    #[test]
+    fn begin_string_body_length_and_msg_type_must_lead() {
+        let msg = build_message(&[(49, "AAA"), (35, "Z"), (100, "1"), (101, "ONLY")], None);
+        assert_eq!(
+            ordering_errors(&msg),
+            ["Tag 35 must be field 3 of the message: the header starts 8, 9, 35"]
+        );
+    }
+
     #[test]
     fn missing_msg_type_still_reports_length_and_tag() {
         let dict = test_lookup();
@@ -637,14 +1653,15 @@ mod tests {
             report
                 .errors
                 .iter()
-                .any(|e| e.contains("Missing required tag 35")),
+                .any(|e| e.message.contains("Missing required tag 35")),
             "expected missing MsgType error"
         );
         assert!(
             report
                 .errors
                 .iter()
-                .any(|e| e.contains("BodyLength mismatch") || e.contains("Checksum mismatch")),
+                .any(|e| e.message.contains("BodyLength mismatch")
+                    || e.message.contains("Checksum mismatch")),
             "expected invariant checks to run even without MsgType"
         );
         assert!(
@@ -652,4 +1669,491 @@ mod tests {
             "tag error map should include tag 35 when missing"
         );
     }
+
+    #[test]
+    fn time_types_follow_version_rules() {
+        let cases: &[(&str, &str, &str, bool)] = &[
+            ("UTCTIMESTAMP", "FIX44", "20240101-12:34:56", true),
+            ("UTCTIMESTAMP", "FIX44", "20240101-12:34:56.789", true),
+            ("UTCTIMESTAMP", "FIX44", "20240101-12:34", true),
+            ("UTCTIMESTAMP", "FIX44", "20240101-12:34:56.789123", false),
+            (
+                "UTCTIMESTAMP",
+                "FIX44",
+                "20240101-12:34:56.789123456",
+                false,
+            ),
+            ("UTCTIMESTAMP", "FIX50SP2", "20240101-12:34:56.789123", true),
+            (
+                "UTCTIMESTAMP",
+                "FIX50SP2",
+                "20240101-12:34:56.789123456",
+                true,
+            ),
+            ("UTCTIMESTAMP", "FIX50SP2", "20240101-12:34", false),
+            ("UTCTIMESTAMP", "FIX50SP2", "20240101-12:34:56.78", false),
+            ("UTCTIMESTAMP", "FIX44", "20251301-12:34:56", false),
+            ("UTCTIMESTAMP", "FIX44", "20250230-12:34:56", false),
+            ("UTCTIMESTAMP", "FIX44", "20240101-25:00:00", false),
+            ("UTCTIMESTAMP", "FIX44", "2024011-12:34:56", false),
+            ("UTCTIMEONLY", "FIX42", "12:34", true),
+            ("UTCTIMEONLY", "FIX42", "12:34:56.789", true),
+            ("UTCTIMEONLY", "FIX42", "12:34:56.789123", false),
+            ("UTCTIMEONLY", "FIX50", "12:34:56.789123", true),
+            ("UTCTIMEONLY", "FIX50", "1:34:56", false),
+            ("UTCDATEONLY", "FIX44", "20240229", true),
+            ("UTCDATEONLY", "FIX44", "20251301", false),
+            ("LOCALMKTDATE", "FIX44", "20240101", true),
+            ("LOCALMKTDATE", "FIX44", "202401011", false),
+            ("TZTIMESTAMP", "FIX44", "20240101-12:34:56Z", true),
+            ("TZTIMESTAMP", "FIX44", "20240101-12:34:56-05", true),
+            ("TZTIMESTAMP", "FIX44", "20240101-12:34:56+05:30", true),
+            ("TZTIMESTAMP", "FIX44", "20240101-12:34:56", true),
+            ("TZTIMESTAMP", "FIX44", "20240101-12:34:56+15", false),
+            ("TZTIMESTAMP", "FIX44", "20241301-12:34:56Z", false),
+            (
+                "TZTIMESTAMP",
+                "FIX50SP2",
+                "20240101-12:34:56.123456789+01:00",
+                true,
+            ),
+            ("TZTIMEONLY", "FIX44", "12:34Z", true),
+            ("TZTIMEONLY", "FIX44", "12:34:56-07:00", true),
+            ("TZTIMEONLY", "FIX44", "12:34:56+7", false),
+            ("TZTIMEONLY", "FIX44", "24:00Z", false),
+        ];
+        for (field_type, key, value, expected) in cases {
+            assert_eq!(
+                is_valid_type(value, field_type, TimeRules::for_schema(key)),
+                *expected,
+                "{field_type} '{value}' under {key}"
+            );
+        }
+    }
+
+    #[test]
+    fn hostile_values_do_not_confuse_framing() {
+        let dict = test_lookup();
+        for hostile in ["X9=Y", "10=123", "8=FIX.4.4", "9=999", "A10=1B9=2"] {
+            let msg = build_message(&[(35, "Z"), (100, "1"), (101, hostile)], None);
+            let report = validate_fix_message(&msg, &dict);
+            assert!(
+                report.is_clean(),
+                "value {hostile:?} should validate clean: {:?}",
+                report.errors
+            );
+        }
+    }
+
+    #[test]
+    fn framing_requires_begin_string_then_body_length() {
+        let body_length_late = format!("8=FIX.4.4{SOH}35=Z{SOH}9=5{SOH}10=000{SOH}");
+        assert_eq!(compute_actual_body_length(&body_length_late), None);
+        assert_eq!(calculate_checksum(&body_length_late), -1);
+
+        let prefixed = format!("X9=Y{SOH}8=FIX.4.4{SOH}9=5{SOH}35=Z{SOH}10=000{SOH}");
+        assert_eq!(compute_actual_body_length(&prefixed), None);
+
+        let msg = format!("8=FIX.4.4{SOH}9=5{SOH}35=Z{SOH}10=000{SOH}");
+        assert_eq!(compute_actual_body_length(&msg), Some(5));
+    }
+
+    /// Run one rule against a message built from `fields`, returning the anchored tags.
+    fn rule_tags(
+        check: fn(&ParsedMessage<'_>) -> Vec<(u32, String)>,
+        fields: &[(u32, &str)],
+    ) -> Vec<u32> {
+        let msg = build_message(fields, None);
+        check(&ParsedMessage::parse(&msg))
+            .into_iter()
+            .map(|(tag, _)| tag)
+            .collect()
+    }
+
+    #[test]
+    fn rule_logon_reset_requires_seqnum_one() {
+        let check = check_logon_reset_seqnum;
+        assert_eq!(
+            rule_tags(check, &[(35, "A"), (34, "7"), (141, "Y")]),
+            vec![34]
+        );
+        assert_eq!(rule_tags(check, &[(35, "A"), (141, "Y")]), vec![34]);
+        assert!(rule_tags(check, &[(35, "A"), (34, "1"), (141, "Y")]).is_empty());
+        assert!(rule_tags(check, &[(35, "A"), (34, "7"), (141, "N")]).is_empty());
+        assert!(rule_tags(check, &[(35, "0"), (34, "7"), (141, "Y")]).is_empty());
+    }
+
+    #[test]
+    fn rule_limit_orders_require_price() {
+        let check = check_limit_price;
+        assert_eq!(rule_tags(check, &[(35, "D"), (40, "2")]), vec![44]);
+        assert_eq!(
+            rule_tags(check, &[(35, "G"), (40, "4"), (99, "9")]),
+            vec![44]
+        );
+        assert!(rule_tags(check, &[(35, "D"), (40, "2"), (44, "1.5")]).is_empty());
+        assert!(rule_tags(check, &[(35, "D"), (40, "1")]).is_empty());
+        assert!(rule_tags(check, &[(35, "8"), (40, "2")]).is_empty());
+    }
+
+    #[test]
+    fn rule_stop_orders_require_stop_px() {
+        let check = check_stop_price;
+        assert_eq!(
+            rule_tags(check, &[(35, "D"), (40, "4"), (44, "1")]),
+            vec![99]
+        );
+        assert_eq!(rule_tags(check, &[(35, "D"), (40, "3")]), vec![99]);
+        assert!(rule_tags(check, &[(35, "D"), (40, "4"), (99, "1")]).is_empty());
+        assert!(rule_tags(check, &[(35, "D"), (40, "2")]).is_empty());
+    }
+
+    #[test]
+    fn rule_fills_require_last_qty_and_px() {
+        let check = check_fill_last;
+        assert_eq!(rule_tags(check, &[(35, "8"), (150, "F")]), vec![32, 31]);
+        assert_eq!(
+            rule_tags(check, &[(35, "8"), (150, "F"), (32, "10")]),
+            vec![31]
+        );
+        assert!(rule_tags(check, &[(35, "8"), (150, "F"), (31, "1"), (32, "10")]).is_empty());
+        assert!(rule_tags(check, &[(35, "8"), (150, "0")]).is_empty());
+    }
+
+    #[test]
+    fn rule_settlement_not_before_trade_date() {
+        let check = check_settl_after_trade;
+        assert_eq!(
+            rule_tags(check, &[(64, "20250101"), (75, "20250102")]),
+            vec![64]
+        );
+        assert!(rule_tags(check, &[(64, "20250102"), (75, "20250102")]).is_empty());
+        assert!(rule_tags(check, &[(64, "20250101")]).is_empty());
+        assert!(rule_tags(check, &[(64, "bad"), (75, "20250102")]).is_empty());
+    }
+
+    fn duplicate_tags(body: &[(u32, &str)]) -> Vec<u32> {
+        let mut fields = vec![(35, "8"), (49, "AAA"), (56, "BBB"), (34, "2")];
+        fields.extend_from_slice(body);
+        let msg = build_message(&fields, None);
+        let dict = crate::decoder::tag_lookup::load_dictionary(&msg);
+        validate_fix_message(&msg, &dict)
+            .errors
+            .into_iter()
+            .filter(|e| e.message.starts_with("Duplicate tag"))
+            .filter_map(|e| e.tag)
+            .collect()
+    }
+
+    #[test]
+    fn duplicates_are_scoped_to_the_message_groups() {
+        // Text at body level alongside a SecAltIDGrp whose entries repeat their tags.
+        let grouped = [
+            (11, "C1"),
+            (58, "note"),
+            (454, "2"),
+            (455, "US0378331005"),
+            (456, "4"),
+            (455, "037833100"),
+            (456, "1"),
+        ];
+        assert!(duplicate_tags(&grouped).is_empty());
+
+        let mut repeated_cl_ord_id = grouped.to_vec();
+        repeated_cl_ord_id.push((11, "C2"));
+        assert_eq!(duplicate_tags(&repeated_cl_ord_id), vec![11]);
+
+        // Text sits in a MarketDataSnapshot group elsewhere in FIX 4.4, but that does not
+        // make it repeatable in an ExecutionReport.
+        assert_eq!(duplicate_tags(&[(58, "a"), (58, "b")]), vec![58]);
+
+        // Within a group, a tag may appear once per entry.
+        assert_eq!(
+            duplicate_tags(&[(454, "1"), (455, "X"), (456, "4"), (456, "1")]),
+            vec![456]
+        );
+
+        // Text both at body level and inside a group of the same message.
+        let xml = r#"
+<fix type='FIX' major='4' minor='4'>
+  <header><field name='BeginString' required='Y'/><field name='MsgType' required='Y'/></header>
+  <trailer><field name='CheckSum' required='Y'/></trailer>
+  <messages>
+    <message name='Annotated' msgtype='U' msgcat='app'>
+      <field name='Text'/>
+      <group name='NoNotes'>
+        <field name='NoteID'/>
+        <field name='Text'/>
+      </group>
+    </message>
+  </messages>
+  <components/>
+  <fields>
+    <field number='8' name='BeginString' type='STRING'/>
+    <field number='9' name='BodyLength' type='LENGTH'/>
+    <field number='10' name='CheckSum' type='STRING'/>
+    <field number='35' name='MsgType' type='STRING'/>
+    <field number='58' name='Text' type='STRING'/>
+    <field number='5000' name='NoNotes' type='NUMINGROUP'/>
+    <field number='5001' name='NoteID' type='STRING'/>
+  </fields>
+</fix>
+"#;
+        let dict = FixTagLookup::from_dictionary(&FixDictionary::from_xml(xml).unwrap(), "TEST");
+        let duplicates = |body: &[(u32, &str)]| -> Vec<u32> {
+            let mut fields = vec![(35, "U")];
+            fields.extend_from_slice(body);
+            validate_fix_message(&build_message(&fields, None), &dict)
+                .errors
+                .into_iter()
+                .filter(|e| e.message.starts_with("Duplicate tag"))
+                .filter_map(|e| e.tag)
+                .collect()
+        };
+        let notes = [(5000, "2"), (5001, "a"), (58, "x"), (5001, "b"), (58, "y")];
+        let mut annotated = vec![(58, "body")];
+        annotated.extend_from_slice(&notes);
+        assert!(duplicates(&annotated).is_empty());
+
+        let mut repeated_text = vec![(58, "body"), (58, "again")];
+        repeated_text.extend_from_slice(&notes);
+        assert_eq!(duplicates(&repeated_text), vec![58]);
+    }
+
+    #[test]
+    fn num_in_group_zero_is_an_empty_group_and_only_a_required_group_may_not_be_absent() {
+        let group_errors = |body: &[(u32, &str)]| {
+            let mut fields = vec![
+                (35, "W"),
+                (49, "AAA"),
+                (56, "BBB"),
+                (34, "2"),
+                (52, "20240101-00:00:00"),
+                (55, "X"),
+            ];
+            fields.extend_from_slice(body);
+            let msg = build_message(&fields, None);
+            let dict = crate::decoder::tag_lookup::load_dictionary(&msg);
+            validate_fix_message(&msg, &dict)
+                .errors
+                .into_iter()
+                .filter(|e| e.tag.is_some_and(|tag| tag > 200))
+                .map(|e| (e.tag, e.message))
+                .collect::<Vec<_>>()
+        };
+        // Present and empty: the entry's required MDEntryType(269) is not wanted.
+        assert_eq!(group_errors(&[(268, "0")]), []);
+        // An optional group, the Instrument's NoSecurityAltID(454), may be left out.
+        assert_eq!(group_errors(&[(268, "1"), (269, "0")]), []);
+        // NoMDEntries is required on a snapshot.
+        assert_eq!(
+            group_errors(&[]),
+            [(
+                Some(268),
+                "Required group 268 (NoMDEntries) missing".to_string()
+            )]
+        );
+        let not_a_number = group_errors(&[(268, "x")]);
+        assert!(
+            not_a_number.contains(&(
+                Some(268),
+                "Invalid NumInGroup value 'x' for tag 268".to_string()
+            )),
+            "{not_a_number:?}"
+        );
+    }
+
+    #[test]
+    fn multi_value_enums_are_checked_code_by_code() {
+        let enum_errors = |exec_inst: &str| {
+            let fields = [
+                (35, "8"),
+                (49, "AAA"),
+                (56, "BBB"),
+                (34, "2"),
+                (18, exec_inst),
+            ];
+            let msg = build_message(&fields, None);
+            let dict = crate::decoder::tag_lookup::load_dictionary(&msg);
+            validate_fix_message(&msg, &dict)
+                .errors
+                .into_iter()
+                .filter(|e| e.message.starts_with("Invalid enum"))
+                .map(|e| e.message)
+                .collect::<Vec<_>>()
+        };
+        assert!(enum_errors("1 6 G").is_empty());
+        assert!(enum_errors("6").is_empty());
+        assert_eq!(
+            enum_errors("1 Z 6 Y"),
+            ["Invalid enum value 'Z'", "Invalid enum value 'Y'"]
+        );
+        assert_eq!(enum_errors(""), ["Invalid enum value ''"]);
+    }
+
+    const FLOAT_TYPES: [&str; 6] = ["FLOAT", "QTY", "PRICE", "PRICEOFFSET", "AMT", "PERCENTAGE"];
+
+    #[test]
+    fn float_encodings_follow_fix_rules() {
+        for kind in FLOAT_TYPES {
+            for valid in ["0", "15", "-15", "1.5", "1.", ".5", "-0.25", "0012.3400"] {
+                assert!(
+                    is_valid_type(valid, kind, TimeRules::Legacy),
+                    "{kind} {valid}"
+                );
+                assert_eq!(
+                    float_encoding_error(valid, kind, None),
+                    None,
+                    "{kind} {valid}"
+                );
+            }
+            for (invalid, reason) in [
+                ("1E-4", "exponent notation is not allowed"),
+                ("2.5e3", "exponent notation is not allowed"),
+                ("+1E4", "exponent notation is not allowed"),
+                ("+1.5", "a leading '+' is not allowed"),
+                ("1,000.25", "thousands separators are not allowed"),
+                ("-1,000,000", "thousands separators are not allowed"),
+                ("1 000", "thousands separators are not allowed"),
+            ] {
+                let err = float_encoding_error(invalid, kind, None)
+                    .unwrap_or_else(|| panic!("{kind} {invalid} should be flagged"));
+                assert_eq!(
+                    err,
+                    format!("Invalid {kind} encoding '{invalid}': {reason}")
+                );
+                assert!(!is_valid_type(invalid, kind, TimeRules::Legacy));
+            }
+            // Not numbers at all: left to the plain type error.
+            for garbage in ["", "-", ".", "1.2.3", "abc", "inf", "NaN", "1,00"] {
+                assert_eq!(
+                    float_encoding_error(garbage, kind, None),
+                    None,
+                    "{kind} {garbage}"
+                );
+                assert!(
+                    !is_valid_type(garbage, kind, TimeRules::Legacy),
+                    "{kind} {garbage}"
+                );
+            }
+        }
+        assert_eq!(float_encoding_error("1E-4", "STRING", None), None);
+    }
+
+    #[test]
+    fn max_decimals_applies_to_prices_quantities_and_amounts() {
+        let twelve = "1.123456789012";
+        for kind in ["PRICE", "PRICEOFFSET", "QTY", "AMT"] {
+            assert_eq!(
+                float_encoding_error(twelve, kind, Some(8)).as_deref(),
+                Some("Too many decimal places: '1.123456789012' has 12, limit is 8")
+            );
+            assert_eq!(float_encoding_error("1.12345678", kind, Some(8)), None);
+            assert_eq!(float_encoding_error(twelve, kind, None), None);
+            assert_eq!(float_encoding_error("100", kind, Some(0)), None);
+            assert!(float_encoding_error("100.0", kind, Some(0)).is_some());
+        }
+        for kind in ["FLOAT", "PERCENTAGE"] {
+            assert_eq!(float_encoding_error(twelve, kind, Some(8)), None);
+        }
+    }
+
+    #[test]
+    fn float_encoding_errors_are_anchored_to_their_tag() {
+        let fields = [
+            (35, "D"),
+            (49, "AAA"),
+            (56, "BBB"),
+            (34, "2"),
+            (44, "1E-4"),
+            (38, "+100"),
+        ];
+        let msg = build_message(&fields, None);
+        let dict = crate::decoder::tag_lookup::load_dictionary(&msg);
+        let encoding: Vec<(Option<u32>, String)> = validate_fix_message(&msg, &dict)
+            .errors
+            .into_iter()
+            .filter(|e| e.message.contains("encoding"))
+            .map(|e| (e.tag, e.message))
+            .collect();
+        assert_eq!(
+            encoding,
+            [
+                (
+                    Some(44),
+                    "Invalid PRICE encoding '1E-4': exponent notation is not allowed".to_string()
+                ),
+                (
+                    Some(38),
+                    "Invalid QTY encoding '+100': a leading '+' is not allowed".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn business_rules_anchor_errors_and_can_be_disabled() {
+        let msg = build_message(&[(35, "D"), (40, "2")], None);
+        let parsed = ParsedMessage::parse(&msg);
+
+        let mut findings = Findings::new(&parsed);
+        validate_business_rules(&parsed, &HashSet::new(), &mut findings);
+        let report = findings.into_report();
+        let expected = vec!["OrdType(40)=2 requires Price(44) [limit-price]".to_string()];
+        assert_eq!(
+            report
+                .errors
+                .iter()
+                .map(|e| e.message.clone())
+                .collect::<Vec<_>>(),
+            expected
+        );
+        assert_eq!(report.tag_errors.get(&44), Some(&expected));
+
+        let mut findings = Findings::new(&parsed);
+        let disabled = HashSet::from(["limit-price"]);
+        validate_business_rules(&parsed, &disabled, &mut findings);
+        let report = findings.into_report();
+        assert!(report.errors.is_empty());
+        assert!(report.tag_errors.is_empty());
+
+        assert!(find_rule("fill-last").is_some());
+        assert!(find_rule("nope").is_none());
+    }
+
+    #[test]
+    fn deprecations_are_warnings_not_errors() {
+        let msg = build_message(&[(35, "8"), (20, "0"), (150, "2"), (39, "2")], None);
+        let parsed = ParsedMessage::parse(&msg);
+
+        let mut findings = Findings::new(&parsed);
+        validate_deprecations(&parsed, "FIX44", &mut findings);
+        let report = findings.into_report();
+        assert!(report.is_clean());
+        assert!(report.tag_errors.is_empty());
+        let warnings: Vec<_> = report
+            .warnings
+            .iter()
+            .map(|w| (w.category, w.tag, w.message.as_str()))
+            .collect();
+        assert_eq!(
+            warnings,
+            vec![
+                (
+                    ErrorCategory::Deprecated,
+                    Some(20),
+                    "ExecTransType(20) is deprecated in FIX44, use ExecType(150)"
+                ),
+                (
+                    ErrorCategory::Deprecated,
+                    Some(150),
+                    "ExecType(150)=2 is deprecated in FIX44, use ExecType(150)=F"
+                ),
+            ]
+        );
+
+        let mut findings = Findings::new(&parsed);
+        validate_deprecations(&parsed, "FIX42", &mut findings);
+        assert!(findings.into_report().warnings.is_empty());
+    }
 }
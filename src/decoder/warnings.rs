@@ -0,0 +1,192 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// SPDX-FileCopyrightText: 2025 Steve Clarke <stephenlclarke@mac.com> - https://xyzzy.tools
+
+//! Warnings on stderr, each printed once.  A warning is identified by a category and
+//! a key, such as the component a recursion was found in, so a bad dictionary or a
+//! damaged capture reports each problem once instead of once per message or packet.
+//! Repeats are counted and reported by [`report_suppressed`] at exit.  `--quiet`
+//! silences warnings altogether and `--verbose-warnings` prints every occurrence.
+//! Both `fixdecoder` and `pcap2fix` report through here.
+
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::io::{self, Write};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum WarningMode {
+    /// Each distinct warning once, with its repeats counted.
+    #[default]
+    Once,
+    /// Every occurrence (`--verbose-warnings`).
+    Every,
+    /// None at all (`--quiet`).
+    Quiet,
+}
+
+static MODE: AtomicU8 = AtomicU8::new(0);
+
+#[derive(Debug)]
+struct Seen {
+    message: String,
+    repeats: usize,
+}
+
+/// Warnings printed so far, in the order they were first seen, indexed by category
+/// and key.
+#[derive(Debug, Default)]
+struct Registry {
+    seen: Vec<Seen>,
+    index: HashMap<(&'static str, String), usize>,
+}
+
+static REGISTRY: Lazy<Mutex<Registry>> = Lazy::new(|| Mutex::new(Registry::default()));
+
+pub fn set_mode(mode: WarningMode) {
+    MODE.store(mode as u8, Ordering::Relaxed);
+}
+
+pub fn mode() -> WarningMode {
+    match MODE.load(Ordering::Relaxed) {
+        1 => WarningMode::Every,
+        2 => WarningMode::Quiet,
+        _ => WarningMode::Once,
+    }
+}
+
+/// Print `message` to stderr unless a warning with the same `category` and `key`
+/// already was, in which case only count the repeat.
+pub fn warn(category: &'static str, key: &str, message: &str) {
+    #[cfg(test)]
+    if CAPTURE.with_borrow_mut(|capture| {
+        capture
+            .as_mut()
+            .map(|(registry, out)| record(registry, out, category, key, message))
+            .is_some()
+    }) {
+        return;
+    }
+    let _ = warn_to(&mut io::stderr(), category, key, message);
+}
+
+fn warn_to(
+    out: &mut dyn Write,
+    category: &'static str,
+    key: &str,
+    message: &str,
+) -> io::Result<()> {
+    match mode() {
+        WarningMode::Quiet => return Ok(()),
+        WarningMode::Every => return writeln!(out, "{message}"),
+        WarningMode::Once => {}
+    }
+    let Ok(mut registry) = REGISTRY.lock() else {
+        return writeln!(out, "{message}");
+    };
+    record(&mut registry, out, category, key, message)
+}
+
+/// Print `message` the first time `category` and `key` are seen in `registry`, and
+/// count it as a repeat after that.
+fn record(
+    registry: &mut Registry,
+    out: &mut dyn Write,
+    category: &'static str,
+    key: &str,
+    message: &str,
+) -> io::Result<()> {
+    let next = registry.seen.len();
+    let pos = *registry
+        .index
+        .entry((category, key.to_string()))
+        .or_insert(next);
+    if pos < next {
+        registry.seen[pos].repeats += 1;
+        return Ok(());
+    }
+    registry.seen.push(Seen {
+        message: message.to_string(),
+        repeats: 0,
+    });
+    writeln!(out, "{message}")
+}
+
+#[cfg(test)]
+thread_local! {
+    /// Set by [`capture`]: warnings on this thread go to a fresh registry and buffer.
+    static CAPTURE: std::cell::RefCell<Option<(Registry, Vec<u8>)>> =
+        const { std::cell::RefCell::new(None) };
+}
+
+/// Run `f`, returning the warnings it printed as they would reach stderr in the
+/// default mode, deduplicated against a fresh registry rather than the process one
+/// other tests share.
+#[cfg(test)]
+pub(crate) fn capture<T>(f: impl FnOnce() -> T) -> (T, String) {
+    CAPTURE.set(Some((Registry::default(), Vec::new())));
+    let result = f();
+    let (_, out) = CAPTURE.take().expect("capture in place");
+    (result, String::from_utf8(out).expect("warnings are UTF-8"))
+}
+
+/// Repeats suppressed so far of the warning with this `category` and `key`, or `None`
+/// if it has not been printed.
+pub fn repeats(category: &'static str, key: &str) -> Option<usize> {
+    let registry = REGISTRY.lock().ok()?;
+    let pos = *registry.index.get(&(category, key.to_string()))?;
+    Some(registry.seen[pos].repeats)
+}
+
+/// One line for each warning that was held back at least once, e.g.
+/// `warning: suppressed 41 repeat(s) of: warning: recursive component ...`.
+pub fn report_suppressed(out: &mut dyn Write) -> io::Result<()> {
+    let Ok(registry) = REGISTRY.lock() else {
+        return Ok(());
+    };
+    for seen in registry.seen.iter().filter(|seen| seen.repeats > 0) {
+        writeln!(
+            out,
+            "warning: suppressed {} repeat(s) of: {}",
+            seen.repeats, seen.message
+        )?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn each_distinct_warning_prints_once_and_its_repeats_are_reported() {
+        let mut out = Vec::new();
+        for _ in 0..3 {
+            warn_to(&mut out, "test-once", "a", "warning: a").unwrap();
+        }
+        warn_to(&mut out, "test-once", "b", "warning: b").unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "warning: a\nwarning: b\n");
+        assert_eq!(repeats("test-once", "a"), Some(2));
+        assert_eq!(repeats("test-once", "b"), Some(0));
+        assert_eq!(repeats("test-once", "c"), None);
+
+        let mut report = Vec::new();
+        report_suppressed(&mut report).unwrap();
+        let report = String::from_utf8(report).unwrap();
+        assert!(
+            report.contains("warning: suppressed 2 repeat(s) of: warning: a\n"),
+            "{report}"
+        );
+        assert!(!report.contains("of: warning: b"), "{report}");
+    }
+
+    #[test]
+    fn captured_warnings_start_from_a_fresh_registry() {
+        warn_to(&mut io::sink(), "test-capture", "a", "warning: a").unwrap();
+        let ((), printed) = capture(|| {
+            warn("test-capture", "a", "warning: a");
+            warn("test-capture", "a", "warning: a");
+        });
+        assert_eq!(printed, "warning: a\n");
+    }
+}
@@ -0,0 +1,186 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// SPDX-FileCopyrightText: 2025 Steve Clarke <stephenlclarke@mac.com> - https://xyzzy.tools
+
+//! Hot-reload support for custom `--xml` dictionaries while following a live log.
+//! The watcher only tracks modification times; parsing and registration are
+//! delegated to a reload callback supplied by the CLI so start-up and reload
+//! share the same code path.
+
+use crate::decoder::colours::palette;
+use crate::decoder::warnings;
+use std::fs;
+use std::io::Write;
+use std::time::{Duration, Instant, SystemTime};
+
+/// How often the watched dictionaries are stat'ed for changes.
+pub const WATCH_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Re-parse and register the dictionary at `path`, returning its schema key.
+pub type ReloadFn = fn(&str) -> anyhow::Result<String>;
+
+struct WatchedFile {
+    path: String,
+    modified: Option<SystemTime>,
+    warned: bool,
+}
+
+/// Polls custom dictionary files and re-registers them when their mtime changes.
+pub struct DictionaryWatcher {
+    files: Vec<WatchedFile>,
+    interval: Duration,
+    last_check: Instant,
+    reload: ReloadFn,
+}
+
+impl DictionaryWatcher {
+    pub fn new(paths: &[String], reload: ReloadFn) -> Self {
+        Self::with_interval(paths, reload, WATCH_INTERVAL)
+    }
+
+    pub fn with_interval(paths: &[String], reload: ReloadFn, interval: Duration) -> Self {
+        let files = paths
+            .iter()
+            .map(|path| WatchedFile {
+                path: path.clone(),
+                modified: modified_time(path),
+                warned: false,
+            })
+            .collect();
+        DictionaryWatcher {
+            files,
+            interval,
+            last_check: Instant::now(),
+            reload,
+        }
+    }
+
+    /// Check for changed dictionaries once the polling interval has elapsed, reloading
+    /// any that changed.  A failed reload keeps the previously registered dictionary and
+    /// emits a single warning until the file changes again.  Returns the number of
+    /// dictionaries successfully reloaded.
+    pub fn poll(&mut self, err_out: &mut dyn Write) -> usize {
+        if self.last_check.elapsed() < self.interval {
+            return 0;
+        }
+        self.last_check = Instant::now();
+
+        let mut reloaded = 0;
+        for file in &mut self.files {
+            let current = modified_time(&file.path);
+            if current.is_none() {
+                if !file.warned {
+                    warnings::warn(
+                        "watch-stat",
+                        &file.path,
+                        &format!("warning: cannot stat {}", file.path),
+                    );
+                    file.warned = true;
+                }
+                continue;
+            }
+            if current == file.modified {
+                continue;
+            }
+            file.modified = current;
+            match (self.reload)(&file.path) {
+                Ok(key) => {
+                    file.warned = false;
+                    reloaded += 1;
+                    let colours = palette();
+                    let _ = writeln!(
+                        err_out,
+                        "{}Reloaded{} {key} dictionary from {}",
+                        colours.title, colours.reset, file.path
+                    );
+                }
+                Err(err) => {
+                    // Keyed on the modification time, so each failed edit is reported.
+                    warnings::warn(
+                        "watch-reload",
+                        &format!("{}@{current:?}", file.path),
+                        &format!(
+                            "warning: {err:#}; keeping previous dictionary for {}",
+                            file.path
+                        ),
+                    );
+                    file.warned = true;
+                }
+            }
+        }
+        reloaded
+    }
+}
+
+fn modified_time(path: &str) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|meta| meta.modified()).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::File;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static RELOADS: AtomicUsize = AtomicUsize::new(0);
+
+    fn counting_reload(path: &str) -> anyhow::Result<String> {
+        let text = fs::read_to_string(path)?;
+        if !text.starts_with("<fix") {
+            anyhow::bail!("failed to parse FIX XML from {path}");
+        }
+        RELOADS.fetch_add(1, Ordering::SeqCst);
+        Ok("FIX44".to_string())
+    }
+
+    fn touch(path: &std::path::Path, contents: &str, offset_secs: u64) {
+        fs::write(path, contents).unwrap();
+        let stamp = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000 + offset_secs);
+        File::options()
+            .write(true)
+            .open(path)
+            .unwrap()
+            .set_modified(stamp)
+            .unwrap();
+    }
+
+    #[test]
+    fn reloads_on_change_and_warns_once_on_failure() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("custom.xml");
+        touch(&path, "<fix/>", 0);
+        let paths = vec![path.to_string_lossy().into_owned()];
+        let mut watcher = DictionaryWatcher::with_interval(&paths, counting_reload, Duration::ZERO);
+        let mut err = Vec::new();
+
+        assert_eq!(watcher.poll(&mut err), 0, "unchanged file is not reloaded");
+
+        touch(&path, "<fix></fix>", 10);
+        assert_eq!(watcher.poll(&mut err), 1);
+        assert_eq!(RELOADS.load(Ordering::SeqCst), 1);
+
+        touch(&path, "not xml", 20);
+        let ((), stderr) = warnings::capture(|| {
+            assert_eq!(watcher.poll(&mut err), 0);
+            assert_eq!(watcher.poll(&mut err), 0);
+        });
+        assert_eq!(
+            stderr.matches("warning:").count(),
+            1,
+            "parse failure should warn exactly once: {stderr}"
+        );
+        assert!(stderr.contains("keeping previous dictionary"));
+        assert_eq!(RELOADS.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn poll_respects_interval() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("custom.xml");
+        touch(&path, "<fix/>", 0);
+        let paths = vec![path.to_string_lossy().into_owned()];
+        let mut watcher =
+            DictionaryWatcher::with_interval(&paths, counting_reload, Duration::from_secs(3600));
+        touch(&path, "<fix></fix>", 10);
+        assert_eq!(watcher.poll(&mut Vec::new()), 0);
+    }
+}
@@ -1,33 +1,116 @@
 // SPDX-License-Identifier: AGPL-3.0-only
 // SPDX-FileCopyrightText: 2025 Steve Clarke <stephenlclarke@mac.com> - https://xyzzy.tools
 
-const FIX40_XML: &str = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/resources/FIX40.xml"));
-const FIX41_XML: &str = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/resources/FIX41.xml"));
-const FIX42_XML: &str = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/resources/FIX42.xml"));
-const FIX43_XML: &str = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/resources/FIX43.xml"));
-const FIX44_XML: &str = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/resources/FIX44.xml"));
-const FIX50_XML: &str = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/resources/FIX50.xml"));
-const FIX50SP1_XML: &str = include_str!(concat!(
-    env!("CARGO_MANIFEST_DIR"),
-    "/resources/FIX50SP1.xml"
-));
-const FIX50SP2_XML: &str = include_str!(concat!(
-    env!("CARGO_MANIFEST_DIR"),
-    "/resources/FIX50SP2.xml"
-));
-const FIXT11_XML: &str = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/resources/FIXT11.xml"));
+#[cfg(not(any(
+    feature = "dict-fix40",
+    feature = "dict-fix41",
+    feature = "dict-fix42",
+    feature = "dict-fix43",
+    feature = "dict-fix44",
+    feature = "dict-fix50",
+    feature = "dict-fix50sp1",
+    feature = "dict-fix50sp2",
+    feature = "dict-fixt11"
+)))]
+compile_error!("enable at least one dict-* feature so the binary carries a FIX dictionary");
 
-pub fn choose_embedded_xml(version: &str) -> &'static str {
-    match version.to_ascii_uppercase().as_str() {
-        "40" => FIX40_XML,
-        "41" => FIX41_XML,
-        "42" => FIX42_XML,
-        "43" => FIX43_XML,
-        "44" => FIX44_XML,
-        "50" => FIX50_XML,
-        "50SP1" => FIX50SP1_XML,
-        "50SP2" => FIX50SP2_XML,
-        "T11" | "FIXT11" => FIXT11_XML,
-        _ => FIX44_XML,
+/// The dictionaries compiled into this build, by XML identifier, with the SHA-256 of
+/// each taken by build.rs.  Each one sits behind its own `dict-*` Cargo feature so slim
+/// builds only carry the versions they need.
+const EMBEDDED: &[(&str, &str, &str)] = &[
+    #[cfg(feature = "dict-fix40")]
+    (
+        "40",
+        include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/resources/FIX40.xml")),
+        env!("FIXDECODER_SHA256_FIX40"),
+    ),
+    #[cfg(feature = "dict-fix41")]
+    (
+        "41",
+        include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/resources/FIX41.xml")),
+        env!("FIXDECODER_SHA256_FIX41"),
+    ),
+    #[cfg(feature = "dict-fix42")]
+    (
+        "42",
+        include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/resources/FIX42.xml")),
+        env!("FIXDECODER_SHA256_FIX42"),
+    ),
+    #[cfg(feature = "dict-fix43")]
+    (
+        "43",
+        include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/resources/FIX43.xml")),
+        env!("FIXDECODER_SHA256_FIX43"),
+    ),
+    #[cfg(feature = "dict-fix44")]
+    (
+        "44",
+        include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/resources/FIX44.xml")),
+        env!("FIXDECODER_SHA256_FIX44"),
+    ),
+    #[cfg(feature = "dict-fix50")]
+    (
+        "50",
+        include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/resources/FIX50.xml")),
+        env!("FIXDECODER_SHA256_FIX50"),
+    ),
+    #[cfg(feature = "dict-fix50sp1")]
+    (
+        "50SP1",
+        include_str!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/resources/FIX50SP1.xml"
+        )),
+        env!("FIXDECODER_SHA256_FIX50SP1"),
+    ),
+    #[cfg(feature = "dict-fix50sp2")]
+    (
+        "50SP2",
+        include_str!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/resources/FIX50SP2.xml"
+        )),
+        env!("FIXDECODER_SHA256_FIX50SP2"),
+    ),
+    #[cfg(feature = "dict-fixt11")]
+    (
+        "T11",
+        include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/resources/FIXT11.xml")),
+        env!("FIXDECODER_SHA256_FIXT11"),
+    ),
+];
+
+/// The embedded XML for `version` (e.g. "44", "50SP2" or "T11"), or `None` when that
+/// dictionary was not compiled in.
+pub fn choose_embedded_xml(version: &str) -> Option<&'static str> {
+    let id = version.to_ascii_uppercase();
+    let id = if id == "FIXT11" { "T11" } else { id.as_str() };
+    EMBEDDED
+        .iter()
+        .find(|(embedded, _, _)| *embedded == id)
+        .map(|(_, xml, _)| *xml)
+}
+
+/// The build-time SHA-256 of `xml`, in hex, when it is one of the embedded dictionaries
+/// rather than a copy of one.
+pub fn embedded_xml_sha256(xml: &str) -> Option<&'static str> {
+    EMBEDDED
+        .iter()
+        .find(|(_, embedded, _)| std::ptr::eq(*embedded, xml))
+        .map(|(_, _, hash)| *hash)
+}
+
+/// XML identifiers of every embedded dictionary, oldest first.
+pub fn embedded_xml_ids() -> Vec<&'static str> {
+    EMBEDDED.iter().map(|(id, _, _)| *id).collect()
+}
+
+/// The dictionary used when nothing more specific applies: FIX 4.4 when it is compiled
+/// in, otherwise the oldest one that is.
+pub fn default_xml_id() -> &'static str {
+    if choose_embedded_xml("44").is_some() {
+        "44"
+    } else {
+        EMBEDDED[0].0
     }
 }
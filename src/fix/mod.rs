@@ -9,13 +9,13 @@ mod dictionaries;
 pub use obfuscator::Obfuscator;
 pub use sensitive::SENSITIVE_TAG_NAMES;
 
-pub fn choose_embedded_xml(version: &str) -> &'static str {
-    dictionaries::choose_embedded_xml(version)
-}
+pub use dictionaries::{
+    choose_embedded_xml, default_xml_id, embedded_xml_ids, embedded_xml_sha256,
+};
 
 #[allow(dead_code)]
-pub fn supported_fix_versions() -> &'static str {
-    "40,41,42,43,44,50,50SP1,50SP2,T11"
+pub fn supported_fix_versions() -> String {
+    embedded_xml_ids().join(",")
 }
 
 pub fn create_obfuscator(enabled: bool) -> Obfuscator {
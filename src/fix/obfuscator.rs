@@ -4,24 +4,58 @@
 //! Lightweight FIX tag obfuscator for sensitive identifiers.
 //! Only the tags listed in `sensitive.rs` are touched, and replacements
 //! remain stable for the lifetime of the process to keep logs consistent.
+//! In stable mode aliases are derived from a keyed HMAC of the value, so the
+//! same identifier masks identically across files and across runs sharing a key.
+//! Format-preserving mode swaps each digit and letter for a random one of the
+//! same class, keeping length and punctuation so downstream parsers still cope.
 
-use std::collections::{BTreeMap, HashMap};
+use crate::decoder::raw_bytes;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::collections::hash_map::RandomState;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::hash::BuildHasher;
+use std::io::{self, Write};
 use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
 
 const SOH: char = '\u{0001}';
 
+/// Number of hex digits of the HMAC kept in a stable alias.
+const STABLE_DIGEST_HEX: usize = 12;
+
+/// Alias handed out for each (tag, original value).
+type AliasMap = HashMap<(u32, String), String>;
+
 /// Shared mutable state for the obfuscator.  Holds the mapping between
 /// original FIX tag values and their aliases so outputs remain consistent.
 #[derive(Default)]
 struct ObfuscatorState {
-    alias_map: HashMap<(u32, String), String>,
+    alias_map: AliasMap,
     counters: HashMap<u32, u32>,
+    /// The input the current sequential aliases belong to.
+    source: Option<String>,
+    /// Aliases of earlier inputs, kept for `--secret-map` once `reset` starts afresh;
+    /// empty unless [`Obfuscator::with_alias_sections`] asked for them.
+    finished: Vec<(Option<String>, AliasMap)>,
+}
+
+/// How aliases are generated: per-session counters or a keyed digest.
+enum AliasMode {
+    Sequential,
+    Stable { key: Vec<u8> },
 }
 
 /// Public obfuscator facade wrapping the sensitive tag map and alias state.
 pub struct Obfuscator {
     enabled: bool,
     tags: HashMap<u32, String>,
+    mode: AliasMode,
+    /// Key for format-preserving masks outside stable mode; `None` when the
+    /// mode is off.
+    format_key: Option<Vec<u8>>,
+    /// Whether `reset` keeps the aliases of the input it ends for `--secret-map`.
+    keep_finished: bool,
     state: Mutex<ObfuscatorState>,
 }
 
@@ -36,10 +70,42 @@ impl Obfuscator {
         Self {
             enabled,
             tags: copy,
+            mode: AliasMode::Sequential,
+            format_key: None,
+            keep_finished: false,
             state: Mutex::new(ObfuscatorState::default()),
         }
     }
 
+    /// Switch to stable aliases keyed by `key`.  Without a key a random per-run
+    /// salt is used, so aliases agree across files but not across runs.
+    pub fn with_stable_key(mut self, key: Option<&str>) -> Self {
+        let key = match key {
+            Some(key) => key.as_bytes().to_vec(),
+            None => random_salt(),
+        };
+        self.mode = AliasMode::Stable { key };
+        self
+    }
+
+    /// Mask values character by character instead of replacing them with a named
+    /// alias: digits become random digits, letters random letters of the same case,
+    /// bytes that are not UTF-8 other such bytes, and everything else is kept, so `ACC-12345678` might become `QJW-83920157`.
+    /// Masks follow the stable key when one is set and a per-run salt otherwise.
+    /// CheckSum(10) is rewritten for messages whose CheckSum was correct.
+    pub fn with_preserved_format(mut self) -> Self {
+        self.format_key = Some(random_salt());
+        self
+    }
+
+    /// Keep the aliases of every input for [`write_alias_map`](Self::write_alias_map),
+    /// as `--secret-map` needs.  Without it `reset` drops those of the input it ends,
+    /// so memory does not grow with the number of inputs.
+    pub fn with_alias_sections(mut self) -> Self {
+        self.keep_finished = true;
+        self
+    }
+
     /// Process a FIX line and return either the original content (when
     /// obfuscation is disabled) or a redacted version.
     pub fn enabled_line(&self, line: &str) -> String {
@@ -49,13 +115,22 @@ impl Obfuscator {
         self.obfuscate_line(line)
     }
 
-    /// Clear all cached aliases to start a new obfuscation session (e.g. per file).
-    pub fn reset(&self) {
-        if !self.enabled {
+    /// Start a new obfuscation session for `source` (a file, URL or stdin), so
+    /// sequential aliases count from one again.  Under
+    /// [`with_alias_sections`](Self::with_alias_sections) the aliases handed out so far
+    /// are kept as their own `--secret-map` section, since the same alias now stands
+    /// for different values in different inputs.  Stable aliases never change, so they
+    /// stay in a single mapping.
+    pub fn reset(&self, source: &str) {
+        if !self.enabled || matches!(self.mode, AliasMode::Stable { .. }) {
             return;
         }
         let mut state = self.state.lock().expect("obfuscator mutex poisoned");
-        state.alias_map.clear();
+        let aliases = std::mem::take(&mut state.alias_map);
+        let previous = state.source.replace(source.to_string());
+        if self.keep_finished && (previous.is_some() || !aliases.is_empty()) {
+            state.finished.push((previous, aliases));
+        }
         state.counters.clear();
     }
 
@@ -93,7 +168,11 @@ impl Obfuscator {
         }
 
         let delim = SOH.to_string();
-        fragments.join(&delim)
+        let mut masked = fragments.join(&delim);
+        if self.format_key.is_some() {
+            refresh_checksums(line, &mut masked);
+        }
+        masked
     }
 
     /// Return the alias for a tag/value pair, creating a new entry the first
@@ -106,13 +185,177 @@ impl Obfuscator {
             return alias.clone();
         }
 
-        let counter = state.counters.entry(tag).or_insert(0);
-        *counter += 1;
-        let alias = format!("{name}{:04}", counter);
+        let alias = match (&self.mode, &self.format_key) {
+            (AliasMode::Stable { key }, Some(_)) | (AliasMode::Sequential, Some(key)) => {
+                mask_preserving_format(key, value)
+            }
+            (AliasMode::Sequential, None) => {
+                let counter = state.counters.entry(tag).or_insert(0);
+                *counter += 1;
+                format!("{name}{:04}", counter)
+            }
+            (AliasMode::Stable { key }, None) => format!("{name}-{}", stable_digest(key, value)),
+        };
         state.alias_map.insert(key, alias.clone());
 
         alias
     }
+
+    /// Write every alias handed out so far as tab-separated
+    /// `tag, name, original, alias` lines, ordered by tag then value.  Sequential
+    /// aliases restart for each input, so each input gets its own section, headed
+    /// `# <source>` and separated from the one before by a blank line.
+    pub fn write_alias_map(&self, out: &mut dyn Write) -> io::Result<()> {
+        let state = self.state.lock().expect("obfuscator mutex poisoned");
+        let sections = state
+            .finished
+            .iter()
+            .map(|(source, aliases)| (source, aliases))
+            .chain(std::iter::once((&state.source, &state.alias_map)));
+        for (index, (source, aliases)) in sections.enumerate() {
+            if index > 0 {
+                writeln!(out)?;
+            }
+            if let Some(source) = source {
+                writeln!(out, "# {source}")?;
+            }
+            writeln!(out, "tag\tname\toriginal\talias")?;
+            let entries: BTreeSet<_> = aliases.iter().collect();
+            for ((tag, value), alias) in entries {
+                let name = self.tags.get(tag).map(String::as_str).unwrap_or("");
+                writeln!(out, "{tag}\t{name}\t{value}\t{alias}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Truncated hex HMAC-SHA256 of `value` under `key`; irreversible without the key.
+fn stable_digest(key: &[u8], value: &str) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts any key length");
+    mac.update(value.as_bytes());
+    let digest = mac.finalize().into_bytes();
+    let mut hex = String::with_capacity(STABLE_DIGEST_HEX);
+    for byte in digest.iter().take(STABLE_DIGEST_HEX / 2) {
+        hex.push_str(&format!("{byte:02x}"));
+    }
+    hex
+}
+
+/// Replace every ASCII digit with a random digit, every letter with a random letter
+/// of the same case and every raw byte stand-in (see [`raw_bytes`]) with another
+/// byte of `0x80..=0xFF`, drawing from an HMAC keystream over `value` so the result
+/// is deterministic for a key.  Other characters are kept, except non-ASCII letters
+/// and digits, which become one ASCII letter per UTF-8 byte so the byte length (and
+/// so BodyLength) never changes.
+fn mask_preserving_format(key: &[u8], value: &str) -> String {
+    let mut stream = Keystream::new(key, value);
+    let mut out = String::with_capacity(value.len());
+    for ch in value.chars() {
+        if ch.is_ascii_digit() {
+            out.push(shift_within(ch as u8, b'0', 10, stream.next_byte()) as char);
+        } else if ch.is_ascii_uppercase() {
+            out.push(shift_within(ch as u8, b'A', 26, stream.next_byte()) as char);
+        } else if ch.is_ascii_lowercase() {
+            out.push(shift_within(ch as u8, b'a', 26, stream.next_byte()) as char);
+        } else if let Some(byte) = raw_bytes::byte_of(ch) {
+            out.push(raw_bytes::stand_in(shift_within(
+                byte,
+                0x80,
+                128,
+                stream.next_byte(),
+            )));
+        } else if ch.is_alphanumeric() {
+            let base = if ch.is_lowercase() { b'a' } else { b'A' };
+            for _ in 0..ch.len_utf8() {
+                out.push((base + stream.next_byte() % 26) as char);
+            }
+        } else {
+            out.push(ch);
+        }
+    }
+    out
+}
+
+/// Move `byte` forward by 0..span-1 places within its class.  A zero step is allowed,
+/// so an unchanged character says nothing about the original.
+fn shift_within(byte: u8, base: u8, span: u8, random: u8) -> u8 {
+    let offset = byte - base;
+    let step = random % span;
+    base + (offset + step) % span
+}
+
+/// Bytes of HMAC-SHA256(key, value || block) for block = 0, 1, 2, ...
+struct Keystream<'a> {
+    key: &'a [u8],
+    value: &'a str,
+    block: u32,
+    buffer: Vec<u8>,
+}
+
+impl<'a> Keystream<'a> {
+    fn new(key: &'a [u8], value: &'a str) -> Self {
+        Self {
+            key,
+            value,
+            block: 0,
+            buffer: Vec::new(),
+        }
+    }
+
+    fn next_byte(&mut self) -> u8 {
+        if self.buffer.is_empty() {
+            let mut mac =
+                Hmac::<Sha256>::new_from_slice(self.key).expect("HMAC accepts any key length");
+            mac.update(self.value.as_bytes());
+            mac.update(&self.block.to_be_bytes());
+            self.block += 1;
+            self.buffer = mac.finalize().into_bytes().to_vec();
+            self.buffer.reverse();
+        }
+        self.buffer.pop().expect("keystream block is never empty")
+    }
+}
+
+/// Rewrite CheckSum(10) in `masked` for every message whose CheckSum was correct
+/// in `original`.  Format-preserving masks keep byte lengths, so each message sits
+/// at the same offsets in both lines.
+fn refresh_checksums(original: &str, masked: &mut String) {
+    let mut search = 0;
+    while let Some(found) = original[search..].find("8=FIX") {
+        let start = search + found;
+        let Some(found) = original[start..].find("\u{0001}10=") else {
+            break;
+        };
+        let trailer = start + found + 1;
+        let digits = trailer + 3..trailer + 6;
+        let Some(declared) = original.get(digits.clone()) else {
+            break;
+        };
+        if masked.len() == original.len()
+            && declared.bytes().all(|b| b.is_ascii_digit())
+            && declared.parse::<u32>().ok() == Some(raw_bytes::checksum(&original[start..trailer]))
+        {
+            let fresh = format!("{:03}", raw_bytes::checksum(&masked[start..trailer]));
+            masked.replace_range(digits.clone(), &fresh);
+        }
+        search = digits.end;
+    }
+}
+
+/// Per-run salt drawn from the std hasher's random keys and the clock.
+fn random_salt() -> Vec<u8> {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or_default();
+    let mut salt = Vec::with_capacity(32);
+    for round in 0..2u128 {
+        let hash = RandomState::new().hash_one(nanos ^ round);
+        salt.extend_from_slice(&hash.to_le_bytes());
+    }
+    salt.extend_from_slice(&nanos.to_le_bytes());
+    salt
 }
 
 /// Tiny helper that splits a FIX fragment on `=` or SOH so we can extract
@@ -138,8 +381,176 @@ mod tests {
         let first = obfuscator.obfuscate_line("49=ABC\u{0001}");
         let second = obfuscator.obfuscate_line("49=DEF\u{0001}");
         assert_ne!(first, second);
-        obfuscator.reset();
+        obfuscator.reset("second.log");
         let third = obfuscator.obfuscate_line("49=ABC\u{0001}");
         assert_eq!(first, third, "aliases should restart after reset");
     }
+
+    #[test]
+    fn stable_aliases_depend_only_on_key_and_value() {
+        let a =
+            Obfuscator::from_sensitive_tags(&SENSITIVE_TAG_NAMES, true).with_stable_key(Some("k1"));
+        let b =
+            Obfuscator::from_sensitive_tags(&SENSITIVE_TAG_NAMES, true).with_stable_key(Some("k1"));
+        b.obfuscate_line("49=OTHER\u{0001}");
+        let first = a.obfuscate_line("49=ABC\u{0001}");
+        a.reset("second.log");
+        assert_eq!(first, a.obfuscate_line("49=ABC\u{0001}"));
+        assert_eq!(first, b.obfuscate_line("49=ABC\u{0001}"));
+        assert!(first.starts_with("49=SenderCompID-"));
+        assert!(!first.contains("ABC"));
+
+        let c =
+            Obfuscator::from_sensitive_tags(&SENSITIVE_TAG_NAMES, true).with_stable_key(Some("k2"));
+        assert_ne!(first, c.obfuscate_line("49=ABC\u{0001}"));
+    }
+
+    #[test]
+    fn alias_map_lists_each_value_once() {
+        let obfuscator = Obfuscator::from_sensitive_tags(&SENSITIVE_TAG_NAMES, true);
+        obfuscator.obfuscate_line("49=ABC\u{0001}56=XYZ\u{0001}49=ABC\u{0001}");
+        let mut out = Vec::new();
+        obfuscator.write_alias_map(&mut out).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        assert_eq!(
+            text,
+            "tag\tname\toriginal\talias\n49\tSenderCompID\tABC\tSenderCompID0001\n56\tTargetCompID\tXYZ\tTargetCompID0001\n"
+        );
+    }
+
+    #[test]
+    fn alias_map_has_a_section_for_each_input_of_a_sequential_run() {
+        let obfuscator =
+            Obfuscator::from_sensitive_tags(&SENSITIVE_TAG_NAMES, true).with_alias_sections();
+        obfuscator.reset("a.log");
+        obfuscator.obfuscate_line("49=ABC\u{0001}");
+        obfuscator.reset("b.log");
+        obfuscator.obfuscate_line("49=DEF\u{0001}");
+        let mut out = Vec::new();
+        obfuscator.write_alias_map(&mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "# a.log\ntag\tname\toriginal\talias\n49\tSenderCompID\tABC\tSenderCompID0001\n\n\
+             # b.log\ntag\tname\toriginal\talias\n49\tSenderCompID\tDEF\tSenderCompID0001\n"
+        );
+    }
+
+    #[test]
+    fn finished_inputs_are_dropped_without_a_secret_map() {
+        let obfuscator = Obfuscator::from_sensitive_tags(&SENSITIVE_TAG_NAMES, true);
+        for source in ["a.log", "b.log", "c.log"] {
+            obfuscator.reset(source);
+            obfuscator.obfuscate_line("49=ABC\u{0001}");
+        }
+        let state = obfuscator.state.lock().unwrap();
+        assert!(state.finished.is_empty());
+        assert_eq!(state.alias_map.len(), 1);
+    }
+
+    #[test]
+    fn format_preserving_masks_keep_shape_without_leaking() {
+        let obfuscator = Obfuscator::from_sensitive_tags(&SENSITIVE_TAG_NAMES, true)
+            .with_stable_key(Some("k1"))
+            .with_preserved_format();
+        let masked =
+            obfuscator.obfuscate_line("1=ACC-12345678\u{0001}50=jo.bloggs@example.com\u{0001}");
+        let (account, rest) = masked
+            .strip_prefix("1=")
+            .and_then(|m| m.split_once('\u{0001}'))
+            .unwrap();
+        assert_eq!(account.len(), "ACC-12345678".len());
+        assert_ne!(account, "ACC-12345678");
+        for (orig, new) in "ACC-12345678".chars().zip(account.chars()) {
+            if orig.is_ascii_alphanumeric() {
+                assert_eq!(orig.is_ascii_digit(), new.is_ascii_digit());
+                assert_eq!(orig.is_ascii_uppercase(), new.is_ascii_uppercase());
+            } else {
+                assert_eq!(orig, new);
+            }
+        }
+        let email = rest
+            .strip_prefix("50=")
+            .unwrap()
+            .trim_end_matches('\u{0001}');
+        assert!(email.contains('@') && email.chars().filter(|c| *c == '.').count() == 2);
+
+        let again = Obfuscator::from_sensitive_tags(&SENSITIVE_TAG_NAMES, true)
+            .with_preserved_format()
+            .with_stable_key(Some("k1"));
+        assert_eq!(
+            masked,
+            again.obfuscate_line("1=ACC-12345678\u{0001}50=jo.bloggs@example.com\u{0001}")
+        );
+    }
+
+    #[test]
+    fn format_preserving_masks_cover_bytes_that_are_not_utf8() {
+        let obfuscator = Obfuscator::from_sensitive_tags(&SENSITIVE_TAG_NAMES, true)
+            .with_stable_key(Some("k1"))
+            .with_preserved_format();
+        // "Société" in Latin-1: each é is a byte carried as a stand-in character.
+        let value = raw_bytes::decode(b"Soci\xe9t\xe9").into_owned();
+        let masked = obfuscator.obfuscate_line(&format!("1={value}\u{0001}"));
+        let masked = masked
+            .strip_prefix("1=")
+            .and_then(|m| m.strip_suffix('\u{0001}'))
+            .unwrap();
+        assert_eq!(raw_bytes::byte_len(masked), raw_bytes::byte_len(&value));
+        let bytes: Vec<Option<u8>> = masked.chars().map(raw_bytes::byte_of).collect();
+        assert!(bytes[4].is_some() && bytes[6].is_some(), "{masked:?}");
+        assert!(bytes.iter().flatten().all(|b| *b >= 0x80));
+        assert_ne!(
+            masked
+                .chars()
+                .filter_map(raw_bytes::byte_of)
+                .collect::<Vec<_>>(),
+            [0xE9, 0xE9],
+            "the Latin-1 bytes are masked"
+        );
+    }
+
+    #[test]
+    fn format_preserving_masks_keep_messages_valid() {
+        use crate::decoder::tag_lookup::load_dictionary;
+        use crate::decoder::validator::validate_fix_message;
+
+        let body = "35=0\u{0001}49=ACC-1234\u{0001}56=BROKER9\u{0001}34=7\u{0001}52=20240101-00:00:00\u{0001}";
+        let head = format!("8=FIX.4.4\u{0001}9={}\u{0001}{body}", body.len());
+        let msg = format!("{head}10={:03}\u{0001}", raw_bytes::checksum(&head));
+        let dict = load_dictionary(&msg);
+        assert!(validate_fix_message(&msg, &dict).is_clean());
+
+        let obfuscator =
+            Obfuscator::from_sensitive_tags(&SENSITIVE_TAG_NAMES, true).with_preserved_format();
+        let masked = obfuscator.obfuscate_line(&format!("IN {msg}"));
+        let masked = masked.strip_prefix("IN ").unwrap();
+        assert_ne!(masked, msg);
+        assert_eq!(masked.len(), msg.len());
+        assert!(!masked.contains("ACC-1234") && !masked.contains("BROKER9"));
+        assert!(validate_fix_message(masked, &dict).is_clean());
+
+        // CheckSum counts a Latin-1 byte once, not as its stand-in's UTF-8.
+        let latin1 = msg.replace(
+            "35=0\u{0001}",
+            "35=0\u{0001}58=Soci\u{10FFE9}t\u{10FFE9}\u{0001}",
+        );
+        let body_len =
+            raw_bytes::byte_len(&latin1[latin1.find("35=").unwrap()..latin1.find("10=").unwrap()]);
+        let latin1 = latin1.replacen(&format!("9={}", body.len()), &format!("9={body_len}"), 1);
+        let trailer = latin1.find("10=").unwrap();
+        let latin1 = format!(
+            "{}10={:03}\u{0001}",
+            &latin1[..trailer],
+            raw_bytes::checksum(&latin1[..trailer])
+        );
+        assert!(validate_fix_message(&latin1, &dict).is_clean());
+        let masked = obfuscator.obfuscate_line(&latin1);
+        assert!(!masked.contains("ACC-1234"));
+        assert!(validate_fix_message(&masked, &dict).is_clean());
+
+        // A CheckSum that was already wrong is left for the validator to report.
+        let broken = msg.replace(&msg[msg.len() - 4..], "000\u{0001}");
+        let masked = obfuscator.obfuscate_line(&broken);
+        assert!(masked.ends_with("10=000\u{0001}"));
+    }
 }
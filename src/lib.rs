@@ -0,0 +1,8 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// SPDX-FileCopyrightText: 2025 Steve Clarke <stephenlclarke@mac.com> - https://xyzzy.tools
+
+//! FIX dictionary tooling and the streaming log prettifier behind the `fixdecoder`
+//! binary, exposed as a library so other tools in the workspace (such as `pcap2fix`)
+//! can decode and validate messages in-process.
+pub mod decoder;
+pub mod fix;
@@ -1,36 +1,98 @@
 // SPDX-License-Identifier: AGPL-3.0-only
 // SPDX-FileCopyrightText: 2025 Steve Clarke <stephenlclarke@mac.com> - https://xyzzy.tools
 
-/// fixdecoder command-line entry point and CLI orchestration.
-///
-/// The binary ties together the dictionary tooling and the streaming FIX log
-/// prettifier.  This file is intentionally light on protocol logic; it wires
-/// user input into the focused modules under `src/decoder` and `src/fix`.
-/// The comments favour UK English and aim to give future maintainers a quick
-/// reminder of why each function exists and how it cooperates with the rest
-/// of the app.
-mod decoder;
-mod fix;
-
-use crate::decoder::colours;
-use anyhow::{Context, Result, anyhow};
+//! fixdecoder command-line entry point and CLI orchestration.
+//!
+//! The binary ties together the dictionary tooling and the streaming FIX log
+//! prettifier.  This file is intentionally light on protocol logic; it wires
+//! user input into the focused modules under `src/decoder` and `src/fix`.
+//! The comments favour UK English and aim to give future maintainers a quick
+//! reminder of why each function exists and how it cooperates with the rest
+//! of the app.
+
+use anyhow::{Context, Result, anyhow, bail};
+use chrono::TimeDelta;
 use clap::error::ErrorKind;
 use clap::parser::ValueSource;
-use clap::{Arg, ArgAction, ArgMatches, Command};
+use clap::{Arg, ArgAction, ArgGroup, ArgMatches, Command};
+use clap_complete::Shell;
 use decoder::{
-    DisplayStyle, FixDictionary, PrettifyContext, disable_output_colours, display_component,
-    display_message, list_all_components, list_all_messages, list_all_tags, prettify_files,
-    print_component_columns, print_message_columns, print_tag_details, print_tags_in_columns,
-    register_fix_dictionary, schema::SchemaTree, summary::OrderSummary, tag_lookup,
+    DisplayStyle, FixDictionary, Outline, PrettifyContext,
+    benchmark::Benchmark,
+    clock_skew::ClockSkewStats,
+    dedupe::{DEFAULT_IGNORE_TAGS, MessageDeduper},
+    delimiter::{self, DELIMITER_FORMS},
+    dict_cache::{self, DiskCache},
+    disable_output_colours,
+    display::{self, DisplayOptions},
+    display_component, display_message,
+    enum_overlay::EnumOverlay,
+    file_prefix::{PrefixedWriter, render_file_table},
+    findings_report::{FindingsReport, ReportFormat},
+    fix_map::{FixMap, FixMapRule},
+    generator::generate_message,
+    input_range::{InputRange, SeekTo},
+    line_timing::{LineTimestampFormat, LineTiming},
+    list_all_components, list_all_messages, list_all_tags,
+    msg_type_filter::MsgTypeFilter,
+    order_chains::OrderChains,
+    prettifier::{
+        CountsLayout, CountsSort, DeclaredVersions, DictionaryCache, ExtractSinks, HiddenBlocks,
+        RunOutcome, ValidationStats,
+    },
+    prettify_files, print_component_columns, print_enum_lookup, print_fields_in_columns,
+    print_message_columns, print_search_results, print_tag_details, print_tags_in_columns,
+    register_fix_dictionary,
+    repair::{RepairStats, repair_stream},
+    replay::Replayer,
+    schema::{Field, SchemaTree},
+    sequence_diagram::{self, DiagramFormat, SequenceDiagram},
+    split_output::{SplitMode, SplitOutput},
+    stream::{LineDecoder, RecordFormat},
+    summary::{DEFAULT_DIFF_TAGS, OrderSummary, SessionSummary, SummaryGroup, ord_status_state},
+    tag_histogram::TagHistogram,
+    tag_lookup::{self, FixTagLookup, normalise_fix_key},
+    tag_usage::TagUsage,
+    udf::UdfCatalogue,
+    validation_summary::ValidationSummary,
+    validator,
+    warnings::WarningMode,
+    watcher::DictionaryWatcher,
 };
-use std::collections::HashMap;
+use fixdecoder::decoder::colours::{self, Theme};
+use fixdecoder::{decoder, fix};
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::ffi::OsString;
 use std::fs;
 use std::io;
 use std::io::IsTerminal;
 use std::io::Write;
+use std::path::PathBuf;
 use std::process;
-use std::sync::OnceLock;
-use std::sync::atomic::Ordering;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{OnceLock, RwLock};
+
+/// Canonical key of the FIXT transport dictionary used by FIX 5.0+ sessions.
+const SESSION_DICTIONARY_KEY: &str = "FIXT11";
+
+/// The custom transport dictionary in force, and the FIX 5.0+ `--xml` files that take
+/// their session header and trailer from it.  A `--watch-xml` reload of the transport
+/// replaces it and re-merges those files; a reload of one of them merges the current
+/// transport.
+static SESSION_LAYER: RwLock<SessionLayer> = RwLock::new(SessionLayer {
+    transport: None,
+    dependents: Vec::new(),
+});
+/// `--strict-dict`: consistency warnings in a custom dictionary fail its load,
+/// including `--watch-xml` reloads.
+static STRICT_DICTIONARIES: AtomicBool = AtomicBool::new(false);
+
+struct SessionLayer {
+    transport: Option<FixDictionary>,
+    dependents: Vec<String>,
+}
 
 /// Wrapper for a custom FIX dictionary sourced from `--xml` along with its path.
 struct CustomDictionary {
@@ -102,16 +164,24 @@ fn install_interrupt_handler() -> Result<()> {
     .context("failed to install Ctrl+C handler")
 }
 
+/// Exit codes: clean run, IO or usage error, and validation failures per `--fail-on`.
+const EXIT_CLEAN: i32 = 0;
+const EXIT_FAILURE: i32 = 1;
+const EXIT_INVALID: i32 = 2;
+const EXIT_WARNINGS: i32 = 3;
+
 /// Conventional `main` that defers to `run` so tests can call the logic
 /// without having to spin up a separate process.
 fn main() {
-    process::exit(match run() {
+    let code = match run() {
         Ok(code) => code,
         Err(err) => {
             eprintln!("{err}");
-            1
+            EXIT_FAILURE
         }
-    });
+    };
+    let _ = decoder::warnings::report_suppressed(&mut io::stderr());
+    process::exit(code);
 }
 
 /// Parse CLI arguments, load dictionaries, respond to informational flags
@@ -119,84 +189,591 @@ fn main() {
 /// here, so the structure favours clarity over cleverness.
 fn run() -> Result<i32> {
     install_interrupt_handler()?;
-    println!("{}", version_string());
 
     let Some(opts) = parse_cli_options()? else {
         return Ok(0);
     };
+    decoder::warnings::set_mode(opts.warning_mode);
+    if opts.extracts_to_stdout()
+        || opts.exports_xml_to_stdout()
+        || opts.json
+        || opts.repair
+        || opts.passthrough
+        || opts.replay.is_some()
+        || opts.split_output.is_some()
+    {
+        eprintln!("{}", version_string());
+    } else {
+        println!("{}", version_string());
+    }
+    if opts.clear_cache {
+        return clear_dictionary_cache();
+    }
+    if !opts.no_cache
+        && let Some(dir) = dict_cache::default_cache_dir()
+    {
+        dict_cache::set_cache_dir(dir);
+    }
+    if opts.repair {
+        return repair_files(&opts);
+    }
 
+    // Overlays must be installed before any dictionary is built.
+    load_enum_overlays(&opts.enum_overlays)?;
+    load_udf(&opts.udf_files, opts.udf_override)?;
     let (custom_dicts, schema) = prepare_schema(&opts)?;
+    if !opts.fix_map.is_empty() {
+        tag_lookup::set_fix_map(FixMap::new(opts.fix_map.clone()));
+    }
+    apply_colour_preferences(&opts)?;
 
     if run_handlers(&opts, &schema, &custom_dicts)? {
         return Ok(0);
     }
 
-    apply_colour_preferences(&opts);
-
-    let obfuscator = fix::create_obfuscator(opts.secret);
+    validator::disable_rules(opts.disabled_rules.clone());
+    if let Some(limit) = opts.max_decimals {
+        validator::set_max_decimals(limit);
+    }
+    if let Some(limit) = opts.clock_skew {
+        validator::set_clock_skew_limit(limit);
+    }
+    validator::set_warn_deprecated(opts.warn_deprecated);
+    let obfuscator = build_obfuscator(&opts);
+    let msg_types = build_msg_type_filter(&opts, &schema)?;
     let files = resolve_input_files(&opts);
+    if opts.seek.is_some() && files.iter().any(|f| f == "-") {
+        bail!("--seek and --seek-line need input files; they cannot be used with stdin");
+    }
+    if opts.seek.is_some() && files.iter().any(|f| decoder::remote::is_remote(f)) {
+        bail!("--seek and --seek-line need local files; they cannot be used with URLs");
+    }
 
-    let mut summary = opts.summary.then(|| OrderSummary::new(opts.delimiter));
+    let mut summary = opts.summary.then(|| build_order_summary(&opts));
     let fix_override = opts
         .fix_from_user
         .then(|| normalise_fix_key(&opts.fix_version))
         .flatten();
     let mut stdout = io::stdout();
+    let mut prefixed = opts.prefix_file.then(|| PrefixedWriter::new(io::stdout()));
+    let file_prefix = prefixed.as_ref().map(PrefixedWriter::prefix);
+    let mut replay_out = io::stdout();
+    let mut suppressed = io::sink();
     let mut stderr = io::stderr();
+    let (mut extract_all, mut extract_invalid) =
+        open_extract_sinks(opts.extract.as_deref(), opts.extract_invalid.as_deref())?;
+    let mut report_file = match &opts.report {
+        Some((path, _)) => Some(io::BufWriter::new(
+            fs::File::create(path).with_context(|| format!("failed to create report {path}"))?,
+        )),
+        None => None,
+    };
+    let split = match &opts.split_output {
+        Some((dir, mode)) => Some(
+            SplitOutput::new(dir, *mode)
+                .with_context(|| format!("failed to create --split-output {}", dir.display()))?,
+        ),
+        None => None,
+    };
+    let out: &mut dyn Write = if opts.extracts_to_stdout()
+        || opts.replay.is_some()
+        || split.is_some()
+        || opts.benchmark
+    {
+        &mut suppressed
+    } else if let Some(prefixed) = prefixed.as_mut() {
+        prefixed
+    } else {
+        &mut stdout
+    };
     let mut ctx = build_context(
         &obfuscator,
         &mut summary,
         fix_override.as_deref(),
         &opts,
-        &mut stdout,
+        out,
         &mut stderr,
     );
-    let code = prettify_files(&files, &mut ctx);
+    ctx.extract = ExtractSinks {
+        all: extract_all.as_deref_mut().map(|w| w as &mut dyn Write),
+        invalid: extract_invalid.as_deref_mut().map(|w| w as &mut dyn Write),
+    };
+    ctx.msg_types = msg_types;
+    ctx.file_prefix = file_prefix;
+    ctx.split = split;
+    if let (Some(file), Some((_, format))) = (report_file.as_mut(), &opts.report) {
+        ctx.report = Some(FindingsReport::new(file, *format, version_string()));
+    }
+    ctx.replay = opts
+        .replay
+        .map(|speed| Replayer::new(&mut replay_out, speed));
+    ctx.benchmark = opts.benchmark.then(Benchmark::start);
+    let outcome = prettify_files(&files, &mut ctx);
+    if let Some(bench) = &ctx.benchmark {
+        let messages = outcome.files.iter().map(|file| file.messages).sum();
+        bench
+            .render(messages, &mut io::stdout())
+            .context("failed to write the benchmark report")?;
+    }
+    if let (Some(path), Some(diagram)) = (&opts.sequence_diagram, &ctx.sequence_diagram) {
+        write_sequence_diagram(path, diagram, ctx.out)?;
+    }
+    if opts.prefix_file {
+        render_file_table(ctx.out, &outcome.files, opts.validate)
+            .context("failed to write the per-file table")?;
+    }
+    ctx.extract
+        .flush()
+        .context("failed to write extracted messages")?;
+    if let Some(report) = ctx.report.as_mut() {
+        report
+            .finish()
+            .context("failed to write the --report file")?;
+    }
 
     warn_on_override_fallback(ctx.err_out);
+    if let Some(path) = &opts.secret_map {
+        write_secret_map(&obfuscator, path)?;
+    }
+
+    Ok(final_exit_code(run_exit_code(&outcome, opts.fail_on)))
+}
+
+/// Write the `--sequence-diagram` to `path`, or to `out` for `-`.
+fn write_sequence_diagram(
+    path: &str,
+    diagram: &SequenceDiagram,
+    out: &mut dyn Write,
+) -> Result<()> {
+    if path == "-" {
+        return diagram
+            .render(out)
+            .context("failed to write the sequence diagram");
+    }
+    let mut file = io::BufWriter::new(
+        fs::File::create(path).with_context(|| format!("failed to create {path}"))?,
+    );
+    diagram
+        .render(&mut file)
+        .and_then(|_| file.flush())
+        .with_context(|| format!("failed to write the sequence diagram to {path}"))
+}
+
+/// `--repair`: write each input message back out with BodyLength and CheckSum
+/// recomputed, then report on stderr how many needed fixing.
+fn repair_files(opts: &CliOptions) -> Result<i32> {
+    let mut stats = RepairStats::default();
+    let mut out = io::BufWriter::new(io::stdout().lock());
+    for path in resolve_input_files(opts) {
+        if path == "-" {
+            repair_stream(
+                &mut io::stdin().lock(),
+                opts.delimiter,
+                &mut out,
+                &mut stats,
+            )?;
+        } else {
+            let file = fs::File::open(&path).with_context(|| format!("failed to open {path}"))?;
+            repair_stream(
+                &mut io::BufReader::new(file),
+                opts.delimiter,
+                &mut out,
+                &mut stats,
+            )?;
+        }
+    }
+    out.flush()?;
+    eprintln!(
+        "Repaired {} message(s), {} already correct",
+        stats.repaired, stats.correct
+    );
+    Ok(0)
+}
+
+/// Read every `--enum-overlay` file, later files winning, and install the result for
+/// all dictionaries loaded from here on.
+fn load_enum_overlays(paths: &[String]) -> Result<()> {
+    let mut overlay = EnumOverlay::default();
+    for path in paths {
+        overlay.extend(EnumOverlay::load(path.as_ref())?);
+    }
+    if !overlay.is_empty() {
+        tag_lookup::set_enum_overlay(overlay);
+    }
+    Ok(())
+}
+
+/// Read every `--udf` file, later files winning, and install the fields for all
+/// dictionaries loaded from here on.
+fn load_udf(paths: &[String], replace: bool) -> Result<()> {
+    let mut udf = UdfCatalogue::default();
+    for path in paths {
+        udf.extend(UdfCatalogue::load(path.as_ref())?);
+    }
+    if !udf.is_empty() {
+        tag_lookup::set_udf(udf, replace);
+    }
+    Ok(())
+}
+
+/// Build the obfuscator, switching to keyed stable aliases when requested.
+fn build_obfuscator(opts: &CliOptions) -> fix::Obfuscator {
+    let mut obfuscator = fix::create_obfuscator(opts.secret);
+    if opts.secret_format {
+        obfuscator = obfuscator.with_preserved_format();
+    }
+    if opts.secret_map.is_some() {
+        obfuscator = obfuscator.with_alias_sections();
+    }
+    if opts.secret_stable || opts.secret_key.is_some() {
+        obfuscator.with_stable_key(opts.secret_key.as_deref())
+    } else {
+        obfuscator
+    }
+}
+
+/// Open an `--extract`/`--extract-invalid` destination: "-" is stdout, anything else
+/// is appended to.
+/// Open the `--extract` and `--extract-invalid` destinations.  When both name the
+/// same path they share one buffered writer, so records reach it whole and in order.
+fn open_extract_sinks(
+    all: Option<&str>,
+    invalid: Option<&str>,
+) -> Result<(ExtractSink, ExtractSink)> {
+    if let Some(path) = all.filter(|path| invalid == Some(*path)) {
+        let shared = Rc::new(RefCell::new(open_extract_sink(path)?));
+        return Ok((
+            Some(Box::new(SharedSink(Rc::clone(&shared)))),
+            Some(Box::new(SharedSink(shared))),
+        ));
+    }
+    Ok((
+        all.map(open_extract_sink).transpose()?,
+        invalid.map(open_extract_sink).transpose()?,
+    ))
+}
+
+type ExtractSink = Option<Box<dyn Write>>;
+
+/// One handle on an extract writer that both `--extract` options point at.
+struct SharedSink(Rc<RefCell<Box<dyn Write>>>);
 
-    Ok(final_exit_code(code))
+impl Write for SharedSink {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.borrow_mut().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.0.borrow_mut().flush()
+    }
+}
+
+fn open_extract_sink(path: &str) -> Result<Box<dyn Write>> {
+    if path == "-" {
+        return Ok(Box::new(io::BufWriter::new(io::stdout())));
+    }
+    let file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("failed to open extract file {path}"))?;
+    Ok(Box::new(io::BufWriter::new(file)))
+}
+
+/// Dump the original-to-alias mapping collected during the run for `--secret-map`.
+fn write_secret_map(obfuscator: &fix::Obfuscator, path: &str) -> Result<()> {
+    let mut file =
+        fs::File::create(path).with_context(|| format!("failed to create secret map {path}"))?;
+    obfuscator
+        .write_alias_map(&mut file)
+        .with_context(|| format!("failed to write secret map {path}"))
 }
 
 fn parse_cli_options() -> Result<Option<CliOptions>> {
+    let args: Vec<OsString> = std::env::args_os().collect();
+    let Some(mut matches) = get_matches(&args)? else {
+        return Ok(None);
+    };
+    if write_shell_integration(&matches)? {
+        return Ok(None);
+    }
+
+    let config = load_config(&matches).inspect_err(|_| {
+        println!("{}", version_string());
+    })?;
+    if let Some(config) = &config {
+        let merged = merge_config_args(&args, config, &build_cli(), &matches, &mut io::stderr())
+            .inspect_err(|_| {
+                println!("{}", version_string());
+            })?;
+        if merged.len() > args.len() {
+            let Some(remerged) = get_matches(&merged)? else {
+                return Ok(None);
+            };
+            matches = remerged;
+        }
+    }
+
+    let opts = CliOptions::from_matches(&matches).inspect_err(|_| {
+        println!("{}", version_string());
+    })?;
+    if matches.get_flag("dump-config") {
+        let source = match &config {
+            Some(config) => format!("# config file: {}", config.path.display()),
+            None => "# no config file".to_string(),
+        };
+        print!("{source}\n{}", dump_config(&build_cli(), &matches)?);
+        return Ok(None);
+    }
+    if opts.show_version {
+        println!("{}", version_string());
+        return Ok(None);
+    }
+
+    Ok(Some(opts))
+}
+
+/// Run clap over `args`, printing help, version or usage errors the way the
+/// rest of the CLI does.  Returns `None` when help or version was shown.
+fn get_matches(args: &[OsString]) -> Result<Option<ArgMatches>> {
     let cmd = build_cli();
-    let matches = match cmd.try_get_matches() {
+    let matches = match cmd.try_get_matches_from(args) {
         Ok(m) => m,
         Err(err) => match err.kind() {
             ErrorKind::DisplayHelp | ErrorKind::DisplayVersion => {
+                println!("{}", version_string());
                 err.print()?;
                 if err.kind() == ErrorKind::DisplayHelp {
                     print_usage();
                 }
                 return Ok(None);
             }
-            _ => err.exit(),
+            _ => {
+                println!("{}", version_string());
+                err.print()?;
+                process::exit(EXIT_FAILURE);
+            }
         },
     };
+    Ok(Some(matches))
+}
 
-    let opts = CliOptions::from_matches(&matches)?;
-    if opts.show_version {
-        return Ok(None);
+/// Options that only make sense on the command line, so a config file can neither
+/// set them nor have them printed by `--dump-config`.
+const CLI_ONLY_OPTIONS: &[&str] = &[
+    "files",
+    "config",
+    "dump-config",
+    "version",
+    "generate-completions",
+    "generate-manpage",
+    "clear-cache",
+];
+
+/// Options a config file may set but `--dump-config` leaves out, so a secret is not
+/// printed where shell history or CI logs would keep it.
+const SECRET_OPTIONS: &[&str] = &["secret-key"];
+
+/// A config file of default options, keyed by long option name.
+struct ConfigFile {
+    path: PathBuf,
+    table: toml::Table,
+}
+
+/// `$XDG_CONFIG_HOME/fixdecoder/config.toml`, falling back to
+/// `~/.config/fixdecoder/config.toml`.
+fn default_config_path() -> Option<PathBuf> {
+    let non_empty = |name| std::env::var_os(name).filter(|v| !v.is_empty());
+    let base = non_empty("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| non_empty("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(base.join("fixdecoder").join("config.toml"))
+}
+
+/// Read the `--config` file, or the default one when it exists.  A missing
+/// default is not an error; a missing `--config` file is.
+fn load_config(matches: &ArgMatches) -> Result<Option<ConfigFile>> {
+    let path = match matches.get_one::<String>("config") {
+        Some(path) => PathBuf::from(path),
+        None => match default_config_path() {
+            Some(path) if path.is_file() => path,
+            _ => return Ok(None),
+        },
+    };
+    let text = fs::read_to_string(&path)
+        .with_context(|| format!("failed to read config file {}", path.display()))?;
+    let table = text
+        .parse::<toml::Table>()
+        .with_context(|| format!("invalid config file {}", path.display()))?;
+    Ok(Some(ConfigFile { path, table }))
+}
+
+/// Insert the config file's entries into `args` as `--key=value` options, skipping
+/// any option already given on the command line or in conflict with one that was,
+/// such as `quiet` against `--verbose-warnings`.  The merged arguments then go
+/// through clap and [`CliOptions::from_matches`] like any other, so a bad value in
+/// the file fails exactly as the same flag would.  Unknown keys are reported on
+/// `warn` and ignored.
+fn merge_config_args(
+    args: &[OsString],
+    config: &ConfigFile,
+    cmd: &Command,
+    matches: &ArgMatches,
+    warn: &mut dyn Write,
+) -> Result<Vec<OsString>> {
+    let path = config.path.display();
+    let mut extra = Vec::new();
+    for (key, value) in &config.table {
+        let id = key.replace('_', "-");
+        let Some(arg) = cmd
+            .get_arguments()
+            .find(|arg| arg.get_id() == id.as_str() && !CLI_ONLY_OPTIONS.contains(&id.as_str()))
+        else {
+            writeln!(
+                warn,
+                "warning: ignoring unknown key '{key}' in config file {path}"
+            )?;
+            continue;
+        };
+        if matches.value_source(&id) == Some(ValueSource::CommandLine)
+            || conflicts_with_command_line(cmd, arg, matches)
+        {
+            continue;
+        }
+        let items = match value {
+            toml::Value::Array(items) => items.iter().collect(),
+            single => vec![single],
+        };
+        let mut values = Vec::new();
+        for item in items {
+            values.push(match item {
+                toml::Value::String(text) => text.clone(),
+                toml::Value::Integer(n) => n.to_string(),
+                toml::Value::Float(n) => n.to_string(),
+                toml::Value::Boolean(b) => b.to_string(),
+                other => bail!("invalid value for '{key}' in config file {path}: {other}"),
+            });
+        }
+        match arg.get_action() {
+            ArgAction::SetTrue => match values.as_slice() {
+                [v] if v == "true" => extra.push(format!("--{id}")),
+                [v] if v == "false" => {}
+                _ => {
+                    bail!("invalid value for '{key}' in config file {path}: expected true or false")
+                }
+            },
+            ArgAction::Append => extra.extend(values.iter().map(|v| format!("--{id}={v}"))),
+            _ if values.len() == 1 => extra.push(format!("--{id}={}", values[0])),
+            _ => {
+                extra.push(format!("--{id}"));
+                extra.extend(values);
+            }
+        }
     }
 
-    Ok(Some(opts))
+    let mut merged = args.to_vec();
+    let at = merged.len().min(1);
+    merged.splice(at..at, extra.into_iter().map(OsString::from));
+    Ok(merged)
+}
+
+/// Whether an option given on the command line conflicts with `arg`, whichever of the
+/// two declares the conflict or through a group that allows only one of its options.
+fn conflicts_with_command_line(cmd: &Command, arg: &Arg, matches: &ArgMatches) -> bool {
+    let given = |id: &clap::Id| matches.value_source(id.as_str()) == Some(ValueSource::CommandLine);
+    let declared = cmd
+        .get_arg_conflicts_with(arg)
+        .iter()
+        .any(|other| given(other.get_id()));
+    let declared_by_other = cmd
+        .get_arguments()
+        .filter(|other| given(other.get_id()))
+        .any(|other| {
+            cmd.get_arg_conflicts_with(other)
+                .iter()
+                .any(|conflict| conflict.get_id() == arg.get_id())
+        });
+    let grouped = cmd
+        .get_groups()
+        .filter(|group| !ArgGroup::clone(group).is_multiple())
+        .filter(|group| group.get_args().any(|id| id == arg.get_id()))
+        .any(|group| group.get_args().any(|id| id != arg.get_id() && given(id)));
+    declared || declared_by_other || grouped
+}
+
+/// Render every option that has a value, whether from the command line, the config
+/// file or a built-in default, as a config file that `--config` would accept.
+/// [`SECRET_OPTIONS`] are left out.
+fn dump_config(cmd: &Command, matches: &ArgMatches) -> Result<String> {
+    let mut table = toml::Table::new();
+    for arg in cmd.get_arguments() {
+        let id = arg.get_id().as_str();
+        if CLI_ONLY_OPTIONS.contains(&id) || SECRET_OPTIONS.contains(&id) {
+            continue;
+        }
+        let Some(raw) = matches.get_raw(id) else {
+            continue;
+        };
+        let values: Vec<String> = raw.map(|v| v.to_string_lossy().into_owned()).collect();
+        let value = match arg.get_action() {
+            ArgAction::SetTrue => toml::Value::Boolean(values.iter().any(|v| v == "true")),
+            ArgAction::Append => toml::Value::Array(values.into_iter().map(Into::into).collect()),
+            _ if values.len() == 1 => toml::Value::String(values[0].clone()),
+            _ => toml::Value::Array(values.into_iter().map(Into::into).collect()),
+        };
+        table.insert(id.to_string(), value);
+    }
+    toml::to_string(&table).context("failed to render configuration")
+}
+
+/// `--clear-cache`: remove every cached dictionary and say how many went.
+fn clear_dictionary_cache() -> Result<i32> {
+    let dir = dict_cache::default_cache_dir()
+        .ok_or_else(|| anyhow!("no cache directory: neither XDG_CACHE_HOME nor HOME is set"))?;
+    let cache = DiskCache::new(dir);
+    let removed = cache
+        .clear()
+        .with_context(|| format!("failed to clear {}", cache.dir().display()))?;
+    println!(
+        "Removed {removed} cached dictionary file(s) from {}",
+        cache.dir().display()
+    );
+    Ok(0)
 }
 
 fn prepare_schema(opts: &CliOptions) -> Result<(HashMap<String, CustomDictionary>, SchemaTree)> {
-    let custom_dicts = load_custom_dictionaries(&opts.xml_paths)?;
+    STRICT_DICTIONARIES.store(opts.strict_dict, Ordering::Relaxed);
+    dict_cache::set_allow_partial(opts.allow_partial_dict);
+    let custom_dicts = load_custom_dictionaries(&opts.xml_paths, opts.transport_xml.as_deref())?;
     ensure_valid_fix_version(opts, &custom_dicts)?;
     let schema = load_schema(opts, &custom_dicts)?;
     Ok((custom_dicts, schema))
 }
 
-fn apply_colour_preferences(opts: &CliOptions) {
-    if let Some(force_colour) = opts.colour {
-        if !force_colour {
-            disable_output_colours();
-        }
-    } else if !std::io::stdout().is_terminal() {
+/// Install the `--theme` palette (with any `FIXDECODER_COLOURS` overrides) and decide
+/// whether colours are shown at all.
+fn apply_colour_preferences(opts: &CliOptions) -> Result<()> {
+    let mut palette = opts.theme.palette();
+    if let Ok(spec) = std::env::var("FIXDECODER_COLOURS") {
+        palette = colours::apply_overrides(palette, &spec)
+            .context("invalid FIXDECODER_COLOURS setting")?;
+    }
+    colours::install_palette(palette);
+
+    let no_color = std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+    // --split-output files are not a terminal, whatever stdout is.
+    let is_terminal =
+        std::io::stdout().is_terminal() && opts.split_output.is_none() && !opts.benchmark;
+    if !colour_enabled(opts.colour, no_color, is_terminal) {
         disable_output_colours();
     }
+    Ok(())
+}
+
+/// An explicit `--colour` wins; otherwise a non-empty `NO_COLOR` or a non-terminal
+/// stdout turns colours off.
+fn colour_enabled(flag: Option<bool>, no_color: bool, is_terminal: bool) -> bool {
+    flag.unwrap_or(!no_color && is_terminal)
 }
 
 fn resolve_input_files(opts: &CliOptions) -> Vec<String> {
@@ -207,6 +784,36 @@ fn resolve_input_files(opts: &CliOptions) -> Vec<String> {
     }
 }
 
+/// How decoded messages are shown, from `--width`, `--max-value-width`, `--decode-xml`,
+/// `--error-locations`, `--md-ladder`, `--show-dict` and `--no-escape`.
+fn display_options(opts: &CliOptions) -> DisplayOptions {
+    DisplayOptions {
+        width: opts.width,
+        max_value_width: opts.max_value_width,
+        decode_xml: opts.decode_xml,
+        error_locations: opts.error_locations,
+        md_ladder: opts.md_ladder,
+        show_dict: opts.show_dict,
+        escape_delimiter: !opts.no_escape,
+        line_prefix_width: 0,
+    }
+}
+
+fn build_order_summary(opts: &CliOptions) -> OrderSummary {
+    let summary = OrderSummary::new(opts.delimiter)
+        .with_display(display_options(opts))
+        .with_keep_open(opts.summary_keep_open)
+        .with_raw_messages(opts.summary_raw)
+        .with_group(opts.summary_group)
+        .with_extra_msg_types(opts.summary_msg_types.clone())
+        .with_diff_tags(opts.summary_diff.clone().unwrap_or_default())
+        .with_max_orders(opts.summary_max_orders);
+    match &opts.summary_terminal {
+        Some(states) => summary.with_terminal_states(states.clone()),
+        None => summary,
+    }
+}
+
 fn build_context<'a>(
     obfuscator: &'a fix::Obfuscator,
     summary: &'a mut Option<OrderSummary>,
@@ -220,13 +827,62 @@ fn build_context<'a>(
         err_out,
         obfuscator,
         display_delimiter: opts.delimiter,
+        display: display_options(opts),
         summary,
+        session_summary: opts.session_summary.then(SessionSummary::new),
+        sequence_diagram: opts
+            .sequence_diagram
+            .as_ref()
+            .map(|_| SequenceDiagram::new(opts.diagram_format, opts.diagram_limit)),
+        line_timing: opts
+            .line_timestamp
+            .clone()
+            .map(|format| LineTiming::new(format, opts.max_gap)),
         fix_override,
         follow: opts.follow,
-        live_status_enabled: std::io::stdout().is_terminal(),
+        live_status_enabled: std::io::stdout().is_terminal() && !opts.benchmark,
         validation_enabled: opts.validate,
+        banner: opts.banner,
+        dictionary_watcher: opts.watch_xml.then(|| {
+            DictionaryWatcher::new(&watched_dictionary_paths(opts), reload_custom_dictionary)
+        }),
+        count_only: opts.count_only || opts.tag_usage || opts.histogram.is_some(),
+        msg_types: MsgTypeFilter::default(),
+        tag_usage: opts.tag_usage.then(TagUsage::new),
+        tag_histogram: opts
+            .histogram
+            .as_ref()
+            .map(|tags| TagHistogram::new(tags, opts.verbose)),
+        split: None,
+        benchmark: None,
+        dict_cache: DictionaryCache::default(),
         message_counts: std::collections::HashMap::new(),
         counts_dirty: false,
+        counts_layout: opts.counts_layout,
+        validation_stats: ValidationStats::default(),
+        validation_summary: opts.validate_summary.then(ValidationSummary::new),
+        clock_skew: opts.clock_skew.map(ClockSkewStats::new),
+        order_chains: opts
+            .validate_session
+            .then(|| OrderChains::new(opts.fill_tolerance)),
+        declared_versions: DeclaredVersions::default(),
+        input_range: InputRange::new(opts.seek, opts.limit, opts.progress),
+        lines: LineDecoder::new(opts.unescape, opts.join_lines),
+        stdin_format: opts.stdin_format,
+        detected_delimiter: None,
+        dedupe: opts.dedupe.clone().map(MessageDeduper::new),
+        passthrough: opts.passthrough,
+        raw_annotate: opts.raw_annotate,
+        hidden: HiddenBlocks {
+            header: opts.hide_header,
+            trailer: opts.hide_trailer,
+        },
+        extract: ExtractSinks::default(),
+        report: None,
+        replay: None,
+        source: String::new(),
+        source_is_file: false,
+        file_prefix: None,
         interrupted: decoder::prettifier::interrupt_flag(),
     }
 }
@@ -242,6 +898,21 @@ fn warn_on_override_fallback(err_out: &mut dyn Write) {
     }
 }
 
+/// Map a decode run onto the exit-code contract: IO failures win over validation
+/// failures, which are only reported when `--fail-on` says the threshold was reached,
+/// and those win over `--warn-deprecated` warnings, which `--fail-on=none` also ignores.
+fn run_exit_code(outcome: &RunOutcome, fail_on: FailOn) -> i32 {
+    if outcome.io_error {
+        EXIT_FAILURE
+    } else if fail_on.is_triggered(outcome.validation.invalid) {
+        EXIT_INVALID
+    } else if outcome.validation.warned > 0 && fail_on != FailOn::Never {
+        EXIT_WARNINGS
+    } else {
+        EXIT_CLEAN
+    }
+}
+
 fn final_exit_code(code: i32) -> i32 {
     let interrupted = decoder::prettifier::interrupt_flag().load(Ordering::Relaxed);
     if interrupted { 130 } else { code }
@@ -261,12 +932,46 @@ fn build_cli() -> Command {
                 .default_value("44")
                 .help("FIX version to use"),
         )
+        .arg(
+            Arg::new("fix-map")
+                .long("fix-map")
+                .value_name("SENDER:TARGET=KEY")
+                .action(ArgAction::Append)
+                .help("Decode one session with the given FIX version; either CompID may be * (repeatable)"),
+        )
         .arg(
             Arg::new("xml")
                 .long("xml")
                 .value_name("FILE")
                 .action(ArgAction::Append)
                 .help("Path to alternative FIX XML dictionary (repeatable)"),
+        )
+        .arg(
+            Arg::new("enum-overlay")
+                .long("enum-overlay")
+                .value_name("FILE")
+                .action(ArgAction::Append)
+                .help("Add enum values and tag names from a TOML or CSV file (repeatable)"),
+        )
+        .arg(
+            Arg::new("udf")
+                .long("udf")
+                .value_name("FILE")
+                .action(ArgAction::Append)
+                .help("Add user-defined fields from a tag,name,type[,enum,description] CSV file (repeatable)"),
+        )
+        .arg(
+            Arg::new("udf-override")
+                .long("udf-override")
+                .action(ArgAction::SetTrue)
+                .requires("udf")
+                .help("Let --udf fields replace tags the dictionary already defines"),
+        )
+        .arg(
+            Arg::new("transport-xml")
+                .long("transport-xml")
+                .value_name("FILE")
+                .help("FIXT transport dictionary supplying the header/trailer for FIX 5.0+ --xml files"),
         );
 
     cmd = add_entity_arg(
@@ -287,6 +992,61 @@ fn build_cli() -> Command {
         "TAG",
         "FIX Tag number to display (omit value to list all)",
     );
+    // Repeatable so `--histogram` can count several tags in one pass.
+    cmd = cmd.mut_arg("tag", |arg| arg.action(ArgAction::Append));
+
+    cmd = cmd
+        .arg(
+            Arg::new("enum")
+                .long("enum")
+                .value_name("VALUE")
+                .requires("tag")
+                .help("With --tag, describe VALUE (space-separated codes for multi-value fields)"),
+        )
+        .arg(
+            Arg::new("find")
+                .long("find")
+                .value_name("TEXT")
+                .help("Search field, message and component names and enum values for TEXT"),
+        );
+
+    cmd = cmd
+        .arg(
+            Arg::new("json")
+                .long("json")
+                .action(ArgAction::SetTrue)
+                .conflicts_with_all(["enum", "generate", "generate-all", "browse"])
+                .help("Write --message, --component, --tag and --info results as JSON"),
+        )
+        .arg(
+            Arg::new("pretty")
+                .long("pretty")
+                .action(ArgAction::SetTrue)
+                .requires("json")
+                .help("Indent --json output"),
+        )
+        .arg(
+            Arg::new("provenance")
+                .long("provenance")
+                .action(ArgAction::SetTrue)
+                .requires("message")
+                .conflicts_with("json")
+                .help("Show the components and groups each --message field is inherited through"),
+        )
+        .arg(
+            Arg::new("depth")
+                .long("depth")
+                .value_name("N")
+                .conflicts_with("json")
+                .help("Collapse --message and --component blocks nested deeper than N"),
+        )
+        .arg(
+            Arg::new("groups-only")
+                .long("groups-only")
+                .action(ArgAction::SetTrue)
+                .conflicts_with("json")
+                .help("Show only the repeating-group structure of --message and --component"),
+        );
 
     cmd = add_flag_args(
         cmd,
@@ -298,6 +1058,20 @@ fn build_cli() -> Command {
             ("info", "Show schema summary"),
             ("secret", "Obfuscate sensitive FIX tag values"),
             ("validate", "Validate FIX messages during decoding"),
+            ("banner", "Print a header line before each decoded message"),
+            (
+                "strict-dict",
+                "Fail when an --xml dictionary has duplicate or undefined fields",
+            ),
+            (
+                "allow-partial-dict",
+                "Load an --xml dictionary that lacks its <components> section",
+            ),
+            (
+                "no-cache",
+                "Parse dictionaries from XML instead of the on-disk cache",
+            ),
+            ("clear-cache", "Remove the cached dictionaries and exit"),
         ],
     );
 
@@ -310,11 +1084,86 @@ fn build_cli() -> Command {
             .default_missing_value("true")
             .help("Force coloured output"),
     )
+    .arg(
+        Arg::new("theme")
+            .long("theme")
+            .value_name("dark|light|mono")
+            .help("Colour theme for decoded output (default: dark)"),
+    )
     .arg(
         Arg::new("delimiter")
             .long("delimiter")
             .value_name("CHAR")
-            .help("Display delimiter between FIX fields (default: SOH)"),
+            .help(format!(
+                "Display delimiter between FIX fields (default: SOH): {DELIMITER_FORMS}"
+            )),
+    )
+    .arg(
+        Arg::new("no-escape")
+            .long("no-escape")
+            .action(ArgAction::SetTrue)
+            .help("Show a value's own copies of the --delimiter character as-is instead of escaping them as \\|"),
+    )
+    .arg(
+        Arg::new("decode-xml")
+            .long("decode-xml")
+            .action(ArgAction::SetTrue)
+            .help("Pretty-print XML carried in DATA fields such as XmlData(213)"),
+    )
+    .arg(
+        Arg::new("md-ladder")
+            .long("md-ladder")
+            .action(ArgAction::SetTrue)
+            .help("Follow MarketData W/X messages with their NoMDEntries(268) as a sorted price ladder"),
+    )
+    .arg(
+        Arg::new("show-dict")
+            .long("show-dict")
+            .action(ArgAction::SetTrue)
+            .help("Follow each decoded message with the dictionary that decoded it, how it was chosen and any fallback it needed"),
+    )
+    .arg(
+        Arg::new("prefix-file")
+            .long("prefix-file")
+            .action(ArgAction::SetTrue)
+            .help("Start each output line with a [file] tag and end with a table of messages per file"),
+    )
+    .arg(
+        Arg::new("hide-header")
+            .long("hide-header")
+            .action(ArgAction::SetTrue)
+            .help("Leave standard header fields other than MsgType(35) out of decoded messages"),
+    )
+    .arg(
+        Arg::new("hide-trailer")
+            .long("hide-trailer")
+            .action(ArgAction::SetTrue)
+            .help("Leave standard trailer fields out of decoded messages"),
+    )
+    .arg(
+        Arg::new("max-value-width")
+            .long("max-value-width")
+            .value_name("N")
+            .help("Truncate displayed field values longer than N characters"),
+    )
+    .arg(
+        Arg::new("width")
+            .long("width")
+            .value_name("N")
+            .help("Output width for separators, columns and wrapped values (default: terminal width)"),
+    )
+    .arg(
+        Arg::new("quiet")
+            .long("quiet")
+            .action(ArgAction::SetTrue)
+            .conflicts_with("verbose-warnings")
+            .help("Print no warnings on stderr"),
+    )
+    .arg(
+        Arg::new("verbose-warnings")
+            .long("verbose-warnings")
+            .action(ArgAction::SetTrue)
+            .help("Print every occurrence of a warning instead of each distinct warning once"),
     )
     .arg(
         Arg::new("version")
@@ -335,6 +1184,122 @@ fn build_cli() -> Command {
             .action(ArgAction::SetTrue)
             .help("Track order state across messages and print a summary"),
     )
+    .arg(
+        Arg::new("summary-keep-open")
+            .long("summary-keep-open")
+            .action(ArgAction::SetTrue)
+            .requires("summary")
+            .help("At end of input, print open orders in full with their raw messages"),
+    )
+    .arg(
+        Arg::new("summary-raw")
+            .long("summary-raw")
+            .action(ArgAction::SetTrue)
+            .requires("summary")
+            .help("Echo each completed order's raw FIX messages under its summary"),
+    )
+    .arg(
+        Arg::new("summary-terminal")
+            .long("summary-terminal")
+            .value_name("STATES")
+            .requires("summary")
+            .help("Comma-separated OrdStatus codes or names that close an order (default 2,3,4,7,8,9,B,C)"),
+    )
+    .arg(
+        Arg::new("summary-group")
+            .long("summary-group")
+            .value_name("BY")
+            .requires("summary")
+            .help("Finish the summary with totals per symbol or symbol-side"),
+    )
+    .arg(
+        Arg::new("summary-msgtypes")
+            .long("summary-msgtypes")
+            .value_name("TYPES")
+            .requires("summary")
+            .help("Comma-separated MsgTypes to track as well as D,F,G,8,9,BN,AB,AC,E,s"),
+    )
+    .arg(
+        Arg::new("summary-diff")
+            .long("summary-diff")
+            .action(ArgAction::SetTrue)
+            .requires("summary")
+            .help("Show the fields each cancel/replace changed in the order timeline"),
+    )
+    .arg(
+        Arg::new("summary-diff-tags")
+            .long("summary-diff-tags")
+            .value_name("TAGS")
+            .requires("summary-diff")
+            .help("Comma-separated tags --summary-diff compares (default: 38,44,40,59,64)"),
+    )
+    .arg(
+        Arg::new("summary-max-orders")
+            .long("summary-max-orders")
+            .value_name("N")
+            .requires("summary")
+            .help("Hold at most N orders, printing and dropping the oldest completed ones first"),
+    )
+    .arg(
+        Arg::new("session-summary")
+            .long("session-summary")
+            .action(ArgAction::SetTrue)
+            .help("Print logons, heartbeats, resends and sequence resets per session at the end"),
+    )
+    .arg(
+        Arg::new("sequence-diagram")
+            .long("sequence-diagram")
+            .num_args(0..=1)
+            .value_name("FILE")
+            .require_equals(true)
+            .default_missing_value("-")
+            .help("Write the messages as a sequence diagram between CompIDs to FILE, or stdout, at the end"),
+    )
+    .arg(
+        Arg::new("diagram-format")
+            .long("diagram-format")
+            .value_name("mermaid|plantuml")
+            .requires("sequence-diagram")
+            .help("Syntax for --sequence-diagram (default: mermaid)"),
+    )
+    .arg(
+        Arg::new("diagram-limit")
+            .long("diagram-limit")
+            .value_name("N")
+            .requires("sequence-diagram")
+            .help("Draw at most N messages in --sequence-diagram (default: 200)"),
+    )
+    .arg(
+        Arg::new("line-timestamp")
+            .long("line-timestamp")
+            .value_name("FORMAT")
+            .help("Read each line's timestamp prefix (iso8601, epoch_ms or a strftime format) and report message gaps per session"),
+    )
+    .arg(
+        Arg::new("max-gap")
+            .long("max-gap")
+            .value_name("SECONDS")
+            .requires("line-timestamp")
+            .help("Flag gaps between consecutive messages of a session longer than SECONDS"),
+    )
+    .arg(
+        Arg::new("replay")
+            .long("replay")
+            .num_args(0..=1)
+            .value_name("SPEED")
+            .require_equals(true)
+            .default_missing_value("1")
+            .conflicts_with_all([
+                "validate",
+                "summary",
+                "count-only",
+                "tag-usage",
+                "repair",
+                "dedupe",
+                "passthrough",
+            ])
+            .help("Write the raw messages to stdout paced by their SendingTime(52) gaps, SPEED times as fast (default 1, 0 for no pauses)"),
+    )
     .arg(
         Arg::new("follow")
             .long("follow")
@@ -342,12 +1307,397 @@ fn build_cli() -> Command {
             .action(ArgAction::SetTrue)
             .help("Stream input like tail -f"),
     )
-}
-
-/// Add a `--name[=VALUE]` argument that can be used with or without a value (defaulting to “true”).
-fn add_entity_arg(
-    cmd: Command,
-    name: &'static str,
+    .arg(
+        Arg::new("count-only")
+            .long("count-only")
+            .action(ArgAction::SetTrue)
+            .conflicts_with("validate")
+            .help("Only count MsgTypes (and track --summary) without decoding messages"),
+    )
+    .arg(
+        Arg::new("counts-wide")
+            .long("counts-wide")
+            .action(ArgAction::SetTrue)
+            .help("Add each MsgType's share of messages, bytes and average size to the counts, with a totals row"),
+    )
+    .arg(
+        Arg::new("counts-sort")
+            .long("counts-sort")
+            .value_name("count|code|bytes")
+            .help("Order the MsgType counts by code (default), most frequent or most bytes"),
+    )
+    .arg(
+        Arg::new("tag-usage")
+            .long("tag-usage")
+            .action(ArgAction::SetTrue)
+            .conflicts_with_all(["validate", "repair", "dedupe", "passthrough"])
+            .help("Count each tag's occurrences per MsgType without decoding messages, flagging tags the dictionary does not define"),
+    )
+    .arg(
+        Arg::new("histogram")
+            .long("histogram")
+            .action(ArgAction::SetTrue)
+            .requires("tag")
+            .conflicts_with_all(["validate", "repair", "dedupe", "passthrough", "enum", "json"])
+            .help("With --tag TAG (repeatable), count each value of TAG across the input files instead of describing the tag"),
+    )
+    .arg(
+        Arg::new("repair")
+            .long("repair")
+            .action(ArgAction::SetTrue)
+            .conflicts_with_all(["validate", "summary", "count-only", "follow", "extract"])
+            .help("Recompute BodyLength and CheckSum and print the corrected raw messages"),
+    )
+    .arg(
+        Arg::new("seek")
+            .long("seek")
+            .value_name("BYTES")
+            .conflicts_with("seek-line")
+            .help("Start each input file at byte offset BYTES, moved forward to the next line"),
+    )
+    .arg(
+        Arg::new("seek-line")
+            .long("seek-line")
+            .value_name("N")
+            .help("Start each input file at line N, keeping absolute line numbers"),
+    )
+    .arg(
+        Arg::new("limit")
+            .long("limit")
+            .value_name("N")
+            .help("Stop after decoding N FIX messages, or show at most N --find results"),
+    )
+    .arg(
+        Arg::new("progress")
+            .long("progress")
+            .action(ArgAction::SetTrue)
+            .help("Show bytes read and percent complete for each input file on stderr"),
+    )
+    .arg(
+        Arg::new("benchmark")
+            .long("benchmark")
+            .action(ArgAction::SetTrue)
+            .conflicts_with_all(["follow", "replay", "split-output", "repair"])
+            .help("Decode with the output discarded and report throughput and time per phase"),
+    )
+    .arg(
+        Arg::new("join-lines")
+            .long("join-lines")
+            .action(ArgAction::SetTrue)
+            .help("Rejoin FIX messages that the logger wrapped across several lines"),
+    )
+    .arg(
+        Arg::new("stdin-format")
+            .long("stdin-format")
+            .value_name("lines|nul|len32")
+            .help("How records are framed on stdin: newline-terminated lines (default), NUL-terminated records, or a 4-byte big-endian length before each record"),
+    )
+    .arg(
+        Arg::new("unescape")
+            .long("unescape")
+            .action(ArgAction::SetTrue)
+            .help("Decode \\u0001, \\x01, \\001 and ^A escapes in log lines as SOH"),
+    )
+    .arg(
+        Arg::new("dedupe")
+            .long("dedupe")
+            .action(ArgAction::SetTrue)
+            .conflicts_with_all(["validate", "summary", "count-only", "repair"])
+            .help("Suppress messages identical to the previous one in their session"),
+    )
+    .arg(
+        Arg::new("dedupe-ignore-tags")
+            .long("dedupe-ignore-tags")
+            .value_name("TAGS")
+            .requires("dedupe")
+            .help("Comma-separated tags --dedupe ignores when comparing (default: 9,10,34,52)"),
+    )
+    .arg(
+        Arg::new("only-msgtype")
+            .long("only-msgtype")
+            .value_name("TYPES")
+            .action(ArgAction::Append)
+            .conflicts_with("repair")
+            .help("Decode only messages of these MsgType codes or names, e.g. 8,ExecutionReport (repeatable)"),
+    )
+    .arg(
+        Arg::new("exclude-msgtype")
+            .long("exclude-msgtype")
+            .value_name("TYPES")
+            .action(ArgAction::Append)
+            .conflicts_with("repair")
+            .help("Skip messages of these MsgType codes or names, e.g. 0,Heartbeat (repeatable)"),
+    )
+    .group(
+        ArgGroup::new("msgtype-filter")
+            .args(["only-msgtype", "exclude-msgtype"])
+            .multiple(true),
+    )
+    .arg(
+        Arg::new("count-filtered")
+            .long("count-filtered")
+            .action(ArgAction::SetTrue)
+            .requires("msgtype-filter")
+            .help("Still count messages --only-msgtype or --exclude-msgtype drop in the MsgType totals"),
+    )
+    .arg(
+        Arg::new("passthrough")
+            .long("passthrough")
+            .action(ArgAction::SetTrue)
+            .conflicts_with_all([
+                "validate",
+                "summary",
+                "count-only",
+                "repair",
+                "secret",
+            ])
+            .help("Echo each input line byte for byte, followed by its decoded messages"),
+    )
+    .arg(
+        Arg::new("watch-xml")
+            .long("watch-xml")
+            .action(ArgAction::SetTrue)
+            .requires("follow")
+            .requires("xml")
+            .help("Reload --xml dictionaries when they change (requires --follow)"),
+    )
+    .arg(
+        Arg::new("generate")
+            .long("generate")
+            .action(ArgAction::SetTrue)
+            .requires("message")
+            .help("Print a skeleton of the --message with its required fields"),
+    )
+    .arg(
+        Arg::new("generate-all")
+            .long("generate-all")
+            .action(ArgAction::SetTrue)
+            .requires("message")
+            .help("Like --generate but include optional fields too"),
+    )
+    .arg(
+        Arg::new("export-xml")
+            .long("export-xml")
+            .num_args(1..=2)
+            .value_names(["KEY", "FILE"])
+            .help("Write the merged dictionary for KEY (e.g. FIX44) as XML to FILE or stdout"),
+    )
+    .arg(
+        Arg::new("browse")
+            .long("browse")
+            .action(ArgAction::SetTrue)
+            .help("Browse the dictionary's messages, structure and enums in an interactive terminal view"),
+    )
+    .arg(
+        Arg::new("fail-on")
+            .long("fail-on")
+            .value_name("none|any|N")
+            .requires("validate")
+            .help("Exit with code 2 when --validate finds invalid messages (default: any)"),
+    )
+    .arg(
+        Arg::new("error-locations")
+            .long("error-locations")
+            .action(ArgAction::SetTrue)
+            .requires("validate")
+            .help("Append the field number and byte range to each --validate error"),
+    )
+    .arg(
+        Arg::new("raw-annotate")
+            .long("raw-annotate")
+            .action(ArgAction::SetTrue)
+            .requires("validate")
+            .help("Show invalid messages raw, with carets under each offending value and a numbered legend"),
+    )
+    .arg(
+        Arg::new("validate-summary")
+            .long("validate-summary")
+            .action(ArgAction::SetTrue)
+            .requires("validate")
+            .help("Finish with a table of --validate errors by category and the MsgTypes affected"),
+    )
+    .arg(
+        Arg::new("max-decimals")
+            .long("max-decimals")
+            .value_name("N")
+            .requires("validate")
+            .help("Flag price, quantity and amount values with more than N decimal places"),
+    )
+    .arg(
+        Arg::new("check-clock-skew")
+            .long("check-clock-skew")
+            .value_name("SECONDS")
+            .requires("validate")
+            .help("Flag messages whose SendingTime(52) and TransactTime(60) differ by more than SECONDS, with max and p99 skew per session"),
+    )
+    .arg(
+        Arg::new("validate-session")
+            .long("validate-session")
+            .action(ArgAction::SetTrue)
+            .requires("validate")
+            .conflicts_with("split-output")
+            .help("Check each order's cancel/replace chain across messages: unknown OrigClOrdID(41), overfills, OrdStatus(39) regressions and mismatched acks"),
+    )
+    .arg(
+        Arg::new("fill-tolerance")
+            .long("fill-tolerance")
+            .value_name("QTY")
+            .requires("validate-session")
+            .help("How far CumQty(14) may exceed OrderQty(38) before --validate-session flags an overfill (default 0)"),
+    )
+    .arg(
+        Arg::new("warn-deprecated")
+            .long("warn-deprecated")
+            .action(ArgAction::SetTrue)
+            .requires("validate")
+            .help("Warn about fields and enum values deprecated in the message's FIX version, exiting with code 3 when only warnings are found"),
+    )
+    .arg(
+        Arg::new("secret-stable")
+            .long("secret-stable")
+            .action(ArgAction::SetTrue)
+            .requires("secret")
+            .help("Derive --secret aliases from a keyed hash so they match across files"),
+    )
+    .arg(
+        Arg::new("secret-key")
+            .long("secret-key")
+            .value_name("KEY")
+            .requires("secret")
+            .help("Key for stable --secret aliases so they also match across runs"),
+    )
+    .arg(
+        Arg::new("secret-format")
+            .long("secret-format")
+            .action(ArgAction::SetTrue)
+            .requires("secret")
+            .help("Mask --secret values digit-for-digit and letter-for-letter, keeping their format"),
+    )
+    .arg(
+        Arg::new("secret-map")
+            .long("secret-map")
+            .value_name("FILE")
+            .requires("secret")
+            .help("Write the original-to-alias mapping for --secret to FILE"),
+    )
+    .arg(
+        Arg::new("extract")
+            .long("extract")
+            .value_name("FILE")
+            .help("Append each raw FIX message to FILE (\"-\" writes them to stdout instead of the decoded output)"),
+    )
+    .arg(
+        Arg::new("extract-invalid")
+            .long("extract-invalid")
+            .value_name("FILE")
+            .requires("validate")
+            .help("Append each raw FIX message that fails --validate to FILE"),
+    )
+    .arg(
+        Arg::new("split-output")
+            .long("split-output")
+            .value_name("DIR")
+            .conflicts_with_all([
+                "follow",
+                "count-only",
+                "tag-usage",
+                "histogram",
+                "replay",
+                "passthrough",
+                "dedupe",
+                "raw-annotate",
+                "repair",
+            ])
+            .help("Write each decoded message to a file of its own in DIR instead of stdout"),
+    )
+    .arg(
+        Arg::new("split-mode")
+            .long("split-mode")
+            .value_name("per-message|per-order")
+            .requires("split-output")
+            .help("One file per message (default), or per order with its summary block (requires --summary)"),
+    )
+    .arg(
+        Arg::new("report")
+            .long("report")
+            .value_name("FILE")
+            .requires("validate")
+            .help("Write every --validate finding to FILE as JSON Lines, or SARIF for a .sarif file"),
+    )
+    .arg(
+        Arg::new("report-format")
+            .long("report-format")
+            .value_name("sarif|jsonl")
+            .requires("report")
+            .help("Format for --report, overriding the one chosen by its file name"),
+    )
+    .arg(
+        Arg::new("rules")
+            .long("rules")
+            .value_name("list|-RULE,...")
+            .action(ArgAction::Append)
+            .allow_hyphen_values(true)
+            .help("List the --validate business rules, or disable some with -NAME"),
+    )
+    .arg(
+        Arg::new("config")
+            .long("config")
+            .value_name("FILE")
+            .help("Read default options from FILE instead of ~/.config/fixdecoder/config.toml"),
+    )
+    .arg(
+        Arg::new("dump-config")
+            .long("dump-config")
+            .action(ArgAction::SetTrue)
+            .help("Print the effective options, merged from the config file and command line, and exit"),
+    )
+    .arg(
+        Arg::new("generate-completions")
+            .long("generate-completions")
+            .value_name("SHELL")
+            .value_parser(["bash", "zsh", "fish"])
+            .hide(true)
+            .help("Print a shell completion script to stdout"),
+    )
+    .arg(
+        Arg::new("generate-manpage")
+            .long("generate-manpage")
+            .action(ArgAction::SetTrue)
+            .hide(true)
+            .help("Print a roff man page to stdout"),
+    )
+}
+
+/// Handle `--generate-completions` and `--generate-manpage`, both driven from
+/// [`build_cli`] so they never drift from the real options.  Returns true when
+/// something was written, in which case nothing else (not even the version line)
+/// may follow on stdout.
+fn write_shell_integration(matches: &ArgMatches) -> Result<bool> {
+    let mut buf = Vec::new();
+    if let Some(shell) = matches.get_one::<String>("generate-completions") {
+        let shell = match shell.as_str() {
+            "zsh" => Shell::Zsh,
+            "fish" => Shell::Fish,
+            _ => Shell::Bash,
+        };
+        clap_complete::generate(shell, &mut build_cli(), "fixdecoder", &mut buf);
+    } else if matches.get_flag("generate-manpage") {
+        // The man page header adds the program name itself, so use the bare version.
+        let cmd = build_cli().version(env!("CARGO_PKG_VERSION"));
+        clap_mangen::Man::new(cmd).render(&mut buf)?;
+    } else {
+        return Ok(false);
+    }
+    io::stdout()
+        .write_all(&buf)
+        .context("failed to write to stdout")?;
+    Ok(true)
+}
+
+/// Add a `--name[=VALUE]` argument that can be used with or without a value (defaulting to “true”).
+fn add_entity_arg(
+    cmd: Command,
+    name: &'static str,
     value_name: &'static str,
     help: &'static str,
 ) -> Command {
@@ -382,29 +1732,149 @@ struct CliOptions {
     fix_version: String,
     fix_from_user: bool,
     xml_paths: Vec<String>,
+    transport_xml: Option<String>,
+    enum_overlays: Vec<String>,
+    /// `--udf` files and whether `--udf-override` lets them replace dictionary tags.
+    udf_files: Vec<String>,
+    udf_override: bool,
+    fix_map: Vec<FixMapRule>,
     message_flag: bool,
     message_value: Option<String>,
     component_flag: bool,
     component_value: Option<String>,
     tag_flag: bool,
+    find: Option<String>,
     tag_value: Option<String>,
+    enum_value: Option<String>,
+    json: bool,
+    pretty: bool,
     column: bool,
     verbose: bool,
+    strict_dict: bool,
+    allow_partial_dict: bool,
+    no_cache: bool,
+    clear_cache: bool,
+    provenance: bool,
+    outline: Outline,
     include_header: bool,
     include_trailer: bool,
     info: bool,
+    export_xml: Option<(String, Option<String>)>,
+    browse: bool,
     secret: bool,
+    secret_stable: bool,
+    secret_format: bool,
+    secret_key: Option<String>,
+    secret_map: Option<String>,
+    extract: Option<String>,
+    extract_invalid: Option<String>,
+    /// `--split-output` and `--split-mode`.
+    split_output: Option<(PathBuf, SplitMode)>,
+    /// `--report`, with the format from `--report-format` or the file name.
+    report: Option<(String, ReportFormat)>,
     validate: bool,
+    error_locations: bool,
+    validate_summary: bool,
+    raw_annotate: bool,
+    max_decimals: Option<usize>,
+    clock_skew: Option<TimeDelta>,
+    validate_session: bool,
+    fill_tolerance: f64,
+    warn_deprecated: bool,
+    seek: Option<SeekTo>,
+    limit: Option<usize>,
+    /// `--replay`: the speed-up applied to the logged gaps, 0 for none.
+    replay: Option<f64>,
+    progress: bool,
+    benchmark: bool,
+    banner: bool,
+    warning_mode: WarningMode,
     colour: Option<bool>,
+    theme: Theme,
     show_version: bool,
     summary: bool,
+    summary_keep_open: bool,
+    summary_raw: bool,
+    session_summary: bool,
+    /// `--sequence-diagram`: where to write it, `-` for stdout.
+    sequence_diagram: Option<String>,
+    diagram_format: DiagramFormat,
+    diagram_limit: usize,
+    line_timestamp: Option<LineTimestampFormat>,
+    max_gap: Option<TimeDelta>,
+    summary_terminal: Option<Vec<String>>,
+    summary_group: Option<SummaryGroup>,
+    summary_msg_types: Vec<String>,
+    /// Tags compared by `--summary-diff`, when it is given.
+    summary_diff: Option<Vec<u32>>,
+    summary_max_orders: Option<usize>,
+    list_rules: bool,
+    disabled_rules: HashSet<&'static str>,
     #[allow(dead_code)]
     follow: bool,
+    watch_xml: bool,
+    count_only: bool,
+    counts_layout: CountsLayout,
+    tag_usage: bool,
+    /// `--histogram`: the `--tag` tags whose values are counted.
+    histogram: Option<Vec<u32>>,
+    repair: bool,
+    join_lines: bool,
+    unescape: bool,
+    stdin_format: RecordFormat,
+    dedupe: Option<Vec<u32>>,
+    /// `--only-msgtype` and `--exclude-msgtype` as given: codes or message names.
+    only_msg_types: Vec<String>,
+    exclude_msg_types: Vec<String>,
+    count_filtered: bool,
+    passthrough: bool,
+    decode_xml: bool,
+    md_ladder: bool,
+    show_dict: bool,
+    prefix_file: bool,
+    hide_header: bool,
+    hide_trailer: bool,
+    max_value_width: Option<usize>,
+    width: Option<usize>,
     files: Vec<String>,
     delimiter: char,
+    no_escape: bool,
+    fail_on: FailOn,
+    generate: bool,
+    generate_all: bool,
+}
+
+/// When a `--validate` run should exit with [`EXIT_INVALID`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FailOn {
+    Never,
+    Any,
+    AtLeast(usize),
+}
+
+impl FailOn {
+    fn is_triggered(self, invalid: usize) -> bool {
+        match self {
+            FailOn::Never => false,
+            FailOn::Any => invalid > 0,
+            FailOn::AtLeast(threshold) => invalid >= threshold,
+        }
+    }
 }
 
 impl CliOptions {
+    /// `--extract -` (or `--extract-invalid -`) sends raw messages to stdout, so the
+    /// decoded output and version line must stay out of it.
+    fn extracts_to_stdout(&self) -> bool {
+        self.extract.as_deref() == Some("-") || self.extract_invalid.as_deref() == Some("-")
+    }
+
+    /// `--export-xml KEY` without a file writes the XML to stdout, where the
+    /// version line would make it malformed.
+    fn exports_xml_to_stdout(&self) -> bool {
+        matches!(self.export_xml, Some((_, None)))
+    }
+
     /// Translate clap’s `ArgMatches` into our strongly typed `CliOptions`.
     /// The function centralises validation so the rest of the code can assume
     /// sane defaults and bail out early when a user supplies nonsense.
@@ -417,10 +1887,13 @@ impl CliOptions {
             .map(|vals| vals.map(|v| v.to_string()).collect())
             .unwrap_or_default();
 
+        let (list_rules, disabled_rules) = parse_rules(matches.get_many::<String>("rules"))?;
+
         let files: Vec<String> = matches
             .get_many::<String>("files")
             .map(|vals| vals.map(|v| v.to_string()).collect())
             .unwrap_or_default();
+        let histogram = parse_histogram_tags(matches)?;
         Ok(Self {
             fix_version: matches
                 .get_one::<String>("fix")
@@ -428,25 +1901,166 @@ impl CliOptions {
                 .unwrap_or_else(|| "44".to_string()),
             fix_from_user,
             xml_paths,
+            transport_xml: matches.get_one::<String>("transport-xml").cloned(),
+            enum_overlays: matches
+                .get_many::<String>("enum-overlay")
+                .map(|vals| vals.cloned().collect())
+                .unwrap_or_default(),
+            udf_files: matches
+                .get_many::<String>("udf")
+                .map(|vals| vals.cloned().collect())
+                .unwrap_or_default(),
+            udf_override: matches.get_flag("udf-override"),
+            fix_map: matches
+                .get_many::<String>("fix-map")
+                .into_iter()
+                .flatten()
+                .map(|spec| FixMapRule::parse(spec))
+                .collect::<Result<_>>()?,
             message_flag: matches.contains_id("message"),
             message_value: extract_optional_arg(matches, "message")?,
             component_flag: matches.contains_id("component"),
             component_value: extract_optional_arg(matches, "component")?,
-            tag_flag: matches.contains_id("tag"),
+            tag_flag: matches.contains_id("tag") && histogram.is_none(),
+            find: matches.get_one::<String>("find").cloned(),
             tag_value: extract_optional_arg(matches, "tag")?,
+            enum_value: matches.get_one::<String>("enum").cloned(),
+            json: matches.get_flag("json"),
+            pretty: matches.get_flag("pretty"),
             column: matches.get_flag("column"),
             verbose: matches.get_flag("verbose"),
+            strict_dict: matches.get_flag("strict-dict"),
+            allow_partial_dict: matches.get_flag("allow-partial-dict"),
+            no_cache: matches.get_flag("no-cache"),
+            clear_cache: matches.get_flag("clear-cache"),
+            provenance: matches.get_flag("provenance"),
+            outline: Outline {
+                depth: parse_depth(matches)?,
+                groups_only: matches.get_flag("groups-only"),
+            },
             include_header: matches.get_flag("header"),
             include_trailer: matches.get_flag("trailer"),
             info: matches.get_flag("info"),
+            export_xml: matches.get_many::<String>("export-xml").map(|mut values| {
+                let key = values.next().cloned().unwrap_or_default();
+                (key, values.next().cloned())
+            }),
+            browse: matches.get_flag("browse"),
             secret: matches.get_flag("secret"),
+            secret_stable: matches.get_flag("secret-stable"),
+            secret_format: matches.get_flag("secret-format"),
+            secret_key: matches.get_one::<String>("secret-key").cloned(),
+            secret_map: matches.get_one::<String>("secret-map").cloned(),
+            extract: matches.get_one::<String>("extract").cloned(),
+            extract_invalid: matches.get_one::<String>("extract-invalid").cloned(),
+            split_output: parse_split_output(matches)?,
+            report: parse_report(matches)?,
             validate: matches.get_flag("validate"),
+            error_locations: matches.get_flag("error-locations"),
+            validate_summary: matches.get_flag("validate-summary"),
+            raw_annotate: matches.get_flag("raw-annotate"),
+            max_decimals: parse_max_decimals(matches.get_one::<String>("max-decimals"))?,
+            clock_skew: parse_seconds(matches, "check-clock-skew")?,
+            validate_session: matches.get_flag("validate-session"),
+            fill_tolerance: parse_fill_tolerance(matches)?,
+            warn_deprecated: matches.get_flag("warn-deprecated"),
+            seek: parse_seek(matches)?,
+            limit: parse_positive(matches, "limit")?,
+            replay: parse_speed(matches)?,
+            progress: matches.get_flag("progress"),
+            benchmark: matches.get_flag("benchmark"),
+            banner: matches.get_flag("banner"),
+            warning_mode: if matches.get_flag("quiet") {
+                WarningMode::Quiet
+            } else if matches.get_flag("verbose-warnings") {
+                WarningMode::Every
+            } else {
+                WarningMode::Once
+            },
             colour: parse_colour(matches.get_one::<String>("colour"))?,
+            theme: parse_theme(matches.get_one::<String>("theme"))?,
             show_version: matches.get_flag("version"),
             summary: matches.get_flag("summary"),
+            summary_keep_open: matches.get_flag("summary-keep-open"),
+            summary_raw: matches.get_flag("summary-raw"),
+            session_summary: matches.get_flag("session-summary"),
+            sequence_diagram: matches.get_one::<String>("sequence-diagram").cloned(),
+            diagram_format: parse_diagram_format(matches.get_one::<String>("diagram-format"))?,
+            diagram_limit: parse_positive(matches, "diagram-limit")?
+                .unwrap_or(sequence_diagram::DEFAULT_LIMIT),
+            line_timestamp: matches
+                .get_one::<String>("line-timestamp")
+                .map(|format| LineTimestampFormat::parse(format))
+                .transpose()?,
+            max_gap: parse_seconds(matches, "max-gap")?,
+            list_rules,
+            disabled_rules,
+            summary_terminal: parse_summary_terminal(
+                matches.get_one::<String>("summary-terminal"),
+            )?,
+            summary_group: parse_summary_group(matches.get_one::<String>("summary-group"))?,
+            summary_msg_types: matches
+                .get_one::<String>("summary-msgtypes")
+                .map(|list| {
+                    list.split(',')
+                        .map(str::trim)
+                        .filter(|mt| !mt.is_empty())
+                        .map(str::to_string)
+                        .collect()
+                })
+                .unwrap_or_default(),
+            summary_diff: matches
+                .get_flag("summary-diff")
+                .then(|| {
+                    parse_tag_list(
+                        matches.get_one::<String>("summary-diff-tags"),
+                        "summary-diff-tags",
+                        DEFAULT_DIFF_TAGS,
+                    )
+                })
+                .transpose()?,
+            summary_max_orders: parse_positive(matches, "summary-max-orders")?,
             follow: matches.get_flag("follow"),
+            watch_xml: matches.get_flag("watch-xml"),
+            count_only: matches.get_flag("count-only"),
+            counts_layout: CountsLayout {
+                wide: matches.get_flag("counts-wide"),
+                sort: parse_counts_sort(matches.get_one::<String>("counts-sort"))?,
+            },
+            tag_usage: matches.get_flag("tag-usage"),
+            histogram,
+            repair: matches.get_flag("repair"),
+            join_lines: matches.get_flag("join-lines"),
+            unescape: matches.get_flag("unescape"),
+            stdin_format: parse_stdin_format(matches.get_one::<String>("stdin-format"))?,
+            dedupe: matches
+                .get_flag("dedupe")
+                .then(|| {
+                    parse_tag_list(
+                        matches.get_one::<String>("dedupe-ignore-tags"),
+                        "dedupe-ignore-tags",
+                        &DEFAULT_IGNORE_TAGS,
+                    )
+                })
+                .transpose()?,
+            only_msg_types: split_list_values(matches.get_many::<String>("only-msgtype")),
+            exclude_msg_types: split_list_values(matches.get_many::<String>("exclude-msgtype")),
+            count_filtered: matches.get_flag("count-filtered"),
+            passthrough: matches.get_flag("passthrough"),
+            decode_xml: matches.get_flag("decode-xml"),
+            md_ladder: matches.get_flag("md-ladder"),
+            show_dict: matches.get_flag("show-dict"),
+            prefix_file: matches.get_flag("prefix-file"),
+            hide_header: matches.get_flag("hide-header"),
+            hide_trailer: matches.get_flag("hide-trailer"),
+            max_value_width: parse_positive(matches, "max-value-width")?,
+            width: parse_positive(matches, "width")?,
             files,
             delimiter: parse_delimiter(matches.get_one::<String>("delimiter"))?,
+            no_escape: matches.get_flag("no-escape"),
+            fail_on: parse_fail_on(matches.get_one::<String>("fail-on"))?,
+            generate: matches.get_flag("generate"),
+            generate_all: matches.get_flag("generate-all"),
         })
     }
 }
@@ -486,81 +2100,587 @@ fn parse_colour(value: Option<&String>) -> Result<Option<bool>> {
     }
 }
 
-/// Load all custom dictionary files specified via `--xml`, registering them and
-/// returning the key-to-dictionary map. Emits warnings on overrides.
-fn load_custom_dictionaries(paths: &[String]) -> Result<HashMap<String, CustomDictionary>> {
+/// Load all custom dictionary files specified via `--xml` (plus an optional
+/// `--transport-xml`), registering them and returning the key-to-dictionary map.
+/// Emits warnings on overrides.
+fn load_custom_dictionaries(
+    paths: &[String],
+    transport: Option<&str>,
+) -> Result<HashMap<String, CustomDictionary>> {
     let mut dicts = HashMap::new();
     let builtin_keys = built_in_fix_keys();
-    for path in paths {
-        let xml_data =
-            fs::read_to_string(path).with_context(|| format!("failed to read {path}"))?;
-        let mut dict = FixDictionary::from_xml(&xml_data)
-            .with_context(|| format!("failed to parse FIX XML from {path}"))?;
-        let key = dictionary_key(&dict);
-        ensure_session_components(&key, &mut dict);
-        register_fix_dictionary(&key, &dict);
-        tag_lookup::clear_override_cache_for(&key);
+    for (path, key, dict) in prepare_custom_dictionaries(paths, transport)? {
+        register_parsed_dictionary(&key, &dict, &path);
         if let Some(existing) = dicts.insert(
             key.clone(),
             CustomDictionary {
                 dict,
-                path: path.to_string(),
+                path: path.clone(),
             },
         ) {
-            eprintln!(
-                "warning: custom dictionary for {key} from {} replaced by {}\n",
-                existing.path, path
+            decoder::warnings::warn(
+                "custom-dictionary-replaced",
+                &key,
+                &format!(
+                    "warning: custom dictionary for {key} from {} replaced by {}",
+                    existing.path, path
+                ),
             );
         } else if builtin_keys.contains(&key) {
-            eprintln!(
-                "warning: custom dictionary for {key} overrides embedded dictionary using {}\n",
-                path
+            decoder::warnings::warn(
+                "custom-dictionary-override",
+                &key,
+                &format!(
+                    "warning: custom dictionary for {key} overrides embedded dictionary using {path}"
+                ),
             );
         }
     }
-    Ok(dicts)
+    let mut layer = SESSION_LAYER.write().expect("session layer lock poisoned");
+    layer.transport = dicts
+        .get(SESSION_DICTIONARY_KEY)
+        .map(|session| session.dict.clone());
+    layer.dependents = dicts
+        .iter()
+        .filter(|(key, _)| requires_session_components(key))
+        .map(|(_, custom)| custom.path.clone())
+        .collect();
+    drop(layer);
+    Ok(dicts)
+}
+
+/// Parse every custom dictionary and order them so FIXT transport dictionaries are
+/// registered first (an explicit `--transport-xml` last among those, so it wins).
+/// FIX 5.0+ application dictionaries then take their header/trailer from the
+/// user-supplied transport rather than the embedded FIXT11.
+fn prepare_custom_dictionaries(
+    paths: &[String],
+    transport: Option<&str>,
+) -> Result<Vec<(String, String, FixDictionary)>> {
+    let mut parsed = Vec::new();
+    for path in paths {
+        // One unreadable --xml file should not cost the run the others.
+        let dict = match dict_cache::parse_file(path) {
+            Ok(dict) => dict,
+            Err(err) => {
+                eprintln!("error: {err:#}; continuing without --xml {path}");
+                continue;
+            }
+        };
+        let key = check_custom_dictionary(path, &dict)?;
+        parsed.push((path.clone(), key, dict));
+    }
+    if let Some(path) = transport {
+        let (key, dict) = parse_custom_dictionary(path)?;
+        if key != SESSION_DICTIONARY_KEY {
+            bail!("--transport-xml {path} defines {key}, expected {SESSION_DICTIONARY_KEY}");
+        }
+        let insert_at = parsed
+            .iter()
+            .rposition(|(_, key, _)| key == SESSION_DICTIONARY_KEY)
+            .map_or(0, |idx| idx + 1);
+        parsed.insert(insert_at, (path.to_string(), key, dict));
+    }
+    parsed.sort_by_key(|(_, key, _)| key != SESSION_DICTIONARY_KEY);
+
+    let session = parsed
+        .iter()
+        .rev()
+        .find(|(_, key, _)| key == SESSION_DICTIONARY_KEY)
+        .map(|(_, _, dict)| dict.clone());
+    for (path, key, dict) in &mut parsed {
+        if session.is_none()
+            && requires_session_components(key)
+            && !component_def_has_entries(&dict.header)
+        {
+            decoder::warnings::warn(
+                "session-layer",
+                key,
+                &format!(
+                    "warning: custom dictionary {key} from {path} has no header and no --transport-xml was given; using the embedded FIXT11 session layer"
+                ),
+            );
+        }
+        ensure_session_components(key, dict, session.as_ref());
+    }
+    Ok(parsed)
+}
+
+/// Parse one custom dictionary, printing its consistency warnings, or failing on
+/// them under `--strict-dict`.
+fn parse_custom_dictionary(path: &str) -> Result<(String, FixDictionary)> {
+    let dict = dict_cache::parse_file(path)?;
+    let key = check_custom_dictionary(path, &dict)?;
+    Ok((key, dict))
+}
+
+/// Print the consistency warnings of a parsed custom dictionary, or fail on them under
+/// `--strict-dict`, and return the key it registers under.
+fn check_custom_dictionary(path: &str, dict: &FixDictionary) -> Result<String> {
+    let warnings = dict.warnings();
+    if !warnings.is_empty() && STRICT_DICTIONARIES.load(Ordering::Relaxed) {
+        let details: Vec<String> = warnings.iter().map(|w| format!("  {w}")).collect();
+        bail!(
+            "{path} has {} dictionary warning(s) and --strict-dict is set:\n{}",
+            warnings.len(),
+            details.join("\n")
+        );
+    }
+    for warning in &warnings {
+        let message = format!("warning: {path}: {warning}");
+        decoder::warnings::warn("dictionary", &message, &message);
+    }
+    if dict.components.items.is_empty() && references_components(dict) {
+        decoder::warnings::warn(
+            "no-components",
+            path,
+            &format!(
+                "warning: {path}: no components were read; fields they would add to messages are unknown"
+            ),
+        );
+    }
+    Ok(dictionary_key(dict))
+}
+
+/// Register a dictionary parsed from `path` for tag lookup, dropping any stale
+/// override combos.
+fn register_parsed_dictionary(key: &str, dict: &FixDictionary, path: &str) {
+    register_fix_dictionary(key, dict, path);
+    tag_lookup::clear_override_cache_for(key);
+}
+
+/// Reload callback used by `--watch-xml`; a parse failure leaves the previously
+/// registered dictionary in place.  FIX 5.0+ files take the current transport
+/// dictionary's header and trailer, and a reloaded transport is merged again into
+/// every FIX 5.0+ `--xml` file that took them from the old one.
+fn reload_custom_dictionary(path: &str) -> Result<String> {
+    let (key, dict) = parse_custom_dictionary(path)?;
+    if key != SESSION_DICTIONARY_KEY {
+        register_application_dictionary(&key, dict, path);
+        return Ok(key);
+    }
+
+    register_parsed_dictionary(&key, &dict, path);
+    let dependents = {
+        let mut layer = SESSION_LAYER.write().expect("session layer lock poisoned");
+        layer.transport = Some(dict);
+        layer.dependents.clone()
+    };
+    for dependent in dependents {
+        match parse_custom_dictionary(&dependent) {
+            Ok((key, dict)) if key != SESSION_DICTIONARY_KEY => {
+                register_application_dictionary(&key, dict, &dependent)
+            }
+            Ok(_) => {}
+            Err(err) => decoder::warnings::warn(
+                "session-layer-reload",
+                &dependent,
+                &format!("warning: {err:#}; keeping previous dictionary for {dependent}"),
+            ),
+        }
+    }
+    Ok(key)
+}
+
+/// Register an application dictionary with the current transport's header and
+/// trailer merged in where it needs them.
+fn register_application_dictionary(key: &str, mut dict: FixDictionary, path: &str) {
+    let layer = SESSION_LAYER.read().expect("session layer lock poisoned");
+    ensure_session_components(key, &mut dict, layer.transport.as_ref());
+    register_parsed_dictionary(key, &dict, path);
+}
+
+/// Dictionary files polled by `--watch-xml`, including any `--transport-xml`.
+fn watched_dictionary_paths(opts: &CliOptions) -> Vec<String> {
+    opts.transport_xml
+        .iter()
+        .chain(opts.xml_paths.iter())
+        .cloned()
+        .collect()
+}
+
+/// Load an embedded FIX dictionary by version string (e.g. "44"), falling back to the
+/// default embedded dictionary when that version was not compiled in.
+fn load_embedded_dictionary(fix_version: &str) -> Result<FixDictionary> {
+    let xml_id = normalise_fix_key(fix_version)
+        .as_deref()
+        .and_then(key_to_xml_id)
+        .unwrap_or_else(fix::default_xml_id);
+    let xml_data = fix::choose_embedded_xml(xml_id)
+        .ok_or_else(|| anyhow!("no embedded dictionary for {fix_version}"))?;
+    dict_cache::parse_embedded(xml_id, xml_data).with_context(|| "failed to parse FIX XML")
+}
+
+/// Load an embedded FIX dictionary by canonical key (e.g. "FIX44").
+fn load_embedded_dictionary_for_key(key: &str) -> Result<FixDictionary> {
+    let xml_id = key_to_xml_id(key).ok_or_else(|| anyhow!("no embedded dictionary for {key}"))?;
+    let xml_data = fix::choose_embedded_xml(xml_id)
+        .ok_or_else(|| anyhow!("no embedded dictionary for {key}"))?;
+    dict_cache::parse_embedded(xml_id, xml_data)
+        .with_context(|| format!("failed to parse embedded FIX XML for {key}"))
+}
+
+/// Parse `--theme`, defaulting to the dark palette.
+fn parse_theme(value: Option<&String>) -> Result<Theme> {
+    match value {
+        None => Ok(Theme::default()),
+        Some(v) => Theme::parse(v).ok_or_else(|| {
+            print_usage();
+            anyhow!("invalid value for --theme: {v} (expected dark, light or mono)")
+        }),
+    }
+}
+
+/// Parse `--report=FILE` and `--report-format`, which defaults to SARIF for a `.sarif`
+/// file and JSON Lines otherwise.
+fn parse_report(matches: &ArgMatches) -> Result<Option<(String, ReportFormat)>> {
+    let Some(path) = matches.get_one::<String>("report") else {
+        return Ok(None);
+    };
+    let format = match matches.get_one::<String>("report-format") {
+        None => ReportFormat::for_path(path),
+        Some(value) => ReportFormat::parse(value).ok_or_else(|| {
+            print_usage();
+            anyhow!("invalid value for --report-format: {value} (expected sarif or jsonl)")
+        })?,
+    };
+    Ok(Some((path.clone(), format)))
+}
+
+/// Parse `--diagram-format`, where Mermaid is the default.
+fn parse_diagram_format(value: Option<&String>) -> Result<DiagramFormat> {
+    match value {
+        None => Ok(DiagramFormat::Mermaid),
+        Some(v) => DiagramFormat::parse(v).ok_or_else(|| {
+            print_usage();
+            anyhow!("invalid value for --diagram-format: {v} (expected mermaid or plantuml)")
+        }),
+    }
+}
+
+/// Parse `--split-output` with its `--split-mode`, one file per message by default.
+/// Per-order files need `--summary` to key the orders, holding every order to the end.
+fn parse_split_output(matches: &ArgMatches) -> Result<Option<(PathBuf, SplitMode)>> {
+    let Some(dir) = matches.get_one::<String>("split-output") else {
+        return Ok(None);
+    };
+    let mode = match matches.get_one::<String>("split-mode") {
+        None => SplitMode::PerMessage,
+        Some(v) => SplitMode::parse(v).ok_or_else(|| {
+            print_usage();
+            anyhow!("invalid value for --split-mode: {v} (expected per-message or per-order)")
+        })?,
+    };
+    if mode == SplitMode::PerOrder && !matches.get_flag("summary") {
+        bail!("--split-mode=per-order requires --summary");
+    }
+    if mode == SplitMode::PerOrder && matches.contains_id("summary-max-orders") {
+        bail!("--split-mode=per-order cannot be used with --summary-max-orders");
+    }
+    Ok(Some((PathBuf::from(dir), mode)))
+}
+
+/// Parse `--stdin-format`, where lines are the default.
+fn parse_stdin_format(value: Option<&String>) -> Result<RecordFormat> {
+    match value {
+        None => Ok(RecordFormat::Lines),
+        Some(v) => RecordFormat::parse(v).ok_or_else(|| {
+            print_usage();
+            anyhow!("invalid value for --stdin-format: {v} (expected lines, nul or len32)")
+        }),
+    }
+}
+
+/// Parse `--counts-sort`, where ordering by MsgType code is the default.
+fn parse_counts_sort(value: Option<&String>) -> Result<CountsSort> {
+    match value {
+        None => Ok(CountsSort::Code),
+        Some(v) => CountsSort::parse(v).ok_or_else(|| {
+            print_usage();
+            anyhow!("invalid value for --counts-sort: {v} (expected count, code or bytes)")
+        }),
+    }
+}
+
+/// Parse `--rules` values: `list` asks for the rule table, `-NAME` disables a rule
+/// and `NAME`/`+NAME` keeps it on.  Values may be repeated or comma-separated.
+fn parse_rules<'a>(
+    values: Option<impl Iterator<Item = &'a String>>,
+) -> Result<(bool, HashSet<&'static str>)> {
+    let mut list = false;
+    let mut disabled = HashSet::new();
+    for item in values
+        .into_iter()
+        .flatten()
+        .flat_map(|v| v.split(','))
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+    {
+        if item.eq_ignore_ascii_case("list") {
+            list = true;
+            continue;
+        }
+        let (enable, name) = match item.strip_prefix('-') {
+            Some(name) => (false, name),
+            None => (true, item.strip_prefix('+').unwrap_or(item)),
+        };
+        let rule = validator::find_rule(name).ok_or_else(|| {
+            anyhow!("unknown rule for --rules: {name} (use --rules list to see them)")
+        })?;
+        if enable {
+            disabled.remove(rule.name);
+        } else {
+            disabled.insert(rule.name);
+        }
+    }
+    Ok((list, disabled))
+}
+
+/// Values of a repeatable option that may also be comma-separated, such as
+/// `--only-msgtype 8,9 --only-msgtype AE`.
+fn split_list_values<'a>(values: Option<impl Iterator<Item = &'a String>>) -> Vec<String> {
+    values
+        .into_iter()
+        .flatten()
+        .flat_map(|v| v.split(','))
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Build the `--only-msgtype` and `--exclude-msgtype` filter, resolving message names
+/// against the `--fix` dictionary.  Naming a MsgType in both is an error.
+fn build_msg_type_filter(opts: &CliOptions, schema: &SchemaTree) -> Result<MsgTypeFilter> {
+    let resolve = |values: &[String], option: &str| -> Result<Vec<String>> {
+        values
+            .iter()
+            .map(|value| {
+                resolve_msg_type(schema, value)
+                    .ok_or_else(|| anyhow!("invalid value for --{option}: unknown MsgType {value}"))
+            })
+            .collect()
+    };
+    let only = resolve(&opts.only_msg_types, "only-msgtype")?;
+    let exclude = resolve(&opts.exclude_msg_types, "exclude-msgtype")?;
+    MsgTypeFilter::new(only, exclude, opts.count_filtered).map_err(|both| {
+        anyhow!(
+            "MsgType {} cannot be given to both --only-msgtype and --exclude-msgtype",
+            both.join(", ")
+        )
+    })
+}
+
+/// A MsgType code for `value`: a code the dictionary defines, a message name in any
+/// case, or a short code of letters and digits it does not know, such as a
+/// user-defined `U1`.
+fn resolve_msg_type(schema: &SchemaTree, value: &str) -> Option<String> {
+    let messages = || schema.messages.values();
+    if let Some(message) = messages().find(|m| m.msg_type == value) {
+        return Some(message.msg_type.clone());
+    }
+    if let Some(message) = messages().find(|m| m.name.eq_ignore_ascii_case(value)) {
+        return Some(message.msg_type.clone());
+    }
+    (value.len() <= 3 && value.chars().all(|c| c.is_ascii_alphanumeric()))
+        .then(|| value.to_string())
+}
+
+/// Parse `--summary-terminal`, a comma-separated list of OrdStatus codes or state
+/// names, into the states that close an order.
+fn parse_summary_terminal(value: Option<&String>) -> Result<Option<Vec<String>>> {
+    let Some(value) = value else {
+        return Ok(None);
+    };
+    let mut states = Vec::new();
+    for item in value.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+        let state = ord_status_state(item).ok_or_else(|| {
+            anyhow!("invalid value for --summary-terminal: unknown OrdStatus {item}")
+        })?;
+        states.push(state.to_string());
+    }
+    if states.is_empty() {
+        return Err(anyhow!("--summary-terminal needs at least one OrdStatus"));
+    }
+    Ok(Some(states))
+}
+
+/// The `--tag` values counted by `--histogram`.  Without `--histogram` only one
+/// `--tag` may be given.
+fn parse_histogram_tags(matches: &ArgMatches) -> Result<Option<Vec<u32>>> {
+    let values: Vec<&String> = matches
+        .get_many::<String>("tag")
+        .into_iter()
+        .flatten()
+        .collect();
+    if !matches.get_flag("histogram") {
+        if values.len() > 1 {
+            bail!("--tag may only be given once unless --histogram is used");
+        }
+        return Ok(None);
+    }
+    let mut tags = Vec::new();
+    for value in values {
+        let tag = value.parse::<u32>().map_err(|_| {
+            anyhow!("--histogram needs a tag number for each --tag, e.g. --tag 150")
+        })?;
+        if !tags.contains(&tag) {
+            tags.push(tag);
+        }
+    }
+    Ok(Some(tags))
+}
+
+/// Parse `--summary-group`: `symbol` or `symbol-side`.
+fn parse_summary_group(value: Option<&String>) -> Result<Option<SummaryGroup>> {
+    value
+        .map(|v| {
+            SummaryGroup::parse(v).ok_or_else(|| {
+                anyhow!("invalid value for --summary-group: {v} (expected symbol or symbol-side)")
+            })
+        })
+        .transpose()
+}
+
+/// Parse a positive number of seconds, fractions allowed, such as `--max-gap`.
+fn parse_seconds(matches: &ArgMatches, name: &str) -> Result<Option<TimeDelta>> {
+    matches
+        .get_one::<String>(name)
+        .map(|v| {
+            v.parse::<f64>()
+                .ok()
+                .filter(|secs| secs.is_finite() && *secs > 0.0)
+                .map(|secs| TimeDelta::microseconds((secs * 1e6).round() as i64))
+                .ok_or_else(|| {
+                    anyhow!(
+                        "invalid value for --{name}: {v} (expected a positive number of seconds)"
+                    )
+                })
+        })
+        .transpose()
+}
+
+/// Parse a comma-separated list of tag numbers given to option `name`, such as
+/// `--dedupe-ignore-tags`; without one, `default` applies.
+fn parse_tag_list(value: Option<&String>, name: &str, default: &[u32]) -> Result<Vec<u32>> {
+    let Some(value) = value else {
+        return Ok(default.to_vec());
+    };
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|tag| !tag.is_empty())
+        .map(|tag| {
+            tag.parse::<u32>()
+                .ok()
+                .filter(|tag| *tag > 0)
+                .ok_or_else(|| anyhow!("invalid value for --{name}: {tag}"))
+        })
+        .collect()
+}
+
+/// Parse `--fail-on`: `none`, `any`, or a positive count of invalid messages that
+/// must be reached before the run fails.
+fn parse_fail_on(value: Option<&String>) -> Result<FailOn> {
+    let Some(value) = value else {
+        return Ok(FailOn::Any);
+    };
+    match value.to_ascii_lowercase().as_str() {
+        "none" => Ok(FailOn::Never),
+        "any" => Ok(FailOn::Any),
+        other => match other.parse::<usize>() {
+            Ok(n) if n > 0 => Ok(FailOn::AtLeast(n)),
+            _ => Err(anyhow!(
+                "invalid value for --fail-on: {value} (expected none, any or a positive number)"
+            )),
+        },
+    }
+}
+
+/// Parse `--max-decimals`, where zero is allowed and means whole numbers only.
+fn parse_max_decimals(value: Option<&String>) -> Result<Option<usize>> {
+    let Some(value) = value else {
+        return Ok(None);
+    };
+    value.parse::<usize>().map(Some).map_err(|_| {
+        anyhow!("invalid value for --max-decimals: {value} (expected a number of decimal places)")
+    })
+}
+
+/// Parse an optional positive count such as `--width=N`.
+fn parse_positive(matches: &ArgMatches, name: &str) -> Result<Option<usize>> {
+    let Some(value) = matches.get_one::<String>(name) else {
+        return Ok(None);
+    };
+    match value.parse::<usize>() {
+        Ok(n) if n > 0 => Ok(Some(n)),
+        _ => Err(anyhow!(
+            "invalid value for --{name}: {value} (expected a positive number)"
+        )),
+    }
 }
 
-/// Load an embedded FIX dictionary by version string (e.g. "44").
-fn load_embedded_dictionary(fix_version: &str) -> Result<FixDictionary> {
-    let xml_data = fix::choose_embedded_xml(fix_version);
-    FixDictionary::from_xml(xml_data).with_context(|| "failed to parse FIX XML")
+/// Parse `--replay=SPEED`, where 2 halves every gap and 0 removes them.
+fn parse_speed(matches: &ArgMatches) -> Result<Option<f64>> {
+    matches
+        .get_one::<String>("replay")
+        .map(|value| {
+            value
+                .parse::<f64>()
+                .ok()
+                .filter(|speed| speed.is_finite() && *speed >= 0.0)
+                .ok_or_else(|| {
+                    anyhow!(
+                        "invalid value for --replay: {value} (expected a speed such as 2.0, or 0 for no pauses)"
+                    )
+                })
+        })
+        .transpose()
 }
 
-/// Load an embedded FIX dictionary by canonical key (e.g. "FIX44").
-fn load_embedded_dictionary_for_key(key: &str) -> Result<FixDictionary> {
-    let xml_id = key_to_xml_id(key).ok_or_else(|| anyhow!("no embedded dictionary for {key}"))?;
-    let xml_data = fix::choose_embedded_xml(xml_id);
-    FixDictionary::from_xml(xml_data)
-        .with_context(|| format!("failed to parse embedded FIX XML for {key}"))
+/// Parse `--fill-tolerance=QTY`, defaulting to no tolerance.
+fn parse_fill_tolerance(matches: &ArgMatches) -> Result<f64> {
+    let Some(value) = matches.get_one::<String>("fill-tolerance") else {
+        return Ok(0.0);
+    };
+    value
+        .parse::<f64>()
+        .ok()
+        .filter(|qty| qty.is_finite() && *qty >= 0.0)
+        .ok_or_else(|| {
+            anyhow!(
+                "invalid value for --fill-tolerance: {value} (expected a non-negative quantity)"
+            )
+        })
 }
 
-/// Parse the delimiter override supplied on the CLI.  Users can pass a
-/// literal character, “SOH”, or a hex escape like `\x1f`.  The parser errs
-/// on the side of helpful messages whilst staying strict.
-fn parse_delimiter(value: Option<&String>) -> Result<char> {
-    const SOH: char = '\u{0001}';
-    match value {
-        None => Ok(SOH),
-        Some(v) if v.is_empty() => Err(anyhow!("delimiter cannot be empty")),
-        Some(v) => {
-            if v.eq_ignore_ascii_case("SOH") {
-                return Ok(SOH);
-            }
-            if let Some(hex) = v.strip_prefix("\\x").or_else(|| v.strip_prefix("0x")) {
-                let code = u32::from_str_radix(hex, 16)
-                    .map_err(|_| anyhow!("invalid delimiter hex value: {v}"))?;
-                return char::from_u32(code)
-                    .ok_or_else(|| anyhow!("delimiter code {v} is not valid Unicode"));
-            }
-            if v.chars().count() == 1 {
-                return Ok(v.chars().next().unwrap());
-            }
-            Err(anyhow!(
-                "delimiter must be a single character or hex code like \\x01"
-            ))
-        }
+/// Parse `--depth=N`, where 0 collapses everything below the message body.
+fn parse_depth(matches: &ArgMatches) -> Result<Option<usize>> {
+    matches
+        .get_one::<String>("depth")
+        .map(|value| {
+            value
+                .parse::<usize>()
+                .map_err(|_| anyhow!("invalid value for --depth: {value} (expected a number)"))
+        })
+        .transpose()
+}
+
+/// Parse `--seek=BYTES` or `--seek-line=N` (clap rejects both together).
+fn parse_seek(matches: &ArgMatches) -> Result<Option<SeekTo>> {
+    if let Some(value) = matches.get_one::<String>("seek") {
+        let offset = value
+            .parse::<u64>()
+            .map_err(|_| anyhow!("invalid value for --seek: {value} (expected a byte offset)"))?;
+        return Ok(Some(SeekTo::Byte(offset)));
     }
+    Ok(parse_positive(matches, "seek-line")?.map(SeekTo::Line))
+}
+
+/// Parse the delimiter override supplied on the CLI, SOH when there is none.  See
+/// [`delimiter::parse_delimiter`] for the accepted forms.
+fn parse_delimiter(value: Option<&String>) -> Result<char> {
+    value.map_or(Ok('\u{0001}'), |v| delimiter::parse_delimiter(v))
 }
 
 /// Load the requested FIX dictionary for CLI queries.  Custom dictionaries
@@ -571,6 +2691,14 @@ fn load_schema(
     opts: &CliOptions,
     custom_dicts: &HashMap<String, CustomDictionary>,
 ) -> Result<SchemaTree> {
+    load_schema_dictionary(opts, custom_dicts).map(SchemaTree::build)
+}
+
+/// The dictionary behind the schema `--fix` selects, with session components merged in.
+fn load_schema_dictionary(
+    opts: &CliOptions,
+    custom_dicts: &HashMap<String, CustomDictionary>,
+) -> Result<FixDictionary> {
     let normalized_key = normalise_fix_key(&opts.fix_version);
 
     let mut dict = if let Some(ref key) = normalized_key
@@ -582,9 +2710,8 @@ fn load_schema(
     };
 
     let dict_key = dictionary_key(&dict);
-    ensure_session_components(&dict_key, &mut dict);
-
-    Ok(SchemaTree::build(dict))
+    ensure_session_components(&dict_key, &mut dict, custom_session(custom_dicts));
+    Ok(dict)
 }
 
 /// Load a dictionary for a specific canonical key, preferring custom entries when present.
@@ -592,14 +2719,51 @@ fn load_schema_for_key(
     key: &str,
     custom_dicts: &HashMap<String, CustomDictionary>,
 ) -> Result<SchemaTree> {
+    load_dictionary_for_key(key, custom_dicts).map(SchemaTree::build)
+}
+
+/// The dictionary the decoder uses for `key`: a custom `--xml` entry or the embedded
+/// one, with the FIXT session header and trailer merged in for FIX 5.0+.
+fn load_dictionary_for_key(
+    key: &str,
+    custom_dicts: &HashMap<String, CustomDictionary>,
+) -> Result<FixDictionary> {
     let normalized = key.to_ascii_uppercase();
     let mut dict = if let Some(custom) = custom_dicts.get(&normalized) {
         custom.dict.clone()
     } else {
         load_embedded_dictionary_for_key(&normalized)?
     };
-    ensure_session_components(&normalized, &mut dict);
-    Ok(SchemaTree::build(dict))
+    ensure_session_components(&normalized, &mut dict, custom_session(custom_dicts));
+    Ok(dict)
+}
+
+/// Handle `--export-xml KEY [FILE]`: write the active dictionary for `KEY` as
+/// normalised QuickFIX-style XML.
+fn export_dictionary_xml(
+    key: &str,
+    path: Option<&str>,
+    custom_dicts: &HashMap<String, CustomDictionary>,
+) -> Result<()> {
+    let key = normalise_fix_key(key).ok_or_else(|| anyhow!("--export-xml needs a KEY"))?;
+    let xml = load_dictionary_for_key(&key, custom_dicts)?.to_xml();
+    match path {
+        Some(path) => {
+            fs::write(path, xml).with_context(|| format!("failed to write {path}"))?;
+            eprintln!("Wrote {key} dictionary to {path}");
+        }
+        None => io::stdout()
+            .write_all(xml.as_bytes())
+            .context("failed to write dictionary XML")?,
+    }
+    Ok(())
+}
+
+/// The user-supplied FIXT transport dictionary, if any.
+fn custom_session(custom_dicts: &HashMap<String, CustomDictionary>) -> Option<&FixDictionary> {
+    custom_dicts
+        .get(SESSION_DICTIONARY_KEY)
+        .map(|custom| &custom.dict)
 }
 
 /// Handle non-streaming commands such as `--message`, `--tag`, `--component`
@@ -618,7 +2782,7 @@ fn run_handlers(
     }
 
     if opts.message_flag {
-        handle_messages(opts, schema)?;
+        handle_messages(opts, schema, custom_dicts)?;
         handled = true;
     }
 
@@ -632,26 +2796,72 @@ fn run_handlers(
         handled = true;
     }
 
+    if let Some(text) = &opts.find {
+        handle_find(opts, schema, text)?;
+        handled = true;
+    }
+
+    if opts.list_rules {
+        print_business_rules();
+        handled = true;
+    }
+
+    if let Some((key, path)) = &opts.export_xml {
+        export_dictionary_xml(key, path.as_deref(), custom_dicts)?;
+        handled = true;
+    }
+
+    if opts.browse {
+        decoder::browser::run(schema)?;
+        handled = true;
+    }
+
     Ok(handled)
 }
 
-/// Ensure user-supplied FIX versions map to either built-in or custom dictionaries.
+/// Print the `--validate` business rules for `--rules list`.
+fn print_business_rules() {
+    let width = validator::BUSINESS_RULES
+        .iter()
+        .map(|rule| rule.name.len())
+        .max()
+        .unwrap_or(0);
+    for rule in validator::BUSINESS_RULES {
+        println!("  {:<width$}  {}", rule.name, rule.description);
+    }
+}
+
+/// Ensure user-supplied FIX versions, from `--fix` and `--fix-map`, map to either
+/// built-in or custom dictionaries.
 fn ensure_valid_fix_version(
     opts: &CliOptions,
     custom_dicts: &HashMap<String, CustomDictionary>,
 ) -> Result<()> {
+    let builtin = built_in_fix_keys();
+    if let Some(rule) = opts.fix_map.iter().find(|rule| {
+        !builtin.iter().any(|key| key == rule.key()) && !custom_dicts.contains_key(rule.key())
+    }) {
+        bail!(
+            "invalid --fix-map dictionary {} (available: {})",
+            rule.key(),
+            available_fix_versions(custom_dicts)
+        );
+    }
     if !opts.fix_from_user {
         return Ok(());
     }
 
-    if let Some(key) = normalise_fix_key(&opts.fix_version) {
-        let builtin = built_in_fix_keys();
-        if builtin.contains(&key) || custom_dicts.contains_key(&key) {
-            return Ok(());
-        }
+    if let Some(key) = normalise_fix_key(&opts.fix_version)
+        && (builtin.contains(&key) || custom_dicts.contains_key(&key))
+    {
+        return Ok(());
     }
 
-    eprintln!("Invalid --fix value: {}", opts.fix_version);
+    eprintln!(
+        "Invalid --fix value: {} (available: {})",
+        opts.fix_version,
+        available_fix_versions(custom_dicts)
+    );
     print_usage();
     Err(anyhow!("invalid --fix value"))
 }
@@ -674,26 +2884,13 @@ fn print_git_clone() {
 /// Print the condensed usage guide.  Kept in one function so we can reuse it
 /// whenever argument parsing fails.
 fn print_usage() {
-    static USAGE: &str = include_str!("../resources/messages/usage_en.txt");
-    println!("\n{USAGE}");
+    println!("\n{}", usage_text());
 }
 
-/// Normalise user-supplied FIX version identifiers (e.g. `4.4`, `fix44`)
-/// into the canonical keys used throughout the project.
-fn normalise_fix_key(raw: &str) -> Option<String> {
-    let trimmed = raw.trim();
-    if trimmed.is_empty() {
-        return None;
-    }
-
-    let mut cleaned = trimmed.replace('.', "");
-    cleaned = cleaned.to_ascii_uppercase();
-
-    if cleaned.starts_with("FIX") {
-        Some(cleaned)
-    } else {
-        Some(format!("FIX{}", cleaned))
-    }
+/// The usage text, with the `--delimiter` forms filled in from the parser's own list.
+fn usage_text() -> String {
+    static USAGE: &str = include_str!("../resources/messages/usage_en.txt");
+    USAGE.replace("{DELIMITER_FORMS}", DELIMITER_FORMS)
 }
 
 /// Derive the canonical dictionary key (e.g. FIX40SP1) from a parsed dictionary.
@@ -716,13 +2913,14 @@ fn dictionary_key(dict: &FixDictionary) -> String {
     key.to_ascii_uppercase()
 }
 
-/// Return the set of built-in FIX dictionary keys shipped with the binary.
+/// Return the set of built-in FIX dictionary keys compiled into the binary.
 fn built_in_fix_keys() -> Vec<String> {
     vec![
         "FIX27", "FIX30", "FIX40", "FIX41", "FIX42", "FIX43", "FIX44", "FIX50", "FIX50SP1",
         "FIX50SP2", "FIXT11",
     ]
     .into_iter()
+    .filter(|key| key_to_xml_id(key).is_some())
     .map(|s| s.to_string())
     .collect()
 }
@@ -791,36 +2989,83 @@ fn requires_session_components(key: &str) -> bool {
     matches!(key, "FIX50" | "FIX50SP1" | "FIX50SP2")
 }
 
-/// Supply header/trailer blocks from FIXT11 into FIX 5.0+ dictionaries when absent.
-fn ensure_session_components(key: &str, dict: &mut FixDictionary) {
+/// Supply header/trailer blocks into FIX 5.0+ dictionaries when absent, taking them
+/// from the user's transport dictionary when given and the embedded FIXT11 otherwise.
+/// Session field definitions missing from the application dictionary are added too.
+fn ensure_session_components(key: &str, dict: &mut FixDictionary, session: Option<&FixDictionary>) {
     if !requires_session_components(key) {
         return;
     }
 
-    let session_xml = fix::choose_embedded_xml("T11");
-    let session = match FixDictionary::from_xml(session_xml) {
-        Ok(dict) => dict,
-        Err(err) => {
-            eprintln!("warning: failed to load FIXT11 session dictionary ({err})");
-            return;
-        }
+    let embedded;
+    let session = match session {
+        Some(session) => session,
+        None => match fix::choose_embedded_xml("T11").map(FixDictionary::from_xml) {
+            Some(Ok(dict)) => {
+                embedded = dict;
+                &embedded
+            }
+            Some(Err(err)) => {
+                decoder::warnings::warn(
+                    "session-layer",
+                    "FIXT11",
+                    &format!("warning: failed to load FIXT11 session dictionary ({err})"),
+                );
+                return;
+            }
+            None => return,
+        },
     };
 
+    let mut merged = false;
     if !component_def_has_entries(&dict.header) {
-        dict.header = session.header;
+        dict.header = session.header.clone();
+        merged = true;
     }
     if !component_def_has_entries(&dict.trailer) {
-        dict.trailer = session.trailer;
+        dict.trailer = session.trailer.clone();
+        merged = true;
+    }
+    if merged {
+        let known: HashSet<u32> = dict.fields.items.iter().map(|f| f.number).collect();
+        let missing = session
+            .fields
+            .items
+            .iter()
+            .filter(|f| !known.contains(&f.number))
+            .cloned();
+        dict.fields.items.extend(missing);
     }
 }
 
+/// Whether anything in `dict` names a component, which a dictionary read without its
+/// `<components>` (`--allow-partial-dict`) then cannot supply.  A dictionary with an
+/// empty `<components/>` and no references is complete as it stands.
+fn references_components(dict: &FixDictionary) -> bool {
+    fn in_groups(groups: &[decoder::schema::GroupDef]) -> bool {
+        groups
+            .iter()
+            .any(|group| !group.components.is_empty() || in_groups(&group.groups))
+    }
+    let blocks = [&dict.header, &dict.trailer];
+    blocks
+        .iter()
+        .any(|block| !block.components.is_empty() || in_groups(&block.groups))
+        || dict
+            .messages
+            .items
+            .iter()
+            .any(|msg| !msg.components.is_empty() || in_groups(&msg.groups))
+}
+
 fn component_def_has_entries(block: &decoder::schema::ComponentDef) -> bool {
     !block.fields.is_empty() || !block.groups.is_empty() || !block.components.is_empty()
 }
 
-/// Map a canonical FIX key to the embedded XML identifier used by `choose_embedded_xml`.
+/// Map a canonical FIX key to the embedded XML identifier used by `choose_embedded_xml`,
+/// or `None` when there is no such dictionary or it was not compiled in.
 fn key_to_xml_id(key: &str) -> Option<&'static str> {
-    match key.to_ascii_uppercase().as_str() {
+    let xml_id = match key.to_ascii_uppercase().as_str() {
         "FIX27" => Some("40"),
         "FIX30" => Some("40"),
         "FIX40" => Some("40"),
@@ -833,14 +3078,17 @@ fn key_to_xml_id(key: &str) -> Option<&'static str> {
         "FIX50SP2" => Some("50SP2"),
         "FIXT11" => Some("T11"),
         _ => None,
-    }
+    };
+    xml_id.filter(|id| fix::choose_embedded_xml(id).is_some())
 }
 
 /// Print a summary table of all available dictionaries (built-in and custom),
 /// optionally highlighting a selected entry.
+/// With `verbose`, each dictionary's consistency warnings follow the table.
 fn print_all_dictionary_info(
     custom_dicts: &HashMap<String, CustomDictionary>,
     highlight: Option<&str>,
+    verbose: bool,
 ) -> Result<()> {
     println!(
         "Available FIX Dictionaries: {}",
@@ -849,17 +3097,40 @@ fn print_all_dictionary_info(
     println!("\nLoaded dictionaries:");
     print_dictionary_header();
 
+    let mut warnings = Vec::new();
     for key in all_dictionary_keys(custom_dicts) {
-        match load_schema_for_key(&key, custom_dicts) {
-            Ok(schema) => {
+        match load_dictionary_for_key(&key, custom_dicts) {
+            Ok(dict) => {
+                if verbose {
+                    warnings.push((key.clone(), dict.warnings()));
+                }
+                let schema = SchemaTree::build(dict);
                 let source = dictionary_source(custom_dicts, &key);
                 let marker = dictionary_marker(highlight, &key);
                 print_dictionary_row(marker, &key, &schema, &source);
             }
-            Err(err) => eprintln!("warning: failed to load {key}: {err}"),
+            Err(err) => decoder::warnings::warn(
+                "dictionary-load",
+                &key,
+                &format!("warning: failed to load {key}: {err}"),
+            ),
         }
     }
     println!();
+    if verbose {
+        println!("Dictionary warnings:");
+        for (key, found) in warnings {
+            if found.is_empty() {
+                println!("  {key}: none");
+                continue;
+            }
+            println!("  {key}: {}", found.len());
+            for warning in found {
+                println!("    {warning}");
+            }
+        }
+        println!();
+    }
     Ok(())
 }
 
@@ -870,23 +3141,93 @@ fn handle_info(
     custom_dicts: &HashMap<String, CustomDictionary>,
 ) -> Result<()> {
     let selected_key = normalise_fix_key(&opts.fix_version).unwrap_or_else(|| "FIX44".to_string());
-    print_all_dictionary_info(custom_dicts, Some(&selected_key))?;
+    if opts.json {
+        return print_dictionary_info_json(opts, custom_dicts, &selected_key);
+    }
+    print_all_dictionary_info(custom_dicts, Some(&selected_key), opts.verbose)?;
+    Ok(())
+}
+
+/// `--info --json`: one object per loadable dictionary, mirroring the table columns.
+fn print_dictionary_info_json(
+    opts: &CliOptions,
+    custom_dicts: &HashMap<String, CustomDictionary>,
+    selected_key: &str,
+) -> Result<()> {
+    let mut rows = Vec::new();
+    for key in all_dictionary_keys(custom_dicts) {
+        match load_schema_for_key(&key, custom_dicts) {
+            Ok(schema) => rows.push(serde_json::json!({
+                "key": key,
+                "selected": key.eq_ignore_ascii_case(selected_key),
+                "service_pack": schema.service_pack,
+                "fields": schema.fields.len(),
+                "components": schema.components.len(),
+                "messages": schema.messages.len(),
+                "source": dictionary_source(custom_dicts, &key),
+            })),
+            Err(err) => decoder::warnings::warn(
+                "dictionary-load",
+                &key,
+                &format!("warning: failed to load {key}: {err}"),
+            ),
+        }
+    }
+    print_json(opts, &rows)
+}
+
+/// Write `value` to stdout as compact JSON, or indented with `--pretty`.
+fn print_json<T: serde::Serialize + ?Sized>(opts: &CliOptions, value: &T) -> Result<()> {
+    let mut out = io::stdout().lock();
+    if opts.pretty {
+        serde_json::to_writer_pretty(&mut out, value)?;
+    } else {
+        serde_json::to_writer(&mut out, value)?;
+    }
+    writeln!(out)?;
     Ok(())
 }
 
 /// Handle `--message` mode (list or render a specific message).
-fn handle_messages(opts: &CliOptions, schema: &SchemaTree) -> Result<()> {
+fn handle_messages(
+    opts: &CliOptions,
+    schema: &SchemaTree,
+    custom_dicts: &HashMap<String, CustomDictionary>,
+) -> Result<()> {
+    if opts.generate || opts.generate_all {
+        return handle_generate(opts, schema);
+    }
+    if opts.json {
+        return match &opts.message_value {
+            None => print_json(opts, &schema.messages.values().collect::<Vec<_>>()),
+            Some(value) => match find_message(schema, value) {
+                Some(message) => print_json(opts, message),
+                None => bail!("Message not found: {value}"),
+            },
+        };
+    }
     match &opts.message_value {
         None => {
             if opts.column {
-                print_message_columns(schema)?;
+                print_message_columns(schema, display::output_width(opts.width))?;
             } else {
                 list_all_messages(schema)?;
             }
         }
         Some(value) => {
             if let Some(message) = find_message(schema, value) {
-                let style = DisplayStyle::new(decoder::colours::palette(), opts.column);
+                let style = DisplayStyle::new(decoder::colours::palette(), opts.column)
+                    .with_width(opts.width);
+                let lookup = if opts.provenance {
+                    let dict = load_schema_dictionary(opts, custom_dicts)?;
+                    Some(FixTagLookup::from_dictionary(&dict, &dictionary_key(&dict)))
+                } else {
+                    None
+                };
+                let provenance = lookup
+                    .as_ref()
+                    .and_then(|lookup| lookup.message_def(&message.msg_type))
+                    .map(|def| &def.provenance);
                 display_message(
                     schema,
                     message,
@@ -895,6 +3236,8 @@ fn handle_messages(opts: &CliOptions, schema: &SchemaTree) -> Result<()> {
                     opts.include_trailer,
                     4,
                     style,
+                    provenance,
+                    opts.outline,
                 )?;
             } else {
                 println!("Message not found: {value}");
@@ -904,42 +3247,157 @@ fn handle_messages(opts: &CliOptions, schema: &SchemaTree) -> Result<()> {
     Ok(())
 }
 
+/// Handle `--message NAME --generate[-all]`: print one skeleton message using the
+/// display delimiter.
+fn handle_generate(opts: &CliOptions, schema: &SchemaTree) -> Result<()> {
+    let value = opts
+        .message_value
+        .as_deref()
+        .ok_or_else(|| anyhow!("--generate requires a message name or MsgType"))?;
+    let Some(message) = find_message(schema, value) else {
+        return Err(anyhow!("Message not found: {value}"));
+    };
+    let now = chrono::Utc::now().naive_utc();
+    let msg = generate_message(schema, message, opts.generate_all, now);
+    println!("{}", msg.replace('\u{0001}', &opts.delimiter.to_string()));
+    Ok(())
+}
+
 /// Handle `--tag` mode (list or show details).
 fn handle_tags(opts: &CliOptions, schema: &SchemaTree) -> Result<()> {
     match &opts.tag_value {
+        None if opts.enum_value.is_some() => {
+            bail!("--enum needs a tag number or name, e.g. --tag 18 --enum M")
+        }
+        None if opts.json => {
+            let mut fields: Vec<&Field> = schema.fields.values().map(|f| f.as_ref()).collect();
+            fields.sort_by_key(|f| f.number);
+            print_json(opts, &fields)?;
+        }
         None => {
             if opts.column {
-                print_tags_in_columns(schema)?;
+                print_tags_in_columns(schema, display::output_width(opts.width))?;
             } else {
                 list_all_tags(schema)?;
             }
         }
-        Some(value) => {
-            let tag: u32 = value.parse().map_err(|_| anyhow!("Invalid tag: {value}"))?;
-            if let Some(field) = schema.find_field_by_number(tag) {
-                print_tag_details(field, opts.verbose, opts.column)?;
-            } else {
-                println!("Tag not found: {tag}");
+        Some(value) => match value.parse::<u32>() {
+            Ok(tag) => {
+                if let Some(field) = schema.find_field_by_number(tag) {
+                    print_field_details(opts, schema, field)?;
+                } else if opts.json {
+                    bail!("Tag not found: {tag}");
+                } else {
+                    println!("Tag not found: {tag}");
+                }
             }
+            Err(_) => print_tags_by_name(opts, schema, value)?,
+        },
+    }
+    Ok(())
+}
+
+/// `--tag NAME` or `--tag 'GLOB'`: resolve field names case-insensitively, listing
+/// every match.  Plain listings use columns with `--column`; `--verbose` shows each
+/// field's enums.
+fn print_tags_by_name(opts: &CliOptions, schema: &SchemaTree, pattern: &str) -> Result<()> {
+    let fields = schema.find_fields_by_name(pattern);
+    if opts.json {
+        if fields.is_empty() {
+            bail!("Tag not found: {pattern}");
+        }
+        return print_json(opts, &fields);
+    }
+    if fields.is_empty() {
+        println!("Tag not found: {pattern}");
+        let similar = schema.similar_field_names(pattern);
+        if !similar.is_empty() {
+            println!("Did you mean: {}", similar.join(", "));
         }
+        return Ok(());
+    }
+    if opts.column && !opts.verbose && fields.len() > 1 {
+        print_fields_in_columns(&fields, display::output_width(opts.width))?;
+        return Ok(());
+    }
+    for field in fields {
+        print_field_details(opts, schema, field)?;
+    }
+    Ok(())
+}
+
+/// One field's details, or just the `--enum` value's description when asked.
+fn print_field_details(opts: &CliOptions, schema: &SchemaTree, field: &Field) -> Result<()> {
+    if opts.json {
+        return print_json(opts, field);
     }
+    match &opts.enum_value {
+        Some(value) => print_enum_lookup(field, value)?,
+        None => print_tag_details(
+            schema,
+            field,
+            opts.verbose,
+            opts.column,
+            display::output_width(opts.width),
+        )?,
+    }
+    Ok(())
+}
+
+/// Handle `--find TEXT`: search field, message and component names and enum values,
+/// showing at most `--limit` results.
+fn handle_find(opts: &CliOptions, schema: &SchemaTree, text: &str) -> Result<()> {
+    let mut matches = schema.search(text);
+    if matches.is_empty() {
+        println!("No fields, messages, components or enums match: {text}");
+        return Ok(());
+    }
+    let total = matches.len();
+    if let Some(limit) = opts.limit {
+        matches.truncate(limit);
+    }
+    print_search_results(
+        &matches,
+        text,
+        opts.column,
+        display::output_width(opts.width),
+        total,
+    )?;
     Ok(())
 }
 
 /// Handle `--component` mode (list or render a specific component).
 fn handle_components(opts: &CliOptions, schema: &SchemaTree) -> Result<()> {
+    if opts.json {
+        return match &opts.component_value {
+            None => print_json(opts, &schema.components.values().collect::<Vec<_>>()),
+            Some(name) => match schema.components.get(name) {
+                Some(component) => print_json(opts, component),
+                None => bail!("Component not found: {name}"),
+            },
+        };
+    }
     match &opts.component_value {
         None => {
             if opts.column {
-                print_component_columns(schema)?;
+                print_component_columns(schema, display::output_width(opts.width))?;
             } else {
                 list_all_components(schema)?;
             }
         }
         Some(name) => {
             if let Some(component) = schema.components.get(name) {
-                let style = DisplayStyle::new(decoder::colours::palette(), opts.column);
-                display_component(schema, None, component, opts.verbose, 0, style)?;
+                let style = DisplayStyle::new(decoder::colours::palette(), opts.column)
+                    .with_width(opts.width);
+                display_component(
+                    schema,
+                    None,
+                    component,
+                    opts.verbose,
+                    0,
+                    style,
+                    opts.outline,
+                )?;
             } else {
                 println!("Component not found: {name}");
             }
@@ -958,25 +3416,107 @@ mod tests {
             fix_version: version.to_string(),
             fix_from_user: true,
             xml_paths: Vec::new(),
+            transport_xml: None,
+            enum_overlays: Vec::new(),
+            udf_files: Vec::new(),
+            udf_override: false,
+            fix_map: Vec::new(),
             message_flag: false,
             message_value: None,
             component_flag: false,
             component_value: None,
             tag_flag: false,
+            find: None,
             tag_value: None,
+            enum_value: None,
+            json: false,
+            pretty: false,
             column: false,
             verbose: false,
+            strict_dict: false,
+            allow_partial_dict: false,
+            no_cache: false,
+            clear_cache: false,
+            provenance: false,
+            outline: Outline::default(),
             include_header: false,
             include_trailer: false,
             info: false,
+            export_xml: None,
+            browse: false,
             secret: false,
+            secret_stable: false,
+            secret_format: false,
+            secret_key: None,
+            secret_map: None,
+            extract: None,
+            extract_invalid: None,
+            split_output: None,
+            report: None,
             validate: false,
+            error_locations: false,
+            validate_summary: false,
+            raw_annotate: false,
+            max_decimals: None,
+            clock_skew: None,
+            validate_session: false,
+            fill_tolerance: 0.0,
+            warn_deprecated: false,
+            seek: None,
+            limit: None,
+            replay: None,
+            progress: false,
+            benchmark: false,
+            banner: false,
+            warning_mode: WarningMode::Once,
             colour: None,
+            theme: Theme::Dark,
             show_version: false,
             summary: false,
+            summary_keep_open: false,
+            summary_raw: false,
+            session_summary: false,
+            sequence_diagram: None,
+            diagram_format: DiagramFormat::Mermaid,
+            diagram_limit: sequence_diagram::DEFAULT_LIMIT,
+            line_timestamp: None,
+            max_gap: None,
+            summary_terminal: None,
+            summary_group: None,
+            summary_msg_types: Vec::new(),
+            summary_diff: None,
+            summary_max_orders: None,
+            list_rules: false,
+            disabled_rules: HashSet::new(),
             follow: false,
+            watch_xml: false,
+            count_only: false,
+            counts_layout: CountsLayout::default(),
+            tag_usage: false,
+            histogram: None,
+            repair: false,
+            join_lines: false,
+            unescape: false,
+            stdin_format: RecordFormat::Lines,
+            dedupe: None,
+            only_msg_types: Vec::new(),
+            exclude_msg_types: Vec::new(),
+            count_filtered: false,
+            passthrough: false,
+            decode_xml: false,
+            md_ladder: false,
+            show_dict: false,
+            prefix_file: false,
+            hide_header: false,
+            hide_trailer: false,
+            max_value_width: None,
+            width: None,
             files: Vec::new(),
             delimiter: '\u{0001}',
+            no_escape: false,
+            fail_on: FailOn::Any,
+            generate: false,
+            generate_all: false,
         }
     }
 
@@ -1025,6 +3565,115 @@ mod tests {
         decoder::prettifier::interrupt_flag().store(false, std::sync::atomic::Ordering::Relaxed);
     }
 
+    #[test]
+    fn run_exit_code_follows_contract() {
+        let outcome = |io_error, invalid| RunOutcome {
+            io_error,
+            validation: ValidationStats {
+                messages: 10,
+                invalid,
+                warned: 0,
+            },
+            files: Vec::new(),
+        };
+        assert_eq!(run_exit_code(&outcome(false, 0), FailOn::Any), EXIT_CLEAN);
+        assert_eq!(run_exit_code(&outcome(false, 1), FailOn::Any), EXIT_INVALID);
+        assert_eq!(run_exit_code(&outcome(false, 3), FailOn::Never), EXIT_CLEAN);
+        assert_eq!(
+            run_exit_code(&outcome(false, 2), FailOn::AtLeast(3)),
+            EXIT_CLEAN
+        );
+        assert_eq!(
+            run_exit_code(&outcome(false, 3), FailOn::AtLeast(3)),
+            EXIT_INVALID
+        );
+        assert_eq!(run_exit_code(&outcome(true, 3), FailOn::Any), EXIT_FAILURE);
+
+        let warned = |invalid| RunOutcome {
+            io_error: false,
+            validation: ValidationStats {
+                messages: 10,
+                invalid,
+                warned: 2,
+            },
+            files: Vec::new(),
+        };
+        assert_eq!(run_exit_code(&warned(0), FailOn::Any), EXIT_WARNINGS);
+        assert_eq!(run_exit_code(&warned(1), FailOn::Any), EXIT_INVALID);
+        assert_eq!(run_exit_code(&warned(1), FailOn::AtLeast(3)), EXIT_WARNINGS);
+        assert_eq!(run_exit_code(&warned(1), FailOn::Never), EXIT_CLEAN);
+    }
+
+    #[test]
+    fn parse_fail_on_accepts_levels() {
+        assert_eq!(parse_fail_on(None).unwrap(), FailOn::Any);
+        assert_eq!(
+            parse_fail_on(Some(&"NONE".to_string())).unwrap(),
+            FailOn::Never
+        );
+        assert_eq!(
+            parse_fail_on(Some(&"5".to_string())).unwrap(),
+            FailOn::AtLeast(5)
+        );
+        assert!(parse_fail_on(Some(&"0".to_string())).is_err());
+        assert!(parse_fail_on(Some(&"some".to_string())).is_err());
+    }
+
+    #[test]
+    fn parse_rules_lists_and_disables() {
+        let values = ["list".to_string(), "-limit-price,-fill-last".to_string()];
+        let (list, disabled) = parse_rules(Some(values.iter())).unwrap();
+        assert!(list);
+        assert_eq!(disabled, HashSet::from(["limit-price", "fill-last"]));
+
+        let values = ["-fill-last,+fill-last".to_string()];
+        let (list, disabled) = parse_rules(Some(values.iter())).unwrap();
+        assert!(!list && disabled.is_empty());
+
+        let values = ["-nope".to_string()];
+        assert!(parse_rules(Some(values.iter())).is_err());
+
+        let matches = build_cli()
+            .try_get_matches_from(["fixdecoder", "--rules", "-stop-price"])
+            .expect("hyphenated rule values parse");
+        let opts = CliOptions::from_matches(&matches).unwrap();
+        assert_eq!(opts.disabled_rules, HashSet::from(["stop-price"]));
+    }
+
+    #[test]
+    fn parse_summary_terminal_accepts_codes_and_names() {
+        assert_eq!(parse_summary_terminal(None).unwrap(), None);
+        assert_eq!(
+            parse_summary_terminal(Some(&"2, canceled,C".to_string())).unwrap(),
+            Some(vec![
+                "Filled".to_string(),
+                "Canceled".to_string(),
+                "Expired".to_string()
+            ])
+        );
+        assert!(parse_summary_terminal(Some(&"2,Z".to_string())).is_err());
+        assert!(parse_summary_terminal(Some(&",".to_string())).is_err());
+    }
+
+    #[test]
+    fn parse_tag_list_defaults_and_rejects_names() {
+        let parse = |value: Option<&str>| {
+            parse_tag_list(
+                value.map(str::to_string).as_ref(),
+                "dedupe-ignore-tags",
+                &DEFAULT_IGNORE_TAGS,
+            )
+        };
+        assert_eq!(parse(None).unwrap(), [9, 10, 34, 52]);
+        assert_eq!(parse(Some("34, 52,")).unwrap(), [34, 52]);
+        assert!(parse(Some("")).unwrap().is_empty());
+        let err = parse(Some("MsgSeqNum")).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid value for --dedupe-ignore-tags: MsgSeqNum"
+        );
+    }
+
     #[test]
     fn parse_colour_recognises_yes_no() {
         assert_eq!(parse_colour(Some(&"yes".to_string())).unwrap(), Some(true));
@@ -1032,6 +3681,25 @@ mod tests {
         assert!(parse_colour(None).unwrap().is_none());
     }
 
+    #[test]
+    fn colour_enabled_honours_no_color() {
+        assert!(colour_enabled(None, false, true));
+        assert!(
+            !colour_enabled(None, true, true),
+            "NO_COLOR disables colours"
+        );
+        assert!(!colour_enabled(None, false, false));
+        assert!(colour_enabled(Some(true), true, false), "--colour=yes wins");
+        assert!(!colour_enabled(Some(false), false, true));
+    }
+
+    #[test]
+    fn parse_theme_accepts_known_names() {
+        assert_eq!(parse_theme(None).unwrap(), Theme::Dark);
+        assert_eq!(parse_theme(Some(&"mono".to_string())).unwrap(), Theme::Mono);
+        assert!(parse_theme(Some(&"neon".to_string())).is_err());
+    }
+
     #[test]
     fn parse_colour_rejects_invalid() {
         let err = parse_colour(Some(&"maybe".to_string())).unwrap_err();
@@ -1050,6 +3718,21 @@ mod tests {
         assert!(err.to_string().contains("empty"));
     }
 
+    #[test]
+    fn parse_delimiter_defaults_to_soh_and_accepts_names() {
+        assert_eq!(parse_delimiter(None).unwrap(), '\u{0001}');
+        assert_eq!(parse_delimiter(Some(&"TAB".to_string())).unwrap(), '\t');
+        let err = parse_delimiter(Some(&"\\n".to_string())).unwrap_err();
+        assert!(err.to_string().contains("line break"), "{err}");
+    }
+
+    #[test]
+    fn usage_lists_the_delimiter_forms_the_parser_accepts() {
+        let usage = usage_text();
+        assert!(!usage.contains("{DELIMITER_FORMS}"));
+        assert!(usage.contains(DELIMITER_FORMS), "{usage}");
+    }
+
     #[test]
     fn invalid_fix_version_errors() {
         let opts = dummy_opts("45");
@@ -1109,16 +3792,122 @@ mod tests {
     }
 
     #[test]
-    fn parse_delimiter_accepts_literal() {
-        let delim = parse_delimiter(Some(&",".to_string())).unwrap();
-        assert_eq!(delim, ',');
+    fn build_cli_watch_xml_requires_follow_and_xml() {
+        assert!(
+            build_cli()
+                .try_get_matches_from(["fixdecoder", "--watch-xml", "--xml", "a.xml"])
+                .is_err()
+        );
+        let matches = build_cli()
+            .try_get_matches_from(["fixdecoder", "--watch-xml", "-f", "--xml", "a.xml"])
+            .expect("parse watch-xml");
+        assert!(matches.get_flag("watch-xml"));
+    }
+
+    #[test]
+    fn config_file_sits_between_defaults_and_command_line() {
+        let options = |args: &[&str], config: Option<&str>| {
+            let args: Vec<OsString> = args.iter().map(OsString::from).collect();
+            let cmd = build_cli();
+            let mut matches = cmd.clone().try_get_matches_from(&args).expect("parse cli");
+            let mut warnings = Vec::new();
+            if let Some(text) = config {
+                let config = ConfigFile {
+                    path: PathBuf::from("config.toml"),
+                    table: text.parse().expect("parse config"),
+                };
+                let merged = merge_config_args(&args, &config, &cmd, &matches, &mut warnings)
+                    .expect("merge config");
+                matches = cmd.try_get_matches_from(merged).expect("parse merged");
+            }
+            let opts = CliOptions::from_matches(&matches).expect("options");
+            (opts, String::from_utf8(warnings).unwrap())
+        };
+        let config = "fix = 42\ndelimiter = \"|\"\nvalidate = true\nmax_value_width = 20\nshade = \"blue\"\n";
+
+        let (opts, _) = options(&["fixdecoder"], None);
+        assert_eq!(
+            (opts.fix_version.as_str(), opts.delimiter, opts.validate),
+            ("44", '\u{0001}', false)
+        );
+
+        let (opts, warnings) = options(&["fixdecoder", "in.log"], Some(config));
+        assert_eq!(
+            (opts.fix_version.as_str(), opts.delimiter, opts.validate),
+            ("42", '|', true)
+        );
+        assert!(opts.fix_from_user);
+        assert_eq!(opts.max_value_width, Some(20));
+        assert_eq!(opts.files, vec!["in.log".to_string()]);
+        assert_eq!(
+            warnings,
+            "warning: ignoring unknown key 'shade' in config file config.toml\n"
+        );
+
+        let (opts, _) = options(
+            &["fixdecoder", "--fix", "50", "--delimiter", ":"],
+            Some(config),
+        );
+        assert_eq!((opts.fix_version.as_str(), opts.delimiter), ("50", ':'));
+        assert!(opts.validate);
+
+        // A flag that conflicts with a config value wins rather than failing, whichever
+        // of the two declares the conflict.
+        let (opts, _) = options(
+            &["fixdecoder", "--verbose-warnings"],
+            Some("quiet = true\n"),
+        );
+        assert_eq!(opts.warning_mode, WarningMode::Every);
+        let (opts, _) = options(
+            &["fixdecoder", "--quiet"],
+            Some("verbose_warnings = true\n"),
+        );
+        assert_eq!(opts.warning_mode, WarningMode::Quiet);
+        let (opts, _) = options(&["fixdecoder", "--seek-line", "2"], Some("seek = 10\n"));
+        assert_eq!(opts.seek, Some(SeekTo::Line(2)));
+    }
+
+    #[test]
+    fn dump_config_round_trips_through_merge() {
+        let cmd = build_cli();
+        let matches = cmd
+            .clone()
+            .try_get_matches_from([
+                "fixdecoder",
+                "--delimiter",
+                "|",
+                "--rules",
+                "-stop-price",
+                "--secret",
+                "--secret-key=hunter2",
+            ])
+            .unwrap();
+        let dumped = dump_config(&cmd, &matches).unwrap();
+        assert!(dumped.contains("secret = true\n"), "{dumped}");
+        assert!(
+            !dumped.contains("secret-key") && !dumped.contains("hunter2"),
+            "{dumped}"
+        );
+        assert!(dumped.contains("delimiter = \"|\"\n"), "{dumped}");
+        assert!(dumped.contains("rules = [\"-stop-price\"]\n"), "{dumped}");
+        assert!(dumped.contains("validate = false\n"), "{dumped}");
+        assert!(!dumped.contains("dump-config"), "{dumped}");
+
+        let config = ConfigFile {
+            path: PathBuf::from("dumped.toml"),
+            table: dumped.parse().unwrap(),
+        };
+        let args = vec![OsString::from("fixdecoder")];
+        let bare = cmd.clone().try_get_matches_from(&args).unwrap();
+        let merged = merge_config_args(&args, &config, &cmd, &bare, &mut io::sink()).unwrap();
+        let matches = cmd.clone().try_get_matches_from(merged).unwrap();
+        assert_eq!(dump_config(&cmd, &matches).unwrap(), dumped);
     }
 
     #[test]
-    fn normalise_fix_key_handles_variants() {
-        assert_eq!(normalise_fix_key("4.4"), Some("FIX44".into()));
-        assert_eq!(normalise_fix_key("fixt1.1"), Some("FIXT11".into()));
-        assert!(normalise_fix_key("   ").is_none());
+    fn parse_delimiter_accepts_literal() {
+        let delim = parse_delimiter(Some(&",".to_string())).unwrap();
+        assert_eq!(delim, ',');
     }
 
     #[test]
@@ -1162,7 +3951,132 @@ mod tests {
         assert_eq!(dictionary_source(&HashMap::new(), "FIX44"), "built-in");
         let all = all_dictionary_keys(&custom);
         assert!(all.contains(&"FIX44".into()));
-        assert!(all.contains(&"FIX27".into()));
+        if cfg!(feature = "dict-fix40") {
+            assert!(all.contains(&"FIX27".into()));
+        }
+    }
+
+    #[test]
+    #[cfg(all(feature = "dict-fix44", not(feature = "dict-fix42")))]
+    fn versions_left_out_of_the_build_are_not_offered() {
+        assert_eq!(key_to_xml_id("FIX42"), None);
+        assert!(!built_in_fix_keys().contains(&"FIX42".to_string()));
+        assert!(ensure_valid_fix_version(&dummy_opts("42"), &HashMap::new()).is_err());
+        assert!(ensure_valid_fix_version(&dummy_opts("44"), &HashMap::new()).is_ok());
+    }
+
+    const CUSTOM_FIXT_XML: &str = r#"<fix type='FIXT' major='1' minor='1'>
+  <header>
+    <field name='BeginString' required='Y'/>
+    <field name='MsgType' required='Y'/>
+    <field name='ApplVerID' required='N'/>
+    <field name='VenueSessionID' required='N'/>
+  </header>
+  <trailer><field name='CheckSum' required='Y'/></trailer>
+  <messages/>
+  <components/>
+  <fields>
+    <field number='8' name='BeginString' type='STRING'/>
+    <field number='10' name='CheckSum' type='STRING'/>
+    <field number='35' name='MsgType' type='STRING'/>
+    <field number='1128' name='ApplVerID' type='STRING'/>
+    <field number='20001' name='VenueSessionID' type='STRING'/>
+  </fields>
+</fix>"#;
+
+    const CUSTOM_FIX50SP2_XML: &str = r#"<fix type='FIX' major='5' minor='0' servicepack='2'>
+  <header/>
+  <trailer/>
+  <messages>
+    <message name='News' msgtype='B' msgcat='app'>
+      <field name='Headline' required='Y'/>
+    </message>
+  </messages>
+  <components/>
+  <fields>
+    <field number='35' name='MsgType' type='STRING'/>
+    <field number='148' name='Headline' type='STRING'/>
+  </fields>
+</fix>"#;
+
+    fn write_xml(dir: &std::path::Path, name: &str, xml: &str) -> String {
+        let path = dir.join(name);
+        fs::write(&path, xml).unwrap();
+        path.to_string_lossy().into_owned()
+    }
+
+    #[test]
+    fn custom_transport_supplies_header_to_custom_app_dictionary() {
+        let dir = tempfile::tempdir().unwrap();
+        let app = write_xml(dir.path(), "app.xml", CUSTOM_FIX50SP2_XML);
+        let fixt = write_xml(dir.path(), "fixt.xml", CUSTOM_FIXT_XML);
+
+        for (paths, transport) in [
+            (vec![app.clone(), fixt.clone()], None),
+            (vec![app.clone()], Some(fixt.as_str())),
+        ] {
+            let prepared = prepare_custom_dictionaries(&paths, transport).unwrap();
+            assert_eq!(prepared[0].1, "FIXT11", "transport is ordered first");
+            let (_, key, dict) = prepared.last().unwrap();
+            assert_eq!(key, "FIX50SP2");
+            let header: Vec<&str> = dict.header.fields.iter().map(|f| f.name.as_str()).collect();
+            assert!(
+                header.contains(&"VenueSessionID"),
+                "header must come from the user FIXT file: {header:?}"
+            );
+            let lookup = tag_lookup::FixTagLookup::from_dictionary(dict, "FIX50SP2");
+            assert_eq!(lookup.field_name(1128), "ApplVerID");
+            assert_eq!(lookup.field_name(20001), "VenueSessionID");
+        }
+    }
+
+    #[test]
+    fn reloading_the_transport_refreshes_the_application_header() {
+        let dir = tempfile::tempdir().unwrap();
+        let app = write_xml(dir.path(), "app.xml", CUSTOM_FIX50SP2_XML);
+        let fixt = write_xml(dir.path(), "fixt.xml", CUSTOM_FIXT_XML);
+        load_custom_dictionaries(std::slice::from_ref(&app), Some(fixt.as_str())).unwrap();
+        let header_field = |tag| {
+            let msg = "8=FIXT.1.1\u{0001}35=B\u{0001}";
+            tag_lookup::load_dictionary_with_override(msg, Some("FIX50SP2"))
+                .field_name(tag)
+                .to_string()
+        };
+        assert_eq!(header_field(20001), "VenueSessionID");
+
+        let renamed = CUSTOM_FIXT_XML.replace("VenueSessionID", "VenueRegion");
+        fs::write(&fixt, renamed).unwrap();
+        assert_eq!(reload_custom_dictionary(&fixt).unwrap(), "FIXT11");
+        assert_eq!(header_field(20001), "VenueRegion");
+        assert_eq!(reload_custom_dictionary(&app).unwrap(), "FIX50SP2");
+        assert_eq!(header_field(20001), "VenueRegion");
+    }
+
+    #[test]
+    fn extracts_to_the_same_path_share_one_writer() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("extract.log");
+        let path = path.to_string_lossy();
+        let (all, invalid) = open_extract_sinks(Some(&path), Some(&path)).unwrap();
+        let (mut all, mut invalid) = (all.unwrap(), invalid.unwrap());
+        all.write_all(b"first\n").unwrap();
+        invalid.write_all(b"second\n").unwrap();
+        all.write_all(b"third\n").unwrap();
+        all.flush().unwrap();
+        invalid.flush().unwrap();
+        drop((all, invalid));
+        assert_eq!(
+            fs::read_to_string(path.as_ref()).unwrap(),
+            "first\nsecond\nthird\n"
+        );
+    }
+
+    #[test]
+    fn transport_xml_must_be_fixt() {
+        let dir = tempfile::tempdir().unwrap();
+        let app = write_xml(dir.path(), "app.xml", CUSTOM_FIX50SP2_XML);
+        let err = prepare_custom_dictionaries(&[], Some(app.as_str())).unwrap_err();
+        assert!(err.to_string().contains("expected FIXT11"), "{err}");
     }
 
     #[test]
@@ -1,21 +1,55 @@
 // SPDX-License-Identifier: AGPL-3.0-only
 // Integration smoke tests for the CLI to ensure end-to-end flows keep working.
 
+mod common;
+
 use assert_cmd::cargo::cargo_bin_cmd;
+use common::{sealed_message, with_checksum};
+use fixdecoder::decoder::{DecodeOptions, FixMessageIter};
 use predicates::prelude::PredicateBooleanExt;
 use predicates::str::contains;
-use std::io::Write;
+use std::collections::BTreeMap;
+use std::io::{BufRead, BufReader, Write};
 use tempfile::NamedTempFile;
 
+/// The fixdecoder binary with its dictionary cache under the target directory, so test
+/// runs never write into the developer's own `~/.cache/fixdecoder`.
+fn fixdecoder() -> assert_cmd::Command {
+    let mut cmd = cargo_bin_cmd!("fixdecoder");
+    cmd.env("XDG_CACHE_HOME", env!("CARGO_TARGET_TMPDIR"));
+    cmd
+}
+
 fn fix_message(body: &str) -> String {
     let soh = '\u{0001}';
     format!("8=FIX.4.4{soh}9=005{soh}{body}10=000{soh}\n")
 }
 
+/// Build a heartbeat with correct BodyLength and CheckSum so it passes `--validate`.
+fn valid_heartbeat(seq: u32) -> String {
+    valid_heartbeat_with(seq, "")
+}
+
+/// A valid heartbeat carrying `extra` (SOH-terminated fields) after the header.
+fn valid_heartbeat_with(seq: u32, extra: &str) -> String {
+    let soh = '\u{0001}';
+    let body =
+        format!("35=0{soh}49=AAA{soh}56=BBB{soh}34={seq}{soh}52=20240101-00:00:00{soh}{extra}");
+    sealed_message("FIX.4.4", &body)
+}
+
+fn write_log(lines: &[String]) -> NamedTempFile {
+    let mut file = NamedTempFile::new().expect("temp file");
+    for line in lines {
+        write!(file, "{line}").expect("write temp");
+    }
+    file
+}
+
 #[test]
 fn decodes_single_message_from_stdin() {
     let msg = fix_message("35=0");
-    cargo_bin_cmd!("fixdecoder")
+    fixdecoder()
         .arg("--fix=44")
         .write_stdin(msg)
         .assert()
@@ -26,11 +60,11 @@ fn decodes_single_message_from_stdin() {
 #[test]
 fn validation_reports_missing_fields() {
     let msg = fix_message(""); // missing MsgType intentionally
-    cargo_bin_cmd!("fixdecoder")
+    fixdecoder()
         .args(["--fix=44", "--validate"])
         .write_stdin(msg)
         .assert()
-        .success()
+        .code(2)
         .stdout(contains("Line 1:").and(contains("MsgType").and(contains("Missing"))));
 }
 
@@ -39,7 +73,7 @@ fn decodes_message_from_file_path() {
     let mut file = NamedTempFile::new().expect("temp file");
     let msg = fix_message("35=0");
     write!(file, "{msg}").expect("write temp");
-    cargo_bin_cmd!("fixdecoder")
+    fixdecoder()
         .args(["--fix=44"])
         .arg(file.path())
         .assert()
@@ -54,7 +88,7 @@ fn summary_mode_outputs_order_summary() {
     let msg1 = format!("8=FIX.4.4{soh}9=005{soh}35=8{soh}37=O1{soh}11=C1{soh}10=000{soh}\n");
     let msg2 = format!("8=FIX.4.4{soh}9=005{soh}35=8{soh}37=O1{soh}11=C1{soh}10=000{soh}\n");
     write!(file, "{msg1}{msg2}").expect("write temp");
-    cargo_bin_cmd!("fixdecoder")
+    fixdecoder()
         .args(["--fix=44", "--summary"])
         .arg(file.path())
         .assert()
@@ -65,13 +99,2703 @@ fn summary_mode_outputs_order_summary() {
 }
 
 #[test]
+fn split_output_writes_files_per_message_or_per_order_and_keeps_stdout_quiet() {
+    let log = write_log(&[
+        fix_message("35=D\u{1}11=A1\u{1}55=X\u{1}54=1\u{1}38=100\u{1}40=1\u{1}"),
+        fix_message("35=0\u{1}"),
+        fix_message("35=8\u{1}37=O1\u{1}11=A1\u{1}17=E1\u{1}150=0\u{1}39=0\u{1}"),
+        fix_message("35=G\u{1}11=A2\u{1}41=A1\u{1}38=200\u{1}"),
+        fix_message("35=8\u{1}37=O1\u{1}11=A2\u{1}17=E2\u{1}150=5\u{1}39=5\u{1}"),
+        fix_message("35=D\u{1}11=B/1\u{1}55=Y\u{1}54=2\u{1}38=5\u{1}40=1\u{1}"),
+    ]);
+    let split = |args: &[&str]| {
+        let dir = tempfile::tempdir().unwrap();
+        let output = fixdecoder()
+            .arg("--fix=44")
+            .arg(format!("--split-output={}", dir.path().display()))
+            .args(args)
+            .arg(log.path())
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        assert!(output.stdout.is_empty(), "{:?}", output.stdout);
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        assert!(stderr.contains("file(s) to"), "{stderr}");
+        dir
+    };
+    let names = |dir: &tempfile::TempDir| {
+        let mut names: Vec<String> = std::fs::read_dir(dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect();
+        names.sort();
+        names
+    };
+
+    let per_message = split(&[]);
+    assert_eq!(
+        names(&per_message),
+        [
+            "000001_D.txt",
+            "000002_0.txt",
+            "000003_8.txt",
+            "000004_G.txt",
+            "000005_8.txt",
+            "000006_D.txt"
+        ]
+    );
+    let heartbeat = std::fs::read_to_string(per_message.path().join("000002_0.txt")).unwrap();
+    assert!(heartbeat.contains("35 (MsgType):"), "{heartbeat}");
+    assert!(!heartbeat.contains('\u{1b}'), "files are not coloured");
+
+    let per_order = split(&["--summary", "--split-mode=per-order"]);
+    assert_eq!(names(&per_order), ["A1.txt", "B_1.txt"]);
+    let order = std::fs::read_to_string(per_order.path().join("A1.txt")).unwrap();
+    let timeline = order.find("Timeline:").expect("summary block first");
+    let first_message = order.find("BeginString").unwrap();
+    assert!(timeline < first_message, "{order}");
+    assert_eq!(order.matches("BeginString").count(), 4, "{order}");
+    for id in ["A2", "E2"] {
+        assert!(order.contains(id), "{id} routed to A1: {order}");
+    }
+    assert!(!order.contains("HEARTBEAT"), "{order}");
+
+    fixdecoder()
+        .args(["--split-output=unused", "--split-mode=per-order"])
+        .arg(log.path())
+        .assert()
+        .failure()
+        .stderr(contains("--split-mode=per-order requires --summary"));
+}
+
+#[test]
+fn summary_diff_shows_what_a_cancel_replace_changed() {
+    let log = write_log(&[
+        fix_message("35=D\u{0001}11=A\u{0001}55=X\u{0001}38=100\u{0001}44=1.10\u{0001}"),
+        fix_message("35=G\u{0001}11=B\u{0001}41=A\u{0001}38=150\u{0001}44=1.12\u{0001}"),
+    ]);
+    let run = |extra: &[&str]| {
+        let output = fixdecoder()
+            .args([
+                "--fix=44",
+                "--colour=no",
+                "--summary",
+                "--summary-keep-open",
+            ])
+            .args(extra)
+            .arg(log.path())
+            .output()
+            .expect("run fixdecoder");
+        assert!(output.status.success());
+        String::from_utf8(output.stdout).unwrap()
+    };
+
+    let all = run(&["--summary-diff"]);
+    assert!(all.contains("38:100→150 44:1.10→1.12"), "{all}");
+    let qty_only = run(&["--summary-diff", "--summary-diff-tags=38"]);
+    assert!(qty_only.contains("38:100→150"), "{qty_only}");
+    assert!(!qty_only.contains("44:1.10"), "{qty_only}");
+    assert!(!run(&[]).contains("→"));
+}
+
+#[test]
+#[cfg(feature = "dict-fix50sp1")]
 fn override_is_honoured_with_fallback() {
     let soh = '\u{0001}';
     let msg = format!("8=FIXT.1.1{soh}9=005{soh}35=0{soh}1128=8{soh}10=000{soh}\n");
-    cargo_bin_cmd!("fixdecoder")
+    fixdecoder()
         .args(["--fix=44"])
         .write_stdin(msg)
         .assert()
         .success()
         .stdout(contains("ApplVerID"));
 }
+
+#[test]
+fn count_only_matches_full_decode_counts() {
+    let mut file = NamedTempFile::new().expect("temp file");
+    let soh = '\u{0001}';
+    for i in 0..200 {
+        let mt = ["D", "8", "0", "8"][i % 4];
+        writeln!(
+            file,
+            "noise {i} 8=FIX.4.4{soh}9=005{soh}35={mt}{soh}37=O{i}{soh}11=C{i}{soh}10=000{soh}"
+        )
+        .expect("write temp");
+    }
+    writeln!(file, "8=FIX.4.2{soh}9=005{soh}35=0{soh}10=000{soh}").expect("write temp");
+
+    let counts_table = |args: &[&str]| {
+        let output = fixdecoder()
+            .args(args)
+            .arg(file.path())
+            .output()
+            .expect("run fixdecoder");
+        assert!(output.status.success());
+        let stdout = String::from_utf8(output.stdout).expect("utf8 output");
+        let start = stdout.find("Message Type").expect("counts table present");
+        stdout[start..].to_string()
+    };
+
+    let full = counts_table(&["--colour=no"]);
+    let fast = counts_table(&["--colour=no", "--count-only"]);
+    assert_eq!(full, fast);
+    assert!(
+        fast.contains("100"),
+        "ExecutionReport count expected: {fast}"
+    );
+}
+
+#[test]
+fn counts_wide_adds_share_and_bytes_and_counts_sort_orders_rows() {
+    let soh = '\u{0001}';
+    let log = write_log(&[
+        format!("8=FIX.4.4{soh}9=5{soh}35=0{soh}10=000{soh}\n"),
+        format!("8=FIX.4.4{soh}9=5{soh}35=0{soh}10=000{soh}\n"),
+        format!("8=FIX.4.4{soh}9=5{soh}35=0{soh}10=000{soh}\n"),
+        format!("8=FIX.4.4{soh}9=5{soh}35=D{soh}11=A{soh}38=100{soh}10=000{soh}\n"),
+        format!("8=FIX.4.4{soh}9=5{soh}35=D{soh}11=B{soh}38=100{soh}10=000{soh}\n"),
+        format!(
+            "8=FIX.4.4{soh}9=5{soh}35=8{soh}58={}{soh}10=000{soh}\n",
+            "x".repeat(100)
+        ),
+    ]);
+    let table = |args: &[&str]| {
+        let output = fixdecoder()
+            .args(["--colour=no", "--count-only"])
+            .args(args)
+            .arg(log.path())
+            .output()
+            .expect("run fixdecoder");
+        assert!(output.status.success());
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        let start = stdout.find("Message Type").expect("counts table present");
+        stdout[start..]
+            .lines()
+            .skip(1)
+            .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+        table(&[]),
+        [
+            "0 (HEARTBEAT) 3",
+            "8 (EXECUTION_REPORT) 1",
+            "D (ORDER_SINGLE) 2"
+        ]
+    );
+    assert_eq!(
+        table(&["--counts-wide"]),
+        [
+            "0 (HEARTBEAT) 3 50.0% 78 26.0",
+            "8 (EXECUTION_REPORT) 1 16.7% 130 130.0",
+            "D (ORDER_SINGLE) 2 33.3% 76 38.0",
+            "Total 6 100.0% 284 47.3"
+        ]
+    );
+    let first_column = |args: &[&str]| -> Vec<String> {
+        table(args)
+            .iter()
+            .map(|row| row.split(' ').next().unwrap().to_string())
+            .collect()
+    };
+    assert_eq!(
+        first_column(&["--counts-wide", "--counts-sort=count"]),
+        ["0", "D", "8", "Total"]
+    );
+    assert_eq!(
+        first_column(&["--counts-wide", "--counts-sort=bytes"]),
+        ["8", "0", "D", "Total"]
+    );
+
+    fixdecoder()
+        .args(["--counts-sort=size"])
+        .arg(log.path())
+        .assert()
+        .code(1)
+        .stderr(contains("invalid value for --counts-sort: size"));
+}
+
+#[test]
+fn binary_framing_between_messages_is_skipped() {
+    let soh = '\u{0001}';
+    let [first, second, third] = [1, 2, 3].map(|seq| valid_heartbeat(seq).trim_end().to_string());
+    let mut log = NamedTempFile::new().expect("temp file");
+    write!(log, "{first}{second}8=FIX.4.4{soh}9=40{soh}35=D{soh}").expect("write temp");
+    log.write_all(&[0xff, 0xfe]).expect("write temp");
+    log.write_all(&(third.len() as u32).to_le_bytes())
+        .expect("write temp");
+    writeln!(log, "{third}").expect("write temp");
+
+    let output = fixdecoder()
+        .args(["--validate", "--colour=no"])
+        .arg(log.path())
+        .output()
+        .expect("run fixdecoder");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "{stdout}");
+    assert!(
+        stdout.contains("Validated 3 message(s): 0 invalid"),
+        "{stdout}"
+    );
+}
+
+#[test]
+fn validate_exit_codes_follow_contract() {
+    let clean = write_log(&[valid_heartbeat(1), valid_heartbeat(2)]);
+    let dirty = write_log(&[valid_heartbeat(1), fix_message("35=0"), fix_message("35=0")]);
+
+    fixdecoder()
+        .args(["--fix=44", "--validate", "--colour=no"])
+        .arg(clean.path())
+        .assert()
+        .code(0)
+        .stdout(contains("Validated 2 message(s): 0 invalid"));
+
+    fixdecoder()
+        .args(["--fix=44", "--validate", "--colour=no"])
+        .arg(dirty.path())
+        .assert()
+        .code(2)
+        .stdout(contains("Validated 3 message(s): 2 invalid"));
+
+    fixdecoder()
+        .args(["--fix=44", "--validate", "--fail-on=none"])
+        .arg(dirty.path())
+        .assert()
+        .code(0);
+
+    fixdecoder()
+        .args(["--fix=44", "--validate", "--fail-on=3"])
+        .arg(dirty.path())
+        .assert()
+        .code(0);
+
+    fixdecoder()
+        .args(["--fix=44", "--validate", "--fail-on=2"])
+        .arg(dirty.path())
+        .assert()
+        .code(2);
+}
+
+#[test]
+fn max_decimals_flags_over_precise_prices() {
+    let log = write_log(&[valid_heartbeat_with(1, "44=1.123456789012\u{0001}")]);
+
+    fixdecoder()
+        .args(["--fix=44", "--colour=no", "--validate", "--max-decimals=8"])
+        .arg(log.path())
+        .assert()
+        .code(2)
+        .stdout(contains(
+            "Too many decimal places: '1.123456789012' has 12, limit is 8",
+        ));
+
+    fixdecoder()
+        .args(["--fix=44", "--validate"])
+        .arg(log.path())
+        .assert()
+        .success()
+        .stdout(contains("Validated 1 message(s): 0 invalid"));
+
+    fixdecoder()
+        .args(["--fix=44", "--validate", "--max-decimals=lots"])
+        .arg(log.path())
+        .assert()
+        .code(1)
+        .stderr(contains("invalid value for --max-decimals: lots"));
+}
+
+#[test]
+fn check_clock_skew_flags_transact_time_drift_per_session() {
+    let log = write_log(&[
+        valid_heartbeat_with(1, "60=20231231-23:59:58.5\u{0001}"),
+        valid_heartbeat_with(2, "60=20231231-23:50:00\u{0001}"),
+        valid_heartbeat_with(3, "60=23:59:59\u{0001}"),
+        valid_heartbeat(4),
+    ]);
+    let output = fixdecoder()
+        .args([
+            "--fix=44",
+            "--colour=no",
+            "--validate",
+            "--check-clock-skew=5",
+        ])
+        .arg(log.path())
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(2));
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(
+        stdout.contains("TransactTime(60) is 10m00.000s behind SendingTime(52), limit is 5.000s"),
+        "{stdout}"
+    );
+    assert_eq!(
+        stdout.matches("behind SendingTime(52)").count(),
+        1,
+        "{stdout}"
+    );
+    let report = &stdout[stdout.find("Clock Skew (limit 5.000s)").unwrap()..];
+    let row = report
+        .lines()
+        .find(|line| line.contains("AAA -> BBB"))
+        .unwrap();
+    let cells: Vec<&str> = row
+        .split("  ")
+        .map(str::trim)
+        .filter(|c| !c.is_empty())
+        .collect();
+    assert_eq!(cells, ["AAA -> BBB", "2", "10m00.000s", "10m00.000s", "1"]);
+    assert!(
+        report.contains("Skipped 1 message(s) with an unparseable"),
+        "{report}"
+    );
+
+    fixdecoder()
+        .args(["--fix=44", "--validate", "--check-clock-skew=soon"])
+        .arg(log.path())
+        .assert()
+        .code(1)
+        .stderr(contains("invalid value for --check-clock-skew: soon"));
+}
+
+#[test]
+fn validate_session_flags_broken_cancel_replace_chains() {
+    let soh = '\u{0001}';
+    let client = |body: &str| fix_message(&format!("49=BUY{soh}56=SELL{soh}{body}"));
+    let venue = |body: &str| fix_message(&format!("49=SELL{soh}56=BUY{soh}{body}"));
+    let log = write_log(&[
+        client("35=D\u{0001}11=A\u{0001}38=100\u{0001}"),
+        venue("35=8\u{0001}11=A\u{0001}150=F\u{0001}39=2\u{0001}14=120\u{0001}"),
+        client("35=G\u{0001}11=B\u{0001}41=Z\u{0001}38=200\u{0001}"),
+        venue("35=8\u{0001}11=A\u{0001}150=0\u{0001}39=0\u{0001}14=120\u{0001}"),
+    ]);
+    let run = |extra: &[&str]| {
+        let output = fixdecoder()
+            .args(["--fix=44", "--colour=no", "--validate"])
+            .args(extra)
+            .arg(log.path())
+            .output()
+            .expect("run fixdecoder");
+        String::from_utf8(output.stdout).unwrap()
+    };
+
+    let plain = run(&[]);
+    assert!(!plain.contains("Order Chain Findings"), "{plain}");
+    let stdout = run(&["--validate-session", "--fill-tolerance=10"]);
+    for finding in [
+        "CumQty(14) 120 exceeds OrderQty(38) 100 by more than the tolerance of 10",
+        "OrigClOrdID(41) Z matches no ClOrdID seen earlier in the session",
+        "OrdStatus(39) New after the order was already Filled",
+    ] {
+        assert!(stdout.contains(finding), "{finding}: {stdout}");
+    }
+    let table = &stdout[stdout.find("Order Chain Findings").unwrap()..];
+    let row = table
+        .lines()
+        .find(|line| line.contains("overfill"))
+        .unwrap();
+    let cells: Vec<&str> = row
+        .split("  ")
+        .map(str::trim)
+        .filter(|c| !c.is_empty())
+        .collect();
+    assert_eq!(cells, ["BUY <-> SELL", "overfill", "2", "2"]);
+    assert!(!run(&["--validate-session", "--fill-tolerance=20"]).contains("exceeds OrderQty"));
+
+    fixdecoder()
+        .args([
+            "--fix=44",
+            "--validate",
+            "--validate-session",
+            "--fill-tolerance=-1",
+        ])
+        .arg(log.path())
+        .assert()
+        .code(1)
+        .stderr(contains("invalid value for --fill-tolerance: -1"));
+}
+
+#[test]
+fn tag_usage_counts_tags_per_msg_type_and_flags_unknown_ones() {
+    let log = write_log(&[
+        valid_heartbeat_with(1, "9999=1\u{0001}"),
+        valid_heartbeat(2),
+        valid_heartbeat_with(3, "112=T1\u{0001}"),
+    ]);
+    let output = fixdecoder()
+        .args(["--fix=44", "--colour=no", "--tag-usage"])
+        .arg(log.path())
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(
+        !stdout.contains("BeginString ="),
+        "no decoded output: {stdout}"
+    );
+    let table = &stdout[stdout
+        .find("Tag Usage (10 tag(s) in 3 message(s))")
+        .unwrap()..];
+    let row = |tag: &str| -> Vec<&str> {
+        table
+            .lines()
+            .find(|line| line.trim_start().starts_with(&format!("{tag} ")))
+            .unwrap()
+            .split("  ")
+            .map(str::trim)
+            .filter(|cell| !cell.is_empty())
+            .collect()
+    };
+    assert_eq!(row("35"), ["35", "MsgType", "3", "0 3 (100%)"]);
+    assert_eq!(row("112"), ["112", "TestReqID", "1", "0 1 (33%)"]);
+    assert_eq!(row("9999"), ["9999", "-", "1", "0 1 (33%)", "unknown"]);
+
+    fixdecoder()
+        .args(["--tag-usage", "--validate"])
+        .arg(log.path())
+        .assert()
+        .failure()
+        .stderr(contains("cannot be used with"));
+}
+
+#[test]
+fn repeated_warnings_print_once_unless_verbose_or_quiet() {
+    let mut xml = NamedTempFile::new().unwrap();
+    write!(
+        xml,
+        "<fix type='FIX' major='4' minor='4' servicepack='0'>\
+         <header><field name='BeginString' required='Y'/><field name='MsgType' required='Y'/></header>\
+         <trailer><field name='CheckSum' required='Y'/></trailer>\
+         <messages>\
+         <message name='Heartbeat' msgtype='0' msgcat='admin'><component name='Loop'/></message>\
+         <message name='TestRequest' msgtype='1' msgcat='admin'><component name='Loop'/></message>\
+         </messages>\
+         <components><component name='Loop'><field name='Text'/><component name='Loop'/></component></components>\
+         <fields>\
+         <field number='8' name='BeginString' type='STRING'/>\
+         <field number='10' name='CheckSum' type='STRING'/>\
+         <field number='35' name='MsgType' type='STRING'/>\
+         <field number='58' name='Text' type='STRING'/>\
+         </fields></fix>"
+    )
+    .unwrap();
+    let log = write_log(&[valid_heartbeat(1), valid_heartbeat(2)]);
+    let stderr = |flag: Option<&str>| {
+        let output = fixdecoder()
+            .arg(format!("--xml={}", xml.path().display()))
+            .args(flag)
+            .arg(log.path())
+            .output()
+            .unwrap();
+        String::from_utf8(output.stderr).unwrap()
+    };
+    let recursion = "warning: recursive component detected at Loop, skipping";
+
+    let once = stderr(None);
+    assert_eq!(once.matches(recursion).count(), 2, "{once}");
+    assert!(
+        once.contains(&format!("warning: suppressed 2 repeat(s) of: {recursion}")),
+        "{once}"
+    );
+
+    let every = stderr(Some("--verbose-warnings"));
+    assert_eq!(every.matches(recursion).count(), 3, "{every}");
+    assert!(!every.contains("suppressed"), "{every}");
+
+    let quiet = stderr(Some("--quiet"));
+    assert!(!quiet.contains("warning:"), "{quiet}");
+}
+
+#[test]
+fn quiet_silences_the_detected_delimiter_notice() {
+    let log = write_log(&[valid_heartbeat(1).replace('\u{0001}', "|")]);
+    let stderr = |flag: Option<&str>| {
+        let output = fixdecoder()
+            .args(["--fix=44", "--colour=no"])
+            .args(flag)
+            .arg(log.path())
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        String::from_utf8(output.stderr).unwrap()
+    };
+    let notice = "Detected '|' as the FIX field delimiter; decoding it as SOH\n";
+    assert_eq!(stderr(None), notice);
+    assert_eq!(stderr(Some("--quiet")), "");
+}
+
+#[test]
+fn histogram_counts_each_value_of_every_requested_tag() {
+    let output = fixdecoder()
+        .args(["--fix=44", "--colour=no", "--histogram"])
+        .args(["--tag", "150", "--tag", "39", "--exclude-msgtype=5"])
+        .arg("tests/fixtures/order_lifecycle.log")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(
+        !stdout.contains("BeginString ="),
+        "no decoded output: {stdout}"
+    );
+    let table = &stdout[stdout
+        .find("Tag Histogram 39 OrdStatus (3 value(s) in 9 message(s))")
+        .unwrap()..];
+    let rows: Vec<Vec<&str>> = table
+        .lines()
+        .skip(3)
+        .take(4)
+        .map(|line| {
+            line.split("  ")
+                .map(str::trim)
+                .filter(|cell| !cell.is_empty())
+                .collect()
+        })
+        .collect();
+    assert_eq!(rows[0], ["0", "NEW", "2", "50.0%"], "{stdout}");
+    assert_eq!(rows[2], ["4", "CANCELED", "1", "25.0%"], "{stdout}");
+    assert_eq!(rows[3], ["5 message(s) without tag 39"], "{stdout}");
+    assert!(stdout.contains("Tag Histogram 150 ExecType (4 value(s)"));
+
+    fixdecoder()
+        .args(["--tag", "150", "--tag", "39"])
+        .assert()
+        .failure()
+        .stderr(contains("unless --histogram"));
+}
+
+#[test]
+fn stdin_format_splits_nul_and_length_prefixed_records() {
+    let records = [valid_heartbeat(1), valid_heartbeat(2)].map(|line| line.trim_end().to_string());
+    let decode = |format: &str, input: Vec<u8>| {
+        let output = fixdecoder()
+            .args(["--fix=44", "--colour=no", "--validate"])
+            .arg(format!("--stdin-format={format}"))
+            .write_stdin(input)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{format}: {output:?}");
+        String::from_utf8(output.stdout).unwrap()
+    };
+    let lines = decode("lines", records.join("\n").into_bytes());
+    assert!(lines.contains("Validated 2 message(s)"), "{lines}");
+    let nul: Vec<u8> = records
+        .iter()
+        .flat_map(|r| format!("{r}\0").into_bytes())
+        .collect();
+    assert_eq!(decode("nul", nul), lines);
+    let len32: Vec<u8> = records
+        .iter()
+        .flat_map(|r| [&(r.len() as u32).to_be_bytes()[..], r.as_bytes()].concat())
+        .collect();
+    assert_eq!(decode("len32", len32), lines);
+
+    fixdecoder()
+        .args(["--stdin-format=csv"])
+        .write_stdin("")
+        .assert()
+        .code(1)
+        .stderr(contains("invalid value for --stdin-format: csv"));
+}
+
+#[test]
+fn error_locations_point_at_the_offending_field() {
+    fixdecoder()
+        .args(["--fix=44", "--validate", "--error-locations", "--colour=no"])
+        .write_stdin(fix_message("35=0\u{0001}"))
+        .assert()
+        .code(2)
+        .stdout(
+            contains("Checksum mismatch: got 000, expected 003 (field #4, bytes 21..27)").and(
+                contains("Missing required tag 49 (SenderCompID) (not present in message)"),
+            ),
+        );
+}
+
+#[test]
+fn missing_file_and_bad_usage_exit_with_one() {
+    fixdecoder()
+        .args(["--fix=44", "/nonexistent/fix.log"])
+        .assert()
+        .code(1);
+
+    fixdecoder().args(["--fail-on=any"]).assert().code(1);
+}
+
+#[test]
+fn light_theme_and_no_color() {
+    let msg = fix_message("35=0");
+    fixdecoder()
+        .args(["--fix=44", "--colour=yes", "--theme=light"])
+        .write_stdin(msg.clone())
+        .assert()
+        .success()
+        .stdout(contains("\u{001b}[38;5;25m").and(contains("\u{001b}[38;5;81m").not()));
+
+    fixdecoder()
+        .args(["--fix=44", "--theme=light"])
+        .env("NO_COLOR", "1")
+        .write_stdin(msg.clone())
+        .assert()
+        .success()
+        .stdout(contains("\u{001b}[").not());
+
+    fixdecoder()
+        .args(["--fix=44", "--colour=yes"])
+        .env("FIXDECODER_COLOURS", "tag=36")
+        .write_stdin(msg)
+        .assert()
+        .success()
+        .stdout(contains("\u{001b}[36m"));
+}
+
+#[test]
+fn substitute_delimiters_are_detected_without_flags() {
+    for delim in ["|", "\t", ";"] {
+        let msg = valid_heartbeat(3).replace('\u{0001}', delim);
+        let log = write_log(&[format!("2024-01-01 INFO recv {}", msg.trim_end())]);
+
+        fixdecoder()
+            .args(["--fix=44", "--colour=no"])
+            .arg(log.path())
+            .assert()
+            .success()
+            .stdout(contains("HEARTBEAT").and(contains(format!("{delim}35=0{delim}"))))
+            .stderr(contains("as the FIX field delimiter"));
+
+        fixdecoder()
+            .args(["--fix=44", "--validate", "--colour=no"])
+            .arg(log.path())
+            .assert()
+            .success()
+            .stdout(contains("Validated 1 message(s): 0 invalid"));
+    }
+}
+
+#[test]
+fn unescape_decodes_json_and_caret_notation_logs() {
+    let fixtures = [
+        ("tests/fixtures/unescape_json.log", "EXECUTION_REPORT"),
+        ("tests/fixtures/unescape_syslog.log", "HEARTBEAT"),
+    ];
+    for (path, msg_type) in fixtures {
+        fixdecoder()
+            .args(["--fix=44", "--unescape", "--colour=no", path])
+            .assert()
+            .success()
+            .stdout(contains(msg_type).and(contains("BeginString")));
+
+        fixdecoder()
+            .args(["--fix=44", "--unescape", "--validate", "--colour=no", path])
+            .assert()
+            .success()
+            .stdout(contains("Validated 1 message(s): 0 invalid"));
+
+        fixdecoder()
+            .args(["--fix=44", "--colour=no", path])
+            .assert()
+            .success()
+            .stdout(contains("BeginString").not());
+    }
+}
+
+#[test]
+fn validate_summary_tallies_errors_by_category() {
+    let output = fixdecoder()
+        .args([
+            "--fix=44",
+            "--validate",
+            "--validate-summary",
+            "--colour=no",
+            "tests/fixtures/validate_summary.log",
+        ])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(2));
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let table = &stdout[stdout.find("Validation Summary").unwrap()..];
+    let rows: Vec<Vec<&str>> = table
+        .lines()
+        .skip(3)
+        .take_while(|line| !line.trim().is_empty())
+        .map(|line| {
+            line.split("  ")
+                .map(str::trim)
+                .filter(|c| !c.is_empty())
+                .collect()
+        })
+        .collect();
+    let expected = [
+        ["checksum", "1", "1 (0)", "1"],
+        ["body length", "1", "1 (0)", "2"],
+        ["missing required", "1", "1 (D)", "3"],
+        ["bad enum", "1", "1 (D)", "4"],
+        ["bad type", "1", "1 (D)", "5"],
+    ];
+    assert_eq!(rows[..5], expected, "{table}");
+    let categories: Vec<&str> = rows.iter().map(|row| row[0]).collect();
+    assert_eq!(
+        categories[5..],
+        ["ordering", "duplicate", "group"],
+        "{table}"
+    );
+    assert_eq!(rows[6][1..], ["1", "1 (D)", "7"], "{table}");
+    assert_eq!(rows[7][1..], ["1", "1 (D)", "8"], "{table}");
+
+    fixdecoder()
+        .args(["--validate-summary", "tests/fixtures/validate_summary.log"])
+        .assert()
+        .failure()
+        .stderr(contains("--validate"));
+}
+
+#[test]
+fn raw_annotate_marks_bad_values_under_the_raw_message() {
+    let soh = '\u{0001}';
+    let log = write_log(&[
+        valid_heartbeat(1).replace(&format!("{soh}10="), &format!("{soh}112=Zürich{soh}10="))
+    ]);
+    let output = fixdecoder()
+        .args([
+            "--validate",
+            "--raw-annotate",
+            "--delimiter=|",
+            "--colour=no",
+        ])
+        .arg(log.path())
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(2));
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let lines: Vec<&str> = stdout.lines().collect();
+    let heading = format!("{} line 1:", log.path().display());
+    let at = lines.iter().position(|line| *line == heading).unwrap();
+    let (raw, carets) = (lines[at + 1], lines[at + 2]);
+    assert!(raw.ends_with("|112=Zürich|10=122|"), "{raw}");
+    let columns: Vec<usize> = carets.match_indices('^').map(|(col, _)| col).collect();
+    let checksum = raw.chars().count() - "122|".len();
+    assert_eq!(
+        columns,
+        [raw.find("|9=").unwrap() + 3, checksum],
+        "{stdout}"
+    );
+    assert!(
+        stdout.contains("  1. 9 (BodyLength): BodyLength mismatch: got 45, expected 57"),
+        "{stdout}"
+    );
+    assert!(stdout.contains("  2. 10 (CheckSum): Checksum mismatch: got 122"));
+}
+
+#[test]
+#[cfg(all(feature = "dict-fix42", feature = "dict-fix50sp2"))]
+fn fix_map_decodes_each_session_with_its_own_dictionary() {
+    let output = fixdecoder()
+        .args([
+            "--colour=no",
+            "--fix-map",
+            "*:LSE=FIX50SP2",
+            "tests/fixtures/fix_map_sessions.log",
+        ])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let appl_ver_ids: Vec<&str> = stdout
+        .lines()
+        .filter(|line| line.contains("SenderCompID") || line.trim_start().starts_with("1128 "))
+        .map(str::trim)
+        .collect();
+    assert_eq!(
+        appl_ver_ids,
+        [
+            "49 (SenderCompID): BANKX",
+            "1128 (1128):         9",
+            "49 (SenderCompID): US",
+            "1128 (ApplVerID):    9 (FIX50SP2)",
+            "49 (SenderCompID): BANKX",
+            "1128 (1128):         9",
+            "49 (SenderCompID): US",
+            "1128 (ApplVerID):    9 (FIX50SP2)",
+        ]
+    );
+    let note = &stdout[stdout.find("Dictionaries by session:").unwrap()..];
+    let sessions: Vec<Vec<&str>> = note
+        .lines()
+        .skip(1)
+        .map(|line| line.split_whitespace().collect())
+        .collect();
+    assert_eq!(
+        sessions,
+        [
+            ["BANKX", "->", "US", "FIX44"],
+            ["US", "->", "LSE", "FIX50SP2"]
+        ]
+    );
+
+    fixdecoder()
+        .args([
+            "--fix-map",
+            "BANKX=FIX42",
+            "tests/fixtures/fix_map_sessions.log",
+        ])
+        .assert()
+        .code(1)
+        .stderr(contains("expected SENDER:TARGET=KEY"));
+}
+
+#[test]
+#[cfg(feature = "dict-fix42")]
+fn find_searches_the_selected_dictionary() {
+    fixdecoder()
+        .args(["--fix=42", "--find=EXECT", "--colour=no"])
+        .assert()
+        .success()
+        .stdout(contains(
+            "Fields (2):\n      20: ExecTransType (CHAR)\n     150: ExecType (CHAR)\n",
+        ));
+
+    fixdecoder()
+        .args(["--find=leg", "--limit=2", "--colour=no"])
+        .assert()
+        .success()
+        .stdout(contains("Fields (2):").and(contains("... 9 more match(es)")));
+
+    fixdecoder()
+        .args(["--find=nosuchthing"])
+        .assert()
+        .success()
+        .stdout(contains(
+            "No fields, messages, components or enums match: nosuchthing",
+        ));
+}
+
+#[test]
+fn max_value_width_truncates_display_only() {
+    let text = "x".repeat(500);
+    let log = write_log(&[valid_heartbeat_with(1, &format!("58={text}\u{0001}"))]);
+
+    fixdecoder()
+        .args([
+            "--fix=44",
+            "--colour=no",
+            "--max-value-width=10",
+            "--width=24",
+        ])
+        .arg(log.path())
+        .assert()
+        .success()
+        .stdout(
+            contains("(Text): xxxxxxxxxx… (+490 bytes)")
+                .and(contains(format!("\n{}\n", "=".repeat(24))))
+                .and(contains(format!("{}\n", "=".repeat(25))).not()),
+        );
+
+    fixdecoder()
+        .args([
+            "--fix=44",
+            "--colour=no",
+            "--max-value-width=10",
+            "--validate",
+        ])
+        .arg(log.path())
+        .assert()
+        .success()
+        .stdout(contains("Validated 1 message(s): 0 invalid"));
+
+    fixdecoder()
+        .args(["--fix=44", "--max-value-width=0"])
+        .arg(log.path())
+        .assert()
+        .failure()
+        .stderr(contains("expected a positive number"));
+}
+
+#[test]
+fn session_summary_reports_admin_traffic() {
+    let soh = '\u{0001}';
+    let admin = |fields: &str| {
+        format!(
+            "8=FIX.4.4{soh}9=005{soh}{}{soh}10=000{soh}\n",
+            fields.replace('|', "\u{1}")
+        )
+    };
+    let log = write_log(&[
+        admin("35=A|49=AAA|56=BBB|34=1"),
+        admin("35=1|49=AAA|56=BBB|34=2|52=20240101-09:00:30.000|112=T1"),
+        admin("35=0|49=BBB|56=AAA|34=2|52=20240101-09:00:30.120|112=T1"),
+        admin("35=2|49=AAA|56=BBB|34=3|7=3|16=0"),
+        admin("35=4|49=BBB|56=AAA|34=3|123=Y|36=6"),
+    ]);
+
+    fixdecoder()
+        .args(["--fix=44", "--colour=no", "--session-summary"])
+        .arg(log.path())
+        .assert()
+        .success()
+        .stdout(
+            contains("Session Summary (1 session(s))")
+                .and(contains("AAA <-> BBB"))
+                .and(contains("avg 0.120s max 0.120s (1)"))
+                .and(contains("ResendRequest: AAA -> BBB 3-infinity"))
+                .and(contains("SequenceReset: GapFill BBB -> AAA 3 -> 6"))
+                .and(contains("TestReqID")),
+        );
+}
+
+#[test]
+fn join_lines_rebuilds_messages_wrapped_across_lines() {
+    let broken = fix_message("35=0");
+    let valid = valid_heartbeat(7);
+    let (b1, rest) = broken.split_at(12);
+    let (b2, b3) = rest.split_at(6);
+    let (v1, rest) = valid.split_at(20);
+    let (v2, v3) = rest.split_at(15);
+    let log = write_log(&[
+        "2024-01-01 INFO starting session\n".to_string(),
+        format!("2024-01-01 INFO sent {b1}\n"),
+        format!("{b2}\n"),
+        b3.to_string(),
+        "2024-01-01 WARN unrelated noise\n".to_string(),
+        format!("2024-01-01 INFO recv {v1}\n"),
+        format!("{v2}\n"),
+        v3.to_string(),
+        "2024-01-01 INFO done\n".to_string(),
+    ]);
+
+    let line = |n: usize| format!("{} line {n}:", log.path().display());
+    fixdecoder()
+        .args(["--fix=44", "--validate", "--colour=no", "--join-lines"])
+        .arg(log.path())
+        .assert()
+        .code(2)
+        .stdout(
+            contains(line(2))
+                .and(contains("Validated 2 message(s): 1 invalid"))
+                .and(contains(line(6)).not()),
+        );
+
+    fixdecoder()
+        .args(["--fix=44", "--validate", "--colour=no"])
+        .arg(log.path())
+        .assert()
+        .code(0)
+        .stdout(contains("Validated 0 message(s)"));
+
+    fixdecoder()
+        .args(["--fix=44", "--colour=no", "--join-lines"])
+        .arg(log.path())
+        .assert()
+        .success()
+        .stdout(contains("unrelated noise").and(contains("HEARTBEAT")));
+}
+
+#[test]
+fn messages_split_by_a_newline_in_text_decode_once_by_default() {
+    let path = "tests/fixtures/embedded_newline.log";
+    fixdecoder()
+        .args(["--validate", "--colour=no", path])
+        .assert()
+        .code(0)
+        .stdout(contains("Validated 3 message(s): 0 invalid"));
+    fixdecoder()
+        .args(["--colour=no", path])
+        .assert()
+        .success()
+        .stdout(contains(
+            "58 (Text):         Order rejected by venue:\\nprice outside collar",
+        ));
+
+    let file = std::fs::File::open(path).expect("fixture");
+    let texts: Vec<(usize, Option<String>)> =
+        FixMessageIter::new(BufReader::new(file), DecodeOptions::default())
+            .map(|msg| msg.expect("decoded"))
+            .map(|msg| (msg.line_number, msg.first(58).map(str::to_string)))
+            .collect();
+    assert_eq!(
+        texts,
+        [
+            (1, None),
+            (
+                2,
+                Some("Order rejected by venue:\nprice outside collar".to_string())
+            ),
+            (4, None),
+        ]
+    );
+}
+
+#[test]
+fn hide_header_and_trailer_leave_the_business_fields() {
+    let log = write_log(&[valid_heartbeat_with(4, "112=PING\u{0001}")]);
+    fixdecoder()
+        .args(["--colour=no", "--hide-header", "--hide-trailer"])
+        .arg(log.path())
+        .assert()
+        .success()
+        .stdout(
+            contains("35 (MsgType):   0")
+                .and(contains("112 (TestReqID): PING"))
+                .and(contains("(SenderCompID)").not())
+                .and(contains("(CheckSum)").not()),
+        );
+    fixdecoder()
+        .args(["--colour=no", "--hide-header"])
+        .arg(log.path())
+        .assert()
+        .success()
+        .stdout(contains("(BodyLength)").not().and(contains("(CheckSum)")));
+}
+
+#[test]
+fn tag_accepts_names_and_globs() {
+    fixdecoder()
+        .args(["--fix=44", "--colour=no", "--tag=orderqty"])
+        .assert()
+        .success()
+        .stdout(contains("38: OrderQty"));
+
+    fixdecoder()
+        .args(["--fix=44", "--colour=no", "--tag=*Px"])
+        .assert()
+        .success()
+        .stdout(contains("31: LastPx").and(contains("99: StopPx")));
+
+    fixdecoder()
+        .args(["--fix=44", "--colour=no", "--tag=RejReason"])
+        .assert()
+        .success()
+        .stdout(contains("Tag not found: RejReason").and(contains("OrdRejReason")));
+}
+
+#[test]
+fn multi_value_fields_are_described_and_validated_per_code() {
+    fixdecoder()
+        .args(["--fix=44", "--colour=no", "--tag=18", "--enum=6"])
+        .assert()
+        .success()
+        .stdout(contains("6 : PARTICIPATE_DONT_INITIATE").and(contains("NOT_HELD").not()));
+
+    let log = write_log(&[valid_heartbeat_with(1, "18=1 6\u{0001}")]);
+    fixdecoder()
+        .args(["--fix=44", "--colour=no"])
+        .arg(log.path())
+        .assert()
+        .success()
+        .stdout(contains(
+            "18 (ExecInst):     1 6 (NOT_HELD, PARTICIPATE_DONT_INITIATE)",
+        ));
+
+    fixdecoder()
+        .args(["--fix=44", "--validate"])
+        .arg(log.path())
+        .assert()
+        .success()
+        .stdout(contains("Validated 1 message(s): 0 invalid"));
+}
+
+#[test]
+fn extract_writes_raw_messages() {
+    let good = valid_heartbeat(1);
+    let bad = fix_message("35=0");
+    let log = write_log(&[
+        format!("IN {good}"),
+        "noise\n".to_string(),
+        format!("OUT {bad}"),
+    ]);
+    let dir = tempfile::tempdir().expect("temp dir");
+    let all = dir.path().join("all.fix");
+    let invalid = dir.path().join("invalid.fix");
+
+    fixdecoder()
+        .args(["--fix=44", "--validate", "--colour=no", "--extract"])
+        .arg(&all)
+        .arg("--extract-invalid")
+        .arg(&invalid)
+        .arg(log.path())
+        .assert()
+        .code(2)
+        .stdout(contains("Validated 2 message(s): 1 invalid"));
+    let read = |path: &std::path::Path| std::fs::read_to_string(path).expect("extract file");
+    assert_eq!(read(&all), format!("{good}{bad}"));
+    assert_eq!(read(&invalid), bad);
+
+    fixdecoder()
+        .args(["--fix=44", "--extract=-"])
+        .arg(log.path())
+        .assert()
+        .success()
+        .stdout(format!("{good}{bad}"));
+
+    fixdecoder()
+        .args(["--fix=44", "--extract=-", "--secret"])
+        .arg(log.path())
+        .assert()
+        .success()
+        .stdout(contains("49=AAA").not().and(contains("35=0")));
+}
+
+#[test]
+fn display_delimiter_inside_a_value_is_escaped_unless_no_escape() {
+    let msg = valid_heartbeat_with(1, "58=a|b\u{0001}");
+    let log = write_log(&[format!("IN {msg}")]);
+    let decode = |extra: &[&str]| {
+        let output = fixdecoder()
+            .args(["--fix=44", "--colour=no", "--delimiter=|"])
+            .args(extra)
+            .arg(log.path())
+            .output()
+            .expect("run fixdecoder");
+        String::from_utf8(output.stdout).expect("utf8 stdout")
+    };
+
+    let escaped = decode(&[]);
+    assert!(escaped.contains("|58=a\\|b|10="), "{escaped}");
+    assert!(
+        escaped.contains("(Text): ") && escaped.contains("a|b"),
+        "{escaped}"
+    );
+    let verbatim = decode(&["--no-escape"]);
+    assert!(verbatim.contains("|58=a|b|10="), "{verbatim}");
+
+    fixdecoder()
+        .args(["--fix=44", "--delimiter=|", "--extract=-"])
+        .arg(log.path())
+        .assert()
+        .success()
+        .stdout(msg);
+}
+
+#[test]
+fn benchmark_discards_output_and_reports_one_parseable_line() {
+    let output = fixdecoder()
+        .args([
+            "--benchmark",
+            "--validate",
+            "tests/fixtures/mixed_messages.log",
+        ])
+        .output()
+        .expect("run fixdecoder");
+    assert!(output.status.code().is_some_and(|code| code != 1));
+    let stdout = String::from_utf8(output.stdout).expect("utf8 stdout");
+    assert!(
+        !stdout.contains("(MsgType)"),
+        "decoded output leaked: {stdout}"
+    );
+
+    let line = stdout
+        .lines()
+        .find_map(|line| line.strip_prefix("benchmark: "))
+        .expect("benchmark line");
+    let report: BTreeMap<&str, &str> = line
+        .split(' ')
+        .map(|pair| pair.split_once('=').expect("key=value"))
+        .collect();
+    let number = |key: &str| report[key].parse::<f64>().expect(key);
+    assert_eq!(number("messages"), 6.0, "{line}");
+    assert_eq!(
+        number("bytes"),
+        std::fs::metadata("tests/fixtures/mixed_messages.log")
+            .unwrap()
+            .len() as f64
+    );
+    assert!(
+        number("wall_s") > 0.0 && number("msgs_per_s") > 0.0,
+        "{line}"
+    );
+    let shares: f64 = ["extract", "parse", "lookup", "validate", "render", "other"]
+        .iter()
+        .map(|phase| number(&format!("{phase}_pct")))
+        .sum();
+    assert!((shares - 100.0).abs() < 1.0, "{line}");
+    assert!(report.contains_key("peak_rss_kb"));
+}
+
+#[test]
+fn secret_format_keeps_masked_messages_valid() {
+    let log = write_log(&[valid_heartbeat(1)]);
+
+    fixdecoder()
+        .args(["--fix=44", "--secret", "--secret-format", "--validate"])
+        .arg(log.path())
+        .assert()
+        .success()
+        .stdout(contains("Validated 1 message(s): 0 invalid"));
+
+    let output = fixdecoder()
+        .args([
+            "--fix=44",
+            "--extract=-",
+            "--secret",
+            "--secret-format",
+            "--secret-key=k1",
+        ])
+        .arg(log.path())
+        .output()
+        .expect("run fixdecoder");
+    let masked = String::from_utf8(output.stdout).expect("utf8 output");
+    let sender = masked
+        .split('\u{0001}')
+        .find_map(|field| field.strip_prefix("49="))
+        .expect("SenderCompID present");
+    assert!(sender.len() == 3 && sender.bytes().all(|b| b.is_ascii_uppercase()));
+    assert_ne!(sender, "AAA");
+}
+
+#[test]
+fn generates_shell_completions_and_man_page() {
+    fixdecoder()
+        .args(["--generate-completions", "bash"])
+        .assert()
+        .success()
+        .stdout(
+            contains("--validate")
+                .and(contains("--xml"))
+                .and(contains("(branch:").not()),
+        );
+
+    fixdecoder()
+        .arg("--generate-manpage")
+        .assert()
+        .success()
+        .stdout(contains(".TH fixdecoder").and(contains("validate")));
+}
+
+#[test]
+fn browse_refuses_to_start_without_a_terminal() {
+    fixdecoder()
+        .args(["--fix=44", "--browse"])
+        .assert()
+        .code(1)
+        .stderr(contains("--browse needs an interactive terminal"));
+}
+
+#[test]
+#[cfg(not(feature = "dict-fix42"))]
+fn fix_versions_left_out_of_the_build_are_rejected() {
+    let output = fixdecoder()
+        .args(["--fix=42", "--message=D"])
+        .output()
+        .expect("run fixdecoder");
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("Invalid --fix value: 42 (available: "),
+        "{stderr}"
+    );
+    assert!(!stderr.contains("FIX42"), "{stderr}");
+}
+
+#[test]
+#[cfg(feature = "dict-fix42")]
+fn export_xml_writes_a_dictionary_that_loads_back() {
+    let output = fixdecoder()
+        .args(["--export-xml", "42"])
+        .output()
+        .expect("run fixdecoder");
+    assert!(output.status.success());
+    let xml = String::from_utf8(output.stdout).expect("utf8 output");
+    assert!(xml.starts_with("<?xml") && xml.contains("<fix type=\"FIX\" major=\"4\" minor=\"2\""));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("(branch:"));
+
+    let exported = NamedTempFile::new().expect("temp file");
+    fixdecoder()
+        .args(["--export-xml", "FIX42"])
+        .arg(exported.path())
+        .assert()
+        .success()
+        .stderr(contains("Wrote FIX42 dictionary"));
+    assert_eq!(std::fs::read_to_string(exported.path()).unwrap(), xml);
+
+    fixdecoder()
+        .args(["--fix=42", "--message=D"])
+        .arg(format!("--xml={}", exported.path().display()))
+        .assert()
+        .success()
+        .stdout(contains("ClOrdID"));
+}
+
+#[test]
+fn decode_xml_pretty_prints_fixml_in_xml_data() {
+    let fixml = r#"<FIXML><ExecRpt ExecID="E1"><Instrmt Sym="VOD.L"/></ExecRpt></FIXML>"#;
+    let extra = format!("212={}\u{0001}213={fixml}\u{0001}", fixml.len());
+    let log = write_log(&[valid_heartbeat_with(1, &extra)]);
+
+    fixdecoder()
+        .args(["--fix=44", "--colour=no", "--decode-xml"])
+        .arg(log.path())
+        .assert()
+        .success()
+        .stdout(
+            contains(format!("(XmlData):      (XML, {} bytes)", fixml.len()))
+                .and(contains("\n           <ExecRpt ExecID=\"E1\">\n"))
+                .and(contains("<Instrmt Sym=\"VOD.L\"/>")),
+        );
+
+    fixdecoder()
+        .args(["--fix=44", "--colour=no"])
+        .arg(log.path())
+        .assert()
+        .success()
+        .stdout(contains(format!("(XmlData):      {fixml}")));
+}
+
+#[test]
+fn config_file_supplies_defaults_that_flags_override() {
+    let mut config = NamedTempFile::new().expect("temp file");
+    write!(
+        config,
+        "fix = 42\ndelimiter = \"|\"\nvalidate = true\ncolor = \"no\"\n"
+    )
+    .unwrap();
+
+    fixdecoder()
+        .arg(format!("--config={}", config.path().display()))
+        .args(["--fix=50", "--dump-config"])
+        .assert()
+        .success()
+        .stdout(
+            contains("fix = \"50\"\n")
+                .and(contains("delimiter = \"|\"\n"))
+                .and(contains("validate = true\n"))
+                .and(contains("(branch:").not()),
+        )
+        .stderr(contains("warning: ignoring unknown key 'color'"));
+
+    let mut bad = NamedTempFile::new().expect("temp file");
+    writeln!(bad, "delimiter = \"ab\"").unwrap();
+    fixdecoder()
+        .arg(format!("--config={}", bad.path().display()))
+        .assert()
+        .code(1)
+        .stderr(contains("delimiter 'ab' is 2 characters; expected"));
+
+    fixdecoder()
+        .args(["--config=/nonexistent/fixdecoder.toml", "--dump-config"])
+        .assert()
+        .code(1)
+        .stderr(contains(
+            "failed to read config file /nonexistent/fixdecoder.toml",
+        ));
+}
+
+#[test]
+fn provenance_annotates_fields_inherited_from_components() {
+    let output = fixdecoder()
+        .args([
+            "--fix=44",
+            "--message=ExecutionReport",
+            "--provenance",
+            "--colour=no",
+        ])
+        .output()
+        .expect("run fixdecoder");
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("448: PartyID (STRING) [via Parties > NoPartyIDs]"),
+        "{stdout}"
+    );
+    assert!(stdout.contains("453: NoPartyIDs (NUMINGROUP) [via Parties]"));
+    assert!(stdout.contains("37: OrderID (STRING) - (Y)\n"));
+}
+
+#[test]
+fn json_flag_serialises_dictionary_entries() {
+    let output = fixdecoder()
+        .args(["--fix=44", "--message=ExecutionReport", "--json"])
+        .output()
+        .expect("run fixdecoder");
+    assert!(output.status.success());
+    let message: serde_json::Value = serde_json::from_slice(&output.stdout).expect("valid JSON");
+    assert_eq!(message["name"], "ExecutionReport");
+    assert_eq!(message["msg_type"], "8");
+    assert_eq!(message["groups"][0]["name"], "NoLegs");
+    let order_id = &message["fields"][0];
+    assert_eq!(order_id["number"], 37);
+    assert_eq!(order_id["required"], true);
+
+    let output = fixdecoder()
+        .args(["--fix=44", "--tag=18", "--json", "--pretty"])
+        .output()
+        .expect("run fixdecoder");
+    let field: serde_json::Value = serde_json::from_slice(&output.stdout).expect("valid JSON");
+    assert_eq!(field["type"], "MULTIPLEVALUESTRING");
+    assert_eq!(
+        field["values"][0],
+        serde_json::json!({"enum": "1", "description": "NOT_HELD"})
+    );
+    assert!(String::from_utf8_lossy(&output.stdout).contains("\n  \"name\": \"ExecInst\""));
+
+    fixdecoder()
+        .args(["--component=NoSuchBlock", "--json"])
+        .assert()
+        .code(1)
+        .stdout("")
+        .stderr(contains("Component not found: NoSuchBlock"));
+}
+
+#[test]
+fn seek_line_and_limit_select_a_slice_with_absolute_line_numbers() {
+    let lines: Vec<String> = (1..=6)
+        .map(|seq| fix_message(&format!("35=0\u{0001}34={seq}\u{0001}")))
+        .collect();
+    let log = write_log(&lines);
+
+    let line = |n: usize| format!("{} line {n}:", log.path().display());
+    fixdecoder()
+        .args(["--validate", "--colour=no", "--seek-line=3", "--limit=2"])
+        .arg(log.path())
+        .assert()
+        .code(2)
+        .stdout(
+            contains(line(3))
+                .and(contains(line(4)))
+                .and(contains(line(2)).not())
+                .and(contains(line(5)).not())
+                .and(contains("Validated 2 message(s): 2 invalid")),
+        );
+
+    fixdecoder()
+        .arg("--seek=10")
+        .write_stdin(lines.concat())
+        .assert()
+        .code(1)
+        .stderr(contains(
+            "--seek and --seek-line need input files; they cannot be used with stdin",
+        ));
+}
+
+#[test]
+fn enum_overlay_extends_decode_and_validation() {
+    let log = write_log(&[valid_heartbeat_with(1, "150=Z\u{0001}9001=Y\u{0001}")]);
+    let mut overlay = tempfile::Builder::new()
+        .suffix(".csv")
+        .tempfile()
+        .expect("temp file");
+    writeln!(
+        overlay,
+        "tag,enum,description\n150,Z,VENUE_PARKED\n9001,,VenueFlag\n9001,Y,YES"
+    )
+    .unwrap();
+
+    fixdecoder()
+        .args(["--validate", "--colour=no"])
+        .arg(log.path())
+        .assert()
+        .stdout(contains("Invalid enum value 'Z'").and(contains("Unknown tag 9001")));
+
+    let overlay_arg = format!("--enum-overlay={}", overlay.path().display());
+    fixdecoder()
+        .args(["--validate", "--colour=no", &overlay_arg])
+        .arg(log.path())
+        .assert()
+        .stdout(
+            contains("Invalid enum value")
+                .not()
+                .and(contains("Unknown tag").not()),
+        );
+
+    fixdecoder()
+        .args(["--colour=no", &overlay_arg])
+        .arg(log.path())
+        .assert()
+        .success()
+        .stdout(
+            contains("VENUE_PARKED")
+                .and(contains("VenueFlag"))
+                .and(contains("YES")),
+        );
+}
+
+#[test]
+fn udf_names_types_and_enumerates_custom_tags() {
+    let log = write_log(&[
+        valid_heartbeat_with(1, "20001=ABC\u{0001}20002=F\u{0001}20003=1.5\u{0001}"),
+        valid_heartbeat_with(2, "20002=Q\u{0001}20003=abc\u{0001}"),
+    ]);
+    let mut udf = NamedTempFile::new().expect("temp file");
+    writeln!(
+        udf,
+        "tag,name,type,enum,description\n\
+         20001,InternalOrderTag,STRING\n\
+         20002,DeskCode,CHAR,E,EQUITIES\n\
+         20002,DeskCode,CHAR,F,FX\n\
+         20003,Notional,AMT\n\
+         54,Direction,STRING"
+    )
+    .unwrap();
+    let udf_arg = format!("--udf={}", udf.path().display());
+
+    fixdecoder()
+        .args(["--fix=44", "--colour=no", &udf_arg])
+        .arg(log.path())
+        .assert()
+        .success()
+        .stdout(
+            contains("20001 (InternalOrderTag): ABC")
+                .and(contains("20002 (DeskCode):         F (FX)")),
+        );
+
+    let output = fixdecoder()
+        .args(["--fix=44", "--validate", "--colour=no", &udf_arg])
+        .arg(log.path())
+        .output()
+        .expect("run fixdecoder");
+    assert_eq!(output.status.code(), Some(2));
+    let stdout = String::from_utf8(output.stdout).expect("utf8 stdout");
+    assert!(stdout.contains("Invalid enum value 'Q'"), "{stdout}");
+    assert!(
+        stdout.contains("Invalid type: expected AMT, got 'abc'"),
+        "{stdout}"
+    );
+    assert!(!stdout.contains("Unknown tag"), "{stdout}");
+    assert!(
+        stdout.contains("Validated 2 message(s): 1 invalid"),
+        "{stdout}"
+    );
+    let stderr = String::from_utf8(output.stderr).expect("utf8 stderr");
+    let clash = "warning: --udf tag 54 (Direction) is already Side in FIX44; keeping the \
+                 dictionary definition (--udf-override replaces it)";
+    assert_eq!(stderr.matches(clash).count(), 1, "{stderr}");
+
+    fixdecoder()
+        .args(["--fix=44", "--udf-override", &udf_arg])
+        .arg(log.path())
+        .assert()
+        .success()
+        .stderr(contains("--udf tag 54").not());
+}
+
+#[test]
+fn warn_deprecated_reports_warnings_with_their_own_exit_code() {
+    // The embedded FIX 4.4 dictionary no longer defines ExecTransType(20), so name it
+    // with an overlay to leave the deprecation as the only finding.
+    let mut overlay = tempfile::Builder::new()
+        .suffix(".csv")
+        .tempfile()
+        .expect("temp file");
+    writeln!(overlay, "tag,enum,description\n20,,ExecTransType\n20,0,NEW").unwrap();
+    let overlay_arg = format!("--enum-overlay={}", overlay.path().display());
+    let log = write_log(&[valid_heartbeat_with(1, "20=0\u{0001}")]);
+
+    fixdecoder()
+        .args([
+            "--validate",
+            "--warn-deprecated",
+            "--colour=no",
+            &overlay_arg,
+        ])
+        .arg(log.path())
+        .assert()
+        .code(3)
+        .stdout(
+            contains("20 (ExecTransType): 0 (NEW) (deprecated, use ExecType(150))")
+                .and(contains("0 invalid, 1 with warnings")),
+        );
+
+    fixdecoder()
+        .args(["--validate", "--colour=no", &overlay_arg])
+        .arg(log.path())
+        .assert()
+        .success()
+        .stdout(contains("with warnings").not());
+
+    let raw = format!("--warn-deprecated --raw-annotate --fail-on=none {overlay_arg}");
+    fixdecoder()
+        .args(["--validate", "--colour=no"])
+        .args(raw.split(' '))
+        .arg(log.path())
+        .assert()
+        .success()
+        .stdout(contains(
+            "1. 20 (ExecTransType): ExecTransType(20) is deprecated in FIX44, use ExecType(150)",
+        ));
+
+    let invalid = write_log(&[valid_heartbeat_with(1, "20=0\u{0001}150=2\u{0001}")]);
+    fixdecoder()
+        .args([
+            "--validate",
+            "--warn-deprecated",
+            "--colour=no",
+            &overlay_arg,
+        ])
+        .arg(invalid.path())
+        .assert()
+        .code(2)
+        .stdout(contains("1 invalid, 1 with warnings"));
+}
+
+#[test]
+fn repair_fixes_length_and_checksum_and_reports_counts() {
+    let good = valid_heartbeat(1);
+    let edited = valid_heartbeat(2).replace("49=AAA", "49=AAAA");
+    let log = write_log(&[good.clone(), format!("OUT {edited}")]);
+
+    let output = fixdecoder()
+        .arg("--repair")
+        .arg(log.path())
+        .output()
+        .expect("run fixdecoder");
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).expect("utf8");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("Repaired 1 message(s), 1 already correct"),
+        "{stderr}"
+    );
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert_eq!(lines[0], good.trim_end());
+    assert!(lines[1].starts_with("8=FIX.4.4\u{0001}9=46\u{0001}35=0"));
+
+    fixdecoder()
+        .args(["--validate", "--colour=no"])
+        .write_stdin(stdout)
+        .assert()
+        .success()
+        .stdout(contains("Validated 2 message(s): 0 invalid"));
+}
+
+#[test]
+fn line_timestamps_label_errors_and_report_session_gaps() {
+    let broken = valid_heartbeat(3).replace("\u{0001}9=45", "\u{0001}9=46");
+    let log = write_log(&[
+        format!("2024-01-02T10:00:00.000Z IN {}", valid_heartbeat(1)),
+        format!("2024-01-02T10:00:00.250Z OUT {}", valid_heartbeat(2)),
+        format!("2024-01-02T10:00:07.250Z IN {broken}"),
+        format!("IN {}", valid_heartbeat(4)),
+    ]);
+
+    let output = fixdecoder()
+        .args([
+            "--validate",
+            "--colour=no",
+            "--line-timestamp=iso8601",
+            "--max-gap=5",
+        ])
+        .arg(log.path())
+        .output()
+        .expect("run fixdecoder");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains(&format!(
+            "{} line 3 [2024-01-02 10:00:07.250]: 2024-01-02T10:00:07.250Z IN 8=FIX",
+            log.path().display()
+        )),
+        "{stdout}"
+    );
+    assert!(stdout.contains("Message Gaps (1 session(s))"), "{stdout}");
+    assert!(stdout.contains("Gaps over 5.000s: 1"), "{stdout}");
+    assert!(stdout.contains("Line 3: AAA -> BBB 7.000s"), "{stdout}");
+    assert!(
+        stdout.contains("1 line(s) with FIX messages had no parseable timestamp"),
+        "{stdout}"
+    );
+}
+
+#[test]
+fn inconsistent_dictionaries_warn_at_load_and_fail_under_strict_dict() {
+    let mut xml = NamedTempFile::new().expect("temp file");
+    write!(
+        xml,
+        "<fix type='FIX' major='4' minor='4'>
+  <header><field name='BeginString' required='Y'/></header>
+  <trailer><field name='CheckSum' required='Y'/></trailer>
+  <messages><message name='Heartbeat' msgtype='0' msgcat='admin'><field name='TestReqId'/></message></messages>
+  <components/>
+  <fields>
+    <field number='8' name='BeginString' type='STRING'/>
+    <field number='10' name='CheckSum' type='STRING'/>
+    <field number='10' name='CheckSumOld' type='STRING'/>
+  </fields>
+</fix>"
+    )
+    .expect("write temp");
+    let path = xml.path().display().to_string();
+
+    let output = fixdecoder()
+        .args(["--info", "--verbose"])
+        .arg(format!("--xml={path}"))
+        .output()
+        .expect("run fixdecoder");
+    assert!(output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(stderr.matches(&format!("warning: {path}: ")).count(), 2);
+    assert!(stderr.contains("field number 10 is defined 2 times (CheckSum, CheckSumOld)"));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains(
+        "  FIX44: 2\n    field number 10 is defined 2 times (CheckSum, CheckSumOld)\n    message Heartbeat (0) references undefined field TestReqId\n"
+    ), "{stdout}");
+
+    fixdecoder()
+        .args(["--info", "--strict-dict"])
+        .arg(format!("--xml={path}"))
+        .assert()
+        .code(1)
+        .stderr(contains("2 dictionary warning(s) and --strict-dict is set"));
+}
+
+#[test]
+fn custom_msg_types_are_labelled_from_the_xml_dictionary() {
+    let fix44 = std::fs::read_to_string("resources/FIX44.xml").expect("read FIX44.xml");
+    let custom = fix44.replacen(
+        "<messages>",
+        "<messages>\n  <message name='VenueStatus' msgtype='U1' msgcat='app'><field name='Text' required='N'/></message>",
+        1,
+    );
+    let mut xml = NamedTempFile::new().expect("temp file");
+    xml.write_all(custom.as_bytes()).expect("write temp");
+    let log = write_log(&[fix_message(
+        "35=U1\u{0001}49=AAA\u{0001}56=BBB\u{0001}34=1\u{0001}52=20240101-00:00:00\u{0001}58=halt\u{0001}",
+    )]);
+
+    for extra in [None, Some("--count-only")] {
+        fixdecoder()
+            .arg(format!("--xml={}", xml.path().display()))
+            .args(["--colour=no"])
+            .args(extra)
+            .arg(log.path())
+            .assert()
+            .success()
+            .stdout(contains("U1    (VenueStatus)"));
+    }
+}
+
+#[test]
+fn passthrough_echoes_invalid_utf8_lines_byte_for_byte() {
+    let mut line = b"\xff\xfe IN \t".to_vec();
+    line.extend_from_slice(valid_heartbeat(1).trim_end().as_bytes());
+    line.extend_from_slice(b" tail \xc3(  \r\n");
+    let plain = b"no fix here \x80\n";
+    let mut log = NamedTempFile::new().expect("temp file");
+    log.write_all(&line).expect("write temp");
+    log.write_all(plain).expect("write temp");
+
+    let output = fixdecoder()
+        .args(["--fix=44", "--colour=no", "--passthrough"])
+        .arg(log.path())
+        .output()
+        .expect("run fixdecoder");
+    assert!(output.status.success());
+    let stdout = output.stdout;
+    assert!(stdout.starts_with(&line));
+    let decoded = String::from_utf8_lossy(&stdout[line.len()..]);
+    assert!(
+        decoded.contains("35 (MsgType):      0 (HEARTBEAT)"),
+        "{decoded}"
+    );
+    assert!(stdout.windows(plain.len()).any(|window| window == plain));
+    assert!(!stdout.contains(&0xEF), "no U+FFFD replacement bytes");
+}
+
+#[test]
+fn dedupe_collapses_heartbeat_runs_but_keeps_counts() {
+    let soh = '\u{0001}';
+    let report = fix_message(&format!(
+        "35=8{soh}49=AAA{soh}56=BBB{soh}34=4{soh}37=O1{soh}150=0{soh}"
+    ));
+    let log = write_log(&[
+        valid_heartbeat(1),
+        valid_heartbeat(2),
+        valid_heartbeat(3),
+        report,
+        valid_heartbeat(5),
+        valid_heartbeat(6),
+    ]);
+    let output = fixdecoder()
+        .args(["--fix=44", "--colour=no", "--dedupe"])
+        .arg(log.path())
+        .output()
+        .expect("run fixdecoder");
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(stdout.matches("(HEARTBEAT)\n").count(), 2, "{stdout}");
+    assert_eq!(stdout.matches("35 (MsgType):      8").count(), 1);
+    let first_note = stdout
+        .find("(… AAA->BBB repeated 2 times)")
+        .expect("note for first run");
+    assert!(first_note < stdout.find("35 (MsgType):      8").unwrap());
+    assert!(stdout.contains("(… AAA->BBB repeated 1 time)"));
+    assert!(
+        stdout
+            .lines()
+            .any(|line| line.contains("(HEARTBEAT)") && line.trim_end().ends_with(" 5")),
+        "{stdout}"
+    );
+
+    fixdecoder()
+        .args([
+            "--fix=44",
+            "--colour=no",
+            "--dedupe",
+            "--dedupe-ignore-tags=9,10",
+        ])
+        .arg(log.path())
+        .assert()
+        .success()
+        .stdout(contains("repeated").not());
+}
+
+#[test]
+fn message_iterator_and_cli_agree_on_the_messages_in_a_log() {
+    let path = "tests/fixtures/mixed_messages.log";
+    let reader = BufReader::new(std::fs::File::open(path).unwrap());
+    let mut counts = BTreeMap::new();
+    for msg in FixMessageIter::new(reader, DecodeOptions::default()) {
+        let msg = msg.unwrap();
+        *counts
+            .entry(msg.msg_type().unwrap().to_string())
+            .or_insert(0) += 1;
+    }
+    let total: usize = counts.values().sum();
+    assert_eq!(total, 6, "{counts:?}");
+
+    fixdecoder()
+        .args(["--validate", "--colour=no", path])
+        .assert()
+        .stdout(contains(format!("Validated {total} message(s)")));
+
+    let output = fixdecoder()
+        .args(["--count-only", "--colour=no", path])
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let cli_counts: BTreeMap<String, usize> = stdout
+        .lines()
+        .skip_while(|line| !line.starts_with("Message Type"))
+        .skip(1)
+        .filter_map(|line| {
+            let cells: Vec<&str> = line.split_whitespace().collect();
+            Some((cells.first()?.to_string(), cells.last()?.parse().ok()?))
+        })
+        .collect();
+    assert_eq!(cli_counts, counts, "{stdout}");
+}
+
+#[test]
+fn dictionary_cache_is_written_reused_bypassed_and_cleared() {
+    let home = tempfile::tempdir().expect("temp dir");
+    let cache_dir = home.path().join("fixdecoder");
+    let cached = cache_dir.join(format!("FIX44-{}.bin", env!("CARGO_PKG_VERSION")));
+    let run = |args: &[&str]| {
+        fixdecoder()
+            .env("XDG_CACHE_HOME", home.path())
+            .args(args)
+            .assert()
+            .success()
+    };
+
+    run(&["--no-cache", "--tag=35"]).stdout(contains("MsgType"));
+    assert!(!cached.exists(), "--no-cache must not write the cache");
+
+    run(&["--tag=35"]).stdout(contains("MsgType"));
+    assert!(cached.exists());
+    // A corrupt file is rebuilt from the embedded XML rather than failing the run.
+    std::fs::write(&cached, b"not a dictionary").unwrap();
+    run(&["--tag=35"]).stdout(contains("MsgType"));
+    assert!(std::fs::metadata(&cached).unwrap().len() > 16);
+
+    run(&["--clear-cache"]).stdout(contains(format!(
+        "Removed 1 cached dictionary file(s) from {}",
+        cache_dir.display()
+    )));
+    assert!(!cached.exists());
+}
+
+#[test]
+fn encoded_text_is_shown_in_its_message_encoding_and_kept_byte_for_byte() {
+    let path = "tests/fixtures/shift_jis.log";
+    fixdecoder()
+        .args(["--colour=no", path])
+        .assert()
+        .success()
+        .stdout(contains(
+            "355 (EncodedText):     注文は拒否されました (Shift_JIS)",
+        ))
+        .stdout(contains("355 (EncodedText):     東京証券取引所 (EUC-JP)"));
+    fixdecoder()
+        .args(["--validate", "--colour=no", path])
+        .assert()
+        .code(0)
+        .stdout(contains("Validated 2 message(s): 0 invalid"));
+
+    let original = std::fs::read(path).expect("fixture");
+    let extracted = NamedTempFile::new().expect("temp file");
+    fixdecoder()
+        .args(["--colour=no", "--extract"])
+        .arg(extracted.path())
+        .arg(path)
+        .assert()
+        .success();
+    assert_eq!(std::fs::read(extracted.path()).expect("extract"), original);
+
+    let first_line = |from: &[u8], to: &[u8]| {
+        let line = original.split(|b| *b == b'\n').next().expect("line");
+        let at = line
+            .windows(from.len())
+            .position(|w| w == from)
+            .expect("field");
+        let mut edited = [&line[..at], to, &line[at + from.len()..]].concat();
+        edited.push(b'\n');
+        let mut file = NamedTempFile::new().expect("temp file");
+        file.write_all(&edited).expect("write temp");
+        file
+    };
+    let short = first_line(b"354=20", b"354=19");
+    fixdecoder()
+        .args(["--validate", "--colour=no"])
+        .arg(short.path())
+        .assert()
+        .code(2)
+        .stdout(contains(
+            "EncodedTextLen(354) declares 19 bytes but EncodedText(355) has 20",
+        ));
+    let unknown = first_line(b"347=SHIFT_JIS", b"347=KOI8-R");
+    fixdecoder()
+        .args(["--colour=no"])
+        .arg(unknown.path())
+        .assert()
+        .success()
+        .stdout(contains("355 (EncodedText):     <20 bytes: 92 8D 95 B6"));
+}
+
+#[test]
+#[cfg(feature = "dict-fix50sp2")]
+fn depth_and_groups_only_shorten_the_message_structure() {
+    let lines = |extra: &[&str]| {
+        let output = fixdecoder()
+            .args(["--fix=50SP2", "--message=ExecutionReport", "--colour=no"])
+            .args(extra)
+            .output()
+            .expect("run");
+        assert!(output.status.success());
+        String::from_utf8(output.stdout).expect("utf-8")
+    };
+    let full = lines(&[]);
+    let shallow = lines(&["--depth=1", "--column"]);
+    assert!(shallow.lines().count() < full.lines().count());
+    assert!(shallow.contains(
+        "\n            Group: NoPartyIDs (453) … (collapsed, 6 fields and 2 components hidden, use --depth)\n"
+    ));
+
+    let groups = lines(&["--groups-only"]);
+    assert!(groups.lines().count() < shallow.lines().count());
+    assert!(groups.contains(
+        "\n             453: NoPartyIDs (NUMINGROUP)\n                 448: PartyID (delimiter)\n"
+    ));
+    assert!(!groups.contains("OrderID"));
+
+    fixdecoder()
+        .args(["--message=D", "--depth=x"])
+        .assert()
+        .code(1)
+        .stderr(contains("invalid value for --depth: x"));
+}
+
+#[test]
+fn report_writes_each_finding_as_jsonl_or_sarif() {
+    let path = "tests/fixtures/validate_summary.log";
+    let dir = tempfile::tempdir().expect("temp dir");
+    let jsonl = dir.path().join("findings.jsonl");
+    let output = fixdecoder()
+        .args([
+            "--fix=44",
+            "--validate",
+            "--validate-summary",
+            "--colour=no",
+        ])
+        .arg(format!("--report={}", jsonl.display()))
+        .arg(path)
+        .output()
+        .expect("run");
+    assert_eq!(output.status.code(), Some(2));
+    let stdout = String::from_utf8(output.stdout).expect("utf-8");
+    let total: usize = stdout
+        .split("Validation Summary (")
+        .nth(1)
+        .and_then(|rest| rest.split(' ').next())
+        .and_then(|n| n.parse().ok())
+        .expect("summary total");
+    let shown_lines: Vec<usize> = stdout
+        .lines()
+        .filter_map(|line| line.strip_prefix(&format!("{path} line ")))
+        .filter_map(|rest| rest.split(':').next()?.parse().ok())
+        .collect();
+
+    let records: Vec<serde_json::Value> = std::fs::read_to_string(&jsonl)
+        .expect("report")
+        .lines()
+        .map(|line| serde_json::from_str(line).expect("JSON line"))
+        .collect();
+    assert_eq!(records.len(), total);
+    let mut record_lines: Vec<usize> = records
+        .iter()
+        .map(|r| r["line"].as_u64().unwrap() as usize)
+        .collect();
+    record_lines.dedup();
+    assert_eq!(record_lines, shown_lines);
+    assert!(records.iter().all(|r| r["file"] == path));
+    let checksum = records
+        .iter()
+        .find(|r| r["category"] == "checksum")
+        .expect("checksum record");
+    assert_eq!(
+        (checksum["msg_type"].as_str(), checksum["tag"].as_u64()),
+        (Some("0"), Some(10))
+    );
+    assert!(checksum["start"].as_u64() < checksum["end"].as_u64());
+
+    let sarif = dir.path().join("findings.sarif");
+    fixdecoder()
+        .args(["--fix=44", "--validate", "--colour=no"])
+        .arg(format!("--report={}", sarif.display()))
+        .arg(path)
+        .assert()
+        .code(2);
+    let document: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&sarif).expect("report")).expect("SARIF");
+    assert_eq!(document["version"], "2.1.0");
+    let run = &document["runs"][0];
+    assert_eq!(run["tool"]["driver"]["name"], "fixdecoder");
+    assert!(
+        run["tool"]["driver"]["fullName"]
+            .as_str()
+            .is_some_and(|name| name.starts_with("fixdecoder "))
+    );
+    assert_eq!(run["results"].as_array().map(Vec::len), Some(total));
+}
+
+#[test]
+fn nested_groups_align_values_and_wrap_to_the_output_width() {
+    // Parties inside each NoMDEntries entry gives three levels of groups:
+    // NoMDEntries > NoPartyIDs > NoPartySubIDs.
+    let fix44 = std::fs::read_to_string("resources/FIX44.xml").expect("read FIX44.xml");
+    let snapshot_group_end = "      <field name='Text' required='N'/>\n    </group>\n  </message>\n  <message name='MarketDataIncrementalRefresh'";
+    assert!(fix44.contains(snapshot_group_end));
+    let nested = fix44.replacen(
+        snapshot_group_end,
+        &snapshot_group_end.replacen(
+            "\n    </group>",
+            "\n      <component name='Parties' required='N'/>\n    </group>",
+            1,
+        ),
+        1,
+    );
+    let mut xml = NamedTempFile::new().expect("temp file");
+    xml.write_all(nested.as_bytes()).expect("write temp");
+
+    // Wide enough for one value column; too narrow for it, so each line keeps its own.
+    for width in [120, 50] {
+        let output = fixdecoder()
+            .arg(format!("--xml={}", xml.path().display()))
+            .args(["--colour=no", &format!("--width={width}")])
+            .arg("tests/fixtures/nested_md_snapshot.log")
+            .output()
+            .expect("run fixdecoder");
+        assert!(output.status.success());
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        // Past the version banner and the raw message.
+        let decoded: String = stdout.split_inclusive('\n').skip(2).collect();
+        let golden = format!("tests/fixtures/nested_md_snapshot.w{width}.txt");
+        let expected = std::fs::read_to_string(&golden).expect("read golden output");
+        assert_eq!(decoded, expected, "{golden}");
+    }
+}
+
+#[test]
+fn replay_at_speed_zero_sends_what_extract_writes() {
+    for secret in [None, Some("--secret")] {
+        let run = |mode: &str| {
+            let output = fixdecoder()
+                .args(["--fix=44", mode])
+                .args(secret)
+                .arg("tests/fixtures/mixed_messages.log")
+                .output()
+                .expect("run fixdecoder");
+            assert!(output.status.success());
+            output.stdout
+        };
+        let replayed = run("--replay=0");
+        assert!(!replayed.is_empty());
+        assert_eq!(
+            String::from_utf8_lossy(&replayed),
+            String::from_utf8_lossy(&run("--extract=-"))
+        );
+    }
+
+    fixdecoder()
+        .args(["--replay=fast", "tests/fixtures/mixed_messages.log"])
+        .assert()
+        .code(1)
+        .stderr(contains("invalid value for --replay: fast"));
+}
+
+#[test]
+fn replay_spaces_messages_by_their_sending_time_gaps() {
+    let soh = '\u{0001}';
+    let log = write_log(&[
+        fix_message(&format!("35=0{soh}34=1{soh}52=20240101-09:00:00.000{soh}")),
+        fix_message(&format!("35=0{soh}34=2{soh}52=20240101-09:00:00.200{soh}")),
+    ]);
+    let mut child = std::process::Command::new(assert_cmd::cargo::cargo_bin!("fixdecoder"))
+        .args(["--fix=44", "--replay=2"])
+        .arg(log.path())
+        .env("XDG_CACHE_HOME", env!("CARGO_TARGET_TMPDIR"))
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::null())
+        .spawn()
+        .expect("run fixdecoder");
+    let mut lines = BufReader::new(child.stdout.take().expect("stdout")).lines();
+    assert!(
+        lines
+            .next()
+            .expect("first message")
+            .unwrap()
+            .contains("34=1")
+    );
+    let first_sent = std::time::Instant::now();
+    assert!(
+        lines
+            .next()
+            .expect("second message")
+            .unwrap()
+            .contains("34=2")
+    );
+    let gap = first_sent.elapsed();
+    assert!(lines.next().is_none());
+    assert!(child.wait().expect("wait").success());
+    // 200ms logged, at twice the speed, with slack for timer granularity.
+    assert!(gap >= std::time::Duration::from_millis(90), "{gap:?}");
+}
+
+#[test]
+fn latin1_values_validate_against_the_bytes_as_sent() {
+    let log = "tests/fixtures/latin1_text.log";
+    let raw = std::fs::read(log).expect("read fixture");
+    assert!(raw.contains(&0xE9), "the fixture carries Latin-1 é");
+
+    fixdecoder()
+        .args(["--validate", "--colour=no", log])
+        .assert()
+        .code(0)
+        .stdout(contains("Validated 1 message(s): 0 invalid"));
+    fixdecoder()
+        .args(["--colour=no", log])
+        .assert()
+        .success()
+        .stdout(contains("(Text):         Refus\\xE9 par Soci\\xE9t\\xE9"));
+
+    let message_start = raw.windows(5).position(|w| w == b"8=FIX").expect("message");
+    let output = fixdecoder()
+        .args(["--extract=-", log])
+        .output()
+        .expect("run fixdecoder");
+    assert_eq!(output.stdout, &raw[message_start..]);
+}
+
+#[test]
+fn msgtype_filters_take_codes_or_names_and_apply_before_the_summary() {
+    let log = "tests/fixtures/mixed_messages.log";
+    let run = |args: &[&str]| {
+        let output = fixdecoder()
+            .args(["--fix=44", "--colour=no"])
+            .args(args)
+            .arg(log)
+            .output()
+            .expect("run fixdecoder");
+        assert!(output.status.success(), "{output:?}");
+        String::from_utf8_lossy(&output.stdout).into_owned()
+    };
+
+    let stdout = run(&["--exclude-msgtype", "Heartbeat", "--exclude-msgtype=A"]);
+    assert!(stdout.contains("(ORDER_SINGLE)"), "{stdout}");
+    assert!(stdout.contains("(EXECUTION_REPORT)"));
+    assert!(!stdout.contains("35 (MsgType):      0"), "{stdout}");
+    assert!(!stdout.contains("35 (MsgType):      A"));
+    assert!(!stdout.contains("98=0"), "filtered lines are not echoed");
+    assert!(stdout.contains("session starting"));
+
+    let stdout = run(&["--only-msgtype=8,d"]);
+    assert!(stdout.contains("35 (MsgType):      8"), "{stdout}");
+    assert!(
+        !stdout.contains("35 (MsgType):      D"),
+        "codes are case-sensitive"
+    );
+
+    let counts = |stdout: &str| {
+        stdout
+            .lines()
+            .skip_while(|line| !line.starts_with("Message Type"))
+            .skip(1)
+            .map(|line| {
+                line.split_whitespace()
+                    .next()
+                    .unwrap_or_default()
+                    .to_string()
+            })
+            .filter(|code| !code.is_empty())
+            .collect::<Vec<_>>()
+    };
+    let stdout = run(&["--summary", "--only-msgtype=ExecutionReport"]);
+    assert!(!stdout.contains("ORDER_SINGLE [O1]"), "{stdout}");
+    assert!(stdout.contains("EXECUTION_REPORT [O1]"));
+    assert_eq!(counts(&stdout), ["8"]);
+    let stdout = run(&["--summary", "--only-msgtype=8", "--count-filtered"]);
+    assert!(!stdout.contains("ORDER_SINGLE [O1]"), "{stdout}");
+    assert_eq!(counts(&stdout), ["0", "8", "A", "D"]);
+
+    fixdecoder()
+        .args(["--only-msgtype=0,8", "--exclude-msgtype=Heartbeat", log])
+        .assert()
+        .code(1)
+        .stderr(contains(
+            "MsgType 0 cannot be given to both --only-msgtype and --exclude-msgtype",
+        ));
+    fixdecoder()
+        .args(["--exclude-msgtype=NoSuchMessage", log])
+        .assert()
+        .code(1)
+        .stderr(contains(
+            "invalid value for --exclude-msgtype: unknown MsgType NoSuchMessage",
+        ));
+    fixdecoder()
+        .args(["--count-filtered", log])
+        .assert()
+        .code(1);
+}
+
+#[test]
+fn truncated_or_spliced_messages_get_a_warning_above_their_fields() {
+    let soh = '\u{0001}';
+    // Keep the BodyLength of the heartbeat as sent but change its body, then reseal.
+    let resealed = |body: &str| {
+        let declared = valid_heartbeat(2).split(soh).nth(1).unwrap().to_string();
+        with_checksum(&format!("8=FIX.4.4{soh}{declared}{soh}{body}"))
+    };
+    let truncated = resealed(&format!(
+        "35=0{soh}49=AAA{soh}56=BBB{soh}34=2{soh}52=20240101-00:00{soh}"
+    ));
+    let elongated = resealed(&format!(
+        "35=0{soh}49=AAA-SPLICED{soh}56=BBB{soh}34=2{soh}52=20240101-00:00:00{soh}"
+    ));
+    let log = write_log(&[truncated, elongated, valid_heartbeat(3)]);
+    let output = fixdecoder()
+        .args(["--fix=44", "--colour=no", "--validate"])
+        .arg(log.path())
+        .output()
+        .expect("run fixdecoder");
+    assert_eq!(output.status.code(), Some(2));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let warnings: Vec<&str> = stdout
+        .lines()
+        .filter(|line| line.starts_with("!! TRUNCATED OR SPLICED: "))
+        .collect();
+    assert_eq!(warnings.len(), 2, "{stdout}");
+    assert!(
+        warnings[0].ends_with(
+            "BodyLength mismatch: got 45, expected 42; it points 3 bytes past the CheckSum field, so the message was likely truncated and resealed"
+        ),
+        "{stdout}"
+    );
+    assert!(
+        warnings[1].ends_with(
+            "BodyLength mismatch: got 45, expected 53; it ends mid-field, 8 bytes before the CheckSum field, so the message was likely spliced"
+        ),
+        "{stdout}"
+    );
+    let first_warning = stdout.find("!! TRUNCATED").unwrap();
+    assert!(first_warning < stdout.find("8 (BeginString)").unwrap());
+    assert!(
+        stdout.contains("Validated 3 message(s): 2 invalid"),
+        "{stdout}"
+    );
+}
+
+/// Serve `body` at `/fix.log` and a 404 for anything else, over plain HTTP on
+/// localhost, until the test process exits.
+fn serve_log(body: String) -> String {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").expect("bind");
+    let addr = listener.local_addr().expect("local addr");
+    std::thread::spawn(move || {
+        for mut stream in listener.incoming().flatten() {
+            let mut reader = BufReader::new(stream.try_clone().expect("clone stream"));
+            let mut request = String::new();
+            let _ = reader.read_line(&mut request);
+            let mut header = String::new();
+            while reader.read_line(&mut header).unwrap_or(0) > 2 {
+                header.clear();
+            }
+            let (status, body) = if request.starts_with("GET /fix.log ") {
+                ("200 OK", body.as_str())
+            } else {
+                ("404 Not Found", "")
+            };
+            let _ = write!(
+                stream,
+                "HTTP/1.1 {status}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            );
+        }
+    });
+    format!("http://{addr}")
+}
+
+#[test]
+fn urls_decode_like_files_and_a_missing_one_does_not_stop_the_rest() {
+    let lines = [valid_heartbeat(1), valid_heartbeat(2)];
+    let log = write_log(&lines);
+    let server = serve_log(lines.concat());
+
+    fixdecoder()
+        .args(["--fix=44", "--extract=-"])
+        .arg(format!("{server}/fix.log"))
+        .assert()
+        .success()
+        .stdout(lines.concat());
+
+    fixdecoder()
+        .args(["--fix=44", "--colour=no", "--extract=-"])
+        .arg(format!("{server}/missing.log"))
+        .arg(log.path())
+        .assert()
+        .code(1)
+        .stdout(lines.concat())
+        .stderr(contains("Cannot open URL: HTTP 404 Not Found from").and(contains("/missing.log")));
+
+    fixdecoder()
+        .args(["--fix=44", "--seek-line=2"])
+        .arg(format!("{server}/fix.log"))
+        .assert()
+        .code(1)
+        .stderr(contains("cannot be used with URLs"));
+}
+
+#[test]
+fn a_local_file_with_a_non_ascii_name_is_not_mistaken_for_a_url() {
+    let dir = tempfile::tempdir().expect("temp dir");
+    let lines = [valid_heartbeat(1)];
+    std::fs::write(dir.path().join("résumé.log"), lines.concat()).expect("write log");
+
+    fixdecoder()
+        .current_dir(dir.path())
+        .args(["--fix=44", "--extract=-", "résumé.log"])
+        .assert()
+        .success()
+        .stdout(lines.concat());
+}
+
+#[test]
+fn prefix_file_tags_each_files_lines_and_ends_with_a_table_per_file() {
+    let clean = write_log(&[valid_heartbeat(1), valid_heartbeat(2)]);
+    let broken = write_log(&[valid_heartbeat(1), fix_message("35=0\u{0001}34=2\u{0001}")]);
+    let name = |log: &NamedTempFile| {
+        let path = log.path();
+        (
+            path.display().to_string(),
+            path.file_name().unwrap().to_string_lossy().into_owned(),
+        )
+    };
+    let ((clean_path, clean_name), (broken_path, broken_name)) = (name(&clean), name(&broken));
+
+    let output = fixdecoder()
+        .args(["--validate", "--prefix-file", "--colour=no"])
+        .arg(&clean_path)
+        .arg(&broken_path)
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(2));
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let heading = format!("[{broken_name}] {broken_path} line 2: 8=FIX.4.4");
+    assert!(stdout.contains(&heading), "{stdout}");
+    let decoded: Vec<&str> = stdout
+        .lines()
+        .skip_while(|line| !line.starts_with('['))
+        .take_while(|line| !line.starts_with("Message Type"))
+        .collect();
+    assert!(
+        decoded
+            .iter()
+            .all(|line| line.starts_with(&format!("[{broken_name}] "))),
+        "{stdout}"
+    );
+
+    let table: Vec<Vec<&str>> = stdout
+        .lines()
+        .skip_while(|line| !line.starts_with("File "))
+        .map(|line| line.split_whitespace().collect())
+        .collect();
+    assert_eq!(
+        table,
+        [
+            vec!["File", "Messages", "Invalid"],
+            vec![clean_path.as_str(), "2", "0"],
+            vec![broken_path.as_str(), "2", "1"],
+        ],
+        "{stdout}"
+    );
+    assert_ne!(clean_name, broken_name);
+}
+
+#[test]
+fn sequence_diagram_draws_an_order_lifecycle_between_comp_ids() {
+    let dir = tempfile::tempdir().expect("temp dir");
+    let diagram = dir.path().join("lifecycle.mmd");
+    fixdecoder()
+        .arg("--count-only")
+        .arg(format!("--sequence-diagram={}", diagram.display()))
+        .arg("tests/fixtures/order_lifecycle.log")
+        .assert()
+        .success();
+    let written = std::fs::read_to_string(&diagram).expect("read diagram");
+    let expected =
+        std::fs::read_to_string("tests/fixtures/order_lifecycle.mmd").expect("read golden");
+    assert_eq!(written, expected);
+
+    // To stdout, only execution reports, capped at two.
+    fixdecoder()
+        .args([
+            "--count-only",
+            "--only-msgtype=8",
+            "--sequence-diagram",
+            "--diagram-limit=2",
+        ])
+        .arg("tests/fixtures/order_lifecycle.log")
+        .assert()
+        .success()
+        .stdout(contains(
+            "sequenceDiagram\n    \
+             participant BROKER\n    \
+             participant CLIENT\n    \
+             BROKER->>CLIENT: ExecutionReport(11=ABC, 37=ORD1, 17=EX1)\n    \
+             BROKER->>CLIENT: ExecutionReport(11=ABD, 37=ORD1, 17=EX2)\n    \
+             Note over BROKER,CLIENT: 2 more message(s) not shown (--diagram-limit 2)\n",
+        ));
+}
+
+#[test]
+fn md_ladder_lists_a_snapshot_best_price_first_on_each_side() {
+    let soh = '\u{0001}';
+    let levels = [
+        ("0", "100.10", "500", "2"),
+        ("1", "100.30", "200", "2"),
+        ("0", "100.20", "300", "1"),
+        ("1", "100.25", "1000", "1"),
+        ("0", "99.95", "50", "5"),
+        ("1", "100.45", "75", "5"),
+        ("0", "100.05", "25", "3"),
+        ("1", "100.35", "40", "3"),
+        ("0", "100.00", "5", "4"),
+        ("1", "100.40", "60", "4"),
+    ];
+    let mut body = format!(
+        "35=W{soh}49=MD{soh}56=CLIENT{soh}34=1{soh}52=20240101-00:00:00{soh}55=VOD.L{soh}268={}{soh}",
+        levels.len()
+    );
+    for (side, px, size, pos) in levels {
+        body.push_str(&format!(
+            "269={side}{soh}270={px}{soh}271={size}{soh}290={pos}{soh}"
+        ));
+    }
+    let log = write_log(&[sealed_message("FIX.4.4", &body)]);
+
+    let output = fixdecoder()
+        .args(["--fix=44", "--colour=no", "--md-ladder"])
+        .arg(log.path())
+        .output()
+        .expect("run fixdecoder");
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let ladder: Vec<&str> = stdout
+        .lines()
+        .skip_while(|line| !line.starts_with("  Ladder"))
+        .take(12)
+        .collect();
+    assert_eq!(
+        ladder,
+        [
+            "  Ladder (10 entries)",
+            "    Type   Price   Size  Position",
+            "    BID    100.20  300   1",
+            "    BID    100.10  500   2",
+            "    BID    100.05  25    3",
+            "    BID    100.00  5     4",
+            "    BID    99.95   50    5",
+            "    OFFER  100.25  1000  1",
+            "    OFFER  100.30  200   2",
+            "    OFFER  100.35  40    3",
+            "    OFFER  100.40  60    4",
+            "    OFFER  100.45  75    5",
+        ],
+        "{stdout}"
+    );
+
+    fixdecoder()
+        .args(["--fix=44", "--colour=no"])
+        .arg(log.path())
+        .assert()
+        .success()
+        .stdout(contains("Ladder").not());
+}
+
+#[test]
+#[cfg(feature = "dict-fix50sp2")]
+fn show_dict_names_the_override_and_the_fallback_it_needed() {
+    let soh = '\u{0001}';
+    let body = format!(
+        "35=D{soh}1128=9{soh}49=BUY{soh}56=SELL{soh}34=2{soh}52=20240101-00:00:00{soh}11=ORD1{soh}"
+    );
+    let log = write_log(&[sealed_message("FIXT.1.1", &body)]);
+
+    let output = fixdecoder()
+        .args(["--fix=44", "--colour=no", "--show-dict"])
+        .arg(log.path())
+        .output()
+        .expect("run fixdecoder");
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let line = stdout
+        .lines()
+        .find(|line| line.starts_with("Dictionary:"))
+        .unwrap_or_else(|| panic!("no Dictionary line in {stdout}"));
+    assert_eq!(
+        line,
+        "Dictionary: FIX44 by --fix override (detected FIX50SP2); \
+         fallback FIX50SP2 (detected version) used for 1128"
+    );
+
+    fixdecoder()
+        .args(["--fix=44", "--colour=no"])
+        .arg(log.path())
+        .assert()
+        .success()
+        .stdout(contains("Dictionary:").not());
+}
+
+#[test]
+fn a_second_xml_for_the_same_version_reports_both_the_override_and_the_replacement() {
+    let fix44 = std::fs::read_to_string("resources/FIX44.xml").expect("read FIX44.xml");
+    let mut first = NamedTempFile::new().expect("temp file");
+    first.write_all(fix44.as_bytes()).expect("write temp");
+    let mut second = NamedTempFile::new().expect("temp file");
+    second.write_all(fix44.as_bytes()).expect("write temp");
+    let (first, second) = (first.path().display(), second.path().display());
+
+    let output = fixdecoder()
+        .args(["--info", "--no-cache"])
+        .arg(format!("--xml={first}"))
+        .arg(format!("--xml={second}"))
+        .output()
+        .expect("run fixdecoder");
+    assert!(output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains(&format!(
+            "warning: custom dictionary for FIX44 overrides embedded dictionary using {first}\n"
+        )),
+        "{stderr}"
+    );
+    assert!(
+        stderr.contains(&format!(
+            "warning: custom dictionary for FIX44 from {first} replaced by {second}\n"
+        )),
+        "{stderr}"
+    );
+    assert!(!stderr.contains("suppressed"), "{stderr}");
+}
+
+#[test]
+#[cfg(feature = "dict-fixt11")]
+fn a_fix50_xml_without_a_header_warns_once_about_the_embedded_session_layer() {
+    let output = fixdecoder()
+        .args(["--info", "--no-cache", "--xml=resources/FIX50SP2.xml"])
+        .output()
+        .expect("run fixdecoder");
+    assert!(output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let warning = "warning: custom dictionary FIX50SP2 from resources/FIX50SP2.xml has no header \
+                   and no --transport-xml was given; using the embedded FIXT11 session layer\n";
+    assert_eq!(stderr.matches(warning).count(), 1, "{stderr}");
+    assert!(!stderr.contains("\n\n"), "{stderr}");
+}
+
+#[test]
+fn a_broken_xml_dictionary_is_reported_and_the_rest_still_load() {
+    let fix44 = std::fs::read_to_string("resources/FIX44.xml").expect("read FIX44.xml");
+    let mut truncated = NamedTempFile::new().expect("temp file");
+    truncated
+        .write_all(&fix44.as_bytes()[..fix44.len() / 2])
+        .expect("write temp");
+    let custom = fix44.replacen(
+        "<messages>",
+        "<messages>\n  <message name='VenueStatus' msgtype='U1' msgcat='app'><field name='Text' required='N'/></message>",
+        1,
+    );
+    let mut good = NamedTempFile::new().expect("temp file");
+    good.write_all(custom.as_bytes()).expect("write temp");
+    let start = fix44.find("<components>").expect("components");
+    let end = fix44.find("</components>").expect("components end") + "</components>".len();
+    let mut partial = NamedTempFile::new().expect("temp file");
+    write!(partial, "{}{}", &fix44[..start], &fix44[end..]).expect("write temp");
+    let log = write_log(&[fix_message(
+        "35=U1\u{0001}49=AAA\u{0001}56=BBB\u{0001}34=1\u{0001}52=20240101-00:00:00\u{0001}58=halt\u{0001}",
+    )]);
+
+    let truncated_path = truncated.path().display().to_string();
+    let output = fixdecoder()
+        .args(["--colour=no", "--no-cache"])
+        .arg(format!("--xml={truncated_path}"))
+        .arg(format!("--xml={}", good.path().display()))
+        .arg(log.path())
+        .output()
+        .expect("run fixdecoder");
+    assert!(output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains(&format!(
+            "failed to parse FIX XML from {truncated_path}: malformed XML at line"
+        )),
+        "{stderr}"
+    );
+    assert!(stderr.contains("the file looks truncated"), "{stderr}");
+    assert!(
+        stderr.contains(&format!("continuing without --xml {truncated_path}")),
+        "{stderr}"
+    );
+    assert!(String::from_utf8_lossy(&output.stdout).contains("U1    (VenueStatus)"));
+
+    fixdecoder()
+        .args(["--info", "--no-cache"])
+        .arg(format!("--xml={}", partial.path().display()))
+        .assert()
+        .success()
+        .stderr(contains(
+            "use --allow-partial-dict to load it without components",
+        ));
+    fixdecoder()
+        .args(["--info", "--no-cache", "--allow-partial-dict"])
+        .arg(format!("--xml={}", partial.path().display()))
+        .assert()
+        .success()
+        .stderr(contains("no components were read").and(contains("continuing without").not()));
+
+    let mut empty_components = NamedTempFile::new().expect("temp file");
+    write!(
+        empty_components,
+        "<fix type='FIX' major='4' minor='4'>\
+         <header><field name='BeginString' required='Y'/></header>\
+         <trailer><field name='CheckSum' required='Y'/></trailer>\
+         <messages><message name='Heartbeat' msgtype='0' msgcat='admin'/></messages>\
+         <components/>\
+         <fields><field number='8' name='BeginString' type='STRING'/>\
+         <field number='10' name='CheckSum' type='STRING'/></fields></fix>"
+    )
+    .expect("write temp");
+    fixdecoder()
+        .args(["--info", "--no-cache", "--allow-partial-dict"])
+        .arg(format!("--xml={}", empty_components.path().display()))
+        .assert()
+        .success()
+        .stderr(contains("no components were read").not());
+}
@@ -0,0 +1,16 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Helpers shared by the integration tests.
+
+/// A message with BeginString `begin` and the correct BodyLength and CheckSum for `body`
+/// (SOH-terminated fields from MsgType on).
+pub fn sealed_message(begin: &str, body: &str) -> String {
+    let soh = '\u{0001}';
+    with_checksum(&format!("8={begin}{soh}9={}{soh}{body}", body.len()))
+}
+
+/// `head` followed by the CheckSum it adds up to, whatever its BodyLength says.
+pub fn with_checksum(head: &str) -> String {
+    let soh = '\u{0001}';
+    let checksum = fixdecoder::decoder::raw_bytes::checksum(head);
+    format!("{head}10={checksum:03}{soh}\n")
+}
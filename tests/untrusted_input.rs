@@ -0,0 +1,206 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Property tests for input from outside parties: whatever a log holds, parsing,
+// validation, prettifying and dictionary loading must return rather than panic, and
+// serialised fields must parse back unchanged.  The fuzz targets under `fuzz/` drive
+// the same entry points with coverage guidance.
+
+mod common;
+
+use common::sealed_message;
+use fixdecoder::decoder::display::DisplayOptions;
+use fixdecoder::decoder::fixparser::parse_fix;
+use fixdecoder::decoder::prettifier::{HiddenBlocks, prettify_with_report};
+use fixdecoder::decoder::raw_bytes;
+use fixdecoder::decoder::tag_lookup::{FixTagLookup, load_dictionary};
+use fixdecoder::decoder::validator::validate_fix_message;
+use fixdecoder::decoder::{DecodeOptions, FixDictionary, FixMessageIter};
+use proptest::prelude::*;
+use proptest::sample::select;
+use std::io::Cursor;
+
+const SOH: char = '\u{0001}';
+
+/// Fragments that steer generated text towards the framing the decoder looks for.
+fn fix_like_text() -> impl Strategy<Value = String> {
+    let token = prop_oneof![
+        select(vec![
+            "8=FIX.4.4",
+            "8=FIXT.1.1",
+            "8=FIX.4.2",
+            "9=",
+            "35=D",
+            "35=AE",
+            "1128=9",
+            "10=",
+            "268=",
+            "555=",
+            "\u{0001}",
+            "|",
+            "\n",
+            "\\u0001",
+            "99999999999999999999",
+            "18446744073709551615",
+        ])
+        .prop_map(String::from),
+        "[0-9]{1,4}".prop_map(String::from),
+        "[0-9]{1,5}=[ -~]{0,8}\u{0001}".prop_map(String::from),
+        any::<String>(),
+    ];
+    prop::collection::vec(token, 0..40).prop_map(|tokens| tokens.concat())
+}
+
+/// Well-framed messages of common types whose fields, group counts included, carry
+/// hostile values.
+fn framed_message() -> impl Strategy<Value = String> {
+    let tag = prop_oneof![
+        select(vec![
+            9u32, 10, 11, 35, 38, 44, 52, 54, 55, 60, 73, 146, 268, 269, 270, 382, 448, 453, 555,
+            600, 624, 687,
+        ]),
+        1u32..6000,
+    ];
+    let value = prop_oneof![
+        select(vec![
+            "-1",
+            "",
+            "99999999999999999999",
+            "4294967296",
+            "20240101-25:61:61.9999999999",
+        ])
+        .prop_map(String::from),
+        "[0-9]{1,3}".prop_map(String::from),
+        "[ -~]{0,12}".prop_map(String::from),
+    ];
+    (
+        select(vec!["D", "8", "W", "AE", "AB", "0", "X"]),
+        prop::collection::vec((tag, value), 0..30),
+    )
+        .prop_map(|(msg_type, fields)| {
+            let mut body = format!("35={msg_type}{SOH}");
+            for (tag, value) in fields {
+                body.push_str(&format!("{tag}={value}{SOH}"));
+            }
+            sealed_message("FIX.4.4", &body)
+        })
+}
+
+/// References between components, groups and fields drawn from a small pool of names,
+/// so self-references, cycles, duplicates and dangling names all turn up.
+fn dictionary_xml() -> impl Strategy<Value = String> {
+    let name = || select(vec!["A", "B", "C", "NoA", "Missing"]);
+    let reference = (0..3u8, name()).prop_map(|(kind, name)| match kind {
+        0 => format!("<field name='{name}' required='Y'/>"),
+        1 => format!("<component name='{name}'/>"),
+        _ => format!("<group name='{name}'><field name='A'/><component name='{name}'/></group>"),
+    });
+    let refs = prop::collection::vec(reference, 0..4).prop_map(|refs| refs.concat());
+    let field = (
+        name(),
+        any::<u32>(),
+        select(vec!["STRING", "NUMINGROUP", "INT"]),
+    )
+        .prop_map(|(name, number, typ)| {
+            format!(
+                "<field number='{number}' name='{name}' type='{typ}'>\
+                 <value enum='1' description='ONE'/></field>"
+            )
+        });
+    (
+        refs.clone(),
+        refs.clone(),
+        prop::collection::vec((name(), refs.clone()), 0..4),
+        prop::collection::vec(refs, 0..3),
+        prop::collection::vec(field, 0..6),
+    )
+        .prop_map(|(header, trailer, components, messages, fields)| {
+            let components: String = components
+                .iter()
+                .map(|(name, body)| format!("<component name='{name}'>{body}</component>"))
+                .collect();
+            let messages: String = messages
+                .iter()
+                .map(|body| format!("<message name='M' msgtype='M' msgcat='app'>{body}</message>"))
+                .collect();
+            format!(
+                "<fix type='FIX' major='4' minor='4'><header>{header}</header>\
+                 <trailer>{trailer}</trailer><messages>{messages}</messages>\
+                 <components>{components}</components><fields>{}</fields></fix>",
+                fields.concat()
+            )
+        })
+}
+
+fn decode_everything(text: &str) {
+    let _ = parse_fix(text);
+    let dict = load_dictionary(text);
+    let report = validate_fix_message(text, &dict);
+    let hidden = HiddenBlocks {
+        header: true,
+        trailer: true,
+    }
+    .tags(&dict, Some(&report));
+    let display = DisplayOptions {
+        max_value_width: Some(16),
+        error_locations: true,
+        ..DisplayOptions::default()
+    };
+    let _ = prettify_with_report(text, &dict, Some(&report), &hidden, &display);
+    let _ = prettify_with_report(text, &dict, None, &Default::default(), &Default::default());
+}
+
+fn iterate_messages(bytes: &[u8], join_lines: bool) {
+    let options = DecodeOptions {
+        validate: true,
+        unescape: true,
+        join_lines,
+        ..DecodeOptions::default()
+    };
+    for msg in FixMessageIter::new(Cursor::new(bytes), options) {
+        let _ = msg;
+    }
+}
+
+proptest! {
+    #[test]
+    fn serialised_fields_parse_back_unchanged(
+        fields in prop::collection::vec((any::<u32>(), "[^\u{0001}]*"), 1..20)
+    ) {
+        let text: String = fields
+            .iter()
+            .map(|(tag, value)| format!("{tag}={value}{SOH}"))
+            .collect();
+        let parsed: Vec<(u32, String)> = parse_fix(&text)
+            .into_iter()
+            .map(|field| (field.tag, field.value))
+            .collect();
+        prop_assert_eq!(parsed, fields);
+    }
+
+    #[test]
+    fn fix_like_text_never_panics(text in fix_like_text()) {
+        decode_everything(&text);
+        iterate_messages(text.as_bytes(), false);
+        iterate_messages(text.as_bytes(), true);
+    }
+
+    #[test]
+    fn arbitrary_bytes_never_panic(bytes in prop::collection::vec(any::<u8>(), 0..512)) {
+        iterate_messages(&bytes, false);
+        decode_everything(&raw_bytes::decode(&bytes));
+    }
+
+    #[test]
+    fn framed_messages_with_hostile_values_never_panic(msg in framed_message()) {
+        decode_everything(&msg);
+        iterate_messages(msg.as_bytes(), false);
+    }
+
+    #[test]
+    fn arbitrary_dictionary_xml_is_rejected_not_panicked_on(
+        xml in prop_oneof![any::<String>(), dictionary_xml()]
+    ) {
+        if let Ok(dict) = FixDictionary::from_xml(&xml) {
+            let _ = FixTagLookup::from_dictionary(&dict, "PROPTEST");
+        }
+    }
+}